@@ -0,0 +1,97 @@
+//! Times exporting a synthetic corpus of 5000 assets as JSON and with
+//! [`bin::write_assets`]. With the `rayon` feature, each export is timed on
+//! thread pools of increasing size, to show how it scales.
+//!
+//! Run with `cargo bench --bench export --features json,rayon`.
+
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use t5_xfile_defs::{
+    XString,
+    misc::{LocalizeEntry, RawFile},
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::export::{
+    bin,
+    json::{BlobEncoding, JsonLayout, JsonOptions},
+    to_json_writer,
+};
+
+const ASSET_COUNT: usize = 5000;
+const RUNS: u32 = 5;
+
+/// Alternating raw files of up to 16 KiB and localize entries.
+fn corpus() -> Vec<XAsset> {
+    (0..ASSET_COUNT)
+        .map(|i| {
+            let asset = if i % 2 == 0 {
+                XAssetGeneric::RawFile(Some(Box::new(RawFile {
+                    name: XString::from(format!("bench/{i}.gsc")),
+                    buffer: (0..(i * 37) % (16 * 1024)).map(|b| b as u8).collect(),
+                })))
+            } else {
+                XAssetGeneric::LocalizeEntry(Some(Box::new(LocalizeEntry::new(
+                    format!("BENCH_{i}"),
+                    "Some localized text. ".repeat(i % 64),
+                ))))
+            };
+            XAsset::PC(asset)
+        })
+        .collect()
+}
+
+/// The fastest of [`RUNS`] runs of `f`.
+fn time(f: impl Fn()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn bench(name: &str, f: impl Fn() + Sync) {
+    #[cfg(feature = "rayon")]
+    {
+        let max = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let mut threads = 1;
+        loop {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let elapsed = pool.install(|| time(&f));
+            println!("{name:<24} {threads:>3} threads {elapsed:>12.2?}");
+            if threads >= max {
+                break;
+            }
+            threads = (threads * 2).min(max);
+        }
+    }
+    #[cfg(not(feature = "rayon"))]
+    println!("{name:<24} {:>12.2?}", time(f));
+}
+
+fn main() {
+    let assets = corpus();
+
+    for (name, blobs) in [
+        ("json (array blobs)", BlobEncoding::Array),
+        ("json (base64 blobs)", BlobEncoding::Base64),
+    ] {
+        let options = JsonOptions {
+            layout: JsonLayout::Ndjson,
+            blobs,
+            ..Default::default()
+        };
+        bench(name, || {
+            to_json_writer(&assets, io::sink(), options).unwrap()
+        });
+    }
+    bench("bin", || bin::write_assets(&assets, 0, io::sink()).unwrap());
+}
@@ -0,0 +1,145 @@
+//! Measures how much memory and time string interning saves when
+//! deserializing a blob of raw files that share a few long names, like the
+//! sampler, technique, and bone names repeated throughout a materials-heavy
+//! file.
+//!
+//! Run with `cargo bench --bench string_interning`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    path::PathBuf,
+    sync::atomic::{AtomicIsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use t5_xfile_defs::{XFilePlatform, xasset::XAsset};
+use t5_xfile_deserializer::T5XFileDeserializerBuilder;
+
+const COPIES: usize = 2000;
+const RUNS: u32 = 5;
+const NAMES: [&str; 4] = [
+    "mc/mtl_weapon_m16_body_col_nml_spc",
+    "mc/mtl_weapon_m16_stock_col_nml_spc",
+    "mc/mtl_weapon_ak47_body_col_nml_spc",
+    "mc/mtl_weapon_ak47_clip_col_nml_spc",
+];
+
+const RAWFILE: u32 = 0x24;
+const INLINE: u32 = 0xFFFFFFFF;
+
+/// Counts the bytes allocated while [`TRACKING`] is set.
+struct CountingAlloc;
+
+static CURRENT: AtomicIsize = AtomicIsize::new(0);
+
+thread_local! {
+    static TRACKING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn tracking() -> bool {
+    TRACKING.try_with(Cell::get).unwrap_or(false)
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if tracking() {
+            CURRENT.fetch_add(layout.size() as isize, Ordering::SeqCst);
+        }
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if tracking() {
+            CURRENT.fetch_sub(layout.size() as isize, Ordering::SeqCst);
+        }
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// What `f` returns, and how many of the bytes it allocated are still
+/// allocated afterwards, i.e. how much memory its result takes up.
+fn retained<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    CURRENT.store(0, Ordering::SeqCst);
+    TRACKING.with(|t| t.set(true));
+    let t = f();
+    TRACKING.with(|t| t.set(false));
+    (t, CURRENT.load(Ordering::SeqCst).max(0) as usize)
+}
+
+fn u32s(v: &[u32]) -> Vec<u8> {
+    v.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// An inflated blob with a one-byte raw file for each of [`COPIES`] names,
+/// cycling through [`NAMES`].
+fn blob() -> Vec<u8> {
+    let names = NAMES.iter().cycle().take(COPIES).collect::<Vec<_>>();
+
+    let mut body = u32s(&[0, INLINE, COPIES as u32, INLINE]);
+    for _ in &names {
+        body.extend(u32s(&[RAWFILE, INLINE]));
+    }
+    for name in &names {
+        body.extend(u32s(&[INLINE, 1, INLINE]));
+        body.extend(name.as_bytes());
+        body.extend([0, b'a', 0]);
+    }
+
+    let mut blob = u32s(&[body.len() as u32, 0, 0, 0, 0, 0, 0, 0, 0]);
+    blob.extend(body);
+    blob
+}
+
+fn deserialize(path: &PathBuf, string_interning: bool) -> Vec<XAsset> {
+    let mut file = std::fs::File::open(path).unwrap();
+    T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .with_string_interning(string_interning)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .and_then(|de| de.deserialize_remaining())
+        .unwrap()
+}
+
+/// The fastest of [`RUNS`] runs of `f`.
+fn time(f: impl Fn()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let path = std::env::temp_dir().join(format!(
+        "t5xfile_bench_interning_{}.cache",
+        std::process::id()
+    ));
+    std::fs::write(&path, blob()).unwrap();
+
+    let name_bytes = NAMES
+        .iter()
+        .cycle()
+        .take(COPIES)
+        .map(|n| n.len())
+        .sum::<usize>();
+    println!("{COPIES} raw files, {name_bytes} bytes of names");
+    for (name, string_interning) in [("without interning", false), ("with interning", true)] {
+        let (assets, bytes) = retained(|| deserialize(&path, string_interning));
+        assert_eq!(assets.len(), COPIES);
+        drop(assets);
+
+        let elapsed = time(|| drop(deserialize(&path, string_interning)));
+        println!("{name:<24} {bytes:>10} bytes retained {elapsed:>12.2?}");
+    }
+
+    let _ = std::fs::remove_file(&path);
+}
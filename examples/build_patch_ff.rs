@@ -0,0 +1,218 @@
+//! Builds a small mod Fastfile from scratch: a localized string, a string
+//! table, a script, and a weapon. The Fastfile is written to
+//! `out/patch_example.ff`, then read back and checked against what went in.
+//!
+//! Run with `cargo run --example build_patch_ff --features serializer`.
+
+use std::{error::Error, fs::File, path::Path};
+
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    common::Vec2,
+    misc::{LocalizeEntry, RawFile, StringTable},
+    weapon::{WeapClass, WeapFireType, WeaponAnimSlot, WeaponDef, WeaponVariantDef},
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, T5XFileSerializerBuilder};
+
+const PLATFORM: XFilePlatform = XFilePlatform::Windows;
+
+const ATTACHMENTS_CSV: &str = "\
+reference,name,damage_scale
+sil,Suppressor,0.8
+ext,\"Extended Mag, 40rd\",1.0
+";
+
+const SCRIPT: &str = "\
+#include maps\\mp\\_utility;
+
+init()
+{
+    level.crate_gun = \"crate_gun_mp\";
+}
+";
+
+fn s(s: &str) -> XString {
+    XString::from(s.to_string())
+}
+
+fn crate_gun() -> WeaponVariantDef {
+    let mut xanims = vec![XString::new(); WeaponAnimSlot::FIRE as usize + 1];
+    xanims[WeaponAnimSlot::IDLE as usize] = s("viewmodel_crate_gun_idle");
+    xanims[WeaponAnimSlot::FIRE as usize] = s("viewmodel_crate_gun_fire");
+
+    let mut bounce_sound: [XString; 31] = Default::default();
+    bounce_sound[0] = s("grenade_bounce_default");
+
+    WeaponVariantDef {
+        internal_name: s("crate_gun_mp"),
+        display_name: s("WEAPON_CRATE_GUN"),
+        xanims: xanims.into(),
+        hide_tags: vec![s("tag_silencer"), s("tag_clip_extended")],
+        clip_size: 30,
+        reload_time: 2500,
+        ammo_name: s("crate_gun_mp"),
+        clip_name: s("crate_gun_mp"),
+        ads_zoom_fov_1: 50.0,
+        oo_pos_anim_length: [0.5, 0.25].into(),
+        silenced: true,
+        weap_def: Some(Box::new(WeaponDef {
+            mode_name: s("crate_gun"),
+            notetrack_sound_map_keys: Some(Box::new(core::array::from_fn(|i| {
+                if i == 0 { "clip_out" } else { "" }.to_string()
+            }))),
+            notetrack_sound_map_values: Some(Box::new(core::array::from_fn(|i| {
+                if i == 0 { "wpn_crate_gun_clip_out" } else { "" }.to_string()
+            }))),
+            weap_class: WeapClass::SMG,
+            fire_type: WeapFireType::BURSTFIRE3,
+            fire_sound: s("wpn_crate_gun_fire"),
+            bounce_sound: Some(Box::new(bounce_sound)),
+            explosion_tag: s("tag_flash"),
+            damage: 40,
+            min_damage: 20,
+            fire_time: 75,
+            accuracy_graph_name: [s("crate_gun_ai.accu"), XString::new()],
+            accuracy_graph_knots: [
+                vec![Vec2::from([0.0, 1.0]), Vec2::from([1.0, 0.5])],
+                Vec::new(),
+            ],
+            parallel_bounce: Some(Box::new([0.5; 31])),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+}
+
+/// The assets in the example patch.
+pub fn patch_assets() -> Result<Vec<XAsset>, Box<dyn Error>> {
+    let mut script = SCRIPT.as_bytes().to_vec();
+    script.push(0);
+
+    Ok(vec![
+        XAsset::PC(XAssetGeneric::LocalizeEntry(Some(Box::new(
+            LocalizeEntry::new("WEAPON_CRATE_GUN", "Crate Gun"),
+        )))),
+        XAsset::PC(XAssetGeneric::StringTable(Some(Box::new(
+            StringTable::from_csv("mp/crate_attachments.csv", ATTACHMENTS_CSV)?,
+        )))),
+        XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+            name: s("maps/mp/gametypes/_crate.gsc"),
+            buffer: script,
+        })))),
+        XAsset::PC(XAssetGeneric::Weapon(Some(Box::new(crate_gun())))),
+    ])
+}
+
+/// Serializes `assets` into a Fastfile at `path`, then deserializes it
+/// again.
+pub fn build_patch(path: &Path, assets: Vec<XAsset>) -> Result<Vec<XAsset>, Box<dyn Error>> {
+    let mut ser = T5XFileSerializerBuilder::new(PLATFORM)
+        .with_silent(true)
+        .build()?;
+    ser.serialize_assets::<1>(assets.into_iter())?;
+    std::fs::write(path, ser.deflate()?)?;
+
+    let mut file = File::open(path)?;
+    let assets = T5XFileDeserializerBuilder::from_file(&mut file, PLATFORM, false)
+        .with_silent(true)
+        .build()?
+        .inflate()?
+        .no_cache()?
+        .deserialize_remaining()?;
+    Ok(assets)
+}
+
+/// Panics if `assets` aren't what [`patch_assets`] put in.
+pub fn check_patch(assets: &[XAsset]) {
+    let names = assets
+        .iter()
+        .map(|a| (a.asset_type(), a.name().unwrap_or_default()))
+        .collect::<Vec<_>>();
+    let expected = patch_assets().unwrap();
+    let expected = expected
+        .iter()
+        .map(|a| (a.asset_type(), a.name().unwrap_or_default()))
+        .collect::<Vec<_>>();
+    assert_eq!(names, expected);
+
+    let [
+        XAsset::PC(XAssetGeneric::LocalizeEntry(Some(localize))),
+        XAsset::PC(XAssetGeneric::StringTable(Some(table))),
+        XAsset::PC(XAssetGeneric::RawFile(Some(script))),
+        XAsset::PC(XAssetGeneric::Weapon(Some(weapon))),
+    ] = assets
+    else {
+        panic!("{assets:#?}");
+    };
+
+    assert_eq!(localize.value.get(), "Crate Gun");
+
+    assert_eq!((table.row_count, table.column_count), (3, 3));
+    assert_eq!(table.cell(2, 1), Some("Extended Mag, 40rd"));
+    assert_eq!(table.values[4].hash, StringTable::hash("Suppressor"));
+    assert_eq!(table.cell_index.len(), 9);
+
+    assert_eq!(script.as_str(), Some(SCRIPT));
+
+    let expected = crate_gun();
+    assert_eq!(weapon.display_name.get(), "WEAPON_CRATE_GUN");
+    assert_eq!(weapon.clip_size, 30);
+    assert_eq!(weapon.oo_pos_anim_length.get(), [0.5, 0.25]);
+    assert!(weapon.silenced);
+    assert_eq!(
+        weapon.xanims.get(WeaponAnimSlot::FIRE).map(XString::get),
+        Some("viewmodel_crate_gun_fire")
+    );
+    assert!(weapon.xanims.get(WeaponAnimSlot::ROOT).is_none());
+    let hide_tags = weapon
+        .hide_tags
+        .iter()
+        .map(XString::get)
+        .collect::<Vec<_>>();
+    assert_eq!(hide_tags[..3], ["tag_silencer", "tag_clip_extended", ""]);
+
+    let def = weapon.weap_def.as_deref().unwrap();
+    let expected = expected.weap_def.as_deref().unwrap();
+    assert_eq!(def.mode_name.get(), "crate_gun");
+    assert_eq!(def.weap_class, WeapClass::SMG);
+    assert_eq!(def.fire_type, WeapFireType::BURSTFIRE3);
+    assert_eq!(
+        (def.damage, def.min_damage, def.fire_time),
+        (expected.damage, expected.min_damage, expected.fire_time)
+    );
+    assert_eq!(def.fire_sound.get(), "wpn_crate_gun_fire");
+    assert_eq!(def.explosion_tag.get(), "tag_flash");
+    assert_eq!(
+        def.notetrack_sound_map_keys,
+        expected.notetrack_sound_map_keys
+    );
+    assert_eq!(
+        def.notetrack_sound_map_values,
+        expected.notetrack_sound_map_values
+    );
+    assert_eq!(
+        def.bounce_sound.as_ref().unwrap()[0].get(),
+        "grenade_bounce_default"
+    );
+    assert_eq!(def.parallel_bounce, expected.parallel_bounce);
+    assert_eq!(def.perpendicular_bounce, None);
+    assert_eq!(def.accuracy_graph_name[0].get(), "crate_gun_ai.accu");
+    let knots = def.accuracy_graph_knots[0]
+        .iter()
+        .map(|k| k.get())
+        .collect::<Vec<_>>();
+    assert_eq!(knots, [[0.0, 1.0], [1.0, 0.5]]);
+    assert_eq!(def.accuracy_graph_knot_count, [2, 0]);
+}
+
+#[allow(dead_code)]
+fn main() -> Result<(), Box<dyn Error>> {
+    let path = Path::new("out/patch_example.ff");
+    std::fs::create_dir_all(path.parent().unwrap())?;
+
+    let assets = build_patch(path, patch_assets()?)?;
+    check_patch(&assets);
+    println!("Wrote {} assets to {}", assets.len(), path.display());
+    Ok(())
+}
@@ -0,0 +1,48 @@
+//! Regenerates the committed corpus summary from the Fastfiles in
+//! `T5_CORPUS_DIR`. See [`t5_xfile_deserializer::corpus`].
+//!
+//! Entries for files that aren't present locally are kept, so contributors
+//! with different sets of files don't clobber each other's entries.
+
+use t5_xfile_deserializer::corpus::{
+    CorpusSummary, SUMMARY_PATH, corpus_dir, find_files, summarize,
+};
+
+fn main() {
+    let dir = corpus_dir().unwrap();
+    let files = find_files(&dir).unwrap();
+    let mut summary = CorpusSummary::load(SUMMARY_PATH).unwrap();
+
+    let mut panicked = 0;
+    for file in &files {
+        match summarize(file) {
+            Ok(s) => {
+                println!(
+                    "{} ({}): {} asset(s), {}",
+                    file.path.display(),
+                    file.key,
+                    s.asset_count,
+                    s.error
+                        .as_deref()
+                        .map_or("no errors".to_string(), |e| format!("stopped at {e}"))
+                );
+                summary.files.insert(file.key.clone(), s);
+            }
+            Err(p) => {
+                println!("{} ({}): panicked: {p}", file.path.display(), file.key);
+                panicked += 1;
+            }
+        }
+    }
+
+    summary.save(SUMMARY_PATH).unwrap();
+    println!(
+        "Summarized {} file(s) into {SUMMARY_PATH}.",
+        files.len() - panicked
+    );
+
+    if panicked > 0 {
+        println!("{panicked} file(s) panicked and weren't recorded.");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,296 @@
+//! Compatibility checks against a local corpus of real Fastfiles.
+//!
+//! Fastfiles can't be committed, so instead a summary of what each one
+//! deserializes to is: the number of assets, how many of each type, how many
+//! bytes were left over at the end of the blob, and what error (if any) the
+//! file stopped at. Files are identified by a hash of their path relative to
+//! the corpus directory, and none of their content ends up in the summary.
+//!
+//! The corpus directory is given by [`CORPUS_DIR_VAR`]. Fastfiles directly
+//! inside it are treated as Windows files; files for other platforms go in a
+//! subdirectory named after the platform (`windows`, `macos`, `xbox360`, or
+//! `ps3`).
+//!
+//! `cargo test --features corpus -- --ignored` checks every file against the
+//! committed summary, and `cargo run --features corpus --bin corpus-update`
+//! regenerates it.
+
+use std::{
+    collections::BTreeMap,
+    panic::{AssertUnwindSafe, catch_unwind},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use t5_xfile_defs::{ErrorKind, XFilePlatform};
+
+use crate::{
+    Error, Result, T5XFileDeserializerBuilder, export::bin::source_checksum, file_line_col,
+};
+
+/// The environment variable naming the corpus directory.
+pub const CORPUS_DIR_VAR: &str = "T5_CORPUS_DIR";
+
+/// Where the committed summary lives.
+pub const SUMMARY_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/corpus/summary.json");
+
+/// Errors that a file is allowed to stop at, by [`ErrorKind::name`]. These
+/// are the ones that mean "not implemented yet" rather than "wrong".
+pub const ALLOWED_ERROR_KINDS: &[&str] = &[
+    "Todo",
+    "UnimplementedPlatform",
+    "UnsupportedPlatform",
+    "UnsupportedCompression",
+];
+
+const PLATFORM_DIRS: [(&str, XFilePlatform); 4] = [
+    ("windows", XFilePlatform::Windows),
+    ("macos", XFilePlatform::macOS),
+    ("xbox360", XFilePlatform::Xbox360),
+    ("ps3", XFilePlatform::PS3),
+];
+
+/// What a single Fastfile deserialized to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileSummary {
+    /// The platform directory the file was found in.
+    pub platform: String,
+    /// How many assets were deserialized before the end of the file or the
+    /// first error.
+    pub asset_count: usize,
+    pub non_null_assets: usize,
+    /// Asset counts by [`XAssetType`](t5_xfile_defs::xasset::XAssetType).
+    pub asset_types: BTreeMap<String, usize>,
+    /// [`None`] if the file stopped at an error.
+    pub trailing_bytes: Option<u64>,
+    /// The [`ErrorKind::name`] of the error the file stopped at.
+    pub error: Option<String>,
+}
+
+/// The committed summary, keyed by [`name_hash`].
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CorpusSummary {
+    pub files: BTreeMap<String, FileSummary>,
+}
+
+impl CorpusSummary {
+    /// Loads the summary at `path`, or returns an empty one if there isn't
+    /// one yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let s = std::fs::read_to_string(path)
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?;
+        serde_json::from_str(&s).map_err(|e| {
+            Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!("corpus: bad summary: {e}")),
+            )
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?;
+        }
+
+        let mut s = serde_json::to_string_pretty(self).unwrap();
+        s.push('\n');
+        std::fs::write(path, s).map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))
+    }
+}
+
+/// A Fastfile found in the corpus directory.
+#[derive(Clone, Debug)]
+pub struct CorpusFile {
+    pub path: PathBuf,
+    pub platform: XFilePlatform,
+    /// The file's [`name_hash`].
+    pub key: String,
+}
+
+/// A 64-bit FNV-1a hash of `name`, as hex. `name` should be the file's path
+/// relative to the corpus directory, with `/` as the separator.
+pub fn name_hash(name: &str) -> String {
+    format!("{:016x}", source_checksum(name.as_bytes()))
+}
+
+/// Reads the corpus directory from [`CORPUS_DIR_VAR`].
+pub fn corpus_dir() -> Result<PathBuf> {
+    match std::env::var_os(CORPUS_DIR_VAR) {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => Err(Error::new(
+            file_line_col!(),
+            ErrorKind::BrokenInvariant(format!(
+                "corpus: {CORPUS_DIR_VAR} must be set to a directory of Fastfiles"
+            )),
+        )),
+    }
+}
+
+/// Finds every Fastfile in `dir` and its platform subdirectories, sorted by
+/// path.
+pub fn find_files(dir: impl AsRef<Path>) -> Result<Vec<CorpusFile>> {
+    let dir = dir.as_ref();
+    let mut files = fastfiles_in(dir, "", XFilePlatform::Windows)?;
+
+    for (name, platform) in PLATFORM_DIRS {
+        let sub = dir.join(name);
+        if sub.is_dir() {
+            files.extend(fastfiles_in(&sub, name, platform)?);
+        }
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+fn fastfiles_in(dir: &Path, prefix: &str, platform: XFilePlatform) -> Result<Vec<CorpusFile>> {
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?
+            .path();
+        let is_ff = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("ff"));
+        if !path.is_file() || !is_ff {
+            continue;
+        }
+
+        let file_name = path.file_name().unwrap().to_string_lossy();
+        let name = if prefix.is_empty() {
+            file_name.into_owned()
+        } else {
+            format!("{prefix}/{file_name}")
+        };
+
+        files.push(CorpusFile {
+            key: name_hash(&name),
+            path,
+            platform,
+        });
+    }
+
+    Ok(files)
+}
+
+fn platform_dir(platform: XFilePlatform) -> &'static str {
+    PLATFORM_DIRS
+        .iter()
+        .find(|(_, p)| *p == platform)
+        .map(|(name, _)| *name)
+        .unwrap()
+}
+
+/// Deserializes `file` and summarizes the result. Returns the panic message
+/// if deserialization panicked.
+pub fn summarize(file: &CorpusFile) -> core::result::Result<FileSummary, String> {
+    catch_unwind(AssertUnwindSafe(|| summarize_unchecked(file))).map_err(|p| {
+        p.downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| p.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "<non-string panic payload>".to_string())
+    })
+}
+
+fn summarize_unchecked(file: &CorpusFile) -> FileSummary {
+    let mut summary = FileSummary {
+        platform: platform_dir(file.platform).to_string(),
+        asset_count: 0,
+        non_null_assets: 0,
+        asset_types: BTreeMap::new(),
+        trailing_bytes: None,
+        error: None,
+    };
+
+    let mut f = match std::fs::File::open(&file.path) {
+        Ok(f) => f,
+        Err(e) => {
+            summary.error = Some(ErrorKind::Io(e).name().to_string());
+            return summary;
+        }
+    };
+
+    let de = T5XFileDeserializerBuilder::from_file(&mut f, file.platform, true)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache());
+    let mut de = match de {
+        Ok(de) => de,
+        Err(e) => {
+            summary.error = Some(e.kind().name().to_string());
+            return summary;
+        }
+    };
+
+    loop {
+        match de.deserialize_next() {
+            Ok(Some(asset)) => {
+                summary.asset_count += 1;
+                if asset.is_some() {
+                    summary.non_null_assets += 1;
+                }
+                *summary
+                    .asset_types
+                    .entry(format!("{:?}", asset.asset_type()))
+                    .or_default() += 1;
+            }
+            Ok(None) => {
+                summary.trailing_bytes = Some(de.trailing_bytes());
+                break;
+            }
+            Err(e) => {
+                summary.error = Some(e.kind().name().to_string());
+                break;
+            }
+        }
+    }
+
+    summary
+}
+
+/// Compares `current` against the summary recorded for the same file,
+/// returning a description of every problem found.
+pub fn check(recorded: Option<&FileSummary>, current: &FileSummary) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if let Some(e) = &current.error
+        && !ALLOWED_ERROR_KINDS.contains(&e.as_str())
+    {
+        problems.push(format!("stopped at a {e} error"));
+    }
+
+    let Some(recorded) = recorded else {
+        problems.push("not in the summary (run corpus-update)".to_string());
+        return problems;
+    };
+
+    if recorded.trailing_bytes == Some(0) && current.trailing_bytes != Some(0) {
+        problems.push(format!(
+            "had no trailing bytes, now has {}",
+            current
+                .trailing_bytes
+                .map_or("an error".to_string(), |n| n.to_string())
+        ));
+    }
+
+    if recorded != current {
+        problems.push(format!(
+            "doesn't match the summary (run corpus-update if this is expected)\n  \
+             recorded: {recorded:?}\n  current:  {current:?}"
+        ));
+    }
+
+    problems
+}
@@ -1,19 +1,31 @@
 use core::marker::PhantomData;
 
-use alloc::collections::VecDeque;
+use alloc::collections::{BTreeMap, VecDeque};
 use serde::de::DeserializeOwned;
 
 use std::{
     io::{Cursor, Read, Seek, Write},
+    panic::{AssertUnwindSafe, catch_unwind},
     path::Path,
 };
 
+#[cfg(feature = "d3d9")]
+use windows::Win32::Graphics::Direct3D9::IDirect3DDevice9;
+
 use crate::{BincodeOptions, Error, ErrorKind, Result, StreamLen, file_line_col, size_of};
 
 use t5_xfile_defs::{
-    FatPointer, ScriptString, T5XFileDeserialize, XFile, XFileDeserializeInto, XFileHeader,
-    XFilePlatform, XFileVersion,
-    xasset::{XAsset, XAssetListRaw, XAssetRaw},
+    CompressionMethod, ErrorAsset, ErrorExcerpt, ScriptString, T5XFileDeserialize, XFile,
+    XFileHeader, XFilePlatform, XFileVersion, XString,
+    cancel::CancellationToken,
+    custom::{self, AssetHandler},
+    gpu::GpuUploader,
+    layout::LayoutOverrides,
+    limits::{ResourceLimit, ResourceLimits},
+    util::{FatPointer, StringInterner, XFileDeserializeInto},
+    xasset::{
+        LoadedAsset, Provenance, XAsset, XAssetGeneric, XAssetListRaw, XAssetRaw, XAssetType,
+    },
 };
 
 pub enum InflateSuccess {
@@ -21,16 +33,207 @@ pub enum InflateSuccess {
     AlreadyInflated,
 }
 
+/// How many bytes of the blob [`T5XFileDeserializerBuilder::with_verbose_errors`]
+/// attaches to errors.
+pub const ERROR_EXCERPT_LEN: usize = 32;
+
 pub enum CacheSuccess {
     CacheCreated,
     CacheOverwritten,
 }
 
+/// Ceilings used to sanity-check the [`XFile`] and asset list at the start
+/// of the blob. If they fail the checks, but would pass them if read with
+/// the other endianness, the file is almost certainly for a different
+/// platform, and [`ErrorKind::ProbableEndianMismatch`] is returned.
+///
+/// The asset list's counts are checked against
+/// [`ResourceLimits::max_assets`] and [`ResourceLimits::max_script_strings`],
+/// the same caps that are enforced on them afterwards.
+#[derive(Copy, Clone, Debug)]
+pub struct HeaderLimits {
+    /// The largest plausible [`XFile::size`], [`XFile::external_size`], and
+    /// block size.
+    pub max_size: u32,
+}
+
+impl Default for HeaderLimits {
+    fn default() -> Self {
+        Self { max_size: 1 << 30 }
+    }
+}
+
+impl HeaderLimits {
+    /// `blob_len` is the length of the whole blob, including the [`XFile`].
+    fn xfile_is_plausible(&self, xfile: &XFile, blob_len: usize, allow_truncated: bool) -> bool {
+        let expected = xfile.size as usize + size_of!(XFile);
+
+        xfile.size <= self.max_size
+            && xfile.external_size <= self.max_size
+            && xfile.block_size.iter().all(|&s| s <= self.max_size)
+            && (expected == blob_len || (allow_truncated && expected > blob_len))
+    }
+}
+
+/// `available` is the number of bytes in the blob after the asset list.
+fn xasset_list_is_plausible(
+    xasset_list: &XAssetListRaw,
+    available: usize,
+    limits: &ResourceLimits,
+) -> bool {
+    let strings = xasset_list.strings.size();
+    let assets = xasset_list.assets.size();
+
+    strings <= limits.max_script_strings.unwrap_or(usize::MAX)
+        && assets <= limits.max_assets.unwrap_or(usize::MAX)
+        && strings
+            .saturating_mul(size_of!(u32))
+            .saturating_add(assets.saturating_mul(size_of!(XAssetRaw)))
+            <= available
+}
+
+/// Returned by [`T5XFileDeserializer::deserialize_remaining_partial`].
+pub struct PartialResult {
+    /// Every asset that was fully deserialized.
+    pub assets: Vec<XAsset>,
+    /// Whether the payload was truncated.
+    pub truncated: bool,
+    /// The asset that straddled the end of the payload, if any.
+    pub cut_asset: Option<TruncatedAsset>,
+}
+
+pub struct TruncatedAsset {
+    /// The asset's index in the asset list.
+    pub index: usize,
+    /// [`None`] if the asset's type wasn't valid.
+    pub asset_type: Option<XAssetType>,
+    /// The error the asset failed with.
+    pub error: Error,
+}
+
+/// Limits for [`T5XFileDeserializer::survey`].
+#[derive(Copy, Clone, Debug)]
+pub struct SurveyLimits {
+    /// Stop after this many assets have failed.
+    pub max_errors: usize,
+    /// How far past the start of a failed asset to look for the next one.
+    pub max_scan_bytes: u64,
+    /// How many plausible starting points to try deserializing the next
+    /// asset at before giving up.
+    pub max_candidates: usize,
+}
+
+impl Default for SurveyLimits {
+    fn default() -> Self {
+        Self {
+            max_errors: 16,
+            max_scan_bytes: 0x10000,
+            max_candidates: 256,
+        }
+    }
+}
+
+/// Returned by [`T5XFileDeserializer::survey`].
+pub struct SurveyResult {
+    /// Every asset that was deserialized without errors.
+    pub assets: Vec<XAsset>,
+    pub errors: Vec<SurveyError>,
+    /// Whether the survey stopped before the end of the asset list, either
+    /// because of [`SurveyLimits`] or because the next asset couldn't be
+    /// found.
+    pub stopped_early: bool,
+}
+
+pub struct SurveyError {
+    /// The asset's index in the asset list.
+    pub index: usize,
+    /// [`None`] if the asset's type wasn't valid.
+    pub asset_type: Option<XAssetType>,
+    /// The error the asset failed with. Panics are caught and reported as
+    /// [`ErrorKind::BrokenInvariant`].
+    pub error: Error,
+    /// How many bytes the asset appears to have taken up, i.e. how far
+    /// ahead the next asset was found. [`None`] if it wasn't found.
+    pub skipped_bytes: Option<u64>,
+}
+
+/// A saved deserializer position. See [`T5XFileDeserializer::checkpoint`].
+#[derive(Clone, Debug)]
+pub struct DeserializeCheckpoint<'a> {
+    pos: u64,
+    script_string_count: usize,
+    xassets_raw: VecDeque<XAssetRaw<'a>>,
+    deserialized_assets: usize,
+    next_index: usize,
+    non_null_assets: usize,
+    allocated_bytes: u64,
+}
+
+impl<'a> DeserializeCheckpoint<'a> {
+    /// The stream position the checkpoint was taken at.
+    pub const fn position(&self) -> u64 {
+        self.pos
+    }
+}
+
+/// Returned by [`T5XFileDeserializer::measure_sizes`].
+#[derive(Clone, Debug)]
+pub struct AssetSizeEstimate {
+    /// The asset's index in the asset list.
+    pub index: usize,
+    pub asset_type: XAssetType,
+    /// See [`XAsset::raw_asset_type`]. Only differs from `asset_type` for
+    /// custom assets whose type isn't an [`XAssetType`].
+    pub raw_asset_type: u32,
+    /// [`None`] for null assets and asset types without a name.
+    pub name: Option<String>,
+    /// The exact number of bytes the asset occupies in the blob.
+    pub blob_bytes: u64,
+    /// Estimated size of the deserialized asset in memory.
+    pub memory_bytes: u64,
+}
+
+/// Per-[`XAssetType`] sums of [`AssetSizeEstimate`]s.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct AssetSizeTotals {
+    pub count: usize,
+    pub blob_bytes: u64,
+    pub memory_bytes: u64,
+}
+
+impl AssetSizeEstimate {
+    /// Sums `estimates` by asset type. Custom assets whose type isn't an
+    /// [`XAssetType`] are left out.
+    pub fn totals_by_type(estimates: &[Self]) -> BTreeMap<XAssetType, AssetSizeTotals> {
+        let mut totals = BTreeMap::<XAssetType, AssetSizeTotals>::new();
+
+        for estimate in estimates {
+            if XAssetType::from_raw(estimate.raw_asset_type).is_none() {
+                continue;
+            }
+
+            let t = totals.entry(estimate.asset_type).or_default();
+            t.count += 1;
+            t.blob_bytes += estimate.blob_bytes;
+            t.memory_bytes += estimate.memory_bytes;
+        }
+
+        totals
+    }
+}
+
 #[cfg(feature = "d3d9")]
 pub struct D3D9State<'a> {
     pub(crate) device: &'a mut IDirect3DDevice9,
 }
 
+#[cfg(feature = "d3d9")]
+impl<'a> D3D9State<'a> {
+    pub fn new(device: &'a mut IDirect3DDevice9) -> Self {
+        Self { device }
+    }
+}
+
 #[cfg(not(feature = "d3d9"))]
 pub(crate) struct D3D9State<'a>(PhantomData<&'a ()>);
 
@@ -57,10 +260,43 @@ pub struct T5XFileDeserializer<'a, T: T5XFileDeserializerTypestate = T5XFileDese
     xasset_list: XAssetListRaw<'a>,
     xassets_raw: VecDeque<XAssetRaw<'a>>,
     deserialized_assets: usize,
+    /// The index in the asset list of the next asset to deserialize. Unlike
+    /// `deserialized_assets`, this also counts assets that failed.
+    next_index: usize,
     non_null_assets: usize,
+    /// The blob span of each asset deserialized so far, by index, or
+    /// [`None`] if the asset failed.
+    spans: Vec<Option<(u64, u64)>>,
     opts: BincodeOptions,
     platform: XFilePlatform,
     d3d9_state: Option<D3D9State<'a>>,
+    gpu_uploader: Option<Box<dyn GpuUploader + 'a>>,
+    allow_truncated: bool,
+    header_limits: HeaderLimits,
+    /// See [`T5XFileDeserializerBuilder::with_resource_limits`].
+    resource_limits: ResourceLimits,
+    /// The running total for [`ResourceLimits::max_allocated_bytes`].
+    allocated_bytes: u64,
+    /// The running total for [`T5XFileDeserialize::track_heap_bytes`],
+    /// which [`Self::measure_sizes`] measures assets by.
+    heap_bytes: u64,
+    /// [`None`] if interning is off. See
+    /// [`T5XFileDeserializerBuilder::with_string_interning`].
+    string_interner: Option<StringInterner>,
+    /// See [`T5XFileDeserializerBuilder::with_layout_overrides`].
+    layout_overrides: LayoutOverrides,
+    /// See [`T5XFileDeserializerBuilder::with_verbose_errors`].
+    verbose_errors: bool,
+    /// See [`T5XFileDeserializerBuilder::with_source`].
+    source: String,
+    /// See [`T5XFileDeserializerBuilder::with_cancellation_token`].
+    cancellation_token: Option<CancellationToken>,
+    /// See [`T5XFileDeserializer::register_handler`].
+    handlers: BTreeMap<u32, Box<dyn AssetHandler>>,
+    truncated: bool,
+    /// [`None`] when reading from a cache file, since that's already
+    /// decompressed.
+    compression: Option<CompressionMethod>,
     _p: PhantomData<T>,
 }
 
@@ -70,7 +306,22 @@ pub struct T5XFileDeserializerBuilder<'a> {
     silent: bool,
     platform: XFilePlatform,
     allow_unsupported_platforms: bool,
+    allow_truncated: bool,
+    header_limits: HeaderLimits,
+    /// See [`T5XFileDeserializerBuilder::with_resource_limits`].
+    resource_limits: ResourceLimits,
+    /// See [`T5XFileDeserializerBuilder::with_string_interning`].
+    string_interning: bool,
+    /// See [`T5XFileDeserializerBuilder::with_layout_overrides`].
+    layout_overrides: LayoutOverrides,
+    /// See [`T5XFileDeserializerBuilder::with_verbose_errors`].
+    verbose_errors: bool,
+    /// See [`T5XFileDeserializerBuilder::with_source`].
+    source: String,
+    /// See [`T5XFileDeserializerBuilder::with_cancellation_token`].
+    cancellation_token: Option<CancellationToken>,
     d3d9_state: Option<D3D9State<'a>>,
+    gpu_uploader: Option<Box<dyn GpuUploader + 'a>>,
 }
 
 impl<'a> T5XFileDeserializerBuilder<'a> {
@@ -85,7 +336,16 @@ impl<'a> T5XFileDeserializerBuilder<'a> {
             platform,
             silent: false,
             allow_unsupported_platforms,
+            allow_truncated: false,
+            header_limits: HeaderLimits::default(),
+            resource_limits: ResourceLimits::default(),
+            string_interning: true,
+            layout_overrides: LayoutOverrides::default(),
+            verbose_errors: false,
+            source: String::new(),
+            cancellation_token: None,
             d3d9_state: None,
+            gpu_uploader: None,
         }
     }
 
@@ -100,7 +360,16 @@ impl<'a> T5XFileDeserializerBuilder<'a> {
             platform,
             silent: false,
             allow_unsupported_platforms,
+            allow_truncated: false,
+            header_limits: HeaderLimits::default(),
+            resource_limits: ResourceLimits::default(),
+            string_interning: true,
+            layout_overrides: LayoutOverrides::default(),
+            verbose_errors: false,
+            source: String::new(),
+            cancellation_token: None,
             d3d9_state: None,
+            gpu_uploader: None,
         }
     }
 
@@ -114,14 +383,96 @@ impl<'a> T5XFileDeserializerBuilder<'a> {
         self
     }
 
+    /// Permits the deserializer to continue with whatever could be recovered
+    /// from a truncated file instead of failing outright. See
+    /// [`T5XFileDeserializer::deserialize_remaining_partial`].
+    pub fn with_allow_truncated(mut self, allow_truncated: bool) -> Self {
+        self.allow_truncated = allow_truncated;
+        self
+    }
+
+    /// Sets the limits used to sanity-check the [`XFile`]. The asset list is
+    /// checked against the [resource limits](Self::with_resource_limits).
+    /// See [`HeaderLimits`].
+    pub fn with_header_limits(mut self, header_limits: HeaderLimits) -> Self {
+        self.header_limits = header_limits;
+        self
+    }
+
+    /// Sets the limits on what the blob can make the deserializer allocate.
+    /// The defaults are generous enough for any retail file; set tighter
+    /// ones (or [`ResourceLimits::unlimited`]) as needed. See
+    /// [`ResourceLimits`].
+    pub fn with_resource_limits(mut self, resource_limits: ResourceLimits) -> Self {
+        self.resource_limits = resource_limits;
+        self
+    }
+
+    /// Makes every [`XString`] with the same contents share one allocation,
+    /// which saves a lot of memory on files with many materials, techniques,
+    /// or models. On by default. See [`StringInterner`].
+    pub fn with_string_interning(mut self, string_interning: bool) -> Self {
+        self.string_interning = string_interning;
+        self
+    }
+
+    /// Tells the deserializer how the file's layout differs from retail's.
+    /// See [`LayoutOverrides`].
+    pub fn with_layout_overrides(mut self, layout_overrides: LayoutOverrides) -> Self {
+        self.layout_overrides = layout_overrides;
+        self
+    }
+
+    /// Attaches an [`ErrorExcerpt`] of the blob around where the error
+    /// occurred to errors returned while deserializing assets.
+    pub fn with_verbose_errors(mut self, verbose_errors: bool) -> Self {
+        self.verbose_errors = verbose_errors;
+        self
+    }
+
+    /// Sets the [`Provenance::source`] of every asset deserialized from this
+    /// file, e.g. its file name.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Makes inflating and deserializing poll `token`, and return
+    /// [`ErrorKind::Cancelled`] soon after it's cancelled.
+    ///
+    /// The token is checked between inflate chunks, between assets, and
+    /// every [`CANCEL_CHECK_INTERVAL`] elements of an array, so the time
+    /// from cancelling to returning is bounded by how long it takes to read
+    /// that many elements of the largest struct an asset has an array of.
+    /// On the fixture corpus, that's well under 100ms.
+    ///
+    /// An asset whose deserialization is cancelled is rewound, so
+    /// cancelling [`T5XFileDeserializer::deserialize_next`] leaves the
+    /// deserializer as it was before the call.
+    ///
+    /// [`CANCEL_CHECK_INTERVAL`]: t5_xfile_defs::util::CANCEL_CHECK_INTERVAL
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
     #[cfg(feature = "d3d9")]
     pub fn with_d3d9(mut self, d3d9_state: Option<D3D9State<'a>>) -> Self {
         self.d3d9_state = d3d9_state;
         self
     }
 
+    /// Passes every image with a load def to `gpu_uploader` as it's
+    /// deserialized. See [`GfxImage::gpu_handle`].
+    ///
+    /// [`GfxImage::gpu_handle`]: t5_xfile_defs::techset::GfxImage::gpu_handle
+    pub fn with_gpu_uploader(mut self, gpu_uploader: Option<Box<dyn GpuUploader + 'a>>) -> Self {
+        self.gpu_uploader = gpu_uploader;
+        self
+    }
+
     pub fn build(mut self) -> Result<T5XFileDeserializer<'a, T5XFileDeserializerDeflated>> {
-        if self.file.is_some() {
+        let de = if self.file.is_some() {
             T5XFileDeserializer::from_file(
                 self.file.take().unwrap(),
                 self.silent,
@@ -139,7 +490,20 @@ impl<'a> T5XFileDeserializerBuilder<'a> {
             )
         } else {
             unreachable!()
-        }
+        };
+
+        de.map(|de| T5XFileDeserializer {
+            allow_truncated: self.allow_truncated,
+            header_limits: self.header_limits,
+            resource_limits: self.resource_limits,
+            string_interner: self.string_interning.then(StringInterner::new),
+            layout_overrides: self.layout_overrides,
+            verbose_errors: self.verbose_errors,
+            source: self.source,
+            cancellation_token: self.cancellation_token,
+            gpu_uploader: self.gpu_uploader,
+            ..de
+        })
     }
 }
 
@@ -164,12 +528,14 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
         }
 
         if platform == XFilePlatform::Xbox360 || platform == XFilePlatform::PS3 {
-            if allow_unsupported_platforms && !silent {
-                println!(
-                    "Warning: {platform} Fastfiles might (and probably do) have differences \
-                     from Windows Fastfiles that aren't accounted for in this \
-                     library. Expect problems."
-                );
+            if allow_unsupported_platforms {
+                if !silent {
+                    println!(
+                        "Warning: {platform} Fastfiles might (and probably do) have differences \
+                         from Windows Fastfiles that aren't accounted for in this \
+                         library. Expect problems."
+                    );
+                }
             } else {
                 if !silent {
                     println!(
@@ -186,21 +552,25 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             }
         }
 
-        if !silent && platform == XFilePlatform::macOS {
+        if platform == XFilePlatform::macOS {
             if allow_unsupported_platforms {
-                println!(
-                    "Warning: macOS Fastfiles are *presumably* identical to \
-                     Windows Fastfiles (being an Aspyr port and all), but the \
-                     author of this library hasn't yet verified that to be true. \
-                     Problems may arise."
-                );
+                if !silent {
+                    println!(
+                        "Warning: macOS Fastfiles are *presumably* identical to \
+                         Windows Fastfiles (being an Aspyr port and all), but the \
+                         author of this library hasn't yet verified that to be true. \
+                         Problems may arise."
+                    );
+                }
             } else {
-                println!(
-                    "Error: macOS Fastfiles are *presumably* identical to \
-                     Windows Fastfiles (being an Aspyr port and all), but the \
-                     author of this library hasn't yet verified that to be true, \
-                     and as such, they are unsupported."
-                );
+                if !silent {
+                    println!(
+                        "Error: macOS Fastfiles are *presumably* identical to \
+                         Windows Fastfiles (being an Aspyr port and all), but the \
+                         author of this library hasn't yet verified that to be true, \
+                         and as such, they are unsupported."
+                    );
+                }
                 return Err(Error::new_with_offset(
                     file_line_col!(),
                     0,
@@ -223,7 +593,10 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
 
         if !header.magic_is_valid() {
             if !silent {
-                println!("Fastfile header magic invalid: valid values are IWffu100 and IWff0100");
+                println!(
+                    "Fastfile header magic invalid: valid values are IWffu100, IWff0100 and \
+                     IWffz100"
+                );
             }
             return Err(Error::new_with_offset(
                 file_line_col!(),
@@ -232,6 +605,20 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             ));
         }
 
+        let Some(compression) = header.compression_method() else {
+            if !silent {
+                println!(
+                    "Fastfile compression byte {:#04X} is unknown: valid values are 'u', '0' and 'z'",
+                    header.compression_byte()
+                );
+            }
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                4,
+                ErrorKind::UnsupportedCompression(header.compression_byte()),
+            ));
+        };
+
         if XFileVersion::is_other_endian(header.version) {
             if !silent {
                 println!(
@@ -251,7 +638,7 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
                 println!(
                     "Fastfile is wrong version (version={:#010X}, expected {:#010X})",
                     header.version,
-                    XFileVersion::from_platform(platform).as_u32()
+                    XFileVersion::CURRENT
                 );
             }
 
@@ -276,10 +663,26 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             xasset_list: XAssetListRaw::default(),
             xassets_raw: VecDeque::new(),
             deserialized_assets: 0,
+            next_index: 0,
+            spans: Vec::new(),
             non_null_assets: 0,
             opts,
             platform,
             d3d9_state,
+            gpu_uploader: None,
+            allow_truncated: false,
+            header_limits: HeaderLimits::default(),
+            resource_limits: ResourceLimits::default(),
+            allocated_bytes: 0,
+            heap_bytes: 0,
+            string_interner: Some(StringInterner::new()),
+            layout_overrides: LayoutOverrides::default(),
+            verbose_errors: false,
+            source: String::new(),
+            cancellation_token: None,
+            handlers: BTreeMap::new(),
+            truncated: false,
+            compression: Some(compression),
             _p: PhantomData,
         };
 
@@ -306,12 +709,14 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
         }
 
         if platform == XFilePlatform::Xbox360 || platform == XFilePlatform::PS3 {
-            if allow_unsupported_platforms && !silent {
-                println!(
-                    "Warning: {platform} Fastfiles might (and probably do) have differences \
-                     from Windows Fastfiles that aren't accounted for in this \
-                     library. Expect problems."
-                );
+            if allow_unsupported_platforms {
+                if !silent {
+                    println!(
+                        "Warning: {platform} Fastfiles might (and probably do) have differences \
+                         from Windows Fastfiles that aren't accounted for in this \
+                         library. Expect problems."
+                    );
+                }
             } else {
                 if !silent {
                     println!(
@@ -328,21 +733,25 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             }
         }
 
-        if !silent && platform == XFilePlatform::macOS {
+        if platform == XFilePlatform::macOS {
             if allow_unsupported_platforms {
-                println!(
-                    "Warning: macOS Fastfiles are *presumably* identical to \
-                     Windows Fastfiles (being an Aspyr port and all), but the \
-                     author of this library hasn't yet verified that to be true. \
-                     Problems may arise."
-                );
+                if !silent {
+                    println!(
+                        "Warning: macOS Fastfiles are *presumably* identical to \
+                         Windows Fastfiles (being an Aspyr port and all), but the \
+                         author of this library hasn't yet verified that to be true. \
+                         Problems may arise."
+                    );
+                }
             } else {
-                println!(
-                    "Error: macOS Fastfiles are *presumably* identical to \
-                     Windows Fastfiles (being an Aspyr port and all), but the \
-                     author of this library hasn't yet verified that to be true, \
-                     and as such, they are unsupported."
-                );
+                if !silent {
+                    println!(
+                        "Error: macOS Fastfiles are *presumably* identical to \
+                         Windows Fastfiles (being an Aspyr port and all), but the \
+                         author of this library hasn't yet verified that to be true, \
+                         and as such, they are unsupported."
+                    );
+                }
                 return Err(Error::new_with_offset(
                     file_line_col!(),
                     0,
@@ -365,21 +774,227 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             xasset_list: XAssetListRaw::default(),
             xassets_raw: VecDeque::new(),
             deserialized_assets: 0,
+            next_index: 0,
+            spans: Vec::new(),
             non_null_assets: 0,
             opts: BincodeOptions::from_platform(platform),
             platform,
             d3d9_state,
+            gpu_uploader: None,
+            allow_truncated: false,
+            header_limits: HeaderLimits::default(),
+            resource_limits: ResourceLimits::default(),
+            allocated_bytes: 0,
+            heap_bytes: 0,
+            string_interner: Some(StringInterner::new()),
+            layout_overrides: LayoutOverrides::default(),
+            verbose_errors: false,
+            source: String::new(),
+            cancellation_token: None,
+            handlers: BTreeMap::new(),
+            truncated: false,
+            compression: None,
             _p: PhantomData,
         })
     }
 
+    /// Decompresses the blob according to the header's compression byte.
+    ///
+    /// For [`CompressionMethod::Uncompressed`], the blob is checked against
+    /// the size in its [`XFile`] to see if it's stored. If it isn't, it's
+    /// LZX-decompressed, which needs the `lzx` feature.
+    fn decompress_payload(&mut self, compressed_payload: Vec<u8>) -> Result<Vec<u8>> {
+        match self.compression {
+            Some(CompressionMethod::Deflate) => {
+                if !self.silent {
+                    println!("Payload read, inflating... (this may take a while)");
+                }
+                let inflated = inflate_partial(
+                    &compressed_payload,
+                    self.cancellation_token.as_ref(),
+                    self.resource_limits.max_inflated_size,
+                )?;
+                self.accept_partial(inflated, ErrorKind::Inflate)
+            }
+            #[cfg(feature = "zstd")]
+            Some(CompressionMethod::Zstd) => {
+                if !self.silent {
+                    println!("Payload read, decompressing zstd blob...");
+                }
+                let decoded = zstd_decode_partial(
+                    &compressed_payload,
+                    self.cancellation_token.as_ref(),
+                    self.resource_limits.max_inflated_size,
+                )?;
+                self.accept_partial(decoded, ErrorKind::Zstd)
+            }
+            #[cfg(not(feature = "zstd"))]
+            Some(CompressionMethod::Zstd) => {
+                if !self.silent {
+                    println!("Error: zstd-compressed blobs need the zstd feature.");
+                }
+                Err(Error::new_with_offset(
+                    file_line_col!(),
+                    4,
+                    ErrorKind::UnsupportedCompression(CompressionMethod::Zstd.as_byte()),
+                ))
+            }
+            Some(CompressionMethod::Uncompressed) => {
+                let stored_len = self.stored_len(&compressed_payload);
+                if stored_len == Some(compressed_payload.len()) {
+                    return self.accept_stored(compressed_payload);
+                }
+
+                // The start of an LZX blob read as an `XFile` almost always
+                // gives a size bigger than the payload too, so a short
+                // payload is only a truncated stored blob if it isn't LZX.
+                if self.allow_truncated && stored_len.is_some_and(|l| compressed_payload.len() < l)
+                {
+                    return self.decompress_lzx_or_stored(compressed_payload);
+                }
+
+                self.decompress_lzx(compressed_payload)
+            }
+            None => unreachable!(), // cache files don't go through here
+        }
+    }
+
+    #[cfg(feature = "lzx")]
+    fn decompress_lzx(&mut self, compressed_payload: Vec<u8>) -> Result<Vec<u8>> {
+        if !self.silent {
+            println!("Payload read, decompressing LZX blob...");
+        }
+        let decoded = lzx_decode_partial(
+            &compressed_payload,
+            self.cancellation_token.as_ref(),
+            self.resource_limits.max_inflated_size,
+        )?;
+        self.accept_partial(decoded, ErrorKind::Lzx)
+    }
+
+    /// [`Self::decompress_lzx`], falling back to `compressed_payload` being
+    /// a truncated stored blob if nothing can be decompressed from it.
+    #[cfg(feature = "lzx")]
+    fn decompress_lzx_or_stored(&mut self, compressed_payload: Vec<u8>) -> Result<Vec<u8>> {
+        let decoded = lzx_decode_partial(
+            &compressed_payload,
+            self.cancellation_token.as_ref(),
+            self.resource_limits.max_inflated_size,
+        )?;
+        if decoded.0.is_empty() {
+            return self.accept_stored(compressed_payload);
+        }
+
+        if !self.silent {
+            println!("Payload read, decompressing LZX blob...");
+        }
+        self.accept_partial(decoded, ErrorKind::Lzx)
+    }
+
+    #[cfg(not(feature = "lzx"))]
+    fn decompress_lzx_or_stored(&mut self, compressed_payload: Vec<u8>) -> Result<Vec<u8>> {
+        self.accept_stored(compressed_payload)
+    }
+
+    #[cfg(not(feature = "lzx"))]
+    fn decompress_lzx(&mut self, _compressed_payload: Vec<u8>) -> Result<Vec<u8>> {
+        if !self.silent {
+            println!(
+                "Error: blob isn't stored uncompressed, and LZX-compressed blobs need the \
+                 lzx feature."
+            );
+        }
+        Err(Error::new_with_offset(
+            file_line_col!(),
+            4,
+            ErrorKind::UnsupportedCompression(CompressionMethod::Uncompressed.as_byte()),
+        ))
+    }
+
+    /// Returns the decompressed payload, or the error (wrapped with `kind`)
+    /// if there was one and what was recovered before it can't be used.
+    fn accept_partial(
+        &mut self,
+        (decompressed_payload, e): (Vec<u8>, Option<String>),
+        kind: fn(String) -> ErrorKind,
+    ) -> Result<Vec<u8>> {
+        if let Some(e) = e {
+            if !self.allow_truncated || decompressed_payload.is_empty() {
+                return Err(Error::new_with_offset(file_line_col!(), 0, kind(e)));
+            }
+
+            if !self.silent {
+                println!(
+                    "Warning: payload couldn't be fully decompressed ({e}), continuing with \
+                     the {} bytes that were recovered.",
+                    decompressed_payload.len()
+                );
+            }
+            self.truncated = true;
+        }
+
+        Ok(decompressed_payload)
+    }
+
+    /// How long `payload` would be if it were an uncompressed blob, going by
+    /// the size in the [`XFile`] at its start.
+    fn stored_len(&self, payload: &[u8]) -> Option<usize> {
+        let xfile = self.opts.deserialize_from::<XFile>(payload).ok()?;
+        Some(xfile.size as usize + size_of!(XFile))
+    }
+
+    /// Takes `payload` as an uncompressed blob.
+    fn accept_stored(&mut self, payload: Vec<u8>) -> Result<Vec<u8>> {
+        check_resource_limit(
+            ResourceLimit::InflatedSize,
+            self.resource_limits.max_inflated_size,
+            payload.len() as _,
+            0,
+        )?;
+        if !self.silent {
+            println!("Payload read, blob is stored uncompressed.");
+        }
+        Ok(payload)
+    }
+
+    fn endian_mismatch(&self, offset: usize) -> Error {
+        if !self.silent {
+            println!(
+                "Error: the blob's headers only make sense with the other endianness. \
+                 The Fastfile is probably for a {} platform, not {}.",
+                if self.platform.is_le() {
+                    "big-endian"
+                } else {
+                    "little-endian"
+                },
+                self.platform
+            );
+        }
+
+        Error::new_with_offset(
+            file_line_col!(),
+            offset as _,
+            ErrorKind::ProbableEndianMismatch(self.platform),
+        )
+    }
+
     pub fn inflate(mut self) -> Result<T5XFileDeserializer<'a, T5XFileDeserializerInflated>> {
         assert!(self.reader.is_none());
+        check_token(self.cancellation_token.as_ref())?;
 
         let reader = if let Some(f) = self.cache_file.take() {
+            // Reading one byte past the limit is enough to tell it's over.
+            let max_len = self.resource_limits.max_inflated_size;
             let mut decompressed_payload = Vec::new();
-            f.read_to_end(&mut decompressed_payload)
+            f.take(max_len.map_or(u64::MAX, |l| l.saturating_add(1)))
+                .read_to_end(&mut decompressed_payload)
                 .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+            check_resource_limit(
+                ResourceLimit::InflatedSize,
+                max_len,
+                decompressed_payload.len() as _,
+                0,
+            )?;
             Cursor::new(decompressed_payload)
         } else if let Some(f) = self.file.take() {
             let mut compressed_payload = Vec::new();
@@ -393,11 +1008,7 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             let bytes_read = f
                 .read_to_end(&mut compressed_payload)
                 .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
-            if !self.silent {
-                println!("Payload read, inflating... (this may take a while)");
-            }
-            let decompressed_payload = inflate::inflate_bytes_zlib(&compressed_payload)
-                .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Inflate(e)))?;
+            let decompressed_payload = self.decompress_payload(compressed_payload)?;
             if !self.silent {
                 println!(
                     "Payload inflated, compressed size: {} bytes, decompressed size: {} bytes",
@@ -414,30 +1025,113 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
 
         let xasset_list = {
             let mut file = self.reader.as_mut().unwrap();
+            let recovered = file.get_ref().len();
             let xfile = self
                 .opts
                 .deserialize_from::<XFile>(&mut file)
-                .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Bincode(e)))?;
+                .map_err(|e| {
+                    Error::new_with_offset(
+                        file_line_col!(),
+                        0,
+                        bincode_error_kind(e, size_of!(XFile) as _, recovered as _),
+                    )
+                })?;
 
             dbg!(xfile);
             //dbg!(StreamLen::stream_len(&mut file)?);
             self.xfile = xfile;
 
+            if !self
+                .header_limits
+                .xfile_is_plausible(&xfile, recovered, self.allow_truncated)
+            {
+                let swapped = BincodeOptions::new(!self.platform.is_le())
+                    .deserialize_from::<XFile>(&file.get_ref()[..]);
+                if swapped.is_ok_and(|x| {
+                    self.header_limits
+                        .xfile_is_plausible(&x, recovered, self.allow_truncated)
+                }) {
+                    return Err(self.endian_mismatch(0));
+                }
+            }
+
+            // `XFile::size` doesn't include the `XFile` itself
+            let expected = xfile.size as usize + size_of!(XFile);
+            if recovered < expected {
+                if !self.allow_truncated {
+                    if !self.silent {
+                        println!(
+                            "Error: payload is truncated (expected {expected} bytes, got \
+                             {recovered})."
+                        );
+                    }
+                    return Err(Error::new_with_offset(
+                        file_line_col!(),
+                        0,
+                        ErrorKind::TruncatedBlob {
+                            recovered,
+                            expected,
+                        },
+                    ));
+                }
+
+                if !self.silent {
+                    println!(
+                        "Warning: payload is truncated (expected {expected} bytes, got \
+                         {recovered}). Only the assets that fit will be deserialized."
+                    );
+                }
+                self.truncated = true;
+            }
+
             // dbg!(file.stream_position().map_err(|e| Error::new(
             //     file_line_col!(),
             //     0,
             //     ErrorKind::Io(e)
             // ))?);
+            let available = recovered.saturating_sub(size_of!(XFile));
             let xasset_list = self
                 .opts
                 .deserialize_from::<XAssetListRaw>(&mut file)
-                .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Bincode(e)))?;
+                .map_err(|e| {
+                    Error::new_with_offset(
+                        file_line_col!(),
+                        size_of!(XFile) as _,
+                        bincode_error_kind(e, size_of!(XAssetListRaw) as _, available as _),
+                    )
+                })?;
             //dbg!(&xasset_list);
             // dbg!(file.stream_position().map_err(|e| Error::new(
             //     file_line_col!(),
             //     0,
             //     ErrorKind::Io(e)
             // ))?);
+
+            let available = available.saturating_sub(size_of!(XAssetListRaw));
+            if !xasset_list_is_plausible(&xasset_list, available, &self.resource_limits) {
+                let swapped = BincodeOptions::new(!self.platform.is_le())
+                    .deserialize_from::<XAssetListRaw>(&file.get_ref()[size_of!(XFile)..]);
+                if swapped
+                    .is_ok_and(|l| xasset_list_is_plausible(&l, available, &self.resource_limits))
+                {
+                    return Err(self.endian_mismatch(size_of!(XFile)));
+                }
+            }
+
+            let off = size_of!(XFile) as u64;
+            check_resource_limit(
+                ResourceLimit::ScriptStrings,
+                self.resource_limits.max_script_strings.map(|l| l as _),
+                xasset_list.strings.size() as _,
+                off,
+            )?;
+            check_resource_limit(
+                ResourceLimit::Assets,
+                self.resource_limits.max_assets.map(|l| l as _),
+                xasset_list.assets.size() as _,
+                off,
+            )?;
+
             xasset_list
         };
 
@@ -455,10 +1149,26 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             xasset_list,
             xassets_raw: VecDeque::new(),
             deserialized_assets: self.deserialized_assets,
+            next_index: self.next_index,
             non_null_assets: self.non_null_assets,
+            spans: self.spans,
             opts: self.opts,
             platform: self.platform,
             d3d9_state: self.d3d9_state,
+            gpu_uploader: self.gpu_uploader,
+            allow_truncated: self.allow_truncated,
+            header_limits: self.header_limits,
+            resource_limits: self.resource_limits,
+            allocated_bytes: self.allocated_bytes,
+            heap_bytes: self.heap_bytes,
+            string_interner: self.string_interner,
+            layout_overrides: self.layout_overrides,
+            verbose_errors: self.verbose_errors,
+            source: self.source,
+            cancellation_token: self.cancellation_token,
+            handlers: self.handlers,
+            truncated: self.truncated,
+            compression: self.compression,
             _p: PhantomData,
         };
 
@@ -467,6 +1177,12 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
 }
 
 impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerInflated> {
+    /// The whole inflated blob, starting with the [`XFile`]. Useful when the
+    /// asset list can't be trusted; see [`crate::recover`].
+    pub fn blob(&self) -> &[u8] {
+        self.reader.as_ref().unwrap().get_ref()
+    }
+
     pub fn cache(
         mut self,
         path: impl AsRef<Path>,
@@ -503,10 +1219,26 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerInflated> {
             xasset_list: self.xasset_list,
             xassets_raw: self.xassets_raw,
             deserialized_assets: self.deserialized_assets,
+            next_index: self.next_index,
             non_null_assets: self.non_null_assets,
+            spans: self.spans,
             opts: self.opts,
             platform: self.platform,
             d3d9_state: self.d3d9_state,
+            gpu_uploader: self.gpu_uploader,
+            allow_truncated: self.allow_truncated,
+            header_limits: self.header_limits,
+            resource_limits: self.resource_limits,
+            allocated_bytes: self.allocated_bytes,
+            heap_bytes: self.heap_bytes,
+            string_interner: self.string_interner,
+            layout_overrides: self.layout_overrides,
+            verbose_errors: self.verbose_errors,
+            source: self.source,
+            cancellation_token: self.cancellation_token,
+            handlers: self.handlers,
+            truncated: self.truncated,
+            compression: self.compression,
             _p: PhantomData,
         };
 
@@ -530,10 +1262,26 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerInflated> {
             xasset_list: self.xasset_list,
             xassets_raw: self.xassets_raw,
             deserialized_assets: self.deserialized_assets,
+            next_index: self.next_index,
             non_null_assets: self.non_null_assets,
+            spans: self.spans,
             opts: self.opts,
             platform: self.platform,
             d3d9_state: self.d3d9_state,
+            gpu_uploader: self.gpu_uploader,
+            allow_truncated: self.allow_truncated,
+            header_limits: self.header_limits,
+            resource_limits: self.resource_limits,
+            allocated_bytes: self.allocated_bytes,
+            heap_bytes: self.heap_bytes,
+            string_interner: self.string_interner,
+            layout_overrides: self.layout_overrides,
+            verbose_errors: self.verbose_errors,
+            source: self.source,
+            cancellation_token: self.cancellation_token,
+            handlers: self.handlers,
+            truncated: self.truncated,
+            compression: self.compression,
             _p: PhantomData,
         };
 
@@ -544,12 +1292,52 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerInflated> {
 }
 
 impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeserialize> {
+    /// Registers `handler` to read assets of type `asset_type`, replacing
+    /// any handler already registered for it. Such assets come back as
+    /// [`XAssetGeneric::Custom`]. See [`t5_xfile_defs::custom`].
+    ///
+    /// Handlers are only used for types that would otherwise fail with
+    /// [`ErrorKind::UnusedXAssetType`] or [`ErrorKind::InvalidXAssetType`],
+    /// and only for assets whose data is inline. Registering one for a type
+    /// T5 uses does nothing.
+    pub fn register_handler(&mut self, asset_type: u32, handler: Box<dyn AssetHandler>) {
+        self.handlers.insert(asset_type, handler);
+    }
+
     pub fn deserialize_next(&mut self) -> Result<Option<XAsset>> {
+        self.deserialize_next_loaded().map(|a| a.map(|a| a.asset))
+    }
+
+    /// Like [`Self::deserialize_next`], but also returns where the asset
+    /// came from.
+    pub fn deserialize_next_loaded(&mut self) -> Result<Option<LoadedAsset>> {
         let Some(asset) = self.xassets_raw.pop_front() else {
             return Ok(None);
         };
 
-        let asset = XAsset::try_get(self, asset, self.platform);
+        let index = self.next_index;
+        let start = self.stream_pos()?;
+        let asset_type = asset.asset_type;
+        if let Err(e) = self.check_cancelled() {
+            self.xassets_raw.push_front(asset);
+            return Err(e);
+        }
+
+        let xasset_raw = asset;
+        let asset = self.get_asset(asset);
+        match &asset {
+            Err(e) if matches!(e.kind(), ErrorKind::Cancelled) => {
+                // Rewind, so that the asset can be deserialized again.
+                self.seek_to(start)?;
+                self.xassets_raw.push_front(xasset_raw);
+            }
+            Err(_) => {
+                self.next_index += 1;
+                self.spans.push(None);
+            }
+            Ok(_) => self.next_index += 1,
+        }
+        let asset = asset.map_err(|e| self.add_error_context(e, asset_type, index));
         //dbg!(&asset);
         if let Ok(ref a) = asset {
             self.deserialized_assets += 1;
@@ -571,7 +1359,137 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeserialize> {
             }
         }
 
-        asset.map(Some)
+        let asset = asset?;
+        let span = (start, self.stream_pos()?);
+        self.spans.push(Some(span));
+        Ok(Some(LoadedAsset {
+            asset,
+            provenance: Provenance {
+                source: self.source.clone(),
+                index,
+                span,
+                platform: self.platform,
+            },
+        }))
+    }
+
+    /// Deserializes the asset `xasset_raw` describes, using a registered
+    /// handler if the library can't read its type.
+    fn get_asset(&mut self, xasset_raw: XAssetRaw<'a>) -> Result<XAsset> {
+        let asset_type = xasset_raw.asset_type;
+        let built_in = XAssetType::from_raw(asset_type).is_some_and(|t| t.is_used_by_t5());
+        if built_in || xasset_raw.asset_data.is_real() || !self.handlers.contains_key(&asset_type) {
+            return XAsset::try_get(self, xasset_raw, self.platform);
+        }
+
+        // The handler needs `self` mutably, so it can't stay in the map.
+        let handlers = core::mem::take(&mut self.handlers);
+        let result = handlers[&asset_type].deserialize(self as &mut dyn custom::AssetReader);
+        self.handlers = handlers;
+
+        let (name, data) = result?;
+        let (type_id, data) = (asset_type, data.into());
+        Ok(if self.platform.is_pc() {
+            XAsset::PC(XAssetGeneric::Custom {
+                type_id,
+                name,
+                data,
+            })
+        } else {
+            XAsset::Console(XAssetGeneric::Custom {
+                type_id,
+                name,
+                data,
+            })
+        })
+    }
+
+    /// Returns `len` bytes of the blob starting at `off`, without moving the
+    /// stream.
+    ///
+    /// Returns [`ErrorKind::InvalidSeek`] if any of the range is past the
+    /// end of the blob.
+    pub fn read_at(&self, off: u64, len: usize) -> Result<Vec<u8>> {
+        let blob = self.reader.as_ref().unwrap().get_ref();
+        let end = off.saturating_add(len as u64);
+        if end > blob.len() as u64 {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                off.min(u32::MAX as _) as _,
+                ErrorKind::InvalidSeek {
+                    off: end.min(u32::MAX as _) as _,
+                    max: blob.len() as _,
+                },
+            ));
+        }
+
+        Ok(blob[off as usize..end as usize].to_vec())
+    }
+
+    /// Like [`Self::read_at`], but formats the bytes as a hexdump with an
+    /// ASCII column, 16 bytes to a line.
+    pub fn hexdump_at(&self, off: u64, len: usize) -> Result<String> {
+        let bytes = self.read_at(off, len)?;
+        Ok(ErrorExcerpt {
+            start: off as _,
+            bytes,
+        }
+        .to_string())
+    }
+
+    /// The blob span of the `index`th asset, if it's been deserialized
+    /// without errors.
+    pub fn asset_span(&self, index: usize) -> Option<(u64, u64)> {
+        self.spans.get(index).copied().flatten()
+    }
+
+    /// The bytes the `index`th asset was deserialized from.
+    ///
+    /// Returns [`ErrorKind::NoAssetSpan`] if the asset hasn't been
+    /// deserialized without errors, or [`ErrorKind::InvalidSeek`] if its span
+    /// is past the end of the blob.
+    pub fn asset_bytes(&self, index: usize) -> Result<Vec<u8>> {
+        let (start, end) = self
+            .asset_span(index)
+            .ok_or_else(|| Error::new(file_line_col!(), ErrorKind::NoAssetSpan(index)))?;
+        self.read_at(start, (end - start) as _)
+    }
+
+    /// Attaches the asset that was being deserialized to `e`, along with an
+    /// [`ErrorExcerpt`] if [`Self::verbose_errors`] is set.
+    fn add_error_context(&mut self, e: Error, asset_type: u32, index: usize) -> Error {
+        let e = match XAssetType::from_raw(asset_type) {
+            Some(asset_type) => e.with_asset(ErrorAsset {
+                asset_type,
+                index,
+                count: self.xasset_list.assets.size(),
+            }),
+            None => e,
+        };
+
+        if !self.verbose_errors {
+            return e;
+        }
+
+        let reader = self.reader.as_ref().unwrap();
+        let blob = reader.get_ref();
+        let off = e.off().map_or(reader.position() as usize, |o| o as usize);
+        let start = off.saturating_sub(ERROR_EXCERPT_LEN / 2).min(blob.len());
+        let end = (start + ERROR_EXCERPT_LEN).min(blob.len());
+        let excerpt = ErrorExcerpt {
+            start: start as _,
+            bytes: blob[start..end].to_vec(),
+        };
+
+        e.with_excerpt(excerpt)
+    }
+
+    /// The number of bytes in the blob past the current position. Once every
+    /// asset has been deserialized, anything nonzero means some asset was
+    /// read short.
+    pub fn trailing_bytes(&self) -> u64 {
+        let reader = self.reader.as_ref().unwrap();
+        (reader.get_ref().len() as u64).saturating_sub(reader.position())
     }
 
     pub fn deserialize_remaining(mut self) -> Result<Vec<XAsset>> {
@@ -584,14 +1502,352 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeserialize> {
         Ok(deserialized_assets)
     }
 
+    /// Like [`Self::deserialize_remaining`], but also returns where each
+    /// asset came from.
+    pub fn deserialize_remaining_loaded(mut self) -> Result<Vec<LoadedAsset>> {
+        let mut deserialized_assets = Vec::new();
+
+        while let Some(asset) = self.deserialize_next_loaded()? {
+            deserialized_assets.push(asset);
+        }
+
+        Ok(deserialized_assets)
+    }
+
+    /// Like [`Self::deserialize_remaining`], except that if the end of the
+    /// blob is reached partway through an asset, deserialization stops
+    /// cleanly and every asset deserialized before it is returned.
+    ///
+    /// Only [`ErrorKind::UnexpectedEof`] is treated this way, plus
+    /// [`ErrorKind::InvalidSeek`] if the blob is shorter than its header
+    /// says (see [`Self::truncated`]); any other error is still returned as
+    /// an [`Err`]. An array running past the end of a blob of the right
+    /// length is a corrupted count, not truncation.
+    pub fn deserialize_remaining_partial(mut self) -> Result<PartialResult> {
+        let mut assets = Vec::new();
+        let mut cut_asset = None;
+
+        loop {
+            let next = self.xassets_raw.front().map(|a| a.asset_type);
+            let index = self.next_index;
+            match self.deserialize_next() {
+                Ok(Some(asset)) => assets.push(asset),
+                Ok(None) => break,
+                Err(e) if self.is_truncation(&e) => {
+                    if !self.silent {
+                        println!(
+                            "Warning: asset {index} straddles the end of the payload, stopping."
+                        );
+                    }
+                    self.truncated = true;
+                    cut_asset = next.map(|asset_type| TruncatedAsset {
+                        index,
+                        asset_type: XAssetType::from_raw(asset_type),
+                        error: e,
+                    });
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(PartialResult {
+            assets,
+            truncated: self.truncated,
+            cut_asset,
+        })
+    }
+
+    /// Deserializes the remaining assets, carrying on past assets that fail
+    /// instead of stopping at the first one. Meant for debugging asset
+    /// layouts, where it shows several mistakes per run instead of one.
+    ///
+    /// After an asset fails, the blob is scanned forward from where the
+    /// asset started for the next one. Offsets where the next asset's first
+    /// field is a null or "follows" pointer (as it is for nearly every
+    /// asset type) are tried in order, and the first one the asset
+    /// deserializes from without errors is taken to be its start. This is
+    /// only a guess, so errors after the first may be knock-on effects of a
+    /// bad guess rather than real problems.
+    ///
+    /// Scanning is bounded by `limits`. Every trial deserialization is
+    /// silent, doesn't create any GPU resources, and is rewound afterwards.
+    pub fn survey(mut self, limits: SurveyLimits) -> Result<SurveyResult> {
+        let mut assets = Vec::new();
+        let mut errors = Vec::new();
+        let mut stopped_early = false;
+        let mut index = 0;
+
+        loop {
+            let start = self.stream_pos()?;
+            let asset_type = self.xassets_raw.front().map(|a| a.asset_type);
+
+            let error = match catch_unwind(AssertUnwindSafe(|| self.deserialize_next())) {
+                Ok(Ok(Some(asset))) => {
+                    assets.push(asset);
+                    index += 1;
+                    continue;
+                }
+                Ok(Ok(None)) => break,
+                Ok(Err(e)) if matches!(e.kind(), ErrorKind::Cancelled) => return Err(e),
+                Ok(Err(e)) => e,
+                Err(p) => Error::new_with_offset(
+                    file_line_col!(),
+                    start as _,
+                    ErrorKind::BrokenInvariant(format!("panicked: {}", panic_message(p))),
+                ),
+            };
+
+            let skipped_bytes = if self.xassets_raw.iter().all(|a| a.asset_data.is_null()) {
+                // Nothing after this asset takes up any space, so it must
+                // have taken up the rest of the blob.
+                Some(self.stream_len()?.saturating_sub(start))
+            } else if errors.len() + 1 < limits.max_errors {
+                self.realign(start, limits)?
+            } else {
+                None
+            };
+            check_token(self.cancellation_token.as_ref())?;
+
+            if !self.silent {
+                println!(
+                    "Warning: asset {index} failed ({error:?}), {}.",
+                    skipped_bytes.map_or("couldn't find the next asset".to_string(), |n| format!(
+                        "skipping {n} bytes"
+                    ))
+                );
+            }
+
+            errors.push(SurveyError {
+                index,
+                asset_type: asset_type.and_then(XAssetType::from_raw),
+                error,
+                skipped_bytes,
+            });
+            index += 1;
+
+            if skipped_bytes.is_none() {
+                stopped_early = true;
+                break;
+            }
+        }
+
+        Ok(SurveyResult {
+            assets,
+            errors,
+            stopped_early,
+        })
+    }
+
+    /// Looks for the start of the next non-null asset after the asset at
+    /// `start` failed. On success, the stream is left at the next asset and
+    /// the number of bytes skipped is returned.
+    fn realign(&mut self, start: u64, limits: SurveyLimits) -> Result<Option<u64>> {
+        let blob = self.reader.as_ref().unwrap().get_ref();
+        let end = (start + limits.max_scan_bytes).min(blob.len() as u64) as usize;
+
+        // Both values read the same in either endianness.
+        let candidates = (start as usize + 1..end.saturating_sub(3))
+            .filter(|&pos| matches!(blob[pos..pos + 4], [0, 0, 0, 0] | [0xFF, 0xFF, 0xFF, 0xFF]))
+            .take(limits.max_candidates)
+            .collect::<Vec<_>>();
+
+        // Null assets don't take up any space, so they'd "succeed" at any
+        // offset. The first non-null asset is the one that has to fit.
+        let skipped_nulls = self
+            .xassets_raw
+            .iter()
+            .take_while(|a| a.asset_data.is_null())
+            .count();
+        let target = self.xassets_raw[skipped_nulls];
+
+        let silent = self.silent;
+        let gpu_uploader = self.gpu_uploader.take();
+        let d3d9_state = self.d3d9_state.take();
+        self.silent = true;
+
+        let mut found = None;
+        for pos in candidates {
+            if check_token(self.cancellation_token.as_ref()).is_err() {
+                break;
+            }
+            self.seek_to(pos as _)?;
+            let fits =
+                catch_unwind(AssertUnwindSafe(|| self.get_asset(target))).is_ok_and(|r| r.is_ok());
+            if fits {
+                found = Some(pos as u64);
+                break;
+            }
+        }
+
+        self.silent = silent;
+        self.gpu_uploader = gpu_uploader;
+        self.d3d9_state = d3d9_state;
+
+        match found {
+            Some(pos) => {
+                self.seek_to(pos)?;
+                Ok(Some(pos - start))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Saves the current position, for exploratory parsing: take a
+    /// checkpoint, try reading something, and [`Self::restore`] it if that
+    /// didn't work out.
+    ///
+    /// Besides the stream position, the checkpoint records the number of
+    /// script strings and which assets are still queued, so restoring it
+    /// also undoes any [`Self::deserialize_next`] calls made since.
+    pub fn checkpoint(&mut self) -> Result<DeserializeCheckpoint<'a>> {
+        Ok(DeserializeCheckpoint {
+            pos: self.stream_pos()?,
+            script_string_count: self.script_strings.len(),
+            xassets_raw: self.xassets_raw.clone(),
+            deserialized_assets: self.deserialized_assets,
+            next_index: self.next_index,
+            non_null_assets: self.non_null_assets,
+            allocated_bytes: self.allocated_bytes,
+        })
+    }
+
+    /// Rewinds to `checkpoint`, dropping any script strings added since.
+    pub fn restore(&mut self, checkpoint: DeserializeCheckpoint<'a>) -> Result<()> {
+        self.seek_to(checkpoint.pos)?;
+        self.script_strings.truncate(checkpoint.script_string_count);
+        self.xassets_raw = checkpoint.xassets_raw;
+        self.deserialized_assets = checkpoint.deserialized_assets;
+        self.next_index = checkpoint.next_index;
+        self.non_null_assets = checkpoint.non_null_assets;
+        self.allocated_bytes = checkpoint.allocated_bytes;
+        self.spans.truncate(checkpoint.next_index);
+        Ok(())
+    }
+
+    /// Reads a `T` without advancing the stream. The position is restored
+    /// whether or not the read succeeds.
+    pub fn peek<T: DeserializeOwned>(&mut self) -> Result<T> {
+        let pos = self.stream_pos()?;
+        let t = self.load_from_xfile();
+        self.seek_to(pos)?;
+        t
+    }
+
+    fn seek_to(&mut self, pos: u64) -> Result<()> {
+        self.reader
+            .as_mut()
+            .unwrap()
+            .seek(std::io::SeekFrom::Start(pos))
+            .map(|_| ())
+            .map_err(|e| Error::new_with_offset(file_line_col!(), pos as _, ErrorKind::Io(e)))
+    }
+
+    /// Measures the size of each remaining asset without consuming them.
+    ///
+    /// This is a full pass, not a skim: the blob can't be walked without
+    /// following every asset's pointers, so each asset is deserialized,
+    /// measured, and dropped before moving on to the next. It takes about as
+    /// long as deserializing the rest of the fastfile, but peak memory is
+    /// only that of the largest asset. The deserializer is rewound
+    /// afterwards, so [`Self::deserialize_next`] picks up from where it was
+    /// before the call.
+    ///
+    /// [`AssetSizeEstimate::memory_bytes`] is the asset's stack size plus
+    /// what was allocated for it as it was deserialized: every array and
+    /// boxed value at its converted size, and every string that wasn't
+    /// already interned earlier in the same call. It doesn't include
+    /// allocator overhead or the interner's own bookkeeping, which is why
+    /// it's still an estimate.
+    pub fn measure_sizes(&mut self) -> Result<Vec<AssetSizeEstimate>> {
+        let checkpoint = self.checkpoint()?;
+        let silent = self.silent;
+        // Estimating shouldn't create any GPU resources.
+        let gpu_uploader = self.gpu_uploader.take();
+        let d3d9_state = self.d3d9_state.take();
+        // Nor intern strings that the real pass would then get for free.
+        let string_interner = self
+            .string_interner
+            .as_mut()
+            .map(|i| core::mem::replace(i, StringInterner::new()));
+
+        self.silent = true;
+        let estimates = self.try_measure_sizes();
+        self.silent = silent;
+        self.gpu_uploader = gpu_uploader;
+        self.d3d9_state = d3d9_state;
+        self.string_interner = string_interner;
+
+        self.restore(checkpoint)?;
+        estimates
+    }
+
+    fn try_measure_sizes(&mut self) -> Result<Vec<AssetSizeEstimate>> {
+        let mut estimates = Vec::new();
+
+        loop {
+            let index = self.next_index;
+            let start = self.stream_pos()?;
+            let heap_bytes = self.heap_bytes;
+            let Some(asset) = self.deserialize_next()? else {
+                break;
+            };
+            let end = self.stream_pos()?;
+
+            let memory_bytes = self.heap_bytes - heap_bytes + size_of!(XAsset) as u64;
+
+            estimates.push(AssetSizeEstimate {
+                index,
+                asset_type: asset.asset_type(),
+                raw_asset_type: asset.raw_asset_type(),
+                name: asset.name().map(ToString::to_string),
+                blob_bytes: end - start,
+                memory_bytes,
+            });
+        }
+
+        Ok(estimates)
+    }
+
+    /// Whether the payload was found to be truncated. Only ever `true` if
+    /// [`T5XFileDeserializerBuilder::with_allow_truncated`] was set.
+    pub const fn truncated(&self) -> bool {
+        self.truncated
+    }
+
     fn get_script_strings_and_assets(&mut self) -> Result<()> {
+        match self.try_get_script_strings_and_assets() {
+            Err(e) if self.allow_truncated && self.is_truncation(&e) => {
+                if !self.silent {
+                    println!("Warning: payload ends inside the string or asset table.");
+                }
+                self.truncated = true;
+                Ok(())
+            }
+            r => r,
+        }
+    }
+
+    /// Whether `e` is what running into the end of a truncated blob looks
+    /// like. An array that doesn't fit is [`ErrorKind::InvalidSeek`], which
+    /// is also what a corrupted count gives, so it only counts if the blob
+    /// is known to be short.
+    fn is_truncation(&self, e: &Error) -> bool {
+        match e.kind() {
+            ErrorKind::UnexpectedEof { .. } => true,
+            ErrorKind::InvalidSeek { .. } => self.truncated,
+            _ => false,
+        }
+    }
+
+    fn try_get_script_strings_and_assets(&mut self) -> Result<()> {
         let xasset_list = self.xasset_list;
 
         self.script_strings = xasset_list
             .strings
             .to_vec(self)?
             .into_iter()
-            .map(|s| s.xfile_deserialize_into(self, ()).map(|s| s.0))
+            .map(|s| s.xfile_deserialize_into(self, ()).map(|s| s.to_string()))
             .collect::<Result<Vec<_>>>()?;
         //dbg!(&strings);
 
@@ -600,16 +1856,6 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeserialize> {
 
         Ok(())
     }
-
-    #[allow(dead_code)]
-    pub(crate) const fn create_d3d9(&self) -> bool {
-        self.d3d9_state.is_some()
-    }
-
-    #[allow(dead_code)]
-    pub(crate) fn d3d9_state(&mut self) -> Option<&mut D3D9State<'a>> {
-        self.d3d9_state.as_mut()
-    }
 }
 
 impl<'a> T5XFileDeserialize for T5XFileDeserializer<'a> {
@@ -627,13 +1873,15 @@ impl<'a> T5XFileDeserialize for T5XFileDeserializer<'a> {
 
     fn load_from_xfile<T: DeserializeOwned>(&mut self) -> Result<T> {
         // FIXME: unwrap
+        let pos = self.stream_pos()?;
+        let available = self.stream_len()?.saturating_sub(pos);
         self.opts
             .deserialize_from(self.reader.as_mut().unwrap())
             .map_err(|e| {
                 Error::new_with_offset(
                     file_line_col!(),
-                    self.stream_pos().unwrap() as _,
-                    ErrorKind::Bincode(e),
+                    pos as _,
+                    bincode_error_kind(e, size_of!(T) as _, available),
                 )
             })
     }
@@ -644,4 +1892,239 @@ impl<'a> T5XFileDeserialize for T5XFileDeserializer<'a> {
             .get(string.as_u16() as usize)
             .map(|s| &**s))
     }
+
+    fn gpu_uploader(&mut self) -> Option<&mut dyn GpuUploader> {
+        match &mut self.gpu_uploader {
+            Some(u) => Some(&mut **u),
+            None => None,
+        }
+    }
+
+    #[cfg(feature = "d3d9")]
+    fn d3d9_device(&mut self) -> Option<&IDirect3DDevice9> {
+        self.d3d9_state.as_ref().map(|s| &*s.device)
+    }
+
+    fn layout_overrides(&self) -> LayoutOverrides {
+        self.layout_overrides
+    }
+
+    fn cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
+    fn resource_limits(&self) -> ResourceLimits {
+        // No array can have more elements than the blob has bytes.
+        let blob_len = self
+            .reader
+            .as_ref()
+            .map_or(usize::MAX, |r| r.get_ref().len());
+        ResourceLimits {
+            max_array_len: self.resource_limits.max_array_len.map(|l| l.min(blob_len)),
+            ..self.resource_limits
+        }
+    }
+
+    fn track_allocated_bytes(&mut self, bytes: u64) -> u64 {
+        self.allocated_bytes = self.allocated_bytes.saturating_add(bytes);
+        self.allocated_bytes
+    }
+
+    fn track_heap_bytes(&mut self, bytes: u64) {
+        self.heap_bytes = self.heap_bytes.saturating_add(bytes);
+    }
+
+    fn intern_string(&mut self, s: &str) -> XString {
+        let Some(interner) = &mut self.string_interner else {
+            self.track_heap_bytes(XString::heap_size(s) as _);
+            return XString::from(s);
+        };
+
+        // Only the first copy of a string allocates.
+        let len = interner.len();
+        let interned = interner.intern(s);
+        if interner.len() > len {
+            self.track_heap_bytes(XString::heap_size(s) as _);
+        }
+        interned
+    }
+}
+
+fn panic_message(p: Box<dyn core::any::Any + Send>) -> String {
+    p.downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| p.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<non-string panic payload>".to_string())
+}
+
+/// Classifies a [`bincode`] error, turning EOFs into
+/// [`ErrorKind::UnexpectedEof`].
+fn bincode_error_kind(e: bincode::Error, needed: u64, available: u64) -> ErrorKind {
+    match *e {
+        bincode::ErrorKind::Io(ref io) if io.kind() == std::io::ErrorKind::UnexpectedEof => {
+            ErrorKind::UnexpectedEof { needed, available }
+        }
+        _ => ErrorKind::Bincode(e),
+    }
+}
+
+/// Returns [`ErrorKind::Cancelled`] if `token` has been cancelled.
+fn check_token(token: Option<&CancellationToken>) -> Result<()> {
+    if token.is_some_and(CancellationToken::is_cancelled) {
+        return Err(Error::new_with_offset(
+            file_line_col!(),
+            0,
+            ErrorKind::Cancelled,
+        ));
+    }
+    Ok(())
+}
+
+/// Returns [`ErrorKind::ResourceLimit`] if `requested` is over `limit`.
+/// `off` is the blob offset to report.
+fn check_resource_limit(
+    which: ResourceLimit,
+    limit: Option<u64>,
+    requested: u64,
+    off: u64,
+) -> Result<()> {
+    match limit {
+        Some(limit) if requested > limit => Err(Error::new_with_offset(
+            file_line_col!(),
+            off as _,
+            ErrorKind::ResourceLimit {
+                which,
+                limit,
+                requested,
+            },
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Inflates as much of `compressed` as possible. Unlike
+/// [`inflate::inflate_bytes_zlib`], the bytes inflated before an error are
+/// returned alongside it. `token` is checked between chunks, and so is
+/// `max_len`, so inflating stops soon after the output passes it.
+fn inflate_partial(
+    compressed: &[u8],
+    token: Option<&CancellationToken>,
+    max_len: Option<u64>,
+) -> Result<(Vec<u8>, Option<String>)> {
+    let mut stream = inflate::InflateStream::from_zlib();
+    let mut decompressed = Vec::new();
+    let mut n = 0;
+
+    loop {
+        check_token(token)?;
+        match stream.update(&compressed[n..]) {
+            Ok((_, [])) => break,
+            Ok((bytes_read, bytes)) => {
+                n += bytes_read;
+                decompressed.extend_from_slice(bytes);
+                check_resource_limit(
+                    ResourceLimit::InflatedSize,
+                    max_len,
+                    decompressed.len() as _,
+                    0,
+                )?;
+            }
+            Err(e) => return Ok((decompressed, Some(e))),
+        }
+    }
+
+    Ok((decompressed, None))
+}
+
+/// The LZX window size used by `XMemCompress`, which console Fastfiles are
+/// compressed with.
+#[cfg(feature = "lzx")]
+const LZX_WINDOW_SIZE: lzxd::WindowSize = lzxd::WindowSize::KB128;
+
+/// The uncompressed size of every `XMemCompress` frame but the last.
+#[cfg(feature = "lzx")]
+const LZX_FRAME_SIZE: usize = 0x8000;
+
+/// Like [`inflate_partial`], but for LZX. The blob is framed the way
+/// `XMemCompress` frames it: each chunk starts with its compressed size as
+/// a big-endian `u16`. A chunk that decompresses to less than
+/// [`LZX_FRAME_SIZE`] (i.e., the last one) is instead marked with `0xFF`,
+/// then its uncompressed and compressed sizes.
+#[cfg(feature = "lzx")]
+fn lzx_decode_partial(
+    compressed: &[u8],
+    token: Option<&CancellationToken>,
+    max_len: Option<u64>,
+) -> Result<(Vec<u8>, Option<String>)> {
+    let mut decoder = lzxd::Lzxd::new(LZX_WINDOW_SIZE);
+    let mut decompressed = Vec::new();
+    let mut rest = compressed;
+
+    while !rest.is_empty() {
+        check_token(token)?;
+
+        let (frame_size, chunk_size, header_len) = match *rest {
+            [0xFF, a, b, c, d, ..] => (
+                u16::from_be_bytes([a, b]) as usize,
+                u16::from_be_bytes([c, d]) as usize,
+                5,
+            ),
+            [a, b, ..] => (LZX_FRAME_SIZE, u16::from_be_bytes([a, b]) as usize, 2),
+            _ => return Ok((decompressed, Some("truncated chunk header".to_string()))),
+        };
+        if frame_size == 0 || chunk_size == 0 {
+            break;
+        }
+
+        let Some(chunk) = rest.get(header_len..header_len + chunk_size) else {
+            return Ok((decompressed, Some("truncated chunk".to_string())));
+        };
+        match decoder.decompress_next(chunk, frame_size) {
+            Ok(frame) => decompressed.extend_from_slice(frame),
+            Err(e) => return Ok((decompressed, Some(e.to_string()))),
+        }
+        check_resource_limit(
+            ResourceLimit::InflatedSize,
+            max_len,
+            decompressed.len() as _,
+            0,
+        )?;
+
+        rest = &rest[header_len + chunk_size..];
+    }
+
+    Ok((decompressed, None))
+}
+
+/// Like [`inflate_partial`], but for zstd.
+#[cfg(feature = "zstd")]
+fn zstd_decode_partial(
+    compressed: &[u8],
+    token: Option<&CancellationToken>,
+    max_len: Option<u64>,
+) -> Result<(Vec<u8>, Option<String>)> {
+    let mut decompressed = Vec::new();
+    let mut decoder = match zstd::stream::read::Decoder::new(compressed) {
+        Ok(decoder) => decoder,
+        Err(e) => return Ok((decompressed, Some(e.to_string()))),
+    };
+
+    let mut chunk = vec![0; 1 << 16];
+    loop {
+        check_token(token)?;
+        match decoder.read(&mut chunk) {
+            Ok(0) => return Ok((decompressed, None)),
+            Ok(n) => {
+                decompressed.extend_from_slice(&chunk[..n]);
+                check_resource_limit(
+                    ResourceLimit::InflatedSize,
+                    max_len,
+                    decompressed.len() as _,
+                    0,
+                )?;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Ok((decompressed, Some(e.to_string()))),
+        }
+    }
 }
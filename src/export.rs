@@ -0,0 +1,41 @@
+//! Formats for saving deserialized assets outside of a Fastfile.
+
+pub mod bin;
+
+#[cfg(feature = "json")]
+pub mod json;
+
+#[cfg(feature = "json")]
+pub use json::to_json_writer;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "rayon")]
+use t5_xfile_defs::Result;
+
+/// Maps `items` with `f` in parallel and hands the results to `write` in
+/// the items' order.
+///
+/// Items are taken a batch at a time, one per thread, and the batch is
+/// written before the next one is started, so only a batch's worth of
+/// results is ever held at once.
+#[cfg(feature = "rayon")]
+pub(crate) fn par_map_in_order<T: Sync, R: Send>(
+    items: impl IntoIterator<Item = T>,
+    f: impl Fn(&T) -> R + Sync,
+    mut write: impl FnMut(R) -> Result<()>,
+) -> Result<()> {
+    let mut items = items.into_iter();
+    loop {
+        let batch = items
+            .by_ref()
+            .take(rayon::current_num_threads())
+            .collect::<Vec<_>>();
+        if batch.is_empty() {
+            return Ok(());
+        }
+        for result in batch.par_iter().map(&f).collect::<Vec<_>>() {
+            write(result)?;
+        }
+    }
+}
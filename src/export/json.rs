@@ -0,0 +1,767 @@
+//! JSON export, for reading assets with tools other than this library.
+//!
+//! Assets are serialized one at a time straight into the writer, so memory
+//! use is bounded by the largest asset rather than the whole file. With the
+//! `rayon` feature, a batch of assets (one per thread) is serialized in
+//! parallel instead, and written in order before the next batch is
+//! started, so it's bounded by the batch. Either way the output is the
+//! same. The
+//! output is what `serde_json` would write, except that byte buffers (such
+//! as a [`RawFile`](t5_xfile_defs::misc::RawFile)'s contents) can be written
+//! as base64 or left out, according to [`JsonOptions::blobs`].
+//!
+//! [`LoadedAsset`]s can be written with their [`Provenance`], if
+//! [`JsonOptions::provenance`] is set. Each is then written the way
+//! `serde_json` would write the [`LoadedAsset`], rather than just the asset.
+
+use std::{
+    fmt::{self, Display},
+    io::{BufWriter, Write},
+};
+
+use serde::{
+    Serialize,
+    ser::{self, Impossible},
+};
+use serde_json::Value;
+use t5_xfile_defs::{
+    Error, ErrorKind, Result,
+    xasset::{LoadedAsset, Provenance, XAsset},
+};
+
+use crate::file_line_col;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum JsonLayout {
+    /// A single array holding every asset.
+    #[default]
+    Array,
+    /// One asset per line (newline-delimited JSON), which `jq` and the like
+    /// can process without reading the whole file.
+    Ndjson,
+}
+
+/// How sequences of bytes are written. An empty one is always written as
+/// `[]`, since there's nothing to tell it apart from any other sequence.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BlobEncoding {
+    /// An array of numbers, as `serde_json` writes them.
+    #[default]
+    Array,
+    /// A string of standard, padded base64.
+    Base64,
+    /// A string holding just the length, e.g. `"<1024 bytes>"`.
+    Stub,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    pub layout: JsonLayout,
+    pub blobs: BlobEncoding,
+    /// Writes each asset's [`Provenance`] alongside it. Only assets that
+    /// have one (see [`JsonAsset::provenance`]) are affected.
+    pub provenance: bool,
+}
+
+impl JsonOptions {
+    pub fn with_provenance(mut self, provenance: bool) -> Self {
+        self.provenance = provenance;
+        self
+    }
+}
+
+/// What [`to_json_writer`] can write: an asset, and maybe where it came
+/// from.
+pub trait JsonAsset {
+    fn asset(&self) -> &XAsset;
+
+    fn provenance(&self) -> Option<&Provenance> {
+        None
+    }
+}
+
+impl JsonAsset for XAsset {
+    fn asset(&self) -> &XAsset {
+        self
+    }
+}
+
+impl JsonAsset for LoadedAsset {
+    fn asset(&self) -> &XAsset {
+        &self.asset
+    }
+
+    fn provenance(&self) -> Option<&Provenance> {
+        Some(&self.provenance)
+    }
+}
+
+impl<T: JsonAsset + ?Sized> JsonAsset for &T {
+    fn asset(&self) -> &XAsset {
+        (**self).asset()
+    }
+
+    fn provenance(&self) -> Option<&Provenance> {
+        (**self).provenance()
+    }
+}
+
+/// Writes `assets` to `w` as JSON, flushing it after each asset.
+pub fn to_json_writer<A: JsonAsset + Sync>(
+    assets: impl IntoIterator<Item = A>,
+    w: impl Write,
+    options: JsonOptions,
+) -> Result<()> {
+    let io = |e| Error::new(file_line_col!(), ErrorKind::Io(e));
+
+    let mut ser = JsonSerializer {
+        w: BufWriter::new(w),
+        blobs: options.blobs,
+    };
+    let array = options.layout == JsonLayout::Array;
+    let before = |i| -> &[u8] {
+        match (array, i) {
+            (false, _) => b"",
+            (true, 0) => b"\n",
+            (true, _) => b",\n",
+        }
+    };
+    let after: &[u8] = if array { b"" } else { b"\n" };
+
+    if array {
+        ser.w.write_all(b"[").map_err(io)?;
+    }
+    #[cfg(not(feature = "rayon"))]
+    for (i, asset) in assets.into_iter().enumerate() {
+        ser.w.write_all(before(i)).map_err(io)?;
+        serialize_asset(&asset, options.provenance, &mut ser)?;
+        ser.w
+            .write_all(after)
+            .and_then(|_| ser.w.flush())
+            .map_err(io)?;
+    }
+    #[cfg(feature = "rayon")]
+    {
+        let mut i = 0;
+        crate::export::par_map_in_order(
+            assets,
+            |asset| {
+                let mut fragment = JsonSerializer {
+                    w: Vec::new(),
+                    blobs: options.blobs,
+                };
+                serialize_asset(asset, options.provenance, &mut fragment).map(|_| fragment.w)
+            },
+            |fragment| {
+                let fragment = fragment?;
+                ser.w.write_all(before(i)).map_err(io)?;
+                i += 1;
+                ser.w
+                    .write_all(&fragment)
+                    .and_then(|_| ser.w.write_all(after))
+                    .and_then(|_| ser.w.flush())
+                    .map_err(io)
+            },
+        )?;
+    }
+    if array {
+        ser.w.write_all(b"\n]\n").map_err(io)?;
+    }
+    ser.w.flush().map_err(io)
+}
+
+/// Laid out like a [`LoadedAsset`], without having to clone the asset into
+/// one.
+#[derive(Serialize)]
+struct LoadedAssetRef<'a> {
+    asset: &'a XAsset,
+    provenance: &'a Provenance,
+}
+
+fn serialize_asset<W: Write>(
+    asset: &impl JsonAsset,
+    provenance: bool,
+    ser: &mut JsonSerializer<W>,
+) -> Result<()> {
+    match asset.provenance().filter(|_| provenance) {
+        Some(provenance) => LoadedAssetRef {
+            asset: asset.asset(),
+            provenance,
+        }
+        .serialize(ser),
+        None => asset.asset().serialize(ser),
+    }
+    .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e.into())))
+}
+
+/// Writes compact JSON directly to `w`. Scalars are handed to `serde_json`,
+/// so they're escaped and formatted the same.
+struct JsonSerializer<W> {
+    w: W,
+    blobs: BlobEncoding,
+}
+
+impl<W: Write> JsonSerializer<W> {
+    fn raw(&mut self, s: &[u8]) -> serde_json::Result<()> {
+        self.w.write_all(s).map_err(serde_json::Error::io)
+    }
+
+    fn scalar<T: Serialize + ?Sized>(&mut self, v: &T) -> serde_json::Result<()> {
+        serde_json::to_writer(&mut self.w, v)
+    }
+
+    /// Writes `key` and the colon after it. Like `serde_json`, numbers and
+    /// bools are accepted as keys, and quoted.
+    fn key<T: Serialize + ?Sized>(&mut self, key: &T) -> serde_json::Result<()> {
+        match serde_json::to_value(key)? {
+            Value::String(s) => self.scalar(&s)?,
+            Value::Number(n) => self.scalar(&n.to_string())?,
+            Value::Bool(b) => self.scalar(&b.to_string())?,
+            v => {
+                return Err(ser::Error::custom(format!(
+                    "map key must be a string, number, or bool, not {v}"
+                )));
+            }
+        }
+        self.raw(b":")
+    }
+}
+
+impl<'a, W: Write> ser::Serializer for &'a mut JsonSerializer<W> {
+    type Ok = ();
+    type Error = serde_json::Error;
+    type SerializeSeq = Seq<'a, W>;
+    type SerializeTuple = Compound<'a, W>;
+    type SerializeTupleStruct = Compound<'a, W>;
+    type SerializeTupleVariant = Compound<'a, W>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
+
+    fn serialize_bool(self, v: bool) -> serde_json::Result<()> {
+        self.scalar(&v)
+    }
+
+    fn serialize_i8(self, v: i8) -> serde_json::Result<()> {
+        self.scalar(&v)
+    }
+
+    fn serialize_i16(self, v: i16) -> serde_json::Result<()> {
+        self.scalar(&v)
+    }
+
+    fn serialize_i32(self, v: i32) -> serde_json::Result<()> {
+        self.scalar(&v)
+    }
+
+    fn serialize_i64(self, v: i64) -> serde_json::Result<()> {
+        self.scalar(&v)
+    }
+
+    fn serialize_u8(self, v: u8) -> serde_json::Result<()> {
+        self.scalar(&v)
+    }
+
+    fn serialize_u16(self, v: u16) -> serde_json::Result<()> {
+        self.scalar(&v)
+    }
+
+    fn serialize_u32(self, v: u32) -> serde_json::Result<()> {
+        self.scalar(&v)
+    }
+
+    fn serialize_u64(self, v: u64) -> serde_json::Result<()> {
+        self.scalar(&v)
+    }
+
+    fn serialize_f32(self, v: f32) -> serde_json::Result<()> {
+        self.scalar(&v)
+    }
+
+    fn serialize_f64(self, v: f64) -> serde_json::Result<()> {
+        self.scalar(&v)
+    }
+
+    fn serialize_char(self, v: char) -> serde_json::Result<()> {
+        self.scalar(&v)
+    }
+
+    fn serialize_str(self, v: &str) -> serde_json::Result<()> {
+        self.scalar(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> serde_json::Result<()> {
+        ser::Serializer::collect_seq(self, v)
+    }
+
+    fn serialize_none(self) -> serde_json::Result<()> {
+        self.raw(b"null")
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> serde_json::Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> serde_json::Result<()> {
+        self.raw(b"null")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> serde_json::Result<()> {
+        self.raw(b"null")
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> serde_json::Result<()> {
+        self.scalar(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> serde_json::Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> serde_json::Result<()> {
+        self.raw(b"{")?;
+        self.key(variant)?;
+        value.serialize(&mut *self)?;
+        self.raw(b"}")
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> serde_json::Result<Seq<'a, W>> {
+        Ok(Seq {
+            ser: self,
+            state: SeqState::Empty,
+        })
+    }
+
+    fn serialize_tuple(self, _len: usize) -> serde_json::Result<Compound<'a, W>> {
+        Compound::new(self, b"[", b"]")
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> serde_json::Result<Compound<'a, W>> {
+        Compound::new(self, b"[", b"]")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> serde_json::Result<Compound<'a, W>> {
+        self.raw(b"{")?;
+        self.key(variant)?;
+        Compound::new(self, b"[", b"]}")
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> serde_json::Result<Compound<'a, W>> {
+        Compound::new(self, b"{", b"}")
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> serde_json::Result<Compound<'a, W>> {
+        Compound::new(self, b"{", b"}")
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> serde_json::Result<Compound<'a, W>> {
+        self.raw(b"{")?;
+        self.key(variant)?;
+        Compound::new(self, b"{", b"}}")
+    }
+}
+
+/// An array or object, closed with `close` when it ends.
+struct Compound<'a, W> {
+    ser: &'a mut JsonSerializer<W>,
+    first: bool,
+    close: &'static [u8],
+}
+
+impl<'a, W: Write> Compound<'a, W> {
+    fn new(
+        ser: &'a mut JsonSerializer<W>,
+        open: &'static [u8],
+        close: &'static [u8],
+    ) -> serde_json::Result<Self> {
+        ser.raw(open)?;
+        Ok(Self {
+            ser,
+            first: true,
+            close,
+        })
+    }
+
+    fn separate(&mut self) -> serde_json::Result<()> {
+        if self.first {
+            self.first = false;
+            Ok(())
+        } else {
+            self.ser.raw(b",")
+        }
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> serde_json::Result<()> {
+        self.separate()?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> serde_json::Result<()> {
+        self.separate()?;
+        self.ser.key(key)?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn close(self) -> serde_json::Result<()> {
+        self.ser.raw(self.close)
+    }
+}
+
+impl<W: Write> ser::SerializeTuple for Compound<'_, W> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> serde_json::Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> serde_json::Result<()> {
+        self.close()
+    }
+}
+
+impl<W: Write> ser::SerializeTupleStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> serde_json::Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> serde_json::Result<()> {
+        self.close()
+    }
+}
+
+impl<W: Write> ser::SerializeTupleVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> serde_json::Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> serde_json::Result<()> {
+        self.close()
+    }
+}
+
+impl<W: Write> ser::SerializeMap for Compound<'_, W> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> serde_json::Result<()> {
+        self.separate()?;
+        self.ser.key(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> serde_json::Result<()> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> serde_json::Result<()> {
+        self.close()
+    }
+}
+
+impl<W: Write> ser::SerializeStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> serde_json::Result<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> serde_json::Result<()> {
+        self.close()
+    }
+}
+
+impl<W: Write> ser::SerializeStructVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> serde_json::Result<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> serde_json::Result<()> {
+        self.close()
+    }
+}
+
+/// A sequence whose first element decides whether it's written as an array
+/// or, if it's a `u8` and blobs aren't [`BlobEncoding::Array`], as a blob.
+struct Seq<'a, W> {
+    ser: &'a mut JsonSerializer<W>,
+    state: SeqState,
+}
+
+enum SeqState {
+    Empty,
+    Array,
+    Blob {
+        len: usize,
+        /// The bytes not yet encoded, if base64.
+        pending: [u8; 3],
+    },
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes up to three bytes as four base64 characters, padded if needed.
+fn base64_group(bytes: &[u8]) -> [u8; 4] {
+    let b = |i| bytes.get(i).copied().unwrap_or(0) as u32;
+    let n = (b(0) << 16) | (b(1) << 8) | b(2);
+    let mut out = [b'='; 4];
+    for (i, c) in out.iter_mut().enumerate().take(bytes.len() + 1) {
+        *c = BASE64[(n >> (18 - 6 * i) & 0x3F) as usize];
+    }
+    out
+}
+
+impl<W: Write> ser::SerializeSeq for Seq<'_, W> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> serde_json::Result<()> {
+        match &mut self.state {
+            SeqState::Empty => {
+                if self.ser.blobs != BlobEncoding::Array
+                    && let Ok(byte) = value.serialize(U8Probe)
+                {
+                    if self.ser.blobs == BlobEncoding::Base64 {
+                        self.ser.raw(b"\"")?;
+                    }
+                    self.state = SeqState::Blob {
+                        len: 1,
+                        pending: [byte, 0, 0],
+                    };
+                    return Ok(());
+                }
+                self.ser.raw(b"[")?;
+                self.state = SeqState::Array;
+                value.serialize(&mut *self.ser)
+            }
+            SeqState::Array => {
+                self.ser.raw(b",")?;
+                value.serialize(&mut *self.ser)
+            }
+            SeqState::Blob { len, pending } => {
+                let byte = value
+                    .serialize(U8Probe)
+                    .map_err(|_| ser::Error::custom("sequence starts with a u8 but isn't bytes"))?;
+                if self.ser.blobs == BlobEncoding::Base64 {
+                    pending[*len % 3] = byte;
+                    if *len % 3 == 2 {
+                        self.ser.raw(&base64_group(pending))?;
+                    }
+                }
+                *len += 1;
+                Ok(())
+            }
+        }
+    }
+
+    fn end(self) -> serde_json::Result<()> {
+        match self.state {
+            SeqState::Empty => self.ser.raw(b"[]"),
+            SeqState::Array => self.ser.raw(b"]"),
+            SeqState::Blob { len, pending } => match self.ser.blobs {
+                BlobEncoding::Base64 => {
+                    if len % 3 != 0 {
+                        self.ser.raw(&base64_group(&pending[..len % 3]))?;
+                    }
+                    self.ser.raw(b"\"")
+                }
+                _ => self.ser.scalar(&format!("<{len} bytes>")),
+            },
+        }
+    }
+}
+
+/// Returned by [`U8Probe`] for anything but a `u8`.
+#[derive(Debug)]
+struct NotU8;
+
+impl Display for NotU8 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a u8")
+    }
+}
+
+impl std::error::Error for NotU8 {}
+
+impl ser::Error for NotU8 {
+    fn custom<T: Display>(_msg: T) -> Self {
+        Self
+    }
+}
+
+/// Serializes a `u8` to itself, and fails on anything else without writing
+/// or allocating.
+struct U8Probe;
+
+type ProbeResult<T> = core::result::Result<T, NotU8>;
+
+macro_rules! not_u8 {
+    ($($f:ident($t:ty)),* $(,)?) => {
+        $(fn $f(self, _v: $t) -> ProbeResult<u8> {
+            Err(NotU8)
+        })*
+    };
+}
+
+impl ser::Serializer for U8Probe {
+    type Ok = u8;
+    type Error = NotU8;
+    type SerializeSeq = Impossible<u8, NotU8>;
+    type SerializeTuple = Impossible<u8, NotU8>;
+    type SerializeTupleStruct = Impossible<u8, NotU8>;
+    type SerializeTupleVariant = Impossible<u8, NotU8>;
+    type SerializeMap = Impossible<u8, NotU8>;
+    type SerializeStruct = Impossible<u8, NotU8>;
+    type SerializeStructVariant = Impossible<u8, NotU8>;
+
+    fn serialize_u8(self, v: u8) -> ProbeResult<u8> {
+        Ok(v)
+    }
+
+    not_u8!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_struct(&'static str),
+    );
+
+    fn serialize_none(self) -> ProbeResult<u8> {
+        Err(NotU8)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> ProbeResult<u8> {
+        Err(NotU8)
+    }
+
+    fn serialize_unit(self) -> ProbeResult<u8> {
+        Err(NotU8)
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> ProbeResult<u8> {
+        Err(NotU8)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _value: &T,
+    ) -> ProbeResult<u8> {
+        Err(NotU8)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _value: &T,
+    ) -> ProbeResult<u8> {
+        Err(NotU8)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> ProbeResult<Impossible<u8, NotU8>> {
+        Err(NotU8)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> ProbeResult<Impossible<u8, NotU8>> {
+        Err(NotU8)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> ProbeResult<Impossible<u8, NotU8>> {
+        Err(NotU8)
+    }
+
+    fn serialize_tuple(self, _: usize) -> ProbeResult<Impossible<u8, NotU8>> {
+        Err(NotU8)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> ProbeResult<Impossible<u8, NotU8>> {
+        Err(NotU8)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> ProbeResult<Impossible<u8, NotU8>> {
+        Err(NotU8)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> ProbeResult<Impossible<u8, NotU8>> {
+        Err(NotU8)
+    }
+}
@@ -0,0 +1,218 @@
+//! Building localization fastfiles.
+//!
+//! A language pack is just a fastfile full of [`LocalizeEntry`] assets,
+//! usually with an [`XGlobals`] alongside. [`LanguagePackBuilder`] checks
+//! each key as it's added and serializes the result into a ready-to-use
+//! `.ff`.
+
+use std::collections::HashMap;
+
+use t5_xfile_defs::{
+    Error, ErrorKind, Result, XFilePlatform,
+    misc::{LocalizeEntry, XGlobals},
+    validate::{Finding, Severity},
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+};
+
+use crate::{T5XFileSerializerBuilder, file_line_col};
+
+/// The longest value [`LanguagePackBuilder`] accepts without a warning,
+/// unless overridden with [`LanguagePackBuilder::with_max_value_len`].
+pub const DEFAULT_MAX_VALUE_LEN: usize = 1024;
+
+pub struct LanguagePackBuilder {
+    platform: XFilePlatform,
+    globals: Option<XGlobals>,
+    prefixes: Vec<String>,
+    max_value_len: usize,
+    entries: Vec<LocalizeEntry>,
+    /// Index into [`Self::entries`] by key.
+    indices: HashMap<String, usize>,
+    /// [`Finding::index`] is into [`Self::entries`] until
+    /// [`Self::warnings`] adjusts it.
+    warnings: Vec<Finding>,
+}
+
+impl LanguagePackBuilder {
+    pub fn new(platform: XFilePlatform) -> Self {
+        Self {
+            platform,
+            globals: None,
+            prefixes: Vec::new(),
+            max_value_len: DEFAULT_MAX_VALUE_LEN,
+            entries: Vec::new(),
+            indices: HashMap::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Adds an [`XGlobals`] asset ahead of the entries.
+    pub fn with_globals(mut self, globals: XGlobals) -> Self {
+        self.globals = Some(globals);
+        self
+    }
+
+    /// Restricts keys to ones whose prefix (everything before the first
+    /// `_`) is in `prefixes`, e.g. `MENU` or `WEAPON`. Any prefix is allowed
+    /// by default.
+    pub fn with_prefixes<S: Into<String>>(mut self, prefixes: impl IntoIterator<Item = S>) -> Self {
+        self.prefixes = prefixes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Values longer than `max_value_len` bytes are warned about.
+    pub fn with_max_value_len(mut self, max_value_len: usize) -> Self {
+        self.max_value_len = max_value_len;
+        self
+    }
+
+    /// Adds an entry. Fails if `key` isn't a valid reference.
+    ///
+    /// Adding a key that's already been added replaces its value but keeps
+    /// its position, and produces a warning.
+    pub fn add(&mut self, key: impl Into<String>, value: impl Into<String>) -> Result<()> {
+        let key = key.into();
+        let value = value.into();
+        self.check_key(&key)?;
+
+        let index = match self.indices.get(&key) {
+            Some(&i) => {
+                self.warn(
+                    i,
+                    &key,
+                    format!("duplicate key (was \"{}\")", self.entries[i].value.get()),
+                );
+                self.entries[i] = LocalizeEntry::new(key.clone(), value.clone());
+                i
+            }
+            None => {
+                self.indices.insert(key.clone(), self.entries.len());
+                self.entries
+                    .push(LocalizeEntry::new(key.clone(), value.clone()));
+                self.entries.len() - 1
+            }
+        };
+
+        if value.len() > self.max_value_len {
+            self.warn(
+                index,
+                &key,
+                format!(
+                    "value is {} bytes (max {})",
+                    value.len(),
+                    self.max_value_len
+                ),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// [`Self::add`]s every `(key, value)` pair, stopping at the first bad
+    /// key.
+    pub fn add_all<K: Into<String>, V: Into<String>>(
+        &mut self,
+        entries: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<()> {
+        for (key, value) in entries {
+            self.add(key, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Duplicate keys and overlong values found so far. Each
+    /// [`Finding::index`] is the entry's index in [`Self::assets`].
+    pub fn warnings(&self) -> Vec<Finding> {
+        let offset = self.globals.is_some() as usize;
+        self.warnings
+            .iter()
+            .cloned()
+            .map(|w| Finding {
+                index: w.index + offset,
+                ..w
+            })
+            .collect()
+    }
+
+    /// The assets the pack will contain: the [`XGlobals`] (if any), then
+    /// every entry in the order it was first added.
+    pub fn assets(&self) -> Vec<XAsset> {
+        if self.platform.is_pc() {
+            self.generic_assets().into_iter().map(XAsset::PC).collect()
+        } else {
+            self.generic_assets()
+                .into_iter()
+                .map(XAsset::Console)
+                .collect()
+        }
+    }
+
+    fn generic_assets<const MAX_LOCAL_CLIENTS: usize>(
+        &self,
+    ) -> Vec<XAssetGeneric<MAX_LOCAL_CLIENTS>> {
+        let globals = self
+            .globals
+            .iter()
+            .map(|g| XAssetGeneric::XGlobals(Some(Box::new(g.clone()))));
+        let entries = self
+            .entries
+            .iter()
+            .map(|e| XAssetGeneric::LocalizeEntry(Some(Box::new(e.clone()))));
+
+        globals.chain(entries).collect()
+    }
+
+    /// Serializes the pack into a complete fastfile.
+    pub fn build(self) -> Result<Vec<u8>> {
+        let mut ser = T5XFileSerializerBuilder::new(self.platform)
+            .with_silent(true)
+            .build()?;
+        ser.serialize_assets::<1>(self.assets().into_iter())?;
+        ser.deflate()
+    }
+
+    /// Keys are uppercase letters, digits, and underscores, with a prefix
+    /// and at least one word after it: `MENU_START_GAME` is fine, but
+    /// `MENU`, `_MENU`, `MENU_`, and `MENU__START` aren't.
+    fn check_key(&self, key: &str) -> Result<()> {
+        let bad = |why: &str| {
+            Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!("LanguagePackBuilder: key \"{key}\" {why}")),
+            ))
+        };
+
+        if let Some(c) = key
+            .chars()
+            .find(|c| !matches!(c, 'A'..='Z' | '0'..='9' | '_'))
+        {
+            return bad(&format!("contains '{c}'"));
+        }
+
+        let Some((prefix, _)) = key.split_once('_') else {
+            return bad("has no prefix");
+        };
+
+        if key.split('_').any(str::is_empty) {
+            return bad("has an empty word");
+        }
+
+        if !self.prefixes.is_empty() && !self.prefixes.iter().any(|p| p == prefix) {
+            return bad(&format!("has an unknown prefix \"{prefix}\""));
+        }
+
+        Ok(())
+    }
+
+    fn warn(&mut self, entry: usize, key: &str, message: String) {
+        self.warnings.push(Finding {
+            index: entry,
+            asset_type: XAssetType::LOCALIZE_ENTRY,
+            asset_name: Some(key.to_string()),
+            field: "value".to_string(),
+            severity: Severity::Warning,
+            message,
+        });
+    }
+}
@@ -8,13 +8,43 @@ extern crate alloc;
 pub mod deserializer;
 
 #[cfg(feature = "deserializer")]
-pub use deserializer::*;
+pub use deserializer::{
+    AssetSizeEstimate, AssetSizeTotals, CacheSuccess, DeserializeCheckpoint, ERROR_EXCERPT_LEN,
+    HeaderLimits, InflateSuccess, PartialResult, SurveyError, SurveyLimits, SurveyResult,
+    T5XFileDeserializer, T5XFileDeserializerBuilder, T5XFileDeserializerDeflated,
+    T5XFileDeserializerDeserialize, T5XFileDeserializerInflated, TruncatedAsset,
+};
+
+#[cfg(all(feature = "deserializer", feature = "d3d9"))]
+pub use deserializer::D3D9State;
 
 #[cfg(feature = "serializer")]
 pub mod serializer;
 
+#[cfg(feature = "corpus")]
+pub mod corpus;
+
+pub mod export;
+
+#[cfg(feature = "serializer")]
+pub mod language_pack;
+
+#[cfg(feature = "localize")]
+pub mod localize;
+
+#[cfg(feature = "serializer")]
+pub mod patch;
+
+#[cfg(feature = "deserializer")]
+pub mod recover;
+
+#[cfg(feature = "json")]
+pub mod sound;
+
 #[cfg(feature = "serializer")]
-pub use serializer::*;
+pub use serializer::{SizeBreakdown, T5XFileSerializer, T5XFileSerializerBuilder};
+
+pub mod prelude;
 
 use std::io::{Seek, SeekFrom};
 
@@ -96,6 +126,14 @@ impl BincodeOptions {
             Self::BE(opts) => opts.serialize_into(writer, &t),
         }
     }
+
+    #[cfg(feature = "serializer")]
+    fn serialized_size<T: serde::ser::Serialize>(&self, t: T) -> bincode::Result<u64> {
+        match self {
+            Self::LE(opts) => opts.serialized_size(&t),
+            Self::BE(opts) => opts.serialized_size(&t),
+        }
+    }
 }
 
 // ============================================================================
@@ -0,0 +1,268 @@
+//! Converting [`LocalizeEntry`]s to and from mobile localization formats.
+//!
+//! Entries are written in the order they're given, keyed by
+//! [`LocalizeEntry::name`]. T5's format tokens (e.g. `&&1`) are left as-is.
+
+use serde_json::{Map, Value};
+use t5_xfile_defs::{Error, ErrorKind, Result, misc::LocalizeEntry};
+
+use crate::file_line_col;
+
+/// Converts `entries` to a Flutter ARB (Application Resource Bundle) object.
+/// Each entry gets a key with its value, and an `@`-prefixed key with a
+/// `"description"` giving the reference the game uses for it.
+pub fn to_arb(entries: &[LocalizeEntry]) -> Value {
+    let mut arb = Map::new();
+    for entry in entries {
+        let key = entry.name.get();
+        arb.insert(key.to_string(), Value::from(entry.value.get()));
+        arb.insert(
+            format!("@{key}"),
+            serde_json::json!({ "description": format!("Referenced as @{key}") }),
+        );
+    }
+
+    Value::Object(arb)
+}
+
+/// Converts `entries` to an iOS `.stringsdict` property list.
+///
+/// T5 has no plural forms, so each entry's value is its
+/// `NSStringLocalizedFormatKey`, with `%` escaped since the key is a format
+/// string.
+pub fn to_stringsdict(entries: &[LocalizeEntry]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+         \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n",
+    );
+
+    for entry in entries {
+        xml.push_str("    <key>");
+        escape_xml(entry.name.get(), &mut xml);
+        xml.push_str("</key>\n    <dict>\n        <key>NSStringLocalizedFormatKey</key>\n");
+        xml.push_str("        <string>");
+        escape_xml(&entry.value.get().replace('%', "%%"), &mut xml);
+        xml.push_str("</string>\n    </dict>\n");
+    }
+
+    xml.push_str("</dict>\n</plist>\n");
+    xml
+}
+
+/// Converts `entries` to an Android `strings.xml` resource file.
+///
+/// Values are escaped for both XML (`&`, `<`, `>`) and Android's resource
+/// compiler (`'`, `"`, `\`, newlines, tabs, and a leading `@` or `?`), so
+/// [`from_android_xml`] gets back exactly what was written.
+pub fn to_android_xml(entries: &[LocalizeEntry]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<resources>\n");
+
+    for entry in entries {
+        xml.push_str("    <string name=\"");
+        escape_xml(entry.name.get(), &mut xml);
+        xml.push_str("\">");
+        escape_android(entry.value.get(), &mut xml);
+        xml.push_str("</string>\n");
+    }
+
+    xml.push_str("</resources>\n");
+    xml
+}
+
+/// Reads the `<string>` elements of an Android `strings.xml` resource file.
+///
+/// Comments and every other element are skipped. Markup inside a
+/// `<string>` (e.g. `<b>`) isn't supported and is an error, as is a
+/// `<string>` without a `name`.
+pub fn from_android_xml(xml: &str) -> Result<Vec<LocalizeEntry>> {
+    let mut entries = Vec::new();
+
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+
+        let skip_past = if rest.starts_with("<!--") {
+            "-->"
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            ">"
+        } else if rest
+            .strip_prefix("<string")
+            .is_some_and(|r| r.starts_with(|c: char| c.is_ascii_whitespace() || c == '>'))
+        {
+            let (entry, r) = parse_string_element(rest)?;
+            entries.push(entry);
+            rest = r;
+            continue;
+        } else {
+            ">"
+        };
+
+        let end = rest
+            .find(skip_past)
+            .ok_or_else(|| bad_xml(format!("\"{skip_past}\" never found")))?;
+        rest = &rest[end + skip_past.len()..];
+    }
+
+    Ok(entries)
+}
+
+/// Parses the `<string>` element at the start of `xml`, and returns it
+/// along with everything after it.
+fn parse_string_element(xml: &str) -> Result<(LocalizeEntry, &str)> {
+    let tag_end = xml
+        .find('>')
+        .ok_or_else(|| bad_xml("unterminated <string> tag".to_string()))?;
+    let (attrs, self_closing) = match xml[..tag_end].strip_suffix('/') {
+        Some(attrs) => (&attrs["<string".len()..], true),
+        None => (&xml["<string".len()..tag_end], false),
+    };
+
+    let name =
+        attribute(attrs, "name").ok_or_else(|| bad_xml("<string> without a name".to_string()))?;
+    let name = unescape_xml(name)?;
+
+    let rest = &xml[tag_end + 1..];
+    if self_closing {
+        return Ok((LocalizeEntry::new(name, ""), rest));
+    }
+
+    let content_end = rest
+        .find("</string>")
+        .ok_or_else(|| bad_xml(format!("<string name=\"{name}\"> isn't closed")))?;
+    let content = &rest[..content_end];
+    if content.contains('<') {
+        return Err(bad_xml(format!("<string name=\"{name}\"> contains markup")));
+    }
+
+    let value = unescape_android(&unescape_xml(content)?)?;
+    Ok((
+        LocalizeEntry::new(name, value),
+        &rest[content_end + "</string>".len()..],
+    ))
+}
+
+/// The raw value of the attribute `name` in `attrs`, which may be quoted
+/// with either `"` or `'`.
+fn attribute<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attrs;
+    loop {
+        rest = rest.trim_start();
+        let eq = rest.find('=')?;
+        let key = rest[..eq].trim_end();
+        let value = rest[eq + 1..].trim_start();
+
+        let quote = value.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        let end = value[1..].find(quote)? + 1;
+        if key == name {
+            return Some(&value[1..end]);
+        }
+        rest = &value[end + 1..];
+    }
+}
+
+fn bad_xml(why: String) -> Error {
+    Error::new(
+        file_line_col!(),
+        ErrorKind::BrokenInvariant(format!("localize: bad XML: {why}")),
+    )
+}
+
+fn escape_xml(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+fn escape_android(s: &str, out: &mut String) {
+    if s.starts_with(['@', '?']) {
+        out.push('\\');
+    }
+
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\'' => out.push_str("\\'"),
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            _ => out.push(c),
+        }
+    }
+}
+
+fn unescape_xml(s: &str) -> Result<String> {
+    let mut out = String::with_capacity(s.len());
+
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let end = rest
+            .find(';')
+            .ok_or_else(|| bad_xml(format!("unterminated entity in \"{s}\"")))?;
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => entity.strip_prefix('#').and_then(|d| d.parse().ok()),
+            }
+            .and_then(char::from_u32),
+        };
+        out.push(c.ok_or_else(|| bad_xml(format!("unknown entity \"&{entity};\"")))?);
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+
+    Ok(out)
+}
+
+fn unescape_android(s: &str) -> Result<String> {
+    let mut out = String::with_capacity(s.len());
+
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('u') => {
+                let hex = chars.by_ref().take(4).collect::<String>();
+                let c = u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| bad_xml(format!("bad \\u escape in \"{s}\"")))?;
+                out.push(c);
+            }
+            Some(c @ ('\'' | '"' | '\\' | '@' | '?')) => out.push(c),
+            Some(c) => return Err(bad_xml(format!("unknown escape \"\\{c}\" in \"{s}\""))),
+            None => return Err(bad_xml(format!("trailing \\ in \"{s}\""))),
+        }
+    }
+
+    Ok(out)
+}
@@ -1,14 +1,40 @@
 use std::path::Path;
 
-use t5_xfile_defs::XFilePlatform;
-use t5_xfile_deserializer::T5XFileDeserializerBuilder;
+use t5_xfile_defs::{
+    XFilePlatform,
+    annotate::annotate,
+    sound::{SndCurve, SndDriverGlobals},
+    validate::validate,
+    xasset::{XAsset, XAssetGeneric},
+};
+#[cfg(feature = "serializer")]
+use t5_xfile_deserializer::T5XFileSerializer;
+#[cfg(feature = "json")]
+use t5_xfile_deserializer::export::{
+    json::{JsonLayout, JsonOptions},
+    to_json_writer,
+};
+use t5_xfile_deserializer::{SurveyLimits, T5XFileDeserializerBuilder, recover};
 
-use clap::{arg, command};
+use clap::{Command, arg, command};
 
 const CACHE_FILE_EXT: &str = "cache";
 
 fn main() {
-    let matches = command!()
+    let annotate_cmd = Command::new("annotate")
+        .about(
+            "Prints each field of an asset's header struct with its offset, \
+             raw bytes, and decoded value. Useful for finding misaligned fields.",
+        )
+        .arg(
+            arg!(--asset_index <N> "Index of the asset to annotate")
+                .required(true)
+                .value_parser(clap::value_parser!(usize)),
+        );
+    #[cfg(feature = "json")]
+    let annotate_cmd = annotate_cmd.arg(arg!(--json "Prints JSON instead of text"));
+
+    let cmd = command!()
         .arg(arg!([FILENAME] "Filename to use (should have .ff or .cache extension)"))
         .arg(arg!(
             -p --platform <PLATFORM>
@@ -26,7 +52,94 @@ fn main() {
             )
             .required(false),
         )
-        .get_matches();
+        .arg(
+            arg!(
+                -t --allow_truncated
+                "Deserializes as much of a truncated Fastfile as possible instead \
+                 of failing."
+            )
+            .required(false),
+        )
+        .subcommand(Command::new("validate").about(
+            "Checks the deserialized assets for suspicious values. Exits with \
+             a nonzero status if any errors are found.",
+        ))
+        .subcommand(
+            Command::new("survey")
+                .about(
+                    "Keeps going past assets that fail to deserialize, and prints \
+                     every failure. Useful when debugging asset layouts.",
+                )
+                .arg(
+                    arg!(--max_errors <N> "Stop after this many failed assets")
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(Command::new("recover").about(
+            "Scans the inflated blob for raw files, string tables, and localized \
+             strings without using the asset list. For Fastfiles whose asset \
+             list is damaged.",
+        ))
+        .subcommand(
+            Command::new("hexdump")
+                .about("Prints a range of the inflated blob as a hexdump.")
+                .arg(
+                    arg!(--offset <OFFSET> "Blob offset to start at (decimal, or hex with 0x)")
+                        .required(true)
+                        .value_parser(parse_offset),
+                )
+                .arg(
+                    arg!(--len <LEN> "Number of bytes to print")
+                        .default_value("256")
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(annotate_cmd)
+        .subcommand(
+            Command::new("sound")
+                .about("Inspects sound aliases.")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("preview")
+                        .about(
+                            "Prints an alias's volume at each of the given distances, \
+                             using the falloff curves from the Fastfile's sound driver \
+                             globals if it has them.",
+                        )
+                        .arg(arg!(--alias <NAME> "Name of the alias to preview").required(true))
+                        .arg(
+                            arg!(--distances <DISTANCES> "Comma-separated distances, in units")
+                                .default_value("0,100,500,1000")
+                                .value_delimiter(',')
+                                .value_parser(clap::value_parser!(f32)),
+                        ),
+                ),
+        );
+
+    #[cfg(feature = "serializer")]
+    let cmd = cmd.subcommand(
+        Command::new("measure")
+            .about(
+                "Prints how many bytes each asset takes up when serialized. \
+                 Only assets matching all of the given filters are printed.",
+            )
+            .arg(arg!(--type <TYPE> "Only measure assets of this type (e.g. weapon)"))
+            .arg(arg!(--name <NAME> "Only measure the asset with this name")),
+    );
+
+    #[cfg(feature = "json")]
+    let cmd = cmd.subcommand(
+        Command::new("json")
+            .about("Prints the deserialized assets as JSON.")
+            .arg(arg!(--ndjson "Prints one asset per line instead of an array"))
+            .arg(arg!(
+                --provenance
+                "Prints each asset with where it came from: the file, its index, \
+                 and its span in the inflated blob"
+            )),
+    );
+
+    let matches = cmd.get_matches();
 
     let Some(filename) = matches.get_one::<String>("FILENAME") else {
         println!(
@@ -71,6 +184,11 @@ fn main() {
             false
         };
 
+    let allow_truncated = matches
+        .get_one::<bool>("allow_truncated")
+        .copied()
+        .unwrap_or(false);
+
     let de = if cache_exists {
         T5XFileDeserializerBuilder::from_cache_file(
             &mut file,
@@ -80,22 +198,285 @@ fn main() {
     } else {
         T5XFileDeserializerBuilder::from_file(&mut file, platform, allow_unsupported_platforms)
     }
-    .with_silent(false);
+    .with_silent(false)
+    .with_allow_truncated(allow_truncated)
+    .with_source(filename.as_str());
 
     #[cfg(feature = "d3d9")]
     let de = de.with_d3d9(None);
 
     let de = de.build().unwrap().inflate().unwrap();
 
+    if matches.subcommand_matches("recover").is_some() {
+        let recovered = recover::scan(de.blob());
+        for r in &recovered {
+            println!(
+                "{:#010X} ({} bytes): {:?} '{}', confidence {:.2}",
+                r.offset,
+                r.len,
+                r.asset.asset_type(),
+                r.asset.name().unwrap_or_default(),
+                r.confidence
+            );
+        }
+        println!("{} asset(s) recovered.", recovered.len());
+        return;
+    }
+
     let de = if !cache_exists {
         de.cache(cached_filename).unwrap().0
     } else {
         de.no_cache().unwrap()
     };
 
-    let assets = de.deserialize_remaining().unwrap();
+    if let Some(m) = matches.subcommand_matches("hexdump") {
+        let off = *m.get_one::<u64>("offset").unwrap();
+        let len = *m.get_one::<usize>("len").unwrap();
+        match de.hexdump_at(off, len) {
+            Ok(dump) => println!("{dump}"),
+            Err(e) => {
+                println!("{e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(m) = matches.subcommand_matches("annotate") {
+        let index = *m.get_one::<usize>("asset_index").unwrap();
+        let mut de = de;
+        let mut asset = None;
+        for _ in 0..=index {
+            asset = de.deserialize_next().unwrap();
+            if asset.is_none() {
+                println!("there's no asset {index}");
+                std::process::exit(1);
+            }
+        }
+
+        let asset_type = asset.unwrap().asset_type();
+        let bytes = match de.asset_bytes(index) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                println!("{e}");
+                std::process::exit(1);
+            }
+        };
+        let fields = match annotate(asset_type, &bytes, platform) {
+            Ok(fields) => fields,
+            Err(e) => {
+                println!("{e}");
+                std::process::exit(1);
+            }
+        };
+
+        #[cfg(feature = "json")]
+        if m.get_flag("json") {
+            println!("{}", serde_json::to_string_pretty(&fields).unwrap());
+            return;
+        }
+
+        println!("Asset {index} ({asset_type:?}), {} bytes:", bytes.len());
+        for field in &fields {
+            println!("{field}");
+        }
+        return;
+    }
+
+    if let Some(m) = matches.subcommand_matches("survey") {
+        let mut limits = SurveyLimits::default();
+        if let Some(&n) = m.get_one::<usize>("max_errors") {
+            limits.max_errors = n;
+        }
+
+        let result = de.survey(limits).unwrap();
+        for e in &result.errors {
+            println!("Asset {} ({:?}): {:?}", e.index, e.asset_type, e.error);
+            match e.skipped_bytes {
+                Some(n) => println!("  next asset found {n} bytes after its start"),
+                None => println!("  next asset not found"),
+            }
+        }
+        println!(
+            "{} asset(s) deserialized, {} failed{}.",
+            result.assets.len(),
+            result.errors.len(),
+            if result.stopped_early {
+                " (stopped early)"
+            } else {
+                ""
+            }
+        );
+        return;
+    }
+
+    #[cfg(feature = "json")]
+    if let Some(m) = matches.subcommand_matches("json") {
+        let layout = if m.get_flag("ndjson") {
+            JsonLayout::Ndjson
+        } else {
+            JsonLayout::Array
+        };
+        let options = JsonOptions {
+            layout,
+            ..Default::default()
+        }
+        .with_provenance(m.get_flag("provenance"));
+
+        let assets = de.deserialize_remaining_loaded().unwrap();
+        if let Err(e) = to_json_writer(&assets, std::io::stdout().lock(), options) {
+            println!("{e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let assets = if allow_truncated {
+        let result = de.deserialize_remaining_partial().unwrap();
+        if let Some(cut) = &result.cut_asset {
+            println!(
+                "Asset {} ({:?}) was cut off by the end of the file.",
+                cut.index, cut.asset_type
+            );
+        }
+        result.assets
+    } else {
+        de.deserialize_remaining().unwrap()
+    };
+
+    if matches.subcommand_matches("validate").is_some() {
+        let report = validate(&assets);
+        for finding in &report.findings {
+            println!("{finding}");
+        }
+        println!(
+            "{} finding(s) in {} asset(s).",
+            report.findings.len(),
+            assets.len()
+        );
+
+        if report.has_errors() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(m) = matches
+        .subcommand_matches("sound")
+        .and_then(|m| m.subcommand_matches("preview"))
+    {
+        let name = m.get_one::<String>("alias").unwrap();
+        let distances = m
+            .get_many::<f32>("distances")
+            .unwrap()
+            .copied()
+            .collect::<Vec<_>>();
+        if !print_sound_preview(&assets, name, &distances) {
+            println!("no alias named '{name}'");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    #[cfg(feature = "serializer")]
+    if let Some(m) = matches.subcommand_matches("measure") {
+        let asset_type = m.get_one::<String>("type");
+        let name = m.get_one::<String>("name");
+
+        for asset in assets.iter().filter(|a| a.is_some()) {
+            let type_name = format!("{:?}", asset.asset_type());
+            if asset_type.is_some_and(|t| !t.eq_ignore_ascii_case(&type_name))
+                || name.is_some_and(|n| Some(n.as_str()) != asset.name())
+            {
+                continue;
+            }
+
+            match T5XFileSerializer::measure(asset) {
+                Ok(s) => println!(
+                    "{type_name} '{}': {} bytes ({} structs, {} strings, {} arrays)",
+                    asset.name().unwrap_or_default(),
+                    s.total,
+                    s.structs,
+                    s.strings,
+                    s.arrays
+                ),
+                Err(e) => println!(
+                    "{type_name} '{}': couldn't be measured: {e:?}",
+                    asset.name().unwrap_or_default(),
+                ),
+            }
+        }
+        return;
+    }
+
     for (i, asset) in assets.into_iter().enumerate() {
         println!("Found asset '{}' ({})", asset.name().unwrap_or_default(), i);
     }
     //dbg!(assets);
 }
+
+/// Prints a table of each variant of the alias `name`'s volume at each of
+/// `distances`. Returns `false` if there's no such alias.
+fn print_sound_preview(assets: &[XAsset], name: &str, distances: &[f32]) -> bool {
+    let mut globals = None;
+    let mut aliases = Vec::new();
+    for asset in assets {
+        match asset {
+            XAsset::PC(XAssetGeneric::SndDriverGlobals(Some(g)))
+            | XAsset::Console(XAssetGeneric::SndDriverGlobals(Some(g))) => globals = Some(g),
+            XAsset::PC(XAssetGeneric::Sound(Some(bank)))
+            | XAsset::Console(XAssetGeneric::Sound(Some(bank))) => aliases.extend(
+                bank.aliases
+                    .iter()
+                    .filter(|l| l.name.get() == name)
+                    .flat_map(|l| &l.aliases),
+            ),
+            _ => {}
+        }
+    }
+
+    if aliases.is_empty() {
+        return false;
+    }
+
+    let default_globals = SndDriverGlobals::default();
+    let globals = globals.map_or_else(
+        || {
+            println!("No sound driver globals found, only the builtin curves are available.");
+            &default_globals
+        },
+        |g| g.as_ref(),
+    );
+
+    for (i, alias) in aliases.iter().enumerate() {
+        let curve = match alias.dry_curve {
+            SndCurve::LINEAR => "linear".to_string(),
+            SndCurve::IDENTITY => "identity".to_string(),
+            c => globals
+                .curves
+                .get(c as usize)
+                .map_or_else(|| format!("{c} (missing)"), |c| c.name.get().to_string()),
+        };
+        println!(
+            "'{name}' variant {i}: dist_min {}, dist_max {}, vol_max {}, curve {curve}",
+            alias.dist_min, alias.dist_max, alias.vol_max
+        );
+
+        println!("  {:>10}  {:>6}", "distance", "volume");
+        for &dist in distances {
+            match alias.volume_at_distance(dist, globals) {
+                Some(v) => println!("  {dist:>10}  {v:>6.3}"),
+                None => println!("  {dist:>10}  {:>6}", "?"),
+            }
+        }
+    }
+
+    true
+}
+
+fn parse_offset(s: &str) -> Result<u64, std::num::ParseIntError> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
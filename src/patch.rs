@@ -0,0 +1,572 @@
+//! Building patch Fastfiles that only hold the assets a mod changed.
+//!
+//! Assets are matched between the base and the modified set by type and
+//! name, and compared by a hash of their serde encoding, so [`build_patch`]
+//! only serializes the ones that are new or differ.
+//!
+//! When a changed asset uses an asset the base also has, and the sub-asset's
+//! type can be written as a reference to another Fastfile's asset (see
+//! [`FxEffectDef::external`]), the patch only holds that reference, and
+//! [`PatchReport::externalized`] lists it. Other sub-assets, such as a
+//! weapon's material, have no reference form the serializer can write, so
+//! the patch carries its own copy, and [`PatchReport::inlined`] lists it.
+//! Sub-assets are found by walking an asset for the structs of the asset
+//! types that can be held inline, and matched by type and name.
+//!
+//! [`FxEffectDef::external`]: t5_xfile_defs::fx::FxEffectDef::external
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{
+    Serialize, Serializer,
+    ser::{
+        SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+        SerializeTupleStruct, SerializeTupleVariant,
+    },
+};
+use t5_xfile_defs::{
+    Error, ErrorKind, Result,
+    xasset::{XAsset, XAssetType},
+};
+
+use crate::{T5XFileSerializer, export::bin::source_checksum, file_line_col};
+
+/// Identifies an asset by its type and name.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PatchAsset {
+    pub asset_type: XAssetType,
+    /// Empty for assets without a name.
+    pub name: String,
+}
+
+impl PatchAsset {
+    pub fn of(asset: &XAsset) -> Self {
+        Self {
+            asset_type: asset.asset_type(),
+            name: asset.name().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/// A base asset used by an asset in the patch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubAssetReference {
+    /// The asset in the patch.
+    pub from: PatchAsset,
+    /// The base asset it uses.
+    pub to: PatchAsset,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PatchReport {
+    /// The assets serialized, in order.
+    pub included: Vec<PatchAsset>,
+    /// Unchanged base assets that included assets refer to by name. See the
+    /// [module docs](self).
+    pub externalized: Vec<SubAssetReference>,
+    /// Unchanged base assets that had to be written into the patch because
+    /// an included asset uses them, and they can't be referred to by name.
+    pub inlined: Vec<SubAssetReference>,
+}
+
+/// Serializes the assets in `modified` that aren't in `base`, or differ
+/// from the base's asset of the same type and name, into `ser`.
+///
+/// Included assets keep their order in `modified`, except that one used by
+/// another included asset is moved before it. Assets in `base` but not in
+/// `modified` are left alone, since a patch can't remove assets.
+///
+/// If nothing changed, nothing is serialized, and `ser` can't be deflated.
+/// Check [`PatchReport::included`] first.
+pub fn build_patch(
+    base: &[XAsset],
+    modified: &[XAsset],
+    ser: &mut T5XFileSerializer,
+) -> Result<PatchReport> {
+    let base = base
+        .iter()
+        .map(|a| Ok((PatchAsset::of(a), source_checksum(&encode(a)?))))
+        .collect::<Result<HashSet<_>>>()?;
+    let base_ids = base.iter().map(|(id, _)| id).collect::<HashSet<_>>();
+
+    let mut changed = Vec::new();
+    for asset in modified {
+        // They'd all share `XAssetType::default`, and can't be serialized.
+        let raw_asset_type = asset.raw_asset_type();
+        if XAssetType::from_raw(raw_asset_type).is_none() {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::Todo(format!(
+                    "patching custom assets (asset type {raw_asset_type:#X})"
+                )),
+            ));
+        }
+
+        let id = PatchAsset::of(asset);
+        let hash = source_checksum(&encode(asset)?);
+        if !base.contains(&(id.clone(), hash)) {
+            changed.push((asset, id));
+        }
+    }
+
+    let index = changed
+        .iter()
+        .enumerate()
+        .map(|(i, (_, id))| (id, i))
+        .collect::<HashMap<_, _>>();
+    let external = base_ids
+        .iter()
+        .copied()
+        .filter(|id| EXTERNAL_TYPES.contains(&id.asset_type) && !index.contains_key(id))
+        .collect::<HashSet<_>>();
+    let sub_assets = changed
+        .iter()
+        .map(|(asset, id)| sub_assets(asset, id, &external))
+        .collect::<Result<Vec<_>>>()?;
+    let uses = sub_assets
+        .iter()
+        .map(|subs| subs.iter().filter_map(|s| index.get(s).copied()).collect())
+        .collect::<Vec<_>>();
+    let order = dependency_order(&uses);
+
+    let mut report = PatchReport::default();
+    for &i in &order {
+        let id = &changed[i].1;
+        for sub in &sub_assets[i] {
+            let reference = || SubAssetReference {
+                from: id.clone(),
+                to: sub.clone(),
+            };
+            if external.contains(sub) {
+                report.externalized.push(reference());
+            } else if !index.contains_key(sub) && base_ids.contains(sub) {
+                report.inlined.push(reference());
+            }
+        }
+        report.included.push(id.clone());
+    }
+
+    ser.external_assets = external
+        .iter()
+        .map(|id| (id.asset_type, id.name.clone()))
+        .collect();
+    let serialized = ser.serialize_assets::<1>(order.iter().map(|&i| changed[i].0.clone()));
+    ser.external_assets.clear();
+
+    serialized.map(|_| report)
+}
+
+/// The indices of the assets `uses` describes, with any asset another one
+/// uses moved before the first that does. `uses[i]` holds the indices of the
+/// assets the `i`th one uses.
+fn dependency_order(uses: &[Vec<usize>]) -> Vec<usize> {
+    let mut order = Vec::with_capacity(uses.len());
+    let mut placed = vec![false; uses.len()];
+    fn place(i: usize, uses: &[Vec<usize>], placed: &mut [bool], order: &mut Vec<usize>) {
+        if placed[i] {
+            return;
+        }
+        // Marked before its dependencies are, so that a cycle ends here.
+        placed[i] = true;
+        for &j in &uses[i] {
+            place(j, uses, placed, order);
+        }
+        order.push(i);
+    }
+
+    for i in 0..uses.len() {
+        place(i, uses, &mut placed, &mut order);
+    }
+    order
+}
+
+fn encode(asset: &XAsset) -> Result<Vec<u8>> {
+    bincode::serialize(asset).map_err(|e| Error::new(file_line_col!(), ErrorKind::Bincode(e)))
+}
+
+/// The asset types the serializer can write as a reference to another
+/// Fastfile's asset. See [`T5XFileSerialize::is_external`].
+///
+/// [`T5XFileSerialize::is_external`]: t5_xfile_defs::T5XFileSerialize::is_external
+const EXTERNAL_TYPES: &[XAssetType] = &[XAssetType::FX];
+
+/// The asset types other assets can hold inline, by the name of their
+/// struct, and the fields that lead to their name.
+const INLINE_ASSETS: &[(&str, XAssetType, &[&str])] = &[
+    ("PhysPreset", XAssetType::PHYSPRESET, &["name"]),
+    ("PhysConstraints", XAssetType::PHYSCONSTRAINTS, &["name"]),
+    ("XModel", XAssetType::XMODEL, &["name"]),
+    ("Material", XAssetType::MATERIAL, &["info", "name"]),
+    ("MaterialTechniqueSet", XAssetType::TECHNIQUE_SET, &["name"]),
+    ("GfxImage", XAssetType::IMAGE, &["name"]),
+    ("MapEnts", XAssetType::MAP_ENTS, &["name"]),
+    ("GfxLightDef", XAssetType::LIGHT_DEF, &["name"]),
+    ("MenuDef", XAssetType::MENU, &["window", "name"]),
+    ("FxEffectDef", XAssetType::FX, &["name"]),
+];
+
+/// The assets `asset` (identified by `id`) holds inline, each once, in the
+/// order they're first found. Those held by a sub-asset in `external`
+/// aren't written, so they're left out.
+fn sub_assets(
+    asset: &XAsset,
+    id: &PatchAsset,
+    external: &HashSet<&PatchAsset>,
+) -> Result<Vec<PatchAsset>> {
+    let mut finder = SubAssetFinder::default();
+    asset.serialize(&mut finder).map_err(|_| {
+        Error::new(
+            file_line_col!(),
+            ErrorKind::BrokenInvariant(format!("couldn't walk {id:?}")),
+        )
+    })?;
+
+    // Parents are found before their children.
+    let mut hidden = Vec::with_capacity(finder.found.len());
+    for found in &finder.found {
+        hidden.push(
+            found
+                .parent
+                .is_some_and(|p| hidden[p] || external.contains(&finder.found[p].asset)),
+        );
+    }
+
+    let mut seen = HashSet::new();
+    Ok(finder
+        .found
+        .into_iter()
+        .zip(hidden)
+        .filter(|(found, hidden)| !hidden && found.asset != *id)
+        .map(|(found, _)| found.asset)
+        .filter(|sub| seen.insert(sub.clone()))
+        .collect())
+}
+
+/// A sub-asset found by [`SubAssetFinder`].
+struct FoundAsset {
+    asset: PatchAsset,
+    /// The index in [`SubAssetFinder::found`] of the sub-asset holding this
+    /// one, if any.
+    parent: Option<usize>,
+}
+
+/// A sub-asset whose struct is being serialized.
+struct OpenAsset {
+    /// Its index in [`SubAssetFinder::found`].
+    index: usize,
+    name_path: &'static [&'static str],
+    /// The length of [`SubAssetFinder::path`] when the struct started.
+    depth: usize,
+    named: bool,
+}
+
+/// A [`Serializer`] that writes nothing, but notes every struct in
+/// [`INLINE_ASSETS`] it's given.
+#[derive(Default)]
+struct SubAssetFinder {
+    /// The fields being serialized, outermost first.
+    path: Vec<&'static str>,
+    /// Whether each struct being serialized, outermost first, is in
+    /// [`INLINE_ASSETS`].
+    structs: Vec<bool>,
+    open: Vec<OpenAsset>,
+    found: Vec<FoundAsset>,
+}
+
+impl SubAssetFinder {
+    fn begin_struct(&mut self, name: &str) {
+        let asset = INLINE_ASSETS.iter().find(|(n, ..)| *n == name);
+        if let Some(&(_, asset_type, name_path)) = asset {
+            self.open.push(OpenAsset {
+                index: self.found.len(),
+                name_path,
+                depth: self.path.len(),
+                named: false,
+            });
+            self.found.push(FoundAsset {
+                asset: PatchAsset {
+                    asset_type,
+                    name: String::new(),
+                },
+                parent: self.open.iter().rev().nth(1).map(|o| o.index),
+            });
+        }
+        self.structs.push(asset.is_some());
+    }
+
+    fn end_struct(&mut self) {
+        if self.structs.pop() == Some(true) {
+            self.open.pop();
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        if let Some(open) = self.open.last_mut()
+            && !open.named
+            && self.path[open.depth..] == *open.name_path
+        {
+            open.named = true;
+            self.found[open.index].asset.name = s.to_string();
+        }
+    }
+}
+
+type Walk = core::result::Result<(), core::fmt::Error>;
+
+impl Serializer for &mut SubAssetFinder {
+    type Ok = ();
+    type Error = core::fmt::Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, _: bool) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_i8(self, _: i8) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_i16(self, _: i16) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_i32(self, _: i32) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_i64(self, _: i64) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_u8(self, _: u8) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_u16(self, _: u16) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_u32(self, _: u32) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_u64(self, _: u64) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_f32(self, _: f32) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_f64(self, _: f64) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_char(self, _: char) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Walk {
+        self.string(v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Walk {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Walk {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Walk {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> core::result::Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> core::result::Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> core::result::Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> core::result::Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> core::result::Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _: usize,
+    ) -> core::result::Result<Self, Self::Error> {
+        self.begin_struct(name);
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> core::result::Result<Self, Self::Error> {
+        self.structs.push(false);
+        Ok(self)
+    }
+}
+
+impl SerializeSeq for &mut SubAssetFinder {
+    type Ok = ();
+    type Error = core::fmt::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Walk {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Walk {
+        Ok(())
+    }
+}
+
+impl SerializeTuple for &mut SubAssetFinder {
+    type Ok = ();
+    type Error = core::fmt::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Walk {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Walk {
+        Ok(())
+    }
+}
+
+impl SerializeTupleStruct for &mut SubAssetFinder {
+    type Ok = ();
+    type Error = core::fmt::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Walk {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Walk {
+        Ok(())
+    }
+}
+
+impl SerializeTupleVariant for &mut SubAssetFinder {
+    type Ok = ();
+    type Error = core::fmt::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Walk {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Walk {
+        Ok(())
+    }
+}
+
+impl SerializeMap for &mut SubAssetFinder {
+    type Ok = ();
+    type Error = core::fmt::Error;
+
+    /// Keys can't hold assets, and aren't walked so that a string key can't
+    /// be mistaken for a name.
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, _: &T) -> Walk {
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Walk {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Walk {
+        Ok(())
+    }
+}
+
+impl SerializeStruct for &mut SubAssetFinder {
+    type Ok = ();
+    type Error = core::fmt::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Walk {
+        self.path.push(key);
+        let walked = value.serialize(&mut **self);
+        self.path.pop();
+        walked
+    }
+
+    fn end(self) -> Walk {
+        self.end_struct();
+        Ok(())
+    }
+}
+
+impl SerializeStructVariant for &mut SubAssetFinder {
+    type Ok = ();
+    type Error = core::fmt::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Walk {
+        self.path.push(key);
+        let walked = value.serialize(&mut **self);
+        self.path.pop();
+        walked
+    }
+
+    fn end(self) -> Walk {
+        self.end_struct();
+        Ok(())
+    }
+}
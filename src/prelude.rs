@@ -0,0 +1,42 @@
+//! The handful of types most programs need, in one import:
+//!
+//! ```
+//! use t5_xfile_deserializer::prelude::*;
+//! ```
+//!
+//! Asset data types aren't included. They stay under their modules in
+//! [`t5_xfile_defs`] (e.g. [`t5_xfile_defs::weapon::WeaponVariantDef`]).
+//!
+//! The raw structs and pointer types the (de)serializer works with internally
+//! live under [`t5_xfile_defs::util`], and most programs shouldn't need them.
+//! Their old root paths still work for one release, but are deprecated:
+//!
+//! ```compile_fail
+//! #![deny(deprecated)]
+//! use t5_xfile_defs::XStringRaw;
+//! ```
+//!
+//! ```compile_fail
+//! #![deny(deprecated)]
+//! use t5_xfile_defs::Ptr32;
+//! ```
+//!
+//! ```
+//! #![deny(deprecated)]
+//! use t5_xfile_defs::util::{Ptr32, XStringRaw};
+//! ```
+
+pub use t5_xfile_defs::{
+    Error, ErrorKind, Result, XFilePlatform, XString,
+    layout::LayoutOverrides,
+    limits::ResourceLimits,
+    validate::{ValidationReport, validate},
+    xasset::{XAsset, XAssetContent, XAssetGeneric, XAssetType},
+    xmodel::xom::write_xom,
+};
+
+#[cfg(feature = "deserializer")]
+pub use crate::{HeaderLimits, PartialResult, T5XFileDeserializer, T5XFileDeserializerBuilder};
+
+#[cfg(feature = "serializer")]
+pub use crate::{T5XFileSerializer, T5XFileSerializerBuilder, language_pack::LanguagePackBuilder};
@@ -0,0 +1,304 @@
+//! Best-effort recovery of assets from a blob whose asset list is damaged.
+//!
+//! Without the asset list there's no way to know where assets start or
+//! what type they are, so [`scan`] looks for byte patterns that only a few
+//! asset types make: an inline name string right after a struct whose
+//! fixed-size fields have sane values. Each candidate is then deserialized
+//! on its own, bounded to a window of the blob, and kept if it parses
+//! cleanly.
+//!
+//! Only [`RawFile`](t5_xfile_defs::misc::RawFile)s,
+//! [`StringTable`](t5_xfile_defs::misc::StringTable)s and
+//! [`LocalizeEntry`](t5_xfile_defs::misc::LocalizeEntry)s are looked for.
+//! This is forensic tooling: anything recovered should be checked by hand.
+
+use std::{
+    io::Cursor,
+    panic::{AssertUnwindSafe, catch_unwind},
+};
+
+use t5_xfile_defs::{
+    Error, ErrorKind, Result, ScriptString, T5XFileDeserialize, XFilePlatform,
+    misc::StringTable,
+    util::Ptr32,
+    xasset::{XAsset, XAssetGeneric, XAssetRaw, XAssetType},
+};
+
+use crate::{BincodeOptions, file_line_col};
+
+/// The most bytes a single candidate can take up.
+const MAX_ASSET_BYTES: usize = 16 << 20;
+
+/// The longest name an asset is expected to have.
+const MAX_NAME_LEN: usize = 256;
+
+const INLINE: [u8; 4] = [0xFF; 4];
+
+/// Extensions of the files usually stored as [`RawFile`]s.
+///
+/// [`RawFile`]: t5_xfile_defs::misc::RawFile
+const RAWFILE_EXTENSIONS: &[&str] = &[
+    ".gsc", ".csc", ".cfg", ".txt", ".csv", ".str", ".vision", ".atr", ".xpo", ".shock", ".arena",
+    ".graph",
+];
+
+pub struct RecoveredAsset {
+    /// Where in the blob the asset's header struct starts.
+    pub offset: u64,
+    /// How many bytes the asset and its data took up.
+    pub len: u64,
+    /// [`XAsset::PC`] if the asset read as little-endian, otherwise
+    /// [`XAsset::Console`].
+    pub asset: XAsset,
+    /// From `0.0` to `1.0`, how likely it is that this really is an asset
+    /// and not data that happens to look like one.
+    pub confidence: f32,
+}
+
+/// Scans `blob` (an inflated Fastfile, or any part of one) for assets. See
+/// the [module docs](self).
+///
+/// Candidates are tried in order, first as little-endian and then as
+/// big-endian. Once one is recovered, scanning picks up after its end, so
+/// the returned assets never overlap.
+pub fn scan(blob: &[u8]) -> Vec<RecoveredAsset> {
+    let mut recovered = Vec::new();
+    let mut pos = 0;
+
+    while pos + INLINE.len() <= blob.len() {
+        if blob[pos..pos + INLINE.len()] != INLINE {
+            pos += 1;
+            continue;
+        }
+
+        let found = [XFilePlatform::Windows, XFilePlatform::Xbox360]
+            .into_iter()
+            .find_map(|platform| try_candidates(blob, pos, platform));
+        match found {
+            Some(asset) => {
+                pos += asset.len as usize;
+                recovered.push(asset);
+            }
+            None => pos += 1,
+        }
+    }
+
+    recovered
+}
+
+fn try_candidates(blob: &[u8], pos: usize, platform: XFilePlatform) -> Option<RecoveredAsset> {
+    let opts = BincodeOptions::from_platform(platform);
+    let u32_at = |off: usize| -> Option<u32> {
+        let bytes = blob.get(pos + off..pos + off + 4)?.try_into().ok()?;
+        Some(if platform.is_le() {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+    let inline_at = |off: usize| blob.get(pos + off..pos + off + 4) == Some(&INLINE[..]);
+
+    // RawFile: name, len, buffer, then the name and `len + 1` bytes.
+    if inline_at(8)
+        && let Some(len) = u32_at(4)
+        && (1..MAX_ASSET_BYTES as u32).contains(&len)
+        && c_str_at(blob, pos + 12).is_some_and(is_file_name)
+        && let Some(asset) = try_deserialize(blob, pos, XAssetType::RAWFILE, &opts, platform)
+        && let Some(confidence) = rawfile_confidence(&asset.asset)
+    {
+        return Some(RecoveredAsset {
+            confidence,
+            ..asset
+        });
+    }
+
+    // StringTable: name, columns, rows, values, cell index, then the name.
+    if let (Some(columns), Some(rows)) = (u32_at(4), u32_at(8))
+        && columns != 0
+        && rows != 0
+        && (columns as u64 * rows as u64) <= i16::MAX as u64
+        && inline_at(12)
+        && (inline_at(16) || u32_at(16) == Some(0))
+        && c_str_at(blob, pos + 20).is_some_and(is_file_name)
+        && let Some(asset) = try_deserialize(blob, pos, XAssetType::STRINGTABLE, &opts, platform)
+        && let Some(confidence) = string_table_confidence(&asset.asset)
+    {
+        return Some(RecoveredAsset {
+            confidence,
+            ..asset
+        });
+    }
+
+    // LocalizeEntry: value, name, then the value and the name.
+    if inline_at(4)
+        && let Some(value) = c_str_at(blob, pos + 8)
+        && c_str_at(blob, pos + 8 + value.len() + 1).is_some_and(is_localize_key)
+        && let Some(asset) = try_deserialize(blob, pos, XAssetType::LOCALIZE_ENTRY, &opts, platform)
+        && let Some(confidence) = localize_confidence(&asset.asset)
+    {
+        return Some(RecoveredAsset {
+            confidence,
+            ..asset
+        });
+    }
+
+    None
+}
+
+/// Deserializes an asset of type `asset_type` at `pos` in isolation. The
+/// confidence is left at `0.0` for the caller to fill in.
+fn try_deserialize(
+    blob: &[u8],
+    pos: usize,
+    asset_type: XAssetType,
+    opts: &BincodeOptions,
+    platform: XFilePlatform,
+) -> Option<RecoveredAsset> {
+    let end = (pos + MAX_ASSET_BYTES).min(blob.len());
+    let mut reader = WindowReader {
+        cursor: Cursor::new(&blob[pos..end]),
+        opts: opts.clone(),
+    };
+    let xasset_raw = XAssetRaw {
+        asset_type: asset_type as u32,
+        asset_data: Ptr32::unreal(),
+    };
+
+    let asset = catch_unwind(AssertUnwindSafe(|| {
+        XAsset::try_get(&mut reader, xasset_raw, platform)
+    }))
+    .ok()?
+    .ok()?;
+
+    Some(RecoveredAsset {
+        offset: pos as u64,
+        len: reader.cursor.position(),
+        asset,
+        confidence: 0.0,
+    })
+}
+
+/// [`None`] if the asset doesn't hold up to a closer look.
+fn rawfile_confidence(asset: &XAsset) -> Option<f32> {
+    let rawfile = match asset {
+        XAsset::PC(XAssetGeneric::RawFile(Some(f)))
+        | XAsset::Console(XAssetGeneric::RawFile(Some(f))) => f,
+        _ => return None,
+    };
+    if rawfile.buffer.last() != Some(&0) {
+        return None;
+    }
+
+    let name = rawfile.name.get();
+    let mut confidence = 0.5;
+    if RAWFILE_EXTENSIONS.iter().any(|ext| name.ends_with(ext)) {
+        confidence += 0.25;
+    }
+    if rawfile.as_str().is_some_and(is_text) {
+        confidence += 0.25;
+    }
+    Some(confidence)
+}
+
+fn string_table_confidence(asset: &XAsset) -> Option<f32> {
+    let table = match asset {
+        XAsset::PC(XAssetGeneric::StringTable(Some(t)))
+        | XAsset::Console(XAssetGeneric::StringTable(Some(t))) => t,
+        _ => return None,
+    };
+    let cells = table.values.len();
+    if table
+        .cell_index
+        .iter()
+        .any(|&i| i < 0 || i as usize >= cells)
+    {
+        return None;
+    }
+
+    let mut confidence = 0.5;
+    if table.name.get().ends_with(".csv") {
+        confidence += 0.25;
+    }
+    if table
+        .values
+        .iter()
+        .all(|c| c.hash == StringTable::hash(c.name.get()))
+    {
+        confidence += 0.25;
+    }
+    Some(confidence)
+}
+
+fn localize_confidence(asset: &XAsset) -> Option<f32> {
+    let entry = match asset {
+        XAsset::PC(XAssetGeneric::LocalizeEntry(Some(e)))
+        | XAsset::Console(XAssetGeneric::LocalizeEntry(Some(e))) => e,
+        _ => return None,
+    };
+
+    let name = entry.name.get();
+    let mut confidence = 0.5;
+    if name.trim_matches('_').contains('_') {
+        confidence += 0.25;
+    }
+    if !entry.value.get().is_empty() && is_text(entry.value.get()) {
+        confidence += 0.25;
+    }
+    Some(confidence)
+}
+
+/// The null-terminated string at `pos`, if it's valid UTF-8 and no longer
+/// than [`MAX_NAME_LEN`].
+fn c_str_at(blob: &[u8], pos: usize) -> Option<&str> {
+    let bytes = blob.get(pos..)?;
+    let len = bytes.iter().take(MAX_NAME_LEN + 1).position(|&b| b == 0)?;
+    core::str::from_utf8(&bytes[..len]).ok()
+}
+
+/// Whether `s` looks like an asset path, e.g. `maps/mp/_load.gsc`.
+fn is_file_name(s: &str) -> bool {
+    !s.is_empty()
+        && s.contains(['/', '\\', '.'])
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./\\".contains(c))
+}
+
+/// Whether `s` looks like a localization key, e.g. `WEAPON_AK47`.
+fn is_localize_key(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn is_text(s: &str) -> bool {
+    s.chars().all(|c| !c.is_control() || c.is_whitespace())
+}
+
+/// Reads from a window of the blob, which is all a candidate is allowed to
+/// take up. There are no script strings, since those are stored with the
+/// (damaged) asset list.
+struct WindowReader<'b> {
+    cursor: Cursor<&'b [u8]>,
+    opts: BincodeOptions,
+}
+
+impl<'b> T5XFileDeserialize for WindowReader<'b> {
+    fn stream_pos(&mut self) -> Result<u64> {
+        Ok(self.cursor.position())
+    }
+
+    fn stream_len(&mut self) -> Result<u64> {
+        Ok(self.cursor.get_ref().len() as _)
+    }
+
+    fn load_from_xfile<T: serde::de::DeserializeOwned>(&mut self) -> Result<T> {
+        let pos = self.cursor.position();
+        self.opts
+            .deserialize_from(&mut self.cursor)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), pos as _, ErrorKind::Bincode(e)))
+    }
+
+    fn get_script_string(&self, _string: ScriptString) -> Result<Option<&str>> {
+        Ok(None)
+    }
+}
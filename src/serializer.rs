@@ -1,21 +1,44 @@
 use serde::Serialize;
 
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     io::{Cursor, Seek, Write},
 };
 
 use crate::{BincodeOptions, file_line_col};
 
 use t5_xfile_defs::{
-    Error, ErrorKind, FatPointerCountFirstU32, Ptr32, Result, ScriptString, T5XFileSerialize,
-    XFile, XFileHeader, XFilePlatform, XFileSerialize,
-    xasset::{XAsset, XAssetListRaw},
+    CompressionMethod, Error, ErrorKind, Result, ScriptString, T5XFileSerialize, XFile,
+    XFileHeader, XFilePlatform,
+    layout::LayoutOverrides,
+    util::{FatPointerCountFirstU32, Ptr32, XFileSerialize, XStringRaw},
+    xasset::{XAsset, XAssetListRaw, XAssetType},
 };
 
+/// Returned by [`T5XFileSerializer::measure`]. All sizes are in bytes.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct SizeBreakdown {
+    pub total: usize,
+    /// Structs stored inline or behind single pointers, including the
+    /// asset's own `XAsset` entry.
+    pub structs: usize,
+    /// String contents, including their terminators.
+    pub strings: usize,
+    /// The elements of pointed-to arrays, except strings.
+    pub arrays: usize,
+}
+
+/// The state of a [`T5XFileSerializer`] that's only measuring.
+struct Measurement {
+    sizes: SizeBreakdown,
+    array_depth: usize,
+}
+
 pub struct T5XFileSerializerBuilder {
     silent: bool,
     platform: XFilePlatform,
+    compression: Option<CompressionMethod>,
+    layout_overrides: LayoutOverrides,
 }
 
 impl T5XFileSerializerBuilder {
@@ -23,6 +46,8 @@ impl T5XFileSerializerBuilder {
         Self {
             platform,
             silent: false,
+            compression: None,
+            layout_overrides: LayoutOverrides::default(),
         }
     }
 
@@ -31,8 +56,26 @@ impl T5XFileSerializerBuilder {
         self
     }
 
+    /// Overrides the platform's usual compression method. Blobs are never
+    /// LZX-compressed, so [`CompressionMethod::Uncompressed`] stores them as
+    /// they are. [`CompressionMethod::Zstd`] needs the `zstd` feature.
+    pub fn with_compression(mut self, method: CompressionMethod) -> Self {
+        self.compression = Some(method);
+        self
+    }
+
+    /// Writes the file with a layout that differs from retail's, for
+    /// builds that expect one. See [`LayoutOverrides`].
+    pub fn with_layout_overrides(mut self, layout_overrides: LayoutOverrides) -> Self {
+        self.layout_overrides = layout_overrides;
+        self
+    }
+
     pub fn build(self) -> Result<T5XFileSerializer> {
-        T5XFileSerializer::new(self.silent, self.platform)
+        let mut ser = T5XFileSerializer::new(self.silent, self.platform)?;
+        ser.compression = self.compression;
+        ser.layout_overrides = self.layout_overrides;
+        Ok(ser)
     }
 }
 
@@ -40,11 +83,23 @@ impl T5XFileSerializerBuilder {
 pub struct T5XFileSerializer {
     silent: bool,
     xfile: XFile,
-    script_strings: HashSet<String>,
+    script_strings: Vec<String>,
+    script_string_indices: HashMap<String, u16>,
+    /// One [`XAssetRaw`](t5_xfile_defs::xasset::XAssetRaw) per serialized
+    /// asset. Kept apart from [`Self::asset_bytes`] since the blob stores
+    /// every entry before any asset's data.
+    asset_table: Vec<u8>,
     asset_bytes: Option<Cursor<Vec<u8>>>,
     serialized_assets: usize,
     opts: BincodeOptions,
     platform: XFilePlatform,
+    compression: Option<CompressionMethod>,
+    layout_overrides: LayoutOverrides,
+    /// [`Some`] if nothing's actually being stored. See [`Self::measure`].
+    measurement: Option<Measurement>,
+    /// Sub-assets written as references to another fastfile's asset. See
+    /// [`T5XFileSerialize::is_external`].
+    pub(crate) external_assets: HashSet<(XAssetType, String)>,
 }
 
 impl<'a> T5XFileSerializer {
@@ -52,20 +107,53 @@ impl<'a> T5XFileSerializer {
         Ok(Self {
             silent,
             xfile: XFile::default(),
-            script_strings: HashSet::new(),
+            script_strings: Vec::new(),
+            script_string_indices: HashMap::new(),
+            asset_table: Vec::new(),
             asset_bytes: None,
             serialized_assets: 0,
             opts: BincodeOptions::from_platform(platform),
             platform,
+            compression: None,
+            layout_overrides: LayoutOverrides::default(),
+            measurement: None,
+            external_assets: HashSet::new(),
         })
     }
 
+    /// Calculates how many bytes `asset` would take up in a serialized blob.
+    ///
+    /// This goes through the same [`XFileSerialize`] impls as
+    /// [`Self::serialize_assets`], except that bytes are only counted, not
+    /// stored, so the numbers match an actual serialization exactly. Script
+    /// strings aren't included, since they're stored in the blob's string
+    /// table and shared between assets.
+    pub fn measure(asset: &XAsset) -> Result<SizeBreakdown> {
+        let platform = if asset.is_pc() {
+            XFilePlatform::Windows
+        } else {
+            XFilePlatform::Xbox360
+        };
+
+        let mut ser = Self::new(true, platform)?;
+        ser.measurement = Some(Measurement {
+            sizes: SizeBreakdown::default(),
+            array_depth: 0,
+        });
+
+        asset.xfile_serialize(&mut ser, ())?;
+        Ok(ser.measurement.unwrap().sizes)
+    }
+
     pub fn serialize_assets<const MAX_LOCAL_CLIENTS: usize>(
         &mut self,
         assets: impl Iterator<Item = XAsset>,
     ) -> Result<()> {
         for asset in assets {
-            asset.xfile_serialize(self, ())?;
+            self.opts
+                .serialize_into(&mut self.asset_table, asset.xasset_raw())
+                .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Bincode(e)))?;
+            asset.serialize_data(self)?;
             self.serialized_assets += 1;
         }
 
@@ -94,13 +182,15 @@ impl<'a> T5XFileSerializer {
 
         let mut bytes = Cursor::new(Vec::new());
         let header = XFileHeader::new(self.platform);
+        let header = match self.compression {
+            Some(c) => header.with_compression(c),
+            None => header,
+        };
 
         self.serialize(&mut bytes, header)?;
 
         let mut blob = Cursor::new(Vec::new());
 
-        self.serialize(&mut blob, self.xfile)?;
-
         if self.serialized_assets == 0 && !self.silent {
             println!("Warning: serializing fastfile with zero assets.");
         }
@@ -116,7 +206,7 @@ impl<'a> T5XFileSerializer {
             },
             assets: FatPointerCountFirstU32 {
                 size: self.serialized_assets as _,
-                p: if asset_bytes_len == 0 {
+                p: if self.serialized_assets == 0 {
                     Ptr32::null()
                 } else {
                     Ptr32::unreal()
@@ -126,29 +216,88 @@ impl<'a> T5XFileSerializer {
 
         self.serialize(&mut blob, xasset_list)?;
 
-        let mut script_string_bytes = Vec::new();
-        for string in self.script_strings.iter() {
-            for c in string.chars() {
-                script_string_bytes.push(c as u8);
-            }
-            script_string_bytes.push(b'\0');
-        }
-
-        self.serialize(&mut blob, script_string_bytes)?;
+        let script_string_table = self.script_string_table()?;
+        blob.write_all(&script_string_table)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+        blob.write_all(&self.asset_table)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
         let asset_bytes = self.asset_bytes.take().unwrap_or_default().into_inner();
-        self.serialize(&mut blob, asset_bytes)?;
-
-        let deflated_blob = deflate::deflate_bytes_zlib(&blob.into_inner());
+        blob.write_all(&asset_bytes)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+
+        // `XFile::size` doesn't include the `XFile` itself
+        let blob = blob.into_inner();
+        let xfile = XFile {
+            size: blob.len() as _,
+            ..self.xfile
+        };
+        let mut xfile_bytes = Cursor::new(Vec::new());
+        self.serialize(&mut xfile_bytes, xfile)?;
+        let blob = [xfile_bytes.into_inner(), blob].concat();
+
+        // LZX isn't supported, so console blobs are stored uncompressed
+        let deflated_blob = match header.compression_method() {
+            Some(CompressionMethod::Deflate) => deflate::deflate_bytes_zlib(&blob),
+            #[cfg(feature = "zstd")]
+            Some(CompressionMethod::Zstd) => zstd::stream::encode_all(&*blob, 0).map_err(|e| {
+                Error::new_with_offset(file_line_col!(), 0, ErrorKind::Zstd(e.to_string()))
+            })?,
+            #[cfg(not(feature = "zstd"))]
+            Some(CompressionMethod::Zstd) => {
+                return Err(Error::new_with_offset(
+                    file_line_col!(),
+                    4,
+                    ErrorKind::UnsupportedCompression(CompressionMethod::Zstd.as_byte()),
+                ));
+            }
+            _ => blob,
+        };
 
         let mut bytes = bytes.into_inner();
         bytes.extend_from_slice(&deflated_blob);
 
         Ok(bytes)
     }
+
+    /// Builds the script string table pointed to by
+    /// [`XAssetListRaw::strings`]: one [`XStringRaw`] per string, followed by
+    /// each non-empty string's bytes. This is the only place the table is
+    /// written.
+    fn script_string_table(&mut self) -> Result<Vec<u8>> {
+        let mut table = Cursor::new(Vec::new());
+
+        for string in self.script_strings.clone() {
+            self.serialize(&mut table, XStringRaw::from_str(&string))?;
+        }
+
+        let mut table = table.into_inner();
+        for string in self.script_strings.iter().filter(|s| !s.is_empty()) {
+            // `intern` only lets in Latin-1, so every char fits in a byte.
+            table.extend(string.chars().map(|c| c as u8));
+            table.push(b'\0');
+        }
+
+        Ok(table)
+    }
 }
 
 impl T5XFileSerialize for T5XFileSerializer {
     fn store_into_xfile<T: Serialize>(&mut self, t: T) -> Result<()> {
+        if let Some(m) = &mut self.measurement {
+            let size =
+                self.opts.serialized_size(t).map_err(|e| {
+                    Error::new_with_offset(file_line_col!(), 0, ErrorKind::Bincode(e))
+                })? as usize;
+
+            m.sizes.total += size;
+            if m.array_depth > 0 {
+                m.sizes.arrays += size;
+            } else {
+                m.sizes.structs += size;
+            }
+            return Ok(());
+        }
+
         self.opts
             .serialize_into(self.asset_bytes.get_or_insert(Cursor::new(Vec::new())), t)
             .map_err(|e| {
@@ -160,27 +309,34 @@ impl T5XFileSerialize for T5XFileSerializer {
             })
     }
 
-    fn get_or_insert_script_string(&mut self, string: &str) -> Result<ScriptString> {
-        if self.script_strings.len() >= u16::MAX as usize {
-            Err(Error::new_with_offset(
+    fn intern(&mut self, string: &str) -> Result<ScriptString> {
+        if let Some(&i) = self.script_string_indices.get(string) {
+            return Ok(ScriptString(i));
+        }
+
+        // Script strings are stored one byte per char.
+        if let Some(c) = string.chars().find(|&c| c as u32 > 0xFF) {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                0,
+                ErrorKind::BadChar(c as _),
+            ));
+        }
+
+        // Every index a `u16` can hold is valid, 0xFFFF included, so the
+        // table can hold 65,536 strings. That's also the deserializer's
+        // default `ResourceLimits::max_script_strings`.
+        let Ok(i) = u16::try_from(self.script_strings.len()) else {
+            return Err(Error::new_with_offset(
                 file_line_col!(),
                 0,
                 ErrorKind::ScriptStringOverflow,
-            ))
-        } else {
-            let string = string.to_owned();
-            if !self.script_strings.contains(&string) {
-                self.script_strings.insert(string.clone());
-            }
+            ));
+        };
 
-            let (i, _) = self
-                .script_strings
-                .iter()
-                .enumerate()
-                .find(|(_, &ref s)| s.as_str() == string.as_str())
-                .unwrap();
-            Ok(ScriptString(i as _))
-        }
+        self.script_strings.push(string.to_owned());
+        self.script_string_indices.insert(string.to_owned(), i);
+        Ok(ScriptString(i))
     }
 
     fn script_strings(&self) -> Vec<&str> {
@@ -194,4 +350,38 @@ impl T5XFileSerialize for T5XFileSerializer {
     fn asset_bytes(&self) -> Option<&[u8]> {
         self.asset_bytes.as_ref().map(|a| &**a.get_ref())
     }
+
+    fn store_string_into_xfile(&mut self, bytes: &[u8]) -> Result<()> {
+        if let Some(m) = &mut self.measurement {
+            m.sizes.total += bytes.len();
+            m.sizes.strings += bytes.len();
+            return Ok(());
+        }
+
+        self.asset_bytes
+            .get_or_insert(Cursor::new(Vec::new()))
+            .write_all(bytes)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))
+    }
+
+    fn begin_array(&mut self) {
+        if let Some(m) = &mut self.measurement {
+            m.array_depth += 1;
+        }
+    }
+
+    fn end_array(&mut self) {
+        if let Some(m) = &mut self.measurement {
+            m.array_depth -= 1;
+        }
+    }
+
+    fn layout_overrides(&self) -> LayoutOverrides {
+        self.layout_overrides
+    }
+
+    fn is_external(&self, asset_type: XAssetType, name: &str) -> bool {
+        self.external_assets
+            .contains(&(asset_type, name.to_string()))
+    }
 }
@@ -0,0 +1,227 @@
+//! A JSON manifest of a [`SndBank`]'s aliases, for audio tools to edit and
+//! re-inject.
+//!
+//! The manifest is an array with one object per alias, in the order they're
+//! in the bank:
+//!
+//! | Key              | Value                                          |
+//! |------------------|------------------------------------------------|
+//! | `name`           | [`SndAlias::name`]                             |
+//! | `file`           | [`SndAlias::file_name`], or `null`             |
+//! | `group`          | [`SndAlias::snapshot_group`]                   |
+//! | `min_dist`       | [`SndAlias::dist_min`]                         |
+//! | `max_dist`       | [`SndAlias::dist_max`]                         |
+//! | `volume`         | [`SndAlias::vol_max`], from `0.0` to `1.0`     |
+//! | `pitch`          | [`SndAlias::pitch_max`], as stored             |
+//! | `randomize_type` | [`SndAlias::randomize_type`]                   |
+//! | `loop_`          | [`SndAlias::is_looping`]                       |
+//! | `is_music`       | [`SndAlias::is_music`]                         |
+//! | `reverb`         | [`SndAlias::radverb_send`], as stored          |
+//! | `spatial_type`   | `"2d"` or `"3d"` (see [`SndAlias::spatial_type`]) |
+
+use serde_json::{Map, Value, json};
+use t5_xfile_defs::{
+    Error, ErrorKind, Result, XString,
+    sound::{SndAlias, SndAliasList, SndBank, SoundFile, SoundFileRef, StreamedSound},
+};
+
+use crate::file_line_col;
+
+/// The manifest of `bank`'s aliases. See the [module docs](self).
+pub fn to_json_manifest(bank: &SndBank) -> Value {
+    bank.aliases
+        .iter()
+        .flat_map(|l| &l.aliases)
+        .map(|a| {
+            json!({
+                "name": a.name.get(),
+                "file": a.file_name(),
+                "group": a.snapshot_group,
+                "min_dist": a.dist_min,
+                "max_dist": a.dist_max,
+                "volume": a.vol_max as f64 / u16::MAX as f64,
+                "pitch": a.pitch_max,
+                "randomize_type": a.randomize_type(),
+                "loop_": a.is_looping(),
+                "is_music": a.is_music(),
+                "reverb": a.radverb_send,
+                "spatial_type": if a.spatial_type() == 0 { "2d" } else { "3d" },
+            })
+        })
+        .collect()
+}
+
+/// Builds a bank from a manifest made by [`to_json_manifest`].
+///
+/// Only what's in the manifest comes back: consecutive aliases with the
+/// same name are put in one [`SndAliasList`], `vol_min` and `pitch_min` are
+/// set to the same as their maximums, and files become [`StreamedSound`]s.
+/// The bank's name, alias IDs, and alias index are left empty. Every key
+/// but `name` is optional.
+pub fn from_json_manifest(v: &Value) -> Result<SndBank> {
+    let entries = v
+        .as_array()
+        .ok_or_else(|| bad_manifest("not an array".to_string()))?;
+
+    let mut aliases = Vec::<SndAliasList>::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let alias = alias_from_json(entry).map_err(|why| bad_manifest(format!("[{i}]: {why}")))?;
+
+        match aliases.last_mut() {
+            Some(list) if list.name.get() == alias.name.get() => list.aliases.push(alias),
+            _ => aliases.push(SndAliasList {
+                name: alias.name.clone(),
+                aliases: vec![alias],
+                ..Default::default()
+            }),
+        }
+    }
+
+    Ok(SndBank {
+        aliases,
+        ..Default::default()
+    })
+}
+
+fn alias_from_json(entry: &Value) -> core::result::Result<SndAlias, String> {
+    let entry = entry.as_object().ok_or("not an object")?;
+    let name = entry.get("name").and_then(Value::as_str).ok_or("no name")?;
+
+    let file = match entry.get("file") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(file)) => Some(Box::new(SoundFile {
+            u: SoundFileRef::Streamed(Some(Box::new(StreamedSound {
+                filename: XString::from(file.clone()),
+                prime_snd: None,
+            }))),
+            exists: true,
+        })),
+        Some(_) => return Err("file isn't a string".to_string()),
+    };
+
+    let volume = get(entry, "volume", Value::as_f64)?.unwrap_or_default();
+    let volume = (volume.clamp(0.0, 1.0) * u16::MAX as f64).round() as u16;
+    let pitch = get_int(entry, "pitch")?;
+
+    let mut flags = 0;
+    if get(entry, "loop_", Value::as_bool)?.unwrap_or_default() {
+        flags |= SndAlias::FLAG_LOOPING;
+    }
+    if get(entry, "is_music", Value::as_bool)?.unwrap_or_default() {
+        flags |= SndAlias::FLAG_MUSIC;
+    }
+    match get(entry, "spatial_type", Value::as_str)? {
+        None | Some("2d") => {}
+        Some("3d") => flags |= SndAlias::FLAG_3D,
+        Some(t) => return Err(format!("unknown spatial_type \"{t}\"")),
+    }
+    let randomize_type: u32 = get_int(entry, "randomize_type")?;
+    if randomize_type > 3 {
+        return Err(format!("randomize_type {randomize_type} is out of range"));
+    }
+    flags |= randomize_type << SndAlias::RANDOMIZE_TYPE_SHIFT;
+
+    Ok(SndAlias {
+        name: XString::from(name.to_string()),
+        sound_file: file,
+        flags,
+        radverb_send: get_int(entry, "reverb")?,
+        vol_min: volume,
+        vol_max: volume,
+        pitch_min: pitch,
+        pitch_max: pitch,
+        dist_min: get_int(entry, "min_dist")?,
+        dist_max: get_int(entry, "max_dist")?,
+        snapshot_group: get_int(entry, "group")?,
+        ..Default::default()
+    })
+}
+
+/// `key`'s value converted with `f`, [`None`] if it's missing, or an error
+/// if it can't be converted.
+fn get<'a, T>(
+    entry: &'a Map<String, Value>,
+    key: &str,
+    f: impl FnOnce(&'a Value) -> Option<T>,
+) -> core::result::Result<Option<T>, String> {
+    match entry.get(key) {
+        None => Ok(None),
+        Some(v) => f(v).map(Some).ok_or_else(|| format!("bad {key} {v}")),
+    }
+}
+
+/// Like [`get`] for integers, defaulting to `0`.
+fn get_int<T: TryFrom<u64> + Default>(
+    entry: &Map<String, Value>,
+    key: &str,
+) -> core::result::Result<T, String> {
+    get(entry, key, |v| v.as_u64().and_then(|v| T::try_from(v).ok())).map(Option::unwrap_or_default)
+}
+
+fn bad_manifest(why: String) -> Error {
+    Error::new(
+        file_line_col!(),
+        ErrorKind::BrokenInvariant(format!("sound: bad manifest: {why}")),
+    )
+}
+
+/// A JSON Patch (RFC 6902) that turns manifest `a` into manifest `b`.
+///
+/// Objects are compared key by key and arrays index by index, so an alias
+/// inserted in the middle of `b` shows up as changes to every alias after
+/// it. Only `add`, `remove`, and `replace` operations are used. Applying
+/// the operations in order to `a` gives `b`.
+pub fn diff_manifests(a: &Value, b: &Value) -> Value {
+    let mut ops = Vec::new();
+    diff(a, b, &mut String::new(), &mut ops);
+    Value::Array(ops)
+}
+
+fn diff(a: &Value, b: &Value, path: &mut String, ops: &mut Vec<Value>) {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, a) in a {
+                let len = push_token(path, key);
+                match b.get(key) {
+                    Some(b) => diff(a, b, path, ops),
+                    None => ops.push(json!({ "op": "remove", "path": path })),
+                }
+                path.truncate(len);
+            }
+            for (key, b) in b.iter().filter(|(k, _)| !a.contains_key(*k)) {
+                let len = push_token(path, key);
+                ops.push(json!({ "op": "add", "path": path, "value": b }));
+                path.truncate(len);
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                let len = push_token(path, &i.to_string());
+                diff(a, b, path, ops);
+                path.truncate(len);
+            }
+            // Removed from the back so that the indices stay valid.
+            for i in (b.len()..a.len()).rev() {
+                let len = push_token(path, &i.to_string());
+                ops.push(json!({ "op": "remove", "path": path }));
+                path.truncate(len);
+            }
+            for b in b.iter().skip(a.len()) {
+                let len = push_token(path, "-");
+                ops.push(json!({ "op": "add", "path": path, "value": b }));
+                path.truncate(len);
+            }
+        }
+        (a, b) if a != b => ops.push(json!({ "op": "replace", "path": path, "value": b })),
+        _ => {}
+    }
+}
+
+/// Appends `token` to the JSON Pointer `path`, escaped, and returns the
+/// length `path` had before.
+fn push_token(path: &mut String, token: &str) -> usize {
+    let len = path.len();
+    path.push('/');
+    path.push_str(&token.replace('~', "~0").replace('/', "~1"));
+    len
+}
@@ -0,0 +1,494 @@
+//! Field-by-field annotation of the raw structs that assets start with, for
+//! tracking down which field of a layout is misaligned.
+//!
+//! Only the fixed-size struct at the start of each asset is annotated, not
+//! the data its pointers lead to, and only the retail layout is described
+//! (see [`LayoutOverrides`](crate::layout::LayoutOverrides)). The layouts
+//! are maintained by hand, with their sizes checked against the raw structs
+//! the deserializer uses.
+
+use core::fmt::{Display, Write};
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    Error, ErrorKind, Result, XFileOffset, XFilePlatform, file_line_col,
+    font::FontRaw,
+    light::GfxLightDefRaw,
+    misc::{LocalizeEntryRaw, MapEntsRaw, PackIndexRaw, RawFileRaw, StringTableRaw, XGlobalsRaw},
+    techset::{GfxImageRaw, MaterialRaw, MaterialTechniqueSetRaw},
+    xanim::XAnimPartsRaw,
+    xasset::XAssetType,
+    xmodel::PhysPresetRaw,
+};
+
+/// How a field's bytes are decoded.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FieldKind {
+    U8,
+    Bool,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    F32,
+    /// A `Ptr32`, an `XStringRaw`, or the pointer half of a fat pointer.
+    Ptr,
+}
+
+impl FieldKind {
+    pub const fn size(self) -> usize {
+        match self {
+            Self::U8 | Self::Bool => 1,
+            Self::U16 | Self::I16 => 2,
+            Self::U32 | Self::I32 | Self::F32 | Self::Ptr => 4,
+            Self::U64 => 8,
+        }
+    }
+}
+
+/// One field of a [`StructLayout`]. Arrays are a single field with a
+/// `count` greater than 1.
+#[derive(Copy, Clone, Debug)]
+pub struct Field {
+    pub name: &'static str,
+    /// The field's type as declared in the raw struct.
+    pub ty: &'static str,
+    pub kind: FieldKind,
+    pub count: usize,
+}
+
+impl Field {
+    const fn new(name: &'static str, ty: &'static str, kind: FieldKind) -> Self {
+        Self::array(name, ty, kind, 1)
+    }
+
+    const fn array(name: &'static str, ty: &'static str, kind: FieldKind, count: usize) -> Self {
+        Self {
+            name,
+            ty,
+            kind,
+            count,
+        }
+    }
+
+    pub const fn size(&self) -> usize {
+        self.kind.size() * self.count
+    }
+}
+
+/// The fields of a raw struct, in the order they're stored.
+#[derive(Copy, Clone, Debug)]
+pub struct StructLayout {
+    pub name: &'static str,
+    /// The size of the raw struct itself, which the fields should add up
+    /// to.
+    pub size: usize,
+    pub fields: &'static [Field],
+}
+
+impl StructLayout {
+    /// Each field along with its offset from the start of the struct.
+    pub fn offsets(&self) -> impl Iterator<Item = (usize, &Field)> {
+        self.fields.iter().scan(0, |off, f| {
+            let start = *off;
+            *off += f.size();
+            Some((start, f))
+        })
+    }
+
+    /// The sum of the fields' sizes.
+    pub fn fields_size(&self) -> usize {
+        self.fields.iter().map(Field::size).sum()
+    }
+}
+
+use FieldKind::*;
+
+const fn xstring(name: &'static str) -> Field {
+    Field::new(name, "XStringRaw", Ptr)
+}
+
+static PHYS_PRESET: StructLayout = StructLayout {
+    name: "PhysPresetRaw",
+    size: size_of::<PhysPresetRaw>(),
+    fields: &[
+        xstring("name"),
+        Field::new("flags", "i32", I32),
+        Field::new("mass", "f32", F32),
+        Field::new("bounce", "f32", F32),
+        Field::new("friction", "f32", F32),
+        Field::new("bullet_force_scale", "f32", F32),
+        Field::new("explosive_force_scale", "f32", F32),
+        xstring("snd_alias_prefix"),
+        Field::new("pieces_spread_fraction", "f32", F32),
+        Field::new("pieces_upward_velocity", "f32", F32),
+        Field::new("can_float", "i32", I32),
+        Field::new("gravity_scale", "f32", F32),
+        Field::array("center_of_mass_offset", "[f32; 3]", F32, 3),
+        Field::array("buoyancy_box_min", "[f32; 3]", F32, 3),
+        Field::array("buoyancy_box_max", "[f32; 3]", F32, 3),
+    ],
+};
+
+static XANIM_PARTS: StructLayout = StructLayout {
+    name: "XAnimPartsRaw",
+    size: size_of::<XAnimPartsRaw>(),
+    fields: &[
+        xstring("name"),
+        Field::new("data_byte_count", "u16", U16),
+        Field::new("data_short_count", "u16", U16),
+        Field::new("data_int_count", "u16", U16),
+        Field::new("random_data_byte_count", "u16", U16),
+        Field::new("random_data_int_count", "u16", U16),
+        Field::new("numframes", "u16", U16),
+        Field::new("loop_", "bool", Bool),
+        Field::new("delta", "bool", Bool),
+        Field::new("left_hand_grip_ik", "bool", Bool),
+        Field::new("streamable", "bool", Bool),
+        Field::new("streamed_file_size", "u32", U32),
+        Field::array("bone_count", "[u8; 10]", U8, 10),
+        Field::new("notify_count", "u8", U8),
+        Field::new("asset_type", "u8", U8),
+        Field::new("is_default", "bool", Bool),
+        Field::array("pad", "[u8; 3]", U8, 3),
+        Field::new("random_data_short_count", "u32", U32),
+        Field::new("index_count", "u32", U32),
+        Field::new("framerate", "f32", F32),
+        Field::new("frequency", "f32", F32),
+        Field::new("primed_length", "f32", F32),
+        Field::new("loop_entry_time", "f32", F32),
+        Field::new("names", "Ptr32<ScriptString>", Ptr),
+        Field::new("data_byte", "Ptr32<u8>", Ptr),
+        Field::new("data_short", "Ptr32<i16>", Ptr),
+        Field::new("data_int", "Ptr32<i32>", Ptr),
+        Field::new("random_data_short", "Ptr32<i16>", Ptr),
+        Field::new("random_data_byte", "Ptr32<u8>", Ptr),
+        Field::new("random_data_int", "Ptr32<i32>", Ptr),
+        Field::new("indices", "XAnimIndicesRaw", Ptr),
+        Field::new("notify", "Ptr32<XAnimNotifyInfoRaw>", Ptr),
+        Field::new("delta_part", "Ptr32<XAnimDeltaPartRaw>", Ptr),
+    ],
+};
+
+static MATERIAL: StructLayout = StructLayout {
+    name: "MaterialRaw",
+    size: size_of::<MaterialRaw>(),
+    fields: &[
+        xstring("info.name"),
+        Field::new("info.game_flags", "u32", U32),
+        Field::new("info.pad", "u8", U8),
+        Field::new("info.sort_key", "u8", U8),
+        Field::new("info.texture_atlas_row_count", "u8", U8),
+        Field::new("info.texture_atlas_column_count", "u8", U8),
+        Field::array("info.pad2", "[u8; 4]", U8, 4),
+        Field::new("info.draw_surf", "GfxDrawSurf", U64),
+        Field::new("info.surface_type_bits", "u32", U32),
+        Field::new("info.layered_surface_types", "u32", U32),
+        Field::new("info.hash_index", "u16", U16),
+        Field::array("info.unused", "[u8; 6]", U8, 6),
+        Field::array("state_bits_entry", "[u8; 130]", U8, 130),
+        Field::new("texture_count", "u8", U8),
+        Field::new("constant_count", "u8", U8),
+        Field::new("state_bits_count", "u8", U8),
+        Field::new("state_flags", "u8", U8),
+        Field::new("camera_region", "u8", U8),
+        Field::new("max_streamed_mips", "u8", U8),
+        Field::new("technique_set", "Ptr32<MaterialTechniqueSetRaw>", Ptr),
+        Field::new("texture_table", "Ptr32<MaterialTextureDefRaw>", Ptr),
+        Field::new("constant_table", "Ptr32<MaterialConstantDef>", Ptr),
+        Field::new("state_bits_table", "Ptr32<GfxStateBits>", Ptr),
+    ],
+};
+
+static TECHNIQUE_SET: StructLayout = StructLayout {
+    name: "MaterialTechniqueSetRaw",
+    size: size_of::<MaterialTechniqueSetRaw>(),
+    fields: &[
+        xstring("name"),
+        Field::new("world_vert_format", "u8", U8),
+        Field::new("unused", "u8", U8),
+        Field::new("techset_flags", "u16", U16),
+        Field::array("techniques", "[Ptr32<MaterialTechniqueRaw>; 130]", Ptr, 130),
+    ],
+};
+
+static IMAGE: StructLayout = StructLayout {
+    name: "GfxImageRaw",
+    size: size_of::<GfxImageRaw>(),
+    fields: &[
+        Field::new("texture", "GfxTextureRaw", Ptr),
+        Field::new("map_type", "u8", U8),
+        Field::new("semantic", "u8", U8),
+        Field::new("category", "u8", U8),
+        Field::new("delay_load_pixels", "bool", Bool),
+        Field::array("picmip", "Picmip", U8, 2),
+        Field::new("no_picmip", "bool", Bool),
+        Field::new("track", "u8", U8),
+        Field::array("card_memory", "CardMemory", U32, 2),
+        Field::new("width", "u16", U16),
+        Field::new("height", "u16", U16),
+        Field::new("depth", "u16", U16),
+        Field::new("level_count", "u8", U8),
+        Field::new("streaming", "bool", Bool),
+        Field::new("base_size", "u32", U32),
+        Field::new("pixels", "Ptr32<u8>", Ptr),
+        Field::new("loaded_size", "u32", U32),
+        Field::new("skipped_mip_levels", "u8", U8),
+        Field::array("pad", "[u8; 3]", U8, 3),
+        xstring("name"),
+        Field::new("hash", "u32", U32),
+    ],
+};
+
+static LIGHT_DEF: StructLayout = StructLayout {
+    name: "GfxLightDefRaw",
+    size: size_of::<GfxLightDefRaw>(),
+    fields: &[
+        xstring("name"),
+        Field::new("attenuation.image", "Ptr32<GfxImageRaw>", Ptr),
+        Field::new("attenuation.sampler_state", "u8", U8),
+        Field::array("attenuation.pad", "[u8; 3]", U8, 3),
+        Field::new("lmap_lookup_start", "i32", I32),
+    ],
+};
+
+static LOCALIZE_ENTRY: StructLayout = StructLayout {
+    name: "LocalizeEntryRaw",
+    size: size_of::<LocalizeEntryRaw>(),
+    fields: &[xstring("value"), xstring("name")],
+};
+
+static RAW_FILE: StructLayout = StructLayout {
+    name: "RawFileRaw",
+    size: size_of::<RawFileRaw>(),
+    fields: &[
+        xstring("name"),
+        Field::new("len", "i32", I32),
+        Field::new("buffer", "Ptr32<u8>", Ptr),
+    ],
+};
+
+static STRING_TABLE: StructLayout = StructLayout {
+    name: "StringTableRaw",
+    size: size_of::<StringTableRaw>(),
+    fields: &[
+        xstring("name"),
+        Field::new("column_count", "i32", I32),
+        Field::new("row_count", "i32", I32),
+        Field::new("values", "Ptr32<StringTableCellRaw>", Ptr),
+        Field::new("cell_index", "Ptr32<i16>", Ptr),
+    ],
+};
+
+static FONT: StructLayout = StructLayout {
+    name: "FontRaw",
+    size: size_of::<FontRaw>(),
+    fields: &[
+        xstring("font_name"),
+        Field::new("pixel_height", "i32", I32),
+        Field::new("glyph_count", "i32", I32),
+        Field::new("material", "Ptr32<MaterialRaw>", Ptr),
+        Field::new("glow_material", "Ptr32<MaterialRaw>", Ptr),
+        Field::new("glyphs", "Ptr32<Glyph>", Ptr),
+    ],
+};
+
+static MAP_ENTS: StructLayout = StructLayout {
+    name: "MapEntsRaw",
+    size: size_of::<MapEntsRaw>(),
+    fields: &[
+        xstring("name"),
+        Field::new("entity_string.p", "Ptr32<u8>", Ptr),
+        Field::new("entity_string.size", "u32", U32),
+    ],
+};
+
+static PACK_INDEX: StructLayout = StructLayout {
+    name: "PackIndexRaw",
+    size: size_of::<PackIndexRaw>(),
+    fields: &[
+        xstring("name"),
+        Field::new("header.magic", "u32", U32),
+        Field::new("header.timestamp", "u32", U32),
+        Field::new("header.count", "u32", U32),
+        Field::new("header.alignment", "u32", U32),
+        Field::new("header.data_start", "u32", U32),
+        Field::new("entries", "Ptr32<PackIndexEntryRaw>", Ptr),
+    ],
+};
+
+static XGLOBALS: StructLayout = StructLayout {
+    name: "XGlobalsRaw",
+    size: size_of::<XGlobalsRaw>(),
+    fields: &[
+        xstring("name"),
+        Field::new("xanim_stream_buffer_size", "i32", I32),
+        Field::new("cinematic_max_width", "i32", I32),
+        Field::new("cinematic_max_height", "i32", I32),
+        Field::new("extracam_resolution", "i32", I32),
+        Field::new("gump_reserve", "i32", I32),
+        Field::array("screen_clear_color", "[f32; 4]", F32, 4),
+    ],
+};
+
+/// The layout of the raw struct that assets of type `asset_type` start
+/// with, if there's a table for it.
+pub fn layout_for(asset_type: XAssetType) -> Option<&'static StructLayout> {
+    Some(match asset_type {
+        XAssetType::PHYSPRESET => &PHYS_PRESET,
+        XAssetType::XANIMPARTS => &XANIM_PARTS,
+        XAssetType::MATERIAL => &MATERIAL,
+        XAssetType::TECHNIQUE_SET => &TECHNIQUE_SET,
+        XAssetType::IMAGE => &IMAGE,
+        XAssetType::LIGHT_DEF => &LIGHT_DEF,
+        XAssetType::LOCALIZE_ENTRY => &LOCALIZE_ENTRY,
+        XAssetType::RAWFILE => &RAW_FILE,
+        XAssetType::STRINGTABLE => &STRING_TABLE,
+        XAssetType::FONT => &FONT,
+        XAssetType::MAP_ENTS => &MAP_ENTS,
+        XAssetType::PACKINDEX => &PACK_INDEX,
+        XAssetType::XGLOBALS => &XGLOBALS,
+        _ => return None,
+    })
+}
+
+/// A field of an asset's raw struct, along with where it was and what it
+/// decoded to.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct AnnotatedField {
+    pub name: String,
+    pub ty: String,
+    /// The field's offset from the start of the struct.
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+    pub value: String,
+}
+
+impl Display for AnnotatedField {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut hex = String::new();
+        for (i, b) in self.bytes.iter().take(16).enumerate() {
+            if i != 0 {
+                hex.push(' ');
+            }
+            let _ = write!(hex, "{b:02X}");
+        }
+        if self.bytes.len() > 16 {
+            hex.push_str(" ...");
+        }
+
+        write!(
+            f,
+            "{:#06X}..{:#06X}  {:<36} {:<28} {:<52} {}",
+            self.offset,
+            self.offset + self.bytes.len(),
+            self.name,
+            self.ty,
+            hex,
+            self.value
+        )
+    }
+}
+
+/// Decodes the raw struct at the start of `bytes`, which should be the
+/// blob bytes of an asset of type `asset_type` whose header is stored
+/// inline.
+///
+/// Fails if there's no layout for `asset_type`, or if `bytes` is too short
+/// to hold its struct.
+pub fn annotate(
+    asset_type: XAssetType,
+    bytes: &[u8],
+    platform: XFilePlatform,
+) -> Result<Vec<AnnotatedField>> {
+    let layout = layout_for(asset_type).ok_or_else(|| {
+        Error::new(
+            file_line_col!(),
+            ErrorKind::Todo(format!("annotate: no layout for {asset_type:?}")),
+        )
+    })?;
+    if bytes.len() < layout.size {
+        return Err(Error::new(
+            file_line_col!(),
+            ErrorKind::UnexpectedEof {
+                needed: layout.size as _,
+                available: bytes.len() as _,
+            },
+        ));
+    }
+
+    let be = platform.is_be();
+    Ok(layout
+        .offsets()
+        .map(|(offset, field)| {
+            let bytes = &bytes[offset..offset + field.size()];
+            let values = bytes
+                .chunks_exact(field.kind.size())
+                .map(|b| decode(field.kind, b, be))
+                .collect::<Vec<_>>();
+            let value = if field.count == 1 {
+                values.into_iter().next().unwrap_or_default()
+            } else {
+                format!("[{}]", values.join(", "))
+            };
+
+            AnnotatedField {
+                name: field.name.to_string(),
+                ty: field.ty.to_string(),
+                offset,
+                bytes: bytes.to_vec(),
+                value,
+            }
+        })
+        .collect())
+}
+
+fn decode(kind: FieldKind, b: &[u8], be: bool) -> String {
+    macro_rules! int {
+        ($t:ty) => {{
+            let b = b.try_into().unwrap();
+            if be {
+                <$t>::from_be_bytes(b)
+            } else {
+                <$t>::from_le_bytes(b)
+            }
+        }};
+    }
+
+    match kind {
+        U8 => b[0].to_string(),
+        Bool => match b[0] {
+            0 => "false".to_string(),
+            1 => "true".to_string(),
+            n => format!("{n} (not a bool)"),
+        },
+        U16 => int!(u16).to_string(),
+        I16 => int!(i16).to_string(),
+        U32 => int!(u32).to_string(),
+        I32 => int!(i32).to_string(),
+        U64 => format!("{:#018X}", int!(u64)),
+        F32 => format!("{:?}", f32::from_bits(int!(u32))),
+        Ptr => match int!(u32) {
+            0 => "null".to_string(),
+            0xFFFFFFFF | 0xFFFFFFFE => "inline (follows)".to_string(),
+            p => {
+                let off = XFileOffset::from_u32(p);
+                format!("block {} + {:#X}", off.block(), off.offset())
+            }
+        },
+    }
+}
@@ -0,0 +1,28 @@
+//! Stopping long-running operations from another thread.
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// A flag that's set once to ask an operation to stop. Clones share the
+/// flag, so one can be handed to the operation and another kept to cancel
+/// it with.
+///
+/// Operations that take a token poll it as they go and return
+/// [`ErrorKind::Cancelled`](crate::ErrorKind::Cancelled) once it's set.
+/// Once cancelled, a token stays cancelled.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
@@ -4,13 +4,17 @@ use alloc::{
     vec::Vec,
 };
 
+use bitflags::bitflags;
+
 use crate::{
-    Error, ErrorKind, FatPointer, FatPointerCountFirstU32, MapEnts, MapEntsRaw, Ptr32, Result,
-    ScriptString, T5XFileDeserialize, XFileDeserializeInto, XString, XStringRaw, assert_size,
+    Error, ErrorKind, FatPointer, Result, ScriptString, T5XFileDeserialize, XFileDeserializeInto,
+    XString, assert_size,
     common::{Mat3, Vec3, Vec4},
     file_line_col,
     fx::{FxEffectDef, FxEffectDefRaw},
+    misc::{MapEnts, MapEntsRaw},
     techset::{Material, MaterialRaw},
+    util::{FatPointerCountFirstU32, Ptr32, XStringRaw},
     xmodel::{
         CBrushSide, CBrushSideRaw, CPlane, CPlaneRaw, PhysConstraint, PhysConstraintRaw,
         PhysPreset, PhysPresetRaw, XModel, XModelPieces, XModelPiecesRaw, XModelRaw,
@@ -68,7 +72,7 @@ pub(crate) struct ClipMapRaw<'a> {
 assert_size!(ClipMapRaw, 332);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct ClipMap {
     pub name: XString,
     pub is_in_use: bool,
@@ -100,7 +104,11 @@ pub struct ClipMap {
     pub box_brush: Option<Box<CBrush>>,
     pub box_model: CModel,
     pub original_dyn_ent_count: u16,
+    /// Indexed by [`DynEntityBasis`] for every dyn ent, then by
+    /// `2 + DynEntityBasis` for the ones the server also tracks (which
+    /// size the last two [`Self::dyn_ent_coll_list`]s).
     pub dyn_ent_count: [u16; 4],
+    /// Indexed by [`DynEntityBasis`].
     pub dyn_ent_def_list: [Vec<DynEntityDef>; 2],
     pub dyn_ent_pose_list: [Vec<DynEntityPose>; 2],
     pub dyn_ent_client_list: [Vec<DynEntityClient>; 2],
@@ -243,6 +251,278 @@ impl<'a> XFileDeserializeInto<ClipMap, ()> for ClipMapRaw<'a> {
     }
 }
 
+impl ClipMap {
+    /// Returns a [`ClipTrigger`] for every brush flagged as a trigger or as
+    /// a no-drop (kill) volume.
+    pub fn trigger_volumes(&self) -> impl Iterator<Item = ClipTrigger> + '_ {
+        self.brushes
+            .iter()
+            .map(ClipTrigger::from)
+            .filter(|t| t.is_script_trigger() || t.is_kill_volume())
+    }
+
+    /// Returns every trigger volume whose bounds contain `point`.
+    pub fn triggers_containing_point(&self, point: Vec3) -> Vec<ClipTrigger> {
+        self.trigger_volumes()
+            .filter(|t| t.contains_point(point))
+            .collect()
+    }
+
+    /// The number of brush models, including the world itself (index 0).
+    /// Every other brush model belongs to an entity (usually a
+    /// `script_brushmodel` or `func_static`).
+    pub fn brush_model_count(&self) -> usize {
+        self.cmodels.len()
+    }
+
+    pub fn brush_model_at(&self, idx: usize) -> Result<&CModel> {
+        let count = self.brush_model_count();
+        if idx < count {
+            Ok(&self.cmodels[idx])
+        } else {
+            Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "ClipMap: brush model index ({idx}) >= brush model count ({count})"
+                )),
+            ))
+        }
+    }
+
+    /// Looks up the brush model named by an entity's `model` key. Brush
+    /// models are referred to as `*N`, where `N` is the index into
+    /// [`Self::cmodels`]. Returns [`None`] for anything else (e.g. the name
+    /// of an [`XModel`](crate::xmodel::XModel)).
+    pub fn brush_model_for_entity(&self, model_str: &str) -> Option<&CModel> {
+        let idx = model_str.strip_prefix('*')?.parse::<usize>().ok()?;
+        self.cmodels.get(idx)
+    }
+
+    /// The indices of the brushes with a side whose plane faces into the
+    /// brush, i.e. whose normal is antiparallel (a dot product below
+    /// `-0.9`) to the face's outward normal computed from the brush's
+    /// vertices. See [`CBrush::inverted_sides`].
+    pub fn check_surface_normals(&self) -> Vec<usize> {
+        self.brushes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.inverted_sides().next().is_some())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The indices of the brushes with a vertex in front of one of their
+    /// own planes. See [`CBrush::is_convex`].
+    pub fn check_brush_convexity(&self) -> Vec<usize> {
+        self.brushes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| !b.is_convex())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Every dyn ent, model-based ones first.
+    pub fn dyn_entities(&self) -> impl Iterator<Item = DynEntity<'_>> {
+        [DynEntityBasis::MODEL, DynEntityBasis::BRUSH]
+            .into_iter()
+            .flat_map(move |basis| {
+                let poses = &self.dyn_ent_pose_list[basis as usize];
+                self.dyn_ent_def_list[basis as usize]
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, def)| DynEntity {
+                        basis,
+                        index,
+                        def,
+                        pose: poses.get(index),
+                    })
+            })
+    }
+
+    /// The number of dyn ents with the given `basis`.
+    pub fn dyn_ent_count(&self, basis: DynEntityBasis) -> usize {
+        self.dyn_ent_count[basis as usize] as _
+    }
+
+    /// The number of dyn ents with the given `basis` that the server tracks
+    /// too. The rest are client-side only.
+    pub fn server_dyn_ent_count(&self, basis: DynEntityBasis) -> usize {
+        self.dyn_ent_count[2 + basis as usize] as _
+    }
+
+    /// Every model used by a static model or dyn ent (including destroyed
+    /// models), without duplicates.
+    pub fn referenced_models(&self) -> Vec<&XModel> {
+        let static_models = self
+            .static_model_list
+            .iter()
+            .filter_map(|m| m.xmodel.as_deref());
+        let dyn_ent_models = self.dyn_entities().flat_map(|e| {
+            [e.def.xmodel.as_deref(), e.def.destroyed_xmodel.as_deref()]
+                .into_iter()
+                .flatten()
+        });
+
+        let mut models = Vec::<&XModel>::new();
+        for model in static_models.chain(dyn_ent_models) {
+            if !models.iter().any(|m| m.name.get() == model.name.get()) {
+                models.push(model);
+            }
+        }
+        models
+    }
+
+    /// Every dyn ent's destruction effect, without duplicates.
+    pub fn referenced_effects(&self) -> Vec<&FxEffectDef> {
+        let mut effects = Vec::<&FxEffectDef>::new();
+        for effect in self
+            .dyn_entities()
+            .filter_map(|e| e.def.destroy_fx.as_deref())
+        {
+            if !effects.iter().any(|f| f.name.get() == effect.name.get()) {
+                effects.push(effect);
+            }
+        }
+        effects
+    }
+
+    /// Every dyn ent's physics preset, without duplicates.
+    pub fn referenced_phys_presets(&self) -> Vec<&PhysPreset> {
+        let mut presets = Vec::<&PhysPreset>::new();
+        for preset in self
+            .dyn_entities()
+            .filter_map(|e| e.def.phys_preset.as_deref())
+        {
+            if !presets.iter().any(|p| p.name.get() == preset.name.get()) {
+                presets.push(preset);
+            }
+        }
+        presets
+    }
+
+    /// The number of brushes under the leaf brush node at `idx`.
+    ///
+    /// A node's children always come after it, so a child offset of 0 is
+    /// skipped, and so is a node already counted, which keeps a corrupted
+    /// tree from looping.
+    fn leaf_brush_node_brush_count(&self, idx: usize) -> usize {
+        let mut visited = vec![false; self.leafbrush_nodes.len()];
+        let mut stack = vec![idx];
+        let mut count = 0;
+
+        while let Some(idx) = stack.pop() {
+            let Some(node) = self.leafbrush_nodes.get(idx) else {
+                continue;
+            };
+            if core::mem::replace(&mut visited[idx], true) {
+                continue;
+            }
+
+            match &node.data {
+                Some(CLeafBrushNodeData::Leaf(leaf)) => count += leaf.brushes.len(),
+                Some(CLeafBrushNodeData::Children(children)) => stack.extend(
+                    children
+                        .child_offset
+                        .iter()
+                        .filter(|&&o| o != 0)
+                        .map(|&o| idx + o as usize),
+                ),
+                None => {}
+            }
+        }
+
+        count
+    }
+}
+
+bitflags! {
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[derive(Copy, Clone, Default, Debug)]
+    pub struct Contents: u32 {
+        const SOLID = 0x00000001;
+        const FOLIAGE = 0x00000002;
+        const NONCOLLIDING = 0x00000004;
+        const GLASS = 0x00000010;
+        const WATER = 0x00000020;
+        const CANSHOOTCLIP = 0x00000040;
+        const MISSILECLIP = 0x00000080;
+        const ITEM = 0x00000100;
+        const VEHICLECLIP = 0x00000200;
+        const ITEMCLIP = 0x00000400;
+        const SKY = 0x00000800;
+        const AI_NOSIGHT = 0x00001000;
+        const CLIPSHOT = 0x00002000;
+        const MOVER = 0x00004000;
+        const PLAYERCLIP = 0x00010000;
+        const MONSTERCLIP = 0x00020000;
+        const TELEPORTER = 0x00040000;
+        const JUMPPAD = 0x00080000;
+        const CLUSTERPORTAL = 0x00100000;
+        const DONOTENTER = 0x00200000;
+        const DONOTENTER_LARGE = 0x00400000;
+        const MANTLE = 0x01000000;
+        const DETAIL = 0x08000000;
+        const STRUCTURAL = 0x10000000;
+        const TRANSPARENT = 0x20000000;
+        const TRIGGER = 0x40000000;
+        const NODROP = 0x80000000;
+    }
+}
+
+/// An axis-aligned trigger volume. See [`ClipMap::trigger_volumes`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug)]
+pub struct ClipTrigger {
+    pub bounds_mins: Vec3,
+    pub bounds_maxs: Vec3,
+    pub contents_flags: u32,
+}
+
+impl From<&CBrush> for ClipTrigger {
+    fn from(value: &CBrush) -> Self {
+        Self {
+            bounds_mins: value.mins,
+            bounds_maxs: value.maxs,
+            contents_flags: value.contents as u32,
+        }
+    }
+}
+
+impl ClipTrigger {
+    pub const fn contents(&self) -> Contents {
+        Contents::from_bits_retain(self.contents_flags)
+    }
+
+    /// Whether anything entering the volume is killed/removed.
+    pub const fn is_kill_volume(&self) -> bool {
+        self.contents().contains(Contents::NODROP)
+    }
+
+    pub const fn is_script_trigger(&self) -> bool {
+        self.contents().contains(Contents::TRIGGER)
+    }
+
+    pub fn center(&self) -> Vec3 {
+        let mins = self.bounds_mins.get();
+        let maxs = self.bounds_maxs.get();
+        [
+            (mins[0] + maxs[0]) * 0.5,
+            (mins[1] + maxs[1]) * 0.5,
+            (mins[2] + maxs[2]) * 0.5,
+        ]
+        .into()
+    }
+
+    /// Inclusive of the bounds themselves.
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        let mins = self.bounds_mins.get();
+        let maxs = self.bounds_maxs.get();
+        let p = point.get();
+        (0..3).all(|i| mins[i] <= p[i] && p[i] <= maxs[i])
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct CStaticModelRaw<'a> {
@@ -388,7 +668,7 @@ pub(crate) struct CLeafRaw {
 assert_size!(CLeafRaw, 44);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct CLeaf {
     pub first_coll_aabb_index: usize,
     pub coll_aabb_count: usize,
@@ -646,7 +926,7 @@ pub(crate) struct CModelRaw {
 assert_size!(CModelRaw, 72);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct CModel {
     pub mins: Vec3,
     pub maxs: Vec3,
@@ -654,6 +934,23 @@ pub struct CModel {
     pub leaf: CLeaf,
 }
 
+impl CModel {
+    /// Returns `(mins, maxs)`.
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        (self.mins, self.maxs)
+    }
+
+    /// The number of brushes the model is made of. `clip_map` should be the
+    /// [`ClipMap`] the model came from.
+    pub fn brush_count(&self, clip_map: &ClipMap) -> usize {
+        if self.leaf.leaf_brush_node < 0 {
+            return 0;
+        }
+
+        clip_map.leaf_brush_node_brush_count(self.leaf.leaf_brush_node as _)
+    }
+}
+
 impl From<CModelRaw> for CModel {
     fn from(value: CModelRaw) -> Self {
         Self {
@@ -714,6 +1011,89 @@ impl<'a> XFileDeserializeInto<CBrush, ()> for CBrushRaw<'a> {
     }
 }
 
+/// How far (in world units) a vertex can be from a plane and still count
+/// as on it.
+const BRUSH_PLANE_EPSILON: f32 = 0.01;
+
+impl CBrush {
+    /// Whether every vertex is behind or on every plane bounding the brush:
+    /// the planes of [`Self::sides`], and the axial ones given by
+    /// [`Self::mins`] and [`Self::maxs`].
+    pub fn is_convex(&self) -> bool {
+        let (mins, maxs) = (self.mins.get(), self.maxs.get());
+        self.verts.iter().all(|v| {
+            let p = v.get();
+            (0..3).all(|i| {
+                mins[i] - BRUSH_PLANE_EPSILON <= p[i] && p[i] <= maxs[i] + BRUSH_PLANE_EPSILON
+            }) && self
+                .sides
+                .iter()
+                .filter_map(|s| s.plane.as_deref())
+                .all(|plane| plane.normal.dot(v) - plane.dist <= BRUSH_PLANE_EPSILON)
+        })
+    }
+
+    /// The indices into [`Self::sides`] of the sides whose plane normal
+    /// points into the brush.
+    ///
+    /// The brush's vertices aren't stored per side, so each face is made of
+    /// the vertices lying on the side's plane, and its normal is taken
+    /// facing away from the centroid of all the vertices. Sides with fewer
+    /// than three (non-collinear) vertices on their plane are skipped.
+    pub fn inverted_sides(&self) -> impl Iterator<Item = usize> + '_ {
+        let centroid = self.verts_centroid();
+        self.sides.iter().enumerate().filter_map(move |(i, side)| {
+            let plane = side.plane.as_deref()?;
+            let normal = self.face_normal(plane, centroid?)?;
+            (normal.dot(&plane.normal.normalize()) < -0.9).then_some(i)
+        })
+    }
+
+    fn verts_centroid(&self) -> Option<Vec3> {
+        if self.verts.is_empty() {
+            return None;
+        }
+        let sum = self
+            .verts
+            .iter()
+            .fold(Vec3::from([0.0; 3]), |acc, &v| acc + v);
+        Some(sum / self.verts.len() as f32)
+    }
+
+    /// The outward unit normal of the face the brush's vertices form on
+    /// `plane`.
+    fn face_normal(&self, plane: &CPlane, centroid: Vec3) -> Option<Vec3> {
+        let on_plane = self
+            .verts
+            .iter()
+            .filter(|v| (plane.normal.dot(v) - plane.dist).abs() <= BRUSH_PLANE_EPSILON)
+            .copied()
+            .collect::<Vec<_>>();
+        let (&a, rest) = on_plane.split_first()?;
+
+        // The largest triangle fanning out from `a` is the least affected
+        // by rounding.
+        let normal = rest
+            .iter()
+            .flat_map(|&b| rest.iter().map(move |&c| (b - a).cross(&(c - a))))
+            .max_by(|x, y| x.length().total_cmp(&y.length()))?;
+        if normal.length() <= f32::EPSILON {
+            return None;
+        }
+
+        let face_centroid = on_plane
+            .iter()
+            .fold(Vec3::from([0.0; 3]), |acc, &v| acc + v)
+            / on_plane.len() as f32;
+        let normal = normal.normalize();
+        Some(if normal.dot(&(face_centroid - centroid)) < 0.0 {
+            -normal
+        } else {
+            normal
+        })
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct DynEntityDefRaw<'a> {
@@ -746,8 +1126,27 @@ pub enum DynEntityType {
     COUNT = 3,
 }
 
+/// Whether a dyn ent is drawn with an [`XModel`] or a brush model. Indexes
+/// [`ClipMap::dyn_ent_def_list`] and the other per-basis dyn ent lists.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DynEntityBasis {
+    MODEL = 0,
+    BRUSH = 1,
+}
+
+/// A dyn ent as returned by [`ClipMap::dyn_entities`].
+#[derive(Copy, Clone, Debug)]
+pub struct DynEntity<'a> {
+    pub basis: DynEntityBasis,
+    /// The index into [`ClipMap::dyn_ent_def_list`]`[basis]`.
+    pub index: usize,
+    pub def: &'a DynEntityDef,
+    pub pose: Option<&'a DynEntityPose>,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Default, Debug)]
 pub struct DynEntityDef {
     pub type_: DynEntityType,
     pub pose: GfxPlacement,
@@ -817,7 +1216,7 @@ pub(crate) struct GfxPlacementRaw {
 assert_size!(GfxPlacementRaw, 28);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct GfxPlacement {
     pub quat: Vec4,
     pub origin: Vec3,
@@ -841,7 +1240,7 @@ pub(crate) struct DynEntityPoseRaw {
 assert_size!(DynEntityPoseRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct DynEntityPose {
     pub pose: GfxPlacement,
     pub radius: f32,
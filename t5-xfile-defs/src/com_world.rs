@@ -1,22 +1,31 @@
 use alloc::{boxed::Box, vec::Vec};
 
 use crate::{
-    FatPointer, FatPointerCountFirstU32, Ptr32ArrayConst, Result, T5XFileDeserialize,
-    T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size,
+    Result, T5XFileSerialize, XFileSerialize, XString, assert_size,
     common::{Vec3, Vec4},
+    util::{FatPointerCountFirstU32, Ptr32ArrayConst, XStringRaw},
 };
 
+use num::FromPrimitive;
+use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
+use t5_xfile_derive::XFileConvert;
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Default, Debug, Deserialize, XFileConvert)]
+#[xfile(into = ComWorld)]
 pub(crate) struct ComWorldRaw<'a> {
+    #[xfile(string)]
     pub name: XStringRaw<'a>,
+    #[xfile(bool)]
     pub is_in_use: i32,
+    #[xfile(fat_ptr)]
     pub primary_lights: FatPointerCountFirstU32<'a, ComPrimaryLightRaw<'a>>,
     pub water_header: ComWaterHeader,
+    #[xfile(fat_ptr_into)]
     pub water_cells: FatPointerCountFirstU32<'a, ComWaterCell>,
     pub burnable_header: ComBurnableHeader,
+    #[xfile(fat_ptr)]
     pub burnable_cells: FatPointerCountFirstU32<'a, ComBurnableCellRaw<'a>>,
 }
 
@@ -32,69 +41,24 @@ pub struct ComWorld {
     pub burnable_cells: Vec<ComBurnableCell>,
 }
 
-impl<'a> XFileDeserializeInto<ComWorld, ()> for ComWorldRaw<'a> {
-    fn xfile_deserialize_into(
-        &self,
-        de: &mut impl T5XFileDeserialize,
-        _data: (),
-    ) -> Result<ComWorld> {
-        let name = self.name.xfile_deserialize_into(de, ())?;
-        let primary_lights = self.primary_lights.xfile_deserialize_into(de, ())?;
-        let water_cells = self.water_cells.to_vec(de)?;
-        let burnable_cells = self.burnable_cells.xfile_deserialize_into(de, ())?;
-
-        Ok(ComWorld {
-            name,
-            is_in_use: self.is_in_use != 0,
-            primary_lights,
-            water_header: self.water_header.clone(),
-            water_cells,
-            burnable_header: self.burnable_header.clone(),
-            burnable_cells,
-        })
-    }
-}
-
-impl XFileSerialize<()> for ComWorld {
-    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let name = XStringRaw::from_str(self.name.get());
-        let primary_lights = FatPointerCountFirstU32::from_slice(&self.primary_lights);
-        let water_header = self.water_header;
-        let water_cells = FatPointerCountFirstU32::from_slice(&self.water_cells);
-        let burnable_header = self.burnable_header;
-        let burnable_cells = FatPointerCountFirstU32::from_slice(&self.burnable_cells);
-
-        let com_world = ComWorldRaw {
-            name,
-            is_in_use: self.is_in_use as _,
-            primary_lights,
-            water_header,
-            water_cells,
-            burnable_header,
-            burnable_cells,
-        };
-
-        ser.store_into_xfile(com_world)?;
-        self.primary_lights.xfile_serialize(ser, ())?;
-        self.water_header.xfile_serialize(ser, ())?;
-        self.water_cells.xfile_serialize(ser, ())?;
-        self.burnable_header.xfile_serialize(ser, ())?;
-        self.burnable_cells.xfile_serialize(ser, ())
-    }
-}
-
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Default, Debug, Deserialize, XFileConvert)]
+#[xfile(into = ComPrimaryLight)]
 pub(crate) struct ComPrimaryLightRaw<'a> {
     pub type_: u8,
+    #[xfile(bool)]
     pub can_use_shadow_map: u8,
     pub exponent: u8,
     pub priority: u8,
     pub cull_dist: i16,
+    #[xfile(pad)]
     #[allow(dead_code)]
     pad: [u8; 2],
+    #[xfile(vector)]
     pub color: [f32; 3],
+    #[xfile(vector)]
     pub dir: [f32; 3],
+    #[xfile(vector)]
     pub origin: [f32; 3],
     pub radius: f32,
     pub cos_half_fov_outer: f32,
@@ -103,15 +67,25 @@ pub(crate) struct ComPrimaryLightRaw<'a> {
     pub rotation_limit: f32,
     pub translation_limit: f32,
     pub mip_distance: f32,
+    #[xfile(vector)]
     pub diffuse_color: [f32; 4],
+    #[xfile(vector)]
     pub specular_color: [f32; 4],
+    #[xfile(vector)]
     pub attenuation: [f32; 4],
+    #[xfile(vector)]
     pub falloff: [f32; 4],
+    #[xfile(vector)]
     pub angle: [f32; 4],
+    #[xfile(vector)]
     pub aabb: [f32; 4],
+    #[xfile(vector)]
     pub cookie_control_0: [f32; 4],
+    #[xfile(vector)]
     pub cookie_control_1: [f32; 4],
+    #[xfile(vector)]
     pub cookie_control_2: [f32; 4],
+    #[xfile(string)]
     pub def_name: XStringRaw<'a>,
 }
 assert_size!(ComPrimaryLightRaw, 220);
@@ -146,92 +120,108 @@ pub struct ComPrimaryLight {
     pub def_name: XString,
 }
 
-impl<'a> XFileDeserializeInto<ComPrimaryLight, ()> for ComPrimaryLightRaw<'a> {
-    fn xfile_deserialize_into(
-        &self,
-        de: &mut impl T5XFileDeserialize,
-        _data: (),
-    ) -> Result<ComPrimaryLight> {
-        let color = self.color.into();
-        let dir = self.dir.into();
-        let origin = self.origin.into();
-        let diffuse_color = self.diffuse_color.into();
-        let specular_color = self.specular_color.into();
-        let attenuation = self.attenuation.into();
-        let falloff = self.falloff.into();
-        let angle = self.angle.into();
-        let aabb = self.aabb.into();
-        let cookie_control_0 = self.cookie_control_0.into();
-        let cookie_control_1 = self.cookie_control_1.into();
-        let cookie_control_2 = self.cookie_control_2.into();
-        let def_name = self.def_name.xfile_deserialize_into(de, ())?;
+impl ComWorld {
+    /// Every light in the world, in a form that doesn't depend on the
+    /// engine's own light types. Lights of type [`GfxLightType::NONE`] (like
+    /// the placeholder at index 0) or of an unknown type are left out.
+    pub fn to_light_list(&self) -> Vec<LightDescriptor> {
+        self.primary_lights
+            .iter()
+            .filter_map(ComPrimaryLight::to_descriptor)
+            .collect()
+    }
 
-        Ok(ComPrimaryLight {
-            type_: self.type_,
-            can_use_shadow_map: self.can_use_shadow_map != 0,
-            exponent: self.exponent,
-            priority: self.priority,
-            cull_dist: self.cull_dist,
-            color,
-            dir,
-            origin,
-            radius: self.radius,
-            cos_half_fov_outer: self.cos_half_fov_outer,
-            cos_half_fov_inner: self.cos_half_fov_inner,
-            cos_half_fov_expanded: self.cos_half_fov_expanded,
-            rotation_limit: self.rotation_limit,
-            translation_limit: self.translation_limit,
-            mip_distance: self.mip_distance,
-            diffuse_color,
-            specular_color,
-            attenuation,
-            falloff,
-            angle,
-            aabb,
-            cookie_control_0,
-            cookie_control_1,
-            cookie_control_2,
-            def_name,
-        })
+    pub fn directional_light_count(&self) -> usize {
+        self.light_count(LightDescriptorType::Directional)
+    }
+
+    pub fn spot_light_count(&self) -> usize {
+        self.light_count(LightDescriptorType::Spot)
+    }
+
+    pub fn omni_light_count(&self) -> usize {
+        self.light_count(LightDescriptorType::Omni)
+    }
+
+    fn light_count(&self, type_: LightDescriptorType) -> usize {
+        self.primary_lights
+            .iter()
+            .filter(|l| l.descriptor_type() == Some(type_))
+            .count()
     }
 }
 
-impl XFileSerialize<()> for ComPrimaryLight {
-    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let def_name = XStringRaw::from_str(self.def_name.get());
+/// Values of [`ComPrimaryLight::type_`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, FromPrimitive)]
+#[repr(u8)]
+pub enum GfxLightType {
+    NONE = 0,
+    DIR = 1,
+    SPOT = 2,
+    SPOT_SQUARE = 3,
+    SPOT_ROUND = 4,
+    OMNI = 5,
+}
 
-        let primary_light = ComPrimaryLightRaw {
-            type_: self.type_,
-            can_use_shadow_map: self.can_use_shadow_map as _,
-            exponent: self.exponent,
-            priority: self.priority,
-            cull_dist: self.cull_dist,
-            pad: [0u8; 2],
-            color: self.color.get(),
-            dir: self.dir.get(),
-            origin: self.origin.get(),
-            radius: self.radius,
-            cos_half_fov_outer: self.cos_half_fov_outer,
-            cos_half_fov_inner: self.cos_half_fov_inner,
-            cos_half_fov_expanded: self.cos_half_fov_expanded,
-            rotation_limit: self.rotation_limit,
-            translation_limit: self.translation_limit,
-            mip_distance: self.mip_distance,
-            diffuse_color: self.diffuse_color.get(),
-            specular_color: self.specular_color.get(),
-            attenuation: self.attenuation.get(),
-            falloff: self.falloff.get(),
-            angle: self.angle.get(),
-            aabb: self.aabb.get(),
-            cookie_control_0: self.cookie_control_0.get(),
-            cookie_control_1: self.cookie_control_1.get(),
-            cookie_control_2: self.cookie_control_2.get(),
-            def_name,
-        };
+impl ComPrimaryLight {
+    /// [`None`] if [`Self::type_`] isn't a valid [`GfxLightType`].
+    pub fn light_type(&self) -> Option<GfxLightType> {
+        GfxLightType::from_u8(self.type_)
+    }
 
-        ser.store_into_xfile(primary_light)?;
-        self.def_name.xfile_serialize(ser, ())
+    fn descriptor_type(&self) -> Option<LightDescriptorType> {
+        match self.light_type()? {
+            GfxLightType::NONE => None,
+            GfxLightType::DIR => Some(LightDescriptorType::Directional),
+            GfxLightType::SPOT | GfxLightType::SPOT_SQUARE | GfxLightType::SPOT_ROUND => {
+                Some(LightDescriptorType::Spot)
+            }
+            GfxLightType::OMNI => Some(LightDescriptorType::Omni),
+        }
     }
+
+    /// [`None`] for lights that [`ComWorld::to_light_list`] leaves out.
+    pub fn to_descriptor(&self) -> Option<LightDescriptor> {
+        let type_ = self.descriptor_type()?;
+        let cone_angle =
+            |cos: f32| (type_ == LightDescriptorType::Spot).then(|| cos.clamp(-1.0, 1.0).acos());
+
+        Some(LightDescriptor {
+            position: self.origin,
+            direction: self.dir,
+            color: self.color,
+            radius: self.radius,
+            cone_inner_angle: cone_angle(self.cos_half_fov_inner),
+            cone_outer_angle: cone_angle(self.cos_half_fov_outer),
+            shadow_casts: self.can_use_shadow_map,
+            type_,
+        })
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LightDescriptorType {
+    Omni,
+    Spot,
+    Directional,
+}
+
+/// A light as returned by [`ComWorld::to_light_list`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct LightDescriptor {
+    pub position: Vec3,
+    pub direction: Vec3,
+    pub color: Vec3,
+    /// Not meaningful for directional lights.
+    pub radius: f32,
+    /// Half-angles of the cone, in radians. Only set for spot lights.
+    pub cone_inner_angle: Option<f32>,
+    pub cone_outer_angle: Option<f32>,
+    pub shadow_casts: bool,
+    pub type_: LightDescriptorType,
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -283,10 +273,12 @@ impl XFileSerialize<()> for ComBurnableHeader {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, Deserialize, XFileConvert)]
+#[xfile(into = ComBurnableCell)]
 pub(crate) struct ComBurnableCellRaw<'a> {
     pub x: i32,
     pub y: i32,
+    #[xfile(boxed_array)]
     pub data: Ptr32ArrayConst<'a, ComBurnableSample, 32>,
 }
 assert_size!(ComWaterHeader, 16);
@@ -299,44 +291,6 @@ pub struct ComBurnableCell {
     pub data: Option<Box<[ComBurnableSample; 32]>>,
 }
 
-impl<'a> XFileDeserializeInto<ComBurnableCell, ()> for ComBurnableCellRaw<'a> {
-    fn xfile_deserialize_into(
-        &self,
-        de: &mut impl T5XFileDeserialize,
-        _data: (),
-    ) -> Result<ComBurnableCell> {
-        let data = if self.data.is_null() {
-            None
-        } else {
-            Some(Box::new(self.data.to_vec(de)?.try_into().unwrap()))
-        };
-        Ok(ComBurnableCell {
-            x: self.x,
-            y: self.y,
-            data,
-        })
-    }
-}
-
-impl XFileSerialize<()> for ComBurnableCell {
-    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let data = self
-            .data
-            .as_ref()
-            .map(|s| Ptr32ArrayConst::from_slice(&**s))
-            .unwrap_or_default();
-
-        let burnable_cell = ComBurnableCellRaw {
-            x: self.x,
-            y: self.y,
-            data,
-        };
-
-        ser.store_into_xfile(burnable_cell)?;
-        self.data.xfile_serialize(ser, ())
-    }
-}
-
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub struct ComBurnableSample {
@@ -212,6 +212,133 @@ impl Vec4 {
     }
 }
 
+impl Vec3 {
+    pub fn dot(&self, rhs: &Vec3) -> f32 {
+        let (a, b) = (self.get(), rhs.get());
+        a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+    }
+
+    pub fn cross(&self, rhs: &Vec3) -> Vec3 {
+        let (a, b) = (self.get(), rhs.get());
+        [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]
+        .into()
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns the zero vector if `self` has zero length.
+    pub fn normalize(&self) -> Vec3 {
+        let len = self.length();
+        if len == 0.0 { *self } else { *self / len }
+    }
+
+    pub fn lerp(&self, other: &Vec3, t: f32) -> Vec3 {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Vec4 {
+    pub fn xyz(&self) -> Vec3 {
+        let v = self.get();
+        [v[0], v[1], v[2]].into()
+    }
+
+    /// Rotates `v` by `self`, treated as a unit quaternion stored as
+    /// `(x, y, z, w)`.
+    pub fn as_quaternion_rotate_vec3(&self, v: &Vec3) -> Vec3 {
+        let q = self.xyz();
+        let w = self.get()[3];
+        let t = q.cross(v) * 2.0;
+        *v + t * w + q.cross(&t)
+    }
+}
+
+macro_rules! impl_vec_ops {
+    ($t:ident, $n:literal) => {
+        impl From<$t> for [f32; $n] {
+            fn from(value: $t) -> Self {
+                value.get()
+            }
+        }
+
+        impl core::ops::Add for $t {
+            type Output = $t;
+
+            fn add(self, rhs: $t) -> $t {
+                let (a, b) = (self.get(), rhs.get());
+                core::array::from_fn::<f32, $n, _>(|i| a[i] + b[i]).into()
+            }
+        }
+
+        impl core::ops::Sub for $t {
+            type Output = $t;
+
+            fn sub(self, rhs: $t) -> $t {
+                let (a, b) = (self.get(), rhs.get());
+                core::array::from_fn::<f32, $n, _>(|i| a[i] - b[i]).into()
+            }
+        }
+
+        impl core::ops::Mul<f32> for $t {
+            type Output = $t;
+
+            fn mul(self, rhs: f32) -> $t {
+                self.get().map(|c| c * rhs).into()
+            }
+        }
+
+        impl core::ops::Div<f32> for $t {
+            type Output = $t;
+
+            fn div(self, rhs: f32) -> $t {
+                self.get().map(|c| c / rhs).into()
+            }
+        }
+
+        impl core::ops::Neg for $t {
+            type Output = $t;
+
+            fn neg(self) -> $t {
+                self.get().map(|c| -c).into()
+            }
+        }
+
+        impl core::ops::Index<usize> for $t {
+            type Output = f32;
+
+            fn index(&self, index: usize) -> &f32 {
+                &self.0[index]
+            }
+        }
+    };
+}
+
+impl_vec_ops!(Vec3, 3);
+impl_vec_ops!(Vec4, 4);
+
+/// An axis-aligned bounding box.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Bounds {
+    pub mins: Vec3,
+    pub maxs: Vec3,
+}
+
+impl Bounds {
+    pub fn new(mins: impl Into<Vec3>, maxs: impl Into<Vec3>) -> Self {
+        Self {
+            mins: mins.into(),
+            maxs: maxs.into(),
+        }
+    }
+}
+
 #[cfg(feature = "cgmath")]
 #[derive(Copy, Clone, Debug)]
 #[repr(transparent)]
@@ -0,0 +1,58 @@
+//! The root paths [`misc`](crate::misc)'s assets and [`util`](crate::util)'s
+//! plumbing used to be re-exported under. They're kept for one release so
+//! that downstream code has time to move to the `misc::` and `util::` paths.
+//!
+//! Types are deprecated aliases, so using them warns. rustc doesn't warn on
+//! deprecated re-exports yet, so the traits and functions only carry the
+//! note in the docs.
+
+use crate::{misc, util};
+
+#[deprecated(note = "use `misc::RawFile`")]
+pub type RawFile = misc::RawFile;
+#[deprecated(note = "use `misc::StringTable`")]
+pub type StringTable = misc::StringTable;
+#[deprecated(note = "use `misc::StringTableCell`")]
+pub type StringTableCell = misc::StringTableCell;
+#[deprecated(note = "use `misc::PackIndex`")]
+pub type PackIndex = misc::PackIndex;
+#[deprecated(note = "use `misc::PackIndexHeader`")]
+pub type PackIndexHeader = misc::PackIndexHeader;
+#[deprecated(note = "use `misc::PackIndexEntry`")]
+pub type PackIndexEntry = misc::PackIndexEntry;
+#[deprecated(note = "use `misc::MapEnts`")]
+pub type MapEnts = misc::MapEnts;
+#[deprecated(note = "use `misc::LocalizeEntry`")]
+pub type LocalizeEntry = misc::LocalizeEntry;
+#[deprecated(note = "use `misc::XGlobals`")]
+pub type XGlobals = misc::XGlobals;
+
+#[deprecated(note = "use `util::XStringRaw`")]
+pub type XStringRaw<'a> = util::XStringRaw<'a>;
+#[deprecated(note = "use `util::Ptr32`")]
+pub type Ptr32<'a, T> = util::Ptr32<'a, T>;
+#[deprecated(note = "use `util::Ptr32Array`")]
+pub type Ptr32Array<'a, T> = util::Ptr32Array<'a, T>;
+#[deprecated(note = "use `util::Ptr32ArrayConst`")]
+pub type Ptr32ArrayConst<'a, T, const N: usize> = util::Ptr32ArrayConst<'a, T, N>;
+#[deprecated(note = "use `util::FatPointerCountFirstU8`")]
+pub type FatPointerCountFirstU8<'a, T> = util::FatPointerCountFirstU8<'a, T>;
+#[deprecated(note = "use `util::FatPointerCountFirstU16`")]
+pub type FatPointerCountFirstU16<'a, T> = util::FatPointerCountFirstU16<'a, T>;
+#[deprecated(note = "use `util::FatPointerCountFirstU32`")]
+pub type FatPointerCountFirstU32<'a, T> = util::FatPointerCountFirstU32<'a, T>;
+#[deprecated(note = "use `util::FatPointerCountLastU8`")]
+pub type FatPointerCountLastU8<'a, T> = util::FatPointerCountLastU8<'a, T>;
+#[deprecated(note = "use `util::FatPointerCountLastU16`")]
+pub type FatPointerCountLastU16<'a, T> = util::FatPointerCountLastU16<'a, T>;
+#[deprecated(note = "use `util::FatPointerCountLastU32`")]
+pub type FatPointerCountLastU32<'a, T> = util::FatPointerCountLastU32<'a, T>;
+
+#[deprecated(note = "use `util::FatPointer`")]
+pub use util::FatPointer;
+#[deprecated(note = "use `util::XFileDeserializeInto`")]
+pub use util::XFileDeserializeInto;
+#[deprecated(note = "use `util::XFileSerialize`")]
+pub use util::XFileSerialize;
+#[deprecated(note = "use `util::validate_ptr32_array`")]
+pub use util::validate_ptr32_array;
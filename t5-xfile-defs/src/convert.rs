@@ -0,0 +1,225 @@
+//! Moving assets deserialized for one platform over to another, so they can
+//! be serialized into a Fastfile for it.
+//!
+//! Most asset types are stored the same way everywhere apart from byte
+//! order, which the serializer already takes care of, so converting them
+//! just moves them between [`XAsset::PC`] and [`XAsset::Console`]. Only a
+//! subset of types is known to be safe to convert that way, though, and
+//! some need their data transformed on the way. Everything else is dropped,
+//! and [`ConversionReport`] says why.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Display;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    Error, ErrorKind, Result, XFilePlatform, file_line_col,
+    gpu::byte_swapped,
+    techset::{GfxImage, GfxTexture, Material, MaterialTextureDefInfo, d3dfmt},
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+};
+
+/// What happened to a single asset.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConversionOutcome {
+    /// Moved over unchanged.
+    Converted,
+    /// Moved over, but its data had to be changed. The string says how.
+    Transformed(String),
+    /// Left out of the output. The string says why.
+    Dropped(String),
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct ConvertedAsset {
+    /// Index of the asset in the list passed to [`convert_platform`].
+    pub index: usize,
+    pub asset_type: XAssetType,
+    pub asset_name: Option<String>,
+    pub outcome: ConversionOutcome,
+}
+
+impl Display for ConvertedAsset {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:?} '{}' ({}): ",
+            self.asset_type,
+            self.asset_name.as_deref().unwrap_or_default(),
+            self.index,
+        )?;
+        match &self.outcome {
+            ConversionOutcome::Converted => write!(f, "converted"),
+            ConversionOutcome::Transformed(how) => write!(f, "converted, {how}"),
+            ConversionOutcome::Dropped(why) => write!(f, "dropped, {why}"),
+        }
+    }
+}
+
+/// Everything [`convert_platform`] did, one entry per input asset.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Default)]
+pub struct ConversionReport {
+    pub assets: Vec<ConvertedAsset>,
+}
+
+impl ConversionReport {
+    /// The assets that made it into the output.
+    pub fn converted(&self) -> impl Iterator<Item = &ConvertedAsset> {
+        self.assets
+            .iter()
+            .filter(|a| !matches!(a.outcome, ConversionOutcome::Dropped(_)))
+    }
+
+    /// The assets that were left out.
+    pub fn dropped(&self) -> impl Iterator<Item = &ConvertedAsset> {
+        self.assets
+            .iter()
+            .filter(|a| matches!(a.outcome, ConversionOutcome::Dropped(_)))
+    }
+}
+
+/// Converts `assets` to `target`'s side of [`XAsset`], dropping the ones
+/// that can't be converted. Assets that are already on the right side are
+/// passed through untouched.
+///
+/// Only fails if `target` is unimplemented (i.e., the Wii).
+///
+/// Converted as-is: `RawFile`, `StringTable`, `LocalizeEntry`, `MapEnts`,
+/// `XGlobals` and `Weapon`. Images have their pixel data byte-swapped
+/// (Xbox 360 tiling isn't undone, though), and materials have the same done
+/// to their images and lose their technique set, since its shaders are
+/// compiled for one platform. Everything else, including sounds (whose
+/// samples need their own fixes) and the types with per-local-client state
+/// (`GfxWorld`, `MenuList` and `Menu`), is dropped.
+pub fn convert_platform(
+    assets: Vec<XAsset>,
+    target: XFilePlatform,
+) -> Result<(Vec<XAsset>, ConversionReport)> {
+    if target == XFilePlatform::Wii {
+        return Err(Error::new(
+            file_line_col!(),
+            ErrorKind::UnimplementedPlatform(target),
+        ));
+    }
+
+    let mut converted = Vec::new();
+    let mut report = ConversionReport::default();
+    for (index, asset) in assets.into_iter().enumerate() {
+        let asset_type = asset.asset_type();
+        let asset_name = asset.name().map(ToString::to_string);
+
+        let (asset, outcome) = match (asset, target.is_pc()) {
+            (a @ XAsset::PC(_), true) | (a @ XAsset::Console(_), false) => {
+                (Some(a), ConversionOutcome::Converted)
+            }
+            (XAsset::Console(a), true) => match convert_generic(a) {
+                Ok((a, outcome)) => (Some(XAsset::PC(a)), outcome),
+                Err(why) => (None, ConversionOutcome::Dropped(why)),
+            },
+            (XAsset::PC(a), false) => match convert_generic(a) {
+                Ok((a, outcome)) => (Some(XAsset::Console(a)), outcome),
+                Err(why) => (None, ConversionOutcome::Dropped(why)),
+            },
+        };
+
+        converted.extend(asset);
+        report.assets.push(ConvertedAsset {
+            index,
+            asset_type,
+            asset_name,
+            outcome,
+        });
+    }
+
+    Ok((converted, report))
+}
+
+fn convert_generic<const FROM: usize, const TO: usize>(
+    asset: XAssetGeneric<FROM>,
+) -> core::result::Result<(XAssetGeneric<TO>, ConversionOutcome), String> {
+    use XAssetGeneric as A;
+
+    let converted = |a| Ok((a, ConversionOutcome::Converted));
+    match asset {
+        A::RawFile(a) => converted(A::RawFile(a)),
+        A::StringTable(a) => converted(A::StringTable(a)),
+        A::LocalizeEntry(a) => converted(A::LocalizeEntry(a)),
+        A::MapEnts(a) => converted(A::MapEnts(a)),
+        A::XGlobals(a) => converted(A::XGlobals(a)),
+        A::Weapon(a) => converted(A::Weapon(a)),
+        A::Image(Some(mut image)) => {
+            let outcome = swap_image(&mut image)?;
+            Ok((A::Image(Some(image)), outcome))
+        }
+        A::Image(None) => converted(A::Image(None)),
+        A::Material(Some(mut material)) => {
+            let outcome = convert_material(&mut material)?;
+            Ok((A::Material(Some(material)), outcome))
+        }
+        A::Material(None) => converted(A::Material(None)),
+        A::Sound(_) => Err("sound samples need endian and sample rate fixes".to_string()),
+        A::GfxWorld(_) | A::MenuList(_) | A::Menu(_) => {
+            Err("has per-local-client state that can't be converted yet".to_string())
+        }
+        _ => Err("no conversion for this type yet".to_string()),
+    }
+}
+
+/// Byte-swaps `image`'s pixel data in its format's swap unit.
+fn swap_image(image: &mut GfxImage) -> core::result::Result<ConversionOutcome, String> {
+    let GfxTexture::LoadDef(Some(load_def)) = &mut image.texture else {
+        return Ok(ConversionOutcome::Converted);
+    };
+
+    let format = load_def.format;
+    let unit = d3dfmt::swap_unit(format)
+        .ok_or_else(|| alloc::format!("can't byte-swap image format {format:#010X}"))?;
+    if unit == 1 {
+        return Ok(ConversionOutcome::Converted);
+    }
+
+    load_def.resource = byte_swapped(&load_def.resource, unit);
+    if !image.pixels.is_empty() {
+        image.pixels = byte_swapped(&image.pixels, unit);
+    }
+    Ok(ConversionOutcome::Transformed(alloc::format!(
+        "pixel data byte-swapped in {unit}-byte units"
+    )))
+}
+
+fn convert_material(material: &mut Material) -> core::result::Result<ConversionOutcome, String> {
+    let mut swapped = 0;
+    for texture in &mut material.textures {
+        if let MaterialTextureDefInfo::Image(Some(image)) = &mut texture.u
+            && let ConversionOutcome::Transformed(_) = swap_image(image)?
+        {
+            swapped += 1;
+        }
+    }
+
+    let dropped_techset = material.technique_set.take().is_some();
+    let mut changes = Vec::new();
+    if swapped != 0 {
+        changes.push(alloc::format!("{swapped} image(s) byte-swapped"));
+    }
+    if dropped_techset {
+        changes.push("technique set dropped".to_string());
+    }
+
+    Ok(if changes.is_empty() {
+        ConversionOutcome::Converted
+    } else {
+        ConversionOutcome::Transformed(changes.join(", "))
+    })
+}
@@ -0,0 +1,113 @@
+//! Deserializing asset types the library doesn't know how to read.
+//!
+//! Some asset types are in [`XAssetType`] but aren't used by T5 (e.g.
+//! [`XAssetType::AITYPE`]), and modded or other-title Fastfiles can have
+//! types that aren't in it at all. An [`AssetHandler`] registered for such a
+//! type reads it into an [`XAssetGeneric::Custom`] instead of the
+//! deserializer failing with [`ErrorKind::UnusedXAssetType`] or
+//! [`ErrorKind::InvalidXAssetType`].
+//!
+//! Handlers are only consulted for types the library can't read itself, so
+//! they can't replace the built-in deserialization of any type.
+//!
+//! [`XAssetType`]: crate::xasset::XAssetType
+//! [`XAssetType::AITYPE`]: crate::xasset::XAssetType::AITYPE
+//! [`XAssetGeneric::Custom`]: crate::xasset::XAssetGeneric::Custom
+//! [`ErrorKind::UnusedXAssetType`]: crate::ErrorKind::UnusedXAssetType
+//! [`ErrorKind::InvalidXAssetType`]: crate::ErrorKind::InvalidXAssetType
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::any::Any;
+
+use crate::{Error, ErrorKind, Result, ScriptString, T5XFileDeserialize, file_line_col};
+
+/// The parts of [`T5XFileDeserialize`] an [`AssetHandler`] can use.
+///
+/// [`T5XFileDeserialize::load_from_xfile`] is generic, so
+/// [`T5XFileDeserialize`] can't be used as a trait object. Every
+/// [`T5XFileDeserialize`] is an `AssetReader`, and reads values with the
+/// blob's endianness the same way.
+pub trait AssetReader {
+    fn stream_pos(&mut self) -> Result<u64>;
+    fn stream_len(&mut self) -> Result<u64>;
+
+    fn read_u8(&mut self) -> Result<u8>;
+    fn read_u16(&mut self) -> Result<u16>;
+    fn read_u32(&mut self) -> Result<u32>;
+    fn read_u64(&mut self) -> Result<u64>;
+    fn read_f32(&mut self) -> Result<f32>;
+
+    fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        (0..len).map(|_| self.read_u8()).collect()
+    }
+
+    /// Reads a null-terminated string, such as the inline data of an
+    /// [`XString`](crate::XString), consuming the terminator.
+    ///
+    /// Returns [`ErrorKind::BadChar`] if the string isn't valid UTF-8.
+    fn read_c_string(&mut self) -> Result<String> {
+        let mut bytes = Vec::new();
+        loop {
+            match self.read_u8()? {
+                0 => break,
+                b => bytes.push(b),
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|e| {
+            let bad = e.as_bytes()[e.utf8_error().valid_up_to()];
+            Error::new(file_line_col!(), ErrorKind::BadChar(bad as _))
+        })
+    }
+
+    /// See [`T5XFileDeserialize::get_script_string`].
+    fn get_script_string(&self, string: ScriptString) -> Result<Option<&str>>;
+}
+
+impl<D: T5XFileDeserialize> AssetReader for D {
+    fn stream_pos(&mut self) -> Result<u64> {
+        T5XFileDeserialize::stream_pos(self)
+    }
+
+    fn stream_len(&mut self) -> Result<u64> {
+        T5XFileDeserialize::stream_len(self)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        self.load_from_xfile()
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        self.load_from_xfile()
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        self.load_from_xfile()
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        self.load_from_xfile()
+    }
+
+    fn read_f32(&mut self) -> Result<f32> {
+        self.load_from_xfile()
+    }
+
+    fn get_script_string(&self, string: ScriptString) -> Result<Option<&str>> {
+        T5XFileDeserialize::get_script_string(self, string)
+    }
+}
+
+/// Reads the assets of one asset type. See the [module docs](self).
+pub trait AssetHandler: Send + Sync {
+    /// Reads an asset whose data starts at `de`'s current position, and
+    /// returns its name and content. The content ends up in
+    /// [`XAssetGeneric::Custom::data`] for the caller to downcast.
+    ///
+    /// The handler has to read all of the asset's data, since the next
+    /// asset starts wherever it leaves off.
+    ///
+    /// [`XAssetGeneric::Custom::data`]: crate::xasset::XAssetGeneric::Custom
+    fn deserialize(&self, de: &mut dyn AssetReader)
+    -> Result<(String, Box<dyn Any + Send + Sync>)>;
+}
@@ -1,8 +1,9 @@
 use alloc::{boxed::Box, vec::Vec};
 
 use crate::{
-    FatPointer, FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result, T5XFileDeserialize,
-    T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size,
+    FatPointer, Result, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize,
+    XString, assert_size,
+    util::{FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, XStringRaw},
 };
 
 use serde::{Deserialize, Serialize};
@@ -16,12 +17,46 @@ pub(crate) struct DdlRootRaw<'a> {
 assert_size!(DdlRootRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct DdlRoot {
     pub name: XString,
     pub ddl_defs: Vec<Box<DdlDef>>,
 }
 
+impl DdlRoot {
+    /// Looks up a struct by name, in the first [`DdlDef`] that has one.
+    pub fn struct_by_name(&self, name: &str) -> Option<&DdlStructDef> {
+        self.ddl_defs.iter().find_map(|d| d.struct_by_name(name))
+    }
+
+    /// Looks up an enum by name, in the first [`DdlDef`] that has one.
+    pub fn enum_by_name(&self, name: &str) -> Option<&DdlEnumDef> {
+        self.ddl_defs.iter().find_map(|d| d.enum_by_name(name))
+    }
+
+    /// The names of every struct in every [`DdlDef`], without duplicates.
+    pub fn all_struct_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for s in self.ddl_defs.iter().flat_map(|d| &d.struct_list) {
+            if !names.contains(&s.name.get()) {
+                names.push(s.name.get());
+            }
+        }
+        names
+    }
+
+    /// The names of every enum in every [`DdlDef`], without duplicates.
+    pub fn all_enum_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for e in self.ddl_defs.iter().flat_map(|d| &d.enum_list) {
+            if !names.contains(&e.name.get()) {
+                names.push(e.name.get());
+            }
+        }
+        names
+    }
+}
+
 impl<'a> XFileDeserializeInto<DdlRoot, ()> for DdlRootRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -79,7 +114,7 @@ pub(crate) struct DdlDefRaw<'a> {
 assert_size!(DdlDefRaw, 28);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct DdlDef {
     pub version: i32,
     pub size: i32,
@@ -87,6 +122,16 @@ pub struct DdlDef {
     pub enum_list: Vec<DdlEnumDef>,
 }
 
+impl DdlDef {
+    pub fn struct_by_name(&self, name: &str) -> Option<&DdlStructDef> {
+        self.struct_list.iter().find(|s| s.name.get() == name)
+    }
+
+    pub fn enum_by_name(&self, name: &str) -> Option<&DdlEnumDef> {
+        self.enum_list.iter().find(|e| e.name.get() == name)
+    }
+}
+
 impl<'a> XFileDeserializeInto<DdlDef, ()> for DdlDefRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -138,13 +183,35 @@ pub(crate) struct DdlStructDefRaw<'a> {
 assert_size!(DdlStructDefRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct DdlStructDef {
     pub name: XString,
     pub size: i32,
     pub members: Vec<DdlMemberDef>,
 }
 
+impl DdlStructDef {
+    pub fn member_by_name(&self, name: &str) -> Option<&DdlMemberDef> {
+        self.members.iter().find(|m| m.name.get() == name)
+    }
+
+    /// The bit offset of the member at `idx`, computed by summing the sizes
+    /// of the members before it. For a well-formed struct this matches
+    /// [`DdlMemberDef::offset_bits`].
+    pub fn computed_offset_bits(&self, idx: usize) -> Option<usize> {
+        if idx >= self.members.len() {
+            return None;
+        }
+
+        Some(
+            self.members[..idx]
+                .iter()
+                .map(DdlMemberDef::size_bits)
+                .sum(),
+        )
+    }
+}
+
 impl<'a> XFileDeserializeInto<DdlStructDef, ()> for DdlStructDefRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -198,7 +265,7 @@ pub(crate) struct DdlMemberDefRaw<'a> {
 assert_size!(DdlMemberDefRaw, 48);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct DdlMemberDef {
     pub name: XString,
     pub size: i32,
@@ -214,6 +281,18 @@ pub struct DdlMemberDef {
     pub permission: i32,
 }
 
+impl DdlMemberDef {
+    /// The member's offset from the start of its struct, in bits.
+    pub fn offset_bits(&self) -> usize {
+        self.offset as _
+    }
+
+    /// The member's size in bits.
+    pub fn size_bits(&self) -> usize {
+        self.size as _
+    }
+}
+
 impl<'a> XFileDeserializeInto<DdlMemberDef, ()> for DdlMemberDefRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -273,7 +352,7 @@ pub(crate) struct DdlEnumDefRaw<'a> {
 assert_size!(DdlEnumDefRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct DdlEnumDef {
     pub name: XString,
     pub members: Vec<XString>,
@@ -1,9 +1,10 @@
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, format, vec::Vec};
 
 use crate::{
-    FatPointer, FatPointerCountFirstU32, Ptr32, Result, ScriptString, T5XFileDeserialize,
-    T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size,
+    Error, ErrorKind, FatPointer, Result, ScriptString, T5XFileDeserialize, T5XFileSerialize,
+    XFileDeserializeInto, XFileSerialize, XString, assert_size, file_line_col,
     fx::{FxEffectDef, FxEffectDefRaw},
+    util::{FatPointerCountFirstU32, Ptr32, XStringRaw},
     xmodel::{PhysConstraints, PhysConstraintsRaw, PhysPreset, PhysPresetRaw, XModel, XModelRaw},
 };
 
@@ -51,6 +52,17 @@ impl<'a> XFileDeserializeInto<DestructibleDef, ()> for DestructibleDefRaw<'a> {
     }
 }
 
+impl DestructibleDef {
+    /// The number of models spawned across every stage of every piece.
+    pub fn total_model_count(&self) -> usize {
+        self.pieces
+            .iter()
+            .flat_map(|p| p.stages.iter())
+            .map(|s| s.models().count())
+            .sum()
+    }
+}
+
 impl XFileSerialize<()> for DestructibleDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -99,6 +111,9 @@ pub(crate) struct DestructiblePieceRaw<'a> {
 }
 assert_size!(DestructiblePieceRaw, 312);
 
+/// Destruction happens per piece. Each piece moves through its stages in
+/// order, starting from stage 0 (intact), as its health drops below each
+/// stage's [`DestructibleStage::health_threshold`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
 pub struct DestructiblePiece {
@@ -157,13 +172,44 @@ impl<'a> XFileDeserializeInto<DestructiblePiece, ()> for DestructiblePieceRaw<'a
     }
 }
 
+impl DestructiblePiece {
+    /// The number of stages the piece actually uses. Unused stages are
+    /// always at the end of [`Self::stages`].
+    pub fn stage_count(&self) -> usize {
+        self.stages
+            .iter()
+            .rposition(|s| !s.is_unused())
+            .map_or(1, |i| i + 1)
+    }
+
+    pub fn stage_at(&self, idx: usize) -> Result<&DestructibleStage> {
+        let count = self.stage_count();
+        if idx < count {
+            Ok(&self.stages[idx])
+        } else {
+            Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "DestructiblePiece: stage index ({idx}) >= stage count ({count})"
+                )),
+            ))
+        }
+    }
+
+    /// Whether stage `idx` is the piece's last, i.e. it can't be damaged any
+    /// further.
+    pub fn is_final_stage(&self, idx: usize) -> bool {
+        idx + 1 == self.stage_count()
+    }
+}
+
 impl XFileSerialize<()> for DestructiblePiece {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let stages = self
             .stages
             .iter()
             .map(|s| {
-                let show_bone = ser.get_or_insert_script_string(s.show_bone.get())?;
+                let show_bone = ser.intern(s.show_bone.get())?;
                 let break_effect = Ptr32::from_box(&s.break_effect);
                 let break_sound = XStringRaw::from_str(s.break_sound.get());
                 let break_notify = XStringRaw::from_str(s.break_notify.get());
@@ -265,7 +311,7 @@ impl<'a> XFileDeserializeInto<DestructibleStage, ()> for DestructibleStageRaw<'a
         _data: (),
     ) -> Result<DestructibleStage> {
         Ok(DestructibleStage {
-            show_bone: XString(self.show_bone.to_string(de).unwrap_or_default()),
+            show_bone: XString::from(self.show_bone.to_string(de).unwrap_or_default()),
             break_health: self.break_health,
             max_time: self.max_time,
             flags: self.flags,
@@ -282,3 +328,38 @@ impl<'a> XFileDeserializeInto<DestructibleStage, ()> for DestructibleStageRaw<'a
         })
     }
 }
+
+impl DestructibleStage {
+    /// The first model spawned when the stage is reached, if any.
+    pub fn model(&self) -> Option<&XModel> {
+        self.models().next()
+    }
+
+    /// Every model spawned when the stage is reached.
+    pub fn models(&self) -> impl Iterator<Item = &XModel> {
+        self.spawn_model.iter().flatten().map(|m| &**m)
+    }
+
+    /// The effect played when the stage is reached.
+    pub fn rubble_fx(&self) -> Option<&FxEffectDef> {
+        self.break_effect.as_deref()
+    }
+
+    /// The sound played when the stage is reached. Empty if there isn't one.
+    pub fn destroy_sound(&self) -> &str {
+        self.break_sound.get()
+    }
+
+    /// The piece's health at or below which it moves to this stage.
+    pub fn health_threshold(&self) -> f32 {
+        self.break_health
+    }
+
+    fn is_unused(&self) -> bool {
+        self.show_bone.get().is_empty()
+            && self.break_health == 0.0
+            && self.break_effect.is_none()
+            && self.break_sound.get().is_empty()
+            && self.spawn_model.iter().all(Option::is_none)
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
-    FatPointer, FatPointerCountFirstU32, Ptr32, Result, T5XFileDeserialize, T5XFileSerialize,
-    XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size,
+    FatPointer, Result, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize,
+    XString, assert_size,
     techset::{GfxImage, GfxImageRaw, Material, MaterialRaw},
+    util::{FatPointerCountFirstU32, Ptr32, XStringRaw},
 };
 
 use serde::{Deserialize, Serialize};
@@ -1,21 +1,28 @@
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, string::String, vec::Vec};
 
 use crate::{
-    FatPointer, Ptr32, Result, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto,
-    XFileSerialize, XString, XStringRaw, assert_size,
+    Result, T5XFileSerialize, XFileSerialize, XString, assert_size,
     techset::{Material, MaterialRaw},
+    util::{Ptr32, XStringRaw},
 };
 
 use serde::{Deserialize, Serialize};
+use t5_xfile_derive::XFileConvert;
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, Deserialize, XFileConvert)]
+#[xfile(into = Font)]
 pub(crate) struct FontRaw<'a> {
+    #[xfile(string)]
     pub font_name: XStringRaw<'a>,
     pub pixel_height: i32,
+    #[xfile(len_of = glyphs)]
     pub glyph_count: i32,
+    #[xfile(ptr)]
     pub material: Ptr32<'a, MaterialRaw<'a>>,
+    #[xfile(ptr)]
     pub glow_material: Ptr32<'a, MaterialRaw<'a>>,
+    #[xfile(array = glyph_count)]
     pub glyphs: Ptr32<'a, Glyph>,
 }
 assert_size!(FontRaw, 24);
@@ -30,45 +37,72 @@ pub struct Font {
     pub glyphs: Vec<Glyph>,
 }
 
-impl<'a> XFileDeserializeInto<Font, ()> for FontRaw<'a> {
-    fn xfile_deserialize_into(&self, de: &mut impl T5XFileDeserialize, _data: ()) -> Result<Font> {
-        Ok(Font {
-            font_name: self.font_name.xfile_deserialize_into(de, ())?,
-            pixel_height: self.pixel_height,
-            material: self.material.xfile_deserialize_into(de, ())?,
-            glow_material: self.glow_material.xfile_deserialize_into(de, ())?,
-            glyphs: self.glyphs.to_array(self.glyph_count as _).to_vec(de)?,
-        })
+impl Font {
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.iter().find(|g| g.letter as u32 == c as u32)
     }
-}
 
-impl<'a> XFileSerialize<()> for Font {
-    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let font_name = XStringRaw::from_str(self.font_name.get());
-        let glyph_count = self.glyphs.len() as _;
-        let material = Ptr32::from_box::<MaterialRaw>(&self.material);
-        let glow_material = Ptr32::from_box::<MaterialRaw>(&self.glow_material);
-        let glyphs = Ptr32::from_slice::<Glyph>(&self.glyphs);
-
-        let font = FontRaw {
-            font_name,
-            pixel_height: self.pixel_height,
-            glyph_count,
-            material,
-            glow_material,
-            glyphs,
-        };
-
-        ser.store_into_xfile(font)?;
-        self.font_name.xfile_serialize(ser, ())?;
-        self.material.xfile_serialize(ser, ())?;
-        self.glow_material.xfile_serialize(ser, ())?;
-        self.glyphs.xfile_serialize(ser, ())
+    /// How far the pen moves after drawing `c`. Characters without a glyph
+    /// are treated as half as wide as the font is tall.
+    pub fn advance(&self, c: char) -> f32 {
+        self.glyph(c)
+            .map_or(self.pixel_height as f32 / 2.0, |g| g.dx as f32)
+    }
+
+    /// Returns the width of `text` drawn on a single line, and the font's
+    /// line height, both in pixels.
+    pub fn measure_string(&self, text: &str) -> (f32, f32) {
+        let width = text.chars().map(|c| self.advance(c)).sum();
+        (width, self.pixel_height as f32)
+    }
+
+    /// Word-wraps `text` so no line is wider than `max_width`, returning
+    /// each line and its width. Lines only break at whitespace or `'\n'`, so
+    /// a single word wider than `max_width` gets a line to itself and is
+    /// still too wide.
+    pub fn measure_multiline_string(&self, text: &str, max_width: f32) -> Vec<(String, f32)> {
+        let space = self.advance(' ');
+        let mut lines = Vec::new();
+
+        for paragraph in text.split('\n') {
+            let mut line = String::new();
+            let mut line_width = 0.0;
+
+            for word in paragraph.split_whitespace() {
+                let (word_width, _) = self.measure_string(word);
+
+                if line.is_empty() {
+                    line.push_str(word);
+                    line_width = word_width;
+                } else if line_width + space + word_width <= max_width {
+                    line.push(' ');
+                    line.push_str(word);
+                    line_width += space + word_width;
+                } else {
+                    lines.push((core::mem::take(&mut line), line_width));
+                    line.push_str(word);
+                    line_width = word_width;
+                }
+            }
+
+            lines.push((line, line_width));
+        }
+
+        lines
+    }
+
+    /// Whether `text`, word-wrapped to `width`, fits in a `width` by
+    /// `height` box.
+    pub fn text_fits_in_box(&self, text: &str, width: f32, height: f32) -> bool {
+        let lines = self.measure_multiline_string(text, width);
+        let line_height = self.pixel_height as f32;
+
+        lines.iter().all(|&(_, w)| w <= width) && lines.len() as f32 * line_height <= height
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub struct Glyph {
     pub letter: u16,
     pub x0: i8,
@@ -1,17 +1,19 @@
-use core::mem::transmute;
+use core::{fmt::Write, mem::transmute};
 
-use alloc::{boxed::Box, vec, vec::Vec};
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
 use crate::{
-    Error, ErrorKind, FatPointer, FatPointerCountFirstU32, Ptr32, Ptr32ArrayConst, Result,
-    T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString,
-    XStringRaw, assert_size,
+    Error, ErrorKind, FatPointer, Result, T5XFileDeserialize, T5XFileSerialize,
+    XFileDeserializeInto, XFileSerialize, XString, assert_size,
     common::{Vec2, Vec3, Vec4},
     file_line_col,
     techset::{Material, MaterialRaw},
+    util::{FatPointerCountFirstU32, Ptr32, Ptr32ArrayConst, XStringRaw},
+    weapon::ImpactType,
+    xasset::XAssetType,
     xmodel::{XModel, XModelRaw},
 };
 
@@ -104,8 +106,148 @@ impl<'a> XFileDeserializeInto<FxEffectDef, ()> for FxEffectDefRaw<'a> {
     }
 }
 
+impl FxEffectDef {
+    /// Creates a placeholder for an effect that lives in another fastfile.
+    ///
+    /// The linker writes these as bare headers whose name is prefixed with
+    /// `','`, and the engine resolves them by name when the zone is loaded.
+    pub fn external(name: impl AsRef<str>) -> Self {
+        Self {
+            name: XString::from(alloc::format!(",{}", name.as_ref())),
+            flags: FxEffectDefFlags::empty(),
+            ef_priority: 0,
+            total_size: 0,
+            msec_looping_life: 0,
+            elem_def_count_looping: 0,
+            elem_def_count_one_shot: 0,
+            elem_def_count_emission: 0,
+            elem_defs: Vec::new(),
+            bounding_box_dim: Vec3::default(),
+            bounding_sphere: Vec4::default(),
+        }
+    }
+
+    /// Whether `self` is a reference to an effect in another fastfile (see
+    /// [`FxEffectDef::external`]).
+    pub fn is_external(&self) -> bool {
+        self.name.get().starts_with(',')
+    }
+
+    /// The names of every sound played by the effect's elements, either as
+    /// a spawn sound or by a sound element, without duplicates.
+    ///
+    /// Like the other `referenced_*` functions, this only looks at the
+    /// effect's own elements, not at effects they spawn.
+    pub fn referenced_sounds(&self) -> Vec<&str> {
+        let mut sounds = Vec::new();
+
+        for elem in &self.elem_defs {
+            let visual_sounds = elem.visuals().filter_map(|v| match v {
+                FxElemVisuals::SoundName(n) => Some(n.get()),
+                _ => None,
+            });
+
+            for sound in core::iter::once(elem.spawn_sound.spawn_sound.get()).chain(visual_sounds) {
+                if !sound.is_empty() && !sounds.contains(&sound) {
+                    sounds.push(sound);
+                }
+            }
+        }
+
+        sounds
+    }
+
+    /// Every material drawn by the effect's elements (billboards, clouds,
+    /// trails, decals, and so on), without duplicates.
+    pub fn referenced_materials(&self) -> Vec<&Material> {
+        let mut materials = Vec::<&Material>::new();
+
+        for elem in &self.elem_defs {
+            let marks = match &elem.visuals {
+                Some(FxElemDefVisuals::MarkArray(marks)) => marks.as_slice(),
+                _ => &[],
+            };
+            let elem_materials = elem
+                .visuals()
+                .filter_map(|v| match v {
+                    FxElemVisuals::Material(m) => m.as_deref(),
+                    _ => None,
+                })
+                .chain(
+                    marks
+                        .iter()
+                        .flat_map(|m| m.materials.iter().flatten().map(|m| &**m)),
+                );
+
+            for material in elem_materials {
+                if !materials
+                    .iter()
+                    .any(|m| m.info.name.get() == material.info.name.get())
+                {
+                    materials.push(material);
+                }
+            }
+        }
+
+        materials
+    }
+
+    /// Every model spawned by the effect's model elements, without
+    /// duplicates.
+    pub fn referenced_models(&self) -> Vec<&XModel> {
+        let mut models = Vec::<&XModel>::new();
+
+        for elem in &self.elem_defs {
+            let elem_models = elem.visuals().filter_map(|v| match v {
+                FxElemVisuals::Model(m) => m.as_deref(),
+                _ => None,
+            });
+
+            for model in elem_models {
+                if !models.iter().any(|m| m.name.get() == model.name.get()) {
+                    models.push(model);
+                }
+            }
+        }
+
+        models
+    }
+
+    /// An upper bound on how many particles the effect can have alive at
+    /// once, summed over its elements.
+    ///
+    /// One-shot and emitted elements spawn at most `count.base +
+    /// count.amplitude` particles. Looping elements spawn one particle per
+    /// interval, so at most as many as fit in their longest life span, and
+    /// no more than their loop count (if they have one).
+    pub fn max_particle_count(&self) -> u32 {
+        let looping = self.elem_def_count_looping.max(0) as usize;
+
+        self.elem_defs
+            .iter()
+            .enumerate()
+            .map(|(i, elem)| {
+                let [a, b] = elem.spawn;
+                if i < looping {
+                    let interval = a.max(1) as u32;
+                    let life_span =
+                        (elem.life_span_msec.base + elem.life_span_msec.amplitude).max(0);
+                    let alive = (life_span as u32).div_ceil(interval).max(1);
+                    if b > 0 { alive.min(b as u32) } else { alive }
+                } else {
+                    a.saturating_add(b).max(0) as u32
+                }
+            })
+            .fold(0u32, u32::saturating_add)
+    }
+}
+
 impl XFileSerialize<()> for FxEffectDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        if !self.is_external() && ser.is_external(XAssetType::FX, self.name.get()) {
+            return Self::external(self.name.get()).xfile_serialize(ser, ());
+        }
+
         let name = XStringRaw::from_str(self.name.get());
         let elem_defs = Ptr32::from_slice(&self.elem_defs);
 
@@ -187,7 +329,12 @@ assert_size!(FxElemDefRaw, 292);
 #[derive(Copy, Clone, Debug, PartialOrd, Ord, PartialEq, Eq, FromPrimitive)]
 #[repr(u8)]
 pub enum FxElemType {
+    /// A camera-facing sprite.
     UNKNOWN = 0x00,
+    SPRITE_ORIENTED = 0x01,
+    SPRITE_ROTATED = 0x02,
+    TAIL = 0x03,
+    LINE = 0x04,
     TRAIL = 0x05,
     CLOUD = 0x06,
     MODEL = 0x07,
@@ -198,6 +345,18 @@ pub enum FxElemType {
     RUNNER = 0x0C,
 }
 
+impl FxElemType {
+    pub fn is_light(self) -> bool {
+        self == Self::OMNI_LIGHT || self == Self::SPOT_LIGHT
+    }
+
+    /// Whether the element is drawn as sprites, which use
+    /// [`FxElemDefUnion::Billboard`].
+    pub fn is_sprite(self) -> bool {
+        self <= Self::LINE
+    }
+}
+
 bitflags! {
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[derive(Clone, Debug)]
@@ -278,6 +437,20 @@ pub struct FxElemDef {
     pub billboard_pivot: Vec2,
 }
 
+impl FxElemDef {
+    /// The element's visuals, except for decal materials (see
+    /// [`FxElemDefVisuals::MarkArray`]).
+    pub fn visuals(&self) -> impl Iterator<Item = &FxElemVisuals> {
+        let visuals = match &self.visuals {
+            Some(FxElemDefVisuals::Array(a)) => a.as_slice(),
+            Some(FxElemDefVisuals::Instance(Some(v))) => core::slice::from_ref(v),
+            _ => &[],
+        };
+
+        visuals.iter()
+    }
+}
+
 impl<'a> XFileDeserializeInto<FxElemDef, ()> for FxElemDefRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -302,9 +475,18 @@ impl<'a> XFileDeserializeInto<FxElemDef, ()> for FxElemDefRaw<'a> {
                 .to_vec_into(de)?
         };
         //dbg!(vis_samples.len());
+        // Decoded first, since it decides how the visuals and the union are
+        // laid out.
+        let elem_type =
+            num::FromPrimitive::from_u8(self.elem_type).ok_or(Error::new_with_offset(
+                file_line_col!(),
+                de.stream_pos()? as _,
+                ErrorKind::BadFromPrimitive(self.elem_type as _),
+            ))?;
+        //dbg!(&elem_type);
         let visuals = self
             .visuals
-            .xfile_deserialize_into(de, (self.elem_type, self.visual_count))?;
+            .xfile_deserialize_into(de, (elem_type, self.visual_count))?;
         //dbg!(&visuals);
         let effect_on_impact = self.effect_on_impact.xfile_deserialize_into(de, ())?;
         //dbg!(&effect_on_impact);
@@ -325,13 +507,7 @@ impl<'a> XFileDeserializeInto<FxElemDef, ()> for FxElemDefRaw<'a> {
             ErrorKind::BadBitflags(self.flags as _),
         ))?;
         //dbg!(&flags);
-        let elem_type =
-            num::FromPrimitive::from_u8(self.elem_type).ok_or(Error::new_with_offset(
-                file_line_col!(),
-                de.stream_pos()? as _,
-                ErrorKind::BadFromPrimitive(self.elem_type as _),
-            ))?;
-        //dbg!(&elem_type);
+        let u = FxElemDefUnion::from_raw(elem_type, self.u);
 
         Ok(FxElemDef {
             flags,
@@ -375,7 +551,7 @@ impl<'a> XFileDeserializeInto<FxElemDef, ()> for FxElemDefRaw<'a> {
             max_wind_strength: self.max_wind_strength,
             spawn_interval_at_max_wind: self.spawn_interval_at_max_wind,
             lifespan_at_max_wind: self.lifespan_at_max_wind,
-            u: None,
+            u,
             spawn_sound,
             billboard_pivot: self.billboard_pivot.into(),
         })
@@ -432,8 +608,13 @@ impl XFileSerialize<()> for FxElemDef {
         let trail_def = Ptr32::from_box(&self.trail_def);
         let u = if let Some(u) = &self.u {
             match u {
-                FxElemDefUnion::Billboard(b) => unsafe { transmute(b) },
-                FxElemDefUnion::CloudDensityRange(r) => unsafe { transmute(r) },
+                // SAFETY: both are 8 bytes of plain data.
+                FxElemDefUnion::Billboard(b) => unsafe {
+                    transmute::<FxBillboardTrim, [u8; 8]>(b.clone())
+                },
+                FxElemDefUnion::CloudDensityRange(r) => unsafe {
+                    transmute::<FxIntRange, [u8; 8]>(*r)
+                },
             }
         } else {
             [0u8; 8]
@@ -503,6 +684,9 @@ impl XFileSerialize<()> for FxElemDef {
     }
 }
 
+/// Which member is used depends on [`FxElemDef::elem_type`]: clouds use
+/// [`Self::CloudDensityRange`], and everything else [`Self::Billboard`]
+/// (though only sprites read it).
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
 pub enum FxElemDefUnion {
@@ -510,6 +694,26 @@ pub enum FxElemDefUnion {
     CloudDensityRange(FxIntRange),
 }
 
+impl FxElemDefUnion {
+    /// Returns [`None`] if `bytes` are all zero, unless `elem_type` is a
+    /// cloud.
+    fn from_raw(elem_type: FxElemType, bytes: [u8; 8]) -> Option<Self> {
+        if elem_type == FxElemType::CLOUD {
+            // SAFETY: `FxIntRange` is two `i32`s.
+            Some(Self::CloudDensityRange(unsafe {
+                transmute::<[u8; 8], FxIntRange>(bytes)
+            }))
+        } else if bytes != [0; 8] {
+            // SAFETY: `FxBillboardTrim` is two `f32`s.
+            Some(Self::Billboard(unsafe {
+                transmute::<[u8; 8], FxBillboardTrim>(bytes)
+            }))
+        } else {
+            None
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
 pub struct FxBillboardTrim {
@@ -572,15 +776,17 @@ pub enum FxElemDefVisuals {
     Instance(Option<FxElemVisuals>),
 }
 
-impl<'a> XFileDeserializeInto<Option<FxElemDefVisuals>, (u8, u8)> for FxElemDefVisualsRaw<'a> {
+impl<'a> XFileDeserializeInto<Option<FxElemDefVisuals>, (FxElemType, u8)>
+    for FxElemDefVisualsRaw<'a>
+{
     fn xfile_deserialize_into(
         &self,
         de: &mut impl T5XFileDeserialize,
-        (elem_type, visual_count): (u8, u8),
+        (elem_type, visual_count): (FxElemType, u8),
     ) -> Result<Option<FxElemDefVisuals>> {
         //dbg!(self, elem_type, visual_count);
 
-        if elem_type == FxElemType::DECAL as u8 {
+        if elem_type == FxElemType::DECAL {
             let mark_array = self
                 .0
                 .cast::<FxElemMarkVisualsRaw>()
@@ -588,11 +794,9 @@ impl<'a> XFileDeserializeInto<Option<FxElemDefVisuals>, (u8, u8)> for FxElemDefV
                 .xfile_deserialize_into(de, ())?;
             Ok(Some(FxElemDefVisuals::MarkArray(mark_array)))
         } else if visual_count < 2 {
-            let instance = self
-                .0
-                .cast::<FxElemVisualsRaw>()
-                .xfile_deserialize_into(de, elem_type)?;
-            Ok(Some(FxElemDefVisuals::Instance(instance.and_then(|p| *p))))
+            // The union holds the instance itself, not a pointer to it.
+            let instance = FxElemVisualsRaw(self.0).xfile_deserialize_into(de, elem_type)?;
+            Ok(Some(FxElemDefVisuals::Instance(instance)))
         } else if !self.0.is_null() {
             let array = self
                 .0
@@ -694,30 +898,26 @@ pub enum FxElemVisuals {
     SoundName(XString),
 }
 
-impl<'a> XFileDeserializeInto<Option<FxElemVisuals>, u8> for FxElemVisualsRaw<'a> {
+impl<'a> XFileDeserializeInto<Option<FxElemVisuals>, FxElemType> for FxElemVisualsRaw<'a> {
     fn xfile_deserialize_into(
         &self,
         de: &mut impl T5XFileDeserialize,
-        elem_type: u8,
+        elem_type: FxElemType,
     ) -> Result<Option<FxElemVisuals>> {
         //dbg!(self, elem_type);
 
-        if elem_type == FxElemType::MODEL as _ {
+        if elem_type == FxElemType::MODEL {
             let model = self.0.cast::<XModelRaw>().xfile_deserialize_into(de, ())?;
             Ok(Some(FxElemVisuals::Model(model)))
-        } else if elem_type == FxElemType::RUNNER as _ {
-            let effect_def = self
-                .0
-                .cast::<FxEffectDefRefRaw>()
-                .xfile_deserialize_into(de, ())?;
-            Ok(effect_def.map(|e| FxElemVisuals::EffectDef(*e)))
-        } else if elem_type == FxElemType::SOUND as _ {
+        } else if elem_type == FxElemType::RUNNER {
+            // Like the other members, held by value.
+            let effect_def = FxEffectDefRefRaw(self.0).xfile_deserialize_into(de, ())?;
+            Ok(Some(FxElemVisuals::EffectDef(effect_def)))
+        } else if elem_type == FxElemType::SOUND {
             let sound = XStringRaw::from_u32(self.0.as_u32()).xfile_deserialize_into(de, ())?;
             //dbg!(&sound);
             Ok(Some(FxElemVisuals::SoundName(sound)))
-        } else if elem_type != FxElemType::OMNI_LIGHT as _
-            && elem_type != FxElemType::SPOT_LIGHT as _
-        {
+        } else if !elem_type.is_light() {
             let material = self
                 .0
                 .cast::<MaterialRaw>()
@@ -1050,6 +1250,82 @@ impl<'a> XFileDeserializeInto<FxImpactTable, ()> for FxImpactTableRaw<'a> {
     }
 }
 
+impl FxImpactTable {
+    /// Returns the effect played when `impact` hits `surface`, if any.
+    /// [`ImpactType::COUNT`] isn't an impact type, so it never has one.
+    pub fn get(&self, impact: ImpactType, surface: SurfaceType) -> Option<&FxEffectDef> {
+        self.row(impact)?.get(surface)
+    }
+
+    /// Returns the effect played when `impact` hits flesh, if any.
+    /// [`ImpactType::COUNT`] isn't an impact type, so it never has one.
+    pub fn get_flesh(&self, impact: ImpactType, hit: FleshHitType) -> Option<&FxEffectDef> {
+        self.row(impact)?.get_flesh(hit)
+    }
+
+    fn row(&self, impact: ImpactType) -> Option<&FxImpactEntry> {
+        if impact == ImpactType::COUNT {
+            return None;
+        }
+
+        self.table.get(impact as usize)
+    }
+
+    /// Iterates over the rows of the table, labeled with the [`ImpactType`]
+    /// they belong to.
+    ///
+    /// The table has 21 rows, but T5 only names the first
+    /// [`ImpactType::COUNT`], so the rest are labeled [`None`].
+    pub fn rows(&self) -> impl Iterator<Item = (Option<ImpactType>, &FxImpactEntry)> {
+        self.table.iter().enumerate().map(|(i, e)| {
+            let impact = if i < ImpactType::COUNT as usize {
+                num::FromPrimitive::from_usize(i)
+            } else {
+                None
+            };
+            (impact, e)
+        })
+    }
+
+    /// Iterates over the column labels of the table (i.e., every
+    /// [`SurfaceType`]).
+    pub fn columns(&self) -> impl Iterator<Item = SurfaceType> {
+        (0..SurfaceType::COUNT as usize).filter_map(num::FromPrimitive::from_usize)
+    }
+
+    /// Pretty-prints the table, one row per impact type and one line per
+    /// populated surface.
+    pub fn to_table_string(&self) -> String {
+        let mut s = String::new();
+        let _ = writeln!(s, "{}", self.name.get());
+
+        for (i, (impact, entry)) in self.rows().enumerate() {
+            match impact {
+                Some(impact) => {
+                    let _ = writeln!(s, "  [{i:2}] {impact:?}");
+                }
+                None => {
+                    let _ = writeln!(s, "  [{i:2}] <unnamed>");
+                }
+            }
+
+            for (surface, effect) in entry.columns() {
+                if let Some(effect) = effect {
+                    let _ = writeln!(s, "    {:<14} {}", surface.name(), effect.name.get());
+                }
+            }
+
+            for (hit, effect) in entry.flesh_columns() {
+                if let Some(effect) = effect {
+                    let _ = writeln!(s, "    {:<14} {}", hit.name(), effect.name.get());
+                }
+            }
+        }
+
+        s
+    }
+}
+
 impl XFileSerialize<()> for FxImpactTable {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -1105,6 +1381,137 @@ impl<'a> XFileDeserializeInto<FxImpactEntry, ()> for FxImpactEntryRaw<'a> {
     }
 }
 
+impl FxImpactEntry {
+    pub fn get(&self, surface: SurfaceType) -> Option<&FxEffectDef> {
+        self.nonflesh.get(surface as usize)?.as_deref()
+    }
+
+    pub fn get_flesh(&self, hit: FleshHitType) -> Option<&FxEffectDef> {
+        self.flesh.get(hit as usize)?.as_deref()
+    }
+
+    /// Iterates over the non-flesh effects, labeled with their
+    /// [`SurfaceType`].
+    pub fn columns(&self) -> impl Iterator<Item = (SurfaceType, Option<&FxEffectDef>)> {
+        self.nonflesh
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| num::FromPrimitive::from_usize(i).map(|s| (s, e.as_deref())))
+    }
+
+    /// Iterates over the flesh effects, labeled with their [`FleshHitType`].
+    pub fn flesh_columns(&self) -> impl Iterator<Item = (FleshHitType, Option<&FxEffectDef>)> {
+        self.flesh
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| num::FromPrimitive::from_usize(i).map(|h| (h, e.as_deref())))
+    }
+}
+
+/// T5's surface types, in the order used to index
+/// [`FxImpactEntry::nonflesh`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+pub enum SurfaceType {
+    #[default]
+    DEFAULT = 0,
+    BARK = 1,
+    BRICK = 2,
+    CARPET = 3,
+    CLOTH = 4,
+    CONCRETE = 5,
+    DIRT = 6,
+    FLESH = 7,
+    FOLIAGE = 8,
+    GLASS = 9,
+    GRASS = 10,
+    GRAVEL = 11,
+    ICE = 12,
+    METAL = 13,
+    MUD = 14,
+    PAPER = 15,
+    PLASTER = 16,
+    ROCK = 17,
+    SAND = 18,
+    SNOW = 19,
+    WATER = 20,
+    WOOD = 21,
+    ASPHALT = 22,
+    CERAMIC = 23,
+    PLASTIC = 24,
+    RUBBER = 25,
+    CUSHION = 26,
+    FRUIT = 27,
+    PAINTED_METAL = 28,
+    PLAYER = 29,
+    TALL_GRASS = 30,
+    COUNT = 31,
+}
+
+impl SurfaceType {
+    /// The name the engine uses for the surface type.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::DEFAULT => "default",
+            Self::BARK => "bark",
+            Self::BRICK => "brick",
+            Self::CARPET => "carpet",
+            Self::CLOTH => "cloth",
+            Self::CONCRETE => "concrete",
+            Self::DIRT => "dirt",
+            Self::FLESH => "flesh",
+            Self::FOLIAGE => "foliage",
+            Self::GLASS => "glass",
+            Self::GRASS => "grass",
+            Self::GRAVEL => "gravel",
+            Self::ICE => "ice",
+            Self::METAL => "metal",
+            Self::MUD => "mud",
+            Self::PAPER => "paper",
+            Self::PLASTER => "plaster",
+            Self::ROCK => "rock",
+            Self::SAND => "sand",
+            Self::SNOW => "snow",
+            Self::WATER => "water",
+            Self::WOOD => "wood",
+            Self::ASPHALT => "asphalt",
+            Self::CERAMIC => "ceramic",
+            Self::PLASTIC => "plastic",
+            Self::RUBBER => "rubber",
+            Self::CUSHION => "cushion",
+            Self::FRUIT => "fruit",
+            Self::PAINTED_METAL => "paintedmetal",
+            Self::PLAYER => "player",
+            Self::TALL_GRASS => "tallgrass",
+            Self::COUNT => "",
+        }
+    }
+}
+
+/// Indexes [`FxImpactEntry::flesh`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+pub enum FleshHitType {
+    #[default]
+    BODY = 0,
+    BODY_FATAL = 1,
+    HEAD = 2,
+    HEAD_FATAL = 3,
+    COUNT = 4,
+}
+
+impl FleshHitType {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::BODY => "flesh_body",
+            Self::BODY_FATAL => "flesh_body_fatal",
+            Self::HEAD => "flesh_head",
+            Self::HEAD_FATAL => "flesh_head_fatal",
+            Self::COUNT => "",
+        }
+    }
+}
+
 impl XFileSerialize<()> for FxImpactEntry {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let nonflesh = self
@@ -1,13 +1,13 @@
 use core::mem::transmute;
 
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, format, vec::Vec};
 
 use crate::{
-    Error, ErrorKind, FatPointer, FatPointerCountFirstU16, FatPointerCountFirstU32, Ptr32, Result,
-    ScriptString, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize,
-    XString, XStringRaw, assert_size,
+    Error, ErrorKind, FatPointer, Result, ScriptString, T5XFileDeserialize, T5XFileSerialize,
+    XFileDeserializeInto, XFileSerialize, XString, assert_size,
     common::{Vec2, Vec3},
     file_line_col,
+    util::{FatPointerCountFirstU16, FatPointerCountFirstU32, Ptr32, XStringRaw},
 };
 
 use bitflags::bitflags;
@@ -20,7 +20,11 @@ pub(crate) struct GameWorldSpRaw<'a> {
     pub name: XStringRaw<'a>,
     pub path: PathDataRaw<'a>,
 }
+assert_size!(GameWorldSpRaw, 44);
 
+/// Unlike in IW4 and later, T5's game worlds are nothing but path data.
+/// There's no glass or vehicle track data in them; breakable glass is its
+/// own asset ([`Glasses`](crate::glass::Glasses)).
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
 pub struct GameWorldSp {
@@ -58,10 +62,10 @@ impl XFileSerialize<()> for GameWorldSp {
             self.path.node_tree.len(),
         );
         let path = PathDataRaw {
-            node_count: self.path.nodes.len() as _,
+            node_count: self.path.node_count() as _,
             nodes,
             basenodes,
-            chain_node_count: self.path.nodes.len() as _,
+            chain_node_count: self.path.node_count() as _,
             chain_node_for_node,
             node_for_chain_node,
             path_vis,
@@ -88,7 +92,9 @@ pub(crate) struct GameWorldMpRaw<'a> {
     pub name: XStringRaw<'a>,
     pub path: PathDataRaw<'a>,
 }
+assert_size!(GameWorldMpRaw, 44);
 
+/// See [`GameWorldSp`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
 pub struct GameWorldMp {
@@ -126,10 +132,10 @@ impl XFileSerialize<()> for GameWorldMp {
             self.path.node_tree.len(),
         );
         let path = PathDataRaw {
-            node_count: self.path.nodes.len() as _,
+            node_count: self.path.node_count() as _,
             nodes,
             basenodes,
-            chain_node_count: self.path.nodes.len() as _,
+            chain_node_count: self.path.node_count() as _,
             chain_node_for_node,
             node_for_chain_node,
             path_vis,
@@ -162,9 +168,10 @@ pub(crate) struct PathDataRaw<'a> {
     pub path_vis: FatPointerCountFirstU32<'a, u8>,
     pub node_tree: FatPointerCountFirstU32<'a, PathNodeTreeRaw>,
 }
+assert_size!(PathDataRaw, 40);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct PathData {
     pub nodes: Vec<PathNode>,
     pub basenodes: Vec<PathBaseNode>,
@@ -174,6 +181,92 @@ pub struct PathData {
     pub node_tree: Vec<PathNodeTree>,
 }
 
+/// How many node slots past the placed ones are stored for nodes the game
+/// adds at runtime.
+const RUNTIME_NODE_SLOTS: usize = 128;
+
+impl PathData {
+    /// The number of nodes placed in the level. [`Self::nodes`] has 128
+    /// more, left empty for nodes added at runtime.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len().saturating_sub(RUNTIME_NODE_SLOTS)
+    }
+
+    pub fn node_at(&self, idx: usize) -> Result<&PathNode> {
+        if idx >= self.node_count() {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "PathData: node index ({idx}) >= node count ({})",
+                    self.node_count()
+                )),
+            ));
+        }
+
+        Ok(&self.nodes[idx])
+    }
+
+    /// The nodes each placed node links to, by index, without duplicates.
+    /// Links to nodes past [`Self::node_count`] are skipped.
+    pub fn node_graph(&self) -> Vec<Vec<usize>> {
+        let count = self.node_count();
+        self.nodes[..count]
+            .iter()
+            .map(|node| {
+                let mut neighbours = Vec::<usize>::new();
+                for link in &node.constant.links {
+                    let n = link.node_num as usize;
+                    if n < count && !neighbours.contains(&n) {
+                        neighbours.push(n);
+                    }
+                }
+                neighbours
+            })
+            .collect()
+    }
+
+    /// Groups the placed nodes into sets that can reach each other, treating
+    /// links as two-way. Each set is sorted, and the sets are in order of
+    /// their first node.
+    pub fn connected_components(&self) -> Vec<Vec<usize>> {
+        let graph = self.node_graph();
+        let mut undirected = graph.clone();
+        for (a, neighbours) in graph.iter().enumerate() {
+            for &b in neighbours {
+                if !undirected[b].contains(&a) {
+                    undirected[b].push(a);
+                }
+            }
+        }
+
+        let mut seen = alloc::vec![false; graph.len()];
+        let mut components = Vec::new();
+        for start in 0..graph.len() {
+            if seen[start] {
+                continue;
+            }
+
+            seen[start] = true;
+            let mut component = Vec::new();
+            let mut stack = alloc::vec![start];
+            while let Some(n) = stack.pop() {
+                component.push(n);
+                for &m in &undirected[n] {
+                    if !seen[m] {
+                        seen[m] = true;
+                        stack.push(m);
+                    }
+                }
+            }
+
+            component.sort_unstable();
+            components.push(component);
+        }
+
+        components
+    }
+}
+
 impl<'a> XFileDeserializeInto<PathData, ()> for PathDataRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -182,11 +275,11 @@ impl<'a> XFileDeserializeInto<PathData, ()> for PathDataRaw<'a> {
     ) -> Result<PathData> {
         let nodes = self
             .nodes
-            .to_array(self.node_count as usize + 128)
+            .to_array(self.node_count as usize + RUNTIME_NODE_SLOTS)
             .xfile_deserialize_into(de, ())?;
         let basenodes = self
             .basenodes
-            .to_array(self.node_count as usize + 128)
+            .to_array(self.node_count as usize + RUNTIME_NODE_SLOTS)
             .to_vec_into(de)?;
         let chain_node_for_node = self
             .chain_node_for_node
@@ -219,7 +312,7 @@ pub(crate) struct PathNodeRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct PathNode {
     pub constant: PathNodeConstant,
     pub dynamic: PathNodeDynamic,
@@ -246,13 +339,11 @@ impl XFileSerialize<()> for PathNode {
         let constant = PathNodeConstantRaw {
             type_: self.constant.type_ as _,
             spawnflags: self.constant.spawnflags.bits(),
-            targetname: ser.get_or_insert_script_string(self.constant.targetname.get())?,
-            script_linkname: ser
-                .get_or_insert_script_string(self.constant.script_linkname.get())?,
-            script_noteworthy: ser
-                .get_or_insert_script_string(self.constant.script_noteworthy.get())?,
-            target: ser.get_or_insert_script_string(self.constant.target.get())?,
-            animscript: ser.get_or_insert_script_string(self.constant.animscript.get())?,
+            targetname: ser.intern(self.constant.targetname.get())?,
+            script_linkname: ser.intern(self.constant.script_linkname.get())?,
+            script_noteworthy: ser.intern(self.constant.script_noteworthy.get())?,
+            target: ser.intern(self.constant.target.get())?,
+            animscript: ser.intern(self.constant.animscript.get())?,
             animscriptfunc: self.constant.animscriptfunc,
             origin: self.constant.origin.get(),
             angle: self.constant.angle,
@@ -357,7 +448,7 @@ pub enum NodeType {
 
 bitflags! {
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-    #[derive(Clone, Debug)]
+    #[derive(Clone, Default, Debug)]
     pub struct SpawnFlags: u16 {
         const DONTLINK = 0x0001;
         const NOTCHAIN = 0x0002;
@@ -396,7 +487,7 @@ impl XFileSerialize<()> for PathLink {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct PathNodeConstant {
     pub type_: NodeType,
     pub spawnflags: SpawnFlags,
@@ -435,11 +526,13 @@ impl<'a> XFileDeserializeInto<PathNodeConstant, ()> for PathNodeConstantRaw<'a>
                 de.stream_pos()? as _,
                 ErrorKind::BadBitflags(self.spawnflags as _),
             ))?,
-            targetname: XString(self.targetname.to_string(de).unwrap_or_default()),
-            script_linkname: XString(self.script_linkname.to_string(de).unwrap_or_default()),
-            script_noteworthy: XString(self.script_noteworthy.to_string(de).unwrap_or_default()),
-            target: XString(self.target.to_string(de).unwrap_or_default()),
-            animscript: XString(self.animscript.to_string(de).unwrap_or_default()),
+            targetname: XString::from(self.targetname.to_string(de).unwrap_or_default()),
+            script_linkname: XString::from(self.script_linkname.to_string(de).unwrap_or_default()),
+            script_noteworthy: XString::from(
+                self.script_noteworthy.to_string(de).unwrap_or_default(),
+            ),
+            target: XString::from(self.target.to_string(de).unwrap_or_default()),
+            animscript: XString::from(self.animscript.to_string(de).unwrap_or_default()),
             animscriptfunc: self.animscriptfunc,
             origin: self.origin.into(),
             angle: self.angle,
@@ -470,7 +563,7 @@ pub(crate) struct PathNodeDynamicRaw {
 assert_size!(PathNodeDynamicRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct PathNodeDynamic {
     pub owner: SentientHandle,
     pub free_time: i32,
@@ -506,7 +599,7 @@ pub(crate) struct SentientHandleRaw {
 assert_size!(SentientHandleRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct SentientHandle {
     pub number: i16,
     pub info_index: usize,
@@ -538,7 +631,7 @@ pub(crate) struct PathNodeTransientRaw<'a> {
 assert_size!(PathNodeTransientRaw, 28);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct PathNodeTransient {
     pub search_frame: i32,
     pub cost: f32,
@@ -4,18 +4,24 @@ use core::ptr::addr_of_mut;
 
 use alloc::{
     boxed::Box,
+    collections::BTreeMap,
+    format,
     string::{String, ToString},
     vec::Vec,
 };
 
 use crate::{
-    FatPointer, FatPointerCountFirstU32, FatPointerCountLastU8, FatPointerCountLastU32, Ptr32,
-    Result, T5XFileDeserialize, XFileDeserializeInto, XString, XStringRaw, assert_size,
+    Error, ErrorKind, FatPointer, Result, T5XFileDeserialize, XFileDeserializeInto, XString,
+    assert_size,
     common::{GfxVertexBuffer, Mat3, Mat4, Vec2, Vec3, Vec4},
+    file_line_col,
     light::{GfxLightDef, GfxLightDefRaw},
     techset::{
         GfxDrawSurf, GfxImage, GfxImageRaw, GfxTexture, GfxTextureRaw, Material, MaterialRaw,
     },
+    util::{
+        FatPointerCountFirstU32, FatPointerCountLastU8, FatPointerCountLastU32, Ptr32, XStringRaw,
+    },
     xmodel::{CPlane, CPlaneRaw, GfxColor, XModel, XModelDrawInfo, XModelRaw},
 };
 
@@ -24,6 +30,20 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "d3d9")]
 use windows::Win32::Graphics::Direct3D9::{D3DPOOL_DEFAULT, IDirect3DVertexBuffer9};
 
+pub mod bvh;
+pub mod visibility;
+
+use bvh::StaticModelBvh;
+use visibility::WorldVisibility;
+
+/// Where [`GfxWorld::dpvs_static_model_aabb_tree`] caches its tree. With
+/// `std` it can be filled in from any thread, so a [`GfxWorld`] can still be
+/// shared between them.
+#[cfg(feature = "std")]
+pub type StaticModelBvhCache = std::sync::OnceLock<StaticModelBvh>;
+#[cfg(not(feature = "std"))]
+pub type StaticModelBvhCache = core::cell::OnceCell<StaticModelBvh>;
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Debug, Deserialize)]
 pub(crate) struct GfxWorldRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
@@ -151,6 +171,10 @@ pub struct GfxWorld<const MAX_LOCAL_CLIENTS: usize> {
     pub outdoor_bounds: Vec<GfxOutdoorBounds>,
     pub hero_lights: Vec<GfxHeroLight>,
     pub hero_light_tree: Vec<GfxHeroLightTree>,
+    /// Filled in by [`Self::dpvs_static_model_aabb_tree`]. It isn't part of
+    /// the asset, so it's never serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub static_model_bvh: StaticModelBvhCache,
 }
 
 impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<GfxWorld<MAX_LOCAL_CLIENTS>, ()>
@@ -324,8 +348,263 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<GfxWorld<MAX_LOCAL
             outdoor_bounds,
             hero_lights,
             hero_light_tree,
+            static_model_bvh: StaticModelBvhCache::new(),
+        })
+    }
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> GfxWorld<MAX_LOCAL_CLIENTS> {
+    pub fn reflection_probe_count(&self) -> usize {
+        self.draw.reflection_probes.len()
+    }
+
+    pub fn reflection_probe_at(&self, idx: usize) -> Result<&GfxReflectionProbe> {
+        self.draw.reflection_probes.get(idx).ok_or_else(|| {
+            Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "GfxWorld: reflection probe index ({idx}) >= probe count ({})",
+                    self.reflection_probe_count()
+                )),
+            )
         })
     }
+
+    /// Returns the index of the probe closest to `point`, and its distance
+    /// from it.
+    pub fn nearest_reflection_probe(&self, point: Vec3) -> Option<(usize, f32)> {
+        self.draw
+            .reflection_probes
+            .iter()
+            .map(|p| (p.position() - point).length())
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    pub fn sky_surface_count(&self) -> usize {
+        self.sky_start_surfs.len()
+    }
+
+    /// Returns the `idx`th sky surface. Fails if it doesn't point to a
+    /// surface with a material.
+    pub fn sky_surface_at(&self, idx: usize) -> Result<GfxSkySurface<'_>> {
+        let surface_index = *self.sky_start_surfs.get(idx).ok_or_else(|| {
+            Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "GfxWorld: sky surface index ({idx}) >= sky surface count ({})",
+                    self.sky_surface_count()
+                )),
+            )
+        })?;
+
+        let surface = usize::try_from(surface_index)
+            .ok()
+            .and_then(|i| self.dpvs.surfaces.get(i))
+            .ok_or_else(|| {
+                Error::new(
+                    file_line_col!(),
+                    ErrorKind::BrokenInvariant(format!(
+                        "GfxWorld: sky surface {idx}'s surface index ({surface_index}) is out of range (surface count is {})",
+                        self.dpvs.surfaces.len()
+                    )),
+                )
+            })?;
+
+        let material = surface.material.as_deref().ok_or_else(|| {
+            Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "GfxWorld: sky surface {idx} (surface {surface_index}) has no material"
+                )),
+            )
+        })?;
+
+        Ok(GfxSkySurface {
+            material,
+            geometric_bounds: (surface.bounds[0], surface.bounds[1]),
+        })
+    }
+
+    /// The materials used by the sky surfaces, without duplicates, in the
+    /// order they're first used. Sky surfaces without a material are
+    /// skipped.
+    pub fn all_sky_materials(&self) -> Vec<&Material> {
+        let mut materials = Vec::<&Material>::new();
+        for surface in (0..self.sky_surface_count()).filter_map(|i| self.sky_surface_at(i).ok()) {
+            let name = surface.material.info.name.get();
+            if !materials.iter().any(|m| m.info.name.get() == name) {
+                materials.push(surface.material);
+            }
+        }
+
+        materials
+    }
+
+    pub fn lightmap_atlas_count(&self) -> usize {
+        self.draw.lightmaps.len()
+    }
+
+    pub fn lightmap_atlas_at(&self, idx: usize) -> Result<&GfxLightmapArray> {
+        self.draw.lightmaps.get(idx).ok_or_else(|| {
+            Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "GfxWorld: lightmap atlas index ({idx}) >= atlas count ({})",
+                    self.lightmap_atlas_count()
+                )),
+            )
+        })
+    }
+
+    /// Returns the index of the lightmap atlas surface `surface_idx` is lit
+    /// from, and the bounds of its vertices' lightmap coordinates in it, as
+    /// `(atlas_index, u_min, v_min, u_max, v_max)`.
+    ///
+    /// Surfaces don't store a lightmap rectangle of their own, so the bounds
+    /// are computed from [`GfxWorldVertex::lmap_coord`]. Fails if the surface
+    /// or its atlas don't exist, or if its vertices are out of range.
+    pub fn surface_lightmap_uv(&self, surface_idx: usize) -> Result<(u8, f32, f32, f32, f32)> {
+        let surface = self.dpvs.surfaces.get(surface_idx).ok_or_else(|| {
+            Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "GfxWorld: surface index ({surface_idx}) >= surface count ({})",
+                    self.dpvs.surfaces.len()
+                )),
+            )
+        })?;
+
+        let atlas_index = surface.lightmap_index;
+        self.lightmap_atlas_at(atlas_index)?;
+
+        let tris = &surface.tris;
+        let vertices = usize::try_from(tris.first_vertex)
+            .ok()
+            .and_then(|first| self.draw.vd.vertices.get(first..first + tris.vertex_count))
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| {
+                Error::new(
+                    file_line_col!(),
+                    ErrorKind::BrokenInvariant(format!(
+                        "GfxWorld: surface {surface_idx}'s vertices ({}..{}+{}) are empty or out of range (vertex count is {})",
+                        tris.first_vertex,
+                        tris.first_vertex,
+                        tris.vertex_count,
+                        self.draw.vd.vertices.len()
+                    )),
+                )
+            })?;
+
+        let (mut min, mut max) = ([f32::MAX; 2], [f32::MIN; 2]);
+        for v in vertices {
+            let coord = v.lmap_coord.get();
+            for i in 0..2 {
+                min[i] = min[i].min(coord[i]);
+                max[i] = max[i].max(coord[i]);
+            }
+        }
+
+        Ok((atlas_index as _, min[0], min[1], max[0], max[1]))
+    }
+
+    /// The number of vertices across [`GfxWorldDpvsStatic::surfaces`].
+    pub fn total_vertex_count(&self) -> u64 {
+        self.dpvs
+            .surfaces
+            .iter()
+            .map(|s| s.tris.vertex_count as u64)
+            .sum()
+    }
+
+    /// The number of triangles across [`GfxWorldDpvsStatic::surfaces`],
+    /// i.e. their index count / 3.
+    pub fn total_triangle_count(&self) -> u64 {
+        self.dpvs
+            .surfaces
+            .iter()
+            .map(|s| s.tris.tri_count as u64)
+            .sum()
+    }
+
+    /// The number of distinct materials (by name) used by
+    /// [`GfxWorldDpvsStatic::surfaces`].
+    pub fn unique_material_count(&self) -> usize {
+        self.surface_count_by_material().len()
+    }
+
+    /// `0.0` if there are no surfaces.
+    pub fn average_vertices_per_surface(&self) -> f32 {
+        match self.dpvs.surfaces.len() {
+            0 => 0.0,
+            n => self.total_vertex_count() as f32 / n as f32,
+        }
+    }
+
+    /// How many of [`GfxWorldDpvsStatic::surfaces`] use each material, by
+    /// name. Surfaces without a material aren't counted.
+    pub fn surface_count_by_material(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for material in self
+            .dpvs
+            .surfaces
+            .iter()
+            .filter_map(|s| s.material.as_ref())
+        {
+            *counts
+                .entry(material.info.name.get().to_string())
+                .or_default() += 1;
+        }
+
+        counts
+    }
+
+    /// The index and vertex count of the surface with the most vertices,
+    /// or the first of them if there's a tie. [`None`] if there are no
+    /// surfaces.
+    pub fn largest_surface(&self) -> Option<(usize, usize)> {
+        self.dpvs
+            .surfaces
+            .iter()
+            .map(|s| s.tris.vertex_count)
+            .enumerate()
+            .reduce(|largest, s| if s.1 > largest.1 { s } else { largest })
+    }
+
+    /// A [`WorldVisibility`] over [`Self::cells`], using
+    /// [`Self::cell_caster_bits`] for visibility.
+    pub fn visibility(&self) -> Result<WorldVisibility<'_>> {
+        WorldVisibility::new(&self.cells, &self.cell_caster_bits)
+    }
+
+    /// Builds a [`StaticModelBvh`] over [`GfxWorldDpvsStatic::smodel_insts`].
+    pub fn build_static_model_bvh(&self) -> StaticModelBvh {
+        StaticModelBvh::new(&self.dpvs.smodel_insts)
+    }
+
+    /// Like [`Self::build_static_model_bvh`], but the tree is only built the
+    /// first time and reused after that.
+    ///
+    /// After changing [`GfxWorldDpvsStatic::smodel_insts`], call
+    /// [`Self::invalidate_static_model_bvh`] so the next call rebuilds it.
+    pub fn dpvs_static_model_aabb_tree(&self) -> &StaticModelBvh {
+        self.static_model_bvh
+            .get_or_init(|| self.build_static_model_bvh())
+    }
+
+    /// Drops the tree cached by [`Self::dpvs_static_model_aabb_tree`].
+    pub fn invalidate_static_model_bvh(&mut self) {
+        self.static_model_bvh.take();
+    }
+}
+
+/// A surface drawn as part of the sky, as returned by
+/// [`GfxWorld::sky_surface_at`].
+#[derive(Copy, Clone, Debug)]
+pub struct GfxSkySurface<'a> {
+    pub material: &'a Material,
+    /// The surface's (mins, maxs).
+    pub geometric_bounds: (Vec3, Vec3),
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -463,7 +742,7 @@ impl<const MAX_LOCAL_CLIENTS: usize> From<SunLightParseParamsRaw<MAX_LOCAL_CLIEN
 {
     fn from(value: SunLightParseParamsRaw<MAX_LOCAL_CLIENTS>) -> Self {
         Self {
-            name: XString(value.name.to_string()),
+            name: XString::from(value.name.to_string()),
             tree_scatter_intensity: value.tree_scatter_intensity,
             tree_scatter_amount: value.tree_scatter_amount,
             sun_settings: value
@@ -1118,6 +1397,17 @@ impl<'a> XFileDeserializeInto<GfxReflectionProbe, ()> for GfxReflectionProbeRaw<
     }
 }
 
+impl GfxReflectionProbe {
+    pub fn position(&self) -> Vec3 {
+        self.origin
+    }
+
+    /// The probe's environment cubemap.
+    pub fn image(&self) -> Option<&GfxImage> {
+        self.image.as_deref()
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Debug, Deserialize)]
 pub(crate) struct GfxReflectionProbeVolumeDataRaw {
@@ -1163,6 +1453,17 @@ pub struct GfxLightmapArray {
     pub secondary_b: Option<Box<GfxImage>>,
 }
 
+impl GfxLightmapArray {
+    pub fn primary_image(&self) -> Option<&GfxImage> {
+        self.primary.as_deref()
+    }
+
+    /// Used for HDR lightmaps.
+    pub fn secondary_image(&self) -> Option<&GfxImage> {
+        self.secondary.as_deref()
+    }
+}
+
 impl<'a> XFileDeserializeInto<GfxLightmapArray, ()> for GfxLightmapArrayRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -1199,7 +1500,7 @@ pub struct GfxWorldVertexData {
 
 impl<'a> XFileDeserializeInto<GfxWorldVertexData, u32> for GfxWorldVertexDataRaw<'a> {
     #[cfg(feature = "d3d9")]
-    fn xfile_into(
+    fn xfile_deserialize_into(
         &self,
         de: &mut impl T5XFileDeserialize,
         vertex_count: u32,
@@ -1208,11 +1509,12 @@ impl<'a> XFileDeserializeInto<GfxWorldVertexData, u32> for GfxWorldVertexDataRaw
             .vertices
             .to_array(vertex_count as _)
             .to_vec_into::<GfxWorldVertex>(de)?;
-        let world_vb = if de.create_d3d9() {
-            let count = vertex_count as usize * sizeof!(GfxWorldVertex);
+        let world_vb = if let Some(device) = de.d3d9_device() {
+            let win_err = |e: windows::core::Error| Error::new(file_line_col!(), e.into());
+            let count = vertex_count as usize * size_of::<GfxWorldVertex>();
             let mut vb = Option::<IDirect3DVertexBuffer9>::None;
             unsafe {
-                de.d3d9_state().unwrap().device.CreateVertexBuffer(
+                device.CreateVertexBuffer(
                     count as u32,
                     8,
                     0,
@@ -1220,12 +1522,13 @@ impl<'a> XFileDeserializeInto<GfxWorldVertexData, u32> for GfxWorldVertexDataRaw
                     addr_of_mut!(vb),
                     core::ptr::null_mut(),
                 )
-            }?;
+            }
+            .map_err(win_err)?;
             let vb = vb.unwrap();
             let mut ppbdata = core::ptr::null_mut();
-            unsafe { vb.Lock(0, 0, addr_of_mut!(ppbdata), 0) }?;
-            unsafe { std::ptr::copy(vertices.as_ptr().cast::<u8>(), ppbdata.cast::<u8>(), count) };
-            unsafe { vb.Unlock() }?;
+            unsafe { vb.Lock(0, 0, addr_of_mut!(ppbdata), 0) }.map_err(win_err)?;
+            unsafe { core::ptr::copy(vertices.as_ptr().cast::<u8>(), ppbdata.cast::<u8>(), count) };
+            unsafe { vb.Unlock() }.map_err(win_err)?;
             Some(Box::new(GfxVertexBuffer(vb)))
         } else {
             None
@@ -2278,7 +2581,7 @@ impl From<OccluderRaw> for Occluder {
     fn from(value: OccluderRaw) -> Self {
         Self {
             flags: value.flags,
-            name: XString(value.name.to_string()),
+            name: XString::from(value.name.to_string()),
             points: [
                 value.points[0].into(),
                 value.points[1].into(),
@@ -0,0 +1,342 @@
+//! A bounding volume hierarchy over a [`GfxWorld`]'s static models, for
+//! box and ray queries that don't have to test every model.
+//!
+//! [`GfxWorld`]: crate::gfx_world::GfxWorld
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{common::Vec3, gfx_world::GfxStaticModelInst};
+
+/// The most models a leaf holds, unless their centroids are all in the same
+/// place.
+const MAX_LEAF_SIZE: usize = 4;
+
+/// How many buckets the centroids are sorted into when looking for a split.
+const BIN_COUNT: usize = 16;
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug)]
+struct Aabb {
+    mins: [f32; 3],
+    maxs: [f32; 3],
+}
+
+impl Aabb {
+    const EMPTY: Self = Self {
+        mins: [f32::INFINITY; 3],
+        maxs: [f32::NEG_INFINITY; 3],
+    };
+
+    fn union(self, other: Self) -> Self {
+        Self {
+            mins: core::array::from_fn(|i| self.mins[i].min(other.mins[i])),
+            maxs: core::array::from_fn(|i| self.maxs[i].max(other.maxs[i])),
+        }
+    }
+
+    fn centroid(self) -> [f32; 3] {
+        core::array::from_fn(|i| (self.mins[i] + self.maxs[i]) * 0.5)
+    }
+
+    fn surface_area(self) -> f32 {
+        let [x, y, z]: [f32; 3] = core::array::from_fn(|i| (self.maxs[i] - self.mins[i]).max(0.0));
+        2.0 * (x * y + y * z + z * x)
+    }
+
+    fn overlaps(self, other: Self) -> bool {
+        (0..3).all(|i| self.mins[i] <= other.maxs[i] && other.mins[i] <= self.maxs[i])
+    }
+
+    /// Where the ray enters the box, if it does so within `max_dist`. A ray
+    /// starting inside the box enters it at `0.0`.
+    fn ray_entry(self, origin: [f32; 3], dir: [f32; 3], max_dist: f32) -> Option<f32> {
+        let (mut near, mut far) = (0.0f32, max_dist);
+        for i in 0..3 {
+            if dir[i] == 0.0 {
+                if origin[i] < self.mins[i] || origin[i] > self.maxs[i] {
+                    return None;
+                }
+                continue;
+            }
+
+            let inv = 1.0 / dir[i];
+            let t0 = (self.mins[i] - origin[i]) * inv;
+            let t1 = (self.maxs[i] - origin[i]) * inv;
+            near = near.max(t0.min(t1));
+            far = far.min(t0.max(t1));
+            if near > far {
+                return None;
+            }
+        }
+
+        Some(near)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+struct BvhNode {
+    bounds: Aabb,
+    /// For a leaf, where its models start in [`StaticModelBvh::indices`].
+    /// Otherwise, the index of the right child. The left child always comes
+    /// right after its parent.
+    first: usize,
+    /// How many models a leaf holds. `0` for interior nodes.
+    count: usize,
+}
+
+/// Where to split a node's models: everything in bins `..=boundary` along
+/// `axis` goes left.
+struct Split {
+    axis: usize,
+    min: f32,
+    extent: f32,
+    boundary: usize,
+}
+
+impl Split {
+    fn bin(&self, centroid: [f32; 3]) -> usize {
+        bin(centroid[self.axis], self.min, self.extent)
+    }
+}
+
+fn bin(x: f32, min: f32, extent: f32) -> usize {
+    (((x - min) / extent * BIN_COUNT as f32) as usize).min(BIN_COUNT - 1)
+}
+
+/// A BVH over static model bounds, built with a binned surface area
+/// heuristic (SAH).
+///
+/// Models are identified by their index in the slice the tree was built
+/// from, i.e. [`GfxWorldDpvsStatic::smodel_insts`].
+///
+/// [`GfxWorldDpvsStatic::smodel_insts`]: crate::gfx_world::GfxWorldDpvsStatic::smodel_insts
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Default)]
+pub struct StaticModelBvh {
+    nodes: Vec<BvhNode>,
+    /// Model indices, grouped by leaf.
+    indices: Vec<usize>,
+    /// The bounds of each model in `indices`, in the same order.
+    bounds: Vec<Aabb>,
+}
+
+impl StaticModelBvh {
+    /// Builds the tree over `insts`' bounds.
+    pub fn new(insts: &[GfxStaticModelInst]) -> Self {
+        let bounds = insts
+            .iter()
+            .map(|i| Aabb {
+                mins: i.mins.get(),
+                maxs: i.maxs.get(),
+            })
+            .collect::<Vec<_>>();
+        let centroids = bounds.iter().map(|b| b.centroid()).collect::<Vec<_>>();
+
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(insts.len().div_ceil(MAX_LEAF_SIZE) * 2),
+            indices: (0..insts.len()).collect(),
+            bounds: Vec::new(),
+        };
+        if !insts.is_empty() {
+            bvh.build(&bounds, &centroids, 0, insts.len());
+        }
+        bvh.bounds = bvh.indices.iter().map(|&i| bounds[i]).collect();
+
+        bvh
+    }
+
+    /// The number of nodes in the tree, leaves included. `0` if it was built
+    /// from no models.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The indices of the models whose bounds overlap the box from `mins` to
+    /// `maxs`, in ascending order. Touching counts as overlapping.
+    pub fn query_aabb(&self, mins: Vec3, maxs: Vec3) -> Vec<usize> {
+        let query = Aabb {
+            mins: mins.get(),
+            maxs: maxs.get(),
+        };
+
+        let mut hits = Vec::new();
+        self.traverse(
+            |b| b.overlaps(query),
+            |i, b| {
+                if b.overlaps(query) {
+                    hits.push(i);
+                }
+            },
+        );
+
+        hits.sort_unstable();
+        hits
+    }
+
+    /// The models hit by the ray from `origin` along `dir`, within
+    /// `max_dist`, sorted from nearest to farthest.
+    ///
+    /// Each hit comes with where the ray enters the model's bounds, as a
+    /// multiple of `dir`. For a normalized `dir`, that's the distance from
+    /// `origin`. Models that `origin` is inside of are hit at `0.0`.
+    pub fn query_ray(&self, origin: Vec3, dir: Vec3, max_dist: f32) -> Vec<(usize, f32)> {
+        let (origin, dir) = (origin.get(), dir.get());
+
+        let mut hits = Vec::new();
+        self.traverse(
+            |b| b.ray_entry(origin, dir, max_dist).is_some(),
+            |i, b| {
+                if let Some(t) = b.ray_entry(origin, dir, max_dist) {
+                    hits.push((i, t));
+                }
+            },
+        );
+
+        hits.sort_by(|(ia, a), (ib, b)| a.total_cmp(b).then(ia.cmp(ib)));
+        hits
+    }
+
+    /// Calls `visit` with the index and bounds of each model in every leaf
+    /// whose bounds, and whose ancestors' bounds, pass `enter`.
+    fn traverse(&self, enter: impl Fn(Aabb) -> bool, mut visit: impl FnMut(usize, Aabb)) {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let mut stack = Vec::from([0]);
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if !enter(node.bounds) {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack.push(node.first);
+                stack.push(n + 1);
+                continue;
+            }
+
+            let leaf = node.first..node.first + node.count;
+            for (&i, &b) in self.indices[leaf.clone()].iter().zip(&self.bounds[leaf]) {
+                visit(i, b);
+            }
+        }
+    }
+
+    /// Builds the subtree over `self.indices[start..end]`, rooted at the
+    /// next node pushed.
+    fn build(&mut self, bounds: &[Aabb], centroids: &[[f32; 3]], start: usize, end: usize) {
+        let models = &self.indices[start..end];
+        let node_bounds = models
+            .iter()
+            .fold(Aabb::EMPTY, |acc, &i| acc.union(bounds[i]));
+
+        let n = self.nodes.len();
+        self.nodes.push(BvhNode {
+            bounds: node_bounds,
+            first: start,
+            count: end - start,
+        });
+
+        if models.len() <= MAX_LEAF_SIZE {
+            return;
+        }
+        let Some(split) = find_split(bounds, centroids, models) else {
+            return;
+        };
+
+        // The split was picked from the same bins, so both sides get at
+        // least one model.
+        let mid = start
+            + partition(&mut self.indices[start..end], |&i| {
+                split.bin(centroids[i]) <= split.boundary
+            });
+
+        self.nodes[n].count = 0;
+        self.build(bounds, centroids, start, mid);
+        self.nodes[n].first = self.nodes.len();
+        self.build(bounds, centroids, mid, end);
+    }
+}
+
+/// Picks the split of `models` with the lowest SAH cost, or [`None`] if
+/// they can't be split.
+fn find_split(bounds: &[Aabb], centroids: &[[f32; 3]], models: &[usize]) -> Option<Split> {
+    let (min, max) = models.iter().fold(
+        ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]),
+        |(min, max), &i| {
+            let c = centroids[i];
+            (
+                core::array::from_fn(|a| min[a].min(c[a])),
+                core::array::from_fn(|a| max[a].max(c[a])),
+            )
+        },
+    );
+    let extent: [f32; 3] = core::array::from_fn(|a| max[a] - min[a]);
+    let axis = (0..3).max_by(|&a, &b| extent[a].total_cmp(&extent[b]))?;
+    if extent[axis] <= 0.0 {
+        // Every centroid is in the same place, so there's no way to split
+        // them.
+        return None;
+    }
+
+    let mut bins = [(Aabb::EMPTY, 0usize); BIN_COUNT];
+    for &i in models {
+        let bin = &mut bins[bin(centroids[i][axis], min[axis], extent[axis])];
+        bin.0 = bin.0.union(bounds[i]);
+        bin.1 += 1;
+    }
+
+    // The area and model count of everything left of each boundary...
+    let mut left = [(0.0, 0usize); BIN_COUNT - 1];
+    let (mut acc, mut count) = (Aabb::EMPTY, 0);
+    for (b, slot) in bins.iter().zip(&mut left) {
+        acc = acc.union(b.0);
+        count += b.1;
+        *slot = (acc.surface_area(), count);
+    }
+
+    // ...and then everything right of it.
+    let (mut acc, mut count) = (Aabb::EMPTY, 0);
+    let mut best = None::<(usize, f32)>;
+    for boundary in (0..BIN_COUNT - 1).rev() {
+        acc = acc.union(bins[boundary + 1].0);
+        count += bins[boundary + 1].1;
+
+        let (left_area, left_count) = left[boundary];
+        if left_count == 0 || count == 0 {
+            continue;
+        }
+
+        let cost = left_area * left_count as f32 + acc.surface_area() * count as f32;
+        if best.is_none_or(|(_, c)| cost < c) {
+            best = Some((boundary, cost));
+        }
+    }
+
+    let (boundary, _) = best?;
+    Some(Split {
+        axis,
+        min: min[axis],
+        extent: extent[axis],
+        boundary,
+    })
+}
+
+/// Moves every element of `v` that passes `pred` to the front, and returns
+/// how many there are.
+fn partition<T>(v: &mut [T], pred: impl Fn(&T) -> bool) -> usize {
+    let mut mid = 0;
+    for i in 0..v.len() {
+        if pred(&v[i]) {
+            v.swap(i, mid);
+            mid += 1;
+        }
+    }
+
+    mid
+}
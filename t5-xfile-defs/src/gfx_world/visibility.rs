@@ -0,0 +1,166 @@
+//! A view of a [`GfxWorld`]'s cells and the portals between them, for
+//! asking which cells can see each other.
+//!
+//! T5 doesn't store a PVS: what's drawn is worked out each frame by
+//! flooding through portals. The closest thing in the asset is
+//! [`GfxWorld::cell_caster_bits`], a cell-by-cell bit matrix of which cells
+//! can cast shadows into which. [`GfxWorld::visibility`] uses that for
+//! [`WorldVisibility::visible`]; [`WorldVisibility::new`] takes any matrix
+//! in the same layout.
+//!
+//! [`GfxWorld`]: crate::gfx_world::GfxWorld
+//! [`GfxWorld::cell_caster_bits`]: crate::gfx_world::GfxWorld::cell_caster_bits
+//! [`GfxWorld::visibility`]: crate::gfx_world::GfxWorld::visibility
+
+use alloc::{format, vec::Vec};
+
+use crate::{Error, ErrorKind, Result, common::Vec3, file_line_col, gfx_world::GfxCell};
+
+/// How far outside a cell's bounds a portal's centroid can be and still
+/// count as leading into it. Portals sit on cell boundaries, so rounding
+/// can put them just outside.
+const PORTAL_EPSILON: f32 = 0.5;
+
+/// A portal from one cell into another.
+#[derive(Clone, Debug)]
+pub struct CellPortal {
+    /// The cell the portal belongs to, i.e. the one it's seen from.
+    pub from: usize,
+    /// The cell on the other side.
+    pub to: usize,
+    pub winding: Vec<Vec3>,
+}
+
+/// See the [module docs](self).
+#[derive(Clone, Debug)]
+pub struct WorldVisibility<'a> {
+    cells: &'a [GfxCell],
+    vis_bits: &'a [u32],
+    row_words: usize,
+    portals: Vec<CellPortal>,
+}
+
+impl<'a> WorldVisibility<'a> {
+    /// `vis_bits` holds a row for each cell, each
+    /// `cells.len().div_ceil(32)` words long. Bit `b` of row `a` is set if
+    /// cell `a` can see cell `b`, with bits counted from the least
+    /// significant bit of each word.
+    ///
+    /// Returns [`ErrorKind::BrokenInvariant`] if `vis_bits` is the wrong
+    /// length.
+    ///
+    /// A portal's destination is [`GfxPortal::cell`] if that's set,
+    /// matched to a cell by its bounds. Since the deserializer leaves
+    /// pointers into other arrays unresolved, it's usually unset, in which
+    /// case the destination is the first other cell whose bounds contain
+    /// the portal's centroid. Portals that lead nowhere are left out.
+    ///
+    /// [`GfxPortal::cell`]: crate::gfx_world::GfxPortal::cell
+    pub fn new(cells: &'a [GfxCell], vis_bits: &'a [u32]) -> Result<Self> {
+        let row_words = cells.len().div_ceil(32);
+        if vis_bits.len() != row_words * cells.len() {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "WorldVisibility: {} visibility words for {} cells, expected {}",
+                    vis_bits.len(),
+                    cells.len(),
+                    row_words * cells.len()
+                )),
+            ));
+        }
+
+        let mut portals = Vec::new();
+        for (from, cell) in cells.iter().enumerate() {
+            for portal in &cell.portals {
+                let to = match &portal.cell {
+                    Some(target) => cells.iter().position(|c| {
+                        c.mins.get() == target.mins.get() && c.maxs.get() == target.maxs.get()
+                    }),
+                    None => centroid(&portal.vertices).and_then(|p| {
+                        (0..cells.len())
+                            .find(|&i| i != from && contains(&cells[i], p, PORTAL_EPSILON))
+                    }),
+                };
+
+                if let Some(to) = to {
+                    portals.push(CellPortal {
+                        from,
+                        to,
+                        winding: portal.vertices.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            cells,
+            vis_bits,
+            row_words,
+            portals,
+        })
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// `(mins, maxs)` of cell `cell`.
+    pub fn cell_bounds(&self, cell: usize) -> Option<(Vec3, Vec3)> {
+        self.cells.get(cell).map(|c| (c.mins, c.maxs))
+    }
+
+    /// Every portal, in order of the cell it belongs to.
+    pub fn portals(&self) -> &[CellPortal] {
+        &self.portals
+    }
+
+    /// The portals leading from `a` into `b` or from `b` into `a`.
+    pub fn portals_between(&self, a: usize, b: usize) -> impl Iterator<Item = &CellPortal> {
+        self.portals
+            .iter()
+            .filter(move |p| (p.from, p.to) == (a, b) || (p.from, p.to) == (b, a))
+    }
+
+    /// Whether cell `a` can see cell `b`. `false` if either is out of
+    /// range.
+    pub fn visible(&self, a: usize, b: usize) -> bool {
+        if a >= self.cells.len() || b >= self.cells.len() {
+            return false;
+        }
+
+        self.vis_bits[a * self.row_words + b / 32] & (1 << (b % 32)) != 0
+    }
+
+    /// The cells whose bounds contain `point`. Cells can overlap, so there
+    /// may be more than one.
+    ///
+    /// This uses the cells' own bounds rather than their
+    /// [`GfxCell::aabb_tree`]s, which only bound the surfaces in each cell
+    /// and so miss the empty space in it.
+    pub fn cells_containing(&self, point: Vec3) -> Vec<usize> {
+        let point = point.get();
+        (0..self.cells.len())
+            .filter(|&i| contains(&self.cells[i], point, 0.0))
+            .collect()
+    }
+}
+
+fn contains(cell: &GfxCell, point: [f32; 3], epsilon: f32) -> bool {
+    let (mins, maxs) = (cell.mins.get(), cell.maxs.get());
+    (0..3).all(|i| mins[i] - epsilon <= point[i] && point[i] <= maxs[i] + epsilon)
+}
+
+fn centroid(vertices: &[Vec3]) -> Option<[f32; 3]> {
+    if vertices.is_empty() {
+        return None;
+    }
+
+    let mut sum = [0.0; 3];
+    for v in vertices {
+        for (s, c) in sum.iter_mut().zip(v.get()) {
+            *s += c;
+        }
+    }
+    Some(sum.map(|s| s / vertices.len() as f32))
+}
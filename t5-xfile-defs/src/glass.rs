@@ -1,9 +1,10 @@
 use crate::{
-    FatPointer, FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result, T5XFileDeserialize,
-    T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size,
+    FatPointer, Result, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize,
+    XString, assert_size,
     common::{Mat3, Vec2, Vec3},
     fx::{FxEffectDef, FxEffectDefRaw},
     techset::{Material, MaterialRaw},
+    util::{FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, XStringRaw},
 };
 
 use serde::{Deserialize, Serialize};
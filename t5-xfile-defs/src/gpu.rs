@@ -0,0 +1,264 @@
+//! Uploading deserialized resources to the GPU.
+//!
+//! Attach a [`GpuUploader`] to the deserializer and every [`GfxImage`] with
+//! a [`GfxImageLoadDef`](crate::techset::GfxImageLoadDef) will be passed to
+//! it as soon as it's deserialized. The handle it returns ends up in
+//! [`GfxImage::gpu_handle`].
+//!
+//! [`NullUploader`] doesn't touch a GPU at all, which makes it useful for
+//! checking that images *could* be uploaded. With the `d3d9` feature,
+//! [`D3D9Uploader`] creates real D3D9 textures.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error, ErrorKind, Result, file_line_col,
+    techset::{GfxImage, d3dfmt},
+};
+
+#[cfg(feature = "d3d9")]
+use windows::Win32::Graphics::Direct3D9::{
+    D3DCUBEMAP_FACES, D3DFORMAT, D3DLOCKED_RECT, D3DPOOL_MANAGED, IDirect3DCubeTexture9,
+    IDirect3DDevice9, IDirect3DTexture9,
+};
+
+/// Identifies a resource created by a [`GpuUploader`]. What it actually
+/// refers to is up to the uploader.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GpuHandle(pub u64);
+
+pub trait GpuUploader {
+    fn upload_image(&mut self, img: &GfxImage) -> Result<GpuHandle>;
+}
+
+/// A [`GpuUploader`] that doesn't upload anything. It still checks that the
+/// image's format is supported and that its pixel data (if any) covers every
+/// face and mip level, so it fails in the same places a real uploader would.
+#[derive(Clone, Default, Debug)]
+pub struct NullUploader {
+    uploaded: u64,
+}
+
+impl NullUploader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How many images have been "uploaded" so far.
+    pub fn uploaded(&self) -> u64 {
+        self.uploaded
+    }
+}
+
+impl GpuUploader for NullUploader {
+    fn upload_image(&mut self, img: &GfxImage) -> Result<GpuHandle> {
+        check_format(img)?;
+
+        if !img.pixel_data().is_empty() {
+            for face in 0..img.face_count() {
+                for level in 0..img.mip_count() {
+                    img.face_mip_data(face as _, level)?;
+                }
+            }
+        }
+
+        let handle = GpuHandle(self.uploaded);
+        self.uploaded += 1;
+        Ok(handle)
+    }
+}
+
+/// Returns the image's format if it's one that can be uploaded.
+fn check_format(img: &GfxImage) -> Result<i32> {
+    let format = img.format().unwrap_or_default();
+
+    if d3dfmt::block_info(format).is_some() && d3dfmt::swap_unit(format).is_some() {
+        Ok(format)
+    } else {
+        Err(Error::new(
+            file_line_col!(),
+            ErrorKind::UnsupportedImageFormat(format as _),
+        ))
+    }
+}
+
+/// Byte-swaps `data` in units of `unit` bytes. Console images are stored
+/// big-endian, and need to be swapped before they're usable on PC.
+///
+/// This only fixes endianness. Xbox 360 images are also tiled, which isn't
+/// undone here.
+pub fn byte_swapped(data: &[u8], unit: usize) -> Vec<u8> {
+    if unit <= 1 {
+        return data.to_vec();
+    }
+
+    data.chunks(unit)
+        .flat_map(|c| c.iter().rev().copied())
+        .collect()
+}
+
+/// The size in bytes of each row of blocks in mip level `level`, and the
+/// number of rows.
+#[cfg_attr(not(feature = "d3d9"), allow(dead_code))]
+fn mip_rows(img: &GfxImage, format: i32, level: usize) -> Result<(usize, usize)> {
+    let (width, height) = img.mip_dimensions(level)?;
+    let (block_dim, block_bytes) = d3dfmt::block_info(format).unwrap();
+
+    Ok((
+        (width as usize).div_ceil(block_dim) * block_bytes,
+        (height as usize).div_ceil(block_dim),
+    ))
+}
+
+#[cfg(feature = "d3d9")]
+#[derive(Clone, Debug)]
+pub enum D3D9Texture {
+    Texture(IDirect3DTexture9),
+    Cube(IDirect3DCubeTexture9),
+}
+
+/// Creates a managed-pool D3D9 texture for every image it's given. The
+/// returned [`GpuHandle`]s are indices into [`Self::textures`].
+#[cfg(feature = "d3d9")]
+pub struct D3D9Uploader {
+    device: IDirect3DDevice9,
+    big_endian: bool,
+    textures: Vec<D3D9Texture>,
+}
+
+#[cfg(feature = "d3d9")]
+impl D3D9Uploader {
+    /// `big_endian` should be set for images from console fastfiles.
+    pub fn new(device: IDirect3DDevice9, big_endian: bool) -> Self {
+        Self {
+            device,
+            big_endian,
+            textures: Vec::new(),
+        }
+    }
+
+    pub fn textures(&self) -> &[D3D9Texture] {
+        &self.textures
+    }
+
+    pub fn texture(&self, handle: GpuHandle) -> Option<&D3D9Texture> {
+        self.textures.get(handle.0 as usize)
+    }
+
+    /// Copies mip `level` of face `face` into the locked rect `rect`.
+    fn fill(
+        &self,
+        img: &GfxImage,
+        format: i32,
+        face: u8,
+        level: usize,
+        rect: &D3DLOCKED_RECT,
+    ) -> Result<()> {
+        let data = img.face_mip_data(face, level)?;
+        let data = if self.big_endian {
+            byte_swapped(data, d3dfmt::swap_unit(format).unwrap())
+        } else {
+            data.to_vec()
+        };
+
+        let (row_bytes, rows) = mip_rows(img, format, level)?;
+        for (row, src) in data.chunks(row_bytes).take(rows).enumerate() {
+            unsafe {
+                let dst = rect.pBits.cast::<u8>().add(row * rect.Pitch as usize);
+                core::ptr::copy_nonoverlapping(src.as_ptr(), dst, src.len());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "d3d9")]
+impl GpuUploader for D3D9Uploader {
+    fn upload_image(&mut self, img: &GfxImage) -> Result<GpuHandle> {
+        let format = check_format(img)?;
+        let d3d_format = D3DFORMAT(format as _);
+        let levels = img.mip_count();
+        let has_pixels = !img.pixel_data().is_empty();
+        let win_err = |e: windows::core::Error| Error::new(file_line_col!(), e.into());
+
+        let texture = if img.is_cubemap() {
+            let mut tex = Option::<IDirect3DCubeTexture9>::None;
+            unsafe {
+                self.device.CreateCubeTexture(
+                    img.width as _,
+                    levels as _,
+                    0,
+                    d3d_format,
+                    D3DPOOL_MANAGED,
+                    &mut tex,
+                    core::ptr::null_mut(),
+                )
+            }
+            .map_err(win_err)?;
+            let tex = tex.unwrap();
+
+            if has_pixels {
+                for face in 0..6 {
+                    let face_type = D3DCUBEMAP_FACES(face);
+                    for level in 0..levels {
+                        let mut rect = D3DLOCKED_RECT::default();
+                        unsafe {
+                            tex.LockRect(face_type, level as _, &mut rect, core::ptr::null(), 0)
+                        }
+                        .map_err(win_err)?;
+                        let res = self.fill(img, format, face as _, level, &rect);
+                        unsafe { tex.UnlockRect(face_type, level as _) }.map_err(win_err)?;
+                        res?;
+                    }
+                }
+            }
+
+            D3D9Texture::Cube(tex)
+        } else if img.map_type == crate::techset::MapType::TWO_DIMENSIONAL {
+            let mut tex = Option::<IDirect3DTexture9>::None;
+            unsafe {
+                self.device.CreateTexture(
+                    img.width as _,
+                    img.height as _,
+                    levels as _,
+                    0,
+                    d3d_format,
+                    D3DPOOL_MANAGED,
+                    &mut tex,
+                    core::ptr::null_mut(),
+                )
+            }
+            .map_err(win_err)?;
+            let tex = tex.unwrap();
+
+            if has_pixels {
+                for level in 0..levels {
+                    let mut rect = D3DLOCKED_RECT::default();
+                    unsafe { tex.LockRect(level as _, &mut rect, core::ptr::null(), 0) }
+                        .map_err(win_err)?;
+                    let res = self.fill(img, format, 0, level, &rect);
+                    unsafe { tex.UnlockRect(level as _) }.map_err(win_err)?;
+                    res?;
+                }
+            }
+
+            D3D9Texture::Texture(tex)
+        } else {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::Todo(alloc::format!(
+                    "D3D9Uploader: unsupported map type {:?}",
+                    img.map_type
+                )),
+            ));
+        };
+
+        self.textures.push(texture);
+        Ok(GpuHandle(self.textures.len() as u64 - 1))
+    }
+}
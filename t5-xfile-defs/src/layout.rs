@@ -0,0 +1,75 @@
+//! Tweaks for fastfiles that don't quite use the retail layout.
+//!
+//! Some modified executables and pre-release builds are reported to write
+//! fastfiles that still claim to be version `0x1D9`, but whose structs differ
+//! slightly from retail's. There's no way to tell from the file itself, so
+//! the deserializer always assumes retail unless it's given a
+//! [`LayoutOverrides`] saying otherwise. The serializer can be given one too,
+//! to write files for those builds.
+//!
+//! This is plumbing only. None of the knobs is tied to a documented build:
+//! they cover the two kinds of difference those reports describe, a longer
+//! animation array and extra bytes at the end of a struct, and the
+//! deserializer and serializer both honor them, but the values a given
+//! build needs have to come from whoever has that build. Fields a build
+//! adds or drops partway through a struct aren't covered. Each needs its
+//! own toggle, checked in that struct's impl through
+//! [`T5XFileDeserialize::layout_overrides`], and none are added until a
+//! build that needs one is documented.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Result, T5XFileDeserialize, T5XFileSerialize};
+
+/// The length of [`WeaponVariantDef::xanims`] in retail fastfiles.
+///
+/// [`WeaponVariantDef::xanims`]: crate::weapon::WeaponVariantDef::xanims
+pub const RETAIL_WEAPON_XANIM_COUNT: usize = 66;
+
+/// Describes how a fastfile's layout differs from retail's. The default is
+/// retail. See the [module docs](self) for what this does and doesn't cover.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LayoutOverrides {
+    /// The length of [`WeaponVariantDef::xanims`], if it isn't
+    /// [`RETAIL_WEAPON_XANIM_COUNT`]. Slots past the retail count are assumed
+    /// to come after the retail ones, so [`WeaponAnimSlot`] still indexes
+    /// the first 66.
+    ///
+    /// [`WeaponVariantDef::xanims`]: crate::weapon::WeaponVariantDef::xanims
+    /// [`WeaponAnimSlot`]: crate::weapon::WeaponAnimSlot
+    pub weapon_xanim_count: Option<usize>,
+    /// Extra bytes at the end of each
+    /// [`WeaponVariantDef`](crate::weapon::WeaponVariantDef), before any of
+    /// the data it points to.
+    pub weapon_variant_def_pad: usize,
+    /// Extra bytes at the end of each
+    /// [`WeaponDef`](crate::weapon::WeaponDef), before any of the data it
+    /// points to.
+    pub weapon_def_pad: usize,
+}
+
+impl LayoutOverrides {
+    pub fn is_retail(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Skips `len` bytes of padding that aren't part of the retail layout.
+pub(crate) fn skip_pad(de: &mut impl T5XFileDeserialize, len: usize) -> Result<()> {
+    for _ in 0..len {
+        de.load_from_xfile::<u8>()?;
+    }
+
+    Ok(())
+}
+
+/// Stores `len` bytes of padding that aren't part of the retail layout.
+pub(crate) fn store_pad(ser: &mut impl T5XFileSerialize, len: usize) -> Result<()> {
+    for _ in 0..len {
+        ser.store_into_xfile(0u8)?;
+    }
+
+    Ok(())
+}
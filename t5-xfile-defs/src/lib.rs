@@ -100,9 +100,14 @@ extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 
+pub mod annotate;
+pub mod cancel;
 pub mod clipmap;
 pub mod com_world;
 pub mod common;
+mod compat;
+pub mod convert;
+pub mod custom;
 pub mod ddl;
 pub mod destructible;
 pub mod emblem;
@@ -111,13 +116,18 @@ pub mod fx;
 pub mod gameworld;
 pub mod gfx_world;
 pub mod glass;
+pub mod gpu;
+pub mod layout;
 pub mod light;
+pub mod limits;
 pub mod menu;
 pub mod misc;
+pub mod packing;
 mod prelude;
 pub mod sound;
 pub mod techset;
 pub mod util;
+pub mod validate;
 pub mod weapon;
 pub mod xanim;
 pub mod xasset;
@@ -126,6 +136,7 @@ pub mod xmodel;
 use alloc::{
     fmt::{Debug, Display},
     string::String,
+    vec::Vec,
 };
 
 use serde::{Deserialize, de::DeserializeOwned};
@@ -136,8 +147,15 @@ use serde::Serialize;
 #[cfg(feature = "d3d9")]
 use windows::Win32::Graphics::Direct3D9::IDirect3DDevice9;
 
-pub use misc::*;
-pub use util::*;
+use cancel::CancellationToken;
+pub use compat::*;
+use gpu::GpuUploader;
+use layout::LayoutOverrides;
+use limits::{ResourceLimit, ResourceLimits};
+pub use util::XString;
+// The rest of `util` is plumbing for the (de)serializer. It stays public
+// under `util`, and the old root paths are deprecated in `compat`.
+pub(crate) use util::{FlexibleArray, FlexibleArrayU16, FlexibleArrayU32, assert_size};
 use xasset::XAssetType;
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -161,15 +179,24 @@ impl XFileHeader {
             XFILE_HEADER_MAGIC_U_RAW
         };
 
-        let version = XFileVersion::from_platform(platform).as_u32();
+        // Written in the platform's byte order, like everything else.
+        let version = XFileVersion::CURRENT;
 
         Self { magic, version }
     }
 
+    /// Replaces the compression byte with `compression`'s.
+    pub const fn with_compression(mut self, compression: CompressionMethod) -> Self {
+        self.magic[4] = compression.as_byte();
+        self
+    }
+
     pub fn magic_string(&self) -> String {
         self.magic.iter().map(|c| *c as char).collect()
     }
 
+    /// Doesn't check the compression byte (`magic[4]`), see
+    /// [`Self::compression_method`] for that.
     pub const fn magic_is_valid(&self) -> bool {
         // won't work in a const fn
         // self.magic == XFILE_HEADER_MAGIC_U_RAW || self.magic == XFILE_HEADER_MAGIC_0_RAW
@@ -177,11 +204,56 @@ impl XFileHeader {
             && self.magic[1] == b'W'
             && self.magic[2] == b'f'
             && self.magic[3] == b'f'
-            && (self.magic[4] == b'u' || self.magic[4] == b'0')
             && self.magic[5] == b'1'
             && self.magic[6] == b'0'
             && self.magic[7] == b'0'
     }
+
+    pub const fn compression_byte(&self) -> u8 {
+        self.magic[4]
+    }
+
+    /// Returns [`None`] if the compression byte isn't one of the known
+    /// values.
+    pub const fn compression_method(&self) -> Option<CompressionMethod> {
+        CompressionMethod::from_byte(self.compression_byte())
+    }
+}
+
+/// The compression method of an XFile's blob, as indicated by the byte after
+/// `"IWff"` in the header.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CompressionMethod {
+    /// `'u'`, used by Windows (and presumably macOS). The blob is
+    /// zlib-compressed.
+    Deflate,
+    /// `'0'`, used by Xbox 360 and PS3. Depending on the dump, the blob is
+    /// either stored uncompressed or LZX-compressed, and there's no way to
+    /// tell which without looking at it. Decompressing LZX needs the `lzx`
+    /// feature.
+    Uncompressed,
+    /// `'z'`. Not used by any official build, but supported for modded ones.
+    /// The blob is zstd-compressed.
+    Zstd,
+}
+
+impl CompressionMethod {
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'u' => Some(Self::Deflate),
+            b'0' => Some(Self::Uncompressed),
+            b'z' => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    pub const fn as_byte(self) -> u8 {
+        match self {
+            Self::Deflate => b'u',
+            Self::Uncompressed => b'0',
+            Self::Zstd => b'z',
+        }
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -225,14 +297,14 @@ pub enum XFileVersion {
 }
 
 impl XFileVersion {
-    pub const fn is_valid(version: u32, platform: XFilePlatform) -> bool {
-        let version = if let Some(v) = Self::from_u32(version) {
-            v.as_u32()
-        } else {
-            return false;
-        };
+    /// The version as read from a header in its platform's byte order.
+    pub const CURRENT: u32 = XFILE_VERSION;
 
-        version == Self::from_platform(platform).as_u32()
+    /// `version` should have been read in `platform`'s byte order, so it's
+    /// the same for every platform. (Comparing it against
+    /// [`Self::from_platform`] rejected every console Fastfile.)
+    pub const fn is_valid(version: u32, platform: XFilePlatform) -> bool {
+        !matches!(platform, XFilePlatform::Wii) && version == Self::CURRENT
     }
 
     pub const fn is_other_endian(version: u32) -> bool {
@@ -264,6 +336,7 @@ impl XFileVersion {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum XFilePlatform {
     Windows,
@@ -340,6 +413,11 @@ pub enum ErrorKind {
     Bincode(Box<bincode::ErrorKind>),
     /// Occurs when an XFile's blob couldn't be inflated.
     Inflate(String),
+    /// Occurs when an XFile's blob couldn't be LZX-decompressed.
+    Lzx(String),
+    /// Occurs when an XFile's blob couldn't be zstd-compressed or
+    /// -decompressed.
+    Zstd(String),
     /// Occurs when an XFile's blob couldn't be deflated.
     Deflate(String),
     /// Occurs when `num::FromPrimitive::from_*` return [`None`].
@@ -355,24 +433,45 @@ pub enum ErrorKind {
     /// Occurs when attempting to seek to an offset beyond the bounds of a
     /// file.
     InvalidSeek { off: u32, max: u32 },
+    /// Occurs when the end of the blob is reached before an object could be
+    /// fully deserialized. Likely indicates the file is truncated.
+    UnexpectedEof { needed: u64, available: u64 },
+    /// Occurs when an XFile's blob inflates to fewer bytes than its
+    /// [`XFile`] header claims. Likely indicates the file is truncated.
+    TruncatedBlob { recovered: usize, expected: usize },
     /// Occurs when an XFile's `magic` field is invalid.
     /// Likely indicates the file is corrupt or isn't an XFile.
     BadHeaderMagic(String),
+    /// Occurs when an XFile's compression byte isn't a known value, or when
+    /// the blob couldn't be decompressed using any method that byte allows.
+    UnsupportedCompression(u8),
     /// Occurs when an XFile's version doesn't match the expected version
     /// ([`XFILE_VERSION`]).
     WrongVersion(u32),
+    /// Occurs when a binary asset export was written with a different schema
+    /// version than the one being read.
+    WrongSchemaVersion { expected: u32, found: u32 },
     /// Occurs when an XFile has the wrong endianness for the given platform.
     WrongEndiannessForPlatform(XFilePlatform),
+    /// Occurs when an XFile's header is valid for the given platform, but
+    /// the blob's own headers only make sense when read with the other
+    /// endianness. Likely indicates the file is for a different platform.
+    ProbableEndianMismatch(XFilePlatform),
     /// Occurs when an XFile's platform is unimplemented (currently just Wii).
     UnimplementedPlatform(XFilePlatform),
     /// Occurs when an XFile's platform is unsupported
     /// (all platforms except Windows).
     UnsupportedPlatform(XFilePlatform),
+    /// Occurs when a [`GfxImage`]'s format isn't one an operation supports.
+    ///
+    /// [`GfxImage`]: techset::GfxImage
+    UnsupportedImageFormat(u32),
     /// Occurs when some part of the library hasn't yet been implemented.
     Todo(String),
     /// Occurs when a [`ScriptString`] isn't a valid index.
     BadScriptString(u16),
-    /// Occurs when more than [`u16::MAX`] [`ScriptString`]s are present.
+    /// Occurs when more than [`u16::MAX`] + 1 [`ScriptString`]s are present,
+    /// i.e. when one wouldn't have a valid index.
     ScriptStringOverflow,
     /// Occurs when an `XAsset`'s `asset_type` isn't a variant of
     /// [`XAssetType`].
@@ -380,11 +479,154 @@ pub enum ErrorKind {
     /// Occurs when an `XAsset`'s `asset_type` *is* a variant of
     /// [`XAssetType`], but that `asset_type` isn't used by T5.
     UnusedXAssetType(XAssetType),
+    /// Occurs when an operation's [`CancellationToken`] is cancelled.
+    ///
+    /// [`CancellationToken`]: cancel::CancellationToken
+    Cancelled,
+    /// Occurs when a blob would make the deserializer allocate more than one
+    /// of its [`ResourceLimits`] allows. `requested` is how much the blob
+    /// asked for.
+    ///
+    /// [`ResourceLimits`]: limits::ResourceLimits
+    ResourceLimit {
+        which: limits::ResourceLimit,
+        limit: u64,
+        requested: u64,
+    },
+    /// Occurs when asking for the blob span of an asset that hasn't been
+    /// deserialized, or that failed to deserialize.
+    NoAssetSpan(usize),
     /// Occurs when an error is returned by D3D9.
     #[cfg(feature = "d3d9")]
     Windows(windows::core::Error),
 }
 
+impl ErrorKind {
+    /// The variant's name, without any of its data. Stable across runs, so
+    /// it's suitable for comparing errors from different files.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(_) => "Io",
+            #[cfg(feature = "bincode")]
+            Self::Bincode(_) => "Bincode",
+            Self::Inflate(_) => "Inflate",
+            Self::Lzx(_) => "Lzx",
+            Self::Zstd(_) => "Zstd",
+            Self::Deflate(_) => "Deflate",
+            Self::BadFromPrimitive(_) => "BadFromPrimitive",
+            Self::BadBitflags(_) => "BadBitflags",
+            Self::BadChar(_) => "BadChar",
+            Self::BrokenInvariant(_) => "BrokenInvariant",
+            Self::InvalidSeek { .. } => "InvalidSeek",
+            Self::UnexpectedEof { .. } => "UnexpectedEof",
+            Self::TruncatedBlob { .. } => "TruncatedBlob",
+            Self::BadHeaderMagic(_) => "BadHeaderMagic",
+            Self::UnsupportedCompression(_) => "UnsupportedCompression",
+            Self::WrongVersion(_) => "WrongVersion",
+            Self::WrongSchemaVersion { .. } => "WrongSchemaVersion",
+            Self::WrongEndiannessForPlatform(_) => "WrongEndiannessForPlatform",
+            Self::ProbableEndianMismatch(_) => "ProbableEndianMismatch",
+            Self::UnimplementedPlatform(_) => "UnimplementedPlatform",
+            Self::UnsupportedPlatform(_) => "UnsupportedPlatform",
+            Self::UnsupportedImageFormat(_) => "UnsupportedImageFormat",
+            Self::Todo(_) => "Todo",
+            Self::BadScriptString(_) => "BadScriptString",
+            Self::ScriptStringOverflow => "ScriptStringOverflow",
+            Self::InvalidXAssetType(_) => "InvalidXAssetType",
+            Self::UnusedXAssetType(_) => "UnusedXAssetType",
+            Self::Cancelled => "Cancelled",
+            Self::ResourceLimit { .. } => "ResourceLimit",
+            Self::NoAssetSpan(_) => "NoAssetSpan",
+            #[cfg(feature = "d3d9")]
+            Self::Windows(_) => "Windows",
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            #[cfg(feature = "bincode")]
+            Self::Bincode(e) => write!(f, "bincode error: {e}"),
+            Self::Inflate(e) => write!(f, "couldn't inflate blob: {e}"),
+            Self::Lzx(e) => write!(f, "couldn't decompress LZX blob: {e}"),
+            Self::Zstd(e) => write!(f, "zstd error: {e}"),
+            Self::Deflate(e) => write!(f, "couldn't deflate blob: {e}"),
+            Self::BadFromPrimitive(v) => write!(f, "bad enum value {v}"),
+            Self::BadBitflags(v) => write!(f, "bad bitflags {v:#010X}"),
+            Self::BadChar(c) => write!(f, "bad character {c:#X}"),
+            Self::BrokenInvariant(e) => write!(f, "broken invariant: {e}"),
+            Self::InvalidSeek { off, max } => {
+                write!(f, "can't seek to {off:#010X} (max {max:#010X})")
+            }
+            Self::UnexpectedEof { needed, available } => write!(
+                f,
+                "unexpected end of blob ({needed} bytes needed, {available} available)"
+            ),
+            Self::TruncatedBlob {
+                recovered,
+                expected,
+            } => write!(
+                f,
+                "blob is truncated ({recovered} of {expected} bytes recovered)"
+            ),
+            Self::BadHeaderMagic(m) => write!(f, "bad header magic \"{m}\""),
+            Self::UnsupportedCompression(c) => write!(f, "unsupported compression {c:#04X}"),
+            Self::WrongVersion(v) => {
+                write!(f, "wrong version {v:#X} (expected {XFILE_VERSION:#X})")
+            }
+            Self::WrongSchemaVersion { expected, found } => {
+                write!(f, "wrong schema version {found} (expected {expected})")
+            }
+            Self::WrongEndiannessForPlatform(p) => {
+                write!(f, "wrong endianness for platform {p:?}")
+            }
+            Self::ProbableEndianMismatch(p) => write!(
+                f,
+                "blob only makes sense with the other endianness (platform {p:?})"
+            ),
+            Self::UnimplementedPlatform(p) => write!(f, "platform {p:?} is unimplemented"),
+            Self::UnsupportedPlatform(p) => write!(f, "platform {p:?} is unsupported"),
+            Self::UnsupportedImageFormat(t) => write!(f, "unsupported image format {t:#010X}"),
+            Self::Todo(e) => write!(f, "not implemented yet: {e}"),
+            Self::BadScriptString(s) => write!(f, "bad script string {s}"),
+            Self::ScriptStringOverflow => write!(f, "too many script strings"),
+            Self::InvalidXAssetType(t) => write!(f, "invalid asset type {t:#X}"),
+            Self::UnusedXAssetType(t) => write!(f, "asset type {t:?} isn't used by T5"),
+            Self::Cancelled => write!(f, "cancelled"),
+            Self::ResourceLimit {
+                which,
+                limit,
+                requested,
+            } => write!(
+                f,
+                "{requested} {} exceeds the limit of {limit}",
+                which.name()
+            ),
+            Self::NoAssetSpan(i) => write!(f, "asset {i} wasn't deserialized"),
+            #[cfg(feature = "d3d9")]
+            Self::Windows(e) => write!(f, "D3D9 error: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for ErrorKind {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(e) => Some(e),
+            #[cfg(feature = "bincode")]
+            Self::Bincode(e) => Some(e),
+            #[cfg(feature = "d3d9")]
+            Self::Windows(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(feature = "std")]
 impl From<std::io::Error> for ErrorKind {
     fn from(value: std::io::Error) -> Self {
@@ -420,11 +662,59 @@ macro_rules! file_line_col {
 
 pub(crate) use file_line_col;
 
+/// The asset that was being deserialized when an [`Error`] occurred.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ErrorAsset {
+    pub asset_type: XAssetType,
+    /// The asset's index in the file's asset list.
+    pub index: usize,
+    /// The number of assets in the file's asset list.
+    pub count: usize,
+}
+
+/// Bytes of the blob around where an [`Error`] occurred.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorExcerpt {
+    /// The blob offset of `bytes[0]`.
+    pub start: u32,
+    pub bytes: Vec<u8>,
+}
+
+impl Display for ErrorExcerpt {
+    /// Formats the bytes as a hexdump, 16 to a line.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, line) in self.bytes.chunks(16).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{:08X}:", self.start as usize + i * 16)?;
+            for b in line {
+                write!(f, " {b:02X}")?;
+            }
+            write!(f, "{:width$} |", "", width = (16 - line.len()) * 3)?;
+            for &b in line {
+                let c = if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                };
+                write!(f, "{c}")?;
+            }
+            write!(f, "|")?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct Error {
     where_: String,
     kind: ErrorKind,
     off: Option<u32>,
+    asset: Option<ErrorAsset>,
+    excerpt: Option<ErrorExcerpt>,
 }
 
 impl Error {
@@ -433,6 +723,8 @@ impl Error {
             where_,
             kind,
             off: None,
+            asset: None,
+            excerpt: None,
         }
     }
 
@@ -441,9 +733,31 @@ impl Error {
             where_,
             kind,
             off: Some(off),
+            asset: None,
+            excerpt: None,
         }
     }
 
+    /// Records which asset was being deserialized.
+    pub fn with_asset(mut self, asset: ErrorAsset) -> Self {
+        self.asset = Some(asset);
+        self
+    }
+
+    /// Attaches the bytes around where the error occurred.
+    pub fn with_excerpt(mut self, excerpt: ErrorExcerpt) -> Self {
+        self.excerpt = Some(excerpt);
+        self
+    }
+
+    pub const fn asset(&self) -> Option<&ErrorAsset> {
+        self.asset.as_ref()
+    }
+
+    pub const fn excerpt(&self) -> Option<&ErrorExcerpt> {
+        self.excerpt.as_ref()
+    }
+
     pub const fn kind(&self) -> &ErrorKind {
         &self.kind
     }
@@ -457,6 +771,43 @@ impl Error {
     }
 }
 
+/// Formats as e.g. `material (asset 212/1890): bad enum value 47 at blob
+/// offset 0x00A3F210 (src/techset.rs:812:17)`, followed by a hexdump if
+/// there's an [`ErrorExcerpt`].
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(a) = &self.asset {
+            write!(
+                f,
+                "{} (asset {}/{}): ",
+                alloc::format!("{:?}", a.asset_type).to_lowercase(),
+                a.index,
+                a.count
+            )?;
+        }
+
+        write!(f, "{}", self.kind)?;
+        if let Some(off) = self.off {
+            write!(f, " at blob offset {off:#010X}")?;
+        }
+        write!(f, " ({})", self.where_)?;
+
+        if let Some(e) = &self.excerpt {
+            write!(f, "\n{e}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl core::error::Error for Error {
+    /// [`Display`] already includes the kind's message, so this skips
+    /// straight to the kind's own source, if any.
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        core::error::Error::source(&self.kind)
+    }
+}
+
 pub type Result<T> = core::result::Result<T, Error>;
 
 pub trait T5XFileDeserialize {
@@ -469,6 +820,113 @@ pub trait T5XFileDeserialize {
     /// if not, or, depending on the implementation, [`Err`].
     fn get_script_string(&self, string: ScriptString) -> Result<Option<&str>>;
 
+    /// Returns the uploader images should be passed to as they're
+    /// deserialized, if there is one.
+    fn gpu_uploader(&mut self) -> Option<&mut dyn GpuUploader> {
+        None
+    }
+
+    /// Returns the device shaders and vertex buffers should be created
+    /// with, if there is one.
+    #[cfg(feature = "d3d9")]
+    fn d3d9_device(&mut self) -> Option<&IDirect3DDevice9> {
+        None
+    }
+
+    /// Returns how the blob's layout differs from retail's.
+    fn layout_overrides(&self) -> LayoutOverrides {
+        LayoutOverrides::default()
+    }
+
+    /// Returns the token long loops should poll, if there is one.
+    fn cancellation_token(&self) -> Option<&CancellationToken> {
+        None
+    }
+
+    /// Returns the limits on what the blob can make the deserializer
+    /// allocate.
+    fn resource_limits(&self) -> ResourceLimits {
+        ResourceLimits::unlimited()
+    }
+
+    /// Adds `bytes` to the running total counted towards
+    /// [`ResourceLimits::max_allocated_bytes`] and returns the new total.
+    /// Implementations that don't keep a total return `bytes`.
+    fn track_allocated_bytes(&mut self, bytes: u64) -> u64 {
+        bytes
+    }
+
+    /// Counts `bytes` towards the memory taken up by the assets being
+    /// deserialized. Called as arrays, boxed values, and strings are
+    /// allocated. Implementations that don't keep a total do nothing.
+    fn track_heap_bytes(&mut self, _bytes: u64) {}
+
+    /// Turns a string read from the blob into an [`XString`].
+    /// Implementations that keep a [`StringInterner`](util::StringInterner)
+    /// return its copy. Otherwise, every call allocates a new one.
+    fn intern_string(&mut self, s: &str) -> XString {
+        self.track_heap_bytes(XString::heap_size(s) as _);
+        XString::from(s)
+    }
+
+    /// Returns [`ErrorKind::ResourceLimit`] if reading `count` elements of
+    /// `size` bytes each would go over [`Self::resource_limits`]. Otherwise,
+    /// counts them towards [`ResourceLimits::max_allocated_bytes`]. Called
+    /// before an array's elements are read, and after a string's bytes are
+    /// (its length isn't known until then, but it can't be longer than the
+    /// blob).
+    fn check_allocation(&mut self, count: usize, size: usize) -> Result<()> {
+        let limits = self.resource_limits();
+
+        if let Some(limit) = limits.max_array_len
+            && count > limit
+        {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                self.stream_pos()? as _,
+                ErrorKind::ResourceLimit {
+                    which: ResourceLimit::ArrayLen,
+                    limit: limit as _,
+                    requested: count as _,
+                },
+            ));
+        }
+
+        let bytes = (count as u64).saturating_mul(size as u64);
+        let total = self.track_allocated_bytes(bytes);
+        if let Some(limit) = limits.max_allocated_bytes
+            && total > limit
+        {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                self.stream_pos()? as _,
+                ErrorKind::ResourceLimit {
+                    which: ResourceLimit::AllocatedBytes,
+                    limit,
+                    requested: total,
+                },
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns [`ErrorKind::Cancelled`] if [`Self::cancellation_token`] has
+    /// been cancelled.
+    fn check_cancelled(&mut self) -> Result<()> {
+        if self
+            .cancellation_token()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                self.stream_pos()? as _,
+                ErrorKind::Cancelled,
+            ));
+        }
+        Ok(())
+    }
+
     fn script_strings(&self) -> Result<Vec<&str>> {
         let mut v = Vec::new();
         let mut i = 0;
@@ -485,12 +943,50 @@ pub trait T5XFileSerialize {
     fn store_into_xfile<T: Serialize>(&mut self, t: T) -> Result<()>;
 
     /// Returns [`Ok`] if `string` was already present or was successfully
-    /// inserted, or [`Err`] when [`Error::ScriptStringOverflow`] or some
-    /// other error occurs.
-    fn get_or_insert_script_string(&mut self, string: &str) -> Result<ScriptString>;
-
+    /// inserted, or [`Err`] when [`ErrorKind::ScriptStringOverflow`] or some
+    /// other error occurs. Script strings are stored one byte per char, so
+    /// strings with chars outside Latin-1 are rejected with
+    /// [`ErrorKind::BadChar`].
+    ///
+    /// Strings are assigned indices in the order they're first interned,
+    /// which matches the order the engine's linker emits them in.
+    fn intern(&mut self, string: &str) -> Result<ScriptString>;
+
+    /// Returns the interned strings, ordered by index.
+    ///
+    /// The serializer writes these out as the asset list's script string
+    /// table when the fastfile is built, so assets only ever intern them.
     fn script_strings(&self) -> Vec<&str>;
 
     fn asset_count(&self) -> usize;
     fn asset_bytes(&self) -> Option<&[u8]>;
+
+    /// Stores a string's bytes, including its terminator. Kept separate from
+    /// [`Self::store_into_xfile`] so serialized sizes can be broken down by
+    /// what the bytes are for.
+    fn store_string_into_xfile(&mut self, bytes: &[u8]) -> Result<()> {
+        for &b in bytes {
+            self.store_into_xfile(b)?;
+        }
+
+        Ok(())
+    }
+
+    /// Called before and after serializing the elements of a pointed-to
+    /// array, for the same reason as [`Self::store_string_into_xfile`].
+    fn begin_array(&mut self) {}
+    fn end_array(&mut self) {}
+
+    /// Returns how the blob's layout should differ from retail's.
+    fn layout_overrides(&self) -> LayoutOverrides {
+        LayoutOverrides::default()
+    }
+
+    /// Whether a sub-asset of type `asset_type` named `name` should be
+    /// written as a reference to the asset of that name in another
+    /// fastfile, instead of in full. Only asked for types that have such a
+    /// reference form, e.g. [`fx::FxEffectDef::external`].
+    fn is_external(&self, _asset_type: xasset::XAssetType, _name: &str) -> bool {
+        false
+    }
 }
@@ -1,13 +1,22 @@
-use alloc::boxed::Box;
+use core::fmt::{self, Display};
+
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 use serde::{Deserialize, Serialize};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
 use crate::{
-    Ptr32, Result, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize,
-    XString, XStringRaw, assert_size,
+    Error, ErrorKind, Result, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto,
+    XFileSerialize, XString, assert_size, file_line_col,
+    gfx_world::GfxLightGrid,
     techset::{GfxImage, GfxImageRaw},
+    util::{Ptr32, XStringRaw},
 };
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -27,6 +36,115 @@ pub struct GfxLightDef {
     pub lmap_lookup_start: i32,
 }
 
+impl GfxLightDef {
+    /// The number of attenuation samples, which is the width of the
+    /// attenuation image (one sample per texel of its first row), or 0 if
+    /// there's no image.
+    pub fn attenuation_sample_count(&self) -> usize {
+        self.attenuation
+            .image
+            .as_ref()
+            .map_or(0, |image| image.width as usize)
+    }
+
+    /// Decodes the attenuation samples from the first row of the
+    /// attenuation image's base mip.
+    ///
+    /// Sample `i` is the light's intensity at `i / (count - 1)` of the way
+    /// from its origin to its radius, from 0.0 (unlit) to 1.0 (full
+    /// intensity), taken from the red channel. Nothing is decoded until this
+    /// is called.
+    pub fn attenuation_samples(&self) -> Result<Vec<f32>> {
+        let Some(image) = &self.attenuation.image else {
+            return Ok(Vec::new());
+        };
+
+        let (width, _, rgba) = image.to_rgba8_mip(0)?;
+        Ok(rgba
+            .chunks_exact(4)
+            .take(width as usize)
+            .map(|px| px[0] as f32 / 255.0)
+            .collect())
+    }
+
+    /// Checks that the lightmap lookup rows used by this def,
+    /// `lmap_lookup_start..lmap_lookup_start + attenuation_sample_count()`,
+    /// fall within `grid`'s entries.
+    ///
+    /// If `lenient` is set, an out-of-range start is clamped into range
+    /// instead, since some community maps ship values that are off by one
+    /// and the engine tolerates them, and what was clamped is returned so
+    /// the caller can warn about it. Fails either way if there are more
+    /// samples than entries.
+    pub fn validate_lmap_lookup(
+        &mut self,
+        grid: &GfxLightGrid,
+        lenient: bool,
+    ) -> Result<Option<LmapLookupClamped>> {
+        let entries = grid.entries.len();
+        let count = self.attenuation_sample_count();
+        let start = self.lmap_lookup_start;
+
+        let in_range = usize::try_from(start)
+            .ok()
+            .and_then(|start| start.checked_add(count))
+            .is_some_and(|end| end <= entries);
+        if in_range {
+            return Ok(None);
+        }
+
+        let Some(max_start) = entries.checked_sub(count).filter(|_| lenient) else {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "GfxLightDef '{}': {}",
+                    self.name.get(),
+                    out_of_range(start, count, entries)
+                )),
+            ));
+        };
+
+        self.lmap_lookup_start = (start.max(0) as usize).min(max_start) as i32;
+        Ok(Some(LmapLookupClamped {
+            name: self.name.get().to_string(),
+            start,
+            clamped_start: self.lmap_lookup_start,
+            sample_count: count,
+            grid_entries: entries,
+        }))
+    }
+}
+
+fn out_of_range(start: i32, count: usize, entries: usize) -> String {
+    format!("lmap lookup ({start}..{start}+{count}) out of range of light grid entries ({entries})")
+}
+
+/// Returned by [`GfxLightDef::validate_lmap_lookup`] when it clamps an
+/// out-of-range start.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LmapLookupClamped {
+    /// The [`GfxLightDef::name`] of the def that was clamped.
+    pub name: String,
+    /// The start before it was clamped.
+    pub start: i32,
+    pub clamped_start: i32,
+    pub sample_count: usize,
+    pub grid_entries: usize,
+}
+
+impl Display for LmapLookupClamped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GfxLightDef '{}': {}, clamped start to {}",
+            self.name,
+            out_of_range(self.start, self.sample_count, self.grid_entries),
+            self.clamped_start
+        )
+    }
+}
+
 impl XFileSerialize<()> for GfxLightDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
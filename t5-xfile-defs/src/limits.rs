@@ -0,0 +1,99 @@
+//! Caps on what a blob can make the deserializer allocate, for reading files
+//! that can't be trusted.
+//!
+//! Counts in a blob are read before anything they count, so without these, a
+//! crafted file can claim millions of assets or a `u32::MAX`-element array
+//! and exhaust memory before any other check fails. Every limit is checked
+//! before the allocation it guards, and exceeding one returns
+//! [`ErrorKind::ResourceLimit`](crate::ErrorKind::ResourceLimit).
+
+/// Which of [`ResourceLimits`]' limits was exceeded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceLimit {
+    /// See [`ResourceLimits::max_assets`].
+    Assets,
+    /// See [`ResourceLimits::max_script_strings`].
+    ScriptStrings,
+    /// See [`ResourceLimits::max_array_len`].
+    ArrayLen,
+    /// See [`ResourceLimits::max_allocated_bytes`].
+    AllocatedBytes,
+    /// See [`ResourceLimits::max_inflated_size`].
+    InflatedSize,
+}
+
+impl ResourceLimit {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Assets => "assets",
+            Self::ScriptStrings => "script strings",
+            Self::ArrayLen => "array length",
+            Self::AllocatedBytes => "allocated bytes",
+            Self::InflatedSize => "inflated size",
+        }
+    }
+}
+
+/// Caps on what a blob can make the deserializer allocate. [`None`] means
+/// unlimited.
+///
+/// The defaults are set well above anything a retail file can contain, so
+/// they only get in the way of crafted or corrupt files:
+///
+/// - assets and script strings are the caps the deserializer also checks
+///   the asset list's header against, to tell a file for another platform
+///   apart from one that's just too big. Script strings are indexed with a
+///   [`u16`], so there can't be more than 65,536 of them.
+/// - the inflated size allows for the largest [`XFile::size`] the header
+///   checks accept (1 GiB), twice over.
+/// - arrays and strings are read out of the inflated blob, so all of them
+///   together can't take more bytes than the blob has (their owned forms
+///   are bigger, but not by an order of magnitude). The byte cap is the
+///   same as the inflated size cap.
+/// - no single array can have more elements than the blob has bytes. The
+///   deserializer caps [`Self::max_array_len`] at the blob's length, so
+///   that's the default.
+///
+/// [`XFile::size`]: crate::XFile::size
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// The most entries the asset list can have.
+    pub max_assets: Option<usize>,
+    /// The most script strings the asset list can have.
+    pub max_script_strings: Option<usize>,
+    /// The most elements any single array (or bytes any single string) can
+    /// have. The deserializer lowers it to the length of the blob it's
+    /// reading, if that's less.
+    pub max_array_len: Option<usize>,
+    /// The most bytes that can be read into arrays and strings, in total.
+    /// Counted from the size of each element as stored in the blob, so it's
+    /// approximate.
+    pub max_allocated_bytes: Option<u64>,
+    /// The most bytes the blob can inflate to.
+    pub max_inflated_size: Option<u64>,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_assets: Some(1 << 20),
+            max_script_strings: Some(u16::MAX as usize + 1),
+            max_array_len: Some(usize::MAX),
+            max_allocated_bytes: Some(1 << 31),
+            max_inflated_size: Some(1 << 31),
+        }
+    }
+}
+
+impl ResourceLimits {
+    /// No limits at all.
+    pub const fn unlimited() -> Self {
+        Self {
+            max_assets: None,
+            max_script_strings: None,
+            max_array_len: None,
+            max_allocated_bytes: None,
+            max_inflated_size: None,
+        }
+    }
+}
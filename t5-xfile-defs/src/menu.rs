@@ -1,6 +1,12 @@
 use core::mem::transmute;
 
-use alloc::{boxed::Box, format, vec::Vec};
+use alloc::{
+    boxed::Box,
+    collections::BTreeSet,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 
 use num::FromPrimitive;
 use num_derive::FromPrimitive;
@@ -10,11 +16,12 @@ use serde::{Deserialize, Serialize};
 use crate::prelude::*;
 
 use crate::{
-    Error, ErrorKind, FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result,
-    T5XFileDeserialize, XFileDeserializeInto, XString, XStringRaw, assert_size,
+    Error, ErrorKind, FatPointer, Result, T5XFileDeserialize, T5XFileSerialize,
+    XFileDeserializeInto, XFileSerialize, XString, assert_size,
     common::Vec4,
     file_line_col,
     techset::{Material, MaterialRaw},
+    util::{FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, XStringRaw},
 };
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -57,6 +64,34 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<MenuList<MAX_LOCAL
     }
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for MenuList<MAX_LOCAL_CLIENTS> {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let menu_list = MenuListRaw::<MAX_LOCAL_CLIENTS> {
+            name: XStringRaw::from_str(self.name.get()),
+            menus: FatPointerCountFirstU32::from_slice(&self.menus),
+        };
+
+        ser.store_into_xfile(menu_list)?;
+        self.name.xfile_serialize(ser, ())?;
+        serialize_ptr_array(ser, &self.menus)
+    }
+}
+
+/// Serializes an array of pointers the way it's laid out in the blob: all
+/// of the pointers first, then each thing they point to.
+fn serialize_ptr_array<T: XFileSerialize<()>>(
+    ser: &mut impl T5XFileSerialize,
+    v: &[Box<T>],
+) -> Result<()> {
+    ser.begin_array();
+    for _ in v {
+        ser.store_into_xfile(Ptr32::<()>::unreal())?;
+    }
+    ser.end_array();
+
+    v.iter().try_for_each(|t| t.xfile_serialize(ser, ()))
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Debug, Deserialize)]
 pub(crate) struct MenuDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
@@ -190,6 +225,48 @@ pub struct MenuDef<const MAX_LOCAL_CLIENTS: usize> {
     pub items: Vec<Box<ItemDef<MAX_LOCAL_CLIENTS>>>,
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> Default for MenuDef<MAX_LOCAL_CLIENTS> {
+    fn default() -> Self {
+        Self {
+            window: WindowDef::default(),
+            font: XString::default(),
+            full_screen: false,
+            ui_3d_window_id: 0,
+            font_index: 0,
+            cursor_item: [0; MAX_LOCAL_CLIENTS],
+            fade_cycle: 0,
+            priority: 0,
+            fade_clamp: 0.0,
+            fade_amount: 0.0,
+            fade_in_amount: 0.0,
+            blur_radius: 0.0,
+            open_slide_speed: 0,
+            close_slide_speed: 0,
+            open_slide_direction: 0,
+            close_slide_direction: 0,
+            intial_rect_info: RectDef::default(),
+            open_fading_time: 0,
+            close_fading_time: 0,
+            fade_time_counter: 0,
+            slide_time_counter: 0,
+            on_event: None,
+            on_key: None,
+            visible_exp: ExpressionStatement::default(),
+            show_bits: 0,
+            hide_bits: 0,
+            allowed_binding: XString::default(),
+            sound_name: XString::default(),
+            image_track: 0,
+            control: 0,
+            focus_color: Vec4::default(),
+            disable_color: Vec4::default(),
+            rect_x_exp: ExpressionStatement::default(),
+            rect_y_exp: ExpressionStatement::default(),
+            items: Vec::default(),
+        }
+    }
+}
+
 impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<MenuDef<MAX_LOCAL_CLIENTS>, ()>
     for MenuDefRaw<'a, MAX_LOCAL_CLIENTS>
 {
@@ -282,6 +359,85 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<MenuDef<MAX_LOCAL_
     }
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for MenuDef<MAX_LOCAL_CLIENTS> {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let menu = MenuDefRaw::<MAX_LOCAL_CLIENTS> {
+            window: self.window.to_raw(),
+            font: XStringRaw::from_str(self.font.get()),
+            full_screen: self.full_screen as _,
+            ui_3d_window_id: self.ui_3d_window_id,
+            item_count: self.items.len() as _,
+            font_index: self.font_index,
+            cursor_item: self.cursor_item,
+            fade_cycle: self.fade_cycle,
+            priority: self.priority,
+            fade_clamp: self.fade_clamp,
+            fade_amount: self.fade_amount,
+            fade_in_amount: self.fade_in_amount,
+            blur_radius: self.blur_radius,
+            open_slide_speed: self.open_slide_speed,
+            close_slide_speed: self.close_slide_speed,
+            open_slide_direction: self.open_slide_direction,
+            close_slide_direction: self.close_slide_direction,
+            intial_rect_info: self.intial_rect_info.to_raw(),
+            open_fading_time: self.open_fading_time,
+            close_fading_time: self.close_fading_time,
+            fade_time_counter: self.fade_time_counter,
+            slide_time_counter: self.slide_time_counter,
+            on_event: Ptr32::from_box(&self.on_event),
+            on_key: Ptr32::from_box(&self.on_key),
+            visible_exp: self.visible_exp.to_raw(),
+            pad: [0; 4],
+            show_bits: self.show_bits,
+            hide_bits: self.hide_bits,
+            allowed_binding: XStringRaw::from_str(self.allowed_binding.get()),
+            sound_name: XStringRaw::from_str(self.sound_name.get()),
+            image_track: self.image_track,
+            control: self.control,
+            focus_color: self.focus_color.get(),
+            disable_color: self.disable_color.get(),
+            rect_x_exp: self.rect_x_exp.to_raw(),
+            rect_y_exp: self.rect_y_exp.to_raw(),
+            items: Ptr32::from_slice(&self.items),
+            pad2: [0; 4],
+        };
+
+        ser.store_into_xfile(menu)?;
+        self.window.serialize_data(ser)?;
+        self.font.xfile_serialize(ser, ())?;
+        self.on_event.xfile_serialize(ser, ())?;
+        self.on_key.xfile_serialize(ser, ())?;
+        self.visible_exp.serialize_data(ser)?;
+        self.allowed_binding.xfile_serialize(ser, ())?;
+        self.sound_name.xfile_serialize(ser, ())?;
+        self.rect_x_exp.serialize_data(ser)?;
+        self.rect_y_exp.serialize_data(ser)?;
+        serialize_ptr_array(ser, &self.items)
+    }
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> MenuDef<MAX_LOCAL_CLIENTS> {
+    /// [`ItemDef::localize_keys`] for every item in the menu, deduplicated.
+    pub fn all_localize_keys_recursive(&self) -> BTreeSet<String> {
+        self.items
+            .iter()
+            .flat_map(|item| item.localize_keys())
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// The keys from [`Self::all_localize_keys_recursive`] that aren't in
+    /// `valid_keys`, in sorted order. `valid_keys` is expected to hold
+    /// [`LocalizeEntry`](crate::misc::LocalizeEntry) names, i.e. without
+    /// the `@`.
+    pub fn validate_localize_keys(&self, valid_keys: &BTreeSet<&str>) -> Vec<String> {
+        self.all_localize_keys_recursive()
+            .into_iter()
+            .filter(|key| !valid_keys.contains(key.as_str()))
+            .collect()
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Debug, Deserialize)]
 pub(crate) struct WindowDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
@@ -368,6 +524,35 @@ pub struct WindowDef<const MAX_LOCAL_CLIENTS: usize> {
     pub background: Option<Box<Material>>,
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> Default for WindowDef<MAX_LOCAL_CLIENTS> {
+    fn default() -> Self {
+        Self {
+            name: XString::default(),
+            rect: RectDef::default(),
+            rect_client: RectDef::default(),
+            group: XString::default(),
+            style: 0,
+            border: 0,
+            modal: 0,
+            frame_sides: 0,
+            frame_tex_size: 0.0,
+            frame_size: 0.0,
+            owner_draw: 0,
+            owner_draw_flags: 0,
+            border_size: 0.0,
+            static_flags: 0,
+            dynamic_flags: [0; MAX_LOCAL_CLIENTS],
+            next_time: 0,
+            fore_color: Vec4::default(),
+            back_color: Vec4::default(),
+            border_color: Vec4::default(),
+            outline_color: Vec4::default(),
+            rotation: 0.0,
+            background: None,
+        }
+    }
+}
+
 impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<WindowDef<MAX_LOCAL_CLIENTS>, ()>
     for WindowDefRaw<'a, MAX_LOCAL_CLIENTS>
 {
@@ -421,6 +606,42 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<WindowDef<MAX_LOCA
     }
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> WindowDef<MAX_LOCAL_CLIENTS> {
+    fn to_raw(&self) -> WindowDefRaw<'static, MAX_LOCAL_CLIENTS> {
+        WindowDefRaw {
+            name: XStringRaw::from_str(self.name.get()),
+            rect: self.rect.to_raw(),
+            rect_client: self.rect_client.to_raw(),
+            group: XStringRaw::from_str(self.group.get()),
+            style: self.style,
+            border: self.border,
+            modal: self.modal,
+            frame_sides: self.frame_sides,
+            frame_tex_size: self.frame_tex_size,
+            frame_size: self.frame_size,
+            owner_draw: self.owner_draw,
+            owner_draw_flags: self.owner_draw_flags,
+            border_size: self.border_size,
+            static_flags: self.static_flags,
+            dynamic_flags: self.dynamic_flags,
+            next_time: self.next_time,
+            fore_color: self.fore_color.get(),
+            back_color: self.back_color.get(),
+            border_color: self.border_color.get(),
+            outline_color: self.outline_color.get(),
+            rotation: self.rotation,
+            background: Ptr32::from_box(&self.background),
+        }
+    }
+
+    /// Serializes what [`Self::to_raw`]'s pointers point to.
+    fn serialize_data(&self, ser: &mut impl T5XFileSerialize) -> Result<()> {
+        self.name.xfile_serialize(ser, ())?;
+        self.group.xfile_serialize(ser, ())?;
+        self.background.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct RectDefRaw {
@@ -434,7 +655,7 @@ pub(crate) struct RectDefRaw {
 assert_size!(RectDefRaw, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct RectDef {
     pub x: f32,
     pub y: f32,
@@ -457,6 +678,19 @@ impl Into<RectDef> for RectDefRaw {
     }
 }
 
+impl RectDef {
+    fn to_raw(&self) -> RectDefRaw {
+        RectDefRaw {
+            x: self.x,
+            y: self.y,
+            w: self.w,
+            h: self.h,
+            horz_align: self.horz_align,
+            vert_align: self.vert_align,
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct GenericEventHandlerRaw<'a> {
@@ -496,6 +730,21 @@ impl<'a> XFileDeserializeInto<GenericEventHandler, ()> for GenericEventHandlerRa
     }
 }
 
+impl XFileSerialize<()> for GenericEventHandler {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let handler = GenericEventHandlerRaw {
+            name: XStringRaw::from_str(self.name.get()),
+            event_script: Ptr32::from_box(&self.event_script),
+            next: Ptr32::from_box(&self.next),
+        };
+
+        ser.store_into_xfile(handler)?;
+        self.name.xfile_serialize(ser, ())?;
+        self.event_script.xfile_serialize(ser, ())?;
+        self.next.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct GenericEventScriptRaw<'a> {
@@ -552,6 +801,28 @@ impl<'a> XFileDeserializeInto<GenericEventScript, ()> for GenericEventScriptRaw<
     }
 }
 
+impl XFileSerialize<()> for GenericEventScript {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let script = GenericEventScriptRaw {
+            prerequisites: Ptr32::from_box(&self.prerequisites),
+            condition: self.condition.to_raw(),
+            type_: self.type_,
+            fire_on_true: self.fire_on_true,
+            pad: [0; 3],
+            action: XStringRaw::from_str(self.action.get()),
+            block_id: self.block_id,
+            construct_id: self.construct_id,
+            next: Ptr32::from_box(&self.next),
+        };
+
+        ser.store_into_xfile(script)?;
+        self.prerequisites.xfile_serialize(ser, ())?;
+        self.condition.serialize_data(ser)?;
+        self.action.xfile_serialize(ser, ())?;
+        self.next.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct ScriptConditionRaw<'a> {
@@ -593,6 +864,21 @@ impl<'a> XFileDeserializeInto<ScriptCondition, ()> for ScriptConditionRaw<'a> {
     }
 }
 
+impl XFileSerialize<()> for ScriptCondition {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let condition = ScriptConditionRaw {
+            fire_on_true: self.fire_on_true,
+            pad: [0; 3],
+            block_id: self.block_id,
+            construct_id: self.construct_id,
+            next: Ptr32::from_box(&self.next),
+        };
+
+        ser.store_into_xfile(condition)?;
+        self.next.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct ExpressionStatementRaw<'a> {
@@ -603,7 +889,7 @@ pub(crate) struct ExpressionStatementRaw<'a> {
 assert_size!(ExpressionStatementRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct ExpressionStatement {
     pub filename: XString,
     pub line: i32,
@@ -627,6 +913,33 @@ impl<'a> XFileDeserializeInto<ExpressionStatement, ()> for ExpressionStatementRa
     }
 }
 
+impl ExpressionStatement {
+    fn to_raw(&self) -> ExpressionStatementRaw<'static> {
+        ExpressionStatementRaw {
+            filename: XStringRaw::from_str(self.filename.get()),
+            line: self.line,
+            rpn: FatPointerCountFirstU32::from_slice(&self.rpn),
+        }
+    }
+
+    /// Serializes what [`Self::to_raw`]'s pointers point to.
+    fn serialize_data(&self, ser: &mut impl T5XFileSerialize) -> Result<()> {
+        self.filename.xfile_serialize(ser, ())?;
+
+        // Every RPN is stored before any of the strings they point to.
+        ser.begin_array();
+        for rpn in &self.rpn {
+            ser.store_into_xfile(rpn.to_raw())?;
+        }
+        for rpn in &self.rpn {
+            rpn.serialize_data(ser)?;
+        }
+        ser.end_array();
+
+        Ok(())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct ExpressionRpnRaw {
@@ -638,6 +951,7 @@ assert_size!(ExpressionRpnRaw, 12);
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
 pub struct ExpressionRpn {
+    pub type_: i32,
     pub data: Option<ExpressionRpnDataUnion>,
 }
 
@@ -648,7 +962,39 @@ impl XFileDeserializeInto<ExpressionRpn, ()> for ExpressionRpnRaw {
         _data: (),
     ) -> Result<ExpressionRpn> {
         let data = self.data.xfile_deserialize_into(de, self.type_)?;
-        Ok(ExpressionRpn { data })
+        Ok(ExpressionRpn {
+            type_: self.type_,
+            data,
+        })
+    }
+}
+
+impl ExpressionRpn {
+    fn to_raw(&self) -> ExpressionRpnRaw {
+        let mut data = [0; 8];
+        match &self.data {
+            Some(ExpressionRpnDataUnion::Constant(c)) => {
+                let c = c.to_raw();
+                data[..4].copy_from_slice(&c.data_type.to_le_bytes());
+                data[4..].copy_from_slice(&c.internals.0.to_le_bytes());
+            }
+            Some(ExpressionRpnDataUnion::CmdIdx(i)) => data[..4].copy_from_slice(&i.to_le_bytes()),
+            None => {}
+        }
+
+        ExpressionRpnRaw {
+            type_: self.type_,
+            data: ExpressionRpnDataUnionRaw(data),
+        }
+    }
+
+    fn serialize_data(&self, ser: &mut impl T5XFileSerialize) -> Result<()> {
+        match &self.data {
+            Some(ExpressionRpnDataUnion::Constant(Operand {
+                internals: OperandInternalDataUnion::String(s),
+            })) => s.xfile_serialize(ser, ()),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -674,6 +1020,10 @@ impl XFileDeserializeInto<Option<ExpressionRpnDataUnion>, i32> for ExpressionRpn
             Ok(Some(ExpressionRpnDataUnion::Constant(
                 unsafe { transmute::<_, OperandRaw>(self.0) }.xfile_deserialize_into(de, ())?,
             )))
+        } else if type_ == 1 {
+            Ok(Some(ExpressionRpnDataUnion::CmdIdx(i32::from_le_bytes(
+                self.0[..4].try_into().unwrap(),
+            ))))
         } else {
             Ok(None)
         }
@@ -719,6 +1069,23 @@ impl XFileDeserializeInto<Operand, ()> for OperandRaw {
     }
 }
 
+impl Operand {
+    fn to_raw(&self) -> OperandRaw {
+        let (data_type, internals) = match &self.internals {
+            OperandInternalDataUnion::Int(i) => (ExpDataType::INT, *i as u32),
+            OperandInternalDataUnion::Float(f) => (ExpDataType::FLOAT, f.to_bits()),
+            OperandInternalDataUnion::String(s) => {
+                (ExpDataType::STRING, XStringRaw::from_str(s.get()).as_u32())
+            }
+        };
+
+        OperandRaw {
+            data_type: data_type as _,
+            internals: OperandInternalDataUnionRaw(internals),
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct OperandInternalDataUnionRaw(u32);
@@ -785,6 +1152,20 @@ impl<'a> XFileDeserializeInto<ItemKeyHandler, ()> for ItemKeyHandlerRaw<'a> {
     }
 }
 
+impl XFileSerialize<()> for ItemKeyHandler {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let handler = ItemKeyHandlerRaw {
+            key: self.key,
+            key_script: Ptr32::from_box(&self.key_script),
+            next: Ptr32::from_box(&self.next),
+        };
+
+        ser.store_into_xfile(handler)?;
+        self.key_script.xfile_serialize(ser, ())?;
+        self.next.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct ItemDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
@@ -835,6 +1216,31 @@ pub struct ItemDef<const MAX_LOCAL_CLIENTS: usize> {
     pub anim_info: Option<Box<UIAnimInfo>>,
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> Default for ItemDef<MAX_LOCAL_CLIENTS> {
+    fn default() -> Self {
+        Self {
+            window: WindowDef::default(),
+            type_: 0,
+            data_type: 0,
+            image_track: 0,
+            dvar: XString::default(),
+            dvar_text: XString::default(),
+            enable_dvar: XString::default(),
+            dvar_flags: 0,
+            type_data: None,
+            parent: None,
+            rect_exp_data: None,
+            visible_exp: ExpressionStatement::default(),
+            show_bits: 0,
+            hide_bits: 0,
+            forecolor_a_exp: ExpressionStatement::default(),
+            ui_3d_window_id: 0,
+            on_event: None,
+            anim_info: None,
+        }
+    }
+}
+
 impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<ItemDef<MAX_LOCAL_CLIENTS>, ()>
     for ItemDefRaw<'a, MAX_LOCAL_CLIENTS>
 {
@@ -910,6 +1316,130 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<ItemDef<MAX_LOCAL_
     }
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for ItemDef<MAX_LOCAL_CLIENTS> {
+    /// [`Self::parent`] is always serialized as null, since it points back
+    /// into the menu that's already been serialized.
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let item = ItemDefRaw::<MAX_LOCAL_CLIENTS> {
+            window: self.window.to_raw(),
+            type_: self.type_,
+            data_type: self.data_type,
+            image_track: self.image_track,
+            dvar: XStringRaw::from_str(self.dvar.get()),
+            dvar_text: XStringRaw::from_str(self.dvar_text.get()),
+            enable_dvar: XStringRaw::from_str(self.enable_dvar.get()),
+            dvar_flags: self.dvar_flags,
+            type_data: ItemDefDataRaw(
+                self.type_data
+                    .as_ref()
+                    .map_or(Ptr32::null(), ItemDefData::ptr),
+            ),
+            parent: Ptr32::null(),
+            rect_exp_data: Ptr32::from_box(&self.rect_exp_data),
+            visible_exp: self.visible_exp.to_raw(),
+            pad: [0; 4],
+            show_bits: self.show_bits,
+            hide_bits: self.hide_bits,
+            forecolor_a_exp: self.forecolor_a_exp.to_raw(),
+            ui_3d_window_id: self.ui_3d_window_id,
+            on_event: Ptr32::from_box(&self.on_event),
+            anim_info: Ptr32::from_box(&self.anim_info),
+            pad2: [0; 4],
+        };
+
+        ser.store_into_xfile(item)?;
+        self.window.serialize_data(ser)?;
+        self.dvar.xfile_serialize(ser, ())?;
+        self.dvar_text.xfile_serialize(ser, ())?;
+        self.enable_dvar.xfile_serialize(ser, ())?;
+        self.type_data.xfile_serialize(ser, self.type_)?;
+        self.rect_exp_data.xfile_serialize(ser, ())?;
+        self.visible_exp.serialize_data(ser)?;
+        self.forecolor_a_exp.serialize_data(ser)?;
+        self.on_event.xfile_serialize(ser, ())?;
+        self.anim_info.xfile_serialize(ser, ())
+    }
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> ItemDef<MAX_LOCAL_CLIENTS> {
+    /// The localize keys the item's strings refer to, i.e. the values
+    /// starting with `@`, with the `@` stripped.
+    ///
+    /// The item's text, mouse-enter and mouse-exit text, and every script
+    /// in [`Self::script_actions`] are looked at. T5 items have no separate
+    /// tooltip field; the mouse-enter text serves as one.
+    pub fn localize_keys(&self) -> Vec<&str> {
+        let texts = [
+            self.text_def().map(|t| t.text.get()),
+            self.focus_item().map(|f| f.mouse_enter_text.get()),
+            self.focus_item().map(|f| f.mouse_exit_text.get()),
+        ];
+
+        texts
+            .into_iter()
+            .flatten()
+            .chain(self.script_actions())
+            .filter_map(|s| s.strip_prefix('@'))
+            .filter(|key| !key.is_empty())
+            .collect()
+    }
+
+    /// Every non-empty script the item runs: its event handlers' actions,
+    /// its mouse-enter and mouse-exit scripts, and its key handlers'
+    /// actions, in that order.
+    pub fn script_actions(&self) -> Vec<&str> {
+        let mut actions = Vec::new();
+        event_handler_actions(self.on_event.as_deref(), &mut actions);
+        if let Some(focus) = self.focus_item() {
+            actions.push(focus.mouse_enter.get());
+            actions.push(focus.mouse_exit.get());
+
+            let mut handler = focus.on_key.as_deref();
+            while let Some(h) = handler {
+                event_script_actions(h.key_script.as_deref(), &mut actions);
+                handler = h.next.as_deref();
+            }
+        }
+
+        actions.retain(|a| !a.is_empty());
+        actions
+    }
+
+    fn text_def(&self) -> Option<&TextDef<MAX_LOCAL_CLIENTS>> {
+        match &self.type_data {
+            Some(ItemDefData::TextDef(Some(t))) => Some(t),
+            _ => None,
+        }
+    }
+
+    fn focus_item(&self) -> Option<&FocusItemDef<MAX_LOCAL_CLIENTS>> {
+        match &self.type_data {
+            Some(ItemDefData::BlankButtonDef(Some(f))) => Some(f),
+            _ => match &self.text_def()?.text_type_data {
+                Some(TextDefData::FocusItemDef(Some(f))) => Some(f),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Pushes the action of every script of every handler in the chain
+/// starting at `handler`.
+fn event_handler_actions<'a>(mut handler: Option<&'a GenericEventHandler>, out: &mut Vec<&'a str>) {
+    while let Some(h) = handler {
+        event_script_actions(h.event_script.as_deref(), out);
+        handler = h.next.as_deref();
+    }
+}
+
+/// Pushes the action of every script in the chain starting at `script`.
+fn event_script_actions<'a>(mut script: Option<&'a GenericEventScript>, out: &mut Vec<&'a str>) {
+    while let Some(s) = script {
+        out.push(s.action.get());
+        script = s.next.as_deref();
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct ItemDefDataRaw<'a, const MAX_LOCAL_CLIENTS: usize>(Ptr32<'a, ()>);
@@ -935,37 +1465,97 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize>
     ) -> Result<Option<ItemDefData<MAX_LOCAL_CLIENTS>>> {
         //dbg!(self);
         if self.0.is_null() {
-            Ok(None)
-        } else if type_ == 2 {
-            Ok(Some(ItemDefData::ImageDef(
+            return Ok(None);
+        }
+
+        match ItemDefDataKind::for_type(type_) {
+            Some(ItemDefDataKind::Image) => Ok(Some(ItemDefData::ImageDef(
                 self.0
                     .cast::<ImageDefRaw>()
                     .xfile_deserialize_into(de, ())?,
-            )))
-        } else if type_ == 21 || type_ == 19 {
-            Ok(Some(ItemDefData::BlankButtonDef(
+            ))),
+            Some(ItemDefDataKind::BlankButton) => Ok(Some(ItemDefData::BlankButtonDef(
                 self.0
                     .cast::<FocusItemDefRaw<MAX_LOCAL_CLIENTS>>()
                     .xfile_deserialize_into(de, type_)?,
-            )))
-        } else if type_ == 6 {
-            Ok(Some(ItemDefData::OwnerDrawDef(
+            ))),
+            Some(ItemDefDataKind::OwnerDraw) => Ok(Some(ItemDefData::OwnerDrawDef(
                 self.0
                     .cast::<OwnerDrawDefRaw>()
                     .xfile_deserialize_into(de, ())?,
-            )))
-        } else if type_ == 17 || type_ > 22 {
-            Err(Error::new_with_offset(
-                file_line_col!(),
-                de.stream_pos()? as _,
-                ErrorKind::BrokenInvariant(format!("ItemDefData: type ({type_}) > 22",)),
-            ))
-        } else {
-            Ok(Some(ItemDefData::TextDef(
+            ))),
+            Some(ItemDefDataKind::Text) => Ok(Some(ItemDefData::TextDef(
                 self.0
                     .cast::<TextDefRaw<MAX_LOCAL_CLIENTS>>()
                     .xfile_deserialize_into(de, type_)?,
-            )))
+            ))),
+            None => Err(Error::new_with_offset(
+                file_line_col!(),
+                de.stream_pos()? as _,
+                ErrorKind::BrokenInvariant(format!("ItemDefData: type ({type_}) > 22",)),
+            )),
+        }
+    }
+}
+
+/// Which [`ItemDefData`] variant an item's type uses.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ItemDefDataKind {
+    Text,
+    Image,
+    BlankButton,
+    OwnerDraw,
+}
+
+impl ItemDefDataKind {
+    const fn for_type(type_: i32) -> Option<Self> {
+        match type_ {
+            2 => Some(Self::Image),
+            19 | 21 => Some(Self::BlankButton),
+            6 => Some(Self::OwnerDraw),
+            17 | 23.. => None,
+            _ => Some(Self::Text),
+        }
+    }
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> ItemDefData<MAX_LOCAL_CLIENTS> {
+    const fn kind(&self) -> ItemDefDataKind {
+        match self {
+            Self::TextDef(_) => ItemDefDataKind::Text,
+            Self::ImageDef(_) => ItemDefDataKind::Image,
+            Self::BlankButtonDef(_) => ItemDefDataKind::BlankButton,
+            Self::OwnerDrawDef(_) => ItemDefDataKind::OwnerDraw,
+        }
+    }
+
+    fn ptr(&self) -> Ptr32<'static, ()> {
+        match self {
+            Self::TextDef(t) => Ptr32::from_box(t),
+            Self::ImageDef(i) => Ptr32::from_box(i),
+            Self::BlankButtonDef(b) => Ptr32::from_box(b),
+            Self::OwnerDrawDef(o) => Ptr32::from_box(o),
+        }
+    }
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> XFileSerialize<i32> for ItemDefData<MAX_LOCAL_CLIENTS> {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, type_: i32) -> Result<()> {
+        if ItemDefDataKind::for_type(type_) != Some(self.kind()) {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "ItemDefData: type ({type_}) doesn't use {:?}",
+                    self.kind()
+                )),
+            ));
+        }
+
+        match self {
+            Self::TextDef(t) => t.xfile_serialize(ser, type_),
+            Self::ImageDef(i) => i.xfile_serialize(ser, ()),
+            Self::BlankButtonDef(b) => b.xfile_serialize(ser, type_),
+            Self::OwnerDrawDef(o) => o.xfile_serialize(ser, ()),
         }
     }
 }
@@ -1026,6 +1616,25 @@ pub struct TextDef<const MAX_LOCAL_CLIENTS: usize> {
     pub text_type_data: Option<TextDefData<MAX_LOCAL_CLIENTS>>,
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> Default for TextDef<MAX_LOCAL_CLIENTS> {
+    fn default() -> Self {
+        Self {
+            text_rect: core::array::from_fn(|_| RectDef::default()),
+            alignment: 0,
+            font_enum: 0,
+            item_flags: 0,
+            text_align_mode: 0,
+            textalignx: 0.0,
+            textaligny: 0.0,
+            textscale: 0.0,
+            text_style: 0,
+            text: XString::default(),
+            text_exp_data: None,
+            text_type_data: None,
+        }
+    }
+}
+
 impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<TextDef<MAX_LOCAL_CLIENTS>, i32>
     for TextDefRaw<'a, MAX_LOCAL_CLIENTS>
 {
@@ -1057,6 +1666,34 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<TextDef<MAX_LOCAL_
     }
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> XFileSerialize<i32> for TextDef<MAX_LOCAL_CLIENTS> {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, type_: i32) -> Result<()> {
+        let text_def = TextDefRaw::<MAX_LOCAL_CLIENTS> {
+            text_rect: self.text_rect.each_ref().map(RectDef::to_raw),
+            alignment: self.alignment,
+            font_enum: self.font_enum,
+            item_flags: self.item_flags,
+            text_align_mode: self.text_align_mode,
+            textalignx: self.textalignx,
+            textaligny: self.textaligny,
+            textscale: self.textscale,
+            text_style: self.text_style,
+            text: XStringRaw::from_str(self.text.get()),
+            text_exp_data: Ptr32::from_box(&self.text_exp_data),
+            text_type_data: TextDefDataRaw(
+                self.text_type_data
+                    .as_ref()
+                    .map_or(Ptr32::null(), TextDefData::ptr),
+            ),
+        };
+
+        ser.store_into_xfile(text_def)?;
+        self.text.xfile_serialize(ser, ())?;
+        self.text_exp_data.xfile_serialize(ser, ())?;
+        self.text_type_data.xfile_serialize(ser, type_)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct TextExpRaw<'a> {
@@ -1082,6 +1719,15 @@ impl<'a> XFileDeserializeInto<TextExp, ()> for TextExpRaw<'a> {
     }
 }
 
+impl XFileSerialize<()> for TextExp {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        ser.store_into_xfile(TextExpRaw {
+            text_exp: self.text_exp.to_raw(),
+        })?;
+        self.text_exp.serialize_data(ser)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct TextDefDataRaw<'a, const MAX_LOCAL_CLIENTS: usize>(Ptr32<'a, ()>);
@@ -1105,30 +1751,75 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize>
     ) -> Result<Option<TextDefData<MAX_LOCAL_CLIENTS>>> {
         //dbg!(self);
         if self.0.is_null() {
-            Ok(None)
-        } else if type_ == 15 {
-            Ok(Some(TextDefData::GameMsgDef(
+            return Ok(None);
+        }
+
+        match TextDefDataKind::for_type(type_) {
+            Some(TextDefDataKind::GameMsgDef) => Ok(Some(TextDefData::GameMsgDef(
                 self.0.cast::<GameMsgDef>().xfile_get(de)?.map(Box::new),
-            )))
-        } else if type_ < 3
-            || type_ == 6
-            || type_ == 7
-            || type_ == 17
-            || type_ == 18
-            || type_ == 19
-            || type_ > 23
-        {
-            Err(Error::new_with_offset(
-                file_line_col!(),
-                de.stream_pos()? as _,
-                ErrorKind::BrokenInvariant(format!("TextDefData: type ({type_}) invalid.",)),
-            ))
-        } else {
-            Ok(Some(TextDefData::FocusItemDef(
+            ))),
+            Some(TextDefDataKind::FocusItemDef) => Ok(Some(TextDefData::FocusItemDef(
                 self.0
                     .cast::<FocusItemDefRaw<MAX_LOCAL_CLIENTS>>()
                     .xfile_deserialize_into(de, type_)?,
-            )))
+            ))),
+            None => Err(Error::new_with_offset(
+                file_line_col!(),
+                de.stream_pos()? as _,
+                ErrorKind::BrokenInvariant(format!("TextDefData: type ({type_}) invalid.",)),
+            )),
+        }
+    }
+}
+
+/// Which [`TextDefData`] variant an item's type uses.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum TextDefDataKind {
+    FocusItemDef,
+    GameMsgDef,
+}
+
+impl TextDefDataKind {
+    const fn for_type(type_: i32) -> Option<Self> {
+        match type_ {
+            15 => Some(Self::GameMsgDef),
+            ..=2 | 6 | 7 | 17 | 18 | 19 | 24.. => None,
+            _ => Some(Self::FocusItemDef),
+        }
+    }
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> TextDefData<MAX_LOCAL_CLIENTS> {
+    const fn kind(&self) -> TextDefDataKind {
+        match self {
+            Self::FocusItemDef(_) => TextDefDataKind::FocusItemDef,
+            Self::GameMsgDef(_) => TextDefDataKind::GameMsgDef,
+        }
+    }
+
+    fn ptr(&self) -> Ptr32<'static, ()> {
+        match self {
+            Self::FocusItemDef(f) => Ptr32::from_box(f),
+            Self::GameMsgDef(g) => Ptr32::from_box(g),
+        }
+    }
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> XFileSerialize<i32> for TextDefData<MAX_LOCAL_CLIENTS> {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, type_: i32) -> Result<()> {
+        if TextDefDataKind::for_type(type_) != Some(self.kind()) {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "TextDefData: type ({type_}) doesn't use {:?}",
+                    self.kind()
+                )),
+            ));
+        }
+
+        match self {
+            Self::FocusItemDef(f) => f.xfile_serialize(ser, type_),
+            Self::GameMsgDef(g) => g.xfile_serialize(ser, ()),
         }
     }
 }
@@ -1146,7 +1837,7 @@ pub(crate) struct FocusItemDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
 assert_size!(FocusItemDefRaw<1>, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct FocusItemDef<const MAX_LOCAL_CLIENTS: usize> {
     pub mouse_enter_text: XString,
     pub mouse_exit_text: XString,
@@ -1183,6 +1874,31 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<FocusItemDef<MAX_L
     }
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> XFileSerialize<i32> for FocusItemDef<MAX_LOCAL_CLIENTS> {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, type_: i32) -> Result<()> {
+        let focus_item_def = FocusItemDefRaw::<MAX_LOCAL_CLIENTS> {
+            mouse_enter_text: XStringRaw::from_str(self.mouse_enter_text.get()),
+            mouse_exit_text: XStringRaw::from_str(self.mouse_exit_text.get()),
+            mouse_enter: XStringRaw::from_str(self.mouse_enter.get()),
+            mouse_exit: XStringRaw::from_str(self.mouse_exit.get()),
+            on_key: Ptr32::from_box(&self.on_key),
+            focus_type_data: FocusDefDataRaw(
+                self.focus_type_data
+                    .as_ref()
+                    .map_or(Ptr32::null(), FocusDefData::ptr),
+            ),
+        };
+
+        ser.store_into_xfile(focus_item_def)?;
+        self.mouse_enter_text.xfile_serialize(ser, ())?;
+        self.mouse_exit_text.xfile_serialize(ser, ())?;
+        self.mouse_enter.xfile_serialize(ser, ())?;
+        self.mouse_exit.xfile_serialize(ser, ())?;
+        self.on_key.xfile_serialize(ser, ())?;
+        self.focus_type_data.xfile_serialize(ser, type_)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct FocusDefDataRaw<'a, const MAX_LOCAL_CLIENTS: usize>(Ptr32<'a, ()>);
@@ -1208,48 +1924,98 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize>
     ) -> Result<Option<FocusDefData<MAX_LOCAL_CLIENTS>>> {
         //dbg!(self);
         if self.0.is_null() {
-            Ok(None)
-        } else if type_ == 4 {
-            Ok(Some(FocusDefData::ListBox(
+            return Ok(None);
+        }
+
+        match FocusDefDataKind::for_type(type_) {
+            Some(FocusDefDataKind::ListBox) => Ok(Some(FocusDefData::ListBox(
                 self.0
                     .cast::<ListBoxDefRaw<MAX_LOCAL_CLIENTS>>()
                     .xfile_deserialize_into(de, ())?,
-            )))
-        } else if type_ == 10 {
-            Ok(Some(FocusDefData::Multi(
+            ))),
+            Some(FocusDefDataKind::Multi) => Ok(Some(FocusDefData::Multi(
                 self.0
                     .cast::<MultiDefRaw>()
                     .xfile_deserialize_into(de, ())?,
-            )))
-        } else if type_ == 11 {
-            Ok(Some(FocusDefData::EnumDvar(
+            ))),
+            Some(FocusDefDataKind::EnumDvar) => Ok(Some(FocusDefData::EnumDvar(
                 self.0
                     .cast::<EnumDvarDefRaw>()
                     .xfile_deserialize_into(de, ())?,
-            )))
-        } else if type_ == 5
-            || type_ == 7
-            || type_ == 8
-            || type_ == 9
-            || type_ == 12
-            || type_ == 13
-            || type_ == 14
-            || type_ == 16
-            || type_ == 22
-            || type_ == 30
-        {
-            Ok(Some(FocusDefData::EditField(
+            ))),
+            Some(FocusDefDataKind::EditField) => Ok(Some(FocusDefData::EditField(
                 self.0
                     .cast::<EditFieldDef<MAX_LOCAL_CLIENTS>>()
                     .xfile_get(de)?
                     .map(Box::new),
-            )))
-        } else {
-            Err(Error::new_with_offset(
+            ))),
+            None => Err(Error::new_with_offset(
                 file_line_col!(),
                 de.stream_pos()? as _,
                 ErrorKind::BrokenInvariant(format!("FocusDefData: type ({type_}) invalid.",)),
-            ))
+            )),
+        }
+    }
+}
+
+/// Which [`FocusDefData`] variant an item's type uses.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum FocusDefDataKind {
+    ListBox,
+    Multi,
+    EditField,
+    EnumDvar,
+}
+
+impl FocusDefDataKind {
+    const fn for_type(type_: i32) -> Option<Self> {
+        match type_ {
+            4 => Some(Self::ListBox),
+            10 => Some(Self::Multi),
+            11 => Some(Self::EnumDvar),
+            5 | 7 | 8 | 9 | 12 | 13 | 14 | 16 | 22 | 30 => Some(Self::EditField),
+            _ => None,
+        }
+    }
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> FocusDefData<MAX_LOCAL_CLIENTS> {
+    const fn kind(&self) -> FocusDefDataKind {
+        match self {
+            Self::ListBox(_) => FocusDefDataKind::ListBox,
+            Self::Multi(_) => FocusDefDataKind::Multi,
+            Self::EditField(_) => FocusDefDataKind::EditField,
+            Self::EnumDvar(_) => FocusDefDataKind::EnumDvar,
+        }
+    }
+
+    fn ptr(&self) -> Ptr32<'static, ()> {
+        match self {
+            Self::ListBox(l) => Ptr32::from_box(l),
+            Self::Multi(m) => Ptr32::from_box(m),
+            Self::EditField(e) => Ptr32::from_box(e),
+            Self::EnumDvar(e) => Ptr32::from_box(e),
+        }
+    }
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> XFileSerialize<i32> for FocusDefData<MAX_LOCAL_CLIENTS> {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, type_: i32) -> Result<()> {
+        if FocusDefDataKind::for_type(type_) != Some(self.kind()) {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "FocusDefData: type ({type_}) doesn't use {:?}",
+                    self.kind()
+                )),
+            ));
+        }
+
+        match self {
+            Self::ListBox(l) => l.xfile_serialize(ser, ()),
+            Self::Multi(m) => m.xfile_serialize(ser, ()),
+            Self::EditField(e) => e.xfile_serialize(ser, ()),
+            Self::EnumDvar(e) => e.xfile_serialize(ser, ()),
         }
     }
 }
@@ -1350,6 +2116,36 @@ pub struct ListBoxDef<const MAX_LOCAL_CLIENTS: usize> {
     pub rows: Vec<MenuRow>,
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> Default for ListBoxDef<MAX_LOCAL_CLIENTS> {
+    fn default() -> Self {
+        Self {
+            mouse_pos: 0,
+            cursor_pos: [0; MAX_LOCAL_CLIENTS],
+            start_pos: [0; MAX_LOCAL_CLIENTS],
+            end_pos: [0; MAX_LOCAL_CLIENTS],
+            draw_padding: false,
+            element_width: 0.0,
+            element_height: 0.0,
+            num_columns: 0,
+            special: 0.0,
+            column_info: Default::default(),
+            not_selectable: false,
+            no_scroll_bars: false,
+            use_paging: false,
+            select_border: Vec4::default(),
+            disable_color: Vec4::default(),
+            focus_color: Vec4::default(),
+            element_highlight_color: Vec4::default(),
+            element_background_color: Vec4::default(),
+            select_icon: None,
+            background_item_listbox: None,
+            highlight_texture: None,
+            no_blinking_highlight: false,
+            rows: Vec::default(),
+        }
+    }
+}
+
 impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<ListBoxDef<MAX_LOCAL_CLIENTS>, ()>
     for ListBoxDefRaw<'a, MAX_LOCAL_CLIENTS>
 {
@@ -1404,6 +2200,54 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<ListBoxDef<MAX_LOC
     }
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for ListBoxDef<MAX_LOCAL_CLIENTS> {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let list_box = ListBoxDefRaw::<MAX_LOCAL_CLIENTS> {
+            mouse_pos: self.mouse_pos,
+            cursor_pos: self.cursor_pos,
+            start_pos: self.start_pos,
+            end_pos: self.end_pos,
+            draw_padding: self.draw_padding as _,
+            element_width: self.element_width,
+            element_height: self.element_height,
+            num_columns: self.num_columns,
+            special: self.special,
+            column_info: self.column_info.each_ref().map(ColumnInfo::to_raw),
+            not_selectable: self.not_selectable as _,
+            no_scroll_bars: self.no_scroll_bars as _,
+            use_paging: self.use_paging as _,
+            select_border: self.select_border.get(),
+            disable_color: self.disable_color.get(),
+            focus_color: self.focus_color.get(),
+            element_highlight_color: self.element_highlight_color.get(),
+            element_background_color: self.element_background_color.get(),
+            select_icon: Ptr32::from_box(&self.select_icon),
+            background_item_listbox: Ptr32::from_box(&self.background_item_listbox),
+            highlight_texture: Ptr32::from_box(&self.highlight_texture),
+            no_blinking_highlight: self.no_blinking_highlight as _,
+            rows: FatPointerCountLastU32::from_slice(&self.rows),
+            row_count: self.rows.len() as _,
+        };
+
+        ser.store_into_xfile(list_box)?;
+        self.select_icon.xfile_serialize(ser, ())?;
+        self.background_item_listbox.xfile_serialize(ser, ())?;
+        self.highlight_texture.xfile_serialize(ser, ())?;
+
+        // Every row is stored before any of the cells they point to.
+        ser.begin_array();
+        for row in &self.rows {
+            ser.store_into_xfile(row.to_raw())?;
+        }
+        for row in &self.rows {
+            row.serialize_data(ser, self.num_columns)?;
+        }
+        ser.end_array();
+
+        Ok(())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct ColumnInfoRaw {
@@ -1414,7 +2258,7 @@ pub(crate) struct ColumnInfoRaw {
 assert_size!(ColumnInfoRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct ColumnInfo {
     pub element_style: i32,
     pub max_chars: i32,
@@ -1433,6 +2277,16 @@ impl Into<ColumnInfo> for ColumnInfoRaw {
     }
 }
 
+impl ColumnInfo {
+    fn to_raw(&self) -> ColumnInfoRaw {
+        ColumnInfoRaw {
+            element_style: self.element_style,
+            max_chars: self.max_chars,
+            rect: self.rect.to_raw(),
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct MenuRowRaw<'a> {
@@ -1447,7 +2301,7 @@ pub(crate) struct MenuRowRaw<'a> {
 assert_size!(MenuRowRaw, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct MenuRow {
     pub cells: Vec<MenuCell>,
     pub event_name: XString,
@@ -1481,6 +2335,46 @@ impl<'a> XFileDeserializeInto<MenuRow, i32> for MenuRowRaw<'a> {
     }
 }
 
+impl MenuRow {
+    fn to_raw(&self) -> MenuRowRaw<'static> {
+        MenuRowRaw {
+            cells: Ptr32::from_slice(&self.cells),
+            event_name: XStringRaw::from_str(self.event_name.get()),
+            on_focus_event_name: XStringRaw::from_str(self.on_focus_event_name.get()),
+            disable_arg: self.disable_arg,
+            pad: [0; 3],
+            status: self.status,
+            name: self.name,
+        }
+    }
+
+    /// Serializes what [`Self::to_raw`]'s pointers point to. A row's cells
+    /// aren't counted separately, so there must be one per column.
+    fn serialize_data(&self, ser: &mut impl T5XFileSerialize, num_columns: i32) -> Result<()> {
+        if !self.cells.is_empty() && self.cells.len() != num_columns as usize {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "MenuRow: {} cells, but the list box has {num_columns} columns",
+                    self.cells.len()
+                )),
+            ));
+        }
+
+        ser.begin_array();
+        for cell in &self.cells {
+            ser.store_into_xfile(cell.to_raw())?;
+        }
+        for cell in &self.cells {
+            cell.string_value.xfile_serialize(ser, ())?;
+        }
+        ser.end_array();
+
+        self.event_name.xfile_serialize(ser, ())?;
+        self.on_focus_event_name.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct MenuCellRaw<'a> {
@@ -1491,7 +2385,7 @@ pub(crate) struct MenuCellRaw<'a> {
 assert_size!(MenuCellRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct MenuCell {
     pub type_: i32,
     pub max_chars: i32,
@@ -1514,6 +2408,16 @@ impl<'a> XFileDeserializeInto<MenuCell, ()> for MenuCellRaw<'a> {
     }
 }
 
+impl MenuCell {
+    fn to_raw(&self) -> MenuCellRaw<'static> {
+        MenuCellRaw {
+            type_: self.type_,
+            max_chars: self.max_chars,
+            string_value: XStringRaw::from_str(self.string_value.get()),
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct MultiDefRaw<'a> {
@@ -1527,7 +2431,7 @@ pub(crate) struct MultiDefRaw<'a> {
 assert_size!(MultiDefRaw, 396);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct MultiDef {
     pub dvar_list: [XString; 32],
     pub dvar_str: [XString; 32],
@@ -1570,6 +2474,29 @@ impl<'a> XFileDeserializeInto<MultiDef, ()> for MultiDefRaw<'a> {
     }
 }
 
+impl XFileSerialize<()> for MultiDef {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let multi = MultiDefRaw {
+            dvar_list: self
+                .dvar_list
+                .each_ref()
+                .map(|s| XStringRaw::from_str(s.get())),
+            dvar_str: self
+                .dvar_str
+                .each_ref()
+                .map(|s| XStringRaw::from_str(s.get())),
+            dvar_value: self.dvar_value,
+            count: self.count,
+            action_on_press_enter_only: self.action_on_press_enter_only as _,
+            str_def: self.str_def,
+        };
+
+        ser.store_into_xfile(multi)?;
+        self.dvar_list.xfile_serialize(ser, ())?;
+        self.dvar_str.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Debug, Deserialize)]
 pub struct EditFieldDef<const MAX_LOCAL_CLIENTS: usize> {
@@ -1602,6 +2529,12 @@ impl<const MAX_LOCAL_CLIENTS: usize> Default for EditFieldDef<MAX_LOCAL_CLIENTS>
     }
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for EditFieldDef<MAX_LOCAL_CLIENTS> {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        ser.store_into_xfile(*self)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct EnumDvarDefRaw<'a> {
@@ -1610,7 +2543,7 @@ pub(crate) struct EnumDvarDefRaw<'a> {
 assert_size!(EnumDvarDefRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct EnumDvarDef {
     pub enum_dvar_name: XString,
 }
@@ -1627,6 +2560,15 @@ impl<'a> XFileDeserializeInto<EnumDvarDef, ()> for EnumDvarDefRaw<'a> {
     }
 }
 
+impl XFileSerialize<()> for EnumDvarDef {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        ser.store_into_xfile(EnumDvarDefRaw {
+            enum_dvar_name: XStringRaw::from_str(self.enum_dvar_name.get()),
+        })?;
+        self.enum_dvar_name.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub struct GameMsgDef {
@@ -1635,6 +2577,12 @@ pub struct GameMsgDef {
 }
 assert_size!(GameMsgDef, 8);
 
+impl XFileSerialize<()> for GameMsgDef {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        ser.store_into_xfile(*self)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct ImageDefRaw<'a> {
@@ -1643,7 +2591,7 @@ pub(crate) struct ImageDefRaw<'a> {
 assert_size!(ImageDefRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct ImageDef {
     pub material_exp: ExpressionStatement,
 }
@@ -1660,6 +2608,15 @@ impl<'a> XFileDeserializeInto<ImageDef, ()> for ImageDefRaw<'a> {
     }
 }
 
+impl XFileSerialize<()> for ImageDef {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        ser.store_into_xfile(ImageDefRaw {
+            material_exp: self.material_exp.to_raw(),
+        })?;
+        self.material_exp.serialize_data(ser)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct OwnerDrawDefRaw<'a> {
@@ -1668,7 +2625,7 @@ pub(crate) struct OwnerDrawDefRaw<'a> {
 assert_size!(OwnerDrawDefRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct OwnerDrawDef {
     pub data_exp: ExpressionStatement,
 }
@@ -1685,6 +2642,15 @@ impl<'a> XFileDeserializeInto<OwnerDrawDef, ()> for OwnerDrawDefRaw<'a> {
     }
 }
 
+impl XFileSerialize<()> for OwnerDrawDef {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        ser.store_into_xfile(OwnerDrawDefRaw {
+            data_exp: self.data_exp.to_raw(),
+        })?;
+        self.data_exp.serialize_data(ser)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct RectDataRaw<'a> {
@@ -1724,6 +2690,23 @@ impl<'a> XFileDeserializeInto<RectData, ()> for RectDataRaw<'a> {
     }
 }
 
+impl XFileSerialize<()> for RectData {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let rect_data = RectDataRaw {
+            rect_x_exp: self.rect_x_exp.to_raw(),
+            rect_y_exp: self.rect_y_exp.to_raw(),
+            rect_w_exp: self.rect_w_exp.to_raw(),
+            rect_h_exp: self.rect_h_exp.to_raw(),
+        };
+
+        ser.store_into_xfile(rect_data)?;
+        self.rect_x_exp.serialize_data(ser)?;
+        self.rect_y_exp.serialize_data(ser)?;
+        self.rect_w_exp.serialize_data(ser)?;
+        self.rect_h_exp.serialize_data(ser)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct UIAnimInfoRaw<'a> {
@@ -1774,6 +2757,24 @@ impl<'a> XFileDeserializeInto<UIAnimInfo, ()> for UIAnimInfoRaw<'a> {
     }
 }
 
+impl XFileSerialize<()> for UIAnimInfo {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let anim_info = UIAnimInfoRaw {
+            anim_states: FatPointerCountFirstU32::from_slice(&self.anim_states),
+            current_anim_state: self.current_anim_state.to_raw(),
+            next_anim_state: self.next_anim_state.to_raw(),
+            animating: self.animating as _,
+            anim_start_time: self.anim_start_time,
+            anim_duration: self.anim_duration,
+        };
+
+        ser.store_into_xfile(anim_info)?;
+        serialize_ptr_array(ser, &self.anim_states)?;
+        self.current_anim_state.serialize_data(ser)?;
+        self.next_anim_state.serialize_data(ser)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct AnimParamsDefRaw<'a> {
@@ -1833,3 +2834,33 @@ impl<'a> XFileDeserializeInto<AnimParamsDef, ()> for AnimParamsDefRaw<'a> {
         })
     }
 }
+
+impl AnimParamsDef {
+    fn to_raw(&self) -> AnimParamsDefRaw<'static> {
+        AnimParamsDefRaw {
+            name: XStringRaw::from_str(self.name.get()),
+            rect_client: self.rect_client.to_raw(),
+            border_size: self.border_size,
+            fore_color: self.fore_color.get(),
+            back_color: self.back_color.get(),
+            border_color: self.border_color.get(),
+            outline_color: self.outline_color.get(),
+            text_scale: self.text_scale,
+            rotation: self.rotation,
+            on_event: Ptr32::from_box(&self.on_event),
+        }
+    }
+
+    /// Serializes what [`Self::to_raw`]'s pointers point to.
+    fn serialize_data(&self, ser: &mut impl T5XFileSerialize) -> Result<()> {
+        self.name.xfile_serialize(ser, ())?;
+        self.on_event.xfile_serialize(ser, ())
+    }
+}
+
+impl XFileSerialize<()> for AnimParamsDef {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        ser.store_into_xfile(self.to_raw())?;
+        self.serialize_data(ser)
+    }
+}
@@ -1,11 +1,20 @@
-use alloc::{ffi::CString, string::ToString, vec::Vec};
+use alloc::{
+    collections::BTreeMap,
+    ffi::CString,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
 use crate::{
-    FatPointer, FatPointerCountLastU32, Ptr32, Result, T5XFileDeserialize, T5XFileSerialize,
-    XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size, common::Vec4,
+    Error, ErrorKind, FatPointer, Result, T5XFileDeserialize, T5XFileSerialize,
+    XFileDeserializeInto, XFileSerialize, XString, assert_size,
+    common::Vec4,
+    file_line_col,
+    util::{FatPointerCountLastU32, Ptr32, XStringRaw},
 };
 
 use serde::{Deserialize, Serialize};
@@ -39,15 +48,165 @@ impl<'a> XFileDeserializeInto<RawFile, ()> for RawFileRaw<'a> {
     }
 }
 
+impl RawFile {
+    /// The buffer without its null terminator.
+    pub fn contents(&self) -> &[u8] {
+        self.buffer.strip_suffix(&[0]).unwrap_or(&self.buffer)
+    }
+
+    /// [`None`] if the contents aren't valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        core::str::from_utf8(self.contents()).ok()
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.as_str().is_some()
+    }
+
+    fn text(&self) -> Result<&str> {
+        self.as_str().ok_or_else(|| {
+            Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "RawFile: {} isn't valid UTF-8",
+                    self.name.get()
+                )),
+            )
+        })
+    }
+
+    /// Parses the contents as CSV, returning the cells of each row. Quoted
+    /// fields can contain commas, line breaks, and `""` (for a literal
+    /// quote), as per RFC 4180. Empty lines are skipped.
+    pub fn parse_as_csv(&self) -> Result<Vec<Vec<String>>> {
+        parse_csv(self.text()?).map_err(|row| {
+            Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "RawFile: {} has an unterminated quoted field in row {row}",
+                    self.name.get(),
+                )),
+            )
+        })
+    }
+
+    /// Parses `key=value` lines, with whitespace around keys and values
+    /// trimmed. Empty lines and lines starting with `//` or `#` are
+    /// skipped. If a key appears more than once, the last value wins.
+    pub fn parse_as_key_value(&self) -> Result<BTreeMap<String, String>> {
+        let mut map = BTreeMap::new();
+
+        for (i, line) in self.text()?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(Error::new(
+                    file_line_col!(),
+                    ErrorKind::BrokenInvariant(format!(
+                        "RawFile: line {} of {} isn't a key=value pair",
+                        i + 1,
+                        self.name.get()
+                    )),
+                ));
+            };
+
+            map.insert(key.trim().to_string(), value.trim().to_string());
+        }
+
+        Ok(map)
+    }
+
+    /// Parses the contents as CSV (see [`Self::parse_as_csv`]), using the
+    /// first row as column names for the rest. Rows with fewer cells than
+    /// there are columns just leave the remaining columns out.
+    pub fn parse_as_config_table(&self) -> Result<Vec<BTreeMap<String, String>>> {
+        let mut rows = self.parse_as_csv()?.into_iter();
+        let Some(header) = rows.next() else {
+            return Ok(Vec::new());
+        };
+
+        rows.enumerate()
+            .map(|(i, row)| {
+                if row.len() > header.len() {
+                    return Err(Error::new(
+                        file_line_col!(),
+                        ErrorKind::BrokenInvariant(format!(
+                            "RawFile: row {} of {} has {} cells, but there are only {} columns",
+                            i + 2,
+                            self.name.get(),
+                            row.len(),
+                            header.len()
+                        )),
+                    ));
+                }
+
+                Ok(header.iter().cloned().zip(row).collect())
+            })
+            .collect()
+    }
+}
+
+/// The parser behind [`RawFile::parse_as_csv`] and [`StringTable::from_csv`].
+/// Fails with the (1-based) row of an unterminated quoted field.
+fn parse_csv(text: &str) -> core::result::Result<Vec<Vec<String>>, usize> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    cell.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if cell.is_empty() => in_quotes = true,
+            ',' if !in_quotes => row.push(core::mem::take(&mut cell)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                if !row.is_empty() || !cell.is_empty() {
+                    row.push(core::mem::take(&mut cell));
+                    rows.push(core::mem::take(&mut row));
+                }
+            }
+            c => cell.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(rows.len() + 1);
+    }
+
+    if !row.is_empty() || !cell.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
 impl XFileSerialize<()> for RawFile {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let name = XStringRaw::from_u32(0xFFFFFFFF);
-        let len = self.buffer.len() as _;
+        let name = XStringRaw::from_str(self.name.get());
+        // `len` doesn't include the null terminator, which is always stored.
+        let contents = self.contents();
+        let len = contents.len() as _;
         let buffer = Ptr32::unreal();
         let raw_file = RawFileRaw { name, len, buffer };
 
         ser.store_into_xfile(raw_file)?;
-        self.buffer.xfile_serialize(ser, ())
+        self.name.xfile_serialize(ser, ())?;
+        let mut buffer = contents.to_vec();
+        buffer.push(0);
+        buffer.xfile_serialize(ser, ())
     }
 }
 
@@ -90,6 +249,79 @@ impl<'a> XFileDeserializeInto<StringTable, ()> for StringTableRaw<'a> {
     }
 }
 
+impl StringTable {
+    /// Builds a table from CSV text (see [`RawFile::parse_as_csv`]) one row
+    /// per record, with short rows padded out with empty cells.
+    pub fn from_csv(name: impl Into<String>, csv: &str) -> Result<Self> {
+        let name = name.into();
+        let rows = parse_csv(csv).map_err(|row| {
+            Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "StringTable: {name} has an unterminated quoted field in row {row}"
+                )),
+            )
+        })?;
+
+        let row_count = rows.len();
+        let column_count = rows.iter().map(Vec::len).max().unwrap_or_default();
+        if row_count * column_count > i16::MAX as usize {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "StringTable: {name} has {} cells (must be <={}).",
+                    row_count * column_count,
+                    i16::MAX
+                )),
+            ));
+        }
+
+        let values = rows
+            .into_iter()
+            .flat_map(|mut row| {
+                row.resize(column_count, String::new());
+                row
+            })
+            .map(|s| StringTableCell {
+                hash: Self::hash(&s),
+                name: XString::from(s),
+            })
+            .collect::<Vec<_>>();
+
+        // The game binary searches the cells by hash through this.
+        let mut cell_index = (0..values.len() as i16).collect::<Vec<_>>();
+        cell_index.sort_by_key(|&i| values[i as usize].hash);
+
+        Ok(StringTable {
+            name: XString::from(name),
+            column_count,
+            row_count,
+            values,
+            cell_index,
+        })
+    }
+
+    /// The game's case-insensitive hash for cell contents.
+    pub fn hash(s: &str) -> i32 {
+        s.bytes().fold(0i32, |hash, b| {
+            hash.wrapping_mul(31)
+                .wrapping_add(b.to_ascii_lowercase() as i32)
+        })
+    }
+
+    /// The contents of the cell at `row` and `column`, or [`None`] if
+    /// they're out of bounds.
+    pub fn cell(&self, row: usize, column: usize) -> Option<&str> {
+        if column >= self.column_count {
+            return None;
+        }
+
+        self.values
+            .get(row * self.column_count + column)
+            .map(|c| c.name.get())
+    }
+}
+
 impl XFileSerialize<()> for StringTable {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -108,7 +340,16 @@ impl XFileSerialize<()> for StringTable {
 
         ser.store_into_xfile(string_table)?;
         self.name.xfile_serialize(ser, ())?;
-        self.values.xfile_serialize(ser, ())?;
+        // All of the cells are read before any of their strings, so they
+        // can't be stored one after the other with `StringTableCell`'s impl.
+        for cell in &self.values {
+            let name = XStringRaw::from_str(cell.name.get());
+            let hash = cell.hash;
+            ser.store_into_xfile(StringTableCellRaw { name, hash })?;
+        }
+        for cell in &self.values {
+            cell.name.xfile_serialize(ser, ())?;
+        }
         self.cell_index.xfile_serialize(ser, ())
     }
 }
@@ -172,6 +413,55 @@ pub struct PackIndex {
     pub entries: Vec<PackIndexEntry>,
 }
 
+impl PackIndex {
+    /// Returns each entry whose hash is the same as an earlier entry's, as
+    /// `(earlier, later)` index pairs. Each duplicate is paired with the
+    /// first entry with that hash.
+    pub fn find_duplicate_hashes(&self) -> Vec<(usize, usize)> {
+        let mut first = BTreeMap::new();
+        let mut duplicates = Vec::new();
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            if let Some(&j) = first.get(&entry.hash) {
+                duplicates.push((j, i));
+            } else {
+                first.insert(entry.hash, i);
+            }
+        }
+
+        duplicates
+    }
+
+    /// Checks that every entry lies within the data section, which starts
+    /// at [`PackIndexHeader::data_start`] and is `total_data_size` bytes
+    /// long.
+    pub fn validate_offsets(&self, total_data_size: usize) -> Result<()> {
+        let data_end = self.header.data_start.saturating_add(total_data_size);
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            let end = entry.offset.checked_add(entry.size);
+            if entry.offset < self.header.data_start || end.is_none_or(|end| end > data_end) {
+                return Err(Error::new(
+                    file_line_col!(),
+                    ErrorKind::BrokenInvariant(format!(
+                        "PackIndex: entry {i} ({:#010X}, {} bytes) isn't within the data \
+                         section ({:#010X}..{data_end:#010X})",
+                        entry.offset, entry.size, self.header.data_start,
+                    )),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sorts the entries by [`PackIndexEntry::offset`], the order they're
+    /// read in.
+    pub fn sort_by_offset(&mut self) {
+        self.entries.sort_by_key(|e| e.offset);
+    }
+}
+
 impl<'a> XFileDeserializeInto<PackIndex, ()> for PackIndexRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -229,6 +519,17 @@ pub struct PackIndexHeader {
     pub data_start: usize,
 }
 
+impl PackIndexHeader {
+    /// Checks that [`Self::alignment`] is a power of two and that
+    /// [`Self::data_start`] is aligned to it.
+    ///
+    /// [`Self::magic`] isn't checked, since the value retail files use
+    /// hasn't been confirmed.
+    pub fn is_valid(&self) -> bool {
+        self.alignment.is_power_of_two() && self.data_start.is_multiple_of(self.alignment)
+    }
+}
+
 impl From<PackIndexHeaderRaw> for PackIndexHeader {
     fn from(value: PackIndexHeaderRaw) -> Self {
         Self {
@@ -277,7 +578,7 @@ pub(crate) struct PackIndexEntryRaw {
 assert_size!(PackIndexEntryRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PackIndexEntry {
     pub hash: u32,
     pub offset: usize,
@@ -341,7 +642,7 @@ impl<'a> XFileDeserializeInto<MapEnts, ()> for MapEntsRaw<'a> {
             chars.push(b'\0');
         }
 
-        let entity_string = XString(
+        let entity_string = XString::from(
             CString::from_vec_with_nul(chars)
                 .unwrap()
                 .to_string_lossy()
@@ -392,6 +693,17 @@ pub struct LocalizeEntry {
     pub name: XString,
 }
 
+impl LocalizeEntry {
+    /// `key` is the full reference, e.g. `MENU_START_GAME`, and `value` is
+    /// the localized text.
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            value: XString::from(value.into()),
+            name: XString::from(key.into()),
+        }
+    }
+}
+
 impl<'a> XFileDeserializeInto<LocalizeEntry, ()> for LocalizeEntryRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -429,6 +741,7 @@ pub(crate) struct XGlobalsRaw<'a> {
     pub gump_reserve: i32,
     pub screen_clear_color: [f32; 4],
 }
+assert_size!(XGlobalsRaw, 40);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
@@ -442,6 +755,160 @@ pub struct XGlobals {
     pub screen_clear_color: Vec4,
 }
 
+impl XGlobals {
+    /// Globals suitable for a patch fastfile named `name`: 720p cinematics,
+    /// a 512x512 extra camera, no xanim stream buffer or gump reserve, and
+    /// an opaque black clear color.
+    pub fn default_for_patch(name: impl Into<String>) -> Self {
+        Self {
+            name: XString::from(name.into()),
+            xanim_stream_buffer_size: 0,
+            cinematic_max_width: 1280,
+            cinematic_max_height: 720,
+            extracam_resolution: 512,
+            gump_reserve: 0,
+            screen_clear_color: [0.0, 0.0, 0.0, 1.0].into(),
+        }
+    }
+
+    /// Every numeric field as a config map key, along with what it does.
+    /// [`Self::screen_clear_color`] is split into one key per channel.
+    const CONFIG_FIELDS: [(&'static str, &'static str); 9] = [
+        (
+            "xanim_stream_buffer_size",
+            "Size in bytes of the buffer streamed xanim data is read into.",
+        ),
+        (
+            "cinematic_max_width",
+            "Widest cinematic the video player allocates for, in pixels.",
+        ),
+        (
+            "cinematic_max_height",
+            "Tallest cinematic the video player allocates for, in pixels.",
+        ),
+        (
+            "extracam_resolution",
+            "Width and height of the extra camera's render target, in pixels.",
+        ),
+        (
+            "gump_reserve",
+            "Bytes of memory set aside for loading gump fastfiles.",
+        ),
+        (
+            "screen_clear_color_r",
+            "Red channel of the color the screen is cleared to.",
+        ),
+        (
+            "screen_clear_color_g",
+            "Green channel of the color the screen is cleared to.",
+        ),
+        (
+            "screen_clear_color_b",
+            "Blue channel of the color the screen is cleared to.",
+        ),
+        (
+            "screen_clear_color_a",
+            "Alpha channel of the color the screen is cleared to.",
+        ),
+    ];
+
+    /// The numeric fields by name, e.g. `"xanim_stream_buffer_size"`. See
+    /// [`Self::describe_field`] for the keys.
+    pub fn to_config_map(&self) -> BTreeMap<&'static str, f32> {
+        let [r, g, b, a] = self.screen_clear_color.get();
+        let values = [
+            self.xanim_stream_buffer_size as f32,
+            self.cinematic_max_width as f32,
+            self.cinematic_max_height as f32,
+            self.extracam_resolution as f32,
+            self.gump_reserve as f32,
+            r,
+            g,
+            b,
+            a,
+        ];
+
+        Self::CONFIG_FIELDS
+            .iter()
+            .map(|&(key, _)| key)
+            .zip(values)
+            .collect()
+    }
+
+    /// The inverse of [`Self::to_config_map`]. `map` has to have every key
+    /// [`Self::to_config_map`] returns; any others are ignored. The integer
+    /// fields have to be whole numbers that fit in an [`i32`].
+    pub fn from_config_map(name: &str, map: &BTreeMap<&str, f32>) -> Result<Self> {
+        let get = |key: &str| {
+            map.get(key).copied().ok_or_else(|| {
+                Error::new(
+                    file_line_col!(),
+                    ErrorKind::BrokenInvariant(format!("XGlobals: config map has no {key}")),
+                )
+            })
+        };
+        let get_int = |key: &str| {
+            let value = get(key)?;
+            // `i32::MAX as f32` rounds up to 2^31, which doesn't fit.
+            if value.fract() != 0.0 || !(i32::MIN as f32..i32::MAX as f32).contains(&value) {
+                return Err(Error::new(
+                    file_line_col!(),
+                    ErrorKind::BrokenInvariant(format!(
+                        "XGlobals: {key} ({value}) isn't an integer"
+                    )),
+                ));
+            }
+            Ok(value as i32)
+        };
+
+        Ok(Self {
+            name: XString::from(name.to_string()),
+            xanim_stream_buffer_size: get_int("xanim_stream_buffer_size")?,
+            cinematic_max_width: get_int("cinematic_max_width")?,
+            cinematic_max_height: get_int("cinematic_max_height")?,
+            extracam_resolution: get_int("extracam_resolution")?,
+            gump_reserve: get_int("gump_reserve")?,
+            screen_clear_color: [
+                get("screen_clear_color_r")?,
+                get("screen_clear_color_g")?,
+                get("screen_clear_color_b")?,
+                get("screen_clear_color_a")?,
+            ]
+            .into(),
+        })
+    }
+
+    /// The keys whose values differ between `a` and `b`, with `a`'s value
+    /// and then `b`'s, in the order [`Self::to_config_map`] lists them. A
+    /// key that's only in one of the maps is reported with NaN for the
+    /// other. Keys that aren't [`XGlobals`] fields are ignored.
+    pub fn diff_config_maps(
+        a: &BTreeMap<&str, f32>,
+        b: &BTreeMap<&str, f32>,
+    ) -> Vec<(&'static str, f32, f32)> {
+        Self::CONFIG_FIELDS
+            .iter()
+            .filter_map(|&(key, _)| match (a.get(key), b.get(key)) {
+                (None, None) => None,
+                (Some(a), Some(b)) if a.to_bits() == b.to_bits() => None,
+                (a, b) => Some((
+                    key,
+                    a.copied().unwrap_or(f32::NAN),
+                    b.copied().unwrap_or(f32::NAN),
+                )),
+            })
+            .collect()
+    }
+
+    /// What the config map key `key` is, or `"unknown"` if it isn't one.
+    pub fn describe_field(key: &str) -> &'static str {
+        Self::CONFIG_FIELDS
+            .iter()
+            .find(|&&(k, _)| k == key)
+            .map_or("unknown", |&(_, description)| description)
+    }
+}
+
 impl<'a> XFileDeserializeInto<XGlobals, ()> for XGlobalsRaw<'a> {
     fn xfile_deserialize_into(
         &self,
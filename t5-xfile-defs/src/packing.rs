@@ -0,0 +1,168 @@
+//! The packed formats vertex data is stored in, and their inverses.
+//!
+//! Unpacking and packing again gives back the same bits for every value the
+//! unpacking functions can return, except for unit vectors: several
+//! encodings can unpack to the same vector, and [`pack_unit_vec`] only
+//! returns one of them. [`GfxPackedVertex::repack`] gets around that by
+//! leaving unchanged fields alone.
+//!
+//! [`GfxPackedVertex::repack`]: crate::xmodel::GfxPackedVertex::repack
+
+/// Converts an IEEE 754 half-precision float to an [`f32`].
+pub fn half_to_f32(h: u16) -> f32 {
+    let sign = ((h & 0x8000) as u32) << 16;
+    let exp = ((h >> 10) & 0x1F) as u32;
+    let mantissa = (h & 0x3FF) as u32;
+
+    let bits = match (exp, mantissa) {
+        (0, 0) => sign,
+        (0, _) => {
+            // Subnormal: shift the mantissa up until it's normalized.
+            let shift = mantissa.leading_zeros() - 21;
+            sign | ((113 - shift) << 23) | (((mantissa << shift) & 0x3FF) << 13)
+        }
+        (0x1F, _) => sign | 0x7F800000 | (mantissa << 13),
+        _ => sign | ((exp + 112) << 23) | (mantissa << 13),
+    };
+
+    f32::from_bits(bits)
+}
+
+/// Converts an [`f32`] to an IEEE 754 half-precision float, rounding to
+/// nearest, ties to even. Values too large for a half become infinity, and
+/// NaNs stay NaNs.
+pub fn f32_to_half(f: f32) -> u16 {
+    let bits = f.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xFF) as i32;
+    let mantissa = bits & 0x7FFFFF;
+
+    if exp == 0xFF {
+        // Keep NaNs NaNs even if their payload is all in the low bits.
+        let payload = (mantissa >> 13) as u16;
+        let nan = if mantissa != 0 && payload == 0 {
+            0x200
+        } else {
+            payload
+        };
+        return sign | 0x7C00 | nan;
+    }
+
+    let exp = exp - 127 + 15;
+    if exp >= 0x1F {
+        return sign | 0x7C00;
+    }
+
+    // Shift out all but the top 10 mantissa bits (more for subnormals),
+    // rounding to nearest even. A carry out of the mantissa correctly bumps
+    // the exponent.
+    let (mantissa, shift, exp) = if exp <= 0 {
+        if exp < -10 {
+            return sign;
+        }
+        (mantissa | 0x800000, (14 - exp) as u32, 0)
+    } else {
+        (mantissa, 13, exp as u32)
+    };
+
+    let half_ulp = 1 << (shift - 1);
+    let rest = mantissa & ((1 << shift) - 1);
+    let mut m = mantissa >> shift;
+    if rest > half_ulp || (rest == half_ulp && m & 1 != 0) {
+        m += 1;
+    }
+
+    sign | ((exp << 10) + m) as u16
+}
+
+/// The game's decoding of a packed texture coordinate. It's laid out like a
+/// half, except that there are no subnormals, infinities or NaNs, and `0`
+/// is the only encoding of zero.
+pub fn unpack_tex_coord(h: u16) -> f32 {
+    if h == 0 {
+        return 0.0;
+    }
+
+    let h = h as u32;
+    let bits = ((h & 0x8000) << 16)
+        | ((((h & 0x3FFF) << 14).wrapping_sub(!(h << 14) & 0x10000000) ^ 0x80000001) >> 1);
+    f32::from_bits(bits)
+}
+
+/// The inverse of [`unpack_tex_coord`], rounding to the nearest value it
+/// can return.
+pub fn pack_tex_coord(f: f32) -> u16 {
+    let bits = f.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let abs = bits & 0x7FFFFFFF;
+
+    // `h`'s low 15 bits are the float's, minus the exponent bias difference.
+    let mag = ((abs.saturating_sub(0x38000000) + 0x1000) >> 13).min(0x7FFF) as u16;
+    match (sign, mag) {
+        // Closer to the smallest positive value than to zero.
+        (0, 0) if f > unpack_tex_coord(1) / 2.0 => 1,
+        // Closer to zero than to `0x8000`, the smallest negative value.
+        (_, 0) if f.abs() < -unpack_tex_coord(0x8000) / 2.0 => 0,
+        _ => sign | mag,
+    }
+}
+
+/// Unpacks a unit vector. The fourth byte is a shared scale for the other
+/// three. `[127, 127, 127, _]` is the zero vector, which the game uses for
+/// missing normals.
+pub fn unpack_unit_vec(packed: [u8; 4]) -> [f32; 3] {
+    let [x, y, z, w] = packed;
+    let scale = (w as f32 + 192.0) / 32385.0;
+    [
+        (x as f32 - 127.0) * scale,
+        (y as f32 - 127.0) * scale,
+        (z as f32 - 127.0) * scale,
+    ]
+}
+
+/// Packs `v` as closely as [`unpack_unit_vec`] can give it back. Of equally
+/// close encodings, the one with the smallest scale is used, so the zero
+/// vector packs to `[127, 127, 127, 0]`.
+///
+/// `v` doesn't have to be normalized, but its components can't be much
+/// bigger than 1.0 in magnitude.
+pub fn pack_unit_vec(v: [f32; 3]) -> [u8; 4] {
+    let mut best = [127, 127, 127, 0];
+    let mut best_dist = f32::INFINITY;
+
+    for w in 0..=255u8 {
+        let scale = (w as f32 + 192.0) / 32385.0;
+        let mut packed = [0, 0, 0, w];
+        let mut fits = true;
+        for (p, c) in packed.iter_mut().zip(v) {
+            let b = (c / scale + 127.0).round();
+            fits &= (0.0..=255.0).contains(&b);
+            *p = b as u8;
+        }
+        if !fits {
+            continue;
+        }
+
+        let dist = unpack_unit_vec(packed)
+            .iter()
+            .zip(v)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f32>();
+        if dist < best_dist {
+            best = packed;
+            best_dist = dist;
+        }
+    }
+
+    best
+}
+
+/// Unpacks a color's bytes, in the order they're stored, to `0.0..=1.0`.
+pub fn unpack_color(packed: [u8; 4]) -> [f32; 4] {
+    packed.map(|c| c as f32 / 255.0)
+}
+
+/// The inverse of [`unpack_color`]. Channels are clamped to `0.0..=1.0`.
+pub fn pack_color(color: [f32; 4]) -> [u8; 4] {
+    color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
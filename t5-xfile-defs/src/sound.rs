@@ -12,9 +12,12 @@ use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    Error, ErrorKind, FatPointer, FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result,
-    T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString,
-    XStringRaw, assert_size, common::Vec2, file_line_col, prelude::*,
+    Error, ErrorKind, FatPointer, Result, T5XFileDeserialize, T5XFileSerialize,
+    XFileDeserializeInto, XFileSerialize, XString, assert_size,
+    common::Vec2,
+    file_line_col,
+    prelude::*,
+    util::{FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, XStringRaw},
 };
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -31,7 +34,7 @@ pub(crate) struct SndBankRaw<'a> {
 assert_size!(SndBankRaw, 40);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct SndBank {
     pub name: XString,
     pub aliases: Vec<SndAliasList>,
@@ -71,6 +74,94 @@ impl<'a> XFileDeserializeInto<SndBank, ()> for SndBankRaw<'a> {
     }
 }
 
+/// The result of [`SndBank::validate`]. Aliases are listed by name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SndBankReport {
+    /// Names shared by more than one [`SndAliasList`], once each.
+    pub duplicate_alias_names: Vec<String>,
+    /// Aliases with no sound file, or whose sound file is null.
+    pub null_sound_files: Vec<String>,
+    /// Aliases whose `vol_min` is greater than their `vol_max`, along with
+    /// both.
+    pub inverted_volumes: Vec<(String, u16, u16)>,
+    /// Aliases whose `snapshot_group` isn't in
+    /// [`SndDriverGlobals::snapshot_groups`], along with the index. Only
+    /// filled in by [`SndBank::validate_with_globals`].
+    pub missing_snapshot_groups: Vec<(String, u8)>,
+}
+
+impl SndBankReport {
+    pub fn is_valid(&self) -> bool {
+        self.duplicate_alias_names.is_empty()
+            && self.null_sound_files.is_empty()
+            && self.inverted_volumes.is_empty()
+            && self.missing_snapshot_groups.is_empty()
+    }
+}
+
+impl SndBank {
+    /// The total number of aliases across all of [`Self::aliases`].
+    pub fn alias_count(&self) -> usize {
+        self.aliases.iter().map(|l| l.aliases.len()).sum()
+    }
+
+    /// The number of [`SndAliasList`]s, i.e. the number of distinct alias
+    /// names.
+    pub fn alias_list_count(&self) -> usize {
+        self.aliases.len()
+    }
+
+    fn all_aliases(&self) -> impl Iterator<Item = &SndAlias> {
+        self.aliases.iter().flat_map(|l| &l.aliases)
+    }
+
+    /// Checks the bank's aliases for problems that don't need any other
+    /// asset to find.
+    pub fn validate(&self) -> SndBankReport {
+        let mut report = SndBankReport::default();
+
+        for (i, list) in self.aliases.iter().enumerate() {
+            let name = list.name.get();
+            let is_duplicate = self.aliases[..i].iter().any(|l| l.name.get() == name);
+            if is_duplicate && !report.duplicate_alias_names.iter().any(|n| n == name) {
+                report.duplicate_alias_names.push(name.to_string());
+            }
+        }
+
+        for alias in self.all_aliases() {
+            let name = alias.name.get();
+
+            if !alias.sound_file.as_ref().is_some_and(|f| f.is_some()) {
+                report.null_sound_files.push(name.to_string());
+            }
+
+            if alias.vol_min > alias.vol_max {
+                report
+                    .inverted_volumes
+                    .push((name.to_string(), alias.vol_min, alias.vol_max));
+            }
+        }
+
+        report
+    }
+
+    /// Like [`Self::validate`], but also checks each alias's
+    /// `snapshot_group` against `globals`.
+    pub fn validate_with_globals(&self, globals: &SndDriverGlobals) -> SndBankReport {
+        let mut report = self.validate();
+
+        for alias in self.all_aliases() {
+            if alias.snapshot_group as usize >= globals.snapshot_groups.len() {
+                report
+                    .missing_snapshot_groups
+                    .push((alias.name.get().to_string(), alias.snapshot_group));
+            }
+        }
+
+        report
+    }
+}
+
 impl XFileSerialize<()> for SndBank {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -108,7 +199,7 @@ pub(crate) struct SndAliasListRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct SndAliasList {
     pub name: XString,
     pub id: u32,
@@ -200,7 +291,7 @@ pub(crate) struct SndAliasRaw<'a> {
 assert_size!(SndAliasRaw, 84);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct SndAlias {
     pub name: XString,
     pub id: u32,
@@ -303,6 +394,70 @@ impl<'a> XFileDeserializeInto<SndAlias, ()> for SndAliasRaw<'a> {
     }
 }
 
+impl SndAlias {
+    /// Bit of [`Self::flags`] set for aliases that loop. The flag layout
+    /// follows T6's, and hasn't been checked against every T5 bank.
+    pub const FLAG_LOOPING: u32 = 1 << 0;
+    /// Bit of [`Self::flags`] set for 3D (positioned) aliases, clear for 2D
+    /// ones. See [`Self::spatial_type`].
+    pub const FLAG_3D: u32 = 1 << 1;
+    /// Bit of [`Self::flags`] set for music.
+    pub const FLAG_MUSIC: u32 = 1 << 6;
+    /// Shift of the two-bit randomize type in [`Self::flags`].
+    pub const RANDOMIZE_TYPE_SHIFT: u32 = 30;
+
+    pub fn is_looping(&self) -> bool {
+        self.flags & Self::FLAG_LOOPING != 0
+    }
+
+    pub fn is_music(&self) -> bool {
+        self.flags & Self::FLAG_MUSIC != 0
+    }
+
+    /// `0` for 2D aliases and `1` for 3D ones.
+    pub fn spatial_type(&self) -> u32 {
+        (self.flags & Self::FLAG_3D) >> 1
+    }
+
+    /// How the variants of the alias are picked between, from `0` to `3`.
+    pub fn randomize_type(&self) -> u32 {
+        self.flags >> Self::RANDOMIZE_TYPE_SHIFT
+    }
+
+    /// The name of the loaded or streamed sound the alias plays, if any.
+    pub fn file_name(&self) -> Option<&str> {
+        match &self.sound_file.as_ref()?.u {
+            SoundFileRef::Loaded(l) => l.as_ref().map(|l| l.name.get()),
+            SoundFileRef::Streamed(s) => s.as_ref().map(|s| s.filename.get()),
+        }
+    }
+
+    /// The alias's volume `dist` units away, from `0.0` to `1.0`, or
+    /// [`None`] if its `dry_curve` is neither one of the builtin curves nor
+    /// in [`SndDriverGlobals::curves`].
+    ///
+    /// `dist` is mapped to `0.0` at `dist_min` and closer and `1.0` at
+    /// `dist_max` and farther, and the curve's value there scales `vol_max`.
+    pub fn volume_at_distance(&self, dist: f32, globals: &SndDriverGlobals) -> Option<f32> {
+        let (min, max) = (self.dist_min as f32, self.dist_max as f32);
+        let t = if max > min {
+            ((dist - min) / (max - min)).clamp(0.0, 1.0)
+        } else if dist <= min {
+            0.0
+        } else {
+            1.0
+        };
+
+        let falloff = match self.dry_curve {
+            SndCurve::LINEAR => 1.0 - t,
+            SndCurve::IDENTITY => 1.0,
+            i => globals.curves.get(i as usize)?.evaluate(t),
+        };
+
+        Some(self.vol_max as f32 / u16::MAX as f32 * falloff)
+    }
+}
+
 impl XFileSerialize<()> for SndAlias {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -379,6 +534,16 @@ pub struct SoundFile {
     pub exists: bool,
 }
 
+impl SoundFile {
+    /// Whether the loaded or streamed sound this refers to is non-null.
+    pub fn is_some(&self) -> bool {
+        match &self.u {
+            SoundFileRef::Loaded(s) => s.is_some(),
+            SoundFileRef::Streamed(s) => s.is_some(),
+        }
+    }
+}
+
 impl<'a> XFileDeserializeInto<SoundFile, ()> for SoundFileRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -810,7 +975,7 @@ pub struct SndRadverb {
 
 impl From<SndRadverbRaw> for SndRadverb {
     fn from(value: SndRadverbRaw) -> Self {
-        let name = XString(value.name.to_string());
+        let name = XString::from(value.name.to_string());
         //dbg!(&name);
         Self {
             name,
@@ -897,9 +1062,9 @@ pub struct SndSnapshot {
 
 impl From<SndSnapshotRaw> for SndSnapshot {
     fn from(value: SndSnapshotRaw) -> Self {
-        let name = XString(value.name.to_string());
+        let name = XString::from(value.name.to_string());
         //dbg!(&name);
-        let occlusion_name = XString(value.occlusion_name.to_string());
+        let occlusion_name = XString::from(value.occlusion_name.to_string());
         //dbg!(&occlusion_name);
 
         Self {
@@ -950,6 +1115,15 @@ pub(crate) struct SndPatchRaw<'a> {
 }
 assert_size!(SndPatchRaw, 20);
 
+/// A patch to already-loaded sound data: a list of alias ids
+/// ([`SndAlias::id`]) and, by index, the files that replace theirs. The
+/// patch can only swap files. It doesn't carry any other alias properties,
+/// and it can't add aliases that aren't already in a bank.
+///
+/// Note that T5's `SndPatch` carries no reverb or occlusion settings of its
+/// own. Those live in [`SndBank::radverbs`] ([`SndRadverb`]) and
+/// [`SndBank::snapshots`] ([`SndSnapshot::occlusion_name`] etc.), and
+/// per-alias settings in [`SndAlias`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
 pub struct SndPatch {
@@ -958,6 +1132,111 @@ pub struct SndPatch {
     pub files: Vec<SoundFile>,
 }
 
+/// One entry of a [`SndPatch`]. See [`SndPatch::overrides`].
+#[derive(Copy, Clone, Debug)]
+pub struct AliasOverride<'a> {
+    pub alias_id: u32,
+    /// [`None`] if the patch has fewer files than elements.
+    pub file: Option<&'a SoundFile>,
+}
+
+/// Returned by [`SndPatch::apply_to`].
+#[derive(Clone, Default, Debug)]
+pub struct ApplyReport {
+    /// Ids of the aliases whose files were replaced.
+    pub patched: Vec<u32>,
+    /// Ids that didn't match any alias in the bank.
+    pub missing: Vec<u32>,
+    /// Ids without a corresponding file in the patch. These are left alone.
+    pub without_file: Vec<u32>,
+}
+
+impl SndPatch {
+    pub fn overrides(&self) -> Vec<AliasOverride<'_>> {
+        self.elements
+            .iter()
+            .enumerate()
+            .map(|(i, &alias_id)| AliasOverride {
+                alias_id,
+                file: self.files.get(i),
+            })
+            .collect()
+    }
+
+    /// Replaces the file of every alias in `bank` whose id is patched.
+    pub fn apply_to(&self, bank: &mut SndBank) -> ApplyReport {
+        let mut report = ApplyReport::default();
+
+        for o in self.overrides() {
+            let Some(file) = o.file else {
+                report.without_file.push(o.alias_id);
+                continue;
+            };
+
+            let mut found = false;
+            for alias in bank
+                .aliases
+                .iter_mut()
+                .flat_map(|l| l.aliases.iter_mut())
+                .filter(|a| a.id == o.alias_id)
+            {
+                alias.sound_file = Some(Box::new(file.clone()));
+                found = true;
+            }
+
+            if found {
+                report.patched.push(o.alias_id);
+            } else {
+                report.missing.push(o.alias_id);
+            }
+        }
+
+        report
+    }
+
+    /// Replaces `alias`'s file if the patch has one for its id, returning
+    /// whether it did. As with [`Self::apply_to`], if the id is patched more
+    /// than once, the last file wins.
+    pub fn apply_to_alias(&self, alias: &mut SndAlias) -> bool {
+        let Some(file) = self
+            .overrides()
+            .into_iter()
+            .rev()
+            .filter(|o| o.alias_id == alias.id)
+            .find_map(|o| o.file)
+        else {
+            return false;
+        };
+
+        alias.sound_file = Some(Box::new(file.clone()));
+        true
+    }
+
+    /// Combines `self` and `other` into one patch with `self`'s name, as if
+    /// `other` were applied after `self`: ids patched by both take `other`'s
+    /// file. Ids without a file patch nothing, so they're dropped.
+    pub fn merge(&self, other: &SndPatch) -> SndPatch {
+        let theirs = other.overrides();
+        let (elements, files) = self
+            .overrides()
+            .into_iter()
+            .filter(|o| {
+                !theirs
+                    .iter()
+                    .any(|t| t.alias_id == o.alias_id && t.file.is_some())
+            })
+            .chain(theirs.iter().copied())
+            .filter_map(|o| Some((o.alias_id, o.file?.clone())))
+            .unzip();
+
+        SndPatch {
+            name: self.name.clone(),
+            elements,
+            files,
+        }
+    }
+}
+
 impl<'a> XFileDeserializeInto<SndPatch, ()> for SndPatchRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -1010,7 +1289,7 @@ pub(crate) struct SndDriverGlobalsRaw<'a> {
 assert_size!(SndDriverGlobalsRaw, 52);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct SndDriverGlobals {
     pub name: XString,
     pub groups: Vec<SndGroup>,
@@ -1124,9 +1403,9 @@ pub struct SndGroup {
 impl TryInto<SndGroup> for SndGroupRaw {
     type Error = Error;
     fn try_into(self) -> core::result::Result<SndGroup, Self::Error> {
-        let name = XString(self.name.to_string());
+        let name = XString::from(self.name.to_string());
         //dbg!(&name);
-        let parent_name = XString(self.parent_name.to_string());
+        let parent_name = XString::from(self.parent_name.to_string());
         //dbg!(&parent_name);
         let category = FromPrimitive::from_u32(self.category).ok_or(Error::new_with_offset(
             file_line_col!(),
@@ -1184,7 +1463,7 @@ pub struct SndCurve {
 
 impl From<SndCurveRaw> for SndCurve {
     fn from(value: SndCurveRaw) -> Self {
-        let name = XString(value.name.to_string());
+        let name = XString::from(value.name.to_string());
         let points = value.points.map(|p| Vec2::from(p));
         //dbg!(&name);
 
@@ -1196,6 +1475,46 @@ impl From<SndCurveRaw> for SndCurve {
     }
 }
 
+impl SndCurve {
+    /// The curve index the engine treats as a straight line from full volume
+    /// at `dist_min` to silence at `dist_max`, without looking it up in
+    /// [`SndDriverGlobals::curves`].
+    pub const LINEAR: u8 = 0;
+    /// The curve index the engine treats as no falloff at all, also without
+    /// looking it up.
+    pub const IDENTITY: u8 = 1;
+
+    /// The curve's value at `t`, linearly interpolated between
+    /// [`Self::points`]. Before the first point or past the last, it's the
+    /// nearest point's value.
+    ///
+    /// Curves with fewer than 8 points are padded out, so points from the
+    /// first one that goes back along the x axis on are ignored.
+    pub fn evaluate(&self, t: f32) -> f32 {
+        let points = self.points.map(|p| p.get());
+        let used = points
+            .windows(2)
+            .position(|w| w[1][0] < w[0][0])
+            .map_or(points.len(), |i| i + 1);
+        let points = &points[..used];
+
+        let [x0, y0] = points[0];
+        if t <= x0 {
+            return y0;
+        }
+
+        for w in points.windows(2) {
+            let ([xa, ya], [xb, yb]) = (w[0], w[1]);
+            // t > xa, so xb > xa too.
+            if t <= xb {
+                return ya + (yb - ya) * (t - xa) / (xb - xa);
+            }
+        }
+
+        points[used - 1][1]
+    }
+}
+
 impl XFileSerialize<()> for SndCurve {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = SndName::from_str(self.name.get())?;
@@ -1240,7 +1559,7 @@ pub struct SndPan {
 
 impl From<SndPanRaw> for SndPan {
     fn from(value: SndPanRaw) -> Self {
-        let name = XString(value.name.to_string());
+        let name = XString::from(value.name.to_string());
         //dbg!(&name);
 
         Self {
@@ -1290,7 +1609,7 @@ pub struct SndSnapshotGroup {
 
 impl From<SndSnapshotGroupRaw> for SndSnapshotGroup {
     fn from(value: SndSnapshotGroupRaw) -> Self {
-        let name = XString(value.name.to_string());
+        let name = XString::from(value.name.to_string());
         //dbg!(&name);
         Self { name }
     }
@@ -1408,7 +1727,7 @@ pub struct SndMaster {
 
 impl From<SndMasterRaw> for SndMaster {
     fn from(value: SndMasterRaw) -> Self {
-        let name = XString(value.name.to_string());
+        let name = XString::from(value.name.to_string());
         //dbg!(&name);
 
         SndMaster {
@@ -1,21 +1,31 @@
 use core::mem::transmute;
 
-use alloc::{boxed::Box, format, vec::Vec};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
 use crate::{
-    Error, ErrorKind, FatPointer, FatPointerCountLastU32, FlexibleArray, FlexibleArrayU16,
-    FlexibleArrayU32, Ptr32, Result, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto,
-    XFileSerialize, XString, XStringRaw, assert_size,
+    Error, ErrorKind, FatPointer, FlexibleArray, FlexibleArrayU16, FlexibleArrayU32, Result,
+    T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString,
+    assert_size,
     common::{GfxCubeTexture, GfxPixelShader, GfxVertexShader, GfxVolumeTexture, Vec2, Vec4},
     file_line_col,
+    gpu::GpuHandle,
+    util::{FatPointerCountLastU32, Ptr32, XStringRaw},
 };
 
+use bitflags::bitflags;
 use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
 
+mod dxt;
+
 const MAX_TECHNIQUES: usize = 130;
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -49,7 +59,9 @@ pub struct MaterialTechniqueSet {
     pub name: XString,
     pub world_vert_format: u8,
     pub techset_flags: u16,
-    pub techniques: Vec<Box<MaterialTechnique>>,
+    /// One entry per technique slot, `None` where the slot is unused. See
+    /// [`TechniqueType`] for what each slot is for.
+    pub techniques: Vec<Option<Box<MaterialTechnique>>>,
 }
 
 impl<'a> XFileDeserializeInto<MaterialTechniqueSet, ()> for MaterialTechniqueSetRaw<'a> {
@@ -66,9 +78,8 @@ impl<'a> XFileDeserializeInto<MaterialTechniqueSet, ()> for MaterialTechniqueSet
         let techniques = self.techniques;
         let techniques = techniques
             .iter()
-            .flat_map(|p| p.xfile_deserialize_into(de, ()))
-            .flatten()
-            .collect::<Vec<_>>();
+            .map(|p| p.xfile_deserialize_into(de, ()))
+            .collect::<Result<Vec<_>>>()?;
 
         //dbg!(techniques);
 
@@ -81,6 +92,282 @@ impl<'a> XFileDeserializeInto<MaterialTechniqueSet, ()> for MaterialTechniqueSet
     }
 }
 
+impl XFileSerialize<()> for MaterialTechniqueSet {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        if self.techniques.len() > MAX_TECHNIQUES {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "MaterialTechniqueSet: {} techniques > {MAX_TECHNIQUES}",
+                    self.techniques.len()
+                )),
+            ));
+        }
+
+        let mut techniques = [Ptr32::null(); MAX_TECHNIQUES];
+        for (p, technique) in techniques.iter_mut().zip(&self.techniques) {
+            *p = Ptr32::from_box(technique);
+        }
+
+        let set = MaterialTechniqueSetRaw {
+            name: XStringRaw::from_str(self.name.get()),
+            world_vert_format: self.world_vert_format,
+            unused: 0,
+            techset_flags: self.techset_flags,
+            techniques,
+        };
+
+        ser.store_into_xfile(set)?;
+        self.name.xfile_serialize(ser, ())?;
+        for technique in self.techniques.iter().flatten() {
+            technique.xfile_serialize(ser, ())?;
+        }
+        Ok(())
+    }
+}
+
+/// Names of the technique slots whose purpose is known, indexed by slot.
+const TECHNIQUE_SLOT_NAMES: [&str; 22] = [
+    "depth_prepass",
+    "build_float_z",
+    "build_shadowmap_depth",
+    "build_shadowmap_color",
+    "unlit",
+    "emissive",
+    "emissive_shadow",
+    "emissive_reflected",
+    "lit",
+    "lit_fade",
+    "lit_sun",
+    "lit_sun_fade",
+    "lit_sun_shadow",
+    "lit_sun_shadow_fade",
+    "lit_spot",
+    "lit_spot_fade",
+    "lit_spot_shadow",
+    "lit_spot_shadow_fade",
+    "lit_omni",
+    "lit_omni_fade",
+    "lit_omni_shadow",
+    "lit_omni_shadow_fade",
+];
+
+/// What a technique slot in [`MaterialTechniqueSet::techniques`] is used for,
+/// in the engine's order, so each variant's value is its slot.
+///
+/// Only the slots in [`MaterialTechniqueSet::slot_name`] have known names.
+/// The rest are named after their slot.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, FromPrimitive)]
+#[repr(u8)]
+pub enum TechniqueType {
+    DEPTH_PREPASS = 0x00,
+    BUILD_FLOAT_Z = 0x01,
+    BUILD_SHADOWMAP_DEPTH = 0x02,
+    BUILD_SHADOWMAP_COLOR = 0x03,
+    UNLIT = 0x04,
+    EMISSIVE = 0x05,
+    EMISSIVE_SHADOW = 0x06,
+    EMISSIVE_REFLECTED = 0x07,
+    LIT = 0x08,
+    LIT_FADE = 0x09,
+    LIT_SUN = 0x0A,
+    LIT_SUN_FADE = 0x0B,
+    LIT_SUN_SHADOW = 0x0C,
+    LIT_SUN_SHADOW_FADE = 0x0D,
+    LIT_SPOT = 0x0E,
+    LIT_SPOT_FADE = 0x0F,
+    LIT_SPOT_SHADOW = 0x10,
+    LIT_SPOT_SHADOW_FADE = 0x11,
+    LIT_OMNI = 0x12,
+    LIT_OMNI_FADE = 0x13,
+    LIT_OMNI_SHADOW = 0x14,
+    LIT_OMNI_SHADOW_FADE = 0x15,
+    UNKNOWN_16 = 0x16,
+    UNKNOWN_17 = 0x17,
+    UNKNOWN_18 = 0x18,
+    UNKNOWN_19 = 0x19,
+    UNKNOWN_1A = 0x1A,
+    UNKNOWN_1B = 0x1B,
+    UNKNOWN_1C = 0x1C,
+    UNKNOWN_1D = 0x1D,
+    UNKNOWN_1E = 0x1E,
+    UNKNOWN_1F = 0x1F,
+    UNKNOWN_20 = 0x20,
+    UNKNOWN_21 = 0x21,
+    UNKNOWN_22 = 0x22,
+    UNKNOWN_23 = 0x23,
+    UNKNOWN_24 = 0x24,
+    UNKNOWN_25 = 0x25,
+    UNKNOWN_26 = 0x26,
+    UNKNOWN_27 = 0x27,
+    UNKNOWN_28 = 0x28,
+    UNKNOWN_29 = 0x29,
+    UNKNOWN_2A = 0x2A,
+    UNKNOWN_2B = 0x2B,
+    UNKNOWN_2C = 0x2C,
+    UNKNOWN_2D = 0x2D,
+    UNKNOWN_2E = 0x2E,
+    UNKNOWN_2F = 0x2F,
+    UNKNOWN_30 = 0x30,
+    UNKNOWN_31 = 0x31,
+    UNKNOWN_32 = 0x32,
+    UNKNOWN_33 = 0x33,
+    UNKNOWN_34 = 0x34,
+    UNKNOWN_35 = 0x35,
+    UNKNOWN_36 = 0x36,
+    UNKNOWN_37 = 0x37,
+    UNKNOWN_38 = 0x38,
+    UNKNOWN_39 = 0x39,
+    UNKNOWN_3A = 0x3A,
+    UNKNOWN_3B = 0x3B,
+    UNKNOWN_3C = 0x3C,
+    UNKNOWN_3D = 0x3D,
+    UNKNOWN_3E = 0x3E,
+    UNKNOWN_3F = 0x3F,
+    UNKNOWN_40 = 0x40,
+    UNKNOWN_41 = 0x41,
+    UNKNOWN_42 = 0x42,
+    UNKNOWN_43 = 0x43,
+    UNKNOWN_44 = 0x44,
+    UNKNOWN_45 = 0x45,
+    UNKNOWN_46 = 0x46,
+    UNKNOWN_47 = 0x47,
+    UNKNOWN_48 = 0x48,
+    UNKNOWN_49 = 0x49,
+    UNKNOWN_4A = 0x4A,
+    UNKNOWN_4B = 0x4B,
+    UNKNOWN_4C = 0x4C,
+    UNKNOWN_4D = 0x4D,
+    UNKNOWN_4E = 0x4E,
+    UNKNOWN_4F = 0x4F,
+    UNKNOWN_50 = 0x50,
+    UNKNOWN_51 = 0x51,
+    UNKNOWN_52 = 0x52,
+    UNKNOWN_53 = 0x53,
+    UNKNOWN_54 = 0x54,
+    UNKNOWN_55 = 0x55,
+    UNKNOWN_56 = 0x56,
+    UNKNOWN_57 = 0x57,
+    UNKNOWN_58 = 0x58,
+    UNKNOWN_59 = 0x59,
+    UNKNOWN_5A = 0x5A,
+    UNKNOWN_5B = 0x5B,
+    UNKNOWN_5C = 0x5C,
+    UNKNOWN_5D = 0x5D,
+    UNKNOWN_5E = 0x5E,
+    UNKNOWN_5F = 0x5F,
+    UNKNOWN_60 = 0x60,
+    UNKNOWN_61 = 0x61,
+    UNKNOWN_62 = 0x62,
+    UNKNOWN_63 = 0x63,
+    UNKNOWN_64 = 0x64,
+    UNKNOWN_65 = 0x65,
+    UNKNOWN_66 = 0x66,
+    UNKNOWN_67 = 0x67,
+    UNKNOWN_68 = 0x68,
+    UNKNOWN_69 = 0x69,
+    UNKNOWN_6A = 0x6A,
+    UNKNOWN_6B = 0x6B,
+    UNKNOWN_6C = 0x6C,
+    UNKNOWN_6D = 0x6D,
+    UNKNOWN_6E = 0x6E,
+    UNKNOWN_6F = 0x6F,
+    UNKNOWN_70 = 0x70,
+    UNKNOWN_71 = 0x71,
+    UNKNOWN_72 = 0x72,
+    UNKNOWN_73 = 0x73,
+    UNKNOWN_74 = 0x74,
+    UNKNOWN_75 = 0x75,
+    UNKNOWN_76 = 0x76,
+    UNKNOWN_77 = 0x77,
+    UNKNOWN_78 = 0x78,
+    UNKNOWN_79 = 0x79,
+    UNKNOWN_7A = 0x7A,
+    UNKNOWN_7B = 0x7B,
+    UNKNOWN_7C = 0x7C,
+    UNKNOWN_7D = 0x7D,
+    UNKNOWN_7E = 0x7E,
+    UNKNOWN_7F = 0x7F,
+    UNKNOWN_80 = 0x80,
+    UNKNOWN_81 = 0x81,
+}
+
+impl TechniqueType {
+    /// The samplers a material needs bound for a technique of this type to
+    /// draw properly. Lit techniques need a color and normal map, unlit and
+    /// emissive ones just a color map, and the depth and shadow map passes
+    /// don't sample the material at all.
+    pub fn required_samplers(self) -> &'static [&'static str] {
+        use TechniqueType as T;
+
+        match self {
+            T::UNLIT | T::EMISSIVE | T::EMISSIVE_SHADOW | T::EMISSIVE_REFLECTED => &["colorMap"],
+            _ if (T::LIT..=T::LIT_OMNI_SHADOW_FADE).contains(&self) => &["colorMap", "normalMap"],
+            _ => &[],
+        }
+    }
+
+    /// The type of slot `slot`, or [`None`] if it's past the last slot.
+    pub fn from_slot(slot: usize) -> Option<Self> {
+        num::FromPrimitive::from_usize(slot)
+    }
+
+    /// The slot in [`MaterialTechniqueSet::techniques`] this type is stored
+    /// in.
+    pub fn slot(self) -> usize {
+        self as usize
+    }
+
+    /// Every type, in slot order.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..MAX_TECHNIQUES).filter_map(Self::from_slot)
+    }
+
+    /// See [`MaterialTechniqueSet::slot_name`].
+    pub fn name(self) -> &'static str {
+        MaterialTechniqueSet::slot_name(self.slot())
+    }
+}
+
+impl MaterialTechniqueSet {
+    /// The number of slots that actually have a technique.
+    pub fn technique_count(&self) -> usize {
+        self.techniques.iter().filter(|t| t.is_some()).count()
+    }
+
+    /// The technique in slot `slot`, if there is one.
+    pub fn technique_at(&self, slot: usize) -> Option<&MaterialTechnique> {
+        self.techniques.get(slot)?.as_deref()
+    }
+
+    /// What slot `slot` is used for, e.g. `"lit_sun"` or
+    /// `"build_shadowmap_depth"`. Only the first few slots have known names;
+    /// the rest (and anything past the last slot) are `"unknown"`.
+    pub fn slot_name(slot: usize) -> &'static str {
+        TECHNIQUE_SLOT_NAMES.get(slot).copied().unwrap_or("unknown")
+    }
+
+    /// The technique used for `ty`, if there is one.
+    pub fn technique(&self, ty: TechniqueType) -> Option<&MaterialTechnique> {
+        self.technique_at(ty.slot())
+    }
+
+    /// Every technique along with what it's used for, in slot order. Empty
+    /// slots are skipped.
+    pub fn techniques_by_type(&self) -> impl Iterator<Item = (TechniqueType, &MaterialTechnique)> {
+        self.techniques
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, t)| Some((TechniqueType::from_slot(slot)?, t.as_deref()?)))
+    }
+
+    /// The types that have a technique, in slot order.
+    pub fn techniques_used(&self) -> Vec<TechniqueType> {
+        self.techniques_by_type().map(|(ty, _)| ty).collect()
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct MaterialTechniqueRaw<'a> {
@@ -98,6 +385,40 @@ pub struct MaterialTechnique {
     pub passes: Vec<MaterialPass>,
 }
 
+impl MaterialTechnique {
+    pub fn pass_count(&self) -> usize {
+        self.passes.len()
+    }
+
+    /// The names of the vertex and pixel shaders used by the technique's
+    /// first pass. Missing shaders (or passes) are empty strings.
+    pub fn shader_names(&self) -> (&str, &str) {
+        self.passes
+            .first()
+            .map(MaterialPass::shader_names)
+            .unwrap_or_default()
+    }
+
+    /// Every pass's shader arguments, decoded, in pass order. If `material`
+    /// is given, material constant names and texture indices are resolved
+    /// against it (techniques are shared between materials, so they can't
+    /// be resolved without one).
+    pub fn arguments(&self, material: Option<&Material>) -> Vec<DecodedShaderArgument> {
+        self.passes
+            .iter()
+            .enumerate()
+            .flat_map(|(pass, p)| {
+                p.args.iter().map(move |arg| DecodedShaderArgument {
+                    pass,
+                    dest: arg.dest,
+                    value: arg.decode(material),
+                    raw: *arg,
+                })
+            })
+            .collect()
+    }
+}
+
 impl<'a> XFileDeserializeInto<MaterialTechnique, ()> for MaterialTechniqueRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -126,6 +447,30 @@ impl<'a> XFileDeserializeInto<MaterialTechnique, ()> for MaterialTechniqueRaw<'a
     }
 }
 
+impl XFileSerialize<()> for MaterialTechnique {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let technique = MaterialTechniqueRaw {
+            name: XStringRaw::from_str(self.name.get()),
+            flags: self.flags,
+            passes: FlexibleArrayU16::new(self.passes.len()),
+        };
+
+        // Like when deserializing, the passes come right after the
+        // technique, and everything they point to after all of them.
+        ser.store_into_xfile(technique)?;
+        ser.begin_array();
+        for pass in &self.passes {
+            ser.store_into_xfile(pass.to_raw())?;
+        }
+        for pass in &self.passes {
+            pass.xfile_serialize(ser, ())?;
+        }
+        ser.end_array();
+
+        self.name.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct MaterialPassRaw<'a> {
@@ -153,6 +498,27 @@ pub struct MaterialPass {
     pub args: Vec<MaterialShaderArgument>,
 }
 
+impl MaterialPass {
+    /// The stream routings that make up the pass's vertex declaration, i.e.
+    /// which vertex stream feeds each vertex shader input. Empty if the pass
+    /// has no vertex declaration.
+    pub fn vertex_decl(&self) -> &[MaterialStreamRouting] {
+        self.vertex_decl.as_deref().map_or(&[], |d| {
+            let data = &d.routing.data;
+            &data[..(d.stream_count as usize).min(data.len())]
+        })
+    }
+
+    /// The names of the pass's vertex and pixel shaders. Missing shaders are
+    /// empty strings.
+    pub fn shader_names(&self) -> (&str, &str) {
+        (
+            self.vertex_shader.as_ref().map_or("", |s| s.name.get()),
+            self.pixel_shader.as_ref().map_or("", |s| s.name.get()),
+        )
+    }
+}
+
 impl<'a> XFileDeserializeInto<MaterialPass, ()> for MaterialPassRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -205,6 +571,47 @@ impl<'a> XFileDeserializeInto<MaterialPass, ()> for MaterialPassRaw<'a> {
     }
 }
 
+impl MaterialPass {
+    fn to_raw<'a>(&self) -> MaterialPassRaw<'a> {
+        MaterialPassRaw {
+            vertex_decl: Ptr32::from_box(&self.vertex_decl),
+            vertex_shader: Ptr32::from_box(&self.vertex_shader),
+            pixel_shader: Ptr32::from_box(&self.pixel_shader),
+            per_prim_arg_count: self.per_prim_arg_count,
+            per_obj_arg_count: self.per_obj_arg_count,
+            stable_arg_count: self.stable_arg_count,
+            custom_sampler_flags: self.custom_sampler_flags,
+            args: Ptr32::<MaterialShaderArgument>::from_slice::<()>(&self.args).as_u32(),
+        }
+    }
+}
+
+/// Serializes what [`MaterialPass::to_raw`] points to. The raw pass itself
+/// is stored by [`MaterialTechnique`].
+impl XFileSerialize<()> for MaterialPass {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let argc = self.per_prim_arg_count as usize
+            + self.per_obj_arg_count as usize
+            + self.stable_arg_count as usize;
+        if !self.args.is_empty() && self.args.len() != argc {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "MaterialPass: {} args, but the counts add up to {argc}",
+                    self.args.len()
+                )),
+            ));
+        }
+
+        if let Some(decl) = &self.vertex_decl {
+            ser.store_into_xfile(**decl)?;
+        }
+        self.vertex_shader.xfile_serialize(ser, ())?;
+        self.pixel_shader.xfile_serialize(ser, ())?;
+        self.args.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub struct MaterialVertexDeclaration {
@@ -268,6 +675,25 @@ impl<'a> XFileDeserializeInto<MaterialVertexShader, ()> for MaterialVertexShader
     }
 }
 
+impl XFileSerialize<()> for MaterialVertexShader {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let program = &self.prog.load_def.program;
+        let shader = MaterialVertexShaderRaw {
+            name: XStringRaw::from_str(self.name.get()),
+            prog: MaterialVertexShaderProgramRaw {
+                vs: Ptr32::null(),
+                load_def: GfxVertexShaderLoadDefRaw {
+                    program: FatPointerCountLastU32::from_slice(program),
+                },
+            },
+        };
+
+        ser.store_into_xfile(shader)?;
+        self.name.xfile_serialize(ser, ())?;
+        program.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct MaterialVertexShaderProgramRaw<'a> {
@@ -288,7 +714,7 @@ impl<'a> XFileDeserializeInto<MaterialVertexShaderProgram, ()>
     for MaterialVertexShaderProgramRaw<'a>
 {
     #[cfg(feature = "d3d9")]
-    fn xfile_into(
+    fn xfile_deserialize_into(
         &self,
         de: &mut impl T5XFileDeserialize,
         _data: (),
@@ -297,13 +723,9 @@ impl<'a> XFileDeserializeInto<MaterialVertexShaderProgram, ()>
 
         let load_def = self.load_def.xfile_deserialize_into(de, ())?;
 
-        let vs = if de.create_d3d9() {
-            let vs = unsafe {
-                de.d3d9_state()
-                    .unwrap()
-                    .device
-                    .CreateVertexShader(load_def.program.as_ptr())
-            }?;
+        let vs = if let Some(device) = de.d3d9_device() {
+            let vs = unsafe { device.CreateVertexShader(load_def.program.as_ptr()) }
+                .map_err(|e| Error::new(file_line_col!(), e.into()))?;
             Some(Box::new(GfxVertexShader(vs)))
         } else {
             None
@@ -394,6 +816,25 @@ impl<'a> XFileDeserializeInto<MaterialPixelShader, ()> for MaterialPixelShaderRa
     }
 }
 
+impl XFileSerialize<()> for MaterialPixelShader {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let program = &self.prog.load_def.program;
+        let shader = MaterialPixelShaderRaw {
+            name: XStringRaw::from_str(self.name.get()),
+            prog: MaterialPixelShaderProgramRaw {
+                ps: Ptr32::null(),
+                load_def: GfxPixelShaderLoadDefRaw {
+                    program: FatPointerCountLastU32::from_slice(program),
+                },
+            },
+        };
+
+        ser.store_into_xfile(shader)?;
+        self.name.xfile_serialize(ser, ())?;
+        program.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct MaterialPixelShaderProgramRaw<'a> {
@@ -414,7 +855,7 @@ impl<'a> XFileDeserializeInto<MaterialPixelShaderProgram, ()>
     for MaterialPixelShaderProgramRaw<'a>
 {
     #[cfg(feature = "d3d9")]
-    fn xfile_into(
+    fn xfile_deserialize_into(
         &self,
         de: &mut impl T5XFileDeserialize,
         _data: (),
@@ -423,13 +864,9 @@ impl<'a> XFileDeserializeInto<MaterialPixelShaderProgram, ()>
 
         let load_def = self.load_def.xfile_deserialize_into(de, ())?;
 
-        let ps = if de.create_d3d9() {
-            let ps = unsafe {
-                de.d3d9_state()
-                    .unwrap()
-                    .device
-                    .CreatePixelShader(load_def.program.as_ptr())
-            }?;
+        let ps = if let Some(device) = de.d3d9_device() {
+            let ps = unsafe { device.CreatePixelShader(load_def.program.as_ptr()) }
+                .map_err(|e| Error::new(file_line_col!(), e.into()))?;
             Some(Box::new(GfxPixelShader(ps)))
         } else {
             None
@@ -483,7 +920,7 @@ impl<'a> XFileDeserializeInto<GfxPixelShaderLoadDef, ()> for GfxPixelShaderLoadD
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug)]
-pub enum MaterialArgumentDefRaw {
+pub(crate) enum MaterialArgumentDefRaw {
     LiteralConst([f32; 4]),
     CodeConst(MaterialArgumentCodeConst),
     CodeSampler(u32),
@@ -571,6 +1008,132 @@ impl XFileDeserializeInto<MaterialShaderArgument, ()> for MaterialShaderArgument
     }
 }
 
+impl XFileSerialize<()> for MaterialShaderArgument {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let literal = matches!(
+            self.arg_type,
+            MtlArg::LITERAL_VERTEX_CONST | MtlArg::LITERAL_PIXEL_CONST
+        );
+        let u = match self.u {
+            MaterialArgumentDef::LiteralConst(_) if literal => Ptr32::<()>::unreal().as_u32(),
+            MaterialArgumentDef::CodeConst(c) if !literal => c.to_u32(),
+            MaterialArgumentDef::CodeSampler(u) | MaterialArgumentDef::NameHash(u) if !literal => u,
+            _ => {
+                return Err(Error::new(
+                    file_line_col!(),
+                    ErrorKind::BrokenInvariant(format!(
+                        "MaterialShaderArgument: {:?} argument holds {:?}",
+                        self.arg_type, self.u
+                    )),
+                ));
+            }
+        };
+
+        ser.store_into_xfile(MaterialShaderArgumentRaw {
+            arg_type: self.arg_type as _,
+            dest: self.dest,
+            u,
+        })?;
+        // Literals are stored right after their argument.
+        if let MaterialArgumentDef::LiteralConst(v) = self.u {
+            ser.store_into_xfile(v.get())?;
+        }
+        Ok(())
+    }
+}
+
+impl MaterialShaderArgument {
+    /// Whether the argument is bound to the pixel shader (as opposed to the
+    /// vertex shader).
+    pub fn is_pixel(&self) -> bool {
+        matches!(
+            self.arg_type,
+            MtlArg::MATERIAL_PIXEL_SAMPLER
+                | MtlArg::CODE_PIXEL_SAMPLER
+                | MtlArg::CODE_PIXEL_CONST
+                | MtlArg::MATERIAL_PRIM_END
+                | MtlArg::LITERAL_PIXEL_CONST
+        )
+    }
+
+    /// Decodes what the argument binds. See [`MaterialTechnique::arguments`]
+    /// for `material`.
+    pub fn decode(&self, material: Option<&Material>) -> ShaderArgumentValue {
+        match self.u {
+            MaterialArgumentDef::LiteralConst(v) => ShaderArgumentValue::LiteralConst(v.get()),
+            MaterialArgumentDef::CodeConst(c) => ShaderArgumentValue::CodeConst {
+                index: c.index,
+                first_row: c.first_row,
+                row_count: c.row_count,
+            },
+            MaterialArgumentDef::CodeSampler(s) => {
+                ShaderArgumentValue::Sampler(ShaderArgumentSampler::Code(s))
+            }
+            MaterialArgumentDef::NameHash(name_hash) => {
+                if let MtlArg::MATERIAL_PIXEL_SAMPLER = self.arg_type {
+                    let texture_index = material
+                        .and_then(|m| m.textures.iter().position(|t| t.name_hash == name_hash));
+                    ShaderArgumentValue::Sampler(ShaderArgumentSampler::Material {
+                        name_hash,
+                        texture_index,
+                    })
+                } else {
+                    let name = material
+                        .and_then(|m| m.constants.iter().find(|c| c.name_hash == name_hash))
+                        .map(MaterialConstantDef::name);
+                    ShaderArgumentValue::MaterialConst { name_hash, name }
+                }
+            }
+        }
+    }
+}
+
+/// What a [`MaterialShaderArgument`] binds to its register.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShaderArgumentValue {
+    LiteralConst([f32; 4]),
+    /// Rows of a constant the engine fills in (e.g., a transform matrix).
+    CodeConst {
+        index: u16,
+        first_row: u8,
+        row_count: u8,
+    },
+    /// One of the material's constants. `name` is [`None`] if it couldn't be
+    /// resolved.
+    MaterialConst {
+        name_hash: u32,
+        name: Option<String>,
+    },
+    Sampler(ShaderArgumentSampler),
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShaderArgumentSampler {
+    /// A sampler the engine fills in, by index.
+    Code(u32),
+    /// One of the material's textures. `texture_index` is its index in
+    /// [`Material::textures`], or [`None`] if it couldn't be resolved.
+    Material {
+        name_hash: u32,
+        texture_index: Option<usize>,
+    },
+}
+
+/// A shader argument, as returned by [`MaterialTechnique::arguments`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct DecodedShaderArgument {
+    /// Index of the pass in [`MaterialTechnique::passes`].
+    pub pass: usize,
+    /// The register the argument is bound to.
+    pub dest: u16,
+    pub value: ShaderArgumentValue,
+    /// The argument as stored, for re-serializing.
+    pub raw: MaterialShaderArgument,
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub struct MaterialArgumentCodeConst {
@@ -584,6 +1147,10 @@ impl MaterialArgumentCodeConst {
     pub fn from_u32(u: u32) -> Self {
         unsafe { transmute(u) }
     }
+
+    pub fn to_u32(self) -> u32 {
+        unsafe { transmute(self) }
+    }
 }
 
 const MTL_ARG_MATERIAL_VERTEX_CONST: u16 = 0;
@@ -718,11 +1285,573 @@ impl<'a> XFileDeserializeInto<Material, ()> for MaterialRaw<'a> {
 }
 
 impl XFileSerialize<()> for Material {
-    fn xfile_serialize(&self, _ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        todo!()
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let material = MaterialRaw {
+            info: self.info.to_raw(),
+            state_bits_entry: self.state_bits_entry,
+            texture_count: self.textures.len() as _,
+            constant_count: self.constants.len() as _,
+            state_bits_count: self.state_bits.len() as _,
+            state_flags: self.state_flags,
+            camera_region: self.camera_region,
+            max_streamed_mips: self.max_streamed_mips,
+            technique_set: Ptr32::from_box(&self.technique_set),
+            texture_table: Ptr32::from_slice(&self.textures),
+            constant_table: Ptr32::from_slice(&self.constants),
+            state_bits_table: Ptr32::from_slice(&self.state_bits),
+        };
+
+        ser.store_into_xfile(material)?;
+        self.info.name.xfile_serialize(ser, ())?;
+        self.technique_set.xfile_serialize(ser, ())?;
+
+        // The texture table is stored in full before any of the images or
+        // water it points to.
+        ser.begin_array();
+        for texture in &self.textures {
+            ser.store_into_xfile(texture.to_raw())?;
+        }
+        for texture in &self.textures {
+            texture.u.xfile_serialize(ser, ())?;
+        }
+        ser.end_array();
+
+        self.constants.xfile_serialize(ser, ())?;
+        self.state_bits.xfile_serialize(ser, ())
     }
 }
 
+impl Material {
+    pub fn sort_key(&self) -> MaterialSortKey {
+        self.info.sort_key.into()
+    }
+
+    pub fn region(&self) -> CameraRegion {
+        self.camera_region.into()
+    }
+
+    /// Which broad group of draws the material belongs to. The camera region
+    /// decides which draw list it ends up in; within the opaque list, the
+    /// sort key separates sky materials from everything else.
+    pub fn sort_class(&self) -> MaterialSortClass {
+        match self.region() {
+            CameraRegion::LitOpaque => match self.sort_key() {
+                MaterialSortKey::Sky | MaterialSortKey::Skybox => MaterialSortClass::Sky,
+                _ => MaterialSortClass::Opaque,
+            },
+            CameraRegion::LitTrans => MaterialSortClass::Transparent,
+            CameraRegion::Emissive => MaterialSortClass::Emissive,
+            CameraRegion::DepthHack => MaterialSortClass::DepthHack,
+            CameraRegion::None | CameraRegion::Other(_) => MaterialSortClass::Unsorted,
+        }
+    }
+
+    /// The technique [`Self::technique_set`] uses for `ty`, if it has a
+    /// technique set and the set has one.
+    pub fn technique_for(&self, ty: TechniqueType) -> Option<&MaterialTechnique> {
+        self.technique_set.as_ref()?.technique(ty)
+    }
+
+    /// Which shading permutation the material needs. See
+    /// [`MaterialPermutationKey`] for how each part is worked out.
+    pub fn permutation_key(&self) -> MaterialPermutationKey {
+        let has = |ty| self.technique_for(ty).is_some();
+
+        let lit = match &self.technique_set {
+            Some(set) => set
+                .techniques_used()
+                .iter()
+                .any(|ty| (TechniqueType::LIT..=TechniqueType::LIT_OMNI_SHADOW_FADE).contains(ty)),
+            None => matches!(
+                self.region(),
+                CameraRegion::LitOpaque | CameraRegion::LitTrans | CameraRegion::DepthHack
+            ),
+        };
+        let lit_type = match (lit, self.region()) {
+            (false, _) => LitType::Unlit,
+            (true, CameraRegion::LitTrans) => LitType::TransparentLit,
+            (true, _) => LitType::OpaqueLit,
+        };
+
+        let shadow_type = if lit_type == LitType::Unlit {
+            ShadowType::None
+        } else if has(TechniqueType::LIT_SUN_SHADOW) || has(TechniqueType::LIT_SUN_SHADOW_FADE) {
+            ShadowType::Depth
+        } else if [
+            TechniqueType::LIT_SPOT_SHADOW,
+            TechniqueType::LIT_SPOT_SHADOW_FADE,
+            TechniqueType::LIT_OMNI_SHADOW,
+            TechniqueType::LIT_OMNI_SHADOW_FADE,
+        ]
+        .into_iter()
+        .any(has)
+        {
+            ShadowType::Pcf
+        } else {
+            ShadowType::None
+        };
+
+        let specular_type = if lit_type != LitType::Unlit
+            && self
+                .textures
+                .iter()
+                .any(|t| t.semantic == Semantic::SPECULAR_MAP)
+        {
+            SpecularType::Blinn
+        } else {
+            SpecularType::None
+        };
+
+        MaterialPermutationKey {
+            lit_type,
+            fog_type: FogType::None,
+            shadow_type,
+            specular_type,
+        }
+    }
+
+    /// Whether [`Self::technique_set`] has a technique in `key`'s slot.
+    pub fn has_technique_for(&self, key: MaterialPermutationKey) -> bool {
+        self.technique_set
+            .as_ref()
+            .is_some_and(|set| set.technique_at(key.technique_set_slot_index()).is_some())
+    }
+
+    /// Every texture in [`Self::textures`], along with the name of the
+    /// sampler it's bound to and the register it's bound at.
+    ///
+    /// Textures only store a hash of their sampler's name, so it's looked up
+    /// in [`KNOWN_SAMPLER_NAMES`], falling back to the usual name for the
+    /// texture's [`Semantic`] and then to `"unknown"`. The slot is the `dest`
+    /// of the first pixel sampler argument that binds the texture, searching
+    /// the technique set in slot order, or the texture's index if no
+    /// argument binds it (or there's no technique set).
+    pub fn sampler_bindings(&self) -> Vec<SamplerBinding<'_>> {
+        self.textures
+            .iter()
+            .enumerate()
+            .map(|(i, t)| SamplerBinding {
+                sampler_name: t.sampler_name(),
+                sampler_slot: self.sampler_slot(t.name_hash).unwrap_or(i as _),
+                image: t.image(),
+            })
+            .collect()
+    }
+
+    /// The image bound to the sampler called `name`, if the material has one.
+    pub fn image_by_sampler_name(&self, name: &str) -> Option<&GfxImage> {
+        let name_hash = MaterialTextureDef::hash_name(name);
+        self.textures
+            .iter()
+            .find(|t| t.name_hash == name_hash)
+            .and_then(MaterialTextureDef::image)
+    }
+
+    /// The samplers the material's techniques need (see
+    /// [`TechniqueType::required_samplers`]) but that don't have an image
+    /// bound, in the order they're first needed.
+    pub fn missing_required_samplers(&self) -> Vec<&'static str> {
+        let Some(set) = &self.technique_set else {
+            return Vec::new();
+        };
+
+        let mut missing = Vec::new();
+        for name in set
+            .techniques_used()
+            .into_iter()
+            .flat_map(TechniqueType::required_samplers)
+        {
+            if !missing.contains(name) && self.image_by_sampler_name(name).is_none() {
+                missing.push(*name);
+            }
+        }
+        missing
+    }
+
+    /// The register of the first pixel sampler argument in
+    /// [`Self::technique_set`] that binds the texture hashed `name_hash`.
+    fn sampler_slot(&self, name_hash: u32) -> Option<u8> {
+        self.technique_set
+            .as_ref()?
+            .techniques_by_type()
+            .flat_map(|(_, t)| &t.passes)
+            .flat_map(|p| &p.args)
+            .find(|a| {
+                matches!(a.arg_type, MtlArg::MATERIAL_PIXEL_SAMPLER)
+                    && matches!(a.u, MaterialArgumentDef::NameHash(h) if h == name_hash)
+            })
+            .map(|a| a.dest as _)
+    }
+}
+
+/// Sampler names used by the stock shaders, for resolving
+/// [`MaterialTextureDef::name_hash`].
+pub const KNOWN_SAMPLER_NAMES: [&str; 11] = [
+    "colorMap",
+    "colorMap1",
+    "colorMap2",
+    "normalMap",
+    "detailMap",
+    "detailNormalMap",
+    "specularMap",
+    "cosinePowerMap",
+    "alphaRevealMap",
+    "waterMap",
+    "heatMap",
+];
+
+/// A texture's binding, as returned by [`Material::sampler_bindings`].
+#[derive(Copy, Clone, Debug)]
+pub struct SamplerBinding<'a> {
+    pub sampler_name: &'a str,
+    /// The sampler register the texture is bound at.
+    pub sampler_slot: u8,
+    /// [`None`] if the texture has no image.
+    pub image: Option<&'a GfxImage>,
+}
+
+/// The shading permutation a material needs, as returned by
+/// [`Material::permutation_key`].
+///
+/// T5 picks a technique by slot, and the slots only encode lighting (see
+/// [`TechniqueType`]), so only [`Self::lit_type`] and [`Self::shadow_type`]
+/// decide [`Self::technique_set_slot_index`]:
+///
+/// | `lit_type`         | `shadow_type` | Slot                   |
+/// |--------------------|---------------|------------------------|
+/// | `Unlit`            | any           | `unlit`                |
+/// | `OpaqueLit`        | `None`        | `lit_sun`              |
+/// | `OpaqueLit`        | `Depth`       | `lit_sun_shadow`       |
+/// | `OpaqueLit`        | `Pcf`         | `lit_spot_shadow`      |
+/// | `TransparentLit`   | `None`        | `lit_sun_fade`         |
+/// | `TransparentLit`   | `Depth`       | `lit_sun_shadow_fade`  |
+/// | `TransparentLit`   | `Pcf`         | `lit_spot_shadow_fade` |
+///
+/// Sun shadows are read from the shadow map as depth, and spot and omni
+/// shadows with PCF. Fog and specular are decided inside the shaders rather
+/// than by slot, so they're carried along for analysis but don't affect the
+/// slot.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialPermutationKey {
+    /// [`LitType::Unlit`] if the technique set has no lit techniques,
+    /// otherwise decided by [`Material::camera_region`].
+    pub lit_type: LitType,
+    /// Fog isn't recorded anywhere in a material, so
+    /// [`Material::permutation_key`] always sets [`FogType::None`].
+    pub fog_type: FogType,
+    /// Which shadowed lit techniques the technique set has. Sun shadows win
+    /// over spot and omni shadows.
+    pub shadow_type: ShadowType,
+    /// [`SpecularType::Blinn`] if a lit material has a specular map. T5 has
+    /// no GGX shaders, so [`SpecularType::Ggx`] is never returned by
+    /// [`Material::permutation_key`].
+    pub specular_type: SpecularType,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LitType {
+    OpaqueLit,
+    TransparentLit,
+    Unlit,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FogType {
+    Linear,
+    Exponential,
+    None,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ShadowType {
+    None,
+    Depth,
+    Pcf,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SpecularType {
+    None,
+    Blinn,
+    Ggx,
+}
+
+impl MaterialPermutationKey {
+    /// The technique type for the key. See the table in the type's docs.
+    pub fn technique_type(&self) -> TechniqueType {
+        use TechniqueType as T;
+
+        match (self.lit_type, self.shadow_type) {
+            (LitType::Unlit, _) => T::UNLIT,
+            (LitType::OpaqueLit, ShadowType::None) => T::LIT_SUN,
+            (LitType::OpaqueLit, ShadowType::Depth) => T::LIT_SUN_SHADOW,
+            (LitType::OpaqueLit, ShadowType::Pcf) => T::LIT_SPOT_SHADOW,
+            (LitType::TransparentLit, ShadowType::None) => T::LIT_SUN_FADE,
+            (LitType::TransparentLit, ShadowType::Depth) => T::LIT_SUN_SHADOW_FADE,
+            (LitType::TransparentLit, ShadowType::Pcf) => T::LIT_SPOT_SHADOW_FADE,
+        }
+    }
+
+    /// The index into [`MaterialTechniqueSet::techniques`] of the key's
+    /// technique.
+    pub fn technique_set_slot_index(&self) -> usize {
+        self.technique_type().slot()
+    }
+
+    /// The key whose technique is in `slot`, with no fog or specular, or
+    /// [`None`] if no key maps to `slot`. The inverse of
+    /// [`Self::technique_set_slot_index`] for keys without fog or specular.
+    pub fn from_slot_index(slot: usize) -> Option<Self> {
+        use TechniqueType as T;
+
+        let (lit_type, shadow_type) = match TechniqueType::from_slot(slot)? {
+            T::UNLIT => (LitType::Unlit, ShadowType::None),
+            T::LIT_SUN => (LitType::OpaqueLit, ShadowType::None),
+            T::LIT_SUN_SHADOW => (LitType::OpaqueLit, ShadowType::Depth),
+            T::LIT_SPOT_SHADOW => (LitType::OpaqueLit, ShadowType::Pcf),
+            T::LIT_SUN_FADE => (LitType::TransparentLit, ShadowType::None),
+            T::LIT_SUN_SHADOW_FADE => (LitType::TransparentLit, ShadowType::Depth),
+            T::LIT_SPOT_SHADOW_FADE => (LitType::TransparentLit, ShadowType::Pcf),
+            _ => return None,
+        };
+
+        Some(Self {
+            lit_type,
+            fog_type: FogType::None,
+            shadow_type,
+            specular_type: SpecularType::None,
+        })
+    }
+}
+
+/// A material's [`MaterialInfo::sort_key`].
+///
+/// The values are the positions of the entries in the `sort` list of the
+/// mod tools' material template (`material.gdf`), which is what the
+/// converter writes, and each variant's doc is the entry's name there. The
+/// first four are shared by every IW-derived engine. Values past the end
+/// of the list are kept as [`Self::Other`]. Either way the raw value is
+/// preserved, and it's what [`sort_materials_for_draw`] sorts by.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MaterialSortKey {
+    /// `opaque ambient`
+    OpaqueAmbient,
+    /// `opaque`
+    Opaque,
+    /// `sky`
+    Sky,
+    /// `skybox`
+    Skybox,
+    /// `decal - bottom 1`
+    DecalBottom1,
+    /// `decal - bottom 2`
+    DecalBottom2,
+    /// `decal - bottom 3`
+    DecalBottom3,
+    /// `decal - static decal`
+    DecalStatic,
+    /// `decal - middle 1`
+    DecalMiddle1,
+    /// `decal - middle 2`
+    DecalMiddle2,
+    /// `decal - middle 3`
+    DecalMiddle3,
+    /// `decal - weapon impact`
+    DecalWeaponImpact,
+    /// `decal - top 1`
+    DecalTop1,
+    /// `decal - top 2`
+    DecalTop2,
+    /// `decal - top 3`
+    DecalTop3,
+    /// `decal - multiplicative`
+    DecalMultiplicative,
+    /// `banner / curtains`
+    BannerCurtains,
+    /// `hair`
+    Hair,
+    /// `underwater`
+    Underwater,
+    /// `transparent water`
+    TransparentWater,
+    /// `corona`
+    Corona,
+    /// `window inside`
+    WindowInside,
+    /// `window outside`
+    WindowOutside,
+    /// `before effects - 1`
+    BeforeEffects1,
+    /// `before effects - 2`
+    BeforeEffects2,
+    /// `before effects - 3`
+    BeforeEffects3,
+    /// `blend / additive`
+    BlendAdditive,
+    /// `effect - auto sort`
+    EffectAutoSort,
+    /// `after effects - bottom`
+    AfterEffectsBottom,
+    /// `after effects - middle`
+    AfterEffectsMiddle,
+    /// `after effects - top`
+    AfterEffectsTop,
+    /// `viewmodel effect`
+    ViewmodelEffect,
+    Other(u8),
+}
+
+impl From<u8> for MaterialSortKey {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::OpaqueAmbient,
+            1 => Self::Opaque,
+            2 => Self::Sky,
+            3 => Self::Skybox,
+            4 => Self::DecalBottom1,
+            5 => Self::DecalBottom2,
+            6 => Self::DecalBottom3,
+            7 => Self::DecalStatic,
+            8 => Self::DecalMiddle1,
+            9 => Self::DecalMiddle2,
+            10 => Self::DecalMiddle3,
+            11 => Self::DecalWeaponImpact,
+            12 => Self::DecalTop1,
+            13 => Self::DecalTop2,
+            14 => Self::DecalTop3,
+            15 => Self::DecalMultiplicative,
+            16 => Self::BannerCurtains,
+            17 => Self::Hair,
+            18 => Self::Underwater,
+            19 => Self::TransparentWater,
+            20 => Self::Corona,
+            21 => Self::WindowInside,
+            22 => Self::WindowOutside,
+            23 => Self::BeforeEffects1,
+            24 => Self::BeforeEffects2,
+            25 => Self::BeforeEffects3,
+            26 => Self::BlendAdditive,
+            27 => Self::EffectAutoSort,
+            28 => Self::AfterEffectsBottom,
+            29 => Self::AfterEffectsMiddle,
+            30 => Self::AfterEffectsTop,
+            31 => Self::ViewmodelEffect,
+            _ => Self::Other(value),
+        }
+    }
+}
+
+impl From<MaterialSortKey> for u8 {
+    fn from(value: MaterialSortKey) -> Self {
+        match value {
+            MaterialSortKey::OpaqueAmbient => 0,
+            MaterialSortKey::Opaque => 1,
+            MaterialSortKey::Sky => 2,
+            MaterialSortKey::Skybox => 3,
+            MaterialSortKey::DecalBottom1 => 4,
+            MaterialSortKey::DecalBottom2 => 5,
+            MaterialSortKey::DecalBottom3 => 6,
+            MaterialSortKey::DecalStatic => 7,
+            MaterialSortKey::DecalMiddle1 => 8,
+            MaterialSortKey::DecalMiddle2 => 9,
+            MaterialSortKey::DecalMiddle3 => 10,
+            MaterialSortKey::DecalWeaponImpact => 11,
+            MaterialSortKey::DecalTop1 => 12,
+            MaterialSortKey::DecalTop2 => 13,
+            MaterialSortKey::DecalTop3 => 14,
+            MaterialSortKey::DecalMultiplicative => 15,
+            MaterialSortKey::BannerCurtains => 16,
+            MaterialSortKey::Hair => 17,
+            MaterialSortKey::Underwater => 18,
+            MaterialSortKey::TransparentWater => 19,
+            MaterialSortKey::Corona => 20,
+            MaterialSortKey::WindowInside => 21,
+            MaterialSortKey::WindowOutside => 22,
+            MaterialSortKey::BeforeEffects1 => 23,
+            MaterialSortKey::BeforeEffects2 => 24,
+            MaterialSortKey::BeforeEffects3 => 25,
+            MaterialSortKey::BlendAdditive => 26,
+            MaterialSortKey::EffectAutoSort => 27,
+            MaterialSortKey::AfterEffectsBottom => 28,
+            MaterialSortKey::AfterEffectsMiddle => 29,
+            MaterialSortKey::AfterEffectsTop => 30,
+            MaterialSortKey::ViewmodelEffect => 31,
+            MaterialSortKey::Other(v) => v,
+        }
+    }
+}
+
+/// A material's [`Material::camera_region`], i.e. which of the camera's draw
+/// lists its surfaces go into. The lists are drawn in declaration order.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CameraRegion {
+    LitOpaque,
+    LitTrans,
+    Emissive,
+    /// Drawn with a compressed depth range, e.g. view models.
+    DepthHack,
+    /// Not drawn by the camera at all.
+    None,
+    Other(u8),
+}
+
+impl From<u8> for CameraRegion {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::LitOpaque,
+            1 => Self::LitTrans,
+            2 => Self::Emissive,
+            3 => Self::DepthHack,
+            4 => Self::None,
+            _ => Self::Other(value),
+        }
+    }
+}
+
+impl From<CameraRegion> for u8 {
+    fn from(value: CameraRegion) -> Self {
+        match value {
+            CameraRegion::LitOpaque => 0,
+            CameraRegion::LitTrans => 1,
+            CameraRegion::Emissive => 2,
+            CameraRegion::DepthHack => 3,
+            CameraRegion::None => 4,
+            CameraRegion::Other(v) => v,
+        }
+    }
+}
+
+/// Returned by [`Material::sort_class`]. Ordered the way the engine draws
+/// them.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MaterialSortClass {
+    Opaque,
+    Sky,
+    Transparent,
+    Emissive,
+    DepthHack,
+    /// Materials that aren't drawn by the camera, or whose camera region
+    /// isn't known.
+    Unsorted,
+}
+
+/// Orders `materials` the way the engine would draw them: by camera region
+/// (i.e. draw list) first, then by sort key. Materials the camera doesn't draw
+/// end up last, and materials that compare equal keep their relative order.
+pub fn sort_materials_for_draw<'a>(materials: &[&'a Material]) -> Vec<&'a Material> {
+    let mut sorted = materials.to_vec();
+    sorted.sort_by_key(|m| (m.camera_region, m.info.sort_key));
+    sorted
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct MaterialInfoRaw<'a> {
@@ -783,6 +1912,25 @@ impl<'a> XFileDeserializeInto<MaterialInfo, ()> for MaterialInfoRaw<'a> {
     }
 }
 
+impl MaterialInfo {
+    fn to_raw(&self) -> MaterialInfoRaw<'static> {
+        MaterialInfoRaw {
+            name: XStringRaw::from_str(self.name.get()),
+            game_flags: self.game_flags,
+            pad: 0,
+            sort_key: self.sort_key,
+            texture_atlas_row_count: self.texture_atlas_row_count,
+            texture_atlas_column_count: self.texture_atlas_column_count,
+            pad2: [0; 4],
+            draw_surf: self.draw_surf,
+            surface_type_bits: self.surface_type_bits,
+            layered_surface_types: self.layered_surface_types,
+            hash_index: self.hash_index as _,
+            unused: [0; 6],
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Debug, Default, Deserialize)]
 pub struct GfxDrawSurf {
@@ -860,6 +2008,56 @@ impl<'a> XFileDeserializeInto<MaterialTextureDef, ()> for MaterialTextureDefRaw<
     }
 }
 
+impl MaterialTextureDef {
+    /// Hashes a sampler name the way [`Self::name_hash`] is.
+    pub fn hash_name(name: &str) -> u32 {
+        name.bytes()
+            .fold(0u32, |hash, c| hash.wrapping_mul(33) ^ (c | 0x20) as u32)
+    }
+
+    /// The name of the sampler the texture is bound to. See
+    /// [`Material::sampler_bindings`].
+    pub fn sampler_name(&self) -> &'static str {
+        KNOWN_SAMPLER_NAMES
+            .into_iter()
+            .find(|n| Self::hash_name(n) == self.name_hash)
+            .unwrap_or(match self.semantic {
+                Semantic::COLOR_MAP => "colorMap",
+                Semantic::NORMAL_MAP => "normalMap",
+                Semantic::SPECULAR_MAP => "specularMap",
+                Semantic::WATER_MAP => "waterMap",
+                _ => "unknown",
+            })
+    }
+
+    /// The texture's image. For water, that's the image the simulation is
+    /// rendered into.
+    pub fn image(&self) -> Option<&GfxImage> {
+        match &self.u {
+            MaterialTextureDefInfo::Image(i) => i.as_deref(),
+            MaterialTextureDefInfo::Water(w) => w.as_ref()?.image.as_deref(),
+        }
+    }
+
+    fn to_raw(&self) -> MaterialTextureDefRaw<'static> {
+        let p = match &self.u {
+            MaterialTextureDefInfo::Image(i) => Ptr32::from_box(i),
+            MaterialTextureDefInfo::Water(w) => Ptr32::from_box(w),
+        };
+
+        MaterialTextureDefRaw {
+            name_hash: self.name_hash,
+            name_start: self.name_start as u32 as _,
+            name_end: self.name_end as u32 as _,
+            sampler_state: self.sampler_state,
+            semantic: self.semantic as _,
+            is_mature_content: self.is_mature_content,
+            pad: [0; 3],
+            u: MaterialTextureDefInfoRaw { p },
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug, Default, PartialEq, FromPrimitive)]
 #[repr(u8)]
@@ -900,6 +2098,15 @@ impl Default for MaterialTextureDefInfo {
     }
 }
 
+impl XFileSerialize<()> for MaterialTextureDefInfo {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        match self {
+            Self::Image(i) => i.xfile_serialize(ser, ()),
+            Self::Water(w) => w.xfile_serialize(ser, ()),
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct WaterRaw<'a> {
@@ -977,6 +2184,31 @@ impl<'a> XFileDeserializeInto<Water, ()> for WaterRaw<'a> {
     }
 }
 
+impl XFileSerialize<()> for Water {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let water = WaterRaw {
+            writable: self.writable,
+            h0: Ptr32::from_slice(&self.h0),
+            w_term: Ptr32::from_slice(&self.w_term),
+            m: self.m,
+            n: self.n,
+            lx: self.lx,
+            ly: self.ly,
+            gravity: self.gravity,
+            windvel: self.windvel,
+            winddir: self.winddir.get(),
+            amplitude: self.amplitude,
+            code_constant: self.code_constant.get(),
+            image: Ptr32::from_box(&self.image),
+        };
+
+        ser.store_into_xfile(water)?;
+        self.h0.xfile_serialize(ser, ())?;
+        self.w_term.xfile_serialize(ser, ())?;
+        self.image.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub struct WaterWrtitable {
@@ -992,6 +2224,12 @@ pub struct Complex {
 }
 assert_size!(Complex, 8);
 
+impl XFileSerialize<()> for Complex {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        ser.store_into_xfile(*self)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct GfxImageRaw<'a> {
@@ -1025,8 +2263,11 @@ assert_size!(GfxImageRaw, 52);
 pub struct GfxImage {
     pub texture: GfxTexture,
     pub map_type: MapType,
-    pub semantic: Semantic,
-    pub category: ImgCategory,
+    /// See [`Self::semantic`]. Kept as-is so that unknown values survive
+    /// reserialization.
+    pub semantic: u8,
+    /// See [`Self::category`].
+    pub category: u8,
     pub delay_load_pixels: bool,
     pub picmip: Option<Picmip>,
     pub track: u8,
@@ -1042,6 +2283,10 @@ pub struct GfxImage {
     pub skipped_mip_levels: u8,
     pub name: XString,
     pub hash: u32,
+    /// Set if a [`GpuUploader`](crate::gpu::GpuUploader) was attached to the deserializer when the
+    /// image was deserialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub gpu_handle: Option<GpuHandle>,
 }
 
 impl<'a> XFileDeserializeInto<GfxImage, ()> for GfxImageRaw<'a> {
@@ -1063,16 +2308,6 @@ impl<'a> XFileDeserializeInto<GfxImage, ()> for GfxImageRaw<'a> {
             de.stream_pos()? as _,
             ErrorKind::BadFromPrimitive(self.map_type as _),
         ))?;
-        let semantic = num::FromPrimitive::from_u8(self.semantic).ok_or(Error::new_with_offset(
-            file_line_col!(),
-            de.stream_pos()? as _,
-            ErrorKind::BadFromPrimitive(self.semantic as _),
-        ))?;
-        let category = num::FromPrimitive::from_u8(self.category).ok_or(Error::new_with_offset(
-            file_line_col!(),
-            de.stream_pos()? as _,
-            ErrorKind::BadFromPrimitive(self.category as _),
-        ))?;
 
         let picmip = if self.no_picmip {
             None
@@ -1080,11 +2315,11 @@ impl<'a> XFileDeserializeInto<GfxImage, ()> for GfxImageRaw<'a> {
             Some(self.picmip)
         };
 
-        Ok(GfxImage {
+        let mut image = GfxImage {
             texture,
             map_type,
-            semantic,
-            category,
+            semantic: self.semantic,
+            category: self.category,
             delay_load_pixels: self.delay_load_pixels,
             picmip,
             track: self.track,
@@ -1100,8 +2335,274 @@ impl<'a> XFileDeserializeInto<GfxImage, ()> for GfxImageRaw<'a> {
             skipped_mip_levels: self.skipped_mip_levels,
             name,
             hash: self.hash,
+            gpu_handle: None,
+        };
+
+        // Images without a load def don't have a format, so there's nothing
+        // an uploader could create from them.
+        if image.format().is_some()
+            && let Some(uploader) = de.gpu_uploader()
+        {
+            image.gpu_handle = Some(uploader.upload_image(&image)?);
+        }
+
+        Ok(image)
+    }
+}
+
+impl GfxImage {
+    /// [`None`] if the semantic isn't a known one.
+    pub fn semantic(&self) -> Option<Semantic> {
+        num::FromPrimitive::from_u8(self.semantic)
+    }
+
+    /// [`None`] if the category isn't a known one.
+    pub fn category(&self) -> Option<ImgCategory> {
+        num::FromPrimitive::from_u8(self.category)
+    }
+
+    /// The image's [`GfxImageLoadDef`] flags, if it has one.
+    pub fn load_flags(&self) -> Option<ImageFlags> {
+        match &self.texture {
+            GfxTexture::LoadDef(Some(d)) => Some(d.image_flags()),
+            _ => None,
+        }
+    }
+
+    /// Whether the image is one of a map's baked lightmaps.
+    pub fn is_lightmap(&self) -> bool {
+        self.category() == Some(ImgCategory::LIGHTMAP)
+    }
+
+    /// Whether the image is drawn as-is (HUD elements, menus, fonts, etc.)
+    /// rather than being sampled by a lit material.
+    pub fn is_ui(&self) -> bool {
+        self.semantic() == Some(Semantic::IDLE)
+    }
+
+    /// Whether the image can have its top mip levels dropped on low
+    /// settings.
+    pub fn can_picmip(&self) -> bool {
+        self.picmip.is_some()
+            && !self
+                .load_flags()
+                .is_some_and(|f| f.contains(ImageFlags::NOPICMIP))
+    }
+
+    /// Whether the image's pixels are meant to be streamed in rather than
+    /// loaded with the rest of the zone.
+    pub fn wants_streaming(&self) -> bool {
+        self.streaming
+            || self
+                .load_flags()
+                .is_some_and(|f| f.contains(ImageFlags::STREAMING))
+    }
+
+    /// The image's format, if it has a [`GfxImageLoadDef`].
+    pub fn format(&self) -> Option<D3DFORMAT> {
+        match &self.texture {
+            GfxTexture::LoadDef(Some(d)) => Some(d.format),
+            _ => None,
+        }
+    }
+
+    /// The raw pixel data for every face and mip level.
+    ///
+    /// [`Self::pixels`] is never populated by the deserializer, so this
+    /// falls back to the [`GfxImageLoadDef`]'s resource.
+    pub fn pixel_data(&self) -> &[u8] {
+        if !self.pixels.is_empty() {
+            return &self.pixels;
+        }
+
+        match &self.texture {
+            GfxTexture::LoadDef(Some(d)) => &d.resource,
+            _ => &[],
+        }
+    }
+
+    pub fn mip_count(&self) -> usize {
+        let level_count = match &self.texture {
+            GfxTexture::LoadDef(Some(d)) => d.level_count,
+            _ => self.level_count,
+        };
+
+        level_count.max(1) as _
+    }
+
+    pub fn is_cubemap(&self) -> bool {
+        self.map_type == MapType::CUBE
+    }
+
+    pub fn face_count(&self) -> usize {
+        if self.is_cubemap() { 6 } else { 1 }
+    }
+
+    pub fn mip_dimensions(&self, level: usize) -> Result<(u32, u32)> {
+        self.check_mip_level(level)?;
+
+        let width = (self.width as u32 >> level).max(1);
+        let height = (self.height as u32 >> level).max(1);
+        Ok((width, height))
+    }
+
+    /// The size of a single face's mip level `level`.
+    pub fn mip_size_bytes(&self, level: usize) -> Result<usize> {
+        let (width, height) = self.mip_dimensions(level)?;
+        let depth = if self.map_type == MapType::THREE_DIMENSIONAL {
+            (self.depth as usize >> level).max(1)
+        } else {
+            1
+        };
+
+        let format = self.format().unwrap_or_default();
+        let Some((block_dim, block_bytes)) = d3dfmt::block_info(format) else {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::UnsupportedImageFormat(format as _),
+            ));
+        };
+
+        let blocks_wide = (width as usize).div_ceil(block_dim);
+        let blocks_high = (height as usize).div_ceil(block_dim);
+        Ok(blocks_wide * blocks_high * block_bytes * depth)
+    }
+
+    /// The offset of mip level `level` from the start of its face.
+    pub fn mip_offset_bytes(&self, level: usize) -> Result<usize> {
+        self.check_mip_level(level)?;
+        (0..level).map(|l| self.mip_size_bytes(l)).sum()
+    }
+
+    /// The size of every mip level of every face.
+    pub fn full_size_bytes(&self) -> Result<usize> {
+        Ok(self.face_size_bytes()? * self.face_count())
+    }
+
+    pub fn mip_data(&self, level: usize) -> Result<&[u8]> {
+        self.face_mip_data(0, level)
+    }
+
+    /// Cubemaps store each face's mip chain one after another, so `face`
+    /// selects the chain and `level` the mip within it.
+    pub fn face_mip_data(&self, face: u8, level: usize) -> Result<&[u8]> {
+        if face as usize >= self.face_count() {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "GfxImage: face ({face}) >= face count ({})",
+                    self.face_count()
+                )),
+            ));
+        }
+
+        let start = face as usize * self.face_size_bytes()? + self.mip_offset_bytes(level)?;
+        let end = start + self.mip_size_bytes(level)?;
+        let data = self.pixel_data();
+        data.get(start..end).ok_or(Error::new(
+            file_line_col!(),
+            ErrorKind::InvalidSeek {
+                off: end as _,
+                max: data.len() as _,
+            },
+        ))
+    }
+
+    /// Decodes mip level `mip` to RGBA8, and returns it along with its width
+    /// and height. Only the first face of a cubemap, and the first slice of
+    /// a volume texture, are decoded.
+    ///
+    /// The DXT formats and the 8-bit-per-channel uncompressed ones are
+    /// supported. The data is assumed to be little-endian, so console images
+    /// have to go through [`byte_swapped`](crate::gpu::byte_swapped) first.
+    pub fn to_rgba8_mip(&self, mip: usize) -> Result<(u32, u32, Vec<u8>)> {
+        let data = self.mip_data(mip)?;
+        let (width, height) = self.mip_dimensions(mip)?;
+        let pixels = width as usize * height as usize;
+
+        let convert = |size: usize, f: fn(&[u8]) -> [u8; 4]| {
+            data.chunks_exact(size)
+                .take(pixels)
+                .flat_map(f)
+                .collect::<Vec<_>>()
+        };
+        let rgba = match self.format().unwrap_or_default() {
+            d3dfmt::DXT1 => dxt::decode_dxt1(data, width, height),
+            d3dfmt::DXT3 => dxt::decode_dxt3(data, width, height),
+            d3dfmt::DXT5 => dxt::decode_dxt5(data, width, height),
+            d3dfmt::A8R8G8B8 => convert(4, |p| [p[2], p[1], p[0], p[3]]),
+            d3dfmt::X8R8G8B8 => convert(4, |p| [p[2], p[1], p[0], 255]),
+            d3dfmt::R5G6B5 => convert(2, |p| dxt::rgb565(u16::from_le_bytes([p[0], p[1]]))),
+            d3dfmt::A8L8 => convert(2, |p| [p[0], p[0], p[0], p[1]]),
+            d3dfmt::L8 => convert(1, |p| [p[0], p[0], p[0], 255]),
+            d3dfmt::A8 => convert(1, |p| [255, 255, 255, p[0]]),
+            format => {
+                return Err(Error::new(
+                    file_line_col!(),
+                    ErrorKind::UnsupportedImageFormat(format as _),
+                ));
+            }
+        };
+
+        Ok((width, height, rgba))
+    }
+
+    /// Saves the base mip level as a PNG at `path`. See
+    /// [`Self::to_rgba8_mip`] for which images are supported.
+    #[cfg(feature = "image")]
+    pub fn save_png(&self, path: &std::path::Path) -> Result<()> {
+        let (width, height, rgba) = self.to_rgba8_mip(0)?;
+
+        image::save_buffer_with_format(
+            path,
+            &rgba,
+            width,
+            height,
+            image::ExtendedColorType::Rgba8,
+            image::ImageFormat::Png,
+        )
+        .map_err(|e| match e {
+            image::ImageError::IoError(e) => Error::new(file_line_col!(), ErrorKind::Io(e)),
+            e => Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!("GfxImage: couldn't save PNG: {e}")),
+            ),
         })
     }
+
+    fn face_size_bytes(&self) -> Result<usize> {
+        (0..self.mip_count()).map(|l| self.mip_size_bytes(l)).sum()
+    }
+
+    fn check_mip_level(&self, level: usize) -> Result<()> {
+        if level >= self.mip_count() {
+            Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "GfxImage: level ({level}) >= mip count ({})",
+                    self.mip_count()
+                )),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Decompresses `img`'s base mip level to RGBA8. Unlike
+/// [`GfxImage::to_rgba8_mip`], it's an error if the image isn't DXT1, DXT3,
+/// or DXT5.
+#[cfg(feature = "image")]
+pub fn decompress_dxt(img: &GfxImage) -> Result<Vec<u8>> {
+    match img.format().unwrap_or_default() {
+        d3dfmt::DXT1 | d3dfmt::DXT3 | d3dfmt::DXT5 => Ok(img.to_rgba8_mip(0)?.2),
+        format => Err(Error::new(
+            file_line_col!(),
+            ErrorKind::BrokenInvariant(format!(
+                "decompress_dxt: format {format:#010X} isn't DXT1, DXT3, or DXT5"
+            )),
+        )),
+    }
 }
 
 impl XFileSerialize<()> for GfxImage {
@@ -1114,11 +2615,11 @@ impl XFileSerialize<()> for GfxImage {
         let image = GfxImageRaw {
             texture,
             map_type: self.map_type as _,
-            semantic: self.semantic as _,
-            category: self.category as _,
+            semantic: self.semantic,
+            category: self.category,
             delay_load_pixels: self.delay_load_pixels,
             picmip: self.picmip.unwrap_or_default(),
-            no_picmip: self.picmip.is_some(),
+            no_picmip: self.picmip.is_none(),
             track: self.track,
             card_memory: self.card_memory,
             width: self.width,
@@ -1225,11 +2726,13 @@ pub enum MapType {
 pub enum ImgCategory {
     #[default]
     UNKNOWN = 0x00,
-    ONE = 0x01,
-    TWO = 0x02,
+    AUTO_GENERATED = 0x01,
+    LIGHTMAP = 0x02,
     LOAD_FROM_FILE = 0x03,
+    RAW = 0x04,
     WATER = 0x05,
     RENDER_TARGET = 0x06,
+    TEMP = 0x07,
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -1255,6 +2758,24 @@ pub struct MaterialConstantDef {
 }
 assert_size!(MaterialConstantDef, 32);
 
+impl MaterialConstantDef {
+    /// The constant's name, up to its first null byte.
+    pub fn name(&self) -> String {
+        let len = self
+            .name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(self.name.len());
+        String::from_utf8_lossy(&self.name[..len]).to_string()
+    }
+}
+
+impl XFileSerialize<()> for MaterialConstantDef {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        ser.store_into_xfile(*self)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub struct GfxStateBits {
@@ -1262,6 +2783,12 @@ pub struct GfxStateBits {
 }
 assert_size!(GfxStateBits, 8);
 
+impl XFileSerialize<()> for GfxStateBits {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        ser.store_into_xfile(*self)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct GfxImageLoadDefRaw {
@@ -1282,8 +2809,84 @@ pub struct GfxImageLoadDef {
     pub resource: Vec<u8>,
 }
 
+impl GfxImageLoadDef {
+    /// Unknown bits are kept, and can be checked for with
+    /// [`ImageFlags::unknown_bits`].
+    pub const fn image_flags(&self) -> ImageFlags {
+        ImageFlags::from_bits_retain(self.flags)
+    }
+}
+
+bitflags! {
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+    pub struct ImageFlags: u8 {
+        const NOPICMIP = 0x01;
+        const NOMIPMAPS = 0x02;
+        const CUBEMAP = 0x04;
+        const VOLMAP = 0x08;
+        const STREAMING = 0x10;
+        const LEGACY_NORMALS = 0x20;
+    }
+}
+
+impl ImageFlags {
+    /// The bits that don't correspond to any known flag.
+    pub const fn unknown_bits(self) -> u8 {
+        self.bits() & !Self::all().bits()
+    }
+}
+
 type D3DFORMAT = i32;
 
+pub mod d3dfmt {
+    use super::D3DFORMAT;
+
+    const fn fourcc(c: &[u8; 4]) -> D3DFORMAT {
+        i32::from_le_bytes(*c)
+    }
+
+    pub const A8R8G8B8: D3DFORMAT = 21;
+    pub const X8R8G8B8: D3DFORMAT = 22;
+    pub const R5G6B5: D3DFORMAT = 23;
+    pub const A8: D3DFORMAT = 28;
+    pub const A16B16G16R16F: D3DFORMAT = 113;
+    pub const L8: D3DFORMAT = 50;
+    pub const A8L8: D3DFORMAT = 51;
+    pub const R32F: D3DFORMAT = 114;
+    pub const DXT1: D3DFORMAT = fourcc(b"DXT1");
+    pub const DXT3: D3DFORMAT = fourcc(b"DXT3");
+    pub const DXT5: D3DFORMAT = fourcc(b"DXT5");
+    pub const ATI2: D3DFORMAT = fourcc(b"ATI2");
+
+    /// Returns the block dimension (in pixels) and the size of a single
+    /// block (in bytes) for `format`. Uncompressed formats are treated as
+    /// having 1x1 blocks.
+    pub const fn block_info(format: D3DFORMAT) -> Option<(usize, usize)> {
+        match format {
+            DXT1 => Some((4, 8)),
+            DXT3 | DXT5 | ATI2 => Some((4, 16)),
+            A8R8G8B8 | X8R8G8B8 | R32F => Some((1, 4)),
+            R5G6B5 | A8L8 => Some((1, 2)),
+            A8 | L8 => Some((1, 1)),
+            A16B16G16R16F => Some((1, 8)),
+            _ => None,
+        }
+    }
+
+    /// Returns the size (in bytes) of the units `format`'s data has to be
+    /// byte-swapped in when converting between big- and little-endian.
+    pub const fn swap_unit(format: D3DFORMAT) -> Option<usize> {
+        match format {
+            DXT1 | DXT3 | DXT5 | ATI2 => Some(2),
+            A8R8G8B8 | X8R8G8B8 | R32F => Some(4),
+            R5G6B5 | A8L8 | A16B16G16R16F => Some(2),
+            A8 | L8 => Some(1),
+            _ => None,
+        }
+    }
+}
+
 impl XFileDeserializeInto<GfxImageLoadDef, ()> for GfxImageLoadDefRaw {
     fn xfile_deserialize_into(
         &self,
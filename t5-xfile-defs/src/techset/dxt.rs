@@ -0,0 +1,122 @@
+//! Software decoding of the DXT (BC1-3) block-compressed formats to RGBA8.
+//!
+//! Each 4x4 block is decoded in full, then cropped to the image, so images
+//! whose sides aren't multiples of 4 (including the smallest mips) work.
+
+use alloc::{vec, vec::Vec};
+
+/// A decoded block, in row-major order.
+type Block = [[u8; 4]; 16];
+
+/// Decodes a `width` x `height` image of DXT1 blocks.
+///
+/// DXT1 has no alpha channel of its own, so pixels are fully opaque, except
+/// for the transparent black that blocks with `color0 <= color1` can use.
+pub(super) fn decode_dxt1(data: &[u8], width: u32, height: u32) -> Vec<u8> {
+    decode(data, width, height, 8, |b| decode_color(b, true))
+}
+
+/// Decodes a `width` x `height` image of DXT3 blocks, which have 4 bits of
+/// explicit alpha per pixel.
+pub(super) fn decode_dxt3(data: &[u8], width: u32, height: u32) -> Vec<u8> {
+    decode(data, width, height, 16, |b| {
+        let mut block = decode_color(&b[8..], false);
+        let alpha = u64::from_le_bytes(b[..8].try_into().unwrap());
+        for (i, px) in block.iter_mut().enumerate() {
+            px[3] = ((alpha >> (4 * i)) & 0xF) as u8 * 17;
+        }
+        block
+    })
+}
+
+/// Decodes a `width` x `height` image of DXT5 blocks, which have
+/// interpolated alpha.
+pub(super) fn decode_dxt5(data: &[u8], width: u32, height: u32) -> Vec<u8> {
+    decode(data, width, height, 16, |b| {
+        let mut block = decode_color(&b[8..], false);
+
+        let (a0, a1) = (b[0] as u32, b[1] as u32);
+        let alphas: [u8; 8] = core::array::from_fn(|i| {
+            let i = i as u32;
+            match i {
+                0 => a0 as u8,
+                1 => a1 as u8,
+                _ if a0 > a1 => (((8 - i) * a0 + (i - 1) * a1) / 7) as u8,
+                6 => 0,
+                7 => 255,
+                _ => (((6 - i) * a0 + (i - 1) * a1) / 5) as u8,
+            }
+        });
+
+        let mut indices = [0; 8];
+        indices[..6].copy_from_slice(&b[2..8]);
+        let indices = u64::from_le_bytes(indices);
+        for (i, px) in block.iter_mut().enumerate() {
+            px[3] = alphas[((indices >> (3 * i)) & 7) as usize];
+        }
+        block
+    })
+}
+
+/// Decodes the 8-byte color part of a block. Only DXT1 blocks can use the
+/// 3-color mode with transparent black.
+fn decode_color(b: &[u8], allow_transparent: bool) -> Block {
+    let c0 = u16::from_le_bytes([b[0], b[1]]);
+    let c1 = u16::from_le_bytes([b[2], b[3]]);
+    let (p0, p1) = (rgb565(c0), rgb565(c1));
+
+    let mix = |w0: u32, w1: u32| -> [u8; 4] {
+        let total = w0 + w1;
+        let ch = |i: usize| ((w0 * p0[i] as u32 + w1 * p1[i] as u32) / total) as u8;
+        [ch(0), ch(1), ch(2), 255]
+    };
+    let palette = if c0 > c1 || !allow_transparent {
+        [p0, p1, mix(2, 1), mix(1, 2)]
+    } else {
+        [p0, p1, mix(1, 1), [0, 0, 0, 0]]
+    };
+
+    let indices = u32::from_le_bytes([b[4], b[5], b[6], b[7]]);
+    core::array::from_fn(|i| palette[((indices >> (2 * i)) & 3) as usize])
+}
+
+pub(super) fn rgb565(c: u16) -> [u8; 4] {
+    let (r, g, b) = ((c >> 11) & 0x1F, (c >> 5) & 0x3F, c & 0x1F);
+    [
+        ((r << 3) | (r >> 2)) as u8,
+        ((g << 2) | (g >> 4)) as u8,
+        ((b << 3) | (b >> 2)) as u8,
+        255,
+    ]
+}
+
+/// Decodes each `block_bytes`-sized block of `data` with `decode_block`
+/// and copies the part of it inside the image to the output.
+fn decode(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    block_bytes: usize,
+    decode_block: impl Fn(&[u8]) -> Block,
+) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let blocks_wide = width.div_ceil(4);
+
+    let mut rgba = vec![0; width * height * 4];
+    for (i, b) in data
+        .chunks_exact(block_bytes)
+        .take(blocks_wide * height.div_ceil(4))
+        .enumerate()
+    {
+        let block = decode_block(b);
+        let (bx, by) = (i % blocks_wide * 4, i / blocks_wide * 4);
+        for y in 0..4.min(height - by) {
+            for x in 0..4.min(width - bx) {
+                let dst = ((by + y) * width + bx + x) * 4;
+                rgba[dst..dst + 4].copy_from_slice(&block[y * 4 + x]);
+            }
+        }
+    }
+
+    rgba
+}
@@ -1,14 +1,11 @@
 use core::{
     fmt::{self, Debug},
     marker::PhantomData,
+    ops::Deref,
 };
 
 use alloc::{
-    boxed::Box,
-    ffi::CString,
-    format,
-    string::{String, ToString},
-    vec::Vec,
+    boxed::Box, collections::BTreeSet, ffi::CString, format, string::String, sync::Arc, vec::Vec,
 };
 
 #[allow(unused_imports)]
@@ -153,28 +150,35 @@ impl<'a> XFileDeserializeInto<XString, ()> for XStringRaw<'a> {
                 break;
             }
         }
+        de.check_allocation(string_buf.len(), 1)?;
 
         //dbg!(xfile.stream_position()?);
-        Ok(XString(
-            CString::from_vec_with_nul(string_buf)
-                .unwrap()
-                .to_string_lossy()
-                .to_string(),
-        ))
+        let s = CString::from_vec_with_nul(string_buf).unwrap();
+        Ok(de.intern_string(&s.to_string_lossy()))
     }
 }
 
+/// A string, shared rather than copied when cloned.
+///
+/// Deserializers may intern these (see [`StringInterner`]), in which case
+/// identical strings throughout a file share one allocation.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug, Default)]
 #[repr(transparent)]
-pub struct XString(pub String);
+pub struct XString(pub Arc<str>);
 
 impl XFileSerialize<()> for XString {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        // Empty strings are serialized as null pointers (see
+        // `XStringRaw::from_str`), so there's nothing to point to.
+        if self.0.is_empty() {
+            return Ok(());
+        }
+
         let mut bytes = self.0.chars().map(|c| c as u8).collect::<Vec<_>>();
         bytes.push(b'\0');
 
-        ser.store_into_xfile(bytes)
+        ser.store_string_into_xfile(&bytes)
     }
 }
 
@@ -183,8 +187,77 @@ impl XString {
         &self.0
     }
 
-    pub const fn new() -> Self {
-        Self(String::new())
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bytes an [`XString`] holding `s` allocates: `s` itself, plus the
+    /// [`Arc`]'s reference counts.
+    pub const fn heap_size(s: &str) -> usize {
+        2 * size_of!(usize) + s.len()
+    }
+}
+
+impl Deref for XString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for XString {
+    fn from(value: String) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<&str> for XString {
+    fn from(value: &str) -> Self {
+        Self(value.into())
+    }
+}
+
+/// Deduplicates [`XString`]s, so that every interned copy of a string
+/// shares the first one's allocation.
+///
+/// Materials, techniques, and models repeat the same handful of names
+/// (`"colorMap"`, bone names, etc.) thousands of times over, so interning
+/// them saves both the allocations and the memory.
+#[derive(Clone, Debug, Default)]
+pub struct StringInterner {
+    strings: BTreeSet<Arc<str>>,
+}
+
+impl StringInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the interned copy of `s`, adding one if there isn't one yet.
+    pub fn intern(&mut self, s: &str) -> XString {
+        if let Some(interned) = self.strings.get(s) {
+            return XString(interned.clone());
+        }
+
+        let interned = Arc::<str>::from(s);
+        self.strings.insert(interned.clone());
+        XString(interned)
+    }
+
+    /// The number of distinct strings interned.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// The bytes taken up by the distinct strings, not counting their
+    /// reference counts.
+    pub fn byte_len(&self) -> usize {
+        self.strings.iter().map(|s| s.len()).sum()
     }
 }
 
@@ -249,9 +322,11 @@ impl<T: XFileSerialize<U>, U: Copy> XFileSerialize<U> for Box<T> {
 
 impl<T: XFileSerialize<U>, U: Copy> XFileSerialize<U> for Vec<T> {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, data: U) -> Result<()> {
+        ser.begin_array();
         for t in self {
             t.xfile_serialize(ser, data)?;
         }
+        ser.end_array();
 
         Ok(())
     }
@@ -419,6 +494,7 @@ impl<'a, T: DeserializeOwned + Clone + Debug + XFileDeserializeInto<U, V>, U, V:
             t
         };
 
+        de.track_heap_bytes(size_of!(U) as _);
         t.xfile_deserialize_into(de, data).map(Box::new).map(Some)
     }
 }
@@ -438,7 +514,7 @@ impl<'a, T: DeserializeOwned + Debug> Ptr32<'a, T> {
         } else {
             // no need to seek for 0xFFFFFFFF / 0xFFFFFFFE
             let old = de.stream_pos()?;
-            let t = de.load_from_xfile::<T>();
+            let t = de.load_from_xfile::<T>()?;
             let new = de.stream_pos()?;
             // bincode will sometimes deserialize less than sizeof!(T) bytes
             // since it treats all structs as packed. those instances need to be
@@ -452,7 +528,7 @@ impl<'a, T: DeserializeOwned + Debug> Ptr32<'a, T> {
             t
         };
 
-        t.map(Some)
+        Ok(Some(t))
     }
 }
 
@@ -512,10 +588,15 @@ pub(crate) trait FlexibleArray<T: DeserializeOwned> {
     fn new(count: usize) -> Self;
 
     fn to_vec(&self, de: &mut impl T5XFileDeserialize) -> Result<Vec<T>> {
-        let mut vt = Vec::new();
+        de.check_allocation(self.count(), size_of!(T))?;
+
+        let mut vt = Vec::with_capacity(self.count());
 
         let old = de.stream_pos()?;
-        for _ in 0..self.count() {
+        for i in 0..self.count() {
+            if i % CANCEL_CHECK_INTERVAL == 0 {
+                de.check_cancelled()?;
+            }
             vt.push(de.load_from_xfile()?);
         }
         let new = de.stream_pos()?;
@@ -523,6 +604,7 @@ pub(crate) trait FlexibleArray<T: DeserializeOwned> {
         // since it treats all structs as packed. those instances need to be
         // caught and fixed, so this is how we catch them
         assert!(new == old + size_of!(T) as u64 * self.count() as u64);
+        de.track_heap_bytes((size_of!(T) * self.count()) as _);
 
         Ok(vt)
     }
@@ -549,6 +631,55 @@ macro_rules! impl_flexible_array {
 
 impl_flexible_array!(FlexibleArrayU16, FlexibleArrayU32,);
 
+/// How many array elements are deserialized between calls to
+/// [`T5XFileDeserialize::check_cancelled`].
+pub const CANCEL_CHECK_INTERVAL: usize = 1024;
+
+/// Checks that `count` [`T`]s starting at `ptr` fit inside the stream before
+/// any of them are deserialized, so that a corrupted count fails fast
+/// instead of reading off the end of the blob.
+///
+/// Inline pointers (`0xFFFFFFFF`/`0xFFFFFFFE`) refer to the current stream
+/// position. Real offsets are never followed, but the array they point to
+/// still has to fit in the stream.
+///
+/// Returns [`ErrorKind::InvalidSeek`] if the array runs past the end of the
+/// stream, or if computing its end overflows. That's never reported as
+/// [`ErrorKind::UnexpectedEof`], even for an inline array, since a
+/// corrupted count would otherwise look like a truncated file.
+pub fn validate_ptr32_array<T>(
+    de: &mut impl T5XFileDeserialize,
+    ptr: &Ptr32<'_, T>,
+    count: usize,
+) -> Result<()> {
+    if ptr.is_null() {
+        return Ok(());
+    }
+
+    let len = de.stream_len()?;
+    let off = if ptr.is_real() {
+        (ptr.as_u32() & 0x1FFFFFFF) as u64
+    } else {
+        de.stream_pos()?
+    };
+    let end = (size_of!(T) as u64)
+        .checked_mul(count as u64)
+        .and_then(|size| size.checked_add(off));
+
+    if end.is_some_and(|end| end <= len) {
+        return Ok(());
+    }
+
+    Err(Error::new_with_offset(
+        file_line_col!(),
+        de.stream_pos()? as _,
+        ErrorKind::InvalidSeek {
+            off: end.map_or(u32::MAX, |end| end.min(u32::MAX as _) as _),
+            max: len as _,
+        },
+    ))
+}
+
 pub trait FatPointer<'a, T: DeserializeOwned + 'a>: Sized {
     fn size(&self) -> usize;
     fn p(&self) -> Ptr32<'a, T>;
@@ -564,14 +695,22 @@ pub trait FatPointer<'a, T: DeserializeOwned + 'a>: Sized {
             return Ok(Vec::new());
         }
 
+        if !self.p().is_real() {
+            de.check_allocation(self.size(), size_of!(T))?;
+        }
+        validate_ptr32_array(de, &self.p(), self.size())?;
+
         let v = if self.p().is_real() {
             //eprintln!("ignoring offset {:#010X}", self.p().as_u32());
             return Ok(Vec::new());
         } else {
             // no need to seek for 0xFFFFFFFF / 0xFFFFFFFE
             let old = de.stream_pos()?;
-            let mut v = Vec::new();
-            for _ in 0..self.size() {
+            let mut v = Vec::with_capacity(self.size());
+            for i in 0..self.size() {
+                if i % CANCEL_CHECK_INTERVAL == 0 {
+                    de.check_cancelled()?;
+                }
                 v.push(de.load_from_xfile::<T>()?);
             }
             let new = de.stream_pos()?;
@@ -584,6 +723,7 @@ pub trait FatPointer<'a, T: DeserializeOwned + 'a>: Sized {
                 size_of!(T) * self.size(),
                 new - old
             );
+            de.track_heap_bytes((size_of!(T) * self.size()) as _);
             v
         };
 
@@ -591,8 +731,9 @@ pub trait FatPointer<'a, T: DeserializeOwned + 'a>: Sized {
     }
 
     fn to_vec_into<U: From<T>>(&self, de: &mut impl T5XFileDeserialize) -> Result<Vec<U>> {
-        self.to_vec(de)
-            .map(|v| v.into_iter().map(Into::<U>::into).collect())
+        let v = self.to_vec(de)?;
+        track_converted::<T, U>(de, v.len());
+        Ok(v.into_iter().map(Into::<U>::into).collect())
     }
 
     fn from_slice<U>(s: &[U]) -> Self {
@@ -759,11 +900,24 @@ impl<'a, T: Debug + Clone + DeserializeOwned + 'a, const N: usize> FatPointer<'a
     }
 
     fn new(p: Ptr32<'a, T>, size: usize) -> Self {
-        assert!(size == N);
+        // `from_slice` makes a null pointer out of an empty slice.
+        assert!(size == N || (size == 0 && p.is_null()));
         Self(p)
     }
 }
 
+impl<'a, T, const N: usize> Ptr32ArrayConst<'a, T, N> {
+    /// For arrays that are stored in full or not at all, e.g.
+    /// `Option<Box<[U; N]>>`.
+    pub const fn from_option<U>(o: &Option<U>) -> Self {
+        if o.is_some() {
+            Self(Ptr32::unreal())
+        } else {
+            Self(Ptr32::null())
+        }
+    }
+}
+
 // ===============================================================================
 // Trying to implement `XFileInto` generically for all `FatPointer<'_, T>` leads to an
 // unconstrained type error, so for now we just implement it individually. FIXME
@@ -777,11 +931,8 @@ macro_rules! impl_xfile_into_for_fat_pointer {
                 V: Copy,
             {
                 fn xfile_deserialize_into(&self, de: &mut impl T5XFileDeserialize, data: V) -> Result<Vec<U>> {
-                    self.clone()
-                        .to_vec(de)?
-                        .into_iter()
-                        .map(|a| a.xfile_deserialize_into(de, data))
-                        .collect()
+                    let v = self.clone().to_vec(de)?;
+                    convert_all(de, v, data)
                 }
             }
         )+
@@ -803,11 +954,31 @@ where
     V: Copy,
 {
     fn xfile_deserialize_into(&self, de: &mut impl T5XFileDeserialize, data: V) -> Result<Vec<U>> {
-        self.clone()
-            .to_vec(de)?
-            .into_iter()
-            .map(|a| a.xfile_deserialize_into(de, data))
-            .collect()
+        let v = self.clone().to_vec(de)?;
+        convert_all(de, v, data)
     }
 }
+
+/// Converts every element of `v`. Unlike collecting into a [`Result`], the
+/// output is allocated at its final size up front.
+fn convert_all<T: XFileDeserializeInto<U, V>, U, V: Copy>(
+    de: &mut impl T5XFileDeserialize,
+    v: Vec<T>,
+    data: V,
+) -> Result<Vec<U>> {
+    track_converted::<T, U>(de, v.len());
+
+    let mut out = Vec::with_capacity(v.len());
+    for t in v {
+        out.push(t.xfile_deserialize_into(de, data)?);
+    }
+    Ok(out)
+}
+
+/// Counts what converting `count` `T`s into `U`s adds to the heap, on top of
+/// the `T`s themselves that `to_vec` already counted.
+fn track_converted<T, U>(de: &mut impl T5XFileDeserialize, count: usize) {
+    let extra = size_of!(U).saturating_sub(size_of!(T));
+    de.track_heap_bytes((extra * count) as _);
+}
 // ===============================================================================
@@ -0,0 +1,515 @@
+//! Sanity checks for deserialized assets.
+//!
+//! A corrupt or hand-edited fastfile can deserialize without any errors and
+//! still be full of garbage - NaNs, counts that wrapped around, empty names,
+//! enum values equal to their `COUNT` sentinel, and so on. [`validate`] looks
+//! for values like that and reports them instead of failing on the first one.
+//!
+//! Only weapons, materials, images, physics presets, com worlds, and clip
+//! maps are checked for now. Other asset types are skipped.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Display;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clipmap::{ClipMap, DynEntityBasis, DynEntityType},
+    com_world::ComWorld,
+    common::Vec3,
+    techset::{GfxImage, Material},
+    weapon::{
+        ActiveReticleType, ImpactType, OffhandClass, PenetrateType, WeapClass, WeapClipType,
+        WeapFireType, WeapInventoryType, WeapType, WeaponDef, WeaponVariantDef,
+    },
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+    xmodel::PhysPreset,
+};
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Unusual, but the engine copes with it.
+    Info,
+    /// Probably wrong, or likely to behave strangely in-game.
+    Warning,
+    /// Almost certainly corrupt.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single suspicious value found by [`validate`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Finding {
+    /// Index of the asset in the list passed to [`validate`].
+    pub index: usize,
+    pub asset_type: XAssetType,
+    pub asset_name: Option<String>,
+    /// Path to the field within the asset, e.g. `weap_def.fire_time` or
+    /// `primary_lights[3].radius`.
+    pub field: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}: {:?} '{}' ({}): {}: {}",
+            self.severity,
+            self.asset_type,
+            self.asset_name.as_deref().unwrap_or_default(),
+            self.index,
+            self.field,
+            self.message
+        )
+    }
+}
+
+/// Returned by [`validate`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Default, Debug)]
+pub struct ValidationReport {
+    pub findings: Vec<Finding>,
+}
+
+impl ValidationReport {
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.count(Severity::Error) != 0
+    }
+
+    pub fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(move |f| f.severity == severity)
+    }
+}
+
+/// Runs every available check on `assets`.
+pub fn validate(assets: &[XAsset]) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    for (index, asset) in assets.iter().enumerate() {
+        let mut c = Checker {
+            report: &mut report,
+            index,
+            asset_type: asset.asset_type(),
+            asset_name: asset.name().map(ToString::to_string),
+        };
+
+        match asset {
+            XAsset::PC(a) => c.asset(a),
+            XAsset::Console(a) => c.asset(a),
+        }
+    }
+
+    report
+}
+
+struct Checker<'r> {
+    report: &'r mut ValidationReport,
+    index: usize,
+    asset_type: XAssetType,
+    asset_name: Option<String>,
+}
+
+impl<'r> Checker<'r> {
+    fn push(&mut self, severity: Severity, field: impl Into<String>, message: impl Into<String>) {
+        self.report.findings.push(Finding {
+            index: self.index,
+            asset_type: self.asset_type,
+            asset_name: self.asset_name.clone(),
+            field: field.into(),
+            severity,
+            message: message.into(),
+        });
+    }
+
+    fn name(&mut self, field: &str, name: &str) {
+        if name.is_empty() {
+            self.push(Severity::Error, field, "name is empty");
+        }
+    }
+
+    fn finite(&mut self, field: &str, v: f32) -> bool {
+        if v.is_finite() {
+            true
+        } else {
+            self.push(Severity::Error, field, format!("{v} is not finite"));
+            false
+        }
+    }
+
+    fn finite_vec3(&mut self, field: &str, v: Vec3) -> bool {
+        if v.get().iter().all(|c| c.is_finite()) {
+            true
+        } else {
+            self.push(
+                Severity::Error,
+                field,
+                format!("{:?} is not finite", v.get()),
+            );
+            false
+        }
+    }
+
+    fn non_negative(&mut self, field: &str, v: f32) {
+        if self.finite(field, v) && v < 0.0 {
+            self.push(Severity::Warning, field, format!("{v} is negative"));
+        }
+    }
+
+    fn non_negative_int(&mut self, field: &str, v: i32) {
+        if v < 0 {
+            self.push(Severity::Warning, field, format!("{v} is negative"));
+        }
+    }
+
+    fn index(&mut self, field: &str, i: usize, len: usize) {
+        if i >= len {
+            self.push(
+                Severity::Error,
+                field,
+                format!("index {i} is out of bounds (len {len})"),
+            );
+        }
+    }
+
+    fn not_sentinel(&mut self, field: &str, is_sentinel: bool, sentinel: &str) {
+        if is_sentinel {
+            self.push(
+                Severity::Error,
+                field,
+                format!("value is the {sentinel} sentinel"),
+            );
+        }
+    }
+
+    fn asset<const MAX_LOCAL_CLIENTS: usize>(&mut self, asset: &XAssetGeneric<MAX_LOCAL_CLIENTS>) {
+        match asset {
+            XAssetGeneric::Weapon(Some(w)) => self.weapon(w),
+            XAssetGeneric::Material(Some(m)) => self.material(m),
+            XAssetGeneric::Image(Some(i)) => self.image(i),
+            XAssetGeneric::PhysPreset(Some(p)) => self.phys_preset(p),
+            XAssetGeneric::ComWorld(Some(c)) => self.com_world(c),
+            XAssetGeneric::ClipMap(Some(c)) | XAssetGeneric::ClipMapPVS(Some(c)) => {
+                self.clip_map(c)
+            }
+            _ => {}
+        }
+    }
+
+    fn weapon(&mut self, w: &WeaponVariantDef) {
+        self.name("internal_name", w.internal_name.get());
+        self.non_negative_int("clip_size", w.clip_size);
+
+        for (field, v) in [
+            ("reload_time", w.reload_time),
+            ("reload_empty_time", w.reload_empty_time),
+            ("reload_quick_time", w.reload_quick_time),
+            ("reload_quick_empty_time", w.reload_quick_empty_time),
+            ("ads_trans_in_time", w.ads_trans_in_time),
+            ("ads_trans_out_time", w.ads_trans_out_time),
+            ("alt_raise_time", w.alt_raise_time),
+        ] {
+            self.non_negative_int(field, v);
+        }
+
+        for (field, v) in [
+            ("ads_zoom_fov_1", w.ads_zoom_fov_1),
+            ("ads_zoom_fov_2", w.ads_zoom_fov_2),
+            ("ads_zoom_fov_3", w.ads_zoom_fov_3),
+            ("ads_zoom_in_frac", w.ads_zoom_in_frac),
+            ("ads_zoom_out_frac", w.ads_zoom_out_frac),
+        ] {
+            self.non_negative(field, v);
+        }
+
+        match &w.weap_def {
+            Some(d) => self.weapon_def(d),
+            None => self.push(Severity::Info, "weap_def", "variant has no weapon def"),
+        }
+    }
+
+    fn weapon_def(&mut self, d: &WeaponDef) {
+        for (field, is_sentinel, sentinel) in [
+            ("weap_def.weap_type", d.weap_type == WeapType::NUM, "NUM"),
+            ("weap_def.weap_class", d.weap_class == WeapClass::NUM, "NUM"),
+            (
+                "weap_def.penetrate_type",
+                d.penetrate_type == PenetrateType::COUNT,
+                "COUNT",
+            ),
+            (
+                "weap_def.impact_type",
+                d.impact_type == ImpactType::COUNT,
+                "COUNT",
+            ),
+            (
+                "weap_def.inventory_type",
+                d.inventory_type == WeapInventoryType::COUNT,
+                "COUNT",
+            ),
+            (
+                "weap_def.fire_type",
+                d.fire_type == WeapFireType::COUNT,
+                "COUNT",
+            ),
+            (
+                "weap_def.clip_type",
+                d.clip_type == WeapClipType::COUNT,
+                "COUNT",
+            ),
+            (
+                "weap_def.offhand_class",
+                d.offhand_class == OffhandClass::COUNT,
+                "COUNT",
+            ),
+            (
+                "weap_def.active_reticle_type",
+                d.active_reticle_type == ActiveReticleType::COUNT,
+                "COUNT",
+            ),
+        ] {
+            self.not_sentinel(field, is_sentinel, sentinel);
+        }
+
+        for (field, v) in [
+            ("weap_def.fire_time", d.fire_time),
+            ("weap_def.last_fire_time", d.last_fire_time),
+            ("weap_def.rechamber_time", d.rechamber_time),
+            ("weap_def.hold_fire_time", d.hold_fire_time),
+            ("weap_def.melee_time", d.melee_time),
+            ("weap_def.drop_time", d.drop_time),
+            ("weap_def.raise_time", d.raise_time),
+            ("weap_def.first_raise_time", d.first_raise_time),
+            ("weap_def.sprint_in_time", d.sprint_in_time),
+            ("weap_def.sprint_out_time", d.sprint_out_time),
+            ("weap_def.fuse_time", d.fuse_time),
+        ] {
+            self.non_negative_int(field, v);
+        }
+
+        self.non_negative_int("weap_def.damage", d.damage);
+        self.non_negative_int("weap_def.min_damage", d.min_damage);
+        self.non_negative("weap_def.damage_duration", d.damage_duration);
+        self.non_negative("weap_def.damage_interval", d.damage_interval);
+        self.non_negative("weap_def.max_range", d.max_range);
+
+        let max_ok = self.finite("weap_def.max_damage_range", d.max_damage_range);
+        let min_ok = self.finite("weap_def.min_damage_range", d.min_damage_range);
+        if max_ok && min_ok && d.min_damage_range < d.max_damage_range {
+            self.push(
+                Severity::Warning,
+                "weap_def.min_damage_range",
+                format!(
+                    "{} is less than max_damage_range ({})",
+                    d.min_damage_range, d.max_damage_range
+                ),
+            );
+        }
+
+        if let Some(m) = &d.location_damage_multipliers {
+            for (i, &v) in m.iter().enumerate() {
+                self.non_negative(&format!("weap_def.location_damage_multipliers[{i}]"), v);
+            }
+        }
+    }
+
+    fn material(&mut self, m: &Material) {
+        self.name("info.name", m.info.name.get());
+
+        for (i, &e) in m.state_bits_entry.iter().enumerate() {
+            // 0xFF means the technique isn't used.
+            if e != 0xFF {
+                self.index(
+                    &format!("state_bits_entry[{i}]"),
+                    e as _,
+                    m.state_bits.len(),
+                );
+            }
+        }
+
+        if m.technique_set.is_none() {
+            self.push(
+                Severity::Info,
+                "technique_set",
+                "material has no technique set",
+            );
+        }
+    }
+
+    fn image(&mut self, i: &GfxImage) {
+        self.name("name", i.name.get());
+
+        if i.semantic().is_none() {
+            self.push(
+                Severity::Warning,
+                "semantic",
+                format!("unknown semantic {:#04X}", i.semantic),
+            );
+        }
+        if i.category().is_none() {
+            self.push(
+                Severity::Warning,
+                "category",
+                format!("unknown category {:#04X}", i.category),
+            );
+        }
+
+        let unknown = i.load_flags().map_or(0, |f| f.unknown_bits());
+        if unknown != 0 {
+            self.push(
+                Severity::Warning,
+                "texture.flags",
+                format!("unknown flag bits {unknown:#04X}"),
+            );
+        }
+    }
+
+    fn phys_preset(&mut self, p: &PhysPreset) {
+        self.name("name", p.name.get());
+
+        if self.finite("mass", p.mass) {
+            if p.mass < 0.0 {
+                self.push(Severity::Warning, "mass", format!("{} is negative", p.mass));
+            } else if p.mass == 0.0 {
+                self.push(Severity::Info, "mass", "mass is zero");
+            }
+        }
+
+        self.non_negative("bounce", p.bounce);
+        self.non_negative("friction", p.friction);
+        self.finite("bullet_force_scale", p.bullet_force_scale);
+        self.finite("explosive_force_scale", p.explosive_force_scale);
+        self.finite("pieces_spread_fraction", p.pieces_spread_fraction);
+        self.finite("pieces_upward_velocity", p.pieces_upward_velocity);
+        self.finite("gravity_scale", p.gravity_scale);
+        self.finite_vec3("center_of_mass_offset", p.center_of_mass_offset);
+
+        let min_ok = self.finite_vec3("buoyancy_box_min", p.buoyancy_box_min);
+        let max_ok = self.finite_vec3("buoyancy_box_max", p.buoyancy_box_max);
+        if min_ok
+            && max_ok
+            && p.buoyancy_box_min
+                .get()
+                .iter()
+                .zip(p.buoyancy_box_max.get())
+                .any(|(&min, max)| min > max)
+        {
+            self.push(
+                Severity::Warning,
+                "buoyancy_box_min",
+                "buoyancy box min is greater than max",
+            );
+        }
+    }
+
+    fn com_world(&mut self, c: &ComWorld) {
+        self.name("name", c.name.get());
+
+        // Light 0 is always an unused placeholder.
+        for (i, l) in c.primary_lights.iter().enumerate().skip(1) {
+            let field = |f: &str| format!("primary_lights[{i}].{f}");
+
+            self.finite_vec3(&field("color"), l.color);
+            self.finite_vec3(&field("dir"), l.dir);
+            self.finite_vec3(&field("origin"), l.origin);
+
+            // Type 1 is the sun, which has no radius.
+            if self.finite(&field("radius"), l.radius) && l.type_ > 1 && l.radius <= 0.0 {
+                self.push(
+                    Severity::Warning,
+                    field("radius"),
+                    format!("{} is not positive", l.radius),
+                );
+            }
+        }
+
+        let h = c.water_header;
+        if h.minx > h.maxx || h.miny > h.maxy {
+            self.push(
+                Severity::Warning,
+                "water_header",
+                format!(
+                    "mins ({}, {}) are greater than maxs ({}, {})",
+                    h.minx, h.miny, h.maxx, h.maxy
+                ),
+            );
+        }
+    }
+
+    fn clip_map(&mut self, c: &ClipMap) {
+        self.name("name", c.name.get());
+
+        for basis in [DynEntityBasis::MODEL, DynEntityBasis::BRUSH] {
+            let (count, server_count) = (c.dyn_ent_count(basis), c.server_dyn_ent_count(basis));
+            if server_count > count {
+                self.push(
+                    Severity::Warning,
+                    format!("dyn_ent_count[{}]", 2 + basis as usize),
+                    format!("{server_count} server dyn ents, but only {count} in total"),
+                );
+            }
+
+            let poses = c.dyn_ent_pose_list[basis as usize].len();
+            if poses != count {
+                self.push(
+                    Severity::Error,
+                    format!("dyn_ent_pose_list[{}]", basis as usize),
+                    format!("{poses} poses for {count} dyn ents"),
+                );
+            }
+        }
+
+        for e in c.dyn_entities() {
+            let field =
+                |f: &str| format!("dyn_ent_def_list[{}][{}].{f}", e.basis as usize, e.index);
+
+            self.not_sentinel(
+                &field("type_"),
+                e.def.type_ == DynEntityType::INVALID,
+                "INVALID",
+            );
+            self.not_sentinel(
+                &field("type_"),
+                e.def.type_ == DynEntityType::COUNT,
+                "COUNT",
+            );
+
+            self.finite_vec3(&field("pose.origin"), e.def.pose.origin);
+            match e.basis {
+                DynEntityBasis::MODEL if e.def.xmodel.is_none() => {
+                    self.push(Severity::Warning, field("xmodel"), "model is missing")
+                }
+                DynEntityBasis::BRUSH => self.index(
+                    &field("brush_model"),
+                    e.def.brush_model as _,
+                    c.cmodels.len(),
+                ),
+                _ => {}
+            }
+        }
+    }
+}
@@ -1,15 +1,22 @@
 use alloc::{boxed::Box, vec::Vec};
+use core::ops::{Deref, DerefMut};
 
 use crate::{
-    Error, ErrorKind, FatPointer, Ptr32, Ptr32ArrayConst, Result, ScriptString, T5XFileDeserialize,
-    XFileDeserializeInto, XString, XStringRaw, assert_size,
+    Error, ErrorKind, FatPointer, Result, ScriptString, T5XFileDeserialize, T5XFileSerialize,
+    XFileDeserializeInto, XFileSerialize, XString, assert_size,
     common::{Vec2, Vec3},
-    file_line_col, fx, techset, xmodel,
+    file_line_col, fx,
+    layout::{self, RETAIL_WEAPON_XANIM_COUNT},
+    techset,
+    util::{Ptr32, Ptr32ArrayConst, XStringRaw},
+    xanim::XAnimParts,
+    xmodel,
 };
 
 use num::FromPrimitive;
 use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
+use t5_xfile_derive::XFileConvert;
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Clone, Default, Debug, Deserialize)]
@@ -18,7 +25,7 @@ pub(crate) struct WeaponVariantDefRaw<'a> {
     pub variant_count: i32,
     pub weap_def: Ptr32<'a, WeaponDefRaw<'a>>,
     pub display_name: XStringRaw<'a>,
-    pub xanims: Ptr32ArrayConst<'a, XStringRaw<'a>, 66>,
+    pub xanims: Ptr32ArrayConst<'a, XStringRaw<'a>, RETAIL_WEAPON_XANIM_COUNT>,
     pub alt_weapon_name: XStringRaw<'a>,
     pub hide_tags: Ptr32ArrayConst<'a, ScriptString, 32>,
     pub alt_weapon_index: u32,
@@ -82,7 +89,7 @@ pub struct WeaponVariantDef {
     pub variant_count: usize,
     pub weap_def: Option<Box<WeaponDef>>,
     pub display_name: XString,
-    pub xanims: Vec<XString>,
+    pub xanims: WeaponXAnims,
     pub alt_weapon_name: XString,
     pub hide_tags: Vec<XString>,
     pub alt_weapon_index: u32,
@@ -133,16 +140,26 @@ impl<'a> XFileDeserializeInto<WeaponVariantDef, ()> for WeaponVariantDefRaw<'a>
         de: &mut impl T5XFileDeserialize,
         _data: (),
     ) -> Result<WeaponVariantDef> {
+        let overrides = de.layout_overrides();
+        layout::skip_pad(de, overrides.weapon_variant_def_pad)?;
+
         let internal_name = self.internal_name.xfile_deserialize_into(de, ())?;
         let weap_def = self.weap_def.xfile_deserialize_into(de, ())?;
         let display_name = self.display_name.xfile_deserialize_into(de, ())?;
-        let xanims = self.xanims.xfile_deserialize_into(de, ())?;
+        let xanims = match overrides.weapon_xanim_count {
+            Some(count) => self
+                .xanims
+                .p()
+                .to_array(count)
+                .xfile_deserialize_into(de, ())?,
+            None => self.xanims.xfile_deserialize_into(de, ())?,
+        };
         let alt_weapon_name = self.alt_weapon_name.xfile_deserialize_into(de, ())?;
         let hide_tags = self
             .hide_tags
             .to_vec(de)?
             .into_iter()
-            .map(|s| XString(s.to_string(de).unwrap_or_default()))
+            .map(|s| XString::from(s.to_string(de).unwrap_or_default()))
             .collect();
         let ammo_name = self.ammo_name.xfile_deserialize_into(de, ())?;
         let clip_name = self.clip_name.xfile_deserialize_into(de, ())?;
@@ -170,7 +187,7 @@ impl<'a> XFileDeserializeInto<WeaponVariantDef, ()> for WeaponVariantDefRaw<'a>
             variant_count: self.variant_count as _,
             weap_def,
             display_name,
-            xanims,
+            xanims: WeaponXAnims(xanims),
             alt_weapon_name,
             hide_tags,
             alt_weapon_index: self.alt_weapon_index,
@@ -217,6 +234,308 @@ impl<'a> XFileDeserializeInto<WeaponVariantDef, ()> for WeaponVariantDefRaw<'a>
     }
 }
 
+impl XFileSerialize<()> for WeaponVariantDef {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let overrides = ser.layout_overrides();
+
+        // Both arrays are read back at their full length, so pad them out to
+        // it. Empty ones are stored as null pointers.
+        let xanim_count = overrides
+            .weapon_xanim_count
+            .unwrap_or(RETAIL_WEAPON_XANIM_COUNT);
+        let xanims = padded(&self.xanims, xanim_count, "xanims")?;
+        let hide_tags = padded(&self.hide_tags, 32, "hide_tags")?;
+
+        let weapon_variant_def = WeaponVariantDefRaw {
+            internal_name: XStringRaw::from_str(self.internal_name.get()),
+            variant_count: self.variant_count as _,
+            weap_def: Ptr32::from_box(&self.weap_def),
+            display_name: XStringRaw::from_str(self.display_name.get()),
+            // Stored in full or not at all, whatever its length.
+            xanims: Ptr32ArrayConst::from_option(&xanims.first()),
+            alt_weapon_name: XStringRaw::from_str(self.alt_weapon_name.get()),
+            hide_tags: Ptr32ArrayConst::from_slice(&hide_tags),
+            alt_weapon_index: self.alt_weapon_index,
+            clip_size: self.clip_size,
+            reload_time: self.reload_time,
+            reload_empty_time: self.reload_empty_time,
+            reload_quick_time: self.reload_quick_time,
+            reload_quick_empty_time: self.reload_quick_empty_time,
+            ads_trans_in_time: self.ads_trans_in_time,
+            ads_trans_out_time: self.ads_trans_out_time,
+            alt_raise_time: self.alt_raise_time,
+            ammo_name: XStringRaw::from_str(self.ammo_name.get()),
+            ammo_index: self.ammo_index as _,
+            clip_name: XStringRaw::from_str(self.clip_name.get()),
+            clip_index: self.clip_index as _,
+            aim_assist_range_ads: self.aim_assist_range_ads,
+            ads_sway_horiz_scale: self.ads_sway_horiz_scale,
+            ads_sway_vert_scale: self.ads_sway_vert_scale,
+            ads_view_kick_center_speed: self.ads_view_kick_center_speed,
+            hip_view_kick_center_speed: self.hip_view_kick_center_speed,
+            ads_zoom_fov_1: self.ads_zoom_fov_1,
+            ads_zoom_fov_2: self.ads_zoom_fov_2,
+            ads_zoom_fov_3: self.ads_zoom_fov_3,
+            ads_zoom_in_frac: self.ads_zoom_in_frac,
+            ads_zoom_out_frac: self.ads_zoom_out_frac,
+            overlay_alpha_scale: self.overlay_alpha_scale,
+            oo_pos_anim_length: self.oo_pos_anim_length.get(),
+            silenced: self.silenced,
+            dual_mag: self.dual_mag,
+            full_metal_jacket: self.full_metal_jacket,
+            hollow_point: self.hollow_point,
+            rapid_fire: self.rapid_fire,
+            pad: [0u8; 3],
+            overlay_material: Ptr32::from_box(&self.overlay_material),
+            overlay_material_low_res: Ptr32::from_box(&self.overlay_material_low_res),
+            dpad_icon: Ptr32::from_box(&self.dpad_icon),
+            dpad_icon_ratio: self.dpad_icon_ratio as _,
+            left_hand_offset: self.left_hand_offset.get(),
+            left_hand_rotation: self.left_hand_rotation.get(),
+            left_hand_prone_offset: self.left_hand_prone_offset.get(),
+            left_hand_prone_rotation: self.left_hand_prone_rotation.get(),
+            left_hand_ui_viewer_offset: self.left_hand_ui_viewer_offset.get(),
+            left_hand_ui_viewer_rotation: self.left_hand_ui_viewer_rotation.get(),
+        };
+
+        ser.store_into_xfile(weapon_variant_def)?;
+        layout::store_pad(ser, overrides.weapon_variant_def_pad)?;
+        self.internal_name.xfile_serialize(ser, ())?;
+        self.weap_def.xfile_serialize(ser, ())?;
+        self.display_name.xfile_serialize(ser, ())?;
+        for xanim in &xanims {
+            ser.store_into_xfile(XStringRaw::from_str(xanim.get()))?;
+        }
+        xanims.xfile_serialize(ser, ())?;
+        self.alt_weapon_name.xfile_serialize(ser, ())?;
+        for tag in &hide_tags {
+            let tag = ser.intern(tag.get())?;
+            ser.store_into_xfile(tag)?;
+        }
+        self.ammo_name.xfile_serialize(ser, ())?;
+        self.clip_name.xfile_serialize(ser, ())?;
+        self.overlay_material.xfile_serialize(ser, ())?;
+        self.overlay_material_low_res.xfile_serialize(ser, ())?;
+        self.dpad_icon.xfile_serialize(ser, ())
+    }
+}
+
+/// `strings` padded with empty strings to `len`, or left empty if it is.
+fn padded(strings: &[XString], len: usize, what: &str) -> Result<Vec<XString>> {
+    if strings.len() > len {
+        return Err(Error::new(
+            file_line_col!(),
+            ErrorKind::BrokenInvariant(alloc::format!(
+                "WeaponVariantDef: {} {what} (must be <={len}).",
+                strings.len()
+            )),
+        ));
+    }
+
+    let mut strings = strings.to_vec();
+    if !strings.is_empty() {
+        strings.resize(len, XString::new());
+    }
+    Ok(strings)
+}
+
+/// A weapon's animations, indexed by [`WeaponAnimSlot`].
+///
+/// Serialized as the flat array it wraps, and derefs to it, so slots past
+/// the retail count (see [`LayoutOverrides::weapon_xanim_count`]) can still
+/// be reached by index.
+///
+/// [`LayoutOverrides::weapon_xanim_count`]: crate::layout::LayoutOverrides::weapon_xanim_count
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Clone, Default, Debug)]
+pub struct WeaponXAnims(pub Vec<XString>);
+
+impl WeaponXAnims {
+    /// The animation in `slot`, or [`None`] if there isn't one.
+    pub fn get(&self, slot: WeaponAnimSlot) -> Option<&XString> {
+        self.0.get(slot as usize).filter(|a| !a.get().is_empty())
+    }
+
+    /// Iterates over every animation that's set, labeled with its slot.
+    pub fn by_slot(&self) -> impl Iterator<Item = (WeaponAnimSlot, &XString)> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| !a.get().is_empty())
+            .filter_map(|(i, a)| WeaponAnimSlot::from_usize(i).map(|s| (s, a)))
+    }
+}
+
+impl From<Vec<XString>> for WeaponXAnims {
+    fn from(value: Vec<XString>) -> Self {
+        Self(value)
+    }
+}
+
+impl Deref for WeaponXAnims {
+    type Target = [XString];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for WeaponXAnims {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Indexes [`WeaponXAnims`], in the engine's order.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+pub enum WeaponAnimSlot {
+    #[default]
+    ROOT = 0,
+    IDLE = 1,
+    EMPTY_IDLE = 2,
+    FIRE_INTRO = 3,
+    FIRE = 4,
+    HOLD_FIRE = 5,
+    LASTSHOT = 6,
+    FINALSHOT = 7,
+    RECHAMBER = 8,
+    MELEE = 9,
+    MELEE1 = 10,
+    MELEE2 = 11,
+    MELEE3 = 12,
+    MELEE_EMPTY = 13,
+    MELEE_CHARGE = 14,
+    MELEE_CHARGE_EMPTY = 15,
+    RELOAD = 16,
+    RELOAD_RIGHT = 17,
+    RELOAD_EMPTY = 18,
+    RELOAD_START = 19,
+    RELOAD_END = 20,
+    RELOAD_QUICK = 21,
+    RELOAD_QUICK_EMPTY = 22,
+    RAISE = 23,
+    FIRST_RAISE = 24,
+    DROP = 25,
+    ALT_RAISE = 26,
+    ALT_DROP = 27,
+    QUICK_RAISE = 28,
+    QUICK_DROP = 29,
+    EMPTY_RAISE = 30,
+    EMPTY_DROP = 31,
+    SPRINT_IN = 32,
+    SPRINT_LOOP = 33,
+    SPRINT_OUT = 34,
+    SPRINT_EMPTY_IN = 35,
+    SPRINT_EMPTY_LOOP = 36,
+    SPRINT_EMPTY_OUT = 37,
+    LOWREADY_IN = 38,
+    LOWREADY_LOOP = 39,
+    LOWREADY_OUT = 40,
+    CONT_FIRE_IN = 41,
+    CONT_FIRE_LOOP = 42,
+    CONT_FIRE_OUT = 43,
+    CRAWL_IN = 44,
+    CRAWL_FORWARD = 45,
+    CRAWL_BACK = 46,
+    CRAWL_RIGHT = 47,
+    CRAWL_LEFT = 48,
+    CRAWL_OUT = 49,
+    CRAWL_EMPTY_IN = 50,
+    CRAWL_EMPTY_FORWARD = 51,
+    CRAWL_EMPTY_BACK = 52,
+    CRAWL_EMPTY_RIGHT = 53,
+    CRAWL_EMPTY_LEFT = 54,
+    CRAWL_EMPTY_OUT = 55,
+    DEPLOY = 56,
+    BREAKDOWN = 57,
+    DETONATE = 58,
+    NIGHTVISION_WEAR = 59,
+    NIGHTVISION_REMOVE = 60,
+    ADS_FIRE = 61,
+    ADS_LASTSHOT = 62,
+    ADS_RECHAMBER = 63,
+    ADS_UP = 64,
+    ADS_DOWN = 65,
+    NUM = 66,
+}
+
+impl WeaponAnimSlot {
+    /// The key the slot's animation is given by in weapon files. The root
+    /// slot isn't set from weapon files, so it doesn't have one.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::IDLE => "idleAnim",
+            Self::EMPTY_IDLE => "emptyIdleAnim",
+            Self::FIRE_INTRO => "fireIntroAnim",
+            Self::FIRE => "fireAnim",
+            Self::HOLD_FIRE => "holdFireAnim",
+            Self::LASTSHOT => "lastShotAnim",
+            Self::FINALSHOT => "finalShotAnim",
+            Self::RECHAMBER => "rechamberAnim",
+            Self::MELEE => "meleeAnim",
+            Self::MELEE1 => "meleeAnim1",
+            Self::MELEE2 => "meleeAnim2",
+            Self::MELEE3 => "meleeAnim3",
+            Self::MELEE_EMPTY => "meleeEmptyAnim",
+            Self::MELEE_CHARGE => "meleeChargeAnim",
+            Self::MELEE_CHARGE_EMPTY => "meleeChargeEmptyAnim",
+            Self::RELOAD => "reloadAnim",
+            Self::RELOAD_RIGHT => "reloadAnimRight",
+            Self::RELOAD_EMPTY => "reloadEmptyAnim",
+            Self::RELOAD_START => "reloadStartAnim",
+            Self::RELOAD_END => "reloadEndAnim",
+            Self::RELOAD_QUICK => "reloadQuickAnim",
+            Self::RELOAD_QUICK_EMPTY => "reloadQuickEmptyAnim",
+            Self::RAISE => "raiseAnim",
+            Self::FIRST_RAISE => "firstRaiseAnim",
+            Self::DROP => "dropAnim",
+            Self::ALT_RAISE => "altRaiseAnim",
+            Self::ALT_DROP => "altDropAnim",
+            Self::QUICK_RAISE => "quickRaiseAnim",
+            Self::QUICK_DROP => "quickDropAnim",
+            Self::EMPTY_RAISE => "emptyRaiseAnim",
+            Self::EMPTY_DROP => "emptyDropAnim",
+            Self::SPRINT_IN => "sprintInAnim",
+            Self::SPRINT_LOOP => "sprintLoopAnim",
+            Self::SPRINT_OUT => "sprintOutAnim",
+            Self::SPRINT_EMPTY_IN => "sprintInEmptyAnim",
+            Self::SPRINT_EMPTY_LOOP => "sprintLoopEmptyAnim",
+            Self::SPRINT_EMPTY_OUT => "sprintOutEmptyAnim",
+            Self::LOWREADY_IN => "lowReadyInAnim",
+            Self::LOWREADY_LOOP => "lowReadyLoopAnim",
+            Self::LOWREADY_OUT => "lowReadyOutAnim",
+            Self::CONT_FIRE_IN => "contFireInAnim",
+            Self::CONT_FIRE_LOOP => "contFireLoopAnim",
+            Self::CONT_FIRE_OUT => "contFireOutAnim",
+            Self::CRAWL_IN => "crawlInAnim",
+            Self::CRAWL_FORWARD => "crawlForwardAnim",
+            Self::CRAWL_BACK => "crawlBackAnim",
+            Self::CRAWL_RIGHT => "crawlRightAnim",
+            Self::CRAWL_LEFT => "crawlLeftAnim",
+            Self::CRAWL_OUT => "crawlOutAnim",
+            Self::CRAWL_EMPTY_IN => "crawlEmptyInAnim",
+            Self::CRAWL_EMPTY_FORWARD => "crawlEmptyForwardAnim",
+            Self::CRAWL_EMPTY_BACK => "crawlEmptyBackAnim",
+            Self::CRAWL_EMPTY_RIGHT => "crawlEmptyRightAnim",
+            Self::CRAWL_EMPTY_LEFT => "crawlEmptyLeftAnim",
+            Self::CRAWL_EMPTY_OUT => "crawlEmptyOutAnim",
+            Self::DEPLOY => "deployAnim",
+            Self::BREAKDOWN => "breakdownAnim",
+            Self::DETONATE => "detonateAnim",
+            Self::NIGHTVISION_WEAR => "nightVisionWearAnim",
+            Self::NIGHTVISION_REMOVE => "nightVisionRemoveAnim",
+            Self::ADS_FIRE => "adsFireAnim",
+            Self::ADS_LASTSHOT => "adsLastShotAnim",
+            Self::ADS_RECHAMBER => "adsRechamberAnim",
+            Self::ADS_UP => "adsUpAnim",
+            Self::ADS_DOWN => "adsDownAnim",
+            Self::ROOT | Self::NUM => "",
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Clone, Default, Debug, Deserialize)]
 pub(crate) struct WeaponDefRaw<'a> {
@@ -1093,6 +1412,7 @@ pub struct WeaponDef {
     pub overheat_sound: XString,
     pub overheat_sound_player: XString,
     pub ads_zoom_sound: XString,
+    /// Indexed by [`fx::SurfaceType`]. See [`Self::bounce_sound_for`].
     pub bounce_sound: Option<Box<[XString; 31]>>,
     pub stand_mounted_weapdef: XString,
     pub crouch_mounted_weapdef: XString,
@@ -1397,6 +1717,7 @@ pub struct WeaponDef {
     pub use_as_melee: bool,
     pub is_camera_sensor: bool,
     pub is_acoustic_sensor: bool,
+    /// Indexed by [`fx::SurfaceType`]. See [`Self::bounce_for`].
     pub parallel_bounce: Option<Box<[f32; 31]>>,
     pub perpendicular_bounce: Option<Box<[f32; 31]>>,
     pub proj_tail_effect: Option<Box<fx::FxEffectDef>>,
@@ -1506,6 +1827,8 @@ impl<'a> XFileDeserializeInto<WeaponDef, ()> for WeaponDefRaw<'a> {
         de: &mut impl T5XFileDeserialize,
         _data: (),
     ) -> Result<WeaponDef> {
+        layout::skip_pad(de, de.layout_overrides().weapon_def_pad)?;
+
         let overlay_name = self.overlay_name.xfile_deserialize_into(de, ())?;
         let gun_xmodel = if self.gun_xmodel.is_null() {
             None
@@ -1772,7 +2095,7 @@ impl<'a> XFileDeserializeInto<WeaponDef, ()> for WeaponDefRaw<'a> {
                 ErrorKind::BadFromPrimitive(self.ammo_counter_clip as _),
             ))?;
         let shared_ammo_cap_name = self.shared_ammo_cap_name.xfile_deserialize_into(de, ())?;
-        let explosion_tag = XString(self.explosion_tag.to_string(de).unwrap_or_default());
+        let explosion_tag = XString::from(self.explosion_tag.to_string(de).unwrap_or_default());
         let spin_loop_sound = self.spin_loop_sound.xfile_deserialize_into(de, ())?;
         let spin_loop_sound_player = self.spin_loop_sound_player.xfile_deserialize_into(de, ())?;
         let start_spin_sound = self.start_spin_sound.xfile_deserialize_into(de, ())?;
@@ -1871,7 +2194,7 @@ impl<'a> XFileDeserializeInto<WeaponDef, ()> for WeaponDefRaw<'a> {
         let proj_ignition_effect = self.proj_ignition_effect.xfile_deserialize_into(de, ())?;
         let proj_ignition_sound = self.proj_ignition_sound.xfile_deserialize_into(de, ())?;
 
-        let mut accuracy_graph_name = [const { XString::new() }; 2];
+        let mut accuracy_graph_name = [XString::new(), XString::new()];
         let mut accuracy_graph_knots = [const { Vec::new() }; 2];
         let mut original_accuracy_graph_knots = [const { Vec::new() }; 2];
         for i in 0..=1 {
@@ -2424,8 +2747,1136 @@ impl<'a> XFileDeserializeInto<WeaponDef, ()> for WeaponDefRaw<'a> {
     }
 }
 
+impl XFileSerialize<()> for WeaponDef {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let weapon_def = WeaponDefRaw {
+            overlay_name: XStringRaw::from_str(self.overlay_name.get()),
+            gun_xmodel: Ptr32ArrayConst::from_option(&self.gun_xmodel),
+            hand_xmodel: Ptr32::from_box(&self.hand_xmodel),
+            mode_name: XStringRaw::from_str(self.mode_name.get()),
+            notetrack_sound_map_keys: Ptr32ArrayConst::from_option(&self.notetrack_sound_map_keys),
+            notetrack_sound_map_values: Ptr32ArrayConst::from_option(
+                &self.notetrack_sound_map_values,
+            ),
+            player_anim_type: self.player_anim_type,
+            weap_type: self.weap_type as _,
+            weap_class: self.weap_class as _,
+            penetrate_type: self.penetrate_type as _,
+            impact_type: self.impact_type as _,
+            inventory_type: self.inventory_type as _,
+            fire_type: self.fire_type as _,
+            clip_type: self.clip_type as _,
+            item_index: self.item_index as _,
+            parent_weapon_name: XStringRaw::from_str(self.parent_weapon_name.get()),
+            jam_fire_time: self.jam_fire_time,
+            tracer_frequency: self.tracer_frequency,
+            tracer_width: self.tracer_width,
+            tracer_length: self.tracer_length,
+            overheat_weapon: self.overheat_weapon,
+            overheat_rate: self.overheat_rate,
+            cooldown_rate: self.cooldown_rate,
+            overheat_end_val: self.overheat_end_val,
+            cool_while_firing: self.cool_while_firing,
+            fuel_tank_weapon: self.fuel_tank_weapon,
+            pad: [0u8; 2],
+            tank_life_time: self.tank_life_time,
+            offhand_class: self.offhand_class as _,
+            offhand_slot: self.offhand_slot as _,
+            stance: self.stance as _,
+            view_flash_effect: Ptr32::from_box(&self.view_flash_effect),
+            world_flash_effect: Ptr32::from_box(&self.world_flash_effect),
+            pickup_sound: XStringRaw::from_str(self.pickup_sound.get()),
+            pickup_sound_player: XStringRaw::from_str(self.pickup_sound_player.get()),
+            ammo_pickup_sound: XStringRaw::from_str(self.ammo_pickup_sound.get()),
+            ammo_pickup_sound_player: XStringRaw::from_str(self.ammo_pickup_sound_player.get()),
+            projectile_sound: XStringRaw::from_str(self.projectile_sound.get()),
+            pullback_sound: XStringRaw::from_str(self.pullback_sound.get()),
+            pullback_sound_player: XStringRaw::from_str(self.pullback_sound_player.get()),
+            fire_sound: XStringRaw::from_str(self.fire_sound.get()),
+            fire_sound_player: XStringRaw::from_str(self.fire_sound_player.get()),
+            fire_loop_sound: XStringRaw::from_str(self.fire_loop_sound.get()),
+            fire_loop_sound_player: XStringRaw::from_str(self.fire_loop_sound_player.get()),
+            fire_loop_end_sound: XStringRaw::from_str(self.fire_loop_end_sound.get()),
+            fire_loop_end_sound_player: XStringRaw::from_str(self.fire_loop_end_sound_player.get()),
+            fire_stop_sound: XStringRaw::from_str(self.fire_stop_sound.get()),
+            fire_stop_sound_player: XStringRaw::from_str(self.fire_stop_sound_player.get()),
+            fire_last_sound: XStringRaw::from_str(self.fire_last_sound.get()),
+            fire_last_sound_player: XStringRaw::from_str(self.fire_last_sound_player.get()),
+            empty_fire_sound: XStringRaw::from_str(self.empty_fire_sound.get()),
+            empty_fire_sound_player: XStringRaw::from_str(self.empty_fire_sound_player.get()),
+            crack_sound: XStringRaw::from_str(self.crack_sound.get()),
+            whiz_by_sound: XStringRaw::from_str(self.whiz_by_sound.get()),
+            melee_swipe_sound: XStringRaw::from_str(self.melee_swipe_sound.get()),
+            melee_swipe_sound_player: XStringRaw::from_str(self.melee_swipe_sound_player.get()),
+            melee_hit_sound: XStringRaw::from_str(self.melee_hit_sound.get()),
+            melee_miss_sound: XStringRaw::from_str(self.melee_miss_sound.get()),
+            rechamber_sound: XStringRaw::from_str(self.rechamber_sound.get()),
+            rechamber_sound_player: XStringRaw::from_str(self.rechamber_sound_player.get()),
+            reload_sound: XStringRaw::from_str(self.reload_sound.get()),
+            reload_sound_player: XStringRaw::from_str(self.reload_sound_player.get()),
+            reload_empty_sound: XStringRaw::from_str(self.reload_empty_sound.get()),
+            reload_empty_sound_player: XStringRaw::from_str(self.reload_empty_sound_player.get()),
+            reload_start_sound: XStringRaw::from_str(self.reload_start_sound.get()),
+            reload_start_sound_player: XStringRaw::from_str(self.reload_start_sound_player.get()),
+            reload_end_sound: XStringRaw::from_str(self.reload_end_sound.get()),
+            reload_end_sound_player: XStringRaw::from_str(self.reload_end_sound_player.get()),
+            rotate_loop_sound: XStringRaw::from_str(self.rotate_loop_sound.get()),
+            rotate_loop_sound_player: XStringRaw::from_str(self.rotate_loop_sound_player.get()),
+            deploy_sound: XStringRaw::from_str(self.deploy_sound.get()),
+            deploy_sound_player: XStringRaw::from_str(self.deploy_sound_player.get()),
+            finish_deploy_sound: XStringRaw::from_str(self.finish_deploy_sound.get()),
+            finish_deploy_sound_player: XStringRaw::from_str(self.finish_deploy_sound_player.get()),
+            breakdown_sound: XStringRaw::from_str(self.breakdown_sound.get()),
+            breakdown_sound_player: XStringRaw::from_str(self.breakdown_sound_player.get()),
+            finish_breakdown_sound: XStringRaw::from_str(self.finish_breakdown_sound.get()),
+            finish_breakdown_sound_player: XStringRaw::from_str(
+                self.finish_breakdown_sound_player.get(),
+            ),
+            detonate_sound: XStringRaw::from_str(self.detonate_sound.get()),
+            detonate_sound_player: XStringRaw::from_str(self.detonate_sound_player.get()),
+            night_vision_wear_sound: XStringRaw::from_str(self.night_vision_wear_sound.get()),
+            night_vision_wear_sound_player: XStringRaw::from_str(
+                self.night_vision_wear_sound_player.get(),
+            ),
+            night_vision_remove_sound: XStringRaw::from_str(self.night_vision_remove_sound.get()),
+            night_vision_remove_sound_player: XStringRaw::from_str(
+                self.night_vision_remove_sound_player.get(),
+            ),
+            alt_switch_sound: XStringRaw::from_str(self.alt_switch_sound.get()),
+            alt_switch_sound_player: XStringRaw::from_str(self.alt_switch_sound_player.get()),
+            raise_sound: XStringRaw::from_str(self.raise_sound.get()),
+            raise_sound_player: XStringRaw::from_str(self.raise_sound_player.get()),
+            first_raise_sound: XStringRaw::from_str(self.first_raise_sound.get()),
+            first_raise_sound_player: XStringRaw::from_str(self.first_raise_sound_player.get()),
+            put_away_sound: XStringRaw::from_str(self.put_away_sound.get()),
+            put_away_sound_player: XStringRaw::from_str(self.put_away_sound_player.get()),
+            overheat_sound: XStringRaw::from_str(self.overheat_sound.get()),
+            overheat_sound_player: XStringRaw::from_str(self.overheat_sound_player.get()),
+            ads_zoom_sound: XStringRaw::from_str(self.ads_zoom_sound.get()),
+            bounce_sound: Ptr32ArrayConst::from_option(&self.bounce_sound),
+            stand_mounted_weapdef: XStringRaw::from_str(self.stand_mounted_weapdef.get()),
+            crouch_mounted_weapdef: XStringRaw::from_str(self.crouch_mounted_weapdef.get()),
+            prone_mounted_weapdef: XStringRaw::from_str(self.prone_mounted_weapdef.get()),
+            stand_mounted_index: self.stand_mounted_index as _,
+            crouch_mounted_index: self.crouch_mounted_index as _,
+            prone_mounted_index: self.prone_mounted_index as _,
+            view_shell_eject_effect: Ptr32::from_box(&self.view_shell_eject_effect),
+            world_shell_eject_effect: Ptr32::from_box(&self.world_shell_eject_effect),
+            view_last_shot_eject_effect: Ptr32::from_box(&self.view_last_shot_eject_effect),
+            world_last_shot_eject_effect: Ptr32::from_box(&self.world_last_shot_eject_effect),
+            reticle_center: Ptr32::from_box(&self.reticle_center),
+            reticle_side: Ptr32::from_box(&self.reticle_side),
+            reticle_center_size: self.reticle_center_size,
+            reticle_side_size: self.reticle_side_size,
+            reticle_min_ofs: self.reticle_min_ofs,
+            active_reticle_type: self.active_reticle_type as _,
+            stand_move: self.stand_move.get(),
+            stand_rot: self.stand_rot.get(),
+            ducked_ofs: self.ducked_ofs.get(),
+            ducked_move: self.ducked_move.get(),
+            ducked_sprint_ofs: self.ducked_sprint_ofs.get(),
+            ducked_sprint_rot: self.ducked_sprint_rot.get(),
+            ducked_sprint_bob: self.ducked_sprint_bob.get(),
+            ducked_sprint_cycle_scale: self.ducked_sprint_cycle_scale,
+            sprint_ofs: self.sprint_ofs.get(),
+            sprint_rot: self.sprint_rot.get(),
+            sprint_bob: self.sprint_bob.get(),
+            sprint_cycle_scale: self.sprint_cycle_scale,
+            low_ready_ofs: self.low_ready_ofs.get(),
+            low_ready_rot: self.low_ready_rot.get(),
+            dtp_ofs: self.dtp_ofs.get(),
+            dtp_rot: self.dtp_rot.get(),
+            dtp_bob: self.dtp_bob.get(),
+            dtp_cycle_scale: self.dtp_cycle_scale,
+            mantle_ofs: self.mantle_ofs.get(),
+            mantle_rot: self.mantle_rot.get(),
+            slide_ofs: self.slide_ofs.get(),
+            slide_rot: self.slide_rot.get(),
+            ducked_rot: self.ducked_rot.get(),
+            prone_ofs: self.prone_ofs.get(),
+            prone_move: self.prone_move.get(),
+            prone_rot: self.prone_rot.get(),
+            strafe_move: self.strafe_move.get(),
+            strafe_rot: self.strafe_rot.get(),
+            pos_move_rate: self.pos_move_rate,
+            pos_prone_move_rate: self.pos_prone_move_rate,
+            stand_move_min_speed: self.stand_move_min_speed,
+            ducked_move_min_speed: self.ducked_move_min_speed,
+            prone_move_min_speed: self.prone_move_min_speed,
+            pos_rot_rate: self.pos_rot_rate,
+            pos_prone_rot_rate: self.pos_prone_rot_rate,
+            stand_rot_min_speed: self.stand_rot_min_speed,
+            ducked_rot_min_speed: self.ducked_rot_min_speed,
+            prone_rot_min_speed: self.prone_rot_min_speed,
+            world_model: Ptr32ArrayConst::from_option(&self.world_model),
+            world_clip_model: Ptr32::from_box(&self.world_clip_model),
+            rocket_model: Ptr32::from_box(&self.rocket_model),
+            mounted_model: Ptr32::from_box(&self.mounted_model),
+            additional_melee_model: Ptr32::from_box(&self.additional_melee_model),
+            hud_icon: Ptr32::from_box(&self.hud_icon),
+            hud_icon_ratio: self.hud_icon_ratio as _,
+            indicator_icon: Ptr32::from_box(&self.indicator_icon),
+            indicator_icon_ratio: self.indicator_icon_ratio as _,
+            ammo_counter_icon: Ptr32::from_box(&self.ammo_counter_icon),
+            ammo_counter_icon_ratio: self.ammo_counter_icon_ratio as _,
+            ammo_counter_clip: self.ammo_counter_clip as _,
+            start_ammo: self.start_ammo,
+            head_index: self.head_index as _,
+            max_ammo: self.max_ammo,
+            shot_count: self.shot_count,
+            shared_ammo_cap_name: XStringRaw::from_str(self.shared_ammo_cap_name.get()),
+            shared_ammo_cap_index: self.shared_ammo_cap_index as _,
+            shared_ammo_cap: self.shared_ammo_cap,
+            unlimited_ammo: self.unlimited_ammo,
+            ammo_count_clip_relative: self.ammo_count_clip_relative,
+            pad2: [0u8; 2],
+            damage: self.damage,
+            damage_duration: self.damage_duration,
+            damage_interval: self.damage_interval,
+            player_damage: self.player_damage,
+            melee_damage: self.melee_damage,
+            damage_type: self.damage_type,
+            explosion_tag: ser.intern(self.explosion_tag.get())?,
+            pad3: [0u8; 2],
+            fire_delay: self.fire_delay,
+            melee_delay: self.melee_delay,
+            melee_charge_delay: self.melee_charge_delay,
+            detonate_delay: self.detonate_delay,
+            spin_up_time: self.spin_up_time,
+            spin_down_time: self.spin_down_time,
+            spin_rate: self.spin_rate,
+            spin_loop_sound: XStringRaw::from_str(self.spin_loop_sound.get()),
+            spin_loop_sound_player: XStringRaw::from_str(self.spin_loop_sound_player.get()),
+            start_spin_sound: XStringRaw::from_str(self.start_spin_sound.get()),
+            start_spin_sound_player: XStringRaw::from_str(self.start_spin_sound_player.get()),
+            stop_spin_sound: XStringRaw::from_str(self.stop_spin_sound.get()),
+            stop_spin_sound_player: XStringRaw::from_str(self.stop_spin_sound_player.get()),
+            fire_time: self.fire_time,
+            last_fire_time: self.last_fire_time,
+            rechamber_time: self.rechamber_time,
+            rechamber_bolt_time: self.rechamber_bolt_time,
+            hold_fire_time: self.hold_fire_time,
+            detonate_fire_time: self.detonate_fire_time,
+            melee_time: self.melee_time,
+            melee_charge_time: self.melee_charge_time,
+            reload_time_right: self.reload_time_right,
+            reload_time_left: self.reload_time_left,
+            reload_show_rocket_time: self.reload_show_rocket_time,
+            reload_empty_time_left: self.reload_empty_time_left,
+            reload_add_time: self.reload_add_time,
+            reload_empty_add_time: self.reload_empty_add_time,
+            reload_quick_add_time: self.reload_quick_add_time,
+            reload_quick_empty_add_time: self.reload_quick_empty_add_time,
+            reload_start_time: self.reload_start_time,
+            reload_start_add_time: self.reload_start_add_time,
+            reload_end_time: self.reload_end_time,
+            drop_time: self.drop_time,
+            raise_time: self.raise_time,
+            alt_drop_time: self.alt_drop_time,
+            quick_drop_time: self.quick_drop_time,
+            quick_raise_time: self.quick_raise_time,
+            first_raise_time: self.first_raise_time,
+            empty_raise_time: self.empty_raise_time,
+            empty_drop_time: self.empty_drop_time,
+            sprint_in_time: self.sprint_in_time,
+            sprint_loop_time: self.sprint_loop_time,
+            sprint_out_time: self.sprint_out_time,
+            low_ready_in_time: self.low_ready_in_time,
+            low_ready_loop_time: self.low_ready_loop_time,
+            low_ready_out_time: self.low_ready_out_time,
+            cont_fire_in_time: self.cont_fire_in_time,
+            cont_fire_loop_time: self.cont_fire_loop_time,
+            cont_fire_out_time: self.cont_fire_out_time,
+            dtp_in_time: self.dtp_in_time,
+            dtp_loop_time: self.dtp_loop_time,
+            dtp_out_time: self.dtp_out_time,
+            slide_in_time: self.slide_in_time,
+            deploy_time: self.deploy_time,
+            breakdown_time: self.breakdown_time,
+            night_vision_wear_time: self.night_vision_wear_time,
+            night_vision_wear_time_fade_out_end: self.night_vision_wear_time_fade_out_end,
+            night_vision_wear_time_power_up: self.night_vision_wear_time_power_up,
+            night_vision_remove_time: self.night_vision_remove_time,
+            night_vision_remove_time_power_down: self.night_vision_remove_time_power_down,
+            night_vision_remove_time_fade_in_start: self.night_vision_remove_time_fade_in_start,
+            fuse_time: self.fuse_time,
+            ai_fuse_time: self.ai_fuse_time,
+            lock_on_radius: self.lock_on_radius,
+            lock_on_speed: self.lock_on_speed,
+            require_lockon_to_fire: self.require_lockon_to_fire,
+            no_ads_when_mag_empty: self.no_ads_when_mag_empty,
+            avoid_drop_cleanup: self.avoid_drop_cleanup,
+            pad4: [0u8; 1],
+            stack_fire: self.stack_fire,
+            stack_fire_spread: self.stack_fire_spread,
+            stack_fire_accuracy_decay: self.stack_fire_accuracy_decay,
+            stack_sound: XStringRaw::from_str(self.stack_sound.get()),
+            auto_aim_range: self.auto_aim_range,
+            aim_assist_range: self.aim_assist_range,
+            mountable_weapon: self.mountable_weapon,
+            pad5: [0u8; 3],
+            aim_padding: self.aim_padding,
+            enemy_crosshair_range: self.enemy_crosshair_range,
+            crosshair_color_change: self.crosshair_color_change,
+            pad6: [0u8; 3],
+            move_speed_scale: self.move_speed_scale,
+            ads_move_speed_scale: self.ads_move_speed_scale,
+            sprint_duration_scale: self.sprint_duration_scale,
+            overlay_reticle: self.overlay_reticle as _,
+            overlay_interface: self.overlay_interface as _,
+            overlay_width: self.overlay_width,
+            overlay_height: self.overlay_height,
+            ads_bob_factor: self.ads_bob_factor,
+            ads_view_bob_mult: self.ads_view_bob_mult,
+            hip_spread_stand_min: self.hip_spread_stand_min,
+            hip_spread_ducked_min: self.hip_spread_ducked_min,
+            hip_spread_prone_min: self.hip_spread_prone_min,
+            hip_spread_stand_max: self.hip_spread_stand_max,
+            hip_spread_ducked_max: self.hip_spread_ducked_max,
+            hip_spread_prone_max: self.hip_spread_prone_max,
+            hip_spread_decay_rate: self.hip_spread_decay_rate,
+            hip_spread_fire_add: self.hip_spread_fire_add,
+            hip_spread_turn_add: self.hip_spread_turn_add,
+            hip_spread_move_add: self.hip_spread_move_add,
+            hip_spread_ducked_decay: self.hip_spread_ducked_decay,
+            hip_spread_prone_decay: self.hip_spread_prone_decay,
+            hip_reticle_side_pos: self.hip_reticle_side_pos,
+            ads_idle_amount: self.ads_idle_amount,
+            hip_idle_amount: self.hip_idle_amount,
+            ads_idle_speed: self.ads_idle_speed,
+            hip_idle_speed: self.hip_idle_speed,
+            idle_crouch_factor: self.idle_crouch_factor,
+            idle_prone_factor: self.idle_prone_factor,
+            gun_max_pitch: self.gun_max_pitch,
+            gun_max_yaw: self.gun_max_yaw,
+            sway_max_angle: self.sway_max_angle,
+            sway_lerp_speed: self.sway_lerp_speed,
+            sway_pitch_scale: self.sway_pitch_scale,
+            sway_yaw_scale: self.sway_yaw_scale,
+            sway_horiz_scale: self.sway_horiz_scale,
+            sway_vert_scale: self.sway_vert_scale,
+            sway_shell_shock_scale: self.sway_shell_shock_scale,
+            ads_sway_max_angle: self.ads_sway_max_angle,
+            ads_sway_lerp_speed: self.ads_sway_lerp_speed,
+            ads_sway_pitch_scale: self.ads_sway_pitch_scale,
+            ads_sway_yaw_scale: self.ads_sway_yaw_scale,
+            shared_ammo: self.shared_ammo,
+            rifle_bullet: self.rifle_bullet,
+            armor_piercing: self.armor_piercing,
+            bolt_action: self.bolt_action,
+            use_alt_tag_flesh: self.use_alt_tag_flesh,
+            use_anti_lag_rewind: self.use_anti_lag_rewind,
+            is_carried_killstreak_weapon: self.is_carried_killstreak_weapon,
+            aim_down_sight: self.aim_down_sight,
+            rechamber_while_ads: self.rechamber_while_ads,
+            reload_while_ads: self.reload_while_ads,
+            pad7: [0u8; 2],
+            ads_view_error_min: self.ads_view_error_min,
+            ads_view_error_max: self.ads_view_error_max,
+            cook_off_hold: self.cook_off_hold,
+            clip_only: self.clip_only,
+            can_use_in_vehicle: self.can_use_in_vehicle,
+            no_drops_or_raises: self.no_drops_or_raises,
+            ads_fire_only: self.ads_fire_only,
+            cancel_auto_holster_when_empty: self.cancel_auto_holster_when_empty,
+            suppress_ammo_reserve_display: self.suppress_ammo_reserve_display,
+            laser_sight_during_nightvision: self.laser_sight_during_nightvision,
+            hide_third_person: self.hide_third_person,
+            has_bayonet: self.has_bayonet,
+            dual_wield: self.dual_wield,
+            explode_on_ground: self.explode_on_ground,
+            throw_back: self.throw_back,
+            retrievable: self.retrievable,
+            die_on_respawn: self.die_on_respawn,
+            no_third_person_drops_or_raises: self.no_third_person_drops_or_raises,
+            continuous_fire: self.continuous_fire,
+            no_ping: self.no_ping,
+            force_bounce: self.force_bounce,
+            use_dropped_model_as_stowed: self.use_dropped_model_as_stowed,
+            no_quick_drop_when_empty: self.no_quick_drop_when_empty,
+            keep_crosshair_when_ads: self.keep_crosshair_when_ads,
+            use_only_alt_weaopon_hide_tags_in_alt_mode: self
+                .use_only_alt_weaopon_hide_tags_in_alt_mode,
+            pad8: [0u8; 1],
+            kill_icon: Ptr32::from_box(&self.kill_icon),
+            kill_icon_ratio: self.kill_icon_ratio as _,
+            flip_kill_icon: self.flip_kill_icon,
+            no_partial_reload: self.no_partial_reload,
+            segmented_reload: self.segmented_reload,
+            no_ads_auto_reload: self.no_ads_auto_reload,
+            reload_ammo_add: self.reload_ammo_add,
+            reload_start_add: self.reload_start_add,
+            spawned_grenade_weapon_name: XStringRaw::from_str(
+                self.spawned_grenade_weapon_name.get(),
+            ),
+            dual_wield_weapon_name: XStringRaw::from_str(self.dual_wield_weapon_name.get()),
+            dual_wield_weapon_index: self.dual_wield_weapon_index as _,
+            drop_ammo_min: self.drop_ammo_min,
+            drop_ammo_max: self.drop_ammo_max,
+            drop_clip_ammo_min: self.drop_clip_ammo_min,
+            drop_clip_ammo_max: self.drop_clip_ammo_max,
+            blocks_prone: self.blocks_prone,
+            show_indicator: self.show_indicator,
+            pad9: [0u8; 2],
+            is_rolling_grenade: self.is_rolling_grenade,
+            explosion_radius: self.explosion_radius,
+            explosion_radius_min: self.explosion_radius_min,
+            indicator_radius: self.indicator_radius,
+            explosion_inner_damage: self.explosion_inner_damage,
+            explosion_outer_damage: self.explosion_outer_damage,
+            damage_cone_angle: self.damage_cone_angle,
+            projectile_speed: self.projectile_speed,
+            projectile_speed_up: self.projectile_speed_up,
+            projectile_speed_relative_up: self.projectile_speed_relative_up,
+            projectile_speed_forward: self.projectile_speed_forward,
+            projectile_active_dist: self.projectile_active_dist,
+            proj_lifetime: self.proj_lifetime,
+            time_to_accelerate: self.time_to_accelerate,
+            projectile_curvature: self.projectile_curvature,
+            projectile_model: Ptr32::from_box(&self.projectile_model),
+            proj_explosion: self.proj_explosion as _,
+            proj_explosion_effect: Ptr32::from_box(&self.proj_explosion_effect),
+            proj_explosion_effect_force_normal_up: self.proj_explosion_effect_force_normal_up,
+            pad10: [0u8; 3],
+            proj_explosion_effect_2: Ptr32::from_box(&self.proj_explosion_effect_2),
+            proj_explosion_effect_2_force_normal_up: self.proj_explosion_effect_2_force_normal_up,
+            pad11: [0u8; 3],
+            proj_explosion_effect_3: Ptr32::from_box(&self.proj_explosion_effect_3),
+            proj_explosion_effect_3_force_normal_up: self.proj_explosion_effect_3_force_normal_up,
+            pad12: [0u8; 3],
+            proj_explosion_effect_4: Ptr32::from_box(&self.proj_explosion_effect_4),
+            proj_explosion_effect_4_force_normal_up: self.proj_explosion_effect_4_force_normal_up,
+            pad13: [0u8; 3],
+            proj_explosion_effect_5: Ptr32::from_box(&self.proj_explosion_effect_5),
+            proj_explosion_effect_5_force_normal_up: self.proj_explosion_effect_5_force_normal_up,
+            pad14: [0u8; 3],
+            proj_dud_effect: Ptr32::from_box(&self.proj_dud_effect),
+            proj_explosion_sound: XStringRaw::from_str(self.proj_explosion_sound.get()),
+            proj_dud_sound: XStringRaw::from_str(self.proj_dud_sound.get()),
+            mortar_shell_sound: XStringRaw::from_str(self.mortar_shell_sound.get()),
+            tank_shell_sound: XStringRaw::from_str(self.tank_shell_sound.get()),
+            proj_impact_explode: self.proj_impact_explode,
+            bullet_impact_explode: self.bullet_impact_explode,
+            pad15: [0u8; 2],
+            stickiness: self.stickiness as _,
+            rotate_type: self.rotate_type as _,
+            plantable: self.plantable,
+            has_detonator: self.has_detonator,
+            time_detonation: self.time_detonation,
+            no_crumple_missile: self.no_crumple_missile,
+            rotate: self.rotate,
+            keep_rolling: self.keep_rolling,
+            hold_button_to_throw: self.hold_button_to_throw,
+            offhand_hold_is_cancelable: self.offhand_hold_is_cancelable,
+            freeze_movement_when_firing: self.freeze_movement_when_firing,
+            pad16: [0u8; 3],
+            low_ammo_warning_threshold: self.low_ammo_warning_threshold,
+            melee_charge_range: self.melee_charge_range,
+            use_as_melee: self.use_as_melee,
+            is_camera_sensor: self.is_camera_sensor,
+            is_acoustic_sensor: self.is_acoustic_sensor,
+            pad17: [0u8; 1],
+            parallel_bounce: Ptr32ArrayConst::from_option(&self.parallel_bounce),
+            perpendicular_bounce: Ptr32ArrayConst::from_option(&self.perpendicular_bounce),
+            proj_tail_effect: Ptr32::from_box(&self.proj_tail_effect),
+            projectile_color: self.projectile_color.get(),
+            guided_missile_type: self.guided_missile_type as _,
+            max_steering_accel: self.max_steering_accel,
+            proj_ignition_delay: self.proj_ignition_delay,
+            proj_ignition_effect: Ptr32::from_box(&self.proj_ignition_effect),
+            proj_ignition_sound: XStringRaw::from_str(self.proj_ignition_sound.get()),
+            ads_aim_pitch: self.ads_aim_pitch,
+            ads_crosshair_in_frac: self.ads_crosshair_in_frac,
+            ads_crosshair_out_frac: self.ads_crosshair_out_frac,
+            ads_gun_kick_reduced_kick_bullets: self.ads_gun_kick_reduced_kick_bullets,
+            ads_gun_kick_reduced_kick_percent: self.ads_gun_kick_reduced_kick_percent,
+            ads_gun_kick_pitch_min: self.ads_gun_kick_pitch_min,
+            ads_gun_kick_pitch_max: self.ads_gun_kick_pitch_max,
+            ads_gun_kick_yaw_min: self.ads_gun_kick_yaw_min,
+            ads_gun_kick_yaw_max: self.ads_gun_kick_yaw_max,
+            ads_gun_kick_accel: self.ads_gun_kick_accel,
+            ads_gun_kick_speed_max: self.ads_gun_kick_speed_max,
+            ads_gun_kick_speed_decay: self.ads_gun_kick_speed_decay,
+            ads_gun_kick_static_decay: self.ads_gun_kick_static_decay,
+            ads_view_kick_pitch_min: self.ads_view_kick_pitch_min,
+            ads_view_kick_pitch_max: self.ads_view_kick_pitch_max,
+            ads_view_kick_yaw_min: self.ads_view_kick_yaw_min,
+            ads_view_kick_yaw_max: self.ads_view_kick_yaw_max,
+            ads_view_scatter_min: self.ads_view_scatter_min,
+            ads_view_scatter_max: self.ads_view_scatter_max,
+            ads_spread: self.ads_spread,
+            hip_gun_kick_reduced_kick_bullets: self.hip_gun_kick_reduced_kick_bullets,
+            hip_gun_kick_reduced_kick_percent: self.hip_gun_kick_reduced_kick_percent,
+            hip_gun_kick_pitch_min: self.hip_gun_kick_pitch_min,
+            hip_gun_kick_pitch_max: self.hip_gun_kick_pitch_max,
+            hip_gun_kick_yaw_min: self.hip_gun_kick_yaw_min,
+            hip_gun_kick_yaw_max: self.hip_gun_kick_yaw_max,
+            hip_gun_kick_accel: self.hip_gun_kick_accel,
+            hip_gun_kick_speed_max: self.hip_gun_kick_speed_max,
+            hip_gun_kick_speed_decay: self.hip_gun_kick_speed_decay,
+            hip_gun_kick_static_decay: self.hip_gun_kick_static_decay,
+            hip_view_kick_pitch_min: self.hip_view_kick_pitch_min,
+            hip_view_kick_pitch_max: self.hip_view_kick_pitch_max,
+            hip_view_kick_yaw_min: self.hip_view_kick_yaw_min,
+            hip_view_kick_yaw_max: self.hip_view_kick_yaw_max,
+            hip_view_scatter_min: self.hip_view_scatter_min,
+            hip_view_scatter_max: self.hip_view_scatter_max,
+            fight_dist: self.fight_dist,
+            max_dist: self.max_dist,
+            accuracy_graph_name: self
+                .accuracy_graph_name
+                .each_ref()
+                .map(|n| XStringRaw::from_str(n.get())),
+            accuracy_graph_knots: self
+                .accuracy_graph_knots
+                .each_ref()
+                .map(|k| Ptr32::from_slice(k)),
+            original_accuracy_graph_knots: self
+                .original_accuracy_graph_knots
+                .each_ref()
+                .map(|k| Ptr32::from_slice(k)),
+            accuracy_graph_knot_count: self.accuracy_graph_knots.each_ref().map(|k| k.len() as _),
+            original_accuracy_graph_knot_count: self
+                .original_accuracy_graph_knots
+                .each_ref()
+                .map(|k| k.len() as _),
+            position_reload_trans_time: self.position_reload_trans_time,
+            left_arc: self.left_arc,
+            right_arc: self.right_arc,
+            top_arc: self.top_arc,
+            bottom_arc: self.bottom_arc,
+            accuracy: self.accuracy,
+            ai_spread: self.ai_spread,
+            player_spread: self.player_spread,
+            min_turn_speed: self.min_turn_speed.get(),
+            max_turn_speed: self.max_turn_speed.get(),
+            pitch_convergence_time: self.pitch_convergence_time,
+            yaw_convergence_time: self.yaw_convergence_time,
+            suppress_time: self.suppress_time,
+            max_range: self.max_range,
+            anim_hor_rotate_inc: self.anim_hor_rotate_inc,
+            player_position_dist: self.player_position_dist,
+            use_hint_string: XStringRaw::from_str(self.use_hint_string.get()),
+            drop_hint_string: XStringRaw::from_str(self.drop_hint_string.get()),
+            use_hint_string_index: self.use_hint_string_index as _,
+            drop_hint_string_index: self.drop_hint_string_index as _,
+            horiz_view_jitter: self.horiz_view_jitter,
+            vert_view_jitter: self.vert_view_jitter,
+            script: XStringRaw::from_str(self.script.get()),
+            min_damage: self.min_damage,
+            min_player_damage: self.min_player_damage,
+            max_damage_range: self.max_damage_range,
+            min_damage_range: self.min_damage_range,
+            destabilization_rate_time: self.destabilization_rate_time,
+            destabilization_curvature_max: self.destabilization_curvature_max,
+            destabilize_distance: self.destabilize_distance,
+            location_damage_multipliers: Ptr32ArrayConst::from_option(
+                &self.location_damage_multipliers,
+            ),
+            fire_rumble: XStringRaw::from_str(self.fire_rumble.get()),
+            melee_impact_rumble: XStringRaw::from_str(self.melee_impact_rumble.get()),
+            reload_rumble: XStringRaw::from_str(self.reload_rumble.get()),
+            ads_dof_start: self.ads_dof_start,
+            ads_dof_end: self.ads_dof_end,
+            hip_dof_start: self.hip_dof_start,
+            hip_dof_end: self.hip_dof_end,
+            scan_speed: self.scan_speed,
+            scan_accel: self.scan_accel,
+            scan_pause_time: self.scan_pause_time,
+            flame_table_first_person: XStringRaw::from_str(self.flame_table_first_person.get()),
+            flame_table_third_person: XStringRaw::from_str(self.flame_table_third_person.get()),
+            flame_table_first_person_ptr: Ptr32::from_box(&self.flame_table_first_person_ptr),
+            flame_table_third_person_ptr: Ptr32::from_box(&self.flame_table_third_person_ptr),
+            tag_fx_preparation_effect: Ptr32::from_box(&self.tag_fx_preparation_effect),
+            tag_flash_preparation_effect: Ptr32::from_box(&self.tag_flash_preparation_effect),
+            do_gibbing: self.do_gibbing,
+            pad18: [0u8; 3],
+            max_gib_distance: self.max_gib_distance,
+        };
+
+        ser.store_into_xfile(weapon_def)?;
+        layout::store_pad(ser, ser.layout_overrides().weapon_def_pad)?;
+        self.overlay_name.xfile_serialize(ser, ())?;
+        serialize_model_array(ser, self.gun_xmodel.as_ref())?;
+        self.hand_xmodel.xfile_serialize(ser, ())?;
+        self.mode_name.xfile_serialize(ser, ())?;
+        if let Some(strings) = &self.notetrack_sound_map_keys {
+            for s in strings.iter() {
+                let s = ser.intern(s)?;
+                ser.store_into_xfile(s)?;
+            }
+        }
+        if let Some(strings) = &self.notetrack_sound_map_values {
+            for s in strings.iter() {
+                let s = ser.intern(s)?;
+                ser.store_into_xfile(s)?;
+            }
+        }
+        self.parent_weapon_name.xfile_serialize(ser, ())?;
+        self.view_flash_effect.xfile_serialize(ser, ())?;
+        self.world_flash_effect.xfile_serialize(ser, ())?;
+        self.pickup_sound.xfile_serialize(ser, ())?;
+        self.pickup_sound_player.xfile_serialize(ser, ())?;
+        self.ammo_pickup_sound.xfile_serialize(ser, ())?;
+        self.ammo_pickup_sound_player.xfile_serialize(ser, ())?;
+        self.projectile_sound.xfile_serialize(ser, ())?;
+        self.pullback_sound.xfile_serialize(ser, ())?;
+        self.pullback_sound_player.xfile_serialize(ser, ())?;
+        self.fire_sound.xfile_serialize(ser, ())?;
+        self.fire_sound_player.xfile_serialize(ser, ())?;
+        self.fire_loop_sound.xfile_serialize(ser, ())?;
+        self.fire_loop_sound_player.xfile_serialize(ser, ())?;
+        self.fire_loop_end_sound.xfile_serialize(ser, ())?;
+        self.fire_loop_end_sound_player.xfile_serialize(ser, ())?;
+        self.fire_stop_sound.xfile_serialize(ser, ())?;
+        self.fire_stop_sound_player.xfile_serialize(ser, ())?;
+        self.fire_last_sound.xfile_serialize(ser, ())?;
+        self.fire_last_sound_player.xfile_serialize(ser, ())?;
+        self.empty_fire_sound.xfile_serialize(ser, ())?;
+        self.empty_fire_sound_player.xfile_serialize(ser, ())?;
+        self.crack_sound.xfile_serialize(ser, ())?;
+        self.whiz_by_sound.xfile_serialize(ser, ())?;
+        self.melee_swipe_sound.xfile_serialize(ser, ())?;
+        self.melee_swipe_sound_player.xfile_serialize(ser, ())?;
+        self.melee_hit_sound.xfile_serialize(ser, ())?;
+        self.melee_miss_sound.xfile_serialize(ser, ())?;
+        self.rechamber_sound.xfile_serialize(ser, ())?;
+        self.rechamber_sound_player.xfile_serialize(ser, ())?;
+        self.reload_sound.xfile_serialize(ser, ())?;
+        self.reload_sound_player.xfile_serialize(ser, ())?;
+        self.reload_empty_sound.xfile_serialize(ser, ())?;
+        self.reload_empty_sound_player.xfile_serialize(ser, ())?;
+        self.reload_start_sound.xfile_serialize(ser, ())?;
+        self.reload_start_sound_player.xfile_serialize(ser, ())?;
+        self.reload_end_sound.xfile_serialize(ser, ())?;
+        self.reload_end_sound_player.xfile_serialize(ser, ())?;
+        self.rotate_loop_sound.xfile_serialize(ser, ())?;
+        self.rotate_loop_sound_player.xfile_serialize(ser, ())?;
+        self.deploy_sound.xfile_serialize(ser, ())?;
+        self.deploy_sound_player.xfile_serialize(ser, ())?;
+        self.finish_deploy_sound.xfile_serialize(ser, ())?;
+        self.finish_deploy_sound_player.xfile_serialize(ser, ())?;
+        self.breakdown_sound.xfile_serialize(ser, ())?;
+        self.breakdown_sound_player.xfile_serialize(ser, ())?;
+        self.finish_breakdown_sound.xfile_serialize(ser, ())?;
+        self.finish_breakdown_sound_player
+            .xfile_serialize(ser, ())?;
+        self.detonate_sound.xfile_serialize(ser, ())?;
+        self.detonate_sound_player.xfile_serialize(ser, ())?;
+        self.night_vision_wear_sound.xfile_serialize(ser, ())?;
+        self.night_vision_wear_sound_player
+            .xfile_serialize(ser, ())?;
+        self.night_vision_remove_sound.xfile_serialize(ser, ())?;
+        self.night_vision_remove_sound_player
+            .xfile_serialize(ser, ())?;
+        self.alt_switch_sound.xfile_serialize(ser, ())?;
+        self.alt_switch_sound_player.xfile_serialize(ser, ())?;
+        self.raise_sound.xfile_serialize(ser, ())?;
+        self.raise_sound_player.xfile_serialize(ser, ())?;
+        self.first_raise_sound.xfile_serialize(ser, ())?;
+        self.first_raise_sound_player.xfile_serialize(ser, ())?;
+        self.put_away_sound.xfile_serialize(ser, ())?;
+        self.put_away_sound_player.xfile_serialize(ser, ())?;
+        self.overheat_sound.xfile_serialize(ser, ())?;
+        self.overheat_sound_player.xfile_serialize(ser, ())?;
+        self.ads_zoom_sound.xfile_serialize(ser, ())?;
+        if let Some(sounds) = &self.bounce_sound {
+            for sound in sounds.iter() {
+                ser.store_into_xfile(XStringRaw::from_str(sound.get()))?;
+            }
+            for sound in sounds.iter() {
+                sound.xfile_serialize(ser, ())?;
+            }
+        }
+        self.stand_mounted_weapdef.xfile_serialize(ser, ())?;
+        self.crouch_mounted_weapdef.xfile_serialize(ser, ())?;
+        self.prone_mounted_weapdef.xfile_serialize(ser, ())?;
+        self.view_shell_eject_effect.xfile_serialize(ser, ())?;
+        self.world_shell_eject_effect.xfile_serialize(ser, ())?;
+        self.view_last_shot_eject_effect.xfile_serialize(ser, ())?;
+        self.world_last_shot_eject_effect.xfile_serialize(ser, ())?;
+        self.reticle_center.xfile_serialize(ser, ())?;
+        self.reticle_side.xfile_serialize(ser, ())?;
+        serialize_model_array(ser, self.world_model.as_deref())?;
+        self.world_clip_model.xfile_serialize(ser, ())?;
+        self.rocket_model.xfile_serialize(ser, ())?;
+        self.mounted_model.xfile_serialize(ser, ())?;
+        self.additional_melee_model.xfile_serialize(ser, ())?;
+        self.hud_icon.xfile_serialize(ser, ())?;
+        self.indicator_icon.xfile_serialize(ser, ())?;
+        self.ammo_counter_icon.xfile_serialize(ser, ())?;
+        self.shared_ammo_cap_name.xfile_serialize(ser, ())?;
+        self.spin_loop_sound.xfile_serialize(ser, ())?;
+        self.spin_loop_sound_player.xfile_serialize(ser, ())?;
+        self.start_spin_sound.xfile_serialize(ser, ())?;
+        self.start_spin_sound_player.xfile_serialize(ser, ())?;
+        self.stop_spin_sound.xfile_serialize(ser, ())?;
+        self.stop_spin_sound_player.xfile_serialize(ser, ())?;
+        self.stack_sound.xfile_serialize(ser, ())?;
+        self.kill_icon.xfile_serialize(ser, ())?;
+        self.spawned_grenade_weapon_name.xfile_serialize(ser, ())?;
+        self.dual_wield_weapon_name.xfile_serialize(ser, ())?;
+        self.projectile_model.xfile_serialize(ser, ())?;
+        self.proj_explosion_effect.xfile_serialize(ser, ())?;
+        self.proj_explosion_effect_2.xfile_serialize(ser, ())?;
+        self.proj_explosion_effect_3.xfile_serialize(ser, ())?;
+        self.proj_explosion_effect_4.xfile_serialize(ser, ())?;
+        self.proj_explosion_effect_5.xfile_serialize(ser, ())?;
+        self.proj_dud_effect.xfile_serialize(ser, ())?;
+        self.proj_explosion_sound.xfile_serialize(ser, ())?;
+        self.proj_dud_sound.xfile_serialize(ser, ())?;
+        self.mortar_shell_sound.xfile_serialize(ser, ())?;
+        self.tank_shell_sound.xfile_serialize(ser, ())?;
+        self.parallel_bounce.xfile_serialize(ser, ())?;
+        self.perpendicular_bounce.xfile_serialize(ser, ())?;
+        self.proj_tail_effect.xfile_serialize(ser, ())?;
+        self.proj_ignition_effect.xfile_serialize(ser, ())?;
+        self.proj_ignition_sound.xfile_serialize(ser, ())?;
+        for i in 0..=1 {
+            self.accuracy_graph_name[i].xfile_serialize(ser, ())?;
+            self.accuracy_graph_knots[i].xfile_serialize(ser, ())?;
+            self.original_accuracy_graph_knots[i].xfile_serialize(ser, ())?;
+        }
+        self.use_hint_string.xfile_serialize(ser, ())?;
+        self.drop_hint_string.xfile_serialize(ser, ())?;
+        self.script.xfile_serialize(ser, ())?;
+        self.location_damage_multipliers.xfile_serialize(ser, ())?;
+        self.fire_rumble.xfile_serialize(ser, ())?;
+        self.melee_impact_rumble.xfile_serialize(ser, ())?;
+        self.reload_rumble.xfile_serialize(ser, ())?;
+        self.flame_table_first_person.xfile_serialize(ser, ())?;
+        self.flame_table_third_person.xfile_serialize(ser, ())?;
+        self.flame_table_first_person_ptr.xfile_serialize(ser, ())?;
+        self.flame_table_third_person_ptr.xfile_serialize(ser, ())?;
+        self.tag_fx_preparation_effect.xfile_serialize(ser, ())?;
+        self.tag_flash_preparation_effect.xfile_serialize(ser, ())?;
+
+        Ok(())
+    }
+}
+
+/// Stores `models` the way [`Ptr32ArrayConst`] reads them back: all of the
+/// pointers first, then each model.
+fn serialize_model_array(
+    ser: &mut impl T5XFileSerialize,
+    models: Option<&[Option<Box<xmodel::XModel>>; 16]>,
+) -> Result<()> {
+    let Some(models) = models else {
+        return Ok(());
+    };
+
+    let pointers: [Ptr32<xmodel::XModelRaw>; 16] = models.each_ref().map(Ptr32::from_box);
+    ser.store_into_xfile(pointers)?;
+    for model in models {
+        model.xfile_serialize(ser, ())?;
+    }
+    Ok(())
+}
+
+/// Metres per game unit. Game units are inches.
+pub const UNIT_SCALE: f32 = 0.0254;
+
+/// The default `g_gravity`, in units per second squared.
+pub const GRAVITY: f32 = 800.0;
+
+/// The step size [`WeaponDef::projectile_max_height`] and
+/// [`WeaponDef::projectile_range`] simulate with, in seconds.
+const PROJECTILE_SIM_DT: f32 = 1.0 / 60.0;
+
+impl WeaponDef {
+    /// Iterates over the notetrack-to-sound map as `(notetrack, sound)`
+    /// pairs, skipping unused entries.
+    pub fn notetrack_sound_map(&self) -> impl Iterator<Item = (&str, &str)> {
+        let keys = self.notetrack_sound_map_keys.iter().flat_map(|k| k.iter());
+        let values = self
+            .notetrack_sound_map_values
+            .iter()
+            .flat_map(|v| v.iter());
+
+        keys.zip(values)
+            .filter(|(k, _)| !k.is_empty())
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// The sounds [`Self::notetrack_sound_map`] plays for `anims`'
+    /// notetracks, as `(seconds, sound)` pairs sorted by time. Times are
+    /// from the start of whichever animation the notetrack is in, e.g. the
+    /// reload animation for reload sounds.
+    pub fn timed_sounds(&self, anims: &[&XAnimParts]) -> Vec<(f32, String)> {
+        let mut sounds = anims
+            .iter()
+            .flat_map(|a| a.notetracks())
+            .filter_map(|n| {
+                let (_, sound) = self.notetrack_sound_map().find(|(k, _)| *k == n.name)?;
+                Some((n.time_seconds, sound.to_string()))
+            })
+            .collect::<Vec<_>>();
+        sounds.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        sounds
+    }
+
+    /// The sound played when the weapon bounces off `surface`, if any.
+    pub fn bounce_sound_for(&self, surface: fx::SurfaceType) -> Option<&XString> {
+        self.bounce_sound
+            .as_ref()?
+            .get(surface as usize)
+            .filter(|s| !s.get().is_empty())
+    }
+
+    /// The `(parallel, perpendicular)` bounce factors for `surface`, if the
+    /// weapon has any.
+    pub fn bounce_for(&self, surface: fx::SurfaceType) -> Option<(f32, f32)> {
+        let parallel = self.parallel_bounce.as_ref()?.get(surface as usize)?;
+        let perpendicular = self.perpendicular_bounce.as_ref()?.get(surface as usize)?;
+        Some((*parallel, *perpendicular))
+    }
+
+    /// Rounds per minute, from [`fire_time`](Self::fire_time) (which is in
+    /// milliseconds).
+    pub fn fire_rate_rpm(&self) -> f32 {
+        60000.0 / self.fire_time as f32
+    }
+
+    /// How long (in seconds) it takes to deal `hitpoints` damage at `range`,
+    /// assuming every shot hits and the weapon fires at
+    /// [`fire_rate_rpm`](Self::fire_rate_rpm). The first shot is fired at
+    /// time zero. [`f32::INFINITY`] if the weapon does no damage at `range`.
+    pub fn time_to_kill_at_range(&self, range: f32, hitpoints: f32) -> f32 {
+        let damage = self.damage_at_range(range);
+        if damage <= 0.0 {
+            return f32::INFINITY;
+        }
+
+        let shots = (hitpoints / damage).ceil().max(1.0);
+        (shots - 1.0) * 60.0 / self.fire_rate_rpm()
+    }
+
+    /// [`projectile_speed`](Self::projectile_speed) (in units per second),
+    /// in metres per second.
+    pub fn muzzle_velocity_m_per_s(&self) -> f32 {
+        self.projectile_speed as f32 * UNIT_SCALE
+    }
+
+    /// [`min_damage_range`](Self::min_damage_range) in metres, i.e. where
+    /// damage stops falling off.
+    pub fn max_effective_range_m(&self) -> f32 {
+        self.min_damage_range * UNIT_SCALE
+    }
+
+    /// Damage dealt at `range`. Full [`damage`](Self::damage) up to
+    /// [`max_damage_range`](Self::max_damage_range), falling off linearly to
+    /// [`min_damage`](Self::min_damage) at
+    /// [`min_damage_range`](Self::min_damage_range) and staying there past
+    /// it. If the two ranges are equal (or out of order), damage drops
+    /// straight to `min_damage` past `max_damage_range`.
+    pub fn damage_at_range(&self, range: f32) -> f32 {
+        let (full, min) = (self.damage as f32, self.min_damage as f32);
+
+        if range <= self.max_damage_range {
+            full
+        } else if range >= self.min_damage_range {
+            min
+        } else {
+            let t =
+                (range - self.max_damage_range) / (self.min_damage_range - self.max_damage_range);
+            full + (min - full) * t
+        }
+    }
+
+    /// The `(range, damage)` control points of the curve used by
+    /// [`damage_at_range`](Self::damage_at_range).
+    pub fn damage_falloff_curve(&self) -> [(f32, f32); 3] {
+        let (full, min) = (self.damage as f32, self.min_damage as f32);
+
+        [
+            (0.0, full),
+            (self.max_damage_range, full),
+            (self.min_damage_range, min),
+        ]
+    }
+
+    /// The range at which damage drops to half of
+    /// [`damage`](Self::damage), or [`f32::INFINITY`] if it never does.
+    pub fn effective_range(&self) -> f32 {
+        let (full, min) = (self.damage as f32, self.min_damage as f32);
+        let half = full / 2.0;
+
+        if min > half {
+            f32::INFINITY
+        } else if self.min_damage_range <= self.max_damage_range || min == full {
+            self.max_damage_range
+        } else {
+            let t = (half - full) / (min - full);
+            self.max_damage_range + (self.min_damage_range - self.max_damage_range) * t
+        }
+    }
+
+    /// Integrates the projectile's flight with `steps` Euler steps of `dt`
+    /// seconds, starting at the origin and fired along +X. Returns the
+    /// position before the first step and after each one.
+    ///
+    /// The launch velocity is [`projectile_speed`](Self::projectile_speed)
+    /// forward plus [`projectile_speed_up`](Self::projectile_speed_up)
+    /// upward. If [`time_to_accelerate`](Self::time_to_accelerate) is set,
+    /// forward speed instead ramps up linearly from zero over that time.
+    /// [`projectile_curvature`](Self::projectile_curvature) turns the
+    /// horizontal velocity to the left by that many degrees per second, and
+    /// [`GRAVITY`] pulls it down. The def has no drag parameter, so there's
+    /// no air resistance.
+    pub fn simulate_projectile(&self, dt: f32, steps: usize) -> Vec<Vec3> {
+        let speed = self.projectile_speed as f32;
+        let turn_rate = self.projectile_curvature.to_radians();
+
+        let mut pos = [0.0f32; 3];
+        let mut heading = 0.0f32;
+        let mut vz = self.projectile_speed_up as f32;
+        let mut points = Vec::with_capacity(steps + 1);
+        points.push(pos.into());
+
+        for step in 0..steps {
+            let t = step as f32 * dt;
+            let forward = if self.time_to_accelerate > 0.0 {
+                speed * (t / self.time_to_accelerate).min(1.0)
+            } else {
+                speed
+            };
+
+            let (sin, cos) = heading.sin_cos();
+            pos[0] += forward * cos * dt;
+            pos[1] += forward * sin * dt;
+            pos[2] += vz * dt;
+            vz -= GRAVITY * dt;
+            heading += turn_rate * dt;
+            points.push(pos.into());
+        }
+
+        points
+    }
+
+    /// Simulates the projectile for [`proj_lifetime`](Self::proj_lifetime)
+    /// seconds. See [`simulate_projectile`](Self::simulate_projectile).
+    fn simulate_projectile_lifetime(&self) -> Vec<Vec3> {
+        if self.proj_lifetime <= 0.0 {
+            return Vec::from([Vec3::from([0.0; 3])]);
+        }
+
+        let steps = (self.proj_lifetime / PROJECTILE_SIM_DT).ceil() as usize;
+        self.simulate_projectile(self.proj_lifetime / steps as f32, steps)
+    }
+
+    /// The highest Z the projectile reaches before its lifetime runs out
+    /// (zero if it never goes up).
+    pub fn projectile_max_height(&self) -> f32 {
+        self.simulate_projectile_lifetime()
+            .iter()
+            .map(|p| p.get()[2])
+            .fold(0.0, f32::max)
+    }
+
+    /// How far along X the projectile is when its lifetime runs out.
+    pub fn projectile_range(&self) -> f32 {
+        self.simulate_projectile_lifetime().last().unwrap().get()[0]
+    }
+
+    /// Every effect the weapon plays when fired, ordered by when it plays.
+    /// Stages with a known time come first, by time; stages that depend on
+    /// the projectile hitting something or a detonator come last. Stages
+    /// that play at the same time keep the order of [`EffectRole`].
+    ///
+    /// Every role is included, even if the weapon has no effect for it.
+    ///
+    /// Timing:
+    /// - The flashes and shell ejects play on firing. The last-shot ejects
+    ///   replace the normal ones on the shot that empties the clip.
+    /// - The ignition and tail effects start once the projectile's motor
+    ///   ignites, [`proj_ignition_delay`](Self::proj_ignition_delay) after
+    ///   firing.
+    /// - The explosions play together. Weapons with a detonator explode
+    ///   [`detonate_delay`](Self::detonate_delay) after it's triggered.
+    ///   Other weapons explode on impact if
+    ///   [`proj_impact_explode`](Self::proj_impact_explode) is set or
+    ///   there's no [`fuse_time`](Self::fuse_time), and when the fuse runs
+    ///   out otherwise.
+    /// - The dud effect replaces the explosions when the projectile hits
+    ///   something before it's armed.
+    pub fn effect_chain(&self) -> Vec<EffectStage<'_>> {
+        let explosion_timing = if self.has_detonator {
+            EffectTiming::DetonateDelay(self.detonate_delay)
+        } else if self.proj_impact_explode || self.fuse_time <= 0 {
+            EffectTiming::OnImpact
+        } else {
+            EffectTiming::FuseTime(self.fuse_time)
+        };
+        let ignition_timing = EffectTiming::ProjIgnitionDelay(self.proj_ignition_delay);
+
+        fn stage(
+            role: EffectRole,
+            effect: &Option<Box<fx::FxEffectDef>>,
+            timing: EffectTiming,
+            force_normal_up: bool,
+        ) -> EffectStage<'_> {
+            EffectStage {
+                role,
+                effect: effect.as_deref(),
+                timing,
+                force_normal_up,
+            }
+        }
+
+        let mut stages = Vec::from([
+            stage(
+                EffectRole::ViewFlash,
+                &self.view_flash_effect,
+                EffectTiming::OnFire,
+                false,
+            ),
+            stage(
+                EffectRole::WorldFlash,
+                &self.world_flash_effect,
+                EffectTiming::OnFire,
+                false,
+            ),
+            stage(
+                EffectRole::ViewShellEject,
+                &self.view_shell_eject_effect,
+                EffectTiming::OnFire,
+                false,
+            ),
+            stage(
+                EffectRole::WorldShellEject,
+                &self.world_shell_eject_effect,
+                EffectTiming::OnFire,
+                false,
+            ),
+            stage(
+                EffectRole::ViewLastShotEject,
+                &self.view_last_shot_eject_effect,
+                EffectTiming::OnFire,
+                false,
+            ),
+            stage(
+                EffectRole::WorldLastShotEject,
+                &self.world_last_shot_eject_effect,
+                EffectTiming::OnFire,
+                false,
+            ),
+            stage(
+                EffectRole::Ignition,
+                &self.proj_ignition_effect,
+                ignition_timing,
+                false,
+            ),
+            stage(
+                EffectRole::Tail,
+                &self.proj_tail_effect,
+                ignition_timing,
+                false,
+            ),
+            stage(
+                EffectRole::Explosion(1),
+                &self.proj_explosion_effect,
+                explosion_timing,
+                self.proj_explosion_effect_force_normal_up,
+            ),
+            stage(
+                EffectRole::Explosion(2),
+                &self.proj_explosion_effect_2,
+                explosion_timing,
+                self.proj_explosion_effect_2_force_normal_up,
+            ),
+            stage(
+                EffectRole::Explosion(3),
+                &self.proj_explosion_effect_3,
+                explosion_timing,
+                self.proj_explosion_effect_3_force_normal_up,
+            ),
+            stage(
+                EffectRole::Explosion(4),
+                &self.proj_explosion_effect_4,
+                explosion_timing,
+                self.proj_explosion_effect_4_force_normal_up,
+            ),
+            stage(
+                EffectRole::Explosion(5),
+                &self.proj_explosion_effect_5,
+                explosion_timing,
+                self.proj_explosion_effect_5_force_normal_up,
+            ),
+            stage(
+                EffectRole::Dud,
+                &self.proj_dud_effect,
+                EffectTiming::OnImpact,
+                false,
+            ),
+        ]);
+
+        stages.sort_by_key(|s| {
+            let ms = s.timing.ms_after_fire();
+            (ms.is_none(), ms)
+        });
+        stages
+    }
+}
+
+/// What an [`EffectStage`] is for. See [`WeaponDef::effect_chain`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum EffectRole {
+    ViewFlash,
+    WorldFlash,
+    ViewShellEject,
+    WorldShellEject,
+    ViewLastShotEject,
+    WorldLastShotEject,
+    Ignition,
+    Tail,
+    /// `1..=5`, for [`WeaponDef::proj_explosion_effect`] through
+    /// [`WeaponDef::proj_explosion_effect_5`].
+    Explosion(u8),
+    Dud,
+}
+
+/// When an [`EffectStage`] plays. Times are in milliseconds, taken from the
+/// field the variant is named after.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum EffectTiming {
+    OnFire,
+    /// After firing.
+    ProjIgnitionDelay(i32),
+    /// After firing.
+    FuseTime(i32),
+    /// After the detonator is triggered.
+    DetonateDelay(i32),
+    OnImpact,
+}
+
+impl EffectTiming {
+    /// How long after firing the stage plays, or [`None`] if that depends
+    /// on something other than the weapon.
+    pub fn ms_after_fire(self) -> Option<i32> {
+        match self {
+            Self::OnFire => Some(0),
+            Self::ProjIgnitionDelay(ms) | Self::FuseTime(ms) => Some(ms.max(0)),
+            Self::DetonateDelay(_) | Self::OnImpact => None,
+        }
+    }
+}
+
+/// Returned by [`WeaponDef::effect_chain`].
+#[derive(Copy, Clone, Debug)]
+pub struct EffectStage<'a> {
+    pub role: EffectRole,
+    pub effect: Option<&'a fx::FxEffectDef>,
+    pub timing: EffectTiming,
+    /// Whether the effect is oriented straight up rather than along the
+    /// surface normal. Only ever set for explosions.
+    pub force_normal_up: bool,
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, Deserialize, XFileConvert)]
+#[xfile(into = FlameTable)]
 pub(crate) struct FlameTableRaw<'a> {
     pub flame_var_stream_chunk_gravity_start: f32,
     pub flame_var_stream_chunk_gravity_end: f32,
@@ -2533,18 +3984,31 @@ pub(crate) struct FlameTableRaw<'a> {
     pub flame_var_smoke_origin_offset: f32,
     pub flame_var_collision_speed_scale: f32,
     pub flame_var_collision_volume_scale: f32,
+    #[xfile(string)]
     pub name: XStringRaw<'a>,
+    #[xfile(ptr)]
     pub fire: Ptr32<'a, techset::MaterialRaw<'a>>,
+    #[xfile(ptr)]
     pub smoke: Ptr32<'a, techset::MaterialRaw<'a>>,
+    #[xfile(ptr)]
     pub heat: Ptr32<'a, techset::MaterialRaw<'a>>,
+    #[xfile(ptr)]
     pub drips: Ptr32<'a, techset::MaterialRaw<'a>>,
+    #[xfile(ptr)]
     pub stream_fuel: Ptr32<'a, techset::MaterialRaw<'a>>,
+    #[xfile(ptr)]
     pub stream_fuel_2: Ptr32<'a, techset::MaterialRaw<'a>>,
+    #[xfile(ptr)]
     pub stream_flame: Ptr32<'a, techset::MaterialRaw<'a>>,
+    #[xfile(ptr)]
     pub stream_flame_2: Ptr32<'a, techset::MaterialRaw<'a>>,
+    #[xfile(string)]
     pub flame_off_loop_sound: XStringRaw<'a>,
+    #[xfile(string)]
     pub flame_ignite_sound: XStringRaw<'a>,
+    #[xfile(string)]
     pub flame_on_loop_sound: XStringRaw<'a>,
+    #[xfile(string)]
     pub flame_cooldown_sound: XStringRaw<'a>,
 }
 assert_size!(FlameTableRaw, 476);
@@ -2673,160 +4137,53 @@ pub struct FlameTable {
     pub flame_cooldown_sound: XString,
 }
 
-impl<'a> XFileDeserializeInto<FlameTable, ()> for FlameTableRaw<'a> {
-    fn xfile_deserialize_into(
-        &self,
-        de: &mut impl T5XFileDeserialize,
-        _data: (),
-    ) -> Result<FlameTable> {
-        let name = self.name.xfile_deserialize_into(de, ())?;
-        let fire = self.fire.xfile_deserialize_into(de, ())?;
-        let smoke = self.smoke.xfile_deserialize_into(de, ())?;
-        let heat = self.heat.xfile_deserialize_into(de, ())?;
-        let drips = self.drips.xfile_deserialize_into(de, ())?;
-        let stream_fuel = self.stream_fuel.xfile_deserialize_into(de, ())?;
-        let stream_fuel_2 = self.stream_fuel_2.xfile_deserialize_into(de, ())?;
-        let stream_flame = self.stream_flame.xfile_deserialize_into(de, ())?;
-        let stream_flame_2 = self.stream_flame_2.xfile_deserialize_into(de, ())?;
-        let flame_off_loop_sound = self.flame_off_loop_sound.xfile_deserialize_into(de, ())?;
-        let flame_ignite_sound = self.flame_ignite_sound.xfile_deserialize_into(de, ())?;
-        let flame_on_loop_sound = self.flame_on_loop_sound.xfile_deserialize_into(de, ())?;
-        let flame_cooldown_sound = self.flame_cooldown_sound.xfile_deserialize_into(de, ())?;
-
-        Ok(FlameTable {
-            flame_var_stream_chunk_gravity_start: self.flame_var_stream_chunk_gravity_start,
-            flame_var_stream_chunk_gravity_end: self.flame_var_stream_chunk_gravity_end,
-            flame_var_stream_chunk_max_size: self.flame_var_stream_chunk_max_size,
-            flame_var_stream_chunk_start_size: self.flame_var_stream_chunk_start_size,
-            flame_var_stream_chunk_end_size: self.flame_var_stream_chunk_end_size,
-            flame_var_stream_chunk_start_size_rand: self.flame_var_stream_chunk_start_size_rand,
-            flame_var_stream_chunk_end_size_rand: self.flame_var_stream_chunk_end_size_rand,
-            flame_var_stream_chunk_dist_scalar: self.flame_var_stream_chunk_dist_scalar,
-            flame_var_stream_chunk_dist_sway_scale: self.flame_var_stream_chunk_dist_sway_scale,
-            flame_var_stream_chunk_dist_sway_vel_max: self.flame_var_stream_chunk_dist_sway_vel_max,
-            flame_var_stream_chunk_speed: self.flame_var_stream_chunk_speed,
-            flame_var_stream_chunk_decel: self.flame_var_stream_chunk_decel,
-            flame_var_stream_chunk_velocity_add: self.flame_var_stream_chunk_velocity_add,
-            flame_var_stream_chunk_duration: self.flame_var_stream_chunk_duration,
-            flame_var_stream_chunk_duration_scale_max_vel: self
-                .flame_var_stream_chunk_duration_scale_max_vel,
-            flame_var_stream_chunk_duration_vel_scalar: self
-                .flame_var_stream_chunk_duration_vel_scalar,
-            flame_var_stream_chunk_size_speed_scale: self.flame_var_stream_chunk_size_speed_scale,
-            flame_var_stream_chunk_size_age_scale: self.flame_var_stream_chunk_size_age_scale,
-            flame_var_stream_chunk_spawn_fire_interval_start: self
-                .flame_var_stream_chunk_spawn_fire_interval_start,
-            flame_var_stream_chunk_spawn_fire_interval_end: self
-                .flame_var_stream_chunk_spawn_fire_interval_end,
-            flame_var_stream_chunk_spawn_fire_min_life_frac: self
-                .flame_var_stream_chunk_spawn_fire_min_life_frac,
-            flame_var_stream_chunk_spawn_fire_max_life_frac: self
-                .flame_var_stream_chunk_spawn_fire_max_life_frac,
-            flame_var_stream_chunk_fire_min_life_frac: self
-                .flame_var_stream_chunk_fire_min_life_frac,
-            flame_var_stream_chunk_fire_min_life_frac_start: self
-                .flame_var_stream_chunk_fire_min_life_frac_start,
-            flame_var_stream_chunk_fire_min_life_frac_end: self
-                .flame_var_stream_chunk_fire_min_life_frac_end,
-            flame_var_stream_chunk_drips_min_life_frac: self
-                .flame_var_stream_chunk_drips_min_life_frac,
-            flame_var_stream_chunk_drips_min_life_frac_start: self
-                .flame_var_stream_chunk_drips_min_life_frac_start,
-            flame_var_stream_chunk_drips_min_life_frac_end: self
-                .flame_var_stream_chunk_drips_min_life_frac_end,
-            flame_var_stream_chunk_rotation_range: self.flame_var_stream_chunk_rotation_range,
-            flame_var_stream_size_rand_sin_wave: self.flame_var_stream_size_rand_sin_wave,
-            flame_var_stream_size_rand_cos_wave: self.flame_var_stream_size_rand_cos_wave,
-            flame_var_stream_drips_chunk_interval: self.flame_var_stream_drips_chunk_interval,
-            flame_var_stream_drips_chunk_min_frac: self.flame_var_stream_drips_chunk_min_frac,
-            flame_var_stream_drips_chunk_rand_frac: self.flame_var_stream_drips_chunk_rand_frac,
-            flame_var_stream_smoke_chunk_interval: self.flame_var_stream_smoke_chunk_interval,
-            flame_var_stream_smoke_chunk_min_frac: self.flame_var_stream_smoke_chunk_min_frac,
-            flame_var_stream_smoke_chunk_rand_frac: self.flame_var_stream_smoke_chunk_rand_frac,
-            flame_var_stream_chunk_cull_dist_size_frac: self
-                .flame_var_stream_chunk_cull_dist_size_frac,
-            flame_var_stream_chunk_cull_min_life: self.flame_var_stream_chunk_cull_min_life,
-            flame_var_stream_chunk_cull_max_life: self.flame_var_stream_chunk_cull_max_life,
-            flame_var_stream_fuel_size_start: self.flame_var_stream_fuel_size_start,
-            flame_var_stream_fuel_size_end: self.flame_var_stream_fuel_size_end,
-            flame_var_stream_fuel_length: self.flame_var_stream_fuel_length,
-            flame_var_stream_fuel_num_segments: self.flame_var_stream_fuel_num_segments,
-            flame_var_stream_fuel_anim_loop_time: self.flame_var_stream_fuel_anim_loop_time,
-            flame_var_stream_flame_size_start: self.flame_var_stream_flame_size_start,
-            flame_var_stream_flame_size_end: self.flame_var_stream_flame_size_end,
-            flame_var_stream_flame_length: self.flame_var_stream_flame_length,
-            flame_var_stream_flame_num_sgments: self.flame_var_stream_flame_num_sgments,
-            flame_var_stream_flame_anim_loop_time: self.flame_var_stream_flame_anim_loop_time,
-            flame_var_stream_primary_light_radius: self.flame_var_stream_primary_light_radius,
-            flame_var_stream_primary_light_radius_flutter: self
-                .flame_var_stream_primary_light_radius_flutter,
-            flame_var_stream_primary_light_r: self.flame_var_stream_primary_light_r,
-            flame_var_stream_primary_light_g: self.flame_var_stream_primary_light_g,
-            flame_var_stream_primary_light_b: self.flame_var_stream_primary_light_b,
-            flame_var_stream_primary_light_flutter_r: self.flame_var_stream_primary_light_flutter_r,
-            flame_var_stream_primary_light_flutter_g: self.flame_var_stream_primary_light_flutter_g,
-            flame_var_stream_primary_light_flutter_b: self.flame_var_stream_primary_light_flutter_b,
-            flame_var_fire_life: self.flame_var_fire_life,
-            flame_var_fire_life_rand: self.flame_var_fire_life_rand,
-            flame_var_fire_speed_scale: self.flame_var_fire_speed_scale,
-            flame_var_fire_speed_scale_rand: self.flame_var_fire_speed_scale_rand,
-            flame_var_fire_velocity_add_z: self.flame_var_fire_velocity_add_z,
-            flame_var_fire_velocity_add_z_rand: self.flame_var_fire_velocity_add_z_rand,
-            flame_var_fire_velocity_add_sideways: self.flame_var_fire_velocity_add_sideways,
-            flame_var_fire_gravity: self.flame_var_fire_gravity,
-            flame_var_fire_gravity_end: self.flame_var_fire_gravity_end,
-            flame_var_fire_max_rot_vel: self.flame_var_fire_max_rot_vel,
-            flame_var_fire_friction: self.flame_var_fire_friction,
-            flame_var_fire_end_size_add: self.flame_var_fire_end_size_add,
-            flame_var_fire_start_size_scale: self.flame_var_fire_start_size_scale,
-            flame_var_fire_end_size_scale: self.flame_var_fire_end_size_scale,
-            flame_var_drips_life: self.flame_var_drips_life,
-            flame_var_drips_life_rand: self.flame_var_drips_life_rand,
-            flame_var_drips_speed_scale: self.flame_var_drips_speed_scale,
-            flame_var_drips_speed_scale_rand: self.flame_var_drips_speed_scale_rand,
-            flame_var_drips_velocity_add_z: self.flame_var_drips_velocity_add_z,
-            flame_var_drips_velocity_add_z_rand: self.flame_var_drips_velocity_add_z_rand,
-            flame_var_drips_velocity_add_sideways: self.flame_var_drips_velocity_add_sideways,
-            flame_var_drips_gravity: self.flame_var_drips_gravity,
-            flame_var_drips_gravity_end: self.flame_var_drips_gravity_end,
-            flame_var_drips_max_rot_vel: self.flame_var_drips_max_rot_vel,
-            flame_var_drips_friction: self.flame_var_drips_friction,
-            flame_var_drips_end_size_add: self.flame_var_drips_end_size_add,
-            flame_var_drips_start_size_scale: self.flame_var_drips_start_size_scale,
-            flame_var_drips_end_size_scale: self.flame_var_drips_end_size_scale,
-            flame_var_smoke_life: self.flame_var_smoke_life,
-            flame_var_smoke_life_rand: self.flame_var_smoke_life_rand,
-            flame_var_smoke_speed_scale: self.flame_var_smoke_speed_scale,
-            flame_var_smoke_velocity_add_z: self.flame_var_smoke_velocity_add_z,
-            flame_var_smoke_gravity: self.flame_var_smoke_gravity,
-            flame_var_smoke_gravity_end: self.flame_var_smoke_gravity_end,
-            flame_var_smoke_max_rotation: self.flame_var_smoke_max_rotation,
-            flame_var_smoke_max_rot_vel: self.flame_var_smoke_max_rot_vel,
-            flame_var_smoke_friction: self.flame_var_smoke_friction,
-            flame_var_smoke_end_size_add: self.flame_var_smoke_end_size_add,
-            flame_var_smoke_start_size_add: self.flame_var_smoke_start_size_add,
-            flame_var_smoke_origin_size_ofs_z_scale: self.flame_var_smoke_origin_size_ofs_z_scale,
-            flame_var_smoke_origin_ofs_z: self.flame_var_smoke_origin_ofs_z,
-            flame_var_smoke_fadein: self.flame_var_smoke_fadein,
-            flame_var_smoke_fadeout: self.flame_var_smoke_fadeout,
-            flame_var_smoke_max_alpha: self.flame_var_smoke_max_alpha,
-            flame_var_smoke_brightness: self.flame_var_smoke_brightness,
-            flame_var_smoke_origin_offset: self.flame_var_smoke_origin_offset,
-            flame_var_collision_speed_scale: self.flame_var_collision_speed_scale,
-            flame_var_collision_volume_scale: self.flame_var_collision_volume_scale,
-            name,
-            fire,
-            smoke,
-            heat,
-            drips,
-            stream_fuel,
-            stream_fuel_2,
-            stream_flame,
-            stream_flame_2,
-            flame_off_loop_sound,
-            flame_ignite_sound,
-            flame_on_loop_sound,
-            flame_cooldown_sound,
-        })
+impl FlameTable {
+    fn material_slots(&self) -> [&Option<Box<techset::Material>>; 8] {
+        [
+            &self.fire,
+            &self.smoke,
+            &self.heat,
+            &self.drips,
+            &self.stream_fuel,
+            &self.stream_fuel_2,
+            &self.stream_flame,
+            &self.stream_flame_2,
+        ]
+    }
+
+    /// Every material the table references, in field order.
+    pub fn all_materials(&self) -> Vec<&techset::Material> {
+        self.material_slots()
+            .into_iter()
+            .filter_map(|m| m.as_deref())
+            .collect()
+    }
+
+    /// The names of [`Self::all_materials`].
+    pub fn referenced_material_names(&self) -> Vec<&str> {
+        self.all_materials()
+            .into_iter()
+            .map(|m| m.info.name.get())
+            .collect()
+    }
+
+    /// The off-loop, ignite, on-loop, and cooldown sounds, in that order.
+    /// Unset sounds are empty strings.
+    pub fn all_sound_names(&self) -> Vec<&str> {
+        [
+            &self.flame_off_loop_sound,
+            &self.flame_ignite_sound,
+            &self.flame_on_loop_sound,
+            &self.flame_cooldown_sound,
+        ]
+        .into_iter()
+        .map(XString::get)
+        .collect()
+    }
+
+    /// Whether all eight materials are present. Some tables only define a
+    /// few.
+    pub fn has_complete_materials(&self) -> bool {
+        self.material_slots().into_iter().all(Option::is_some)
     }
 }
@@ -1,14 +1,17 @@
 use core::mem::transmute;
 
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
 use serde::{Deserialize, Serialize};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
 use crate::{
-    FatPointer, Ptr32, Result, ScriptString, T5XFileDeserialize, T5XFileSerialize,
-    XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size, common::Vec3,
+    Error, ErrorKind, FatPointer, Result, ScriptString, T5XFileDeserialize, T5XFileSerialize,
+    XFileDeserializeInto, XFileSerialize, XString, assert_size,
+    common::Vec3,
+    file_line_col,
+    util::{Ptr32, XStringRaw},
 };
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -97,7 +100,7 @@ impl<'a> XFileDeserializeInto<XAnimParts, ()> for XAnimPartsRaw<'a> {
             .to_array(self.bone_count[PART_TYPE_ALL] as _)
             .to_vec(de)?
             .into_iter()
-            .map(|s| s.to_string(de).map(XString))
+            .map(|s| s.to_string(de).map(XString::from))
             .collect::<Result<Vec<_>>>()?;
         //dbg!(&names);
         let notify = self
@@ -241,6 +244,759 @@ impl<'a> XFileSerialize<()> for XAnimParts {
     }
 }
 
+impl XAnimParts {
+    /// The net displacement of the root bone from the first frame to the
+    /// last. [`None`] for non-delta animations.
+    pub fn root_motion_delta(&self) -> Option<Vec3> {
+        if !self.delta {
+            return None;
+        }
+
+        let Some(trans) = self.delta_part.as_ref().and_then(|d| d.trans.as_ref()) else {
+            return Some(Vec3::default());
+        };
+
+        let (first, last) = trans.endpoints()?;
+        Some(last - first)
+    }
+
+    /// The net rotation of the root bone from the first frame to the last,
+    /// as an `[x, y, z, w]` quaternion. Delta rotations are yaw-only, so only
+    /// `z` and `w` are ever nonzero. [`None`] for non-delta animations.
+    pub fn root_rotation_delta(&self) -> Option<[f32; 4]> {
+        if !self.delta {
+            return None;
+        }
+
+        let Some(quat) = self.delta_part.as_ref().and_then(|d| d.quat.as_ref()) else {
+            return Some([0.0, 0.0, 0.0, 1.0]);
+        };
+
+        let ([z0, w0], [z1, w1]) = quat.endpoints()?;
+        // last * conjugate(first), for rotations about the z axis only
+        Some([0.0, 0.0, z1 * w0 - w1 * z0, w1 * w0 + z1 * z0])
+    }
+
+    /// How far the root bone moves each time a looping animation plays
+    /// through. [`None`] for non-looping or non-delta animations.
+    pub fn motion_per_cycle(&self) -> Option<Vec3> {
+        if !self.loop_ {
+            return None;
+        }
+
+        self.root_motion_delta()
+    }
+
+    /// Whether the animation has the engine place the left hand on the
+    /// weapon with IK. That's the only IK data an `XAnimParts` has; the
+    /// targets themselves aren't stored in the asset.
+    pub fn has_ik_data(&self) -> bool {
+        self.left_hand_grip_ik
+    }
+
+    /// The root bone's displacement at `frame` relative to the first frame,
+    /// interpolated between keyframes. [`None`] for non-delta animations or
+    /// if `frame` is past the last frame.
+    pub fn root_offset_at(&self, frame: usize) -> Option<Vec3> {
+        if !self.delta || frame > self.numframes as usize {
+            return None;
+        }
+
+        let Some(trans) = self.delta_part.as_ref().and_then(|d| d.trans.as_ref()) else {
+            return Some(Vec3::default());
+        };
+
+        Some(trans.at_frame(frame as f32)? - trans.at_frame(0.0)?)
+    }
+
+    /// Blends `self` with `other`: a `weight` of `0.0` is all `self`, `1.0`
+    /// is all `other`. `other` is sampled at the same point through its
+    /// length as `self`, so the result is as long as `self`.
+    ///
+    /// The root motion in [`Self::delta_part`] isn't one of [`Self::names`],
+    /// and is always blended. `bone_mask` is indexed like [`Self::names`],
+    /// and bones that are `false` in it (or past its end) keep `self`'s
+    /// tracks, as do bones `other` doesn't have. Other bones are blended
+    /// relative to their parents (see [`Self::bone_tracks`]), a channel at
+    /// a time; a channel only one of the two animates keeps `self`'s.
+    pub fn blend_with(
+        &self,
+        other: &XAnimParts,
+        weight: f32,
+        bone_mask: &[bool],
+    ) -> Result<XAnimParts> {
+        let numframes = self.numframes;
+        let scale = match numframes {
+            0 => 0.0,
+            n => other.numframes as f32 / n as f32,
+        };
+
+        let mut blended = if self.delta || other.delta {
+            let (trans, yaw) = (0..=numframes)
+                .map(|f| {
+                    let (f, g) = (f as f32, f as f32 * scale);
+                    let (a, b) = (self.root_trans_at(f), other.root_trans_at(g));
+                    (
+                        a + (b - a) * weight,
+                        nlerp_yaw(self.root_yaw_at(f), other.root_yaw_at(g), weight),
+                    )
+                })
+                .unzip::<_, _, Vec<_>, Vec<_>>();
+            self.with_root_motion(numframes, &trans, &yaw)
+        } else {
+            self.clone()
+        };
+
+        if weight == 0.0 || !bone_mask.iter().any(|&b| b) {
+            return Ok(blended);
+        }
+
+        let others = other.bone_tracks()?;
+        let tracks = self
+            .bone_tracks()?
+            .into_iter()
+            .zip(bone_mask.iter().copied().chain(core::iter::repeat(false)))
+            .map(
+                |(track, masked)| match others.iter().find(|o| o.name == track.name) {
+                    Some(o) if masked => track.blend(o, weight, numframes, scale),
+                    _ => track,
+                },
+            )
+            .collect::<Vec<_>>();
+        blended.set_bone_tracks(&tracks)?;
+        Ok(blended)
+    }
+
+    /// Applies `self`, an additive animation, on top of `base`. The motion
+    /// `self` makes from its first frame is added to `base`'s at each
+    /// frame, for the root and for each of `self`'s bones, relative to its
+    /// parent; once the shorter animation ends, it holds its last frame.
+    /// The result is a copy of `base` as long as the longer of the two.
+    ///
+    /// Bones `base` doesn't have are added to the result, and channels
+    /// `base` doesn't animate are added to the identity.
+    pub fn additive_apply(&self, base: &XAnimParts) -> Result<XAnimParts> {
+        let numframes = self.numframes.max(base.numframes);
+
+        let mut applied = if self.delta {
+            let (first_trans, first_yaw) = (self.root_trans_at(0.0), self.root_yaw_at(0.0));
+            let inverse_first_yaw = [-first_yaw[0], first_yaw[1]];
+            let (trans, yaw) = (0..=numframes)
+                .map(|f| {
+                    let f = f as f32;
+                    let offset = self.root_trans_at(f) - first_trans;
+                    let turn = compose_yaw(self.root_yaw_at(f), inverse_first_yaw);
+                    (
+                        base.root_trans_at(f) + offset,
+                        compose_yaw(base.root_yaw_at(f), turn),
+                    )
+                })
+                .unzip::<_, _, Vec<_>, Vec<_>>();
+            base.with_root_motion(numframes, &trans, &yaw)
+        } else {
+            XAnimParts {
+                numframes,
+                ..base.clone()
+            }
+        };
+
+        let deltas = self.bone_tracks()?;
+        if deltas.is_empty() && numframes == base.numframes {
+            return Ok(applied);
+        }
+
+        // Re-encoded even if there's nothing to add, since the key indices
+        // may need to be wider.
+        let mut tracks = base.bone_tracks()?;
+        for delta in &deltas {
+            match tracks.iter_mut().find(|t| t.name == delta.name) {
+                Some(track) => *track = track.add(delta, numframes),
+                None => tracks.push(XAnimBoneTrack::new(delta.name.clone()).add(delta, numframes)),
+            }
+        }
+        applied.set_bone_tracks(&tracks)?;
+        Ok(applied)
+    }
+
+    /// Decodes the tracks of each of [`Self::names`], in order, from the
+    /// packed streams. Each bone's transform is relative to its parent's.
+    ///
+    /// [`Self::names`] are sorted by how their rotations are stored, and
+    /// [`Self::bone_count`] counts each kind, by part type:
+    ///
+    /// | Part type | Rotation          | Translation            |
+    /// |-----------|-------------------|------------------------|
+    /// | 0         | none              |                        |
+    /// | 1         | yaw, keyed        |                        |
+    /// | 2         | full, keyed       |                        |
+    /// | 3         | yaw, one key      |                        |
+    /// | 4         | full, one key     |                        |
+    /// | 5         |                   | 8-bit keys             |
+    /// | 6         |                   | 16-bit keys            |
+    /// | 7         |                   | one key                |
+    /// | 8         |                   | none                   |
+    /// | 9         | all bones         |                        |
+    ///
+    /// Rotations are read in the order of [`Self::names`], and translations
+    /// by part type, each starting with the bone's index into
+    /// [`Self::names`] in [`Self::data_byte`]. A keyed track starts with its
+    /// number of keys less one in [`Self::data_short`], followed there by
+    /// a coarse lookup table of `((size - 1) >> 8) + 1` shorts if that's
+    /// `64` or more, which is skipped here; each key's frame comes next in
+    /// [`Self::indices`]. Keyed rotations are `[z, w]` or `[x, y, z, w]`
+    /// quantized to `i16`s in [`Self::random_data_short`], and translations
+    /// are a minimum and a step size of 3 `f32`s each in [`Self::data_int`],
+    /// with each key's steps from the minimum in [`Self::random_data_byte`]
+    /// or [`Self::random_data_short`]. Rotations with one key are stored in
+    /// [`Self::data_short`], and translations in [`Self::data_int`].
+    ///
+    /// Returns [`ErrorKind::BrokenInvariant`] if the streams are too short
+    /// for the tracks [`Self::bone_count`] says there are.
+    pub fn bone_tracks(&self) -> Result<Vec<XAnimBoneTrack>> {
+        let [
+            no_quat,
+            half_quat,
+            full_quat,
+            half_quat_no_size,
+            full_quat_no_size,
+            small_trans,
+            trans,
+            trans_no_size,
+            _,
+            _,
+        ] = self.bone_count.map(|c| c as usize);
+        let rotated = half_quat + full_quat + half_quat_no_size + full_quat_no_size;
+        if no_quat + rotated != self.names.len() {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "XAnimParts: {} bones, but {} names",
+                    no_quat + rotated,
+                    self.names.len()
+                )),
+            ));
+        }
+
+        let mut tracks = self
+            .names
+            .iter()
+            .map(|name| XAnimBoneTrack::new(name.get().to_string()))
+            .collect::<Vec<_>>();
+        let mut packed = PackedReader::new(self);
+
+        for (i, track) in tracks[no_quat..].iter_mut().enumerate() {
+            track.rotation = if i < half_quat + full_quat {
+                let half = i < half_quat;
+                packed
+                    .keys()?
+                    .into_iter()
+                    .map(|k| Ok((k, packed.rotation(half, true)?)))
+                    .collect::<Result<_>>()?
+            } else {
+                let half = i < half_quat + full_quat + half_quat_no_size;
+                vec![(0, packed.rotation(half, false)?)]
+            };
+        }
+
+        for i in 0..small_trans + trans + trans_no_size {
+            let bone = packed.bone_index(tracks.len())?;
+            tracks[bone].translation = if i < small_trans + trans {
+                let keys = packed.keys()?;
+                let (mins, step) = (packed.vec3()?, packed.vec3()?);
+                keys.into_iter()
+                    .map(|k| Ok((k, packed.translation(i < small_trans, mins, step)?)))
+                    .collect::<Result<_>>()?
+            } else {
+                vec![(0, packed.vec3()?.into())]
+            };
+        }
+
+        Ok(tracks)
+    }
+
+    /// Packs `tracks` into [`Self::names`], [`Self::bone_count`] and the
+    /// streams, replacing what was there, as [`Self::bone_tracks`] decodes
+    /// them. Bones are sorted by how their rotations are stored, keeping
+    /// their order otherwise. Rotations with no `x` or `y` are stored as
+    /// yaw, and keyed translations with 16 bits.
+    ///
+    /// Returns [`ErrorKind::BrokenInvariant`] if there are more than 255
+    /// bones, or if any track's keys aren't in increasing order up to
+    /// [`Self::numframes`].
+    pub fn set_bone_tracks(&mut self, tracks: &[XAnimBoneTrack]) -> Result<()> {
+        if tracks.len() > u8::MAX as usize {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "XAnimParts: {} bones, but at most 255 can be stored",
+                    tracks.len()
+                )),
+            ));
+        }
+        for track in tracks {
+            let rotation = track.rotation.iter().map(|k| k.0);
+            let translation = track.translation.iter().map(|k| k.0);
+            if !keys_in_order(rotation, self.numframes)
+                || !keys_in_order(translation, self.numframes)
+            {
+                return Err(Error::new(
+                    file_line_col!(),
+                    ErrorKind::BrokenInvariant(format!(
+                        "XAnimParts: bone {}'s keys aren't in order up to frame {}",
+                        track.name, self.numframes
+                    )),
+                ));
+            }
+        }
+
+        let rotations = tracks
+            .iter()
+            .map(|t| {
+                t.rotation
+                    .iter()
+                    .map(|&(k, q)| (k, quantize_quat(q)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let part_type = |keys: &[(u16, [i16; 4])]| {
+            let half = keys.iter().all(|(_, [x, y, ..])| *x == 0 && *y == 0);
+            match (keys.len(), half) {
+                (0, _) => 0,
+                (1, true) => 3,
+                (1, false) => 4,
+                (_, true) => 1,
+                (_, false) => 2,
+            }
+        };
+        let mut order = (0..tracks.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| part_type(&rotations[i]));
+
+        let mut bone_count = [0u8; 10];
+        let mut packed = PackedWriter::new();
+        for &i in &order {
+            let part_type = part_type(&rotations[i]);
+            bone_count[part_type] += 1;
+            let half = part_type == 1 || part_type == 3;
+            match rotations[i].as_slice() {
+                [] => {}
+                [(_, q)] => packed.rotation(*q, half, false),
+                keys => {
+                    packed.keys(keys.iter().map(|k| k.0));
+                    for &(_, q) in keys {
+                        packed.rotation(q, half, true);
+                    }
+                }
+            }
+        }
+        bone_count[PART_TYPE_ALL] = tracks.len() as _;
+
+        for (bone, &i) in order.iter().enumerate() {
+            if let keys @ [_, _, ..] = tracks[i].translation.as_slice() {
+                bone_count[6] += 1;
+                packed.bytes.push(bone as _);
+                packed.keys(keys.iter().map(|k| k.0));
+                let samples = keys.iter().map(|k| k.1).collect::<Vec<_>>();
+                let (mins, step, frames) = quantize_translations(&samples);
+                packed.vec3(mins);
+                packed.vec3(step);
+                for q in frames {
+                    packed.random_shorts.extend(q.map(|c| c as i16));
+                }
+            }
+        }
+        for (bone, &i) in order.iter().enumerate() {
+            match tracks[i].translation.as_slice() {
+                [(_, v)] => {
+                    bone_count[7] += 1;
+                    packed.bytes.push(bone as _);
+                    packed.vec3(v.get());
+                }
+                [] => bone_count[8] += 1,
+                _ => {}
+            }
+        }
+
+        self.names = order
+            .iter()
+            .map(|&i| XString::from(tracks[i].name.clone()))
+            .collect();
+        self.bone_count = bone_count;
+        self.index_count = packed.indices.len() as _;
+        self.indices = if self.numframes < 256 {
+            XAnimIndices::_1(packed.indices.iter().map(|&k| k as u8).collect())
+        } else {
+            XAnimIndices::_2(packed.indices)
+        };
+        self.data_byte = packed.bytes;
+        self.data_short = packed.shorts;
+        self.data_int = packed.ints;
+        self.random_data_byte = Vec::new();
+        self.random_data_short = packed.random_shorts;
+        self.random_data_int = Vec::new();
+        Ok(())
+    }
+
+    /// How long one play through the animation takes. [`Self::frequency`]
+    /// is how many times a second it plays through, so this is its
+    /// reciprocal, falling back to [`Self::numframes`] at
+    /// [`Self::framerate`] if it isn't set. `0.0` if neither is.
+    pub fn duration_seconds(&self) -> f32 {
+        if self.frequency > 0.0 {
+            1.0 / self.frequency
+        } else if self.framerate > 0.0 {
+            self.numframes as f32 / self.framerate
+        } else {
+            0.0
+        }
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.loop_
+    }
+
+    /// [`Self::notify`], with times converted to frames and seconds, sorted
+    /// by time. Notetrack times are stored as a fraction of the way
+    /// through the animation, and aren't necessarily stored in order.
+    pub fn notetracks(&self) -> Vec<Notetrack> {
+        let duration = self.duration_seconds();
+        let mut notetracks = self
+            .notify
+            .iter()
+            .map(|n| Notetrack {
+                name: n.name.get().to_string(),
+                frame: (n.time * self.numframes as f32).round().max(0.0) as u32,
+                time_seconds: n.time * duration,
+            })
+            .collect::<Vec<_>>();
+        notetracks.sort_by(|a, b| a.time_seconds.total_cmp(&b.time_seconds));
+        notetracks
+    }
+
+    /// The [`Self::notetracks`] whose names start with `prefix`, e.g.
+    /// `"sound_"`.
+    pub fn notetracks_matching(&self, prefix: &str) -> Vec<Notetrack> {
+        let mut notetracks = self.notetracks();
+        notetracks.retain(|n| n.name.starts_with(prefix));
+        notetracks
+    }
+
+    /// The root bone's translation at `frame`; zero for non-delta
+    /// animations.
+    fn root_trans_at(&self, frame: f32) -> Vec3 {
+        self.delta_part
+            .as_ref()
+            .and_then(|d| d.trans.as_ref())
+            .filter(|_| self.delta)
+            .and_then(|t| t.at_frame(frame))
+            .unwrap_or_default()
+    }
+
+    /// The root bone's `[z, w]` rotation at `frame`; no rotation for
+    /// non-delta animations.
+    fn root_yaw_at(&self, frame: f32) -> [f32; 2] {
+        self.delta_part
+            .as_ref()
+            .and_then(|d| d.quat.as_ref())
+            .filter(|_| self.delta)
+            .and_then(|q| q.at_frame(frame))
+            .unwrap_or([0.0, 1.0])
+    }
+
+    /// A copy of `self`, `numframes` long, whose root motion has a key at
+    /// each frame.
+    fn with_root_motion(&self, numframes: u16, trans: &[Vec3], yaw: &[[f32; 2]]) -> XAnimParts {
+        XAnimParts {
+            numframes,
+            delta: true,
+            delta_part: Some(Box::new(XAnimDeltaPart {
+                trans: Some(Box::new(XAnimPartTrans::from_samples(trans, numframes))),
+                quat: Some(Box::new(XAnimDeltaPartQuat::from_samples(yaw, numframes))),
+            })),
+            ..self.clone()
+        }
+    }
+}
+
+/// A bone's animation, as returned by [`XAnimParts::bone_tracks`]. Its
+/// transform is relative to its parent's.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct XAnimBoneTrack {
+    pub name: String,
+    /// `[x, y, z, w]` rotation keys, by frame. Empty if the animation
+    /// doesn't rotate the bone.
+    pub rotation: Vec<(u16, [f32; 4])>,
+    /// Translation keys, by frame. Empty if the animation doesn't move the
+    /// bone.
+    pub translation: Vec<(u16, Vec3)>,
+}
+
+impl XAnimBoneTrack {
+    /// A track for `name` that doesn't animate it.
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            rotation: Vec::new(),
+            translation: Vec::new(),
+        }
+    }
+
+    /// The rotation at `frame`, interpolated between the keys on either
+    /// side of it and clamped to the first and last. [`None`] if the bone
+    /// isn't rotated.
+    pub fn rotation_at(&self, frame: f32) -> Option<[f32; 4]> {
+        let keys = self.rotation.iter().map(|k| k.0 as f32).collect::<Vec<_>>();
+        let (k0, k1, t) = surrounding(&keys, frame)?;
+        Some(nlerp_quat(self.rotation[k0].1, self.rotation[k1].1, t))
+    }
+
+    /// The translation at `frame`, linearly interpolated between the keys
+    /// on either side of it and clamped to the first and last. [`None`] if
+    /// the bone isn't moved.
+    pub fn translation_at(&self, frame: f32) -> Option<Vec3> {
+        let keys = self
+            .translation
+            .iter()
+            .map(|k| k.0 as f32)
+            .collect::<Vec<_>>();
+        let (k0, k1, t) = surrounding(&keys, frame)?;
+        let (a, b) = (self.translation[k0].1, self.translation[k1].1);
+        Some(a + (b - a) * t)
+    }
+
+    /// `self` blended with `other` at each of `numframes`, sampling `other`
+    /// `scale` times as far in. Channels either doesn't animate are left as
+    /// they are.
+    fn blend(self, other: &XAnimBoneTrack, weight: f32, numframes: u16, scale: f32) -> Self {
+        let rotation = (0..=numframes)
+            .map(|f| {
+                let a = self.rotation_at(f as f32)?;
+                let b = other.rotation_at(f as f32 * scale)?;
+                Some((f, nlerp_quat(a, b, weight)))
+            })
+            .collect::<Option<Vec<_>>>();
+        let translation = (0..=numframes)
+            .map(|f| {
+                let a = self.translation_at(f as f32)?;
+                let b = other.translation_at(f as f32 * scale)?;
+                Some((f, a + (b - a) * weight))
+            })
+            .collect::<Option<Vec<_>>>();
+
+        Self {
+            rotation: rotation.unwrap_or(self.rotation),
+            translation: translation.unwrap_or(self.translation),
+            name: self.name,
+        }
+    }
+
+    /// `self` with the motion `delta` makes from its first frame added at
+    /// each of `numframes`. Channels `delta` doesn't animate are left as
+    /// they are.
+    fn add(&self, delta: &XAnimBoneTrack, numframes: u16) -> Self {
+        let rotation = match delta.rotation_at(0.0) {
+            Some(first @ [x, y, z, w]) => {
+                let inverse_first = [-x, -y, -z, w];
+                (0..=numframes)
+                    .map(|f| {
+                        let frame = f as f32;
+                        let turn =
+                            compose_quat(delta.rotation_at(frame).unwrap_or(first), inverse_first);
+                        let base = self.rotation_at(frame).unwrap_or([0.0, 0.0, 0.0, 1.0]);
+                        (f, compose_quat(base, turn))
+                    })
+                    .collect()
+            }
+            None => self.rotation.clone(),
+        };
+        let translation = match delta.translation_at(0.0) {
+            Some(first) => (0..=numframes)
+                .map(|f| {
+                    let frame = f as f32;
+                    let offset = delta.translation_at(frame).unwrap_or(first) - first;
+                    let base = self.translation_at(frame).unwrap_or_default();
+                    (f, base + offset)
+                })
+                .collect(),
+            None => self.translation.clone(),
+        };
+
+        Self {
+            name: self.name.clone(),
+            rotation,
+            translation,
+        }
+    }
+}
+
+/// Reads [`XAnimParts`]' packed streams, in the order
+/// [`XAnimParts::bone_tracks`] describes.
+struct PackedReader<'a> {
+    numframes: u16,
+    bytes: core::slice::Iter<'a, u8>,
+    shorts: core::slice::Iter<'a, i16>,
+    ints: core::slice::Iter<'a, i32>,
+    random_bytes: core::slice::Iter<'a, u8>,
+    random_shorts: core::slice::Iter<'a, i16>,
+    indices: alloc::vec::IntoIter<u16>,
+}
+
+impl<'a> PackedReader<'a> {
+    fn new(parts: &'a XAnimParts) -> Self {
+        let indices = match &parts.indices {
+            XAnimIndices::_1(v) => v.iter().map(|&k| k as u16).collect::<Vec<_>>(),
+            XAnimIndices::_2(v) => v.clone(),
+        };
+
+        Self {
+            numframes: parts.numframes,
+            bytes: parts.data_byte.iter(),
+            shorts: parts.data_short.iter(),
+            ints: parts.data_int.iter(),
+            random_bytes: parts.random_data_byte.iter(),
+            random_shorts: parts.random_data_short.iter(),
+            indices: indices.into_iter(),
+        }
+    }
+
+    /// The frame of each of a keyed track's keys.
+    fn keys(&mut self) -> Result<Vec<u16>> {
+        let size = next(&mut self.shorts, "data_short")? as u16;
+        if size >= 64 {
+            for _ in 0..=(size - 1) >> 8 {
+                next(&mut self.shorts, "data_short")?;
+            }
+        }
+
+        (0..=size)
+            .map(|_| {
+                let k = self.indices.next().ok_or_else(|| too_short("indices"))?;
+                if k > self.numframes {
+                    return Err(Error::new(
+                        file_line_col!(),
+                        ErrorKind::BrokenInvariant(format!(
+                            "XAnimParts: key at frame {k}, past the last frame ({})",
+                            self.numframes
+                        )),
+                    ));
+                }
+                Ok(k)
+            })
+            .collect()
+    }
+
+    fn rotation(&mut self, half: bool, keyed: bool) -> Result<[f32; 4]> {
+        let (stream, name) = if keyed {
+            (&mut self.random_shorts, "random_data_short")
+        } else {
+            (&mut self.shorts, "data_short")
+        };
+        let mut q = [0.0; 4];
+        for c in &mut q[if half { 2 } else { 0 }..] {
+            *c = next(stream, name)? as f32;
+        }
+        Ok(normalize_quat(q))
+    }
+
+    fn bone_index(&mut self, bones: usize) -> Result<usize> {
+        let bone = next(&mut self.bytes, "data_byte")? as usize;
+        if bone >= bones {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "XAnimParts: translation for bone {bone}, but there are only {bones}"
+                )),
+            ));
+        }
+        Ok(bone)
+    }
+
+    fn vec3(&mut self) -> Result<[f32; 3]> {
+        let mut v = [0.0; 3];
+        for c in &mut v {
+            *c = f32::from_bits(next(&mut self.ints, "data_int")? as u32);
+        }
+        Ok(v)
+    }
+
+    fn translation(&mut self, small: bool, mins: [f32; 3], step: [f32; 3]) -> Result<Vec3> {
+        let mut v = mins;
+        for (i, c) in v.iter_mut().enumerate() {
+            let q = if small {
+                next(&mut self.random_bytes, "random_data_byte")? as f32
+            } else {
+                next(&mut self.random_shorts, "random_data_short")? as u16 as f32
+            };
+            *c += q * step[i];
+        }
+        Ok(v.into())
+    }
+}
+
+fn next<T: Copy>(stream: &mut core::slice::Iter<T>, name: &str) -> Result<T> {
+    stream.next().copied().ok_or_else(|| too_short(name))
+}
+
+fn too_short(name: &str) -> Error {
+    Error::new(
+        file_line_col!(),
+        ErrorKind::BrokenInvariant(format!("XAnimParts: {name} is too short for its tracks")),
+    )
+}
+
+/// Builds [`XAnimParts`]' packed streams. See [`PackedReader`].
+struct PackedWriter {
+    bytes: Vec<u8>,
+    shorts: Vec<i16>,
+    ints: Vec<i32>,
+    random_shorts: Vec<i16>,
+    indices: Vec<u16>,
+}
+
+impl PackedWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            shorts: Vec::new(),
+            ints: Vec::new(),
+            random_shorts: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    /// Writes a keyed track's size, lookup table and key frames.
+    fn keys(&mut self, keys: impl ExactSizeIterator<Item = u16>) {
+        let size = (keys.len() - 1) as u16;
+        let start = self.indices.len();
+        self.shorts.push(size as _);
+        self.indices.extend(keys);
+        if size >= 64 {
+            for i in 0..=(size as usize - 1) >> 8 {
+                self.shorts.push(self.indices[start + i * 256] as _);
+            }
+        }
+    }
+
+    fn rotation(&mut self, q: [i16; 4], half: bool, keyed: bool) {
+        let q = &q[if half { 2 } else { 0 }..];
+        if keyed {
+            self.random_shorts.extend_from_slice(q);
+        } else {
+            self.shorts.extend_from_slice(q);
+        }
+    }
+
+    fn vec3(&mut self, v: [f32; 3]) {
+        self.ints.extend(v.map(|c| c.to_bits() as i32));
+    }
+}
+
+/// Whether `keys` increase, ending no later than `numframes`.
+fn keys_in_order(keys: impl Iterator<Item = u16>, numframes: u16) -> bool {
+    let keys = keys.collect::<Vec<_>>();
+    keys.windows(2).all(|w| w[0] < w[1]) && keys.last().is_none_or(|&k| k <= numframes)
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Debug, Default, Deserialize)]
 pub(crate) struct XAnimIndicesRaw<'a>(Ptr32<'a, ()>);
@@ -296,6 +1052,17 @@ pub struct XAnimNotifyInfo {
     pub time: f32,
 }
 
+/// A notetrack, as returned by [`XAnimParts::notetracks`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notetrack {
+    pub name: String,
+    /// The nearest frame to the notetrack.
+    pub frame: u32,
+    /// How far into the animation the notetrack fires.
+    pub time_seconds: f32,
+}
+
 impl XFileDeserializeInto<XAnimNotifyInfo, ()> for XAnimNotifyInfoRaw {
     fn xfile_deserialize_into(
         &self,
@@ -303,7 +1070,7 @@ impl XFileDeserializeInto<XAnimNotifyInfo, ()> for XAnimNotifyInfoRaw {
         _data: (),
     ) -> Result<XAnimNotifyInfo> {
         Ok(XAnimNotifyInfo {
-            name: XString(self.name.to_string(de).unwrap_or_default()),
+            name: XString::from(self.name.to_string(de).unwrap_or_default()),
             time: self.time,
         })
     }
@@ -311,7 +1078,7 @@ impl XFileDeserializeInto<XAnimNotifyInfo, ()> for XAnimNotifyInfoRaw {
 
 impl XFileSerialize<()> for XAnimNotifyInfo {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let name = ser.get_or_insert_script_string(self.name.get())?;
+        let name = ser.intern(self.name.get())?;
 
         let notify = XAnimNotifyInfoRaw {
             name,
@@ -401,6 +1168,124 @@ impl XFileDeserializeInto<XAnimPartTrans, u16> for XAnimPartTransRaw {
     }
 }
 
+impl XAnimPartTrans {
+    /// The translation at the first and last keyframes.
+    fn endpoints(&self) -> Option<(Vec3, Vec3)> {
+        match self.u.as_ref()? {
+            XAnimPartTransData::Frame0(v) => Some((*v, *v)),
+            XAnimPartTransData::Frames(f) => {
+                let last = match &f.frames {
+                    XAnimDynamicFrames::_1(v) => v.len(),
+                    XAnimDynamicFrames::_2(v) => v.len(),
+                }
+                .checked_sub(1)?;
+                Some((f.frame(0)?, f.frame(last)?))
+            }
+        }
+    }
+
+    /// The translation at `frame`, linearly interpolated between the
+    /// keyframes on either side of it and clamped to the first and last.
+    fn at_frame(&self, frame: f32) -> Option<Vec3> {
+        let f = match self.u.as_ref()? {
+            XAnimPartTransData::Frame0(v) => return Some(*v),
+            XAnimPartTransData::Frames(f) => f,
+        };
+
+        let (k0, k1, t) = surrounding_keys(&f.indices, frame)?;
+        let (a, b) = (f.frame(k0)?.get(), f.frame(k1)?.get());
+        Some(Vec3::from(core::array::from_fn::<_, 3, _>(|i| {
+            a[i] + (b[i] - a[i]) * t
+        })))
+    }
+
+    /// A translation with a key for each of `samples`, quantized to 16 bits.
+    /// `numframes` is the animation's, which decides how wide the key
+    /// indices are.
+    fn from_samples(samples: &[Vec3], numframes: u16) -> Self {
+        if let [v] = samples {
+            return Self {
+                size: 0,
+                small_trans: 0,
+                u: Some(XAnimPartTransData::Frame0(*v)),
+            };
+        }
+
+        let (mins, step, frames) = quantize_translations(samples);
+
+        Self {
+            size: (samples.len() - 1) as _,
+            small_trans: 0,
+            u: Some(XAnimPartTransData::Frames(XAnimPartTransFrames {
+                mins: mins.into(),
+                maxs: step.into(),
+                frames: XAnimDynamicFrames::_2(frames),
+                indices: key_indices(samples.len(), numframes),
+            })),
+        }
+    }
+}
+
+/// Quantizes `samples` to 16 bits each, spread evenly between the smallest
+/// and largest on each axis. Returns the smallest, the size of each step,
+/// and each sample's steps from the smallest.
+fn quantize_translations(samples: &[Vec3]) -> ([f32; 3], [f32; 3], Vec<[u16; 3]>) {
+    let (mut mins, mut maxs) = ([f32::MAX; 3], [f32::MIN; 3]);
+    for v in samples {
+        for (i, c) in v.get().into_iter().enumerate() {
+            mins[i] = mins[i].min(c);
+            maxs[i] = maxs[i].max(c);
+        }
+    }
+    let step = core::array::from_fn::<_, 3, _>(|i| (maxs[i] - mins[i]) / u16::MAX as f32);
+    let frames = samples
+        .iter()
+        .map(|v| {
+            let v = v.get();
+            core::array::from_fn(|i| match step[i] {
+                0.0 => 0,
+                step_i => ((v[i] - mins[i]) / step_i).round() as u16,
+            })
+        })
+        .collect();
+
+    (mins, step, frames)
+}
+
+/// The keys on either side of `frame` and how far it is from the first to
+/// the second, clamped to the first and last keys.
+fn surrounding_keys(indices: &XAnimDynamicIndices, frame: f32) -> Option<(usize, usize, f32)> {
+    let keys = match indices {
+        XAnimDynamicIndices::_1(v) => v.iter().map(|&k| k as f32).collect::<Vec<_>>(),
+        XAnimDynamicIndices::_2(v) => v.iter().map(|&k| k as f32).collect(),
+    };
+    surrounding(&keys, frame)
+}
+
+/// [`surrounding_keys`], for keys at frames `keys`.
+fn surrounding(keys: &[f32], frame: f32) -> Option<(usize, usize, f32)> {
+    let next = keys.partition_point(|&k| k <= frame);
+    if next == 0 {
+        return (!keys.is_empty()).then_some((0, 0, 0.0));
+    }
+    if next == keys.len() {
+        return Some((next - 1, next - 1, 0.0));
+    }
+
+    let (k0, k1) = (keys[next - 1], keys[next]);
+    Some((next - 1, next, (frame - k0) / (k1 - k0)))
+}
+
+/// Key indices `0..count`, as wide as an animation with `numframes` frames
+/// stores them.
+fn key_indices(count: usize, numframes: u16) -> XAnimDynamicIndices {
+    if numframes < 256 {
+        XAnimDynamicIndices::_1((0..count).map(|k| k as u8).collect())
+    } else {
+        XAnimDynamicIndices::_2((0..count).map(|k| k as u16).collect())
+    }
+}
+
 impl XFileSerialize<()> for XAnimPartTrans {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let u = if let Some(u) = &self.u {
@@ -531,6 +1416,24 @@ impl<'a> XFileDeserializeInto<XAnimPartTransFrames, (u16, u8, u16)>
         })
     }
 }
+impl XAnimPartTransFrames {
+    /// Dequantizes keyframe `key`. Despite its name, [`Self::maxs`] holds the
+    /// size of one quantization step on each axis, not the upper bound.
+    pub fn frame(&self, key: usize) -> Option<Vec3> {
+        let q = match &self.frames {
+            XAnimDynamicFrames::_1(v) => v.get(key)?.map(|c| c as f32),
+            XAnimDynamicFrames::_2(v) => v.get(key)?.map(|c| c as f32),
+        };
+        let (mins, step) = (self.mins.get(), self.maxs.get());
+
+        Some(Vec3::from([
+            mins[0] + q[0] * step[0],
+            mins[1] + q[1] * step[1],
+            mins[2] + q[2] * step[2],
+        ]))
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Debug, Default, Deserialize)]
 pub(crate) struct XAnimDynamicFramesRaw<'a>(Ptr32<'a, ()>);
@@ -633,6 +1536,117 @@ impl XFileDeserializeInto<XAnimDeltaPartQuat, u16> for XAnimDeltaPartQuatRaw {
     }
 }
 
+impl XAnimDeltaPartQuat {
+    /// The `[z, w]` rotation at the first and last keyframes.
+    fn endpoints(&self) -> Option<([f32; 2], [f32; 2])> {
+        let (first, last) = match self.u.as_ref()? {
+            XAnimDeltaPartQuatData::Frame0(q) => (*q, *q),
+            XAnimDeltaPartQuatData::Frames(f) => (*f.frames.first()?, *f.frames.last()?),
+        };
+        Some((dequantize_yaw(first), dequantize_yaw(last)))
+    }
+
+    /// The `[z, w]` rotation at `frame`, interpolated between the keyframes
+    /// on either side of it and clamped to the first and last.
+    fn at_frame(&self, frame: f32) -> Option<[f32; 2]> {
+        let f = match self.u.as_ref()? {
+            XAnimDeltaPartQuatData::Frame0(q) => return Some(dequantize_yaw(*q)),
+            XAnimDeltaPartQuatData::Frames(f) => f,
+        };
+
+        let (k0, k1, t) = surrounding_keys(&f.indices, frame)?;
+        let (a, b) = (*f.frames.get(k0)?, *f.frames.get(k1)?);
+        Some(nlerp_yaw(dequantize_yaw(a), dequantize_yaw(b), t))
+    }
+
+    /// A rotation with a key for each of `samples`. See
+    /// [`XAnimPartTrans::from_samples`].
+    fn from_samples(samples: &[[f32; 2]], numframes: u16) -> Self {
+        let quantize = |[z, w]: [f32; 2]| [z, w].map(|c| (c * i16::MAX as f32).round() as i16);
+        if let [q] = samples {
+            return Self {
+                size: 0,
+                u: Some(XAnimDeltaPartQuatData::Frame0(quantize(*q))),
+            };
+        }
+
+        Self {
+            size: (samples.len() - 1) as _,
+            u: Some(XAnimDeltaPartQuatData::Frames(
+                XAnimDeltaPartQuatDataFrames {
+                    frames: samples.iter().copied().map(quantize).collect(),
+                    indices: key_indices(samples.len(), numframes),
+                },
+            )),
+        }
+    }
+}
+
+/// Interpolates between two `[z, w]` yaw rotations along the shorter arc.
+fn nlerp_yaw(a: [f32; 2], b: [f32; 2], t: f32) -> [f32; 2] {
+    let b = if a[0] * b[0] + a[1] * b[1] < 0.0 {
+        [-b[0], -b[1]]
+    } else {
+        b
+    };
+    let q = [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t];
+    let len = (q[0] * q[0] + q[1] * q[1]).sqrt();
+    if len == 0.0 {
+        [0.0, 1.0]
+    } else {
+        [q[0] / len, q[1] / len]
+    }
+}
+
+/// `a * b` for `[z, w]` yaw rotations, i.e. `b` then `a`.
+fn compose_yaw(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] * b[1] + a[1] * b[0], a[1] * b[1] - a[0] * b[0]]
+}
+
+/// Interpolates between two `[x, y, z, w]` rotations along the shorter
+/// arc.
+fn nlerp_quat(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    let dot = (0..4).map(|i| a[i] * b[i]).sum::<f32>();
+    let b = if dot < 0.0 { b.map(|c| -c) } else { b };
+    normalize_quat(core::array::from_fn(|i| a[i] + (b[i] - a[i]) * t))
+}
+
+/// `a * b` for `[x, y, z, w]` rotations, i.e. `b` then `a`.
+fn compose_quat([ax, ay, az, aw]: [f32; 4], [bx, by, bz, bw]: [f32; 4]) -> [f32; 4] {
+    [
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+        aw * bw - ax * bx - ay * by - az * bz,
+    ]
+}
+
+/// Scales `q` to unit length, or returns no rotation if it's zero.
+fn normalize_quat(q: [f32; 4]) -> [f32; 4] {
+    let len = q.iter().map(|c| c * c).sum::<f32>().sqrt();
+    if len == 0.0 {
+        [0.0, 0.0, 0.0, 1.0]
+    } else {
+        q.map(|c| c / len)
+    }
+}
+
+/// Quantizes a unit `[x, y, z, w]` rotation to `i16`s.
+fn quantize_quat(q: [f32; 4]) -> [i16; 4] {
+    normalize_quat(q).map(|c| (c * i16::MAX as f32).round() as i16)
+}
+
+/// Converts a quantized `[z, w]` yaw quaternion to a unit one.
+fn dequantize_yaw(q: [i16; 2]) -> [f32; 2] {
+    let (z, w) = (q[0] as f32, q[1] as f32);
+    let len = (z * z + w * w).sqrt();
+    if len == 0.0 {
+        [0.0, 1.0]
+    } else {
+        [z / len, w / len]
+    }
+}
+
 impl XFileSerialize<()> for XAnimDeltaPartQuat {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let u = if let Some(u) = &self.u {
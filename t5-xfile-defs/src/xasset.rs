@@ -1,4 +1,12 @@
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    sync::Arc,
+    vec::Vec,
+};
+use core::any::{Any, TypeId};
+
 use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
 
@@ -6,10 +14,8 @@ use serde::{Deserialize, Serialize};
 use crate::prelude::*;
 
 use crate::{
-    Error, ErrorKind, FatPointerCountFirstU32, LocalizeEntry, LocalizeEntryRaw, MapEnts,
-    MapEntsRaw, PackIndex, PackIndexRaw, Ptr32, RawFile, RawFileRaw, Result, StringTable,
-    StringTableRaw, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFilePlatform,
-    XFileSerialize, XGlobals, XGlobalsRaw, XString, XStringRaw, assert_size,
+    Error, ErrorKind, FatPointer, Result, T5XFileDeserialize, T5XFileSerialize,
+    XFileDeserializeInto, XFilePlatform, XFileSerialize, XString, assert_size,
     clipmap::{ClipMap, ClipMapRaw},
     com_world::{ComWorld, ComWorldRaw},
     ddl::{DdlRoot, DdlRootRaw},
@@ -23,10 +29,15 @@ use crate::{
     glass::{Glasses, GlassesRaw},
     light::{GfxLightDef, GfxLightDefRaw},
     menu::{MenuDef, MenuDefRaw, MenuList, MenuListRaw},
+    misc::{
+        LocalizeEntry, LocalizeEntryRaw, MapEnts, MapEntsRaw, PackIndex, PackIndexRaw, RawFile,
+        RawFileRaw, StringTable, StringTableRaw, XGlobals, XGlobalsRaw,
+    },
     sound::{SndBank, SndBankRaw, SndDriverGlobals, SndDriverGlobalsRaw, SndPatch, SndPatchRaw},
     techset::{
         GfxImage, GfxImageRaw, Material, MaterialRaw, MaterialTechniqueSet, MaterialTechniqueSetRaw,
     },
+    util::{FatPointerCountFirstU32, Ptr32, XStringRaw},
     weapon::{WeaponVariantDef, WeaponVariantDefRaw},
     xanim::{XAnimParts, XAnimPartsRaw},
     xmodel::{PhysConstraints, PhysConstraintsRaw, PhysPreset, PhysPresetRaw, XModel, XModelRaw},
@@ -94,10 +105,176 @@ impl XAsset {
             Self::Console(a) => a.asset_type(),
         }
     }
+
+    /// See [`XAssetGeneric::raw_asset_type`].
+    pub fn raw_asset_type(&self) -> u32 {
+        match self {
+            Self::PC(a) => a.raw_asset_type(),
+            Self::Console(a) => a.raw_asset_type(),
+        }
+    }
+
+    /// See [`XAssetGeneric::xasset_raw`].
+    #[doc(hidden)]
+    pub fn xasset_raw(&self) -> XAssetRaw<'static> {
+        match self {
+            Self::PC(a) => a.xasset_raw(),
+            Self::Console(a) => a.xasset_raw(),
+        }
+    }
+
+    /// See [`XAssetGeneric::serialize_data`].
+    pub fn serialize_data(&self, ser: &mut impl T5XFileSerialize) -> Result<()> {
+        match self {
+            Self::PC(a) => a.serialize_data(ser),
+            Self::Console(a) => a.serialize_data(ser),
+        }
+    }
+}
+
+/// Where a deserialized asset came from.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Provenance {
+    /// Identifies the fastfile, e.g. its file name. Whatever the
+    /// deserializer was given; empty if it wasn't given anything.
+    pub source: String,
+    /// The asset's index in the file's asset list.
+    pub index: usize,
+    /// The bytes the asset took up in the inflated blob, as
+    /// `[start, end)` offsets.
+    pub span: (u64, u64),
+    pub platform: XFilePlatform,
 }
 
+/// An [`XAsset`] along with its [`Provenance`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
+pub struct LoadedAsset {
+    pub asset: XAsset,
+    pub provenance: Provenance,
+}
+
+impl From<LoadedAsset> for XAsset {
+    fn from(value: LoadedAsset) -> Self {
+        value.asset
+    }
+}
+
+impl AsRef<XAsset> for LoadedAsset {
+    fn as_ref(&self) -> &XAsset {
+        &self.asset
+    }
+}
+
+/// Returned by [`merge_asset_lists`] and [`merge_loaded_assets`]. All names
+/// are those of assets from `secondary`.
+#[derive(Clone, Default, Debug)]
+pub struct MergeReport {
+    /// Assets that replaced an asset of the same name and type.
+    pub overridden: Vec<String>,
+    /// Assets whose name wasn't present in `primary`.
+    pub added: Vec<String>,
+    /// Assets that share a name with an asset in `primary`, but not its
+    /// type. These are dropped.
+    pub conflicts: Vec<String>,
+}
+
+/// Merges `secondary` into `primary`, the same way an overlay (DLC, mod,
+/// etc.) would be loaded on top of a base XFile.
+///
+/// Assets in `secondary` with the same name and type as one in `primary`
+/// replace it in place. Assets with a name not in `primary` are appended.
+/// Assets with the same name as one in `primary` but a different type are
+/// reported as conflicts, and the `primary` version is kept.
+///
+/// `secondary` is merged one asset at a time, so an asset it lists twice is
+/// added once and then overridden by the later copy, and two of its assets
+/// with the same name but different types conflict.
+///
+/// Null assets in `secondary` are skipped.
+pub fn merge_asset_lists(
+    primary: Vec<XAsset>,
+    secondary: Vec<XAsset>,
+) -> (Vec<XAsset>, MergeReport) {
+    merge_by(primary, secondary, |a| a)
+}
+
+/// [`merge_asset_lists`] for [`LoadedAsset`]s. Each asset in the merged list
+/// keeps its [`Provenance`], so it says which list supplied the copy that
+/// won.
+pub fn merge_loaded_assets(
+    primary: Vec<LoadedAsset>,
+    secondary: Vec<LoadedAsset>,
+) -> (Vec<LoadedAsset>, MergeReport) {
+    merge_by(primary, secondary, |a| &a.asset)
+}
+
+fn merge_by<T>(
+    primary: Vec<T>,
+    secondary: Vec<T>,
+    asset: impl Fn(&T) -> &XAsset,
+) -> (Vec<T>, MergeReport) {
+    let mut merged = primary;
+    let mut report = MergeReport::default();
+
+    let mut indices = BTreeMap::new();
+    let mut names = BTreeSet::new();
+    for (i, a) in merged.iter().enumerate() {
+        let a = asset(a);
+        if let Some(name) = a.name() {
+            indices.insert((a.raw_asset_type(), String::from(name)), i);
+            names.insert(String::from(name));
+        }
+    }
+
+    for t in secondary {
+        let a = asset(&t);
+        if a.is_none() {
+            continue;
+        }
+
+        let Some(name) = a.name().map(String::from) else {
+            merged.push(t);
+            continue;
+        };
+
+        let key = (a.raw_asset_type(), name);
+        if let Some(&i) = indices.get(&key) {
+            merged[i] = t;
+            report.overridden.push(key.1);
+        } else if names.contains(&key.1) {
+            report.conflicts.push(key.1);
+        } else {
+            indices.insert(key.clone(), merged.len());
+            names.insert(key.1.clone());
+            merged.push(t);
+            report.added.push(key.1);
+        }
+    }
+
+    (merged, report)
+}
+
+/// Undoes [`merge_asset_lists`], returning the assets whose names aren't in
+/// `secondary_names` and those whose names are, in that order.
+///
+/// Assets without a name always go to the first list. Since overridden
+/// assets replace their `primary` counterparts, those counterparts can't be
+/// recovered.
+#[cfg(feature = "std")]
+pub fn split_asset_list(
+    merged: Vec<XAsset>,
+    secondary_names: &std::collections::HashSet<String>,
+) -> (Vec<XAsset>, Vec<XAsset>) {
+    merged
+        .into_iter()
+        .partition(|a| a.name().is_none_or(|name| !secondary_names.contains(name)))
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum XAssetGeneric<const MAX_LOCAL_CLIENTS: usize = 1> {
     PhysPreset(Option<Box<PhysPreset>>),
     PhysConstraints(Option<Box<PhysConstraints>>),
@@ -132,6 +309,25 @@ pub enum XAssetGeneric<const MAX_LOCAL_CLIENTS: usize = 1> {
     Ddl(Option<Box<DdlRoot>>),
     Glasses(Option<Box<Glasses>>),
     EmblemSet(Option<Box<EmblemSet>>),
+    AssetList(Option<Box<AssetListAsset>>),
+    /// An asset read by an [`AssetHandler`](crate::custom::AssetHandler).
+    ///
+    /// `data` can't be serialized, so exports only keep `type_id` and
+    /// `name`; reading one back gives a `Custom` whose `data` is `()`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_custom_stub",
+            deserialize_with = "deserialize_custom_stub"
+        )
+    )]
+    Custom {
+        /// The asset's `asset_type` in the blob, which may not be a variant
+        /// of [`XAssetType`].
+        type_id: u32,
+        name: String,
+        data: Arc<dyn Any + Send + Sync>,
+    },
 }
 
 impl<const MAX_LOCAL_CLIENTS: usize> XAssetGeneric<MAX_LOCAL_CLIENTS> {
@@ -170,6 +366,8 @@ impl<const MAX_LOCAL_CLIENTS: usize> XAssetGeneric<MAX_LOCAL_CLIENTS> {
             Self::Ddl(p) => p.is_some(),
             Self::Glasses(p) => p.is_some(),
             Self::EmblemSet(p) => p.is_some(),
+            Self::AssetList(p) => p.is_some(),
+            Self::Custom { .. } => true,
         }
     }
 
@@ -212,11 +410,22 @@ impl<const MAX_LOCAL_CLIENTS: usize> XAssetGeneric<MAX_LOCAL_CLIENTS> {
             Self::Ddl(p) => p.as_ref().map(|p| p.name.get()),
             Self::Glasses(p) => p.as_ref().map(|p| p.name.get()),
             Self::EmblemSet(_) => Some("emblemset"),
+            Self::AssetList(p) => p.as_ref().map(|p| p.name.get()),
+            Self::Custom { name, .. } => Some(name),
         }
     }
 
+    /// For a [`Self::Custom`] whose `type_id` isn't a variant of
+    /// [`XAssetType`], this is [`XAssetType::default`]. Use
+    /// [`Self::raw_asset_type`] to tell those apart.
     pub fn asset_type(&self) -> XAssetType {
-        match *self {
+        XAssetType::from_raw(self.raw_asset_type()).unwrap_or_default()
+    }
+
+    /// The asset's type as stored in the blob, which for a [`Self::Custom`]
+    /// is its `type_id`.
+    pub fn raw_asset_type(&self) -> u32 {
+        let asset_type = match *self {
             Self::PhysPreset(_) => XAssetType::PHYSPRESET,
             Self::PhysConstraints(_) => XAssetType::PHYSCONSTRAINTS,
             Self::DestructibleDef(_) => XAssetType::DESTRUCTIBLEDEF,
@@ -250,10 +459,120 @@ impl<const MAX_LOCAL_CLIENTS: usize> XAssetGeneric<MAX_LOCAL_CLIENTS> {
             Self::Ddl(_) => XAssetType::DDL,
             Self::Glasses(_) => XAssetType::GLASSES,
             Self::EmblemSet(_) => XAssetType::EMBLEMSET,
+            Self::AssetList(_) => XAssetType::ASSETLIST,
+            Self::Custom { type_id, .. } => return type_id,
+        };
+        asset_type as _
+    }
+}
+
+mod sealed {
+    pub trait Sealed<const MAX_LOCAL_CLIENTS: usize> {}
+}
+
+/// A type that can be the content of an [`XAssetGeneric`], e.g.
+/// [`Material`] or [`XModel`]. See [`XAssetGeneric::as_typed`].
+///
+/// This trait is sealed, since [`XAssetGeneric`]'s variants are fixed.
+pub trait XAssetContent<const MAX_LOCAL_CLIENTS: usize = 1>:
+    sealed::Sealed<MAX_LOCAL_CLIENTS> + 'static
+{
+    /// Returns the content of `asset` if it's a `Self`.
+    fn from_asset(asset: &XAssetGeneric<MAX_LOCAL_CLIENTS>) -> Option<&Self>;
+
+    /// Returns the content of `asset` if it's a `Self`.
+    fn from_asset_mut(asset: &mut XAssetGeneric<MAX_LOCAL_CLIENTS>) -> Option<&mut Self>;
+}
+
+macro_rules! impl_xasset_content {
+    ($($t:ty => $($variant:ident)|+,)+) => {
+        $(
+            impl<const MAX_LOCAL_CLIENTS: usize> sealed::Sealed<MAX_LOCAL_CLIENTS> for $t {}
+
+            impl<const MAX_LOCAL_CLIENTS: usize> XAssetContent<MAX_LOCAL_CLIENTS> for $t {
+                fn from_asset(asset: &XAssetGeneric<MAX_LOCAL_CLIENTS>) -> Option<&Self> {
+                    match asset {
+                        $(XAssetGeneric::$variant(p))|+ => p.as_deref(),
+                        _ => None,
+                    }
+                }
+
+                fn from_asset_mut(
+                    asset: &mut XAssetGeneric<MAX_LOCAL_CLIENTS>,
+                ) -> Option<&mut Self> {
+                    match asset {
+                        $(XAssetGeneric::$variant(p))|+ => p.as_deref_mut(),
+                        _ => None,
+                    }
+                }
+            }
+        )+
+
+        impl<const MAX_LOCAL_CLIENTS: usize> XAssetGeneric<MAX_LOCAL_CLIENTS> {
+            /// The [`TypeId`] of the asset's content type, whether or not the
+            /// asset is [`Self::is_some`]. Mostly useful for debugging.
+            pub fn content_type_id(&self) -> TypeId {
+                match self {
+                    $($(Self::$variant(_))|+ => TypeId::of::<$t>(),)+
+                    Self::Custom { data, .. } => (**data).type_id(),
+                }
+            }
         }
+    };
+}
+
+impl_xasset_content!(
+    PhysPreset => PhysPreset,
+    PhysConstraints => PhysConstraints,
+    DestructibleDef => DestructibleDef,
+    XAnimParts => XAnimParts,
+    XModel => XModel,
+    Material => Material,
+    MaterialTechniqueSet => TechniqueSet,
+    GfxImage => Image,
+    SndBank => Sound,
+    SndPatch => SoundPatch,
+    ClipMap => ClipMap | ClipMapPVS,
+    ComWorld => ComWorld,
+    GameWorldSp => GameWorldSp,
+    GameWorldMp => GameWorldMp,
+    MapEnts => MapEnts,
+    GfxWorld<MAX_LOCAL_CLIENTS> => GfxWorld,
+    GfxLightDef => LightDef,
+    Font => Font,
+    MenuList<MAX_LOCAL_CLIENTS> => MenuList,
+    MenuDef<MAX_LOCAL_CLIENTS> => Menu,
+    LocalizeEntry => LocalizeEntry,
+    WeaponVariantDef => Weapon,
+    SndDriverGlobals => SndDriverGlobals,
+    FxEffectDef => Fx,
+    FxImpactTable => ImpactFx,
+    RawFile => RawFile,
+    StringTable => StringTable,
+    PackIndex => PackIndex,
+    XGlobals => XGlobals,
+    DdlRoot => Ddl,
+    Glasses => Glasses,
+    EmblemSet => EmblemSet,
+    AssetListAsset => AssetList,
+);
+
+impl<const MAX_LOCAL_CLIENTS: usize> XAssetGeneric<MAX_LOCAL_CLIENTS> {
+    /// Returns the asset's content if it's a `T` and isn't null, e.g.
+    /// `asset.as_typed::<Material>()`. [`ClipMap`] matches both
+    /// [`Self::ClipMap`] and [`Self::ClipMapPVS`].
+    pub fn as_typed<T: XAssetContent<MAX_LOCAL_CLIENTS>>(&self) -> Option<&T> {
+        T::from_asset(self)
+    }
+
+    /// Like [`Self::as_typed`], but mutable.
+    pub fn as_typed_mut<T: XAssetContent<MAX_LOCAL_CLIENTS>>(&mut self) -> Option<&mut T> {
+        T::from_asset_mut(self)
     }
 }
 
+// Public only for the deserializer and serializer in the main crate.
+#[doc(hidden)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub struct XAssetListRaw<'a> {
@@ -268,6 +587,68 @@ pub struct XAssetList {
     pub assets: Vec<XAsset>,
 }
 
+impl XAssetList {
+    /// Sorts `assets` by type, in [`XAssetType`] order (with custom assets
+    /// placed by their raw type), then by name. Unnamed
+    /// assets come before named ones of the same type. The sort is stable,
+    /// so assets with the same type and name keep their order.
+    pub fn sort_by_type_and_name(assets: &mut [XAsset]) {
+        assets.sort_by(|a, b| {
+            a.raw_asset_type()
+                .cmp(&b.raw_asset_type())
+                .then_with(|| a.name().cmp(&b.name()))
+        });
+    }
+
+    /// `assets` grouped by type. Each group keeps the order of `assets`.
+    /// Custom assets whose type isn't an [`XAssetType`] are left out.
+    pub fn grouped_by_type(assets: &[XAsset]) -> BTreeMap<XAssetType, Vec<&XAsset>> {
+        let mut groups = BTreeMap::<_, Vec<_>>::new();
+        for asset in assets {
+            if let Some(asset_type) = XAssetType::from_raw(asset.raw_asset_type()) {
+                groups.entry(asset_type).or_default().push(asset);
+            }
+        }
+        groups
+    }
+
+    /// A hash of the types and names of `assets` that doesn't depend on
+    /// their order, so it only changes if an asset is added, removed,
+    /// renamed, or changes type. Asset contents aren't hashed.
+    ///
+    /// The assets are sorted as by [`Self::sort_by_type_and_name`] and their
+    /// types and names fed through 64-bit FNV-1a in that order. Folding
+    /// each name's hash with XOR instead would let a repeated name cancel
+    /// itself out.
+    pub fn canonical_hash(assets: &[XAsset]) -> u64 {
+        let mut keys = assets
+            .iter()
+            .map(|a| (a.raw_asset_type(), a.name()))
+            .collect::<Vec<_>>();
+        keys.sort();
+
+        keys.into_iter()
+            .fold(FNV_OFFSET_BASIS, |hash, (asset_type, name)| {
+                let hash = fnv1a(hash, &asset_type.to_le_bytes());
+                // Tells an unnamed asset apart from one named `""`.
+                let hash = fnv1a(hash, &[name.is_some() as u8]);
+                let hash = fnv1a(hash, name.unwrap_or_default().as_bytes());
+                fnv1a(hash, &[0])
+            })
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xCBF29CE484222325;
+
+/// Feeds `bytes` through 64-bit FNV-1a, starting from `hash`.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(hash, |h, &b| (h ^ b as u64).wrapping_mul(0x100000001B3))
+}
+
+// Public only for the deserializer and serializer in the main crate.
+#[doc(hidden)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub struct XAssetRaw<'a> {
@@ -276,40 +657,88 @@ pub struct XAssetRaw<'a> {
 }
 assert_size!(XAssetRaw, 8);
 
-impl<'a> XFileSerialize<()> for XAssetList {
-    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let script_strings = ser.script_strings();
+/// One more than the highest [`XAssetType`].
+const ASSET_TYPE_COUNT: usize = XAssetType::ASSETLIST as usize + 1;
 
-        let asset_list = XAssetListRaw {
-            strings: FatPointerCountFirstU32 {
-                size: script_strings.len() as _,
-                p: Ptr32::unreal(),
-            },
-            assets: FatPointerCountFirstU32 {
-                size: ser.asset_count() as _,
-                p: Ptr32::unreal(),
-            },
-        };
+/// The `ASSETLIST` asset found in some dev zone dumps. It lists, by type, the
+/// names of the assets the zone was built from, and doesn't otherwise
+/// affect loading.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub(crate) struct AssetListAssetRaw<'a> {
+    pub name: XStringRaw<'a>,
+    /// Indexed by [`XAssetType`].
+    #[serde(with = "serde_arrays")]
+    pub entries: [FatPointerCountFirstU32<'a, XStringRaw<'a>>; ASSET_TYPE_COUNT],
+}
+assert_size!(AssetListAssetRaw, 364);
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Default)]
+pub struct AssetListAsset {
+    pub name: XString,
+    /// Asset names by type. Types without any assets are left out.
+    pub entries: BTreeMap<XAssetType, Vec<XString>>,
+}
 
-        let mut script_string_bytes = Vec::new();
-        for string in script_strings.iter() {
-            for c in string.chars() {
-                script_string_bytes.push(c as u8);
+impl AssetListAsset {
+    /// The total number of asset names across all types.
+    pub fn len(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> XFileDeserializeInto<AssetListAsset, ()> for AssetListAssetRaw<'a> {
+    fn xfile_deserialize_into(
+        &self,
+        de: &mut impl T5XFileDeserialize,
+        _data: (),
+    ) -> Result<AssetListAsset> {
+        let name = self.name.xfile_deserialize_into(de, ())?;
+
+        let mut entries = BTreeMap::new();
+        for (i, names) in self.entries.iter().enumerate() {
+            let names = names.xfile_deserialize_into(de, ())?;
+            if !names.is_empty() {
+                // `i` is always a valid type since the array is sized by
+                // `ASSET_TYPE_COUNT`
+                entries.insert(XAssetType::from_raw(i as _).unwrap(), names);
             }
-            script_string_bytes.push(b'\0');
         }
 
-        let asset_bytes = ser.asset_bytes().map(|a| a.to_vec()).unwrap_or_default();
+        Ok(AssetListAsset { name, entries })
+    }
+}
+
+impl XFileSerialize<()> for AssetListAsset {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        let name = XStringRaw::from_str(self.name.get());
+        let mut entries = [FatPointerCountFirstU32::default(); ASSET_TYPE_COUNT];
+        for (&asset_type, names) in &self.entries {
+            entries[asset_type as usize] = FatPointerCountFirstU32::from_slice(names);
+        }
+
+        let asset_list = AssetListAssetRaw { name, entries };
 
         ser.store_into_xfile(asset_list)?;
-        ser.store_into_xfile(script_string_bytes)?;
-        ser.store_into_xfile(asset_bytes)
+        self.name.xfile_serialize(ser, ())?;
+        for names in self.entries.values() {
+            for name in names {
+                ser.store_into_xfile(XStringRaw::from_str(name.get()))?;
+            }
+            names.xfile_serialize(ser, ())?;
+        }
+        Ok(())
     }
 }
 
 /// T5 doesn't actually use all of these.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug, FromPrimitive)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, FromPrimitive)]
 #[repr(u32)]
 pub enum XAssetType {
     #[default]
@@ -360,6 +789,177 @@ pub enum XAssetType {
     ASSETLIST = 0x2C,
 }
 
+impl XAssetType {
+    /// Converts an [`XAssetRaw::asset_type`] to an [`XAssetType`], if valid.
+    pub fn from_raw(asset_type: u32) -> Option<Self> {
+        num::FromPrimitive::from_u32(asset_type)
+    }
+
+    /// Whether T5 has assets of this type. Fastfiles with any of the others
+    /// fail to deserialize with [`ErrorKind::UnusedXAssetType`].
+    pub const fn is_used_by_t5(&self) -> bool {
+        !matches!(
+            self,
+            Self::XMODELPIECES
+                | Self::UI_MAP
+                | Self::WEAPONDEF
+                | Self::WEAPON_VARIANT
+                | Self::AITYPE
+                | Self::MPTYPE
+                | Self::MPBODY
+                | Self::MPHEAD
+                | Self::CHARACTER
+                | Self::XMODELALIAS
+                | Self::STRING
+        )
+    }
+
+    /// Every type for which [`Self::is_used_by_t5`] is `true`, in order.
+    pub fn all_used() -> &'static [XAssetType] {
+        &USED_ASSET_TYPES
+    }
+
+    pub const fn category(&self) -> AssetCategory {
+        match self {
+            Self::XMODELPIECES
+            | Self::PHYSPRESET
+            | Self::PHYSCONSTRAINTS
+            | Self::DESTRUCTIBLEDEF
+            | Self::XMODEL
+            | Self::XMODELALIAS => AssetCategory::Geometry,
+            Self::MATERIAL | Self::TECHNIQUE_SET | Self::IMAGE | Self::LIGHT_DEF => {
+                AssetCategory::Material
+            }
+            Self::XANIMPARTS => AssetCategory::Animation,
+            Self::SOUND | Self::SOUND_PATCH | Self::SNDDRIVER_GLOBALS => AssetCategory::Audio,
+            Self::CLIPMAP
+            | Self::CLIPMAP_PVS
+            | Self::COMWORLD
+            | Self::GAMEWORLD_SP
+            | Self::GAMEWORLD_MP
+            | Self::MAP_ENTS
+            | Self::GFXWORLD
+            | Self::GLASSES => AssetCategory::World,
+            Self::UI_MAP
+            | Self::FONT
+            | Self::MENULIST
+            | Self::MENU
+            | Self::LOCALIZE_ENTRY
+            | Self::EMBLEMSET => AssetCategory::UI,
+            Self::WEAPON | Self::WEAPONDEF | Self::WEAPON_VARIANT => AssetCategory::Weapon,
+            Self::FX
+            | Self::IMPACT_FX
+            | Self::AITYPE
+            | Self::MPTYPE
+            | Self::MPBODY
+            | Self::MPHEAD
+            | Self::CHARACTER
+            | Self::RAWFILE
+            | Self::STRINGTABLE
+            | Self::PACKINDEX
+            | Self::XGLOBALS
+            | Self::DDL
+            | Self::STRING
+            | Self::ASSETLIST => AssetCategory::Misc,
+        }
+    }
+
+    /// The size of the raw struct that assets of this type start with, or
+    /// [`None`] if T5 doesn't use the type.
+    ///
+    /// These are the sizes on PC. Consoles have 4 local clients rather than
+    /// 1, which makes `GFXWORLD`, `MENULIST` and `MENU` larger there.
+    pub const fn expected_raw_struct_size(&self) -> Option<usize> {
+        Some(match self {
+            Self::PHYSPRESET => size_of::<PhysPresetRaw>(),
+            Self::PHYSCONSTRAINTS => size_of::<PhysConstraintsRaw>(),
+            Self::DESTRUCTIBLEDEF => size_of::<DestructibleDefRaw>(),
+            Self::XANIMPARTS => size_of::<XAnimPartsRaw>(),
+            Self::XMODEL => size_of::<XModelRaw>(),
+            Self::MATERIAL => size_of::<MaterialRaw>(),
+            Self::TECHNIQUE_SET => size_of::<MaterialTechniqueSetRaw>(),
+            Self::IMAGE => size_of::<GfxImageRaw>(),
+            Self::SOUND => size_of::<SndBankRaw>(),
+            Self::SOUND_PATCH => size_of::<SndPatchRaw>(),
+            Self::CLIPMAP | Self::CLIPMAP_PVS => size_of::<ClipMapRaw>(),
+            Self::COMWORLD => size_of::<ComWorldRaw>(),
+            Self::GAMEWORLD_SP => size_of::<GameWorldSpRaw>(),
+            Self::GAMEWORLD_MP => size_of::<GameWorldMpRaw>(),
+            Self::MAP_ENTS => size_of::<MapEntsRaw>(),
+            Self::GFXWORLD => size_of::<GfxWorldRaw<1>>(),
+            Self::LIGHT_DEF => size_of::<GfxLightDefRaw>(),
+            Self::FONT => size_of::<FontRaw>(),
+            Self::MENULIST => size_of::<MenuListRaw<1>>(),
+            Self::MENU => size_of::<MenuDefRaw<1>>(),
+            Self::LOCALIZE_ENTRY => size_of::<LocalizeEntryRaw>(),
+            Self::WEAPON => size_of::<WeaponVariantDefRaw>(),
+            Self::SNDDRIVER_GLOBALS => size_of::<SndDriverGlobalsRaw>(),
+            Self::FX => size_of::<FxEffectDefRaw>(),
+            Self::IMPACT_FX => size_of::<FxImpactTableRaw>(),
+            Self::RAWFILE => size_of::<RawFileRaw>(),
+            Self::STRINGTABLE => size_of::<StringTableRaw>(),
+            Self::PACKINDEX => size_of::<PackIndexRaw>(),
+            Self::XGLOBALS => size_of::<XGlobalsRaw>(),
+            Self::DDL => size_of::<DdlRootRaw>(),
+            Self::GLASSES => size_of::<GlassesRaw>(),
+            Self::EMBLEMSET => size_of::<EmblemSetRaw>(),
+            Self::ASSETLIST => size_of::<AssetListAssetRaw>(),
+            _ => return None,
+        })
+    }
+}
+
+static USED_ASSET_TYPES: [XAssetType; 34] = [
+    XAssetType::PHYSPRESET,
+    XAssetType::PHYSCONSTRAINTS,
+    XAssetType::DESTRUCTIBLEDEF,
+    XAssetType::XANIMPARTS,
+    XAssetType::XMODEL,
+    XAssetType::MATERIAL,
+    XAssetType::TECHNIQUE_SET,
+    XAssetType::IMAGE,
+    XAssetType::SOUND,
+    XAssetType::SOUND_PATCH,
+    XAssetType::CLIPMAP,
+    XAssetType::CLIPMAP_PVS,
+    XAssetType::COMWORLD,
+    XAssetType::GAMEWORLD_SP,
+    XAssetType::GAMEWORLD_MP,
+    XAssetType::MAP_ENTS,
+    XAssetType::GFXWORLD,
+    XAssetType::LIGHT_DEF,
+    XAssetType::FONT,
+    XAssetType::MENULIST,
+    XAssetType::MENU,
+    XAssetType::LOCALIZE_ENTRY,
+    XAssetType::WEAPON,
+    XAssetType::SNDDRIVER_GLOBALS,
+    XAssetType::FX,
+    XAssetType::IMPACT_FX,
+    XAssetType::RAWFILE,
+    XAssetType::STRINGTABLE,
+    XAssetType::PACKINDEX,
+    XAssetType::XGLOBALS,
+    XAssetType::DDL,
+    XAssetType::GLASSES,
+    XAssetType::EMBLEMSET,
+    XAssetType::ASSETLIST,
+];
+
+/// A coarse grouping of [`XAssetType`]s, for display.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AssetCategory {
+    Geometry,
+    Material,
+    Animation,
+    Audio,
+    World,
+    UI,
+    Weapon,
+    Misc,
+}
+
 impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<XAssetGeneric<MAX_LOCAL_CLIENTS>, ()>
     for XAssetRaw<'a>
 {
@@ -542,6 +1142,11 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<XAssetGeneric<MAX_
                     .cast::<EmblemSetRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            XAssetType::ASSETLIST => XAssetGeneric::AssetList(
+                self.asset_data
+                    .cast::<AssetListAssetRaw>()
+                    .xfile_deserialize_into(de, ())?,
+            ),
             _ => {
                 //dbg!(asset_type);
                 return Err(Error::new_with_offset(
@@ -556,15 +1161,29 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<XAssetGeneric<MAX_
 
 impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MAX_LOCAL_CLIENTS> {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let asset_type = self.asset_type() as _;
-        let asset_data = Ptr32::unreal();
+        ser.store_into_xfile(self.xasset_raw())?;
+        self.serialize_data(ser)
+    }
+}
 
-        let asset = XAssetRaw {
-            asset_type,
-            asset_data,
-        };
+impl<const MAX_LOCAL_CLIENTS: usize> XAssetGeneric<MAX_LOCAL_CLIENTS> {
+    /// This asset's entry in the blob's asset table.
+    #[doc(hidden)]
+    pub fn xasset_raw(&self) -> XAssetRaw<'static> {
+        XAssetRaw {
+            asset_type: self.raw_asset_type(),
+            asset_data: if self.is_some() {
+                Ptr32::unreal()
+            } else {
+                Ptr32::null()
+            },
+        }
+    }
 
-        ser.store_into_xfile(asset)?;
+    /// Serializes the asset's data without its [`XAssetRaw`] entry. A blob
+    /// stores every asset's entry first, followed by each asset's data in
+    /// the same order.
+    pub fn serialize_data(&self, ser: &mut impl T5XFileSerialize) -> Result<()> {
         match self {
             Self::PhysPreset(p) => {
                 if let Some(p) = p {
@@ -601,7 +1220,13 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
-            // Self::Material(p) => if let Some(p) = p { p.xfile_serialize(ser, ()) } else { Ok(()) },
+            Self::Material(p) => {
+                if let Some(p) = p {
+                    p.xfile_serialize(ser, ())
+                } else {
+                    Ok(())
+                }
+            }
             // Self::TechniqueSet(p) => if let Some(p) = p { p.xfile_serialize(ser, ()) } else { Ok(()) },
             Self::Image(p) => {
                 if let Some(p) = p {
@@ -669,8 +1294,20 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
-            // Self::MenuList(p) => if let Some(p) = p { p.xfile_serialize(ser, ()) } else { Ok(()) },
-            // Self::Menu(p) => if let Some(p) = p { p.xfile_serialize(ser, ()) } else { Ok(()) },
+            Self::MenuList(p) => {
+                if let Some(p) = p {
+                    p.xfile_serialize(ser, ())
+                } else {
+                    Ok(())
+                }
+            }
+            Self::Menu(p) => {
+                if let Some(p) = p {
+                    p.xfile_serialize(ser, ())
+                } else {
+                    Ok(())
+                }
+            }
             Self::LocalizeEntry(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -678,7 +1315,13 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
-            // Self::Weapon(p) => if let Some(p) = p { p.xfile_serialize(ser, ()) } else { Ok(()) },
+            Self::Weapon(p) => {
+                if let Some(p) = p {
+                    p.xfile_serialize(ser, ())
+                } else {
+                    Ok(())
+                }
+            }
             Self::SndDriverGlobals(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -749,7 +1392,38 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            Self::AssetList(p) => {
+                if let Some(p) = p {
+                    p.xfile_serialize(ser, ())
+                } else {
+                    Ok(())
+                }
+            }
+            Self::Custom { type_id, .. } => Err(Error::new(
+                file_line_col!(),
+                ErrorKind::Todo(format!(
+                    "serializing custom assets (asset type {type_id:#X})"
+                )),
+            )),
             _ => todo!(),
         }
     }
 }
+
+#[cfg(feature = "serde")]
+fn serialize_custom_stub<S: serde::Serializer>(
+    type_id: &u32,
+    name: &String,
+    _data: &Arc<dyn Any + Send + Sync>,
+    serializer: S,
+) -> core::result::Result<S::Ok, S::Error> {
+    (type_id, name).serialize(serializer)
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_custom_stub<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> core::result::Result<(u32, String, Arc<dyn Any + Send + Sync>), D::Error> {
+    let (type_id, name) = <(u32, String)>::deserialize(deserializer)?;
+    Ok((type_id, name, Arc::new(())))
+}
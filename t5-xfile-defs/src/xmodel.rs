@@ -1,4 +1,4 @@
-use alloc::{boxed::Box, format, string::String, vec::Vec};
+use alloc::{boxed::Box, collections::BTreeMap, format, string::String, vec::Vec};
 use bitflags::bitflags;
 use num::FromPrimitive;
 use num_derive::FromPrimitive;
@@ -8,14 +8,17 @@ use serde::{Deserialize, Serialize};
 use crate::prelude::*;
 
 use crate::{
-    Error, ErrorKind, FatPointer, FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result,
-    ScriptString, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize,
-    XString, XStringRaw, assert_size,
-    common::{GfxIndexBuffer, GfxVertexBuffer, Mat3, Vec3, Vec4},
-    file_line_col,
+    Error, ErrorKind, FatPointer, Result, ScriptString, T5XFileDeserialize, T5XFileSerialize,
+    XFileDeserializeInto, XFileSerialize, XString, assert_size,
+    common::{Bounds, GfxIndexBuffer, GfxVertexBuffer, Mat3, Vec3, Vec4},
+    file_line_col, packing,
     techset::{Material, MaterialRaw},
+    util::{FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, XStringRaw},
 };
 
+#[cfg(feature = "std")]
+pub mod xom;
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct XModelRaw<'a> {
@@ -68,6 +71,15 @@ pub enum XModelLodRampType {
 
 pub const MAX_LODS: usize = 4;
 
+/// The vertical field of view [`XModelLodInfo::dist`] is authored for, in
+/// degrees. See [`XModel::select_lod`].
+pub const LOD_REFERENCE_FOV: f32 = 65.0;
+
+/// The screen height [`XModelLodInfo::dist`] is authored for, in pixels:
+/// the height of the engine's virtual 640x480 screen. See
+/// [`XModel::select_lod`].
+pub const LOD_REFERENCE_SCREEN_HEIGHT: u32 = 480;
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Default, Debug)]
 pub struct XModel {
@@ -205,7 +217,7 @@ impl<'a> XFileDeserializeInto<XModel, ()> for XModelRaw<'a> {
             self.lod_info[2].try_into()?,
             self.lod_info[3].try_into()?,
         ];
-        let coll_surfs = self.coll_surfs.xfile_deserialize_into(de, ())?;
+        let coll_surfs = self.coll_surfs.xfile_deserialize_into(de, self.num_bones)?;
         //dbg!(&coll_surfs);
         //dbg!(de.stream_pos()?);
         let bone_info = self
@@ -285,12 +297,264 @@ impl<'a> XFileDeserializeInto<XModel, ()> for XModelRaw<'a> {
     }
 }
 
+impl XModel {
+    /// The [`HitLocation`] assigned to bone `bone_idx`.
+    pub fn hit_location(&self, bone_idx: usize) -> HitLocation {
+        self.part_classification
+            .get(bone_idx)
+            .and_then(|&c| HitLocation::from_u8(c))
+            .unwrap_or_default()
+    }
+
+    /// The parent of bone `bone_idx`, or [`None`] if it's a root bone (or
+    /// doesn't exist).
+    pub fn bone_parent(&self, bone_idx: usize) -> Option<usize> {
+        if bone_idx < self.num_root_bones {
+            return None;
+        }
+
+        let offset = *self.parent_list.get(bone_idx - self.num_root_bones)? as usize;
+        bone_idx.checked_sub(offset)
+    }
+
+    /// The number of LODs the model has, i.e. how many of
+    /// [`Self::lod_info`] are used.
+    pub fn lod_count(&self) -> usize {
+        (self.num_lods.max(0) as usize).min(self.lod_info.len())
+    }
+
+    /// Each LOD's [`XModelLodInfo::dist`], in LOD order. See
+    /// [`Self::select_lod`] for what they mean.
+    pub fn lod_dist_thresholds(&self) -> Vec<f32> {
+        self.lod_info[..self.lod_count()]
+            .iter()
+            .map(|l| l.dist)
+            .collect()
+    }
+
+    /// The LOD the model is drawn with when it's `dist` units from the
+    /// camera, on a screen `screen_height_pixels` tall with a vertical field
+    /// of view of `fov_degrees`.
+    ///
+    /// Each LOD's [`XModelLodInfo::dist`] is the distance it's used up to,
+    /// in a reference view [`LOD_REFERENCE_SCREEN_HEIGHT`] pixels tall with
+    /// a [`LOD_REFERENCE_FOV`] degree field of view. A distance stands for
+    /// the angular size the model's bounding sphere has at it: a sphere of
+    /// radius `r` at distance `d` is `r / (d * tan(fov / 2))` screen
+    /// heights across. So `dist` is converted to the distance at which the
+    /// model would be the same number of pixels across in the reference
+    /// view, which is what's compared with the thresholds. The radius
+    /// cancels out, so it doesn't affect the result.
+    ///
+    /// The first LOD whose threshold is past that distance (or is `0.0`,
+    /// which means no limit) is returned. The engine stops drawing a model
+    /// past its last threshold; in that case the last LOD is returned.
+    /// The engine itself only corrects for the field of view, so pass
+    /// [`LOD_REFERENCE_SCREEN_HEIGHT`] to match it exactly.
+    pub fn select_lod(&self, dist: f32, screen_height_pixels: u32, fov_degrees: f32) -> usize {
+        let half_fov_tan = |fov: f32| (fov.to_radians() / 2.0).tan();
+        let scale = (half_fov_tan(fov_degrees) / half_fov_tan(LOD_REFERENCE_FOV))
+            * (LOD_REFERENCE_SCREEN_HEIGHT as f32 / screen_height_pixels.max(1) as f32);
+        let reference_dist = dist * scale;
+
+        let thresholds = self.lod_dist_thresholds();
+        thresholds
+            .iter()
+            .position(|&t| t == 0.0 || reference_dist < t)
+            .unwrap_or(thresholds.len().saturating_sub(1))
+    }
+
+    /// The surfaces making up LOD `lod`, with their indices into
+    /// [`Self::surfs`]. The same index finds a surface's material in
+    /// [`Self::material_handles`].
+    pub fn lod_surfaces(&self, lod: usize) -> impl Iterator<Item = (usize, &XSurface)> {
+        let (start, count) = self
+            .lod_info
+            .get(lod)
+            .filter(|_| lod < self.num_lods.max(0) as usize)
+            .map_or((0, 0), |l| (l.surf_index, l.numsurfs));
+
+        self.surfs.iter().enumerate().skip(start).take(count)
+    }
+
+    /// Returns one [`Hitbox`] per collision surface, labeled with the bone
+    /// it's attached to and that bone's [`HitLocation`].
+    pub fn hitboxes(&self) -> Vec<Hitbox> {
+        self.coll_surfs
+            .iter()
+            .map(|s| Hitbox {
+                bone: self.bone_names.get(s.bone_idx).cloned().unwrap_or_default(),
+                bounds: s.bounds(),
+                loc: self.hit_location(s.bone_idx),
+            })
+            .collect()
+    }
+
+    /// The size in bytes of one vertex in a buffer built with `format`.
+    pub const fn vertex_stride(format: VertexBufferFormat) -> usize {
+        match format {
+            VertexBufferFormat::PositionNormalUV => 32,
+            VertexBufferFormat::PositionNormalUVColor => 36,
+            VertexBufferFormat::PositionNormalUVSkinned => 48,
+        }
+    }
+
+    /// Packs every vertex of LOD `lod` into one interleaved, little-endian
+    /// buffer laid out as described by `format`. Surfaces follow each other
+    /// in the same order as [`Self::lod_surfaces`].
+    pub fn build_vertex_buffer(&self, lod: usize, format: VertexBufferFormat) -> Result<Vec<u8>> {
+        let surfaces = self.checked_lod_surfaces(lod)?;
+        let vertex_count = surfaces.iter().map(|s| s.verts0.len()).sum::<usize>();
+
+        let mut buf = Vec::with_capacity(vertex_count * Self::vertex_stride(format));
+        for surf in surfaces {
+            let weights = if format == VertexBufferFormat::PositionNormalUVSkinned {
+                surf.bone_weights()?
+            } else {
+                Vec::new()
+            };
+
+            for (i, v) in surf.verts0.iter().enumerate() {
+                v.extend_position_normal_uv(&mut buf);
+
+                match format {
+                    VertexBufferFormat::PositionNormalUV => {}
+                    VertexBufferFormat::PositionNormalUVColor => buf.extend(v.color.0),
+                    VertexBufferFormat::PositionNormalUVSkinned => {
+                        for &(bone, _) in &weights[i] {
+                            let bone = u16::try_from(bone).map_err(|_| {
+                                Error::new(
+                                    file_line_col!(),
+                                    ErrorKind::BrokenInvariant(format!(
+                                        "XModel: bone index ({bone}) > u16::MAX"
+                                    )),
+                                )
+                            })?;
+                            buf.extend(bone.to_le_bytes());
+                        }
+                        for &(_, weight) in &weights[i] {
+                            let weight = (weight.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16;
+                            buf.extend(weight.to_le_bytes());
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(buf)
+    }
+
+    /// The triangles of LOD `lod` as a list of indices into the buffer
+    /// [`Self::build_vertex_buffer`] returns for the same LOD.
+    pub fn build_index_buffer(&self, lod: usize) -> Result<Vec<u16>> {
+        let surfaces = self.checked_lod_surfaces(lod)?;
+
+        let mut indices = Vec::with_capacity(surfaces.iter().map(|s| s.tri_indices.len()).sum());
+        let mut base = 0;
+        for surf in surfaces {
+            for &i in surf.tri_indices.iter().take(surf.tri_indices.len() / 3 * 3) {
+                if i as usize >= surf.verts0.len() {
+                    return Err(Error::new(
+                        file_line_col!(),
+                        ErrorKind::BrokenInvariant(format!(
+                            "XModel: index ({i}) >= vertex count ({})",
+                            surf.verts0.len()
+                        )),
+                    ));
+                }
+
+                let i = u16::try_from(base + i as usize).map_err(|_| {
+                    Error::new(
+                        file_line_col!(),
+                        ErrorKind::BrokenInvariant(format!(
+                            "XModel: LOD {lod} has more than {} vertices",
+                            u16::MAX as usize + 1
+                        )),
+                    )
+                })?;
+                indices.push(i);
+            }
+            base += surf.verts0.len();
+        }
+
+        Ok(indices)
+    }
+
+    /// [`Self::lod_surfaces`], but an error if the model has no LOD `lod`.
+    fn checked_lod_surfaces(&self, lod: usize) -> Result<Vec<&XSurface>> {
+        if lod >= self.num_lods.max(0) as usize || lod >= self.lod_info.len() {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "XModel: LOD ({lod}) >= LOD count ({})",
+                    self.num_lods
+                )),
+            ));
+        }
+
+        Ok(self.lod_surfaces(lod).map(|(_, s)| s).collect())
+    }
+}
+
+/// The vertex layouts [`XModel::build_vertex_buffer`] can produce. Every
+/// layout starts with the position (`[f32; 3]`), normal (`[f32; 3]`) and UV
+/// (`[f32; 2]`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VertexBufferFormat {
+    /// Nothing past the UV: 32 bytes per vertex.
+    PositionNormalUV,
+    /// Followed by the vertex color (`[u8; 4]`, as stored): 36 bytes per
+    /// vertex.
+    PositionNormalUVColor,
+    /// Followed by four bone indices (`[u16; 4]`) and their weights
+    /// (`[u16; 4]`, normalized so that `u16::MAX` is `1.0`): 48 bytes per
+    /// vertex.
+    PositionNormalUVSkinned,
+}
+
 impl XFileSerialize<()> for XModel {
     fn xfile_serialize(&self, _ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         todo!()
     }
 }
 
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Default, Debug)]
+pub struct Hitbox {
+    pub bone: String,
+    pub bounds: Bounds,
+    pub loc: HitLocation,
+}
+
+/// Indexed by [`XModel::part_classification`]. Determines the damage
+/// multiplier applied when a bone is hit.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq, FromPrimitive)]
+#[repr(u8)]
+pub enum HitLocation {
+    #[default]
+    NONE = 0x00,
+    HELMET = 0x01,
+    HEAD = 0x02,
+    NECK = 0x03,
+    TORSO_UPR = 0x04,
+    TORSO_LWR = 0x05,
+    R_ARM_UPR = 0x06,
+    L_ARM_UPR = 0x07,
+    R_ARM_LWR = 0x08,
+    L_ARM_LWR = 0x09,
+    R_HAND = 0x0A,
+    L_HAND = 0x0B,
+    R_LEG_UPR = 0x0C,
+    L_LEG_UPR = 0x0D,
+    R_LEG_LWR = 0x0E,
+    L_LEG_LWR = 0x0F,
+    R_FOOT = 0x10,
+    L_FOOT = 0x11,
+    GUN = 0x12,
+    NUM = 0x13,
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct DObjAnimMatRaw {
@@ -407,6 +671,235 @@ impl<'a> XFileDeserializeInto<XSurface, ()> for XSurfaceRaw<'a> {
     }
 }
 
+/// The size of a `DObjSkelMat`. Bone offsets in [`XSurfaceVertexInfo`] and
+/// [`XRigidVertList`] are in these units.
+const SKEL_MAT_SIZE: usize = 64;
+
+impl XSurface {
+    /// Up to four `(bone index, weight)` pairs for each vertex in
+    /// [`Self::verts0`]. Unused slots have a weight of `0.0`.
+    ///
+    /// Rigid surfaces take their bones from [`Self::vert_list`], and skinned
+    /// ones from [`XSurfaceVertexInfo::verts_blend`]. Vertices not covered
+    /// by either are attached to bone 0.
+    pub fn bone_weights(&self) -> Result<Vec<[(usize, f32); 4]>> {
+        let mut weights = Vec::with_capacity(self.verts0.len());
+
+        if !self.vert_list.is_empty() {
+            for list in &self.vert_list {
+                let bone = list.bone_offset / SKEL_MAT_SIZE;
+                weights.extend(core::iter::repeat_n(
+                    [(bone, 1.0), (0, 0.0), (0, 0.0), (0, 0.0)],
+                    list.vert_count,
+                ));
+            }
+        } else {
+            let mut blend = self.vert_info.verts_blend.iter().copied();
+            for (influences, &count) in self.vert_info.vert_count.iter().enumerate() {
+                for _ in 0..count.max(0) {
+                    let mut w = [(0, 0.0); 4];
+                    let mut next = || {
+                        blend.next().ok_or_else(|| {
+                            Error::new(
+                                file_line_col!(),
+                                ErrorKind::BrokenInvariant(
+                                    "XSurface: verts_blend is too short".into(),
+                                ),
+                            )
+                        })
+                    };
+
+                    w[0].0 = next()? as usize / SKEL_MAT_SIZE;
+                    let mut rest = 0.0;
+                    for slot in w.iter_mut().skip(1).take(influences) {
+                        slot.0 = next()? as usize / SKEL_MAT_SIZE;
+                        slot.1 = packing::half_to_f32(next()?);
+                        rest += slot.1;
+                    }
+                    w[0].1 = 1.0 - rest;
+                    weights.push(w);
+                }
+            }
+        }
+
+        if weights.len() > self.verts0.len() {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "XSurface: bone weights for {} vertices, but only {} vertices",
+                    weights.len(),
+                    self.verts0.len()
+                )),
+            ));
+        }
+
+        weights.resize(self.verts0.len(), [(0, 1.0), (0, 0.0), (0, 0.0), (0, 0.0)]);
+        Ok(weights)
+    }
+
+    /// [`Self::verts0`], decoded.
+    pub fn decoded_vertices(&self) -> Vec<DecodedVertex> {
+        self.verts0.iter().map(GfxPackedVertex::decode).collect()
+    }
+
+    /// Repacks each of [`Self::verts0`] from `verts` with
+    /// [`GfxPackedVertex::repack`]. `verts` has to have one vertex for each
+    /// already in the surface.
+    pub fn set_decoded_vertices(&mut self, verts: &[DecodedVertex]) -> Result<()> {
+        if verts.len() != self.verts0.len() {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "XSurface: {} decoded vertices for {} vertices",
+                    verts.len(),
+                    self.verts0.len()
+                )),
+            ));
+        }
+
+        for (packed, v) in self.verts0.iter_mut().zip(verts) {
+            packed.repack(v);
+        }
+        Ok(())
+    }
+
+    /// The triangles with two or more corners at the same position, as
+    /// indices of triangles (not of [`Self::tri_indices`] entries).
+    pub fn find_degenerate_triangles(&self) -> Vec<usize> {
+        let welded = self.welded_indices();
+        self.triangles()
+            .enumerate()
+            .filter(|&(_, tri)| is_degenerate(weld(&welded, tri)))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Edges shared by more than two triangles.
+    ///
+    /// Edges are between positions rather than vertices, so vertices split
+    /// along UV seams or hard edges count as one. Each position is
+    /// represented by the lowest index of a vertex there, and each edge
+    /// has the lower index first. Degenerate triangles are left out.
+    pub fn find_non_manifold_edges(&self) -> Vec<[usize; 2]> {
+        self.edge_counts()
+            .into_iter()
+            .filter(|&(_, n)| n > 2)
+            .map(|(e, _)| e)
+            .collect()
+    }
+
+    /// Edges belonging to only one triangle, i.e. the borders of holes.
+    /// See [`Self::find_non_manifold_edges`] for how edges are represented.
+    pub fn find_open_edges(&self) -> Vec<[usize; 2]> {
+        self.edge_counts()
+            .into_iter()
+            .filter(|&(_, n)| n == 1)
+            .map(|(e, _)| e)
+            .collect()
+    }
+
+    /// Whether no edge is shared by more than two triangles.
+    pub fn is_manifold(&self) -> bool {
+        self.edge_counts().values().all(|&n| n <= 2)
+    }
+
+    /// Whether every edge is shared by exactly two triangles, i.e. the
+    /// surface is manifold and has no holes.
+    pub fn is_closed(&self) -> bool {
+        self.edge_counts().values().all(|&n| n == 2)
+    }
+
+    /// All of the above at once.
+    pub fn topology_report(&self) -> TopologyReport {
+        let edges = self.edge_counts();
+        let edges_where = |f: fn(usize) -> bool| {
+            edges
+                .iter()
+                .filter(|&(_, &n)| f(n))
+                .map(|(&e, _)| e)
+                .collect()
+        };
+
+        TopologyReport {
+            degenerate_triangles: self.find_degenerate_triangles(),
+            non_manifold_edges: edges_where(|n| n > 2),
+            open_edges: edges_where(|n| n == 1),
+        }
+    }
+
+    /// Whole triangles of [`Self::tri_indices`]. Leftover indices are
+    /// ignored.
+    fn triangles(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        self.tri_indices
+            .chunks_exact(3)
+            .map(|t| [t[0] as usize, t[1] as usize, t[2] as usize])
+    }
+
+    /// For each vertex, the index of the first vertex at the same position.
+    fn welded_indices(&self) -> Vec<usize> {
+        let mut first = BTreeMap::new();
+        self.verts0
+            .iter()
+            .enumerate()
+            // `+ 0.0` turns -0.0 into 0.0, so the two compare equal.
+            .map(|(i, v)| {
+                *first
+                    .entry(v.xyz.get().map(|c| (c + 0.0).to_bits()))
+                    .or_insert(i)
+            })
+            .collect()
+    }
+
+    /// How many non-degenerate triangles share each edge.
+    fn edge_counts(&self) -> BTreeMap<[usize; 2], usize> {
+        let welded = self.welded_indices();
+        let mut counts = BTreeMap::new();
+        for tri in self.triangles().map(|tri| weld(&welded, tri)) {
+            if is_degenerate(tri) {
+                continue;
+            }
+
+            let [a, b, c] = tri;
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                *counts.entry([u.min(v), u.max(v)]).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+}
+
+/// Maps `tri`'s corners through `welded`. Out-of-range indices are left
+/// alone.
+fn weld(welded: &[usize], tri: [usize; 3]) -> [usize; 3] {
+    tri.map(|i| welded.get(i).copied().unwrap_or(i))
+}
+
+fn is_degenerate([a, b, c]: [usize; 3]) -> bool {
+    a == b || b == c || a == c
+}
+
+/// Returned by [`XSurface::topology_report`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct TopologyReport {
+    /// See [`XSurface::find_degenerate_triangles`].
+    pub degenerate_triangles: Vec<usize>,
+    /// See [`XSurface::find_non_manifold_edges`].
+    pub non_manifold_edges: Vec<[usize; 2]>,
+    /// See [`XSurface::find_open_edges`].
+    pub open_edges: Vec<[usize; 2]>,
+}
+
+impl TopologyReport {
+    pub fn is_manifold(&self) -> bool {
+        self.non_manifold_edges.is_empty()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.non_manifold_edges.is_empty() && self.open_edges.is_empty()
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct XSurfaceVertexInfoRaw<'a> {
@@ -471,6 +964,68 @@ pub struct GfxPackedVertex {
     pub tangent: UnitVec,
 }
 
+/// A [`GfxPackedVertex`] with everything unpacked.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug)]
+pub struct DecodedVertex {
+    pub xyz: Vec3,
+    pub binormal_sign: f32,
+    pub color: [f32; 4],
+    pub tex_coord: [f32; 2],
+    pub normal: Vec3,
+    pub tangent: Vec3,
+}
+
+impl GfxPackedVertex {
+    pub fn decode(&self) -> DecodedVertex {
+        DecodedVertex {
+            xyz: self.xyz,
+            binormal_sign: self.binormal_sign,
+            color: self.color.get(),
+            tex_coord: self.tex_coord.get(),
+            normal: self.normal.get(),
+            tangent: self.tangent.get(),
+        }
+    }
+
+    /// Appends the position, normal, and UV as eight little-endian [`f32`]s,
+    /// which is how every vertex starts in both
+    /// [`XModel::build_vertex_buffer`]'s buffers and `.xom` files.
+    pub(crate) fn extend_position_normal_uv(&self, buf: &mut Vec<u8>) {
+        let floats = self
+            .xyz
+            .get()
+            .into_iter()
+            .chain(self.normal.get().get())
+            .chain(self.tex_coord.get());
+        for f in floats {
+            buf.extend(f.to_le_bytes());
+        }
+    }
+
+    /// Packs `v` into `self`. Fields that `v` has the same value for as
+    /// `self` already does are left alone, so decoding a vertex and
+    /// repacking it unmodified gives back exactly the same bits.
+    pub fn repack(&mut self, v: &DecodedVertex) {
+        let old = self.decode();
+
+        self.xyz = v.xyz;
+        self.binormal_sign = v.binormal_sign;
+        if old.color != v.color {
+            self.color = GfxColor::pack(v.color);
+        }
+        if old.tex_coord != v.tex_coord {
+            self.tex_coord = TexCoords::pack(v.tex_coord);
+        }
+        if old.normal.get() != v.normal.get() {
+            self.normal = UnitVec::pack(v.normal);
+        }
+        if old.tangent.get() != v.tangent.get() {
+            self.tangent = UnitVec::pack(v.tangent);
+        }
+    }
+}
+
 impl From<GfxPackedVertexRaw> for GfxPackedVertex {
     fn from(value: GfxPackedVertexRaw) -> Self {
         Self {
@@ -489,16 +1044,57 @@ impl From<GfxPackedVertexRaw> for GfxPackedVertex {
 pub struct GfxColor(pub [u8; 4]);
 assert_size!(GfxColor, 4);
 
+impl GfxColor {
+    /// The channels in the order they're stored, from `0.0` to `1.0`.
+    pub fn get(self) -> [f32; 4] {
+        packing::unpack_color(self.0)
+    }
+
+    /// The inverse of [`Self::get`].
+    pub fn pack(color: [f32; 4]) -> Self {
+        Self(packing::pack_color(color))
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub struct TexCoords(pub u32);
 assert_size!(TexCoords, 4);
 
+impl TexCoords {
+    /// Unpacks the two 16-bit floats. U is in the high half, V in the low.
+    /// See [`packing::unpack_tex_coord`].
+    pub fn get(self) -> [f32; 2] {
+        [
+            packing::unpack_tex_coord((self.0 >> 16) as u16),
+            packing::unpack_tex_coord(self.0 as u16),
+        ]
+    }
+
+    /// The inverse of [`Self::get`].
+    pub fn pack([u, v]: [f32; 2]) -> Self {
+        Self((packing::pack_tex_coord(u) as u32) << 16 | packing::pack_tex_coord(v) as u32)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub struct UnitVec(pub [u8; 4]);
 assert_size!(UnitVec, 4);
 
+impl UnitVec {
+    /// Unpacks the vector. The fourth byte is a shared scale for the other
+    /// three. See [`packing::unpack_unit_vec`].
+    pub fn get(self) -> Vec3 {
+        packing::unpack_unit_vec(self.0).into()
+    }
+
+    /// The inverse of [`Self::get`]. See [`packing::pack_unit_vec`].
+    pub fn pack(v: Vec3) -> Self {
+        Self(packing::pack_unit_vec(v.get()))
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct XRigidVertListRaw<'a> {
@@ -714,12 +1310,34 @@ pub struct XModelCollSurf {
     pub surf_flags: i32,
 }
 
-impl<'a> XFileDeserializeInto<XModelCollSurf, ()> for XModelCollSurfRaw<'a> {
+impl<'a> XFileDeserializeInto<XModelCollSurf, u8> for XModelCollSurfRaw<'a> {
     fn xfile_deserialize_into(
         &self,
         de: &mut impl T5XFileDeserialize,
-        _data: (),
+        num_bones: u8,
     ) -> Result<XModelCollSurf> {
+        if self.bone_idx < 0 {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                de.stream_pos()? as _,
+                ErrorKind::BrokenInvariant(format!(
+                    "XModelCollSurf: bone_idx ({}) < 0",
+                    self.bone_idx
+                )),
+            ));
+        }
+
+        if self.bone_idx >= num_bones as i32 {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                de.stream_pos()? as _,
+                ErrorKind::BrokenInvariant(format!(
+                    "XModelCollSurf: bone_idx ({}) >= num_bones ({})",
+                    self.bone_idx, num_bones
+                )),
+            ));
+        }
+
         Ok(XModelCollSurf {
             coll_tris: self.coll_tris.to_vec_into(de)?,
             mins: self.mins.into(),
@@ -731,6 +1349,15 @@ impl<'a> XFileDeserializeInto<XModelCollSurf, ()> for XModelCollSurfRaw<'a> {
     }
 }
 
+impl XModelCollSurf {
+    pub fn bounds(&self) -> Bounds {
+        Bounds {
+            mins: self.mins,
+            maxs: self.maxs,
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub(crate) struct XModelCollTriRaw {
@@ -1288,6 +1915,42 @@ impl<'a> XFileDeserializeInto<PhysConstraints, ()> for PhysConstraintsRaw<'a> {
     }
 }
 
+impl PhysConstraints {
+    /// Checks that every constraint attached to a bone references a bone
+    /// that exists in a model with `num_bones` bones.
+    pub fn validate(&self, num_bones: usize) -> Result<()> {
+        if self.count > self.data.len() {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "PhysConstraints: count ({}) > {}",
+                    self.count,
+                    self.data.len()
+                )),
+            ));
+        }
+
+        for (i, c) in self.data.iter().take(self.count).enumerate() {
+            for (attach_point_type, target_index) in [
+                (c.attach_point_type1, c.target_index1),
+                (c.attach_point_type2, c.target_index2),
+            ] {
+                if attach_point_type == AttachPointType::BONE && target_index >= num_bones {
+                    return Err(Error::new(
+                        file_line_col!(),
+                        ErrorKind::BrokenInvariant(format!(
+                            "PhysConstraints: constraint {i}'s target_index ({target_index}) \
+                             >= num_bones ({num_bones})",
+                        )),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl XFileSerialize<()> for PhysConstraints {
     fn xfile_serialize(&self, _ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         todo!()
@@ -1354,7 +2017,7 @@ pub enum ConstraintType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug, FromPrimitive)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, FromPrimitive)]
 #[repr(i32)]
 pub enum AttachPointType {
     #[default]
@@ -1405,10 +2068,10 @@ impl<'a> XFileDeserializeInto<PhysConstraint, ()> for PhysConstraintRaw<'a> {
         _data: (),
     ) -> Result<PhysConstraint> {
         //dbg!(self);
-        let targetname = XString(self.targetname.to_string(de).unwrap_or_default());
-        let target_ent1 = XString(self.target_ent1.to_string(de).unwrap_or_default());
+        let targetname = XString::from(self.targetname.to_string(de).unwrap_or_default());
+        let target_ent1 = XString::from(self.target_ent1.to_string(de).unwrap_or_default());
         let target_bone1 = self.target_bone1.xfile_deserialize_into(de, ())?;
-        let target_ent2 = XString(self.target_ent2.to_string(de).unwrap_or_default());
+        let target_ent2 = XString::from(self.target_ent2.to_string(de).unwrap_or_default());
         let target_bone2 = self.target_bone2.xfile_deserialize_into(de, ())?;
         let material = self.material.xfile_deserialize_into(de, ())?;
         //dbg!(&targetname);
@@ -1467,6 +2130,49 @@ impl<'a> XFileDeserializeInto<PhysConstraint, ()> for PhysConstraintRaw<'a> {
     }
 }
 
+impl PhysConstraint {
+    /// Assembles the joint's frame into a column-major 4x4 matrix.
+    ///
+    /// Constraints don't store a full rotation, only [`Self::dir`], so the
+    /// frame's X axis is `dir` and its Y and Z axes are an arbitrary (but
+    /// stable) orthonormal completion of it. The translation is
+    /// [`Self::pos`].
+    pub fn joint_transform(&self) -> [[f32; 4]; 4] {
+        let [x, y, z] = self.joint_axes().map(Vec3::get);
+        let pos = self.pos.get();
+
+        [
+            [x[0], x[1], x[2], 0.0],
+            [y[0], y[1], y[2], 0.0],
+            [z[0], z[1], z[2], 0.0],
+            [pos[0], pos[1], pos[2], 1.0],
+        ]
+    }
+
+    /// Returns the joint frame's local X (`0`), Y (`1`), or Z (`2`) axis.
+    /// Any other value of `axis` is treated as Z.
+    pub fn joint_axis(&self, axis: u8) -> Vec3 {
+        self.joint_axes()[(axis as usize).min(2)]
+    }
+
+    fn joint_axes(&self) -> [Vec3; 3] {
+        if self.dir.length() == 0.0 {
+            return [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]].map(Vec3::from);
+        }
+
+        let x = self.dir.normalize();
+        let up = Vec3::from(if x.get()[2].abs() > 0.999 {
+            [1.0, 0.0, 0.0]
+        } else {
+            [0.0, 0.0, 1.0]
+        });
+        let y = up.cross(&x).normalize();
+        let z = x.cross(&y);
+
+        [x, y, z]
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[derive(Copy, Clone, Default, Debug, Deserialize)]
 pub struct XModelDrawInfo {
@@ -0,0 +1,199 @@
+//! Exporting skinned [`XModel`]s to `.xom` files.
+//!
+//! OBJ can't carry bone weights, so skinned meshes go out in a small format
+//! of their own instead. Everything is little-endian:
+//!
+//! ```text
+//! magic        b"XOM1"
+//! header_len   u32
+//! header       header_len bytes of UTF-8 JSON
+//! surfaces     one block per entry in header.surfaces, in order
+//! ```
+//!
+//! The header looks like this:
+//!
+//! ```json
+//! {
+//!   "name": "c_usa_specops_body",
+//!   "bones": [
+//!     { "name": "tag_origin", "parent": null,
+//!       "position": [0, 0, 0], "rotation": [0, 0, 0, 1] }
+//!   ],
+//!   "surfaces": [
+//!     { "material": "mtl_body", "vertex_count": 1024, "triangle_count": 1800 }
+//!   ]
+//! }
+//! ```
+//!
+//! `parent` indexes into `bones`. `position` and `rotation` (a quaternion,
+//! `[x, y, z, w]`) are the bone's rest pose in model space.
+//!
+//! Each surface block is `vertex_count` vertices followed by
+//! `triangle_count` triangles. A vertex is 52 bytes:
+//!
+//! ```text
+//! position      [f32; 3]
+//! normal        [f32; 3]
+//! uv            [f32; 2]
+//! bone_indices  [u8; 4]
+//! bone_weights  [f32; 4]
+//! ```
+//!
+//! and a triangle is three `u16` vertex indices into the same surface.
+
+use std::io::Write;
+
+use crate::{
+    Error, ErrorKind, Result, file_line_col,
+    xmodel::{DObjAnimMat, XModel, XSurface},
+};
+
+const MAGIC: &[u8; 4] = b"XOM1";
+
+/// Writes LOD 0 of `model` to `writer`, using the model's own
+/// [`XModel::base_mat`] as the skeleton's rest pose.
+pub fn write_xom(model: &XModel, writer: &mut impl Write) -> Result<()> {
+    write_with_skeleton(model, &model.base_mat, writer)
+}
+
+/// Writes LOD 0 of `model` to `writer`, with `rest_pose` as the skeleton's
+/// rest pose. `rest_pose` needs one transform per bone, in model space.
+pub fn write_with_skeleton(
+    model: &XModel,
+    rest_pose: &[DObjAnimMat],
+    writer: &mut impl Write,
+) -> Result<()> {
+    if rest_pose.len() < model.bone_names.len() {
+        return Err(Error::new(
+            file_line_col!(),
+            ErrorKind::BrokenInvariant(format!(
+                "xom: rest pose has {} bones, but {} has {}",
+                rest_pose.len(),
+                model.name.get(),
+                model.bone_names.len()
+            )),
+        ));
+    }
+
+    let surfaces = model.lod_surfaces(0).collect::<Vec<_>>();
+    let weights = surfaces
+        .iter()
+        .map(|(_, s)| s.bone_weights())
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut header = String::new();
+    header.push_str("{\"name\":");
+    push_json_str(&mut header, model.name.get());
+
+    header.push_str(",\"bones\":[");
+    for (i, name) in model.bone_names.iter().enumerate() {
+        if i > 0 {
+            header.push(',');
+        }
+        let pose = &rest_pose[i];
+        header.push_str("{\"name\":");
+        push_json_str(&mut header, name);
+        match model.bone_parent(i) {
+            Some(parent) => header.push_str(&format!(",\"parent\":{parent}")),
+            None => header.push_str(",\"parent\":null"),
+        }
+        header.push_str(",\"position\":");
+        push_json_floats(&mut header, &pose.trans.get());
+        header.push_str(",\"rotation\":");
+        push_json_floats(&mut header, &pose.quat.get());
+        header.push('}');
+    }
+
+    header.push_str("],\"surfaces\":[");
+    for (i, (idx, surf)) in surfaces.iter().enumerate() {
+        if i > 0 {
+            header.push(',');
+        }
+        header.push_str("{\"material\":");
+        match model.material_handles.get(*idx) {
+            Some(m) => push_json_str(&mut header, m.info.name.get()),
+            None => header.push_str("null"),
+        }
+        header.push_str(&format!(
+            ",\"vertex_count\":{},\"triangle_count\":{}}}",
+            surf.verts0.len(),
+            surf.tri_indices.len() / 3
+        ));
+    }
+    header.push_str("]}");
+
+    let io = |e| Error::new(file_line_col!(), ErrorKind::Io(e));
+    writer.write_all(MAGIC).map_err(io)?;
+    writer
+        .write_all(&(header.len() as u32).to_le_bytes())
+        .map_err(io)?;
+    writer.write_all(header.as_bytes()).map_err(io)?;
+
+    for ((_, surf), weights) in surfaces.iter().zip(weights) {
+        write_surface(surf, &weights, writer)?;
+    }
+
+    Ok(())
+}
+
+fn write_surface(
+    surf: &XSurface,
+    weights: &[[(usize, f32); 4]],
+    writer: &mut impl Write,
+) -> Result<()> {
+    let mut buf = Vec::with_capacity(surf.verts0.len() * 52 + surf.tri_indices.len() * 2);
+
+    for (v, w) in surf.verts0.iter().zip(weights) {
+        v.extend_position_normal_uv(&mut buf);
+
+        for &(bone, _) in w {
+            let bone = u8::try_from(bone).map_err(|_| {
+                Error::new(
+                    file_line_col!(),
+                    ErrorKind::BrokenInvariant(format!("xom: bone index ({bone}) > 255")),
+                )
+            })?;
+            buf.push(bone);
+        }
+        for &(_, weight) in w {
+            buf.extend(weight.to_le_bytes());
+        }
+    }
+
+    for &i in surf.tri_indices.iter().take(surf.tri_indices.len() / 3 * 3) {
+        buf.extend(i.to_le_bytes());
+    }
+
+    writer
+        .write_all(&buf)
+        .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))
+}
+
+fn push_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// JSON has no NaN or infinity, so those are written as `null`.
+fn push_json_floats(out: &mut String, fs: &[f32]) {
+    out.push('[');
+    for (i, f) in fs.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if f.is_finite() {
+            out.push_str(&format!("{f}"));
+        } else {
+            out.push_str("null");
+        }
+    }
+    out.push(']');
+}
@@ -0,0 +1,298 @@
+//! `#[derive(XFileConvert)]`, used by `t5-xfile-defs` to generate the
+//! [`XFileDeserializeInto`] and [`XFileSerialize`] impls for its `*Raw`
+//! structs instead of writing them by hand.
+//!
+//! The generated code refers to items through `crate::`, so the derive is
+//! only usable from within `t5-xfile-defs` itself.
+//!
+//! ```ignore
+//! #[derive(XFileConvert)]
+//! #[xfile(into = Font)]
+//! pub(crate) struct FontRaw<'a> {
+//!     #[xfile(string)]
+//!     pub font_name: XStringRaw<'a>,
+//!     pub pixel_height: i32,
+//!     #[xfile(len_of = glyphs)]
+//!     pub glyph_count: i32,
+//!     #[xfile(ptr)]
+//!     pub material: Ptr32<'a, MaterialRaw<'a>>,
+//!     #[xfile(array = glyph_count)]
+//!     pub glyphs: Ptr32<'a, Glyph>,
+//! }
+//! ```
+//!
+//! `into` names the converted struct. Every field of the raw struct,
+//! except those marked `len_of` or `pad`, must have a field of the same name
+//! there. Fields are deserialized in declaration order, and whatever they
+//! point to is serialized after the raw struct in that same order, so the
+//! two impls can't disagree about the layout.
+//!
+//! Field attributes:
+//! * *(none)*: copied as-is.
+//! * `bool`: an integer where nonzero means `true`.
+//! * `vector`: a `[f32; N]` converted to/from `Vec2`/`Vec3`/`Vec4`.
+//! * `string`: an `XStringRaw` converted to/from an `XString`.
+//! * `script_string`: a `ScriptString` converted to/from an `XString`.
+//! * `enum_from_u32`: an integer converted to/from a `FromPrimitive` enum.
+//!   Unknown values are an `ErrorKind::BadFromPrimitive`.
+//! * `ptr`: a `Ptr32<T>` converted to/from an `Option<Box<U>>`, where `T`
+//!   implements `XFileDeserializeInto<U, ()>`.
+//! * `fat_ptr`: any `FatPointer<T>` (`FatPointerCountFirstU32`, etc.)
+//!   converted to/from a `Vec<U>`, where `T` implements
+//!   `XFileDeserializeInto<U, ()>`.
+//! * `fat_ptr_into`: like `fat_ptr`, but where `U: From<T>`.
+//! * `array = count`: a `Ptr32<T>` to as many `T`s as the field `count`
+//!   says, converted to/from a `Vec<T>`.
+//! * `boxed_array`: a `Ptr32ArrayConst<T, N>` converted to/from an
+//!   `Option<Box<[T; N]>>`.
+//! * `len_of = field`: raw-only. Serialized as `field`'s length.
+//! * `pad`: raw-only. Serialized as [`Default::default`].
+//!
+//! [`XFileDeserializeInto`]: ../t5_xfile_defs/trait.XFileDeserializeInto.html
+//! [`XFileSerialize`]: ../t5_xfile_defs/trait.XFileSerialize.html
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Field, Fields, Ident, Path, Result, parse_macro_input};
+
+#[proc_macro_derive(XFileConvert, attributes(xfile))]
+pub fn derive_xfile_convert(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+enum FieldKind {
+    Copy,
+    Bool,
+    Vector,
+    String,
+    ScriptString,
+    EnumFromU32,
+    Ptr,
+    FatPtr,
+    FatPtrInto,
+    Array(Ident),
+    BoxedArray,
+    LenOf(Ident),
+    Pad,
+}
+
+impl FieldKind {
+    fn from_field(field: &Field) -> Result<Self> {
+        let mut kind = None;
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("xfile")) {
+            attr.parse_nested_meta(|meta| {
+                let Some(ident) = meta.path.get_ident() else {
+                    return Err(meta.error("expected an identifier"));
+                };
+
+                let k = match ident.to_string().as_str() {
+                    "bool" => FieldKind::Bool,
+                    "vector" => FieldKind::Vector,
+                    "string" => FieldKind::String,
+                    "script_string" => FieldKind::ScriptString,
+                    "enum_from_u32" => FieldKind::EnumFromU32,
+                    "ptr" => FieldKind::Ptr,
+                    "fat_ptr" => FieldKind::FatPtr,
+                    "fat_ptr_into" => FieldKind::FatPtrInto,
+                    "array" => FieldKind::Array(meta.value()?.parse()?),
+                    "boxed_array" => FieldKind::BoxedArray,
+                    "len_of" => FieldKind::LenOf(meta.value()?.parse()?),
+                    "pad" => FieldKind::Pad,
+                    _ => return Err(meta.error("unknown xfile field attribute")),
+                };
+
+                if kind.replace(k).is_some() {
+                    return Err(meta.error("only one xfile field attribute is allowed"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(kind.unwrap_or(FieldKind::Copy))
+    }
+
+    /// Whether the field also exists in the converted struct.
+    const fn is_converted(&self) -> bool {
+        !matches!(self, Self::LenOf(_) | Self::Pad)
+    }
+
+    /// Expression converting raw field `f` for the converted struct.
+    fn deserialize(&self, f: &Ident) -> TokenStream2 {
+        match self {
+            Self::Copy => quote!(self.#f),
+            Self::Bool => quote!(self.#f != 0),
+            Self::Vector => quote!(self.#f.into()),
+            Self::String | Self::Ptr | Self::FatPtr => {
+                quote!(self.#f.xfile_deserialize_into(de, ())?)
+            }
+            Self::ScriptString => quote!(crate::XString(self.#f.to_string(de)?)),
+            Self::EnumFromU32 => quote! {
+                match ::num::FromPrimitive::from_u32(self.#f as _) {
+                    ::core::option::Option::Some(v) => v,
+                    ::core::option::Option::None => {
+                        return ::core::result::Result::Err(crate::Error::new_with_offset(
+                            crate::file_line_col!(),
+                            de.stream_pos()? as _,
+                            crate::ErrorKind::BadFromPrimitive(self.#f as _),
+                        ));
+                    }
+                }
+            },
+            Self::FatPtrInto => quote!(self.#f.to_vec_into(de)?),
+            Self::Array(count) => quote!(self.#f.to_array(self.#count as _).to_vec(de)?),
+            Self::BoxedArray => quote! {
+                if self.#f.is_null() {
+                    ::core::option::Option::None
+                } else {
+                    ::core::option::Option::Some(alloc::boxed::Box::new(
+                        self.#f.to_vec(de)?.try_into().unwrap(),
+                    ))
+                }
+            },
+            Self::LenOf(_) | Self::Pad => unreachable!(),
+        }
+    }
+
+    /// Expression converting field `f` of the converted struct back for the
+    /// raw struct.
+    fn serialize(&self, f: &Ident) -> TokenStream2 {
+        match self {
+            Self::Copy => quote!(self.#f),
+            Self::Bool | Self::EnumFromU32 => quote!(self.#f as _),
+            Self::Vector => quote!(self.#f.get()),
+            Self::String => quote!(crate::util::XStringRaw::from_str(self.#f.get())),
+            Self::ScriptString => quote!(ser.intern(self.#f.get())?),
+            Self::Ptr => quote!(crate::util::Ptr32::from_box(&self.#f)),
+            Self::FatPtr | Self::FatPtrInto => quote!(crate::FatPointer::from_slice(&self.#f)),
+            Self::Array(_) => quote!(crate::util::Ptr32::from_slice(&self.#f)),
+            Self::BoxedArray => quote! {
+                self.#f
+                    .as_ref()
+                    .map(|s| crate::FatPointer::from_slice(&**s))
+                    .unwrap_or_default()
+            },
+            Self::LenOf(of) => quote!(self.#of.len() as _),
+            Self::Pad => quote!(::core::default::Default::default()),
+        }
+    }
+
+    /// Whether whatever field `f` points to has to be serialized after the
+    /// raw struct.
+    const fn has_data(&self) -> bool {
+        matches!(
+            self,
+            Self::String
+                | Self::Ptr
+                | Self::FatPtr
+                | Self::FatPtrInto
+                | Self::Array(_)
+                | Self::BoxedArray
+        )
+    }
+}
+
+fn target(input: &DeriveInput) -> Result<Path> {
+    let mut target = None;
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("xfile")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("into") {
+                target = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown xfile attribute"))
+            }
+        })?;
+    }
+
+    target.ok_or_else(|| Error::new_spanned(&input.ident, "missing #[xfile(into = ...)]"))
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "XFileConvert can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "XFileConvert can only be derived for structs with named fields",
+        ));
+    };
+
+    let target = target(input)?;
+    let raw = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = fields
+        .named
+        .iter()
+        .map(|f| Ok((f.ident.clone().unwrap(), FieldKind::from_field(f)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let converted = fields.iter().filter(|(_, k)| k.is_converted());
+    let de_lets = converted.clone().map(|(f, k)| {
+        let e = k.deserialize(f);
+        quote!(let #f = #e;)
+    });
+    let de_fields = converted.map(|(f, _)| f);
+
+    let ser_fields = fields.iter().map(|(f, k)| {
+        let e = k.serialize(f);
+        quote!(#f: #e)
+    });
+    let ser_data = fields
+        .iter()
+        .filter(|(_, k)| k.has_data())
+        .map(|(f, _)| quote!(self.#f.xfile_serialize(ser, ())?;));
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics crate::XFileDeserializeInto<#target, ()> for #raw #ty_generics
+        #where_clause
+        {
+            fn xfile_deserialize_into(
+                &self,
+                de: &mut impl crate::T5XFileDeserialize,
+                _data: (),
+            ) -> crate::Result<#target> {
+                #[allow(unused_imports)]
+                use crate::{FatPointer as _, XFileDeserializeInto as _};
+
+                #(#de_lets)*
+
+                ::core::result::Result::Ok(#target {
+                    #(#de_fields,)*
+                })
+            }
+        }
+
+        #[automatically_derived]
+        impl crate::XFileSerialize<()> for #target {
+            fn xfile_serialize(
+                &self,
+                ser: &mut impl crate::T5XFileSerialize,
+                _data: (),
+            ) -> crate::Result<()> {
+                #[allow(unused_imports)]
+                use crate::XFileSerialize as _;
+
+                let raw = #raw {
+                    #(#ser_fields,)*
+                };
+
+                ser.store_into_xfile(raw)?;
+                #(#ser_data)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
@@ -0,0 +1,122 @@
+//! Checks the layout tables in [`t5_xfile_defs::annotate`] against the raw
+//! structs' `assert_size!`-checked sizes, and the annotation of a
+//! hand-built asset header.
+
+use t5_xfile_defs::{
+    XFilePlatform,
+    annotate::{annotate, layout_for},
+    xasset::XAssetType,
+};
+
+#[test]
+fn layouts_match_raw_sizes() {
+    let expected = [
+        (XAssetType::PHYSPRESET, 84),
+        (XAssetType::XANIMPARTS, 104),
+        (XAssetType::MATERIAL, 192),
+        (XAssetType::TECHNIQUE_SET, 528),
+        (XAssetType::IMAGE, 52),
+        (XAssetType::LIGHT_DEF, 16),
+        (XAssetType::LOCALIZE_ENTRY, 8),
+        (XAssetType::RAWFILE, 12),
+        (XAssetType::STRINGTABLE, 20),
+        (XAssetType::FONT, 24),
+        (XAssetType::MAP_ENTS, 12),
+        (XAssetType::PACKINDEX, 28),
+        (XAssetType::XGLOBALS, 40),
+    ];
+
+    for (asset_type, size) in expected {
+        let layout = layout_for(asset_type).unwrap();
+        assert_eq!(layout.size, size, "{}", layout.name);
+        assert_eq!(layout.fields_size(), size, "{}", layout.name);
+    }
+    assert!(layout_for(XAssetType::GFXWORLD).is_none());
+}
+
+fn offset_of(asset_type: XAssetType, name: &str) -> usize {
+    layout_for(asset_type)
+        .unwrap()
+        .offsets()
+        .find(|(_, f)| f.name == name)
+        .unwrap()
+        .0
+}
+
+#[test]
+fn field_offsets() {
+    for (asset_type, name, offset) in [
+        (XAssetType::XANIMPARTS, "numframes", 14),
+        (XAssetType::XANIMPARTS, "bone_count", 24),
+        (XAssetType::XANIMPARTS, "framerate", 48),
+        (XAssetType::XANIMPARTS, "delta_part", 100),
+        (XAssetType::MATERIAL, "info.draw_surf", 16),
+        (XAssetType::MATERIAL, "state_bits_entry", 40),
+        (XAssetType::MATERIAL, "technique_set", 176),
+        (XAssetType::IMAGE, "card_memory", 12),
+        (XAssetType::IMAGE, "width", 20),
+        (XAssetType::IMAGE, "name", 44),
+        (XAssetType::LIGHT_DEF, "lmap_lookup_start", 12),
+    ] {
+        assert_eq!(offset_of(asset_type, name), offset, "{asset_type:?} {name}");
+    }
+}
+
+/// A `RawFileRaw` with an inline name, a length of 300, and a buffer at
+/// block 5 + 0x40.
+fn raw_file(be: bool) -> Vec<u8> {
+    let words: [u32; 3] = [0xFFFFFFFF, 300, (5 << 29) + 0x40 + 1];
+    words
+        .iter()
+        .flat_map(|w| if be { w.to_be_bytes() } else { w.to_le_bytes() })
+        .chain([0xAA; 4])
+        .collect()
+}
+
+#[test]
+fn annotate_raw_file() {
+    for (platform, be) in [
+        (XFilePlatform::Windows, false),
+        (XFilePlatform::Xbox360, true),
+    ] {
+        let fields = annotate(XAssetType::RAWFILE, &raw_file(be), platform).unwrap();
+
+        let summary = fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.offset, f.bytes.len(), f.value.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("name", 0, 4, "inline (follows)"),
+                ("len", 4, 4, "300"),
+                ("buffer", 8, 4, "block 5 + 0x40"),
+            ]
+        );
+        assert_eq!(fields[1].ty, "i32");
+
+        // One line per field, with the range and decoded value.
+        let line = fields[1].to_string();
+        assert!(line.starts_with("0x0004..0x0008"), "{line}");
+        assert!(line.ends_with(" 300"), "{line}");
+    }
+}
+
+#[test]
+fn annotate_arrays() {
+    let mut bytes = vec![0; 40];
+    bytes[24..28].copy_from_slice(&1.5f32.to_le_bytes());
+    bytes[36..40].copy_from_slice(&(-2.0f32).to_le_bytes());
+    let fields = annotate(XAssetType::XGLOBALS, &bytes, XFilePlatform::Windows).unwrap();
+
+    let color = fields.last().unwrap();
+    assert_eq!(color.name, "screen_clear_color");
+    assert_eq!(color.value, "[1.5, 0.0, 0.0, -2.0]");
+    assert_eq!(fields[0].value, "null");
+}
+
+#[test]
+fn annotate_errors() {
+    assert!(annotate(XAssetType::RAWFILE, &[0; 11], XFilePlatform::Windows).is_err());
+    assert!(annotate(XAssetType::GFXWORLD, &[0; 4096], XFilePlatform::Windows).is_err());
+}
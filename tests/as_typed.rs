@@ -0,0 +1,60 @@
+//! Checks [`XAssetGeneric::as_typed`] against a mixed asset list.
+
+use core::any::TypeId;
+
+use t5_xfile_defs::{
+    XString,
+    techset::{Material, MaterialInfo},
+    xasset::XAssetGeneric,
+    xmodel::XModel,
+};
+
+fn assets() -> Vec<XAssetGeneric> {
+    vec![
+        XAssetGeneric::Material(Some(Box::new(Material {
+            info: MaterialInfo {
+                name: XString::from("mc/mtl_crate".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))),
+        XAssetGeneric::XModel(Some(Box::new(XModel {
+            name: XString::from("p_crate".to_string()),
+            ..Default::default()
+        }))),
+        XAssetGeneric::XModel(None),
+    ]
+}
+
+#[test]
+fn as_typed() {
+    let assets = assets();
+
+    let materials = assets
+        .iter()
+        .filter_map(XAssetGeneric::as_typed::<Material>)
+        .collect::<Vec<_>>();
+    assert_eq!(materials.len(), 1);
+    assert_eq!(materials[0].info.name.get(), "mc/mtl_crate");
+
+    let models = assets
+        .iter()
+        .filter_map(XAssetGeneric::as_typed::<XModel>)
+        .collect::<Vec<_>>();
+    assert_eq!(models.len(), 1);
+    assert_eq!(models[0].name.get(), "p_crate");
+
+    assert_eq!(assets[0].content_type_id(), TypeId::of::<Material>());
+    assert_eq!(assets[1].content_type_id(), TypeId::of::<XModel>());
+    // Null assets still have a content type.
+    assert_eq!(assets[2].content_type_id(), TypeId::of::<XModel>());
+}
+
+#[test]
+fn as_typed_mut() {
+    let mut assets = assets();
+
+    assert!(assets[0].as_typed_mut::<XModel>().is_none());
+    assets[1].as_typed_mut::<XModel>().unwrap().name = XString::from("p_barrel".to_string());
+    assert_eq!(assets[1].name(), Some("p_barrel"));
+}
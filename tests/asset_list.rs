@@ -0,0 +1,88 @@
+//! Checks that the `ASSETLIST` asset from dev zone dumps survives being
+//! serialized and read back.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    xasset::{AssetListAsset, XAsset, XAssetGeneric, XAssetType},
+};
+use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+use common::TempFile;
+
+fn names(names: &[&str]) -> Vec<XString> {
+    names.iter().copied().map(XString::from).collect()
+}
+
+fn roundtrip(name: &str, list: AssetListAsset) -> Vec<XAsset> {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>(
+        [XAsset::PC(XAssetGeneric::AssetList(Some(Box::new(list))))].into_iter(),
+    )
+    .unwrap();
+
+    let temp = TempFile::new(&format!("asset_list_{name}.ff"), ser.deflate().unwrap());
+    common::deserialize(&temp, false).unwrap()
+}
+
+#[test]
+fn roundtrip_two_types() {
+    let list = AssetListAsset {
+        name: XString::from("mp_test_assets"),
+        entries: BTreeMap::from([
+            (
+                XAssetType::RAWFILE,
+                names(&["maps/mp/mp_test.gsc", "maps/mp/_load.gsc"]),
+            ),
+            (XAssetType::MATERIAL, names(&["mc/mtl_test", "", "white"])),
+        ]),
+    };
+
+    let assets = roundtrip("two_types", list.clone());
+    let [XAsset::PC(XAssetGeneric::AssetList(Some(read)))] = assets.as_slice() else {
+        panic!("expected one asset list, got {assets:?}");
+    };
+
+    assert_eq!(read.name.get(), "mp_test_assets");
+    assert_eq!(read.len(), 5);
+    assert_eq!(
+        read.entries.keys().collect::<Vec<_>>(),
+        list.entries.keys().collect::<Vec<_>>()
+    );
+    for (asset_type, names) in &list.entries {
+        assert_eq!(
+            read.entries[asset_type]
+                .iter()
+                .map(|n| n.get())
+                .collect::<Vec<_>>(),
+            names.iter().map(|n| n.get()).collect::<Vec<_>>()
+        );
+    }
+    assert_eq!(assets[0].name(), Some("mp_test_assets"));
+}
+
+#[test]
+fn roundtrip_empty() {
+    // Types with no names are left out, whether or not they were there to
+    // begin with.
+    let list = AssetListAsset {
+        name: XString::from("empty"),
+        entries: BTreeMap::from([(XAssetType::SOUND, Vec::new())]),
+    };
+
+    let assets = roundtrip("empty", list);
+    let [XAsset::PC(XAssetGeneric::AssetList(Some(read)))] = assets.as_slice() else {
+        panic!("expected one asset list, got {assets:?}");
+    };
+    assert_eq!(read.name.get(), "empty");
+    assert!(read.entries.is_empty());
+    assert!(read.is_empty());
+}
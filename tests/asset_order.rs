@@ -0,0 +1,107 @@
+//! Checks the deterministic ordering helpers on [`XAssetList`].
+
+use t5_xfile_defs::{
+    XString,
+    misc::{LocalizeEntry, RawFile},
+    xasset::{XAsset, XAssetGeneric, XAssetList, XAssetType},
+};
+
+fn raw_file(name: &str) -> XAsset {
+    XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+        name: XString::from(name.to_string()),
+        buffer: Vec::new(),
+    }))))
+}
+
+fn localize(name: &str) -> XAsset {
+    XAsset::PC(XAssetGeneric::LocalizeEntry(Some(Box::new(
+        LocalizeEntry::new(name, ""),
+    ))))
+}
+
+fn assets() -> Vec<XAsset> {
+    vec![
+        raw_file("b.cfg"),
+        localize("MENU_B"),
+        raw_file("a.cfg"),
+        XAsset::PC(XAssetGeneric::RawFile(None)),
+        localize("MENU_A"),
+        raw_file("a.cfg"),
+    ]
+}
+
+fn keys(assets: &[XAsset]) -> Vec<(XAssetType, Option<&str>)> {
+    assets.iter().map(|a| (a.asset_type(), a.name())).collect()
+}
+
+#[test]
+fn sort_by_type_and_name() {
+    let mut assets = assets();
+    XAssetList::sort_by_type_and_name(&mut assets);
+
+    assert!(XAssetType::LOCALIZE_ENTRY < XAssetType::RAWFILE);
+    assert_eq!(
+        keys(&assets),
+        [
+            (XAssetType::LOCALIZE_ENTRY, Some("MENU_A")),
+            (XAssetType::LOCALIZE_ENTRY, Some("MENU_B")),
+            (XAssetType::RAWFILE, None),
+            (XAssetType::RAWFILE, Some("a.cfg")),
+            (XAssetType::RAWFILE, Some("a.cfg")),
+            (XAssetType::RAWFILE, Some("b.cfg")),
+        ]
+    );
+}
+
+#[test]
+fn grouped_by_type() {
+    let assets = assets();
+    let groups = XAssetList::grouped_by_type(&assets);
+
+    assert_eq!(
+        groups.keys().copied().collect::<Vec<_>>(),
+        [XAssetType::LOCALIZE_ENTRY, XAssetType::RAWFILE]
+    );
+    let names = |ty| groups[&ty].iter().map(|a| a.name()).collect::<Vec<_>>();
+    assert_eq!(
+        names(XAssetType::LOCALIZE_ENTRY),
+        [Some("MENU_B"), Some("MENU_A")]
+    );
+    assert_eq!(
+        names(XAssetType::RAWFILE),
+        [Some("b.cfg"), Some("a.cfg"), None, Some("a.cfg")]
+    );
+}
+
+#[test]
+fn canonical_hash() {
+    let hash = XAssetList::canonical_hash(&assets());
+
+    let mut reversed = assets();
+    reversed.reverse();
+    assert_eq!(XAssetList::canonical_hash(&reversed), hash);
+    let mut sorted = assets();
+    XAssetList::sort_by_type_and_name(&mut sorted);
+    assert_eq!(XAssetList::canonical_hash(&sorted), hash);
+
+    // Adding, removing, renaming, or retyping an asset all change the hash,
+    // including adding a second copy of a name that's already there.
+    let mut added = assets();
+    added.push(raw_file("c.cfg"));
+    let mut duplicated = assets();
+    duplicated.push(raw_file("b.cfg"));
+    let mut removed = assets();
+    removed.remove(0);
+    let mut renamed = assets();
+    renamed[0] = raw_file("c.cfg");
+    let mut retyped = assets();
+    retyped[0] = localize("b.cfg");
+    for changed in [added, duplicated, removed, renamed, retyped] {
+        assert_ne!(XAssetList::canonical_hash(&changed), hash);
+    }
+
+    assert_ne!(
+        XAssetList::canonical_hash(&[XAsset::PC(XAssetGeneric::RawFile(None))]),
+        XAssetList::canonical_hash(&[raw_file("")])
+    );
+}
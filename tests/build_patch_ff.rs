@@ -0,0 +1,19 @@
+//! Runs the `build_patch_ff` example's flow, writing to a temporary file
+//! instead of `out/`.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+#[path = "../examples/build_patch_ff.rs"]
+mod example;
+
+mod common;
+
+use common::TempFile;
+
+#[test]
+fn build_patch_ff() {
+    let out = TempFile::empty("build_patch_ff.ff");
+    let assets = example::build_patch(out.path(), example::patch_assets().unwrap()).unwrap();
+
+    example::check_patch(&assets);
+}
@@ -0,0 +1,171 @@
+//! Checks that inflating, deserializing and exporting can be cancelled.
+
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform, XString,
+    cancel::CancellationToken,
+    misc::RawFile,
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::{T5XFileDeserializer, T5XFileDeserializerBuilder, export};
+
+use common::{TempFile, u32s};
+
+const RAWFILE: u32 = 0x24;
+const INLINE: u32 = 0xFFFFFFFF;
+
+/// Big enough that reading one takes far longer than the tests wait before
+/// cancelling.
+const RAWFILE_LEN: usize = 16 << 20;
+
+/// An inflated blob holding `count` [`RAWFILE_LEN`]-byte raw files.
+fn blob(count: u32) -> Vec<u8> {
+    let mut data = u32s(&[0, 0, count, INLINE]);
+    for _ in 0..count {
+        data.extend(u32s(&[RAWFILE, INLINE]));
+    }
+    for i in 0..count {
+        data.extend(u32s(&[INLINE, RAWFILE_LEN as u32, INLINE]));
+        data.extend(format!("cancel/{i}.txt\0").as_bytes());
+        data.resize(data.len() + RAWFILE_LEN, b'a');
+        data.push(0);
+    }
+
+    let mut blob = u32s(&[data.len() as u32, 0, 0, 0, 0, 0, 0, 0, 0]);
+    blob.extend(data);
+    blob
+}
+
+fn with_deserializer(
+    name: &str,
+    count: u32,
+    token: CancellationToken,
+    f: impl FnOnce(T5XFileDeserializer),
+) {
+    let temp = TempFile::new(&format!("cancel_{name}.cache"), blob(count));
+
+    let mut file = temp.open();
+    let de = T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .with_cancellation_token(token)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .unwrap();
+    f(de);
+}
+
+/// Cancels `token` after `delay` on another thread. The returned slot holds
+/// when it did.
+fn cancel_after(token: &CancellationToken, delay: Duration) -> Arc<Mutex<Option<Instant>>> {
+    let cancelled_at = Arc::new(Mutex::new(None));
+    let token = token.clone();
+    let slot = cancelled_at.clone();
+    thread::spawn(move || {
+        thread::sleep(delay);
+        *slot.lock().unwrap() = Some(Instant::now());
+        token.cancel();
+    });
+    cancelled_at
+}
+
+#[test]
+fn token() {
+    let token = CancellationToken::new();
+    let clone = token.clone();
+    assert!(!token.is_cancelled());
+    clone.cancel();
+    assert!(token.is_cancelled());
+    assert!(!CancellationToken::default().is_cancelled());
+}
+
+#[test]
+fn cancel_remaining() {
+    let token = CancellationToken::new();
+    with_deserializer("remaining", 2, token.clone(), |de| {
+        let cancelled_at = cancel_after(&token, Duration::from_millis(20));
+        let e = de.deserialize_remaining().unwrap_err();
+        let returned = Instant::now();
+
+        assert!(matches!(e.kind(), ErrorKind::Cancelled), "{e}");
+        let latency = returned - cancelled_at.lock().unwrap().unwrap();
+        assert!(latency < Duration::from_millis(100), "{latency:?}");
+    });
+}
+
+#[test]
+fn cancel_next_rewinds() {
+    let token = CancellationToken::new();
+    with_deserializer("next", 1, token.clone(), |mut de| {
+        let before = de.checkpoint().unwrap().position();
+
+        cancel_after(&token, Duration::from_millis(20));
+        let e = de.deserialize_next().unwrap_err();
+        assert!(matches!(e.kind(), ErrorKind::Cancelled), "{e}");
+
+        // Nothing of the half-read asset is left behind.
+        assert_eq!(de.checkpoint().unwrap().position(), before);
+        assert!(de.asset_span(0).is_none());
+
+        // The asset is still queued, and cancelled again straight away.
+        let e = de.deserialize_next().unwrap_err();
+        assert!(matches!(e.kind(), ErrorKind::Cancelled), "{e}");
+        assert_eq!(de.checkpoint().unwrap().position(), before);
+    });
+}
+
+#[test]
+fn cancel_before_inflate() {
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let temp = TempFile::new("cancel_inflate.cache", blob(1));
+    let mut file = temp.open();
+    let result =
+        T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .with_cancellation_token(token)
+            .build()
+            .and_then(|de| de.inflate());
+
+    let e = result.map(|_| ()).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::Cancelled), "{e}");
+}
+
+#[test]
+fn export() {
+    let assets = (0..4)
+        .map(|i| {
+            XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+                name: XString::from(format!("cancel/{i}.txt")),
+                buffer: b"text\0".to_vec(),
+            }))))
+        })
+        .collect::<Vec<_>>();
+
+    let mut plain = Vec::new();
+    export::bin::write_assets(&assets, 7, &mut plain).unwrap();
+    let mut cancellable = Vec::new();
+    let token = CancellationToken::new();
+    export::bin::write_assets_cancellable(&assets, 7, &mut cancellable, &token).unwrap();
+    assert_eq!(plain, cancellable);
+
+    let (header, read) = export::bin::read_assets_cancellable(&plain[..], &token).unwrap();
+    assert_eq!(header.source_checksum, 7);
+    assert_eq!(read.len(), assets.len());
+
+    token.cancel();
+    let e = export::bin::write_assets_cancellable(&assets, 7, Vec::new(), &token).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::Cancelled), "{e}");
+    let e = export::bin::read_assets_cancellable(&plain[..], &token).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::Cancelled), "{e}");
+}
@@ -0,0 +1,81 @@
+//! Checks that [`T5XFileDeserializer::peek`] and
+//! [`T5XFileDeserializer::restore`] leave the deserializer where they found
+//! it.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use std::fs::File;
+
+use t5_xfile_defs::{T5XFileDeserialize, XFilePlatform, xasset::XAsset};
+use t5_xfile_deserializer::{
+    T5XFileDeserializer, T5XFileDeserializerBuilder, language_pack::LanguagePackBuilder,
+};
+
+use common::TempFile;
+
+/// Writes a small fastfile to a temporary file.
+fn write_fastfile(name: &str) -> TempFile {
+    let mut builder = LanguagePackBuilder::new(XFilePlatform::Windows);
+    builder
+        .add_all([("MENU_A", "a"), ("MENU_B", "b"), ("MENU_C", "c")])
+        .unwrap();
+
+    TempFile::new(&format!("{name}.ff"), builder.build().unwrap())
+}
+
+fn open(file: &mut File) -> T5XFileDeserializer<'_> {
+    T5XFileDeserializerBuilder::from_file(file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .unwrap()
+}
+
+fn names(assets: &[XAsset]) -> Vec<&str> {
+    assets.iter().filter_map(XAsset::name).collect()
+}
+
+#[test]
+fn peek_doesnt_advance() {
+    let temp = write_fastfile("checkpoint_peek");
+    let mut file = temp.open();
+    let mut de = open(&mut file);
+
+    let pos = de.stream_pos().unwrap();
+    // The first entry's value is stored inline, so its pointer is -1.
+    assert_eq!(de.peek::<u32>().unwrap(), 0xFFFFFFFF);
+    assert_eq!(de.peek::<u32>().unwrap(), 0xFFFFFFFF);
+    assert_eq!(de.stream_pos().unwrap(), pos);
+
+    let assets = de.deserialize_remaining().unwrap();
+    assert_eq!(names(&assets), ["MENU_A", "MENU_B", "MENU_C"]);
+}
+
+#[test]
+fn restore_after_failed_parse() {
+    let temp = write_fastfile("checkpoint_restore");
+    let mut file = temp.open();
+    let mut de = open(&mut file);
+
+    let checkpoint = de.checkpoint().unwrap();
+    let first = de.deserialize_next().unwrap().unwrap();
+    assert_eq!(first.name(), Some("MENU_A"));
+
+    // Reading past the end of the blob fails...
+    let end = de.stream_len().unwrap();
+    while de.stream_pos().unwrap() < end {
+        de.load_from_xfile::<u8>().unwrap();
+    }
+    assert!(de.load_from_xfile::<u32>().is_err());
+    assert!(de.peek::<u32>().is_err());
+
+    // ...but restoring undoes both the read and the deserialized asset.
+    de.restore(checkpoint.clone()).unwrap();
+    assert_eq!(de.stream_pos().unwrap(), checkpoint.position());
+
+    let assets = de.deserialize_remaining().unwrap();
+    assert_eq!(names(&assets), ["MENU_A", "MENU_B", "MENU_C"]);
+}
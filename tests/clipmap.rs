@@ -0,0 +1,309 @@
+//! Checks [`ClipMap`]'s dyn ent, brush model, and trigger accessors,
+//! validation, and brush checks.
+
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    clipmap::{
+        CBrush, CLeaf, CLeafBrushNode, CLeafBrushNodeChildren, CLeafBrushNodeData,
+        CLeafBrushNodeLeaf, CModel, ClipMap, ClipTrigger, Contents, DynEntityBasis, DynEntityDef,
+        DynEntityPose, DynEntityType,
+    },
+    common::Vec3,
+    validate::{Severity, validate},
+    xasset::{XAsset, XAssetGeneric},
+    xmodel::{CBrushSide, CPlane, PhysPreset, XModel},
+};
+
+fn clip_map() -> ClipMap {
+    let clutter = DynEntityDef {
+        type_: DynEntityType::CLUTTER,
+        xmodel: Some(Box::new(XModel {
+            name: XString::from("p_crate".to_string()),
+            ..Default::default()
+        })),
+        phys_preset: Some(Box::new(PhysPreset {
+            name: XString::from("wood".to_string()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    // Missing its model.
+    let destruct = DynEntityDef {
+        type_: DynEntityType::DESTRUCT,
+        health: 100,
+        targetname: "barrel".to_string(),
+        ..Default::default()
+    };
+
+    ClipMap {
+        name: XString::from("maps/mp/mp_test.d3dbsp".to_string()),
+        dyn_ent_count: [2, 0, 1, 0],
+        dyn_ent_def_list: [vec![clutter, destruct], Vec::new()],
+        dyn_ent_pose_list: [vec![DynEntityPose::default(); 2], Vec::new()],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn dyn_entities() {
+    let c = clip_map();
+
+    let entities = c.dyn_entities().collect::<Vec<_>>();
+    assert_eq!(entities.len(), 2);
+    assert!(entities.iter().all(|e| e.basis == DynEntityBasis::MODEL));
+    assert!(entities.iter().all(|e| e.pose.is_some()));
+    assert_eq!(entities[0].def.type_, DynEntityType::CLUTTER);
+    assert_eq!(entities[1].def.type_, DynEntityType::DESTRUCT);
+    assert_eq!(entities[1].index, 1);
+
+    assert_eq!(c.dyn_ent_count(DynEntityBasis::MODEL), 2);
+    assert_eq!(c.server_dyn_ent_count(DynEntityBasis::MODEL), 1);
+
+    let models = c.referenced_models();
+    assert_eq!(models.len(), 1);
+    assert_eq!(models[0].name.get(), "p_crate");
+    assert_eq!(c.referenced_phys_presets()[0].name.get(), "wood");
+    assert!(c.referenced_effects().is_empty());
+}
+
+#[test]
+fn missing_model_warning() {
+    let assets = [XAsset::PC(XAssetGeneric::ClipMap(Some(Box::new(
+        clip_map(),
+    ))))];
+    let report = validate(&assets);
+
+    let findings = report.findings.iter().collect::<Vec<_>>();
+    assert_eq!(findings.len(), 1, "{findings:?}");
+    assert_eq!(findings[0].severity, Severity::Warning);
+    assert_eq!(findings[0].field, "dyn_ent_def_list[0][1].xmodel");
+}
+
+/// A 2x2x2 cube centered on `x` along the X axis, with a side for each of
+/// its faces. If `inverted`, the +X side's plane faces into the cube.
+fn cube(x: f32, inverted: bool) -> CBrush {
+    let side = |normal: [f32; 3], dist: f32| CBrushSide {
+        plane: Some(Box::new(CPlane {
+            normal: normal.into(),
+            dist,
+            ..Default::default()
+        })),
+        cflags: 0,
+        sflags: 0,
+    };
+    let mut sides = vec![
+        side([1.0, 0.0, 0.0], x + 1.0),
+        side([-1.0, 0.0, 0.0], -(x - 1.0)),
+        side([0.0, 1.0, 0.0], 1.0),
+        side([0.0, -1.0, 0.0], 1.0),
+        side([0.0, 0.0, 1.0], 1.0),
+        side([0.0, 0.0, -1.0], 1.0),
+    ];
+    if inverted {
+        // The same plane, facing the other way.
+        sides[0] = side([-1.0, 0.0, 0.0], -(x + 1.0));
+    }
+
+    let verts = (0..8)
+        .map(|i| {
+            let c = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+            Vec3::from([x + c(1), c(2), c(4)])
+        })
+        .collect();
+
+    CBrush {
+        mins: [x - 1.0, -1.0, -1.0].into(),
+        contents: 1,
+        maxs: [x + 1.0, 1.0, 1.0].into(),
+        sides,
+        axial_cflags: [[0; 3]; 2],
+        axial_sflags: [[0; 3]; 2],
+        verts,
+    }
+}
+
+#[test]
+fn inverted_brush() {
+    let c = ClipMap {
+        brushes: vec![cube(0.0, false), cube(4.0, true)],
+        ..Default::default()
+    };
+
+    assert_eq!(c.brushes[0].inverted_sides().count(), 0);
+    assert_eq!(c.brushes[1].inverted_sides().collect::<Vec<_>>(), [0]);
+    assert_eq!(c.check_surface_normals(), [1]);
+
+    // The flipped plane has the rest of the cube in front of it.
+    assert!(c.brushes[0].is_convex());
+    assert!(!c.brushes[1].is_convex());
+    assert_eq!(c.check_brush_convexity(), [1]);
+}
+
+#[test]
+fn vertex_outside_brush() {
+    let mut brush = cube(0.0, false);
+    // Within the bounds, but in front of a side's plane.
+    brush.sides.push(CBrushSide {
+        plane: Some(Box::new(CPlane {
+            normal: Vec3::from([1.0, 1.0, 0.0]).normalize(),
+            dist: 1.0,
+            ..Default::default()
+        })),
+        cflags: 0,
+        sflags: 0,
+    });
+    assert!(!brush.is_convex());
+
+    let mut brush = cube(0.0, false);
+    brush.verts.push([0.0, 0.0, 2.0].into());
+    assert!(!brush.is_convex());
+}
+
+fn brush_model(leaf_brush_node: i32, mins: [f32; 3], maxs: [f32; 3]) -> CModel {
+    CModel {
+        mins: mins.into(),
+        maxs: maxs.into(),
+        leaf: CLeaf {
+            leaf_brush_node,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn leaf_brush_node(data: Option<CLeafBrushNodeData>) -> CLeafBrushNode {
+    CLeafBrushNode {
+        axis: 0,
+        leaf_brush_count: 0,
+        contents: 1,
+        data,
+    }
+}
+
+fn leaf(brushes: &[u16]) -> CLeafBrushNode {
+    leaf_brush_node(Some(CLeafBrushNodeData::Leaf(CLeafBrushNodeLeaf {
+        brushes: brushes.to_vec(),
+    })))
+}
+
+#[test]
+fn brush_models() {
+    // Node 0 splits into nodes 1 and 2, which hold 2 and 3 brushes. Node 3
+    // has nothing under it.
+    let c = ClipMap {
+        leafbrush_nodes: vec![
+            leaf_brush_node(Some(CLeafBrushNodeData::Children(CLeafBrushNodeChildren {
+                child_offset: [1, 2],
+                ..Default::default()
+            }))),
+            leaf(&[0, 1]),
+            leaf(&[2, 3, 4]),
+            leaf_brush_node(None),
+        ],
+        cmodels: vec![
+            // The world doesn't own any brushes through its leaf.
+            brush_model(-1, [-4096.0; 3], [4096.0; 3]),
+            brush_model(0, [0.0, 0.0, 0.0], [64.0, 32.0, 16.0]),
+            brush_model(2, [-8.0; 3], [8.0; 3]),
+            brush_model(3, [0.0; 3], [0.0; 3]),
+            brush_model(100, [0.0; 3], [0.0; 3]),
+        ],
+        ..Default::default()
+    };
+
+    assert_eq!(c.brush_model_count(), 5);
+    let (mins, maxs) = c.brush_model_at(1).unwrap().bounds();
+    assert_eq!(mins.get(), [0.0, 0.0, 0.0]);
+    assert_eq!(maxs.get(), [64.0, 32.0, 16.0]);
+    let e = c.brush_model_at(5).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+
+    let counts = c
+        .cmodels
+        .iter()
+        .map(|m| m.brush_count(&c))
+        .collect::<Vec<_>>();
+    assert_eq!(counts, [0, 5, 3, 0, 0]);
+
+    assert_eq!(
+        c.brush_model_for_entity("*2").unwrap().bounds().1.get(),
+        [8.0; 3]
+    );
+    assert!(c.brush_model_for_entity("*0").is_some());
+    assert!(c.brush_model_for_entity("*5").is_none());
+    assert!(c.brush_model_for_entity("*").is_none());
+    assert!(c.brush_model_for_entity("*-1").is_none());
+    assert!(c.brush_model_for_entity("2").is_none());
+    assert!(c.brush_model_for_entity("p_crate").is_none());
+}
+
+#[test]
+fn self_referencing_leaf_brush_node() {
+    // Node 0 lists itself as a child, and node 1 has one past the end of
+    // the list.
+    let children = |child_offset| {
+        leaf_brush_node(Some(CLeafBrushNodeData::Children(CLeafBrushNodeChildren {
+            child_offset,
+            ..Default::default()
+        })))
+    };
+    let c = ClipMap {
+        leafbrush_nodes: vec![children([0, 2]), children([u16::MAX, 1]), leaf(&[0, 1])],
+        cmodels: vec![
+            brush_model(0, [0.0; 3], [0.0; 3]),
+            brush_model(1, [0.0; 3], [0.0; 3]),
+        ],
+        ..Default::default()
+    };
+
+    assert_eq!(c.cmodels[0].brush_count(&c), 2);
+    assert_eq!(c.cmodels[1].brush_count(&c), 2);
+}
+
+#[test]
+fn trigger_volumes() {
+    let with_contents = |x, contents: Contents| CBrush {
+        contents: contents.bits() as i32,
+        ..cube(x, false)
+    };
+    let c = ClipMap {
+        brushes: vec![
+            with_contents(0.0, Contents::TRIGGER),
+            with_contents(4.0, Contents::NODROP),
+            with_contents(8.0, Contents::SOLID),
+        ],
+        ..clip_map()
+    };
+
+    // The solid brush isn't a trigger.
+    let triggers = c.trigger_volumes().collect::<Vec<_>>();
+    assert_eq!(triggers.len(), 2);
+    assert!(triggers[0].is_script_trigger() && !triggers[0].is_kill_volume());
+    assert!(triggers[1].is_kill_volume() && !triggers[1].is_script_trigger());
+    assert_eq!(triggers[1].center().get(), [4.0, 0.0, 0.0]);
+
+    // Inside, on the edge of, and outside the script trigger.
+    let inside = c.triggers_containing_point([0.5, -0.5, 0.0].into());
+    assert_eq!(inside.len(), 1);
+    assert!(inside[0].is_script_trigger());
+    assert!(triggers[0].contains_point([1.0, 1.0, -1.0].into()));
+    assert!(!triggers[0].contains_point([1.5, 0.0, 0.0].into()));
+    assert!(
+        c.triggers_containing_point([2.0, 0.0, 0.0].into())
+            .is_empty()
+    );
+
+    // Inside the solid brush, which isn't a trigger.
+    assert!(
+        c.triggers_containing_point([8.0, 0.0, 0.0].into())
+            .is_empty()
+    );
+
+    let both = ClipTrigger {
+        bounds_mins: [0.0; 3].into(),
+        bounds_maxs: [0.0; 3].into(),
+        contents_flags: (Contents::TRIGGER | Contents::NODROP).bits(),
+    };
+    assert!(both.is_script_trigger() && both.is_kill_volume());
+    assert!(both.contains_point([0.0; 3].into()));
+}
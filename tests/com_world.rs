@@ -0,0 +1,53 @@
+//! Checks [`ComWorld::to_light_list`] and the per-type light counts.
+
+use t5_xfile_defs::{
+    com_world::{ComPrimaryLight, ComWorld, GfxLightType, LightDescriptorType},
+    common::Vec3,
+};
+
+fn light(type_: GfxLightType) -> ComPrimaryLight {
+    ComPrimaryLight {
+        type_: type_ as u8,
+        origin: Vec3::from([1.0, 2.0, 3.0]),
+        radius: 100.0,
+        cos_half_fov_inner: 0.5,
+        cos_half_fov_outer: 0.0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn light_list() {
+    let world = ComWorld {
+        primary_lights: vec![
+            light(GfxLightType::NONE),
+            light(GfxLightType::DIR),
+            light(GfxLightType::SPOT),
+            light(GfxLightType::SPOT_ROUND),
+            light(GfxLightType::OMNI),
+            light(GfxLightType::OMNI),
+            light(GfxLightType::OMNI),
+        ],
+        ..Default::default()
+    };
+
+    let lights = world.to_light_list();
+    assert_eq!(lights.len(), 6);
+    assert_eq!(world.directional_light_count(), 1);
+    assert_eq!(world.spot_light_count(), 2);
+    assert_eq!(world.omni_light_count(), 3);
+    assert_eq!(
+        world.directional_light_count() + world.spot_light_count() + world.omni_light_count(),
+        lights.len()
+    );
+
+    let spot = &lights[1];
+    assert_eq!(spot.type_, LightDescriptorType::Spot);
+    assert!((spot.cone_inner_angle.unwrap() - core::f32::consts::FRAC_PI_3).abs() < 1e-6);
+    assert!((spot.cone_outer_angle.unwrap() - core::f32::consts::FRAC_PI_2).abs() < 1e-6);
+
+    let omni = &lights[3];
+    assert_eq!(omni.type_, LightDescriptorType::Omni);
+    assert_eq!(omni.cone_inner_angle, None);
+    assert_eq!(omni.position.get(), [1.0, 2.0, 3.0]);
+}
@@ -0,0 +1,91 @@
+//! An allocator that counts what tracked threads allocate. Test binaries opt
+//! in with:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOC: CountingAlloc = CountingAlloc;
+//! ```
+//!
+//! Every measurement has counters of its own, and a thread counts towards
+//! the measurement whose token it holds, so tests running side by side don't
+//! reset or add to each other's counts.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicIsize, Ordering},
+};
+
+/// Counts the bytes allocated by threads that hold a measurement's token.
+pub struct CountingAlloc;
+
+#[derive(Default)]
+struct Counters {
+    current: AtomicIsize,
+    peak: AtomicIsize,
+}
+
+/// A measurement's token. The counters are leaked so that a thread that
+/// outlives the measurement never holds a dangling token.
+type Token = &'static Counters;
+
+thread_local! {
+    static TOKEN: Cell<Option<Token>> = const { Cell::new(None) };
+}
+
+fn token() -> Option<Token> {
+    TOKEN.try_with(Cell::get).ok().flatten()
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if let Some(counters) = token() {
+            let size = layout.size() as isize;
+            let current = counters.current.fetch_add(size, Ordering::SeqCst) + size;
+            counters.peak.fetch_max(current, Ordering::SeqCst);
+        }
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if let Some(counters) = token() {
+            counters
+                .current
+                .fetch_sub(layout.size() as isize, Ordering::SeqCst);
+        }
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+/// The most bytes allocated at once by this thread while running `f`, and
+/// with `rayon`, by the threads of the pool it's running in. The pool should
+/// be one of the test's own, as anything else running in it is counted too.
+pub fn peak_allocated(f: impl FnOnce()) -> usize {
+    let (_, counters) = track(f);
+    counters.peak.load(Ordering::SeqCst) as usize
+}
+
+/// What `f` returns, and how many of the bytes this thread allocated while
+/// running it are still allocated afterwards.
+pub fn retained_allocation<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let (t, counters) = track(f);
+    (t, counters.current.load(Ordering::SeqCst).max(0) as usize)
+}
+
+fn track<T>(f: impl FnOnce() -> T) -> (T, Token) {
+    let counters: Token = Box::leak(Box::default());
+    set_token(Some(counters));
+    let t = f();
+    set_token(None);
+    (t, counters)
+}
+
+fn set_token(token: Option<Token>) {
+    TOKEN.with(|t| t.set(token));
+    // Only a pool the caller is already running in is tracked, never the
+    // global one every test shares.
+    #[cfg(feature = "rayon")]
+    if rayon::current_thread_index().is_some() {
+        rayon::broadcast(|_| TOKEN.with(|t| t.set(token)));
+    }
+}
@@ -0,0 +1,88 @@
+//! Helpers shared between the integration tests.
+
+#![allow(dead_code)]
+
+pub mod alloc;
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[cfg(feature = "deserializer")]
+use t5_xfile_defs::{XFilePlatform, xasset::XAsset};
+#[cfg(feature = "deserializer")]
+use t5_xfile_deserializer::T5XFileDeserializerBuilder;
+
+/// A file in the system's temporary directory that's removed when dropped,
+/// whether or not the test using it passed.
+///
+/// The deserializer builders only read from a [`File`], so tests that build
+/// a fastfile or cache blob in memory go through one of these to read it
+/// back.
+pub struct TempFile(PathBuf);
+
+impl TempFile {
+    /// Reserves a unique path ending in `name` without creating the file.
+    pub fn empty(name: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+        let n = NEXT.fetch_add(1, Ordering::Relaxed);
+        Self(std::env::temp_dir().join(format!("t5xfile_{}_{n}_{name}", std::process::id())))
+    }
+
+    /// Writes `bytes` to a unique path ending in `name`.
+    pub fn new(name: &str, bytes: impl AsRef<[u8]>) -> Self {
+        let file = Self::empty(name);
+        std::fs::write(&file.0, bytes).unwrap();
+        file
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn open(&self) -> File {
+        File::open(&self.0).unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// `v` as little-endian bytes, for laying out hand-built blobs.
+pub fn u32s(v: &[u32]) -> Vec<u8> {
+    v.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Reads every asset out of `temp`, a Windows Fastfile, or a cache blob if
+/// `cache` is set.
+#[cfg(feature = "deserializer")]
+pub fn deserialize(temp: &TempFile, cache: bool) -> t5_xfile_defs::Result<Vec<XAsset>> {
+    deserialize_with(temp, cache, |builder| builder)
+}
+
+/// Like [`deserialize`], with `configure` applied to the builder first.
+#[cfg(feature = "deserializer")]
+pub fn deserialize_with(
+    temp: &TempFile,
+    cache: bool,
+    configure: impl for<'a> FnOnce(T5XFileDeserializerBuilder<'a>) -> T5XFileDeserializerBuilder<'a>,
+) -> t5_xfile_defs::Result<Vec<XAsset>> {
+    let mut file = temp.open();
+    let builder = if cache {
+        T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, false)
+    } else {
+        T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Windows, false)
+    };
+
+    configure(builder.with_silent(true))
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .and_then(|de| de.deserialize_remaining())
+}
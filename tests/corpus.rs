@@ -0,0 +1,81 @@
+//! Checks a local corpus of Fastfiles against `tests/corpus/summary.json`.
+//! See [`t5_xfile_deserializer::corpus`] for how to set it up.
+
+#![cfg(feature = "corpus")]
+
+use t5_xfile_deserializer::corpus::{
+    CorpusSummary, SUMMARY_PATH, check, corpus_dir, find_files, summarize,
+};
+
+#[test]
+#[ignore = "needs a directory of Fastfiles in T5_CORPUS_DIR"]
+fn corpus() {
+    let dir = corpus_dir().unwrap();
+    let files = find_files(&dir).unwrap();
+    assert!(!files.is_empty(), "no Fastfiles found in {}", dir.display());
+
+    let summary = CorpusSummary::load(SUMMARY_PATH).unwrap();
+
+    let mut failures = Vec::new();
+    for file in &files {
+        let problems = match summarize(file) {
+            Ok(current) => check(summary.files.get(&file.key), &current),
+            Err(p) => vec![format!("panicked: {p}")],
+        };
+
+        failures.extend(
+            problems
+                .into_iter()
+                .map(|p| format!("{} ({}): {p}", file.path.display(), file.key)),
+        );
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} problem(s) in {} file(s):\n{}",
+        failures.len(),
+        files.len(),
+        failures.join("\n")
+    );
+}
+
+/// Round-trips every deserializable asset in the corpus through
+/// [`export::bin`](t5_xfile_deserializer::export::bin).
+#[test]
+#[ignore = "needs a directory of Fastfiles in T5_CORPUS_DIR"]
+fn export_bin_round_trip() {
+    use t5_xfile_deserializer::{
+        T5XFileDeserializerBuilder,
+        export::bin::{read_assets, source_checksum, write_assets},
+    };
+
+    let dir = corpus_dir().unwrap();
+    for file in find_files(&dir).unwrap() {
+        let bytes = std::fs::read(&file.path).unwrap();
+        let mut f = std::fs::File::open(&file.path).unwrap();
+        let Ok(mut de) = T5XFileDeserializerBuilder::from_file(&mut f, file.platform, true)
+            .with_silent(true)
+            .build()
+            .and_then(|de| de.inflate())
+            .and_then(|de| de.no_cache())
+        else {
+            continue;
+        };
+
+        let mut assets = Vec::new();
+        while let Ok(Some(asset)) = de.deserialize_next() {
+            assets.push(asset);
+        }
+
+        let checksum = source_checksum(&bytes);
+        let mut written = Vec::new();
+        write_assets(&assets, checksum, &mut written).unwrap();
+        let (header, read) = read_assets(written.as_slice())
+            .unwrap_or_else(|e| panic!("{}: {e}", file.path.display()));
+        assert_eq!(header.source_checksum, checksum);
+
+        let mut rewritten = Vec::new();
+        write_assets(&read, checksum, &mut rewritten).unwrap();
+        assert!(written == rewritten, "{}", file.path.display());
+    }
+}
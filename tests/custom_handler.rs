@@ -0,0 +1,246 @@
+//! Checks that registered [`AssetHandler`]s read the asset types the library
+//! can't.
+
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use std::any::Any;
+
+use t5_xfile_defs::{
+    Error, ErrorKind, Result, XFilePlatform,
+    custom::{AssetHandler, AssetReader},
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+};
+use t5_xfile_deserializer::{T5XFileDeserializer, T5XFileDeserializerBuilder, export};
+
+use common::{TempFile, u32s};
+
+const RAWFILE: u32 = 0x24;
+const INLINE: u32 = 0xFFFFFFFF;
+
+/// Not a variant of [`XAssetType`].
+const UNKNOWN: u32 = 0x100;
+
+/// A made-up layout for [`XAssetType::AITYPE`]: an inline name pointer, a
+/// count, then the name and `count` `u32`s.
+#[derive(Debug, PartialEq)]
+struct AiType {
+    weights: Vec<u32>,
+}
+
+struct AiTypeHandler;
+
+impl AssetHandler for AiTypeHandler {
+    fn deserialize(
+        &self,
+        de: &mut dyn AssetReader,
+    ) -> Result<(String, Box<dyn Any + Send + Sync>)> {
+        assert_eq!(de.read_u32()?, INLINE);
+        let count = de.read_u32()?;
+        let name = de.read_c_string()?;
+        let weights = (0..count).map(|_| de.read_u32()).collect::<Result<_>>()?;
+        Ok((name, Box::new(AiType { weights })))
+    }
+}
+
+/// Fails the test if it's ever used.
+struct Unreachable;
+
+impl AssetHandler for Unreachable {
+    fn deserialize(&self, _: &mut dyn AssetReader) -> Result<(String, Box<dyn Any + Send + Sync>)> {
+        panic!("a handler was used for a built-in type");
+    }
+}
+
+/// An inflated blob holding an asset of type `custom_type` in
+/// [`AiTypeHandler`]'s layout, followed by a raw file.
+fn blob(custom_type: u32) -> Vec<u8> {
+    let mut data = u32s(&[0, 0, 2, INLINE, custom_type, INLINE, RAWFILE, INLINE]);
+    data.extend(u32s(&[INLINE, 3]));
+    data.extend(b"ai/grunt\0");
+    data.extend(u32s(&[10, 20, 30]));
+    data.extend(u32s(&[INLINE, 4, INLINE]));
+    data.extend(b"after.txt\0text\0");
+
+    let mut blob = u32s(&[data.len() as u32, 0, 0, 0, 0, 0, 0, 0, 0]);
+    blob.extend(data);
+    blob
+}
+
+fn deserialize(
+    name: &str,
+    custom_type: u32,
+    register: impl FnOnce(&mut T5XFileDeserializer),
+) -> Result<Vec<XAsset>> {
+    let temp = TempFile::new(&format!("custom_{name}.cache"), blob(custom_type));
+
+    let mut file = temp.open();
+
+    T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .and_then(|mut de| {
+            register(&mut de);
+            de.deserialize_remaining()
+        })
+}
+
+fn check_raw_file(asset: &XAsset) {
+    let XAsset::PC(XAssetGeneric::RawFile(Some(rawfile))) = asset else {
+        panic!("{asset:?}");
+    };
+    assert_eq!(rawfile.name.get(), "after.txt");
+    assert_eq!(rawfile.as_str(), Some("text"));
+}
+
+#[test]
+fn handler_reads_unused_type() {
+    let assets = deserialize("unused", XAssetType::AITYPE as _, |de| {
+        de.register_handler(XAssetType::AITYPE as _, Box::new(AiTypeHandler));
+    })
+    .unwrap();
+    assert_eq!(assets.len(), 2);
+
+    let custom = &assets[0];
+    assert!(custom.is_some());
+    assert_eq!(custom.name(), Some("ai/grunt"));
+    assert_eq!(custom.asset_type(), XAssetType::AITYPE);
+    assert_eq!(custom.xasset_raw().asset_type, XAssetType::AITYPE as u32);
+
+    let XAsset::PC(asset @ XAssetGeneric::Custom { type_id, data, .. }) = custom else {
+        panic!("{custom:?}");
+    };
+    assert_eq!(*type_id, XAssetType::AITYPE as u32);
+    assert_eq!(asset.content_type_id(), std::any::TypeId::of::<AiType>());
+    assert_eq!(
+        data.downcast_ref::<AiType>(),
+        Some(&AiType {
+            weights: vec![10, 20, 30]
+        })
+    );
+
+    // The handler read exactly the asset's data.
+    check_raw_file(&assets[1]);
+}
+
+#[test]
+fn handler_reads_unknown_type() {
+    let assets = deserialize("unknown", UNKNOWN, |de| {
+        de.register_handler(UNKNOWN, Box::new(AiTypeHandler));
+    })
+    .unwrap();
+
+    let XAsset::PC(XAssetGeneric::Custom { type_id, name, .. }) = &assets[0] else {
+        panic!("{:?}", assets[0]);
+    };
+    assert_eq!((*type_id, name.as_str()), (UNKNOWN, "ai/grunt"));
+    assert_eq!(assets[0].raw_asset_type(), UNKNOWN);
+    assert_eq!(assets[0].xasset_raw().asset_type, UNKNOWN);
+    check_raw_file(&assets[1]);
+}
+
+#[test]
+fn without_handler() {
+    let e = deserialize("no_handler_unused", XAssetType::AITYPE as _, |_| {}).unwrap_err();
+    assert!(
+        matches!(e.kind(), ErrorKind::UnusedXAssetType(XAssetType::AITYPE)),
+        "{e}"
+    );
+
+    let e = deserialize("no_handler_unknown", UNKNOWN, |de| {
+        de.register_handler(XAssetType::AITYPE as _, Box::new(AiTypeHandler));
+    })
+    .unwrap_err();
+    assert!(
+        matches!(e.kind(), ErrorKind::InvalidXAssetType(UNKNOWN)),
+        "{e}"
+    );
+}
+
+#[test]
+fn built_in_types_win() {
+    let assets = deserialize("built_in", XAssetType::AITYPE as _, |de| {
+        de.register_handler(XAssetType::AITYPE as _, Box::new(AiTypeHandler));
+        de.register_handler(RAWFILE, Box::new(Unreachable));
+    })
+    .unwrap();
+    check_raw_file(&assets[1]);
+}
+
+#[test]
+fn export_stub() {
+    let assets = deserialize("export", XAssetType::AITYPE as _, |de| {
+        de.register_handler(XAssetType::AITYPE as _, Box::new(AiTypeHandler));
+    })
+    .unwrap();
+
+    let mut exported = Vec::new();
+    export::bin::write_assets(&assets, 0, &mut exported).unwrap();
+    let (_, read) = export::bin::read_assets(&exported[..]).unwrap();
+
+    // Only the type and name survive.
+    let XAsset::PC(XAssetGeneric::Custom {
+        type_id,
+        name,
+        data,
+    }) = &read[0]
+    else {
+        panic!("{:?}", read[0]);
+    };
+    assert_eq!(
+        (*type_id, name.as_str()),
+        (XAssetType::AITYPE as u32, "ai/grunt")
+    );
+    assert!(data.downcast_ref::<()>().is_some());
+    check_raw_file(&read[1]);
+}
+
+/// Reads the asset like [`AiTypeHandler`], then rejects it.
+struct RejectingHandler;
+
+impl AssetHandler for RejectingHandler {
+    fn deserialize(
+        &self,
+        de: &mut dyn AssetReader,
+    ) -> Result<(String, Box<dyn Any + Send + Sync>)> {
+        AiTypeHandler.deserialize(de)?;
+        Err(Error::new(
+            "RejectingHandler".to_string(),
+            ErrorKind::BrokenInvariant("rejected".to_string()),
+        ))
+    }
+}
+
+#[test]
+fn index_after_failure() {
+    let temp = TempFile::new(
+        "custom_index_after_failure.cache",
+        blob(XAssetType::AITYPE as _),
+    );
+    let mut file = temp.open();
+
+    let mut de =
+        T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .build()
+            .and_then(|de| de.inflate())
+            .and_then(|de| de.no_cache())
+            .unwrap();
+    de.register_handler(XAssetType::AITYPE as _, Box::new(RejectingHandler));
+
+    let e = de.deserialize_next_loaded().unwrap_err();
+    assert_eq!(e.asset().map(|a| a.index), Some(0), "{e}");
+
+    // The failed asset still takes up its place in the asset list.
+    let loaded = de.deserialize_next_loaded().unwrap().unwrap();
+    check_raw_file(&loaded.asset);
+    assert_eq!(loaded.provenance.index, 1);
+    assert_eq!(de.asset_span(0), None);
+    assert_eq!(de.asset_span(1), Some(loaded.provenance.span));
+    let e = de.asset_bytes(0).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::NoAssetSpan(0)), "{e}");
+    assert!(de.asset_bytes(1).unwrap().ends_with(b"after.txt\0text\0"));
+}
@@ -0,0 +1,86 @@
+//! Checks [`DdlRoot`]'s lookups and [`DdlStructDef`]'s member offsets.
+
+use t5_xfile_defs::{
+    XString,
+    ddl::{DdlDef, DdlEnumDef, DdlMemberDef, DdlRoot, DdlStructDef},
+};
+
+fn member(name: &str, offset: i32, size: i32) -> DdlMemberDef {
+    DdlMemberDef {
+        name: XString::from(name.to_string()),
+        offset,
+        size,
+        ..Default::default()
+    }
+}
+
+fn root() -> DdlRoot {
+    let player = DdlStructDef {
+        name: XString::from("playerstats".to_string()),
+        size: 1 + 32 + 320,
+        members: vec![
+            member("prestige_reset", 0, 1),
+            member("rank", 1, 32),
+            member("kills", 33, 320),
+        ],
+    };
+    let weapon = DdlStructDef {
+        name: XString::from("weaponstats".to_string()),
+        size: 16,
+        members: vec![member("used", 0, 16)],
+    };
+    let class = DdlEnumDef {
+        name: XString::from("class_t".to_string()),
+        members: vec![XString::from("CLASS_ASSAULT".to_string())],
+    };
+
+    DdlRoot {
+        name: XString::from("mp/playerdata.ddl".to_string()),
+        ddl_defs: vec![
+            Box::new(DdlDef {
+                version: 2,
+                struct_list: vec![player.clone()],
+                enum_list: vec![class],
+                ..Default::default()
+            }),
+            Box::new(DdlDef {
+                version: 1,
+                struct_list: vec![player, weapon],
+                ..Default::default()
+            }),
+        ],
+    }
+}
+
+#[test]
+fn lookups() {
+    let root = root();
+
+    assert_eq!(root.all_struct_names(), ["playerstats", "weaponstats"]);
+    assert_eq!(root.all_enum_names(), ["class_t"]);
+    assert_eq!(root.struct_by_name("weaponstats").unwrap().size, 16);
+    assert!(root.struct_by_name("missing").is_none());
+    assert_eq!(root.enum_by_name("class_t").unwrap().members.len(), 1);
+
+    let player = root.struct_by_name("playerstats").unwrap();
+    assert_eq!(player.member_by_name("rank").unwrap().offset_bits(), 1);
+    assert!(player.member_by_name("deaths").is_none());
+}
+
+#[test]
+fn member_offsets() {
+    let root = root();
+    let player = root.struct_by_name("playerstats").unwrap();
+
+    for (i, m) in player.members.iter().enumerate() {
+        assert_eq!(player.computed_offset_bits(i), Some(m.offset_bits()));
+    }
+    assert_eq!(player.computed_offset_bits(player.members.len()), None);
+
+    let total = player
+        .members
+        .iter()
+        .map(DdlMemberDef::size_bits)
+        .sum::<usize>();
+    assert_eq!(total, player.size as usize);
+}
@@ -0,0 +1,251 @@
+//! Checks that the types ported to `#[derive(XFileConvert)]` serialize to
+//! exactly the bytes their hand-written impls used to produce, or, where
+//! those impls were broken, that they're read back unchanged.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    com_world::{
+        ComBurnableCell, ComBurnableHeader, ComBurnableSample, ComPrimaryLight, ComWaterCell,
+        ComWaterHeader, ComWorld,
+    },
+    common::{Vec3, Vec4},
+    font::{Font, Glyph},
+    weapon::{FlameTable, WeaponDef, WeaponVariantDef},
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, T5XFileSerializerBuilder};
+
+use common::TempFile;
+
+/// A pointer to data that follows.
+const FOLLOWS: u32 = 0xFFFF_FFFF;
+
+/// Builds the expected bytes of an asset, little-endian like Windows.
+#[derive(Default)]
+struct Bytes(Vec<u8>);
+
+impl Bytes {
+    fn u8(&mut self, v: u8) -> &mut Self {
+        self.0.push(v);
+        self
+    }
+
+    fn u16(&mut self, v: u16) -> &mut Self {
+        self.0.extend(v.to_le_bytes());
+        self
+    }
+
+    fn u32(&mut self, v: u32) -> &mut Self {
+        self.0.extend(v.to_le_bytes());
+        self
+    }
+
+    fn f32s(&mut self, v: &[f32]) -> &mut Self {
+        for f in v {
+            self.0.extend(f.to_le_bytes());
+        }
+        self
+    }
+
+    fn string(&mut self, s: &str) -> &mut Self {
+        self.0.extend(s.bytes());
+        self.0.push(0);
+        self
+    }
+}
+
+/// The blob of a Windows Fastfile holding just `asset`.
+fn blob(name: &str, asset: XAssetGeneric) -> Vec<u8> {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>([XAsset::PC(asset)].into_iter())
+        .unwrap();
+
+    let temp = TempFile::new(&format!("derive_layout_{name}.ff"), ser.deflate().unwrap());
+    let mut file = temp.open();
+
+    T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .unwrap()
+        .blob()
+        .to_vec()
+}
+
+/// Serializes `asset` into a Windows Fastfile and reads it back.
+fn roundtrip(name: &str, asset: XAssetGeneric) -> Vec<XAsset> {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>([XAsset::PC(asset)].into_iter())
+        .unwrap();
+
+    let temp = TempFile::new(&format!("derive_layout_{name}.ff"), ser.deflate().unwrap());
+    common::deserialize(&temp, false).unwrap()
+}
+
+/// Assets are serialized last, so the blob ends with them.
+fn assert_ends_with(blob: &[u8], expected: &Bytes) {
+    let expected = &expected.0;
+    assert!(blob.len() >= expected.len());
+    assert_eq!(&blob[blob.len() - expected.len()..], expected.as_slice());
+}
+
+#[test]
+fn com_world() {
+    let world = ComWorld {
+        name: XString::from("maps/mp/mp_test.d3dbsp"),
+        is_in_use: true,
+        primary_lights: vec![ComPrimaryLight {
+            type_: 2,
+            can_use_shadow_map: true,
+            exponent: 3,
+            priority: 4,
+            cull_dist: -5,
+            color: Vec3::from([1.0, 0.5, 0.25]),
+            radius: 300.0,
+            diffuse_color: Vec4::from([0.1, 0.2, 0.3, 0.4]),
+            cookie_control_2: Vec4::from([4.0; 4]),
+            def_name: XString::from("light_def"),
+            ..Default::default()
+        }],
+        water_header: ComWaterHeader {
+            minx: -1,
+            miny: -2,
+            maxx: 3,
+            maxy: 4,
+        },
+        water_cells: vec![ComWaterCell {
+            waterheight: 7,
+            flooroffset: 8,
+            shoredist: 9,
+            color: [1, 2, 3, 4],
+        }],
+        burnable_header: ComBurnableHeader {
+            minx: 5,
+            miny: 6,
+            maxx: 7,
+            maxy: 8,
+        },
+        burnable_cells: vec![
+            ComBurnableCell {
+                x: 10,
+                y: 11,
+                data: None,
+            },
+            ComBurnableCell {
+                x: 13,
+                y: 14,
+                data: Some(Box::new([ComBurnableSample { state: 12 }; 32])),
+            },
+        ],
+    };
+
+    let read = roundtrip(
+        "com_world",
+        XAssetGeneric::ComWorld(Some(Box::new(world.clone()))),
+    );
+    let [XAsset::PC(XAssetGeneric::ComWorld(Some(read)))] = read.as_slice() else {
+        panic!("expected one com world, got {read:?}");
+    };
+    assert_eq!(format!("{read:?}"), format!("{world:?}"));
+}
+
+#[test]
+fn font() {
+    let glyph = |letter: u16| {
+        let mut g = Glyph::default();
+        g.letter = letter;
+        g.x0 = -1;
+        g.y0 = 2;
+        g.dx = 10;
+        g.pixel_width = 11;
+        g.pixel_height = 12;
+        g.s0 = 0.25;
+        g.t1 = 0.75;
+        g
+    };
+    let font = Font {
+        font_name: XString::from("fonts/test"),
+        pixel_height: 16,
+        material: None,
+        glow_material: None,
+        glyphs: vec![glyph(b'A' as _), glyph(b'B' as _)],
+    };
+
+    let mut expected = Bytes::default();
+    expected
+        .u32(FOLLOWS)
+        .u32(16)
+        .u32(2)
+        .u32(0)
+        .u32(0)
+        .u32(FOLLOWS)
+        .string("fonts/test");
+    for letter in [b'A', b'B'] {
+        expected
+            .u16(letter as _)
+            .u8(-1i8 as u8)
+            .u8(2)
+            .u8(10)
+            .u8(11)
+            .u8(12)
+            .u8(0)
+            .f32s(&[0.25, 0.0, 0.0, 0.75]);
+    }
+
+    let blob = blob("font", XAssetGeneric::Font(Some(Box::new(font))));
+    assert_ends_with(&blob, &expected);
+}
+
+#[test]
+fn flame_table() {
+    let table = FlameTable {
+        flame_var_stream_chunk_gravity_start: 1.5,
+        flame_var_collision_volume_scale: 2.5,
+        name: XString::from("flamethrower_fp"),
+        flame_ignite_sound: XString::from("flame_ignite"),
+        flame_cooldown_sound: XString::from("flame_cooldown"),
+        ..Default::default()
+    };
+    let weapon = WeaponVariantDef {
+        internal_name: XString::from("ft_mp"),
+        weap_def: Some(Box::new(WeaponDef {
+            flame_table_third_person_ptr: Some(Box::new(table)),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+
+    // The table is the last thing the weapon points to, so it ends the
+    // blob: the floats, the name, the eight materials, the four sounds,
+    // then the strings.
+    let mut expected = Bytes::default();
+    expected
+        .f32s(&[1.5])
+        .f32s(&[0.0; 104])
+        .f32s(&[2.5])
+        .u32(FOLLOWS);
+    for _ in 0..8 {
+        expected.u32(0);
+    }
+    expected
+        .u32(0)
+        .u32(FOLLOWS)
+        .u32(0)
+        .u32(FOLLOWS)
+        .string("flamethrower_fp")
+        .string("flame_ignite")
+        .string("flame_cooldown");
+
+    let blob = blob("flame_table", XAssetGeneric::Weapon(Some(Box::new(weapon))));
+    assert_ends_with(&blob, &expected);
+}
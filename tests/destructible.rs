@@ -0,0 +1,175 @@
+//! Checks the [`DestructiblePiece`] stage accessors on a hand-built
+//! destructible car.
+
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    common::{Vec3, Vec4},
+    destructible::{DestructibleDef, DestructiblePiece, DestructibleStage},
+    fx::{FxEffectDef, FxEffectDefFlags},
+    xmodel::XModel,
+};
+
+fn model(name: &str) -> Option<Box<XModel>> {
+    Some(Box::new(XModel {
+        name: XString::from(name),
+        ..Default::default()
+    }))
+}
+
+fn effect(name: &str) -> Option<Box<FxEffectDef>> {
+    Some(Box::new(FxEffectDef {
+        name: XString::from(name),
+        flags: FxEffectDefFlags::empty(),
+        ef_priority: 0,
+        total_size: 0,
+        msec_looping_life: 0,
+        elem_def_count_looping: 0,
+        elem_def_count_one_shot: 0,
+        elem_def_count_emission: 0,
+        elem_defs: Vec::new(),
+        bounding_box_dim: Vec3::default(),
+        bounding_sphere: Vec4::default(),
+    }))
+}
+
+fn unused() -> DestructibleStage {
+    DestructibleStage {
+        show_bone: XString::default(),
+        break_health: 0.0,
+        max_time: 0.0,
+        flags: 0,
+        break_effect: None,
+        break_sound: XString::default(),
+        break_notify: XString::default(),
+        loop_sound: XString::default(),
+        spawn_model: [None, None, None],
+        phys_preset: None,
+    }
+}
+
+fn piece(stages: [DestructibleStage; 5]) -> DestructiblePiece {
+    DestructiblePiece {
+        stages,
+        parent_piece: 0xFF,
+        parent_damage_percent: 0.0,
+        bullet_damage_scale: 1.0,
+        explosive_damage_scale: 1.0,
+        melee_damage_scale: 1.0,
+        impact_damage_scael: 1.0,
+        entity_damage_transfer: 0.0,
+        phys_constraints: None,
+        health: 1000,
+        damage_sound: XString::default(),
+        burn_effect: None,
+        burn_sound: XString::default(),
+        enable_label: 0,
+        hide_bones: [-1; 5],
+    }
+}
+
+/// The body goes intact -> dented -> burnt out. The door just falls off.
+fn car() -> DestructibleDef {
+    let body = piece([
+        DestructibleStage {
+            show_bone: XString::from("tag_body"),
+            ..unused()
+        },
+        DestructibleStage {
+            show_bone: XString::from("tag_body_d1"),
+            break_health: 600.0,
+            break_sound: XString::from("car_dent"),
+            spawn_model: [model("car_glass_shard"), None, model("car_mirror")],
+            ..unused()
+        },
+        DestructibleStage {
+            break_health: 0.0,
+            break_effect: effect("destructibles/car_explode"),
+            spawn_model: [None, model("car_wheel"), None],
+            ..unused()
+        },
+        unused(),
+        unused(),
+    ]);
+    let door = piece([
+        DestructibleStage {
+            show_bone: XString::from("tag_door"),
+            ..unused()
+        },
+        unused(),
+        unused(),
+        unused(),
+        DestructibleStage {
+            break_health: 100.0,
+            spawn_model: [model("car_door"), None, None],
+            ..unused()
+        },
+    ]);
+
+    DestructibleDef {
+        name: XString::from("dest_car"),
+        model: model("car"),
+        pristine_model: None,
+        pieces: vec![body, door],
+        client_only: false,
+    }
+}
+
+#[test]
+fn stages() {
+    let car = car();
+    let body = &car.pieces[0];
+
+    assert_eq!(body.stage_count(), 3);
+    assert_eq!(body.stage_at(0).unwrap().show_bone.get(), "tag_body");
+    // A stage that only spawns something and breaks at 0 health still
+    // counts.
+    assert!(body.stage_at(2).is_ok());
+    let e = body.stage_at(3).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+
+    assert!(!body.is_final_stage(0));
+    assert!(!body.is_final_stage(1));
+    assert!(body.is_final_stage(2));
+    assert!(!body.is_final_stage(3));
+
+    // Unused stages in the middle don't end the piece early.
+    let door = &car.pieces[1];
+    assert_eq!(door.stage_count(), 5);
+    assert!(door.is_final_stage(4));
+
+    // A piece with nothing set still has its intact stage.
+    let blank = piece([unused(), unused(), unused(), unused(), unused()]);
+    assert_eq!(blank.stage_count(), 1);
+    assert!(blank.stage_at(0).is_ok());
+    assert!(blank.is_final_stage(0));
+}
+
+#[test]
+fn stage_contents() {
+    let car = car();
+    let dented = car.pieces[0].stage_at(1).unwrap();
+
+    assert_eq!(dented.model().unwrap().name.get(), "car_glass_shard");
+    assert_eq!(
+        dented.models().map(|m| m.name.get()).collect::<Vec<_>>(),
+        ["car_glass_shard", "car_mirror"]
+    );
+    assert_eq!(dented.destroy_sound(), "car_dent");
+    assert_eq!(dented.health_threshold(), 600.0);
+    assert!(dented.rubble_fx().is_none());
+
+    let burnt = car.pieces[0].stage_at(2).unwrap();
+    // The first model slot being empty doesn't hide the others.
+    assert_eq!(burnt.model().unwrap().name.get(), "car_wheel");
+    assert_eq!(
+        burnt.rubble_fx().unwrap().name.get(),
+        "destructibles/car_explode"
+    );
+    assert_eq!(burnt.destroy_sound(), "");
+
+    let intact = car.pieces[0].stage_at(0).unwrap();
+    assert!(intact.model().is_none());
+    assert_eq!(intact.models().count(), 0);
+
+    assert_eq!(car.total_model_count(), 4);
+}
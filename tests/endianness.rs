@@ -0,0 +1,119 @@
+//! Checks that a blob read with the wrong endianness is reported as
+//! [`ErrorKind::ProbableEndianMismatch`] instead of failing somewhere in
+//! the assets.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform, XString,
+    misc::RawFile,
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::{HeaderLimits, T5XFileDeserializerBuilder, T5XFileSerializerBuilder};
+
+use common::TempFile;
+
+/// The size of the [`XFile`] at the start of the blob.
+///
+/// [`XFile`]: t5_xfile_defs::XFile
+const XFILE_SIZE: usize = 36;
+
+/// The inflated blob of a little-endian Fastfile.
+fn blob() -> Vec<u8> {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>(
+        [RawFile {
+            name: XString::from("maps/mp/endian.gsc"),
+            buffer: b"main() {}\0".to_vec(),
+        }]
+        .into_iter()
+        .map(|f| XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(f))))),
+    )
+    .unwrap();
+
+    let temp = TempFile::new("endianness_blob.ff", ser.deflate().unwrap());
+    let mut file = temp.open();
+
+    T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .unwrap()
+        .blob()
+        .to_vec()
+}
+
+fn deserialize(
+    name: &str,
+    blob: &[u8],
+    platform: XFilePlatform,
+    limits: HeaderLimits,
+) -> t5_xfile_defs::Result<Vec<XAsset>> {
+    let temp = TempFile::new(&format!("endianness_{name}"), blob);
+    let mut file = temp.open();
+
+    T5XFileDeserializerBuilder::from_cache_file(&mut file, platform, true)
+        .with_silent(true)
+        .with_header_limits(limits)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .and_then(|de| de.deserialize_remaining())
+}
+
+#[test]
+fn xfile_mismatch() {
+    let blob = blob();
+    for platform in [XFilePlatform::Xbox360, XFilePlatform::PS3] {
+        let e = deserialize("xfile", &blob, platform, HeaderLimits::default()).unwrap_err();
+        assert!(
+            matches!(e.kind(), ErrorKind::ProbableEndianMismatch(p) if *p == platform),
+            "{e}"
+        );
+        assert_eq!(e.off(), Some(0));
+    }
+
+    // Read the right way round, it's fine.
+    let assets = deserialize("ok", &blob, XFilePlatform::Windows, HeaderLimits::default());
+    assert_eq!(assets.unwrap().len(), 1);
+}
+
+#[test]
+fn asset_list_mismatch() {
+    // Swapping just the XFile makes it read correctly as big-endian, so
+    // it's the asset list that gives the platform away.
+    let mut blob = blob();
+    for word in blob[..XFILE_SIZE].chunks_mut(4) {
+        word.reverse();
+    }
+
+    let e = deserialize(
+        "list",
+        &blob,
+        XFilePlatform::Xbox360,
+        HeaderLimits::default(),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(
+            e.kind(),
+            ErrorKind::ProbableEndianMismatch(XFilePlatform::Xbox360)
+        ),
+        "{e}"
+    );
+    assert_eq!(e.off(), Some(XFILE_SIZE as _));
+}
+
+#[test]
+fn implausible_either_way() {
+    // Headers that don't pass the checks with either endianness aren't
+    // blamed on the platform.
+    let limits = HeaderLimits { max_size: 0 };
+    let assets = deserialize("limits", &blob(), XFilePlatform::Windows, limits);
+    assert_eq!(assets.unwrap().len(), 1);
+}
@@ -0,0 +1,80 @@
+//! Checks the [`Display`](core::fmt::Display) output of an error returned
+//! while deserializing an asset.
+
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use t5_xfile_defs::{Error, ErrorKind, xasset::XAssetType};
+use t5_xfile_deserializer::ERROR_EXCERPT_LEN;
+
+use common::{TempFile, u32s};
+
+const WEAPON: u32 = 0x18;
+const WEAPON_VARIANT_DEF_SIZE: usize = 228;
+const DPAD_ICON_RATIO_OFFSET: usize = 152;
+const INLINE: u32 = 0xFFFFFFFF;
+
+/// An inflated blob holding one weapon whose `dpad_icon_ratio` is 47, which
+/// isn't a valid `WeaponIconRatioType`.
+fn blob() -> Vec<u8> {
+    let mut data = u32s(&[0, 0, 1, INLINE, WEAPON, INLINE]);
+
+    let mut variant = vec![0u8; WEAPON_VARIANT_DEF_SIZE];
+    variant[0..4].copy_from_slice(&INLINE.to_le_bytes());
+    variant[DPAD_ICON_RATIO_OFFSET..DPAD_ICON_RATIO_OFFSET + 4]
+        .copy_from_slice(&47u32.to_le_bytes());
+    data.extend(variant);
+    data.extend(b"test_weapon\0");
+
+    let mut blob = u32s(&[data.len() as u32, 0, 0, 0, 0, 0, 0, 0, 0]);
+    blob.extend(data);
+    blob
+}
+
+fn deserialize(verbose_errors: bool) -> Error {
+    let temp = TempFile::new(&format!("error_display_{verbose_errors}.cache"), blob());
+
+    let assets = common::deserialize_with(&temp, true, |b| b.with_verbose_errors(verbose_errors));
+    assets.unwrap_err()
+}
+
+#[test]
+fn message() {
+    let e = deserialize(false);
+    assert!(matches!(e.kind(), ErrorKind::BadFromPrimitive(47)));
+
+    let asset = e.asset().unwrap();
+    assert_eq!(asset.asset_type, XAssetType::WEAPON);
+    assert_eq!((asset.index, asset.count), (0, 1));
+    assert!(e.excerpt().is_none());
+
+    // The source location changes whenever weapon.rs does, so only check
+    // that it's there.
+    let message = e.to_string();
+    let (message, location) = message.rsplit_once(" (").unwrap();
+    assert_eq!(
+        message,
+        "weapon (asset 0/1): bad enum value 47 at blob offset 0x0000012C"
+    );
+    assert!(location.starts_with("t5-xfile-defs/src/weapon.rs:"));
+}
+
+#[test]
+fn hexdump() {
+    let e = deserialize(true);
+
+    let excerpt = e.excerpt().unwrap();
+    let blob = blob();
+    let start = 0x12C - ERROR_EXCERPT_LEN / 2;
+    assert_eq!(excerpt.start as usize, start);
+    assert_eq!(excerpt.bytes, blob[start..]);
+
+    // The name is the last thing in the blob, so the excerpt is cut short.
+    let message = e.to_string();
+    let dump = message.lines().skip(1).collect::<Vec<_>>();
+    assert_eq!(
+        dump,
+        ["0000011C: 00 00 00 00 74 65 73 74 5F 77 65 61 70 6F 6E 00 |....test_weapon.|"]
+    );
+}
@@ -0,0 +1,148 @@
+//! Checks that [`t5_xfile_deserializer::export::bin`] round-trips assets and
+//! refuses files it can't read.
+
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    misc::{LocalizeEntry, RawFile},
+    techset::{GfxImage, Material, MaterialInfo},
+    xasset::{XAsset, XAssetGeneric},
+    xmodel::XModel,
+};
+use t5_xfile_deserializer::export::bin::{
+    MAGIC, SCHEMA_VERSION, read_assets, read_header, source_checksum, write_assets,
+};
+
+fn assets() -> Vec<XAsset> {
+    vec![
+        XAsset::PC(XAssetGeneric::Material(Some(Box::new(Material {
+            info: MaterialInfo {
+                name: XString::from("mc/mtl_crate".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        })))),
+        XAsset::PC(XAssetGeneric::XModel(Some(Box::new(XModel {
+            name: XString::from("p_crate".to_string()),
+            ..Default::default()
+        })))),
+        XAsset::PC(XAssetGeneric::XModel(None)),
+        XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+            name: XString::from("maps/mp/crate.gsc".to_string()),
+            buffer: b"main() {}\0".to_vec(),
+        })))),
+        XAsset::Console(XAssetGeneric::LocalizeEntry(Some(Box::new(
+            LocalizeEntry::new("MENU_CRATE", "Crate"),
+        )))),
+        XAsset::Console(XAssetGeneric::Image(Some(Box::new(GfxImage {
+            width: 64,
+            height: 32,
+            ..Default::default()
+        })))),
+    ]
+}
+
+fn write(assets: &[XAsset], checksum: u64) -> Vec<u8> {
+    let mut buf = Vec::new();
+    write_assets(assets, checksum, &mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn round_trip() {
+    let checksum = source_checksum(b"not really a fastfile");
+    let bytes = write(&assets(), checksum);
+    assert_eq!(bytes[..4], MAGIC);
+
+    let (header, read) = read_assets(bytes.as_slice()).unwrap();
+    assert_eq!(header.schema_version, SCHEMA_VERSION);
+    assert_eq!(header.source_checksum, checksum);
+    assert_eq!(read_header(bytes.as_slice()).unwrap(), header);
+
+    // Writing what was read gives back the same bytes.
+    assert_eq!(write(&read, checksum), bytes);
+
+    let summary = read
+        .iter()
+        .map(|a| (a.asset_type(), a.name()))
+        .collect::<Vec<_>>();
+    let expected = assets();
+    let expected = expected
+        .iter()
+        .map(|a| (a.asset_type(), a.name()))
+        .collect::<Vec<_>>();
+    assert_eq!(summary, expected);
+    assert!(matches!(read[4], XAsset::Console(_)));
+    let XAsset::PC(XAssetGeneric::RawFile(Some(raw_file))) = &read[3] else {
+        panic!("{:?}", read[3]);
+    };
+    assert_eq!(raw_file.buffer, b"main() {}\0");
+}
+
+#[test]
+fn wrong_schema_version() {
+    let mut bytes = write(&assets(), 0);
+    bytes[4..8].copy_from_slice(&(SCHEMA_VERSION + 1).to_le_bytes());
+
+    let e = read_assets(bytes.as_slice()).unwrap_err();
+    assert!(
+        matches!(
+            e.kind(),
+            ErrorKind::WrongSchemaVersion { expected, found }
+                if *expected == SCHEMA_VERSION && *found == SCHEMA_VERSION + 1
+        ),
+        "{e:?}"
+    );
+}
+
+#[test]
+fn bad_input() {
+    let mut bytes = write(&assets(), 0);
+    bytes[0] = b'X';
+    assert!(matches!(
+        read_assets(bytes.as_slice()).unwrap_err().kind(),
+        ErrorKind::BadHeaderMagic(m) if m == "X5XB"
+    ));
+
+    let bytes = write(&assets(), 0);
+    assert!(read_assets(&bytes[..bytes.len() - 1]).is_err());
+    assert!(read_assets(&bytes[..10]).is_err());
+}
+
+/// Compares against JSON on a larger list of assets. Run with
+/// `cargo test --release --features json --test export_bin -- --ignored --nocapture`.
+#[cfg(feature = "json")]
+#[test]
+#[ignore = "benchmark"]
+fn bench_against_json() {
+    use std::time::Instant;
+
+    let assets = assets()
+        .into_iter()
+        .cycle()
+        .take(60_000)
+        .collect::<Vec<_>>();
+
+    let start = Instant::now();
+    let bin = write(&assets, 0);
+    let bin_write = start.elapsed();
+    let start = Instant::now();
+    read_assets(bin.as_slice()).unwrap();
+    let bin_read = start.elapsed();
+
+    let start = Instant::now();
+    let json = serde_json::to_vec(&assets).unwrap();
+    let json_write = start.elapsed();
+    let start = Instant::now();
+    serde_json::from_slice::<Vec<XAsset>>(&json).unwrap();
+    let json_read = start.elapsed();
+
+    println!(
+        "bin:  {} bytes, write {bin_write:?}, read {bin_read:?}",
+        bin.len()
+    );
+    println!(
+        "json: {} bytes, write {json_write:?}, read {json_read:?}",
+        json.len()
+    );
+    assert!(bin.len() < json.len());
+}
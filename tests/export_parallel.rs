@@ -0,0 +1,122 @@
+//! Checks that exports are written the same whether or not the `rayon`
+//! feature serializes assets in parallel.
+//!
+//! Both ways are checked against output built independently, asset by
+//! asset, with `serde_json` and `bincode`. With `rayon`, thread pools of
+//! different sizes are checked against each other too, so that batches of
+//! different sizes, including ones cut short at the end, are covered.
+
+#![cfg(feature = "json")]
+
+use t5_xfile_defs::{
+    XString,
+    misc::{LocalizeEntry, RawFile},
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::export::{
+    bin::{self, MAGIC, SCHEMA_VERSION},
+    json::{BlobEncoding, JsonLayout, JsonOptions},
+    to_json_writer,
+};
+
+/// Assets of very different sizes, so that they finish out of order when
+/// serialized in parallel.
+fn assets() -> Vec<XAsset> {
+    (0..101)
+        .map(|i| {
+            XAsset::PC(match i % 3 {
+                0 => XAssetGeneric::RawFile(Some(Box::new(RawFile {
+                    name: XString::from(format!("parallel/{i}.gsc")),
+                    buffer: vec![i as u8; (100 - i) * 97],
+                }))),
+                1 => XAssetGeneric::LocalizeEntry(Some(Box::new(LocalizeEntry::new(
+                    format!("PARALLEL_{i}"),
+                    "\"quoted\"\n".repeat(i),
+                )))),
+                _ => XAssetGeneric::RawFile(None),
+            })
+        })
+        .collect()
+}
+
+fn json(assets: &[XAsset], layout: JsonLayout, blobs: BlobEncoding) -> Vec<u8> {
+    let mut out = Vec::new();
+    to_json_writer(
+        assets,
+        &mut out,
+        JsonOptions {
+            layout,
+            blobs,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    out
+}
+
+fn bin(assets: &[XAsset]) -> Vec<u8> {
+    let mut out = Vec::new();
+    bin::write_assets(assets, 7, &mut out).unwrap();
+    out
+}
+
+#[test]
+fn matches_asset_by_asset() {
+    let assets = assets();
+    let fragments = assets
+        .iter()
+        .map(|a| serde_json::to_string(a).unwrap())
+        .collect::<Vec<_>>();
+
+    let array = format!("[\n{}\n]\n", fragments.join(",\n"));
+    assert_eq!(
+        String::from_utf8(json(&assets, JsonLayout::Array, BlobEncoding::Array)).unwrap(),
+        array
+    );
+    let ndjson = fragments
+        .iter()
+        .map(|f| format!("{f}\n"))
+        .collect::<String>();
+    assert_eq!(
+        String::from_utf8(json(&assets, JsonLayout::Ndjson, BlobEncoding::Array)).unwrap(),
+        ndjson
+    );
+
+    let mut expected = MAGIC.to_vec();
+    expected.extend(SCHEMA_VERSION.to_le_bytes());
+    expected.extend(7u64.to_le_bytes());
+    expected.extend(bincode::serialize(&assets).unwrap());
+    assert_eq!(bin(&assets), expected);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn same_for_any_thread_count() {
+    let assets = assets();
+    let export = |threads| {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            let mut out = vec![bin(&assets)];
+            for blobs in [
+                BlobEncoding::Array,
+                BlobEncoding::Base64,
+                BlobEncoding::Stub,
+            ] {
+                for layout in [JsonLayout::Array, JsonLayout::Ndjson] {
+                    out.push(json(&assets, layout, blobs));
+                }
+            }
+            out
+        })
+    };
+
+    // One thread writes each asset before serializing the next, like the
+    // serial export.
+    let serial = export(1);
+    for threads in [2, 3, 8] {
+        assert!(export(threads) == serial, "{threads} threads");
+    }
+}
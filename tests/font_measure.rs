@@ -0,0 +1,92 @@
+//! Checks [`Font`]'s text measurement and word wrapping.
+
+use t5_xfile_defs::{
+    XString,
+    font::{Font, Glyph},
+};
+
+/// A 16 pixel font where `'a'` is 8 wide, `'b'` is 10 wide, and a space is
+/// 4 wide. Anything else falls back to half the height.
+fn font() -> Font {
+    let glyph = |letter: char, dx| {
+        let mut g = Glyph::default();
+        g.letter = letter as u16;
+        g.dx = dx;
+        g
+    };
+
+    Font {
+        font_name: XString::from("fonts/test"),
+        pixel_height: 16,
+        material: None,
+        glow_material: None,
+        glyphs: vec![glyph('a', 8), glyph('b', 10), glyph(' ', 4)],
+    }
+}
+
+#[test]
+fn single_line() {
+    let font = font();
+    assert_eq!(font.glyph('b').unwrap().dx, 10);
+    assert!(font.glyph('c').is_none());
+    assert_eq!(font.advance('a'), 8.0);
+    assert_eq!(font.advance('c'), 8.0);
+    assert_eq!(font.advance('\u{263A}'), 8.0);
+
+    assert_eq!(font.measure_string(""), (0.0, 16.0));
+    assert_eq!(font.measure_string("ab"), (18.0, 16.0));
+    assert_eq!(font.measure_string("ab ba"), (40.0, 16.0));
+    // Newlines aren't special on a single line.
+    assert_eq!(font.measure_string("a\na"), (24.0, 16.0));
+}
+
+#[test]
+fn wrapping() {
+    let font = font();
+    let lines = |text, width| font.measure_multiline_string(text, width);
+
+    // "ab" is 18 wide, so two of them with a space are 40.
+    assert_eq!(lines("ab ab", 40.0), [("ab ab".to_string(), 40.0)]);
+    assert_eq!(
+        lines("ab ab", 39.0),
+        [("ab".to_string(), 18.0), ("ab".to_string(), 18.0)]
+    );
+
+    // Runs of whitespace collapse to one space.
+    assert_eq!(lines("  ab   ab ", 100.0), [("ab ab".to_string(), 40.0)]);
+
+    // A word wider than the line still gets a line to itself.
+    assert_eq!(
+        lines("a bbbb a", 20.0),
+        [
+            ("a".to_string(), 8.0),
+            ("bbbb".to_string(), 40.0),
+            ("a".to_string(), 8.0),
+        ]
+    );
+
+    // Explicit newlines always break, and empty paragraphs are kept.
+    assert_eq!(
+        lines("a\n\nb", 100.0),
+        [
+            ("a".to_string(), 8.0),
+            (String::new(), 0.0),
+            ("b".to_string(), 10.0),
+        ]
+    );
+    assert_eq!(lines("", 100.0), [(String::new(), 0.0)]);
+}
+
+#[test]
+fn fits_in_box() {
+    let font = font();
+
+    assert!(font.text_fits_in_box("ab ab", 40.0, 16.0));
+    // Wraps onto a second line, which needs the height for it.
+    assert!(!font.text_fits_in_box("ab ab", 39.0, 16.0));
+    assert!(font.text_fits_in_box("ab ab", 39.0, 32.0));
+    // No amount of height helps a word that's too wide.
+    assert!(!font.text_fits_in_box("bbbb", 39.0, 1000.0));
+    // An empty string still takes up a line.
+    assert!(!font.text_fits_in_box("", 100.0, 15.0));
+}
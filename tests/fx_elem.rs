@@ -0,0 +1,346 @@
+//! Checks that an effect's sound, light, cloud, sprite, and runner elements
+//! deserialize with all their data, without throwing off the asset after
+//! it.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    common::Vec4,
+    fx::{
+        FxBillboardTrim, FxEffectDef, FxEffectDefFlags, FxEffectDefRef, FxElemAtlas, FxElemDef,
+        FxElemDefUnion, FxElemDefVisuals, FxElemFlags, FxElemSpawnSound, FxElemType,
+        FxElemVisStateSample, FxElemVisualState, FxElemVisuals, FxFloatRange, FxIntRange,
+    },
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+use common::{TempFile, u32s};
+
+const FX: u32 = 0x1C;
+const RAWFILE: u32 = 0x24;
+const INLINE: u32 = 0xFFFFFFFF;
+
+fn range() -> FxFloatRange {
+    FxFloatRange {
+        base: 1.0,
+        amplitude: 0.5,
+    }
+}
+
+fn no_effect() -> FxEffectDefRef {
+    FxEffectDefRef::Name(XString::from(String::new()))
+}
+
+fn elem(elem_type: FxElemType, visuals: Option<FxElemVisuals>) -> FxElemDef {
+    FxElemDef {
+        flags: FxElemFlags::empty(),
+        spawn: [100, 0],
+        spawn_range: range(),
+        fade_in_range: range(),
+        fade_out_range: range(),
+        spawn_frustum_cull_radius: 0.0,
+        spawn_delay_msec: FxIntRange {
+            base: 0,
+            amplitude: 0,
+        },
+        life_span_msec: FxIntRange {
+            base: 1000,
+            amplitude: 250,
+        },
+        spawn_origin: [range(); 3],
+        spawn_offset_radius: range(),
+        spawn_offset_height: range(),
+        spawn_angles: [range(); 3],
+        angular_velocity: [range(); 3],
+        initial_rotation: range(),
+        rotation_axis: 0,
+        gravity: range(),
+        reflection_factor: range(),
+        atlas: FxElemAtlas {
+            behavior: 0,
+            index: 0,
+            fps: 0,
+            loop_count: 0,
+            col_index_bits: 0,
+            row_index_bits: 0,
+            entry_count_and_index_range: 0,
+        },
+        wind_influence: 0.0,
+        elem_type,
+        visual_count: visuals.is_some() as u8,
+        vel_interval_count: 0,
+        vis_state_interval_count: 0,
+        vel_samples: Vec::new(),
+        vis_samples: Vec::new(),
+        visuals: Some(FxElemDefVisuals::Instance(visuals)),
+        coll_mins: [0.0; 3].into(),
+        coll_maxs: [0.0; 3].into(),
+        effect_on_impact: no_effect(),
+        effect_on_death: no_effect(),
+        effect_emitted: no_effect(),
+        emit_dist: range(),
+        emit_dist_variance: range(),
+        effect_attached: no_effect(),
+        trail_def: None,
+        sort_order: 0,
+        lighting_frac: 0,
+        alpha_fade_time_msec: 0,
+        max_wind_strength: 0,
+        spawn_interval_at_max_wind: 0,
+        lifespan_at_max_wind: 0,
+        u: None,
+        spawn_sound: FxElemSpawnSound {
+            spawn_sound: XString::from(String::new()),
+        },
+        billboard_pivot: [0.0; 2].into(),
+    }
+}
+
+fn visual_state(color: [u8; 4], scale: f32) -> FxElemVisualState {
+    FxElemVisualState {
+        color,
+        rotation_delta: 0.0,
+        rotation_total: 0.0,
+        size: [0.0; 2].into(),
+        scale,
+    }
+}
+
+fn effect() -> FxEffectDef {
+    let mut sound = elem(
+        FxElemType::SOUND,
+        Some(FxElemVisuals::SoundName(XString::from(
+            "fx_fire_loop".to_string(),
+        ))),
+    );
+    sound.spawn_sound.spawn_sound = XString::from("fx_fire_ignite".to_string());
+
+    // Lights have no visuals; their color and radius come from the visual
+    // state samples.
+    let mut light = elem(FxElemType::OMNI_LIGHT, None);
+    light.vis_samples = vec![
+        FxElemVisStateSample {
+            base: visual_state([255, 128, 0, 255], 250.0),
+            amplitude: visual_state([0; 4], 0.0),
+        };
+        2
+    ];
+    light.vis_state_interval_count = 1;
+
+    let mut cloud = elem(FxElemType::CLOUD, Some(FxElemVisuals::Material(None)));
+    cloud.u = Some(FxElemDefUnion::CloudDensityRange(FxIntRange {
+        base: 3,
+        amplitude: 7,
+    }));
+
+    let mut sprite = elem(
+        FxElemType::SPRITE_ORIENTED,
+        Some(FxElemVisuals::Material(None)),
+    );
+    sprite.u = Some(FxElemDefUnion::Billboard(FxBillboardTrim {
+        top_width: 1.5,
+        bottom_width: 0.5,
+    }));
+
+    let runner = elem(
+        FxElemType::RUNNER,
+        Some(FxElemVisuals::EffectDef(FxEffectDefRef::Name(
+            XString::from("fx/child".to_string()),
+        ))),
+    );
+
+    FxEffectDef {
+        name: XString::from("fx/campfire".to_string()),
+        flags: FxEffectDefFlags::empty(),
+        ef_priority: 0,
+        total_size: 0,
+        msec_looping_life: 0,
+        elem_def_count_looping: 5,
+        elem_def_count_one_shot: 0,
+        elem_def_count_emission: 0,
+        elem_defs: vec![sound, light, cloud, sprite, runner],
+        bounding_box_dim: [0.0; 3].into(),
+        bounding_sphere: Vec4::from([0.0; 4]),
+    }
+}
+
+/// An `FxElemDefRaw` with everything but the given fields zeroed. Offsets
+/// are from the game's struct.
+fn elem_raw(
+    elem_type: FxElemType,
+    visual_count: u8,
+    vis_samples: bool,
+    visuals: u32,
+    u: [u8; 8],
+    spawn_sound: bool,
+) -> Vec<u8> {
+    let mut raw = vec![0u8; 292];
+    raw[184] = elem_type as u8;
+    raw[185] = visual_count;
+    raw[187] = vis_samples as u8;
+    if vis_samples {
+        raw[192..196].copy_from_slice(&INLINE.to_le_bytes());
+    }
+    raw[196..200].copy_from_slice(&visuals.to_le_bytes());
+    raw[272..280].copy_from_slice(&u);
+    if spawn_sound {
+        raw[280..284].copy_from_slice(&INLINE.to_le_bytes());
+    }
+    raw
+}
+
+/// An `FxElemVisStateSampleRaw` with the given base color and scale.
+fn vis_sample_raw(color: [u8; 4], scale: f32) -> Vec<u8> {
+    let mut raw = vec![0u8; 48];
+    raw[..4].copy_from_slice(&color);
+    raw[20..24].copy_from_slice(&scale.to_le_bytes());
+    raw
+}
+
+/// An inflated blob holding an effect with one of each kind of element,
+/// then a raw file.
+fn blob() -> Vec<u8> {
+    let f32s = |a: f32, b: f32| [a.to_le_bytes(), b.to_le_bytes()].concat();
+
+    let mut data = u32s(&[0, 0, 2, INLINE, FX, INLINE, RAWFILE, INLINE]);
+
+    // FxEffectDefRaw, its name, and its elements.
+    let mut header = vec![0u8; 60];
+    header[..4].copy_from_slice(&INLINE.to_le_bytes());
+    header[16..20].copy_from_slice(&5u32.to_le_bytes());
+    header[28..32].copy_from_slice(&INLINE.to_le_bytes());
+    data.extend(header);
+    data.extend(b"fx/campfire\0");
+
+    data.extend(elem_raw(FxElemType::SOUND, 1, false, INLINE, [0; 8], true));
+    data.extend(elem_raw(FxElemType::OMNI_LIGHT, 0, true, 0, [0; 8], false));
+    data.extend(elem_raw(
+        FxElemType::CLOUD,
+        1,
+        false,
+        0,
+        u32s(&[3, 7]).try_into().unwrap(),
+        false,
+    ));
+    data.extend(elem_raw(
+        FxElemType::SPRITE_ORIENTED,
+        1,
+        false,
+        0,
+        f32s(1.5, 0.5).try_into().unwrap(),
+        false,
+    ));
+    data.extend(elem_raw(
+        FxElemType::RUNNER,
+        1,
+        false,
+        INLINE,
+        [0; 8],
+        false,
+    ));
+
+    // Sound: the visual's name, then the spawn sound.
+    data.extend(b"fx_fire_loop\0");
+    data.extend(b"fx_fire_ignite\0");
+    // Light: two visual state samples.
+    data.extend(vis_sample_raw([255, 128, 0, 255], 250.0));
+    data.extend(vis_sample_raw([0; 4], 0.0));
+    // Runner: the effect's name.
+    data.extend(b"fx/child\0");
+
+    data.extend(u32s(&[INLINE, 5, INLINE]));
+    data.extend(b"fx_after.txt\0after\0");
+
+    let mut blob = u32s(&[data.len() as u32, 0, 0, 0, 0, 0, 0, 0, 0]);
+    blob.extend(data);
+    blob
+}
+
+#[test]
+fn elements() {
+    let temp = TempFile::new("fx_elem.cache", blob());
+    let assets = common::deserialize(&temp, true);
+    let assets = assets.unwrap();
+
+    let [
+        XAsset::PC(XAssetGeneric::Fx(Some(fx))),
+        XAsset::PC(XAssetGeneric::RawFile(Some(rawfile))),
+    ] = &assets[..]
+    else {
+        panic!("{assets:#?}");
+    };
+    assert_eq!(rawfile.name.get(), "fx_after.txt");
+    assert_eq!(rawfile.as_str(), Some("after"));
+
+    let [sound, light, cloud, sprite, runner] = &fx.elem_defs[..] else {
+        panic!("{:#?}", fx.elem_defs);
+    };
+
+    assert_eq!(sound.spawn_sound.spawn_sound.get(), "fx_fire_ignite");
+    assert_eq!(fx.referenced_sounds(), ["fx_fire_ignite", "fx_fire_loop"]);
+
+    assert!(light.elem_type.is_light());
+    assert!(matches!(
+        light.visuals,
+        Some(FxElemDefVisuals::Instance(None))
+    ));
+    assert_eq!(light.vis_samples.len(), 2);
+    assert_eq!(light.vis_samples[0].base.color, [255, 128, 0, 255]);
+    assert_eq!(light.vis_samples[0].base.scale, 250.0);
+
+    assert!(matches!(
+        cloud.u,
+        Some(FxElemDefUnion::CloudDensityRange(FxIntRange {
+            base: 3,
+            amplitude: 7
+        }))
+    ));
+
+    assert!(sprite.elem_type.is_sprite());
+    let Some(FxElemDefUnion::Billboard(trim)) = &sprite.u else {
+        panic!("{:?}", sprite.u);
+    };
+    assert_eq!((trim.top_width, trim.bottom_width), (1.5, 0.5));
+
+    let Some(FxElemDefVisuals::Instance(Some(FxElemVisuals::EffectDef(FxEffectDefRef::Name(
+        name,
+    ))))) = &runner.visuals
+    else {
+        panic!("{:?}", runner.visuals);
+    };
+    assert_eq!(name.get(), "fx/child");
+}
+
+/// The union used to be dropped when deserializing, and zeroed when
+/// serializing again.
+#[test]
+fn union_round_trip() {
+    let mut expected = effect();
+    expected
+        .elem_defs
+        .retain(|e| e.elem_type == FxElemType::CLOUD);
+    expected.elem_def_count_looping = 1;
+
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>(std::iter::once(XAsset::PC(XAssetGeneric::Fx(Some(
+        Box::new(expected.clone()),
+    )))))
+    .unwrap();
+    let bytes = ser.deflate().unwrap();
+
+    let temp = TempFile::new("fx_elem.ff", bytes);
+    let assets = common::deserialize(&temp, false);
+    let assets = assets.unwrap();
+
+    let [XAsset::PC(XAssetGeneric::Fx(Some(fx)))] = &assets[..] else {
+        panic!("{assets:#?}");
+    };
+    assert_eq!(format!("{fx:?}"), format!("{expected:?}"));
+}
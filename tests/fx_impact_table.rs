@@ -0,0 +1,155 @@
+//! Checks the labeled lookups on [`FxImpactTable`], and that a table with
+//! external effects survives a serialize round trip.
+
+mod common;
+
+use t5_xfile_defs::{
+    XString,
+    common::{Vec3, Vec4},
+    fx::{FleshHitType, FxEffectDef, FxEffectDefFlags, FxImpactEntry, FxImpactTable, SurfaceType},
+    weapon::ImpactType,
+};
+
+fn effect(name: &str) -> Option<Box<FxEffectDef>> {
+    Some(Box::new(FxEffectDef {
+        name: XString::from(name),
+        flags: FxEffectDefFlags::empty(),
+        ef_priority: 0,
+        total_size: 0,
+        msec_looping_life: 0,
+        elem_def_count_looping: 0,
+        elem_def_count_one_shot: 0,
+        elem_def_count_emission: 0,
+        elem_defs: Vec::new(),
+        bounding_box_dim: Vec3::default(),
+        bounding_sphere: Vec4::default(),
+    }))
+}
+
+fn entry(row: usize) -> FxImpactEntry {
+    let mut nonflesh = <[Option<Box<FxEffectDef>>; 31]>::default();
+    nonflesh[SurfaceType::METAL as usize] = effect(&format!("impacts/metal_{row}"));
+    let mut flesh = <[Option<Box<FxEffectDef>>; 4]>::default();
+    flesh[FleshHitType::HEAD as usize] = effect(&format!("impacts/head_{row}"));
+    FxImpactEntry { nonflesh, flesh }
+}
+
+/// Every one of the 21 rows has a metal and a head effect named after it.
+fn table() -> FxImpactTable {
+    FxImpactTable {
+        name: XString::from("default"),
+        table: (0..21).map(entry).collect(),
+    }
+}
+
+#[test]
+fn get() {
+    let table = table();
+    let name = |e: Option<&FxEffectDef>| e.map(|e| e.name.get().to_string());
+
+    assert_eq!(
+        name(table.get(ImpactType::BLADE, SurfaceType::METAL)),
+        Some("impacts/metal_15".to_string())
+    );
+    assert_eq!(name(table.get(ImpactType::BLADE, SurfaceType::WOOD)), None);
+    assert_eq!(
+        name(table.get_flesh(ImpactType::BULLET_SMALL, FleshHitType::HEAD)),
+        Some("impacts/head_1".to_string())
+    );
+
+    // Row 16 exists, but `COUNT` doesn't name it.
+    assert!(table.get(ImpactType::COUNT, SurfaceType::METAL).is_none());
+    assert!(
+        table
+            .get_flesh(ImpactType::COUNT, FleshHitType::HEAD)
+            .is_none()
+    );
+}
+
+#[test]
+fn rows() {
+    let table = table();
+    let rows = table.rows().collect::<Vec<_>>();
+    assert_eq!(rows.len(), 21);
+
+    for (i, (impact, _)) in rows.iter().enumerate() {
+        match impact {
+            Some(impact) => assert_eq!(*impact as usize, i),
+            None => assert!(i >= ImpactType::COUNT as usize, "row {i} is unnamed"),
+        }
+    }
+    assert_eq!(rows[15].0, Some(ImpactType::BLADE));
+    assert_eq!(rows[16].0, None);
+
+    let s = table.to_table_string();
+    assert!(s.contains("[15] BLADE"), "{s}");
+    assert!(s.contains("[16] <unnamed>"), "{s}");
+    assert!(!s.contains("COUNT"), "{s}");
+}
+
+#[test]
+fn external() {
+    let fx = FxEffectDef::external("impacts/fx_flesh_hit");
+    assert_eq!(fx.name.get(), ",impacts/fx_flesh_hit");
+    assert!(fx.is_external());
+    assert!(fx.elem_defs.is_empty());
+
+    assert!(!effect("impacts/fx_flesh_hit").unwrap().is_external());
+}
+
+#[cfg(all(feature = "serializer", feature = "deserializer"))]
+#[test]
+fn round_trip() {
+    use t5_xfile_defs::{
+        XFilePlatform,
+        xasset::{XAsset, XAssetGeneric},
+    };
+    use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+    use common::TempFile;
+
+    // Only the last row has effects, since a `Vec`'s elements are each
+    // written with their data right behind them rather than after the whole
+    // array.
+    let mut table = FxImpactTable {
+        name: XString::from("default"),
+        table: (0..21)
+            .map(|_| FxImpactEntry {
+                nonflesh: Default::default(),
+                flesh: Default::default(),
+            })
+            .collect(),
+    };
+    let last = table.table.last_mut().unwrap();
+    last.nonflesh[SurfaceType::METAL as usize] =
+        Some(Box::new(FxEffectDef::external("impacts/fx_metal")));
+    last.flesh[FleshHitType::HEAD as usize] = effect("impacts/fx_head");
+
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>(std::iter::once(XAsset::PC(XAssetGeneric::ImpactFx(Some(
+        Box::new(table),
+    )))))
+    .unwrap();
+    let temp = TempFile::new("fx_impact_table.ff", ser.deflate().unwrap());
+    let assets = common::deserialize(&temp, false).unwrap();
+    let [XAsset::PC(XAssetGeneric::ImpactFx(Some(table)))] = assets.as_slice() else {
+        panic!("expected one impact table, got {assets:?}");
+    };
+
+    assert_eq!(table.name.get(), "default");
+    assert_eq!(table.table.len(), 21);
+    let metal = table.table[20].get(SurfaceType::METAL).unwrap();
+    assert!(metal.is_external());
+    assert_eq!(metal.name.get(), ",impacts/fx_metal");
+    let head = table.table[20].flesh[FleshHitType::HEAD as usize].as_ref();
+    assert!(!head.unwrap().is_external());
+    assert_eq!(head.unwrap().name.get(), "impacts/fx_head");
+    assert!(
+        table.table[..20]
+            .iter()
+            .all(|e| e.nonflesh.iter().chain(&e.flesh).all(Option::is_none))
+    );
+}
@@ -0,0 +1,243 @@
+//! Checks [`FxEffectDef`]'s referenced sound, material, and model listings,
+//! and its particle count bound.
+
+use t5_xfile_defs::{
+    XString,
+    common::{Vec3, Vec4},
+    fx::{
+        FxEffectDef, FxEffectDefFlags, FxEffectDefRef, FxElemAtlas, FxElemDef, FxElemDefVisuals,
+        FxElemFlags, FxElemMarkVisuals, FxElemSpawnSound, FxElemType, FxElemVisuals, FxFloatRange,
+        FxIntRange,
+    },
+    techset::{Material, MaterialInfo},
+    xmodel::XModel,
+};
+
+fn range() -> FxFloatRange {
+    FxFloatRange {
+        base: 0.0,
+        amplitude: 0.0,
+    }
+}
+
+fn no_effect() -> FxEffectDefRef {
+    FxEffectDefRef::Name(XString::default())
+}
+
+fn material(name: &str) -> Option<Box<Material>> {
+    Some(Box::new(Material {
+        info: MaterialInfo {
+            name: XString::from(name),
+            ..Default::default()
+        },
+        ..Default::default()
+    }))
+}
+
+fn model(name: &str) -> FxElemVisuals {
+    FxElemVisuals::Model(Some(Box::new(XModel {
+        name: XString::from(name),
+        ..Default::default()
+    })))
+}
+
+/// An element living 1000-1250ms, that spawns according to `spawn`.
+fn elem(
+    elem_type: FxElemType,
+    spawn: [i32; 2],
+    spawn_sound: &str,
+    visuals: FxElemDefVisuals,
+) -> FxElemDef {
+    FxElemDef {
+        flags: FxElemFlags::empty(),
+        spawn,
+        spawn_range: range(),
+        fade_in_range: range(),
+        fade_out_range: range(),
+        spawn_frustum_cull_radius: 0.0,
+        spawn_delay_msec: FxIntRange {
+            base: 0,
+            amplitude: 0,
+        },
+        life_span_msec: FxIntRange {
+            base: 1000,
+            amplitude: 250,
+        },
+        spawn_origin: [range(); 3],
+        spawn_offset_radius: range(),
+        spawn_offset_height: range(),
+        spawn_angles: [range(); 3],
+        angular_velocity: [range(); 3],
+        initial_rotation: range(),
+        rotation_axis: 0,
+        gravity: range(),
+        reflection_factor: range(),
+        atlas: FxElemAtlas {
+            behavior: 0,
+            index: 0,
+            fps: 0,
+            loop_count: 0,
+            col_index_bits: 0,
+            row_index_bits: 0,
+            entry_count_and_index_range: 0,
+        },
+        wind_influence: 0.0,
+        elem_type,
+        visual_count: 0,
+        vel_interval_count: 0,
+        vis_state_interval_count: 0,
+        vel_samples: Vec::new(),
+        vis_samples: Vec::new(),
+        visuals: Some(visuals),
+        coll_mins: [0.0; 3].into(),
+        coll_maxs: [0.0; 3].into(),
+        effect_on_impact: no_effect(),
+        effect_on_death: no_effect(),
+        effect_emitted: no_effect(),
+        emit_dist: range(),
+        emit_dist_variance: range(),
+        effect_attached: no_effect(),
+        trail_def: None,
+        sort_order: 0,
+        lighting_frac: 0,
+        alpha_fade_time_msec: 0,
+        max_wind_strength: 0,
+        spawn_interval_at_max_wind: 0,
+        lifespan_at_max_wind: 0,
+        u: None,
+        spawn_sound: FxElemSpawnSound {
+            spawn_sound: XString::from(spawn_sound),
+        },
+        billboard_pivot: [0.0; 2].into(),
+    }
+}
+
+fn effect(elem_def_count_looping: i32, elem_defs: Vec<FxElemDef>) -> FxEffectDef {
+    FxEffectDef {
+        name: XString::from("explosions/test"),
+        flags: FxEffectDefFlags::empty(),
+        ef_priority: 0,
+        total_size: 0,
+        msec_looping_life: 0,
+        elem_def_count_looping,
+        elem_def_count_one_shot: elem_defs.len() as i32 - elem_def_count_looping,
+        elem_def_count_emission: 0,
+        elem_defs,
+        bounding_box_dim: Vec3::default(),
+        bounding_sphere: Vec4::default(),
+    }
+}
+
+/// Two looping elements (smoke and debris), then a bang, a scorch mark, and
+/// some more debris.
+fn explosion() -> FxEffectDef {
+    effect(
+        2,
+        vec![
+            elem(
+                FxElemType::SPRITE_ORIENTED,
+                [100, 0],
+                "fx_smoke_loop",
+                FxElemDefVisuals::Array(vec![
+                    FxElemVisuals::Material(material("mtl_smoke")),
+                    FxElemVisuals::Material(material("mtl_smoke")),
+                    FxElemVisuals::Material(None),
+                ]),
+            ),
+            elem(
+                FxElemType::MODEL,
+                [500, 2],
+                "",
+                FxElemDefVisuals::Instance(Some(model("debris_a"))),
+            ),
+            elem(
+                FxElemType::SOUND,
+                [3, 2],
+                "fx_boom",
+                FxElemDefVisuals::Instance(Some(FxElemVisuals::SoundName(XString::from(
+                    "fx_boom",
+                )))),
+            ),
+            elem(
+                FxElemType::DECAL,
+                [1, 0],
+                "fx_sizzle",
+                FxElemDefVisuals::MarkArray(vec![FxElemMarkVisuals {
+                    materials: [material("mtl_scorch"), material("mtl_smoke")],
+                }]),
+            ),
+            elem(
+                FxElemType::MODEL,
+                [-5, 1],
+                "",
+                FxElemDefVisuals::Array(vec![model("debris_a"), model("debris_b")]),
+            ),
+        ],
+    )
+}
+
+#[test]
+fn references() {
+    let fx = explosion();
+
+    assert_eq!(
+        fx.referenced_sounds(),
+        ["fx_smoke_loop", "fx_boom", "fx_sizzle"]
+    );
+    assert_eq!(
+        fx.referenced_materials()
+            .iter()
+            .map(|m| m.info.name.get())
+            .collect::<Vec<_>>(),
+        ["mtl_smoke", "mtl_scorch"]
+    );
+    assert_eq!(
+        fx.referenced_models()
+            .iter()
+            .map(|m| m.name.get())
+            .collect::<Vec<_>>(),
+        ["debris_a", "debris_b"]
+    );
+
+    // Decal materials aren't visuals.
+    let decal = &fx.elem_defs[3];
+    assert_eq!(decal.visuals().count(), 0);
+    assert_eq!(fx.elem_defs[0].visuals().count(), 3);
+    assert_eq!(fx.elem_defs[1].visuals().count(), 1);
+
+    let empty = effect(0, Vec::new());
+    assert!(empty.referenced_sounds().is_empty());
+    assert!(empty.referenced_materials().is_empty());
+    assert!(empty.referenced_models().is_empty());
+}
+
+#[test]
+fn max_particles() {
+    // Smoke: one every 100ms for up to 1250ms, so 13.
+    // Debris: one every 500ms would be 3, but it only loops twice.
+    // Bang: 3 + 2. Scorch: 1. More debris: a negative count is none.
+    assert_eq!(explosion().max_particle_count(), 13 + 2 + 5 + 1);
+
+    // A looping element always has at least one particle alive, and a zero
+    // interval is treated as 1ms.
+    let mut instant = elem(
+        FxElemType::SPRITE_ORIENTED,
+        [0, 0],
+        "",
+        FxElemDefVisuals::Instance(None),
+    );
+    instant.life_span_msec = FxIntRange {
+        base: 0,
+        amplitude: 0,
+    };
+    assert_eq!(effect(1, vec![instant.clone()]).max_particle_count(), 1);
+    instant.life_span_msec.base = 40;
+    assert_eq!(effect(1, vec![instant.clone()]).max_particle_count(), 40);
+
+    // Huge counts saturate instead of overflowing.
+    instant.spawn = [i32::MAX, i32::MAX];
+    assert_eq!(
+        effect(0, vec![instant.clone(), instant.clone(), instant]).max_particle_count(),
+        u32::MAX
+    );
+}
@@ -0,0 +1,109 @@
+//! Checks [`GfxImage`]'s mip size and offset calculations for block
+//! compressed formats, and that unsupported formats are reported.
+
+use t5_xfile_defs::{
+    ErrorKind,
+    techset::{GfxImage, GfxImageLoadDef, GfxTexture, MapType, d3dfmt},
+};
+
+fn image(format: i32, map_type: MapType, width: u16, height: u16, level_count: u8) -> GfxImage {
+    let mut img = GfxImage {
+        texture: GfxTexture::LoadDef(Some(Box::new(GfxImageLoadDef {
+            level_count,
+            flags: 0,
+            format,
+            resource: Vec::new(),
+        }))),
+        map_type,
+        width,
+        height,
+        depth: 1,
+        level_count,
+        ..Default::default()
+    };
+
+    // Fill every face with its index so face lookups can be checked.
+    let len = img.full_size_bytes().unwrap_or(0);
+    let face_len = len / img.face_count();
+    if let GfxTexture::LoadDef(Some(d)) = &mut img.texture {
+        d.resource = (0..len).map(|i| (i / face_len.max(1)) as u8).collect();
+    }
+    img
+}
+
+fn sizes(img: &GfxImage) -> Vec<usize> {
+    (0..img.mip_count())
+        .map(|l| img.mip_size_bytes(l).unwrap())
+        .collect()
+}
+
+fn offsets(img: &GfxImage) -> Vec<usize> {
+    (0..img.mip_count())
+        .map(|l| img.mip_offset_bytes(l).unwrap())
+        .collect()
+}
+
+#[test]
+fn dxt1() {
+    // 16x8, 8x4, 4x2, 2x1, 1x1. Every level below 4x4 still takes a whole
+    // 8-byte block.
+    let img = image(d3dfmt::DXT1, MapType::TWO_DIMENSIONAL, 16, 8, 5);
+    assert_eq!(sizes(&img), [64, 16, 8, 8, 8]);
+    assert_eq!(offsets(&img), [0, 64, 80, 88, 96]);
+    assert_eq!(img.full_size_bytes().unwrap(), 104);
+    assert_eq!(img.mip_data(1).unwrap().len(), 16);
+}
+
+#[test]
+fn dxt5() {
+    // 16x16, 8x8, 4x4, 2x2, with 16-byte blocks.
+    let img = image(d3dfmt::DXT5, MapType::TWO_DIMENSIONAL, 16, 16, 4);
+    assert_eq!(sizes(&img), [256, 64, 16, 16]);
+    assert_eq!(offsets(&img), [0, 256, 320, 336]);
+    assert_eq!(img.full_size_bytes().unwrap(), 352);
+}
+
+#[test]
+fn dxt1_cubemap() {
+    let img = image(d3dfmt::DXT1, MapType::CUBE, 8, 8, 2);
+    assert_eq!(sizes(&img), [32, 8]);
+    assert_eq!(img.full_size_bytes().unwrap(), 6 * 40);
+
+    // Each face's chain follows the last, so face 3's second mip starts
+    // three chains and one mip in.
+    let data = img.face_mip_data(3, 1).unwrap();
+    assert_eq!(data, [3; 8]);
+    assert!(img.face_mip_data(6, 0).is_err());
+}
+
+#[test]
+fn level_out_of_range() {
+    let img = image(d3dfmt::DXT1, MapType::TWO_DIMENSIONAL, 16, 8, 5);
+    for e in [
+        img.mip_size_bytes(5).unwrap_err(),
+        img.mip_offset_bytes(5).unwrap_err(),
+    ] {
+        assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+    }
+}
+
+#[test]
+fn unsupported_format() {
+    let img = image(0x1234, MapType::TWO_DIMENSIONAL, 16, 8, 1);
+    for e in [
+        img.mip_size_bytes(0).unwrap_err(),
+        img.full_size_bytes().unwrap_err(),
+        img.to_rgba8_mip(0).unwrap_err(),
+    ] {
+        assert!(
+            matches!(e.kind(), ErrorKind::UnsupportedImageFormat(0x1234)),
+            "{e}"
+        );
+    }
+    assert!(
+        img.full_size_bytes()
+            .unwrap_err()
+            .to_string()
+            .contains("0x00001234")
+    );
+}
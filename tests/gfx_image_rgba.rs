@@ -0,0 +1,171 @@
+//! Checks [`GfxImage::to_rgba8_mip`] against hand-decoded DXT blocks.
+
+mod common;
+
+use t5_xfile_defs::techset::{GfxImage, GfxImageLoadDef, GfxTexture, MapType, d3dfmt};
+
+fn image(format: i32, width: u16, height: u16, level_count: u8, resource: Vec<u8>) -> GfxImage {
+    GfxImage {
+        texture: GfxTexture::LoadDef(Some(Box::new(GfxImageLoadDef {
+            level_count,
+            flags: 0,
+            format,
+            resource,
+        }))),
+        map_type: MapType::TWO_DIMENSIONAL,
+        width,
+        height,
+        depth: 1,
+        level_count,
+        ..Default::default()
+    }
+}
+
+/// Red (`0xF800`) and blue (`0x001F`) endpoints, with each row using the
+/// next palette entry.
+const DXT1_BLOCK: [u8; 8] = [0x00, 0xF8, 0x1F, 0x00, 0x00, 0x55, 0xAA, 0xFF];
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+fn rows(rgba: &[u8]) -> Vec<[u8; 4]> {
+    rgba.chunks(16)
+        .map(|r| r[..4].try_into().unwrap())
+        .collect()
+}
+
+#[test]
+fn dxt1() {
+    let (width, height, rgba) = image(d3dfmt::DXT1, 4, 4, 1, DXT1_BLOCK.to_vec())
+        .to_rgba8_mip(0)
+        .unwrap();
+    assert_eq!((width, height), (4, 4));
+    assert_eq!(rgba.len(), 4 * 4 * 4);
+
+    // Two thirds of the way from one endpoint to the other, and fully
+    // opaque throughout.
+    assert_eq!(
+        rows(&rgba),
+        [RED, BLUE, [170, 0, 85, 255], [85, 0, 170, 255]]
+    );
+    for row in rgba.chunks(16) {
+        assert!(row.chunks(4).all(|px| px == &row[..4]));
+    }
+}
+
+#[test]
+fn dxt1_transparent() {
+    // With the endpoints swapped, color0 <= color1, so the third entry is
+    // halfway and the fourth is transparent black.
+    let block = [0x1F, 0x00, 0x00, 0xF8, 0x00, 0x55, 0xAA, 0xFF];
+    let (_, _, rgba) = image(d3dfmt::DXT1, 4, 4, 1, block.to_vec())
+        .to_rgba8_mip(0)
+        .unwrap();
+
+    assert_eq!(rows(&rgba), [BLUE, RED, [127, 0, 127, 255], [0, 0, 0, 0]]);
+}
+
+#[test]
+fn dxt3() {
+    // Alpha goes 0, 1, ..., 15 (times 17) across the block.
+    let mut block = 0xFEDC_BA98_7654_3210u64.to_le_bytes().to_vec();
+    block.extend(DXT1_BLOCK);
+    let (_, _, rgba) = image(d3dfmt::DXT3, 4, 4, 1, block).to_rgba8_mip(0).unwrap();
+
+    let alphas = rgba.chunks(4).map(|px| px[3]).collect::<Vec<_>>();
+    assert_eq!(alphas, (0..16).map(|a| a * 17).collect::<Vec<_>>());
+    assert_eq!(rows(&rgba)[1], [0, 0, 255, 4 * 17]);
+}
+
+#[test]
+fn dxt5() {
+    // 8-alpha mode from 255 to 31, with pixel i using index i % 8.
+    let indices = (0..16u64).fold(0, |acc, i| acc | ((i % 8) << (3 * i)));
+    let mut block = vec![255, 31];
+    block.extend(&indices.to_le_bytes()[..6]);
+    block.extend(DXT1_BLOCK);
+    let (_, _, rgba) = image(d3dfmt::DXT5, 4, 4, 1, block).to_rgba8_mip(0).unwrap();
+
+    let alphas = rgba.chunks(4).take(8).map(|px| px[3]).collect::<Vec<_>>();
+    assert_eq!(alphas, [255, 31, 223, 191, 159, 127, 95, 63]);
+
+    // 6-alpha mode, where 6 and 7 are fully transparent and opaque.
+    let mut block = vec![0, 100];
+    block.extend(&indices.to_le_bytes()[..6]);
+    block.extend(DXT1_BLOCK);
+    let (_, _, rgba) = image(d3dfmt::DXT5, 4, 4, 1, block).to_rgba8_mip(0).unwrap();
+
+    let alphas = rgba.chunks(4).take(8).map(|px| px[3]).collect::<Vec<_>>();
+    assert_eq!(alphas, [0, 100, 20, 40, 60, 80, 0, 255]);
+}
+
+#[test]
+fn small_mips() {
+    // 8x4 at mip 0 (two blocks), then 4x2 and 2x1, a block each.
+    let mut resource = [DXT1_BLOCK; 4].concat();
+    resource[8..10].copy_from_slice(&[0x1F, 0x00]);
+    let img = image(d3dfmt::DXT1, 8, 4, 3, resource);
+
+    let (w, h, rgba) = img.to_rgba8_mip(0).unwrap();
+    assert_eq!((w, h, rgba.len()), (8, 4, 8 * 4 * 4));
+    assert_eq!(rgba[..4], RED);
+    // The second block has blue for both endpoints.
+    assert_eq!(rgba[16..20], BLUE);
+
+    let (w, h, rgba) = img.to_rgba8_mip(1).unwrap();
+    assert_eq!((w, h), (4, 2));
+    assert_eq!(rows(&rgba), [RED, BLUE]);
+
+    let (w, h, rgba) = img.to_rgba8_mip(2).unwrap();
+    assert_eq!((w, h), (2, 1));
+    assert_eq!(rgba, [RED, RED].concat());
+
+    assert!(img.to_rgba8_mip(3).is_err());
+}
+
+#[test]
+fn uncompressed() {
+    // B, G, R, A in memory.
+    let img = image(d3dfmt::A8R8G8B8, 1, 1, 1, vec![1, 2, 3, 4]);
+    assert_eq!(img.to_rgba8_mip(0).unwrap().2, [3, 2, 1, 4]);
+
+    let img = image(d3dfmt::X8R8G8B8, 1, 1, 1, vec![1, 2, 3, 4]);
+    assert_eq!(img.to_rgba8_mip(0).unwrap().2, [3, 2, 1, 255]);
+
+    let img = image(d3dfmt::A8L8, 1, 1, 1, vec![9, 200]);
+    assert_eq!(img.to_rgba8_mip(0).unwrap().2, [9, 9, 9, 200]);
+
+    // Unsupported, and truncated.
+    assert!(
+        image(d3dfmt::ATI2, 4, 4, 1, vec![0; 16])
+            .to_rgba8_mip(0)
+            .is_err()
+    );
+    assert!(
+        image(d3dfmt::DXT1, 4, 4, 1, vec![0; 7])
+            .to_rgba8_mip(0)
+            .is_err()
+    );
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn decompress_dxt_and_save_png() {
+    use common::TempFile;
+    use t5_xfile_defs::techset::decompress_dxt;
+
+    let img = image(d3dfmt::DXT1, 4, 4, 1, DXT1_BLOCK.to_vec());
+    assert_eq!(
+        decompress_dxt(&img).unwrap(),
+        img.to_rgba8_mip(0).unwrap().2
+    );
+    assert!(decompress_dxt(&image(d3dfmt::L8, 1, 1, 1, vec![0])).is_err());
+
+    let png = TempFile::empty("dxt1.png");
+    img.save_png(png.path()).unwrap();
+    assert!(
+        std::fs::read(png.path())
+            .unwrap()
+            .starts_with(b"\x89PNG\r\n\x1a\n")
+    );
+}
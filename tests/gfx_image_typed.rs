@@ -0,0 +1,138 @@
+//! Checks [`GfxImage`]'s typed semantic, category, and flag accessors, and
+//! that unknown raw values are reported and survive reserialization.
+
+mod common;
+
+use t5_xfile_defs::{
+    XString,
+    techset::{
+        GfxImage, GfxImageLoadDef, GfxTexture, ImageFlags, ImgCategory, MapType, Picmip, Semantic,
+        d3dfmt,
+    },
+    validate::{Severity, validate},
+    xasset::{XAsset, XAssetGeneric},
+};
+
+fn image(semantic: u8, category: u8, flags: u8) -> GfxImage {
+    GfxImage {
+        texture: GfxTexture::LoadDef(Some(Box::new(GfxImageLoadDef {
+            level_count: 1,
+            flags,
+            format: d3dfmt::DXT1,
+            resource: vec![0; 8],
+        }))),
+        map_type: MapType::TWO_DIMENSIONAL,
+        semantic,
+        category,
+        width: 4,
+        height: 4,
+        depth: 1,
+        level_count: 1,
+        name: XString::from("img"),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn typed_accessors() {
+    let lightmap = image(Semantic::COLOR_MAP as u8, ImgCategory::LIGHTMAP as u8, 0);
+    assert_eq!(lightmap.semantic(), Some(Semantic::COLOR_MAP));
+    assert_eq!(lightmap.category(), Some(ImgCategory::LIGHTMAP));
+    assert!(lightmap.is_lightmap());
+    assert!(!lightmap.is_ui());
+
+    let hud = image(Semantic::IDLE as u8, ImgCategory::LOAD_FROM_FILE as u8, 0);
+    assert!(hud.is_ui());
+    assert!(!hud.is_lightmap());
+
+    let unknown = image(0x7F, 0x42, 0);
+    assert_eq!(unknown.semantic(), None);
+    assert_eq!(unknown.category(), None);
+    assert!(!unknown.is_ui());
+    assert!(!unknown.is_lightmap());
+}
+
+#[test]
+fn flags() {
+    let flags = ImageFlags::NOMIPMAPS | ImageFlags::STREAMING;
+    let mut i = image(0, 0, flags.bits() | 0x80);
+    assert_eq!(i.load_flags().unwrap().bits(), flags.bits() | 0x80);
+    assert!(i.load_flags().unwrap().contains(flags));
+    assert_eq!(i.load_flags().unwrap().unknown_bits(), 0x80);
+    assert_eq!(flags.unknown_bits(), 0);
+
+    // Streaming is wanted if either the image or its load def says so.
+    assert!(i.wants_streaming());
+    assert!(!image(0, 0, 0).wants_streaming());
+    i.texture = GfxTexture::LoadDef(None);
+    assert_eq!(i.load_flags(), None);
+    assert!(!i.wants_streaming());
+    i.streaming = true;
+    assert!(i.wants_streaming());
+
+    // Picmip needs both the picmip settings and no NOPICMIP flag.
+    let mut i = image(0, 0, 0);
+    assert!(!i.can_picmip());
+    i.picmip = Some(Picmip::default());
+    assert!(i.can_picmip());
+    let mut i = image(0, 0, ImageFlags::NOPICMIP.bits());
+    i.picmip = Some(Picmip::default());
+    assert!(!i.can_picmip());
+}
+
+#[test]
+fn unknown_values_warn() {
+    let assets = [
+        XAsset::PC(XAssetGeneric::Image(Some(Box::new(image(0x02, 0x02, 0))))),
+        XAsset::PC(XAssetGeneric::Image(Some(Box::new(image(
+            0x7F, 0x42, 0x80,
+        ))))),
+    ];
+    let report = validate(&assets);
+
+    let findings = report.findings.iter().collect::<Vec<_>>();
+    assert_eq!(findings.len(), 3, "{findings:?}");
+    assert!(findings.iter().all(|f| f.severity == Severity::Warning));
+    assert_eq!(
+        findings
+            .iter()
+            .map(|f| f.field.as_str())
+            .collect::<Vec<_>>(),
+        ["semantic", "category", "texture.flags"]
+    );
+    assert!(
+        findings[0].message.contains("0x7F"),
+        "{}",
+        findings[0].message
+    );
+}
+
+#[cfg(all(feature = "serializer", feature = "deserializer"))]
+#[test]
+fn unknown_values_round_trip() {
+    use crate::common::TempFile;
+    use t5_xfile_defs::XFilePlatform;
+    use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>(
+        [image(0x7F, 0x42, 0x80)]
+            .into_iter()
+            .map(|i| XAsset::PC(XAssetGeneric::Image(Some(Box::new(i))))),
+    )
+    .unwrap();
+
+    let temp = TempFile::new("gfx_image_typed.ff", ser.deflate().unwrap());
+    let assets = common::deserialize(&temp, false);
+
+    let assets = assets.unwrap();
+    let [XAsset::PC(XAssetGeneric::Image(Some(i)))] = assets.as_slice() else {
+        panic!("expected one image");
+    };
+    assert_eq!(i.semantic, 0x7F);
+    assert_eq!(i.category, 0x42);
+    assert_eq!(i.load_flags().unwrap().bits(), 0x80);
+}
@@ -0,0 +1,179 @@
+//! Checks [`GfxWorld`]'s sky surfaces, static model BVH, lightmap atlases
+//! and geometry statistics against an outdoor level from the local corpus.
+//! See [`t5_xfile_deserializer::corpus`] for how to set it up.
+
+#![cfg(feature = "corpus")]
+
+use t5_xfile_defs::{
+    gfx_world::GfxWorld,
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::{
+    T5XFileDeserializerBuilder,
+    corpus::{corpus_dir, find_files},
+};
+
+/// An outdoor multiplayer level, so it's sure to have a sky.
+const OUTDOOR_LEVEL: &str = "mp_array.ff";
+
+/// Every asset in [`OUTDOOR_LEVEL`].
+fn outdoor_level() -> Vec<XAsset> {
+    let dir = corpus_dir().unwrap();
+    let file = find_files(&dir)
+        .unwrap()
+        .into_iter()
+        .find(|f| f.path.file_name().is_some_and(|n| n == OUTDOOR_LEVEL))
+        .unwrap_or_else(|| panic!("{OUTDOOR_LEVEL} not found in {}", dir.display()));
+
+    let mut f = std::fs::File::open(&file.path).unwrap();
+    T5XFileDeserializerBuilder::from_file(&mut f, file.platform, true)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .and_then(|de| de.deserialize_remaining())
+        .unwrap()
+}
+
+#[test]
+#[ignore = "needs a directory of Fastfiles in T5_CORPUS_DIR"]
+fn sky_materials() {
+    let mut worlds = 0;
+    for asset in &outdoor_level() {
+        let (count, materials) = match asset {
+            XAsset::PC(XAssetGeneric::GfxWorld(Some(world))) => {
+                (world.sky_surface_count(), world.all_sky_materials())
+            }
+            XAsset::Console(XAssetGeneric::GfxWorld(Some(world))) => {
+                (world.sky_surface_count(), world.all_sky_materials())
+            }
+            _ => continue,
+        };
+        worlds += 1;
+
+        assert!(count >= 1, "no sky surfaces");
+        assert!(!materials.is_empty(), "no sky materials");
+        for material in materials {
+            assert!(!material.info.name.get().is_empty());
+        }
+    }
+
+    assert_eq!(worlds, 1, "expected one GfxWorld in {OUTDOOR_LEVEL}");
+}
+
+/// Checks that a box around every so many models finds exactly what a
+/// linear scan does, and that the cached tree is reused.
+fn check_static_model_bvh<const N: usize>(world: &GfxWorld<N>) {
+    let insts = &world.dpvs.smodel_insts;
+    assert!(!insts.is_empty(), "no static models");
+
+    let node_count = world.dpvs_static_model_aabb_tree().node_count();
+    assert!(node_count > 0);
+
+    let tree = world.dpvs_static_model_aabb_tree();
+    assert_eq!(tree.node_count(), node_count);
+    for inst in insts.iter().step_by(insts.len().div_ceil(64)) {
+        let (mins, maxs) = (inst.mins.get(), inst.maxs.get());
+        let expected = (0..insts.len())
+            .filter(|&i| {
+                let (m, n) = (insts[i].mins.get(), insts[i].maxs.get());
+                (0..3).all(|a| m[a] <= maxs[a] && mins[a] <= n[a])
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(tree.query_aabb(inst.mins, inst.maxs), expected);
+    }
+}
+
+#[test]
+#[ignore = "needs a directory of Fastfiles in T5_CORPUS_DIR"]
+fn static_model_bvh() {
+    let mut worlds = 0;
+    for asset in &outdoor_level() {
+        match asset {
+            XAsset::PC(XAssetGeneric::GfxWorld(Some(world))) => check_static_model_bvh(world),
+            XAsset::Console(XAssetGeneric::GfxWorld(Some(world))) => check_static_model_bvh(world),
+            _ => continue,
+        }
+        worlds += 1;
+    }
+
+    assert_eq!(worlds, 1, "expected one GfxWorld in {OUTDOOR_LEVEL}");
+}
+
+/// Checks that every atlas has a primary image with a size, and that the
+/// lightmap coordinates of every so many surfaces point into one of them.
+fn check_lightmap_atlases<const N: usize>(world: &GfxWorld<N>) {
+    let count = world.lightmap_atlas_count();
+    assert!(count >= 1, "no lightmap atlases");
+    assert!(world.lightmap_atlas_at(count).is_err());
+
+    for i in 0..count {
+        let image = world.lightmap_atlas_at(i).unwrap().primary_image().unwrap();
+        assert!(image.width > 0 && image.height > 0, "atlas {i}");
+    }
+
+    let surfaces = world.dpvs.surfaces.len();
+    assert!(world.surface_lightmap_uv(surfaces).is_err());
+    for i in (0..surfaces).step_by(surfaces.div_ceil(64)) {
+        let (atlas, u_min, v_min, u_max, v_max) = world.surface_lightmap_uv(i).unwrap();
+        assert!((atlas as usize) < count);
+        assert!(u_min <= u_max && v_min <= v_max, "surface {i}");
+    }
+}
+
+#[test]
+#[ignore = "needs a directory of Fastfiles in T5_CORPUS_DIR"]
+fn lightmap_atlases() {
+    let mut worlds = 0;
+    for asset in &outdoor_level() {
+        match asset {
+            XAsset::PC(XAssetGeneric::GfxWorld(Some(world))) => check_lightmap_atlases(world),
+            XAsset::Console(XAssetGeneric::GfxWorld(Some(world))) => check_lightmap_atlases(world),
+            _ => continue,
+        }
+        worlds += 1;
+    }
+
+    assert_eq!(worlds, 1, "expected one GfxWorld in {OUTDOOR_LEVEL}");
+}
+
+/// Checks the geometry statistics against each other and the surfaces.
+fn check_vertex_buffer_stats<const N: usize>(world: &GfxWorld<N>) {
+    let surfaces = &world.dpvs.surfaces;
+    assert!(world.total_triangle_count() > 0);
+    assert!(world.total_vertex_count() > 0);
+
+    let by_material = world.surface_count_by_material();
+    assert_eq!(world.unique_material_count(), by_material.len());
+    assert_eq!(
+        by_material.values().sum::<usize>(),
+        surfaces.iter().filter(|s| s.material.is_some()).count()
+    );
+
+    let average = world.average_vertices_per_surface();
+    let expected = world.total_vertex_count() as f32 / surfaces.len() as f32;
+    assert!((average - expected).abs() <= expected * 1e-6);
+
+    let (index, vertex_count) = world.largest_surface().unwrap();
+    assert_eq!(surfaces[index].tris.vertex_count, vertex_count);
+    assert!(surfaces.iter().all(|s| s.tris.vertex_count <= vertex_count));
+    assert!(vertex_count as f32 >= average);
+}
+
+#[test]
+#[ignore = "needs a directory of Fastfiles in T5_CORPUS_DIR"]
+fn vertex_buffer_stats() {
+    let mut worlds = 0;
+    for asset in &outdoor_level() {
+        match asset {
+            XAsset::PC(XAssetGeneric::GfxWorld(Some(world))) => check_vertex_buffer_stats(world),
+            XAsset::Console(XAssetGeneric::GfxWorld(Some(world))) => {
+                check_vertex_buffer_stats(world)
+            }
+            _ => continue,
+        }
+        worlds += 1;
+    }
+
+    assert_eq!(worlds, 1, "expected one GfxWorld in {OUTDOOR_LEVEL}");
+}
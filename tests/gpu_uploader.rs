@@ -0,0 +1,164 @@
+//! Checks [`NullUploader`], [`byte_swapped`], and that images are handed to
+//! an attached [`GpuUploader`] as they're deserialized.
+
+mod common;
+
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    gpu::{GpuHandle, GpuUploader, NullUploader, byte_swapped},
+    techset::{GfxImage, GfxImageLoadDef, GfxTexture, MapType, d3dfmt},
+};
+
+fn image(
+    name: &str,
+    format: i32,
+    map_type: MapType,
+    level_count: u8,
+    resource: Vec<u8>,
+) -> GfxImage {
+    GfxImage {
+        texture: GfxTexture::LoadDef(Some(Box::new(GfxImageLoadDef {
+            level_count,
+            flags: 0,
+            format,
+            resource,
+        }))),
+        map_type,
+        width: 8,
+        height: 8,
+        depth: 1,
+        level_count,
+        name: XString::from(name),
+        ..Default::default()
+    }
+}
+
+/// An 8x8 DXT1 image with both mips: 4 blocks, then 1.
+fn dxt1(name: &str) -> GfxImage {
+    image(
+        name,
+        d3dfmt::DXT1,
+        MapType::TWO_DIMENSIONAL,
+        2,
+        vec![0; 5 * 8],
+    )
+}
+
+#[test]
+fn null_uploader() {
+    let mut uploader = NullUploader::new();
+    assert_eq!(uploader.upload_image(&dxt1("a")).unwrap(), GpuHandle(0));
+    assert_eq!(uploader.upload_image(&dxt1("b")).unwrap(), GpuHandle(1));
+
+    // Every face needs every mip.
+    let cube = image("sky", d3dfmt::DXT1, MapType::CUBE, 2, vec![0; 6 * 5 * 8]);
+    assert_eq!(uploader.upload_image(&cube).unwrap(), GpuHandle(2));
+    let short = image("sky", d3dfmt::DXT1, MapType::CUBE, 2, vec![0; 5 * 8]);
+    assert!(uploader.upload_image(&short).is_err());
+
+    // No pixel data yet is fine, since it's loaded later.
+    let delayed = image(
+        "delayed",
+        d3dfmt::DXT1,
+        MapType::TWO_DIMENSIONAL,
+        2,
+        Vec::new(),
+    );
+    assert_eq!(uploader.upload_image(&delayed).unwrap(), GpuHandle(3));
+
+    let e = uploader
+        .upload_image(&image(
+            "bad",
+            0x1234,
+            MapType::TWO_DIMENSIONAL,
+            1,
+            vec![0; 64],
+        ))
+        .unwrap_err();
+    assert!(
+        matches!(e.kind(), ErrorKind::UnsupportedImageFormat(0x1234)),
+        "{e}"
+    );
+
+    // Failed uploads aren't counted.
+    assert_eq!(uploader.uploaded(), 4);
+}
+
+#[test]
+fn swap() {
+    let data = [1, 2, 3, 4, 5, 6, 7, 8];
+    assert_eq!(byte_swapped(&data, 1), data);
+    assert_eq!(byte_swapped(&data, 2), [2, 1, 4, 3, 6, 5, 8, 7]);
+    assert_eq!(byte_swapped(&data, 4), [4, 3, 2, 1, 8, 7, 6, 5]);
+    assert_eq!(byte_swapped(&byte_swapped(&data, 4), 4), data);
+}
+
+#[cfg(all(feature = "serializer", feature = "deserializer"))]
+mod deserialize {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::common::TempFile;
+    use t5_xfile_defs::{
+        Result, XFilePlatform,
+        xasset::{XAsset, XAssetGeneric},
+    };
+    use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+    /// Records the name of every image it's given.
+    struct Recorder(Rc<RefCell<Vec<String>>>);
+
+    impl GpuUploader for Recorder {
+        fn upload_image(&mut self, img: &GfxImage) -> Result<GpuHandle> {
+            let mut names = self.0.borrow_mut();
+            names.push(img.name.get().to_string());
+            Ok(GpuHandle(100 + names.len() as u64))
+        }
+    }
+
+    fn deserialize(uploader: Option<Box<dyn GpuUploader>>) -> Vec<XAsset> {
+        let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+            .with_silent(true)
+            .build()
+            .unwrap();
+        ser.serialize_assets::<1>(
+            [dxt1("img_a"), dxt1("img_b")]
+                .into_iter()
+                .map(|i| XAsset::PC(XAssetGeneric::Image(Some(Box::new(i))))),
+        )
+        .unwrap();
+
+        let temp = TempFile::new(
+            &format!("gpu_uploader_{}.ff", uploader.is_some()),
+            ser.deflate().unwrap(),
+        );
+        let assets = common::deserialize_with(&temp, false, |b| b.with_gpu_uploader(uploader));
+        assets.unwrap()
+    }
+
+    fn handles(assets: &[XAsset]) -> Vec<Option<GpuHandle>> {
+        assets
+            .iter()
+            .map(|a| match a {
+                XAsset::PC(XAssetGeneric::Image(Some(i))) => i.gpu_handle,
+                _ => panic!("expected an image, got {a:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn uploaded_while_deserializing() {
+        let names = Rc::new(RefCell::new(Vec::new()));
+        let assets = deserialize(Some(Box::new(Recorder(names.clone()))));
+        assert_eq!(*names.borrow(), ["img_a", "img_b"]);
+        assert_eq!(
+            handles(&assets),
+            [Some(GpuHandle(101)), Some(GpuHandle(102))]
+        );
+    }
+
+    #[test]
+    fn no_uploader() {
+        assert_eq!(handles(&deserialize(None)), [None, None]);
+    }
+}
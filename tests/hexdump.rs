@@ -0,0 +1,102 @@
+//! Checks the deserializer's raw blob helpers.
+
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use t5_xfile_defs::{ErrorKind, XFilePlatform};
+use t5_xfile_deserializer::{T5XFileDeserializer, T5XFileDeserializerBuilder};
+
+use common::{TempFile, u32s};
+
+const WEAPON: u32 = 0x18;
+const WEAPON_VARIANT_DEF_SIZE: usize = 228;
+const INLINE: u32 = 0xFFFFFFFF;
+
+/// Where the weapon starts: past the [`XFile`] and the asset list.
+///
+/// [`XFile`]: t5_xfile_defs::XFile
+const WEAPON_START: u64 = 36 + 24;
+
+/// An inflated blob holding one weapon with only its name set.
+fn blob() -> Vec<u8> {
+    let mut data = u32s(&[0, 0, 1, INLINE, WEAPON, INLINE]);
+
+    let mut variant = vec![0u8; WEAPON_VARIANT_DEF_SIZE];
+    variant[0..4].copy_from_slice(&INLINE.to_le_bytes());
+    data.extend(variant);
+    data.extend(b"test_weapon\0");
+
+    let mut blob = u32s(&[data.len() as u32, 0, 0, 0, 0, 0, 0, 0, 0]);
+    blob.extend(data);
+    blob
+}
+
+fn with_deserializer(name: &str, f: impl FnOnce(T5XFileDeserializer)) {
+    let temp = TempFile::new(&format!("hexdump_{name}.cache"), blob());
+
+    let mut file = temp.open();
+    let de = T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .unwrap();
+    f(de);
+}
+
+#[test]
+fn read_at() {
+    with_deserializer("read_at", |mut de| {
+        let len = blob().len() as u64;
+        assert_eq!(de.read_at(len - 12, 12).unwrap(), b"test_weapon\0");
+        assert!(de.read_at(len, 0).unwrap().is_empty());
+
+        // Reading doesn't move the stream.
+        assert!(de.deserialize_next().unwrap().is_some());
+        assert_eq!(de.trailing_bytes(), 0);
+    });
+}
+
+#[test]
+fn past_eof() {
+    with_deserializer("past_eof", |de| {
+        let len = blob().len() as u64;
+        for (off, n) in [(len - 4, 5), (len, 1), (u64::MAX, 1)] {
+            let e = de.read_at(off, n).unwrap_err();
+            assert!(
+                matches!(e.kind(), ErrorKind::InvalidSeek { max, .. } if *max as u64 == len),
+                "{e}"
+            );
+            assert!(de.hexdump_at(off, n).is_err());
+        }
+    });
+}
+
+#[test]
+fn hexdump_at() {
+    with_deserializer("hexdump_at", |de| {
+        let len = blob().len() as u64;
+        assert_eq!(
+            de.hexdump_at(len - 20, 20).unwrap(),
+            "00000118: 00 00 00 00 00 00 00 00 74 65 73 74 5F 77 65 61 |........test_wea|\n\
+             00000128: 70 6F 6E 00                                     |pon.|"
+        );
+        assert_eq!(de.hexdump_at(len, 0).unwrap(), "");
+    });
+}
+
+#[test]
+fn asset_bytes() {
+    with_deserializer("asset_bytes", |mut de| {
+        let e = de.asset_bytes(0).unwrap_err();
+        assert!(matches!(e.kind(), ErrorKind::NoAssetSpan(0)), "{e}");
+        de.deserialize_next().unwrap();
+
+        let blob = blob();
+        assert_eq!(de.asset_span(0), Some((WEAPON_START, blob.len() as u64)));
+        assert_eq!(de.asset_bytes(0).unwrap(), blob[WEAPON_START as usize..]);
+        let e = de.asset_bytes(1).unwrap_err();
+        assert!(matches!(e.kind(), ErrorKind::NoAssetSpan(1)), "{e}");
+    });
+}
@@ -0,0 +1,244 @@
+//! Checks that [`to_json_writer`] writes what `serde_json` would, encodes
+//! blobs as asked, and doesn't buffer the whole export.
+
+#![cfg(feature = "json")]
+
+mod common;
+
+use std::io::{self, Write};
+
+use serde_json::Value;
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    misc::{LocalizeEntry, RawFile},
+    xasset::{LoadedAsset, Provenance, XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::export::{
+    json::{BlobEncoding, JsonLayout, JsonOptions},
+    to_json_writer,
+};
+
+use common::alloc::{CountingAlloc, peak_allocated};
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// Counts what's written to it, and throws it away.
+#[derive(Default)]
+struct CountingSink(usize);
+
+impl Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn raw_file(name: &str, buffer: Vec<u8>) -> XAsset {
+    XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+        name: XString::from(name.to_string()),
+        buffer,
+    }))))
+}
+
+fn assets() -> Vec<XAsset> {
+    vec![
+        XAsset::PC(XAssetGeneric::LocalizeEntry(Some(Box::new(
+            LocalizeEntry::new("JSON_TEST", "Quotes \" and\nnewlines"),
+        )))),
+        raw_file("json/a.cfg", b"set a 1\0".to_vec()),
+        raw_file("json/empty.cfg", Vec::new()),
+        XAsset::PC(XAssetGeneric::RawFile(None)),
+    ]
+}
+
+fn export(assets: &[XAsset], layout: JsonLayout, blobs: BlobEncoding) -> String {
+    let mut out = Vec::new();
+    to_json_writer(
+        assets,
+        &mut out,
+        JsonOptions {
+            layout,
+            blobs,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+/// The `buffer` of the raw file at `index` in a parsed export.
+fn buffer(v: &Value, index: usize) -> &Value {
+    &v[index]["PC"]["RawFile"]["buffer"]
+}
+
+#[test]
+fn matches_serde_json() {
+    let assets = assets();
+    let expected = serde_json::to_value(&assets).unwrap();
+
+    let array = export(&assets, JsonLayout::Array, BlobEncoding::Array);
+    assert_eq!(serde_json::from_str::<Value>(&array).unwrap(), expected);
+
+    let ndjson = export(&assets, JsonLayout::Ndjson, BlobEncoding::Array);
+    let lines = ndjson
+        .lines()
+        .map(|l| serde_json::from_str::<Value>(l).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(Value::Array(lines), expected);
+
+    assert_eq!(
+        export(&[], JsonLayout::Array, BlobEncoding::Array)
+            .parse::<Value>()
+            .unwrap(),
+        Value::Array(Vec::new())
+    );
+    assert_eq!(export(&[], JsonLayout::Ndjson, BlobEncoding::Array), "");
+}
+
+#[test]
+fn blobs() {
+    let assets = vec![
+        raw_file("1", b"a".to_vec()),
+        raw_file("2", b"ab".to_vec()),
+        raw_file("3", b"abc".to_vec()),
+        raw_file("4", b"set a 1\0".to_vec()),
+        raw_file("empty", Vec::new()),
+    ];
+
+    let v =
+        serde_json::from_str::<Value>(&export(&assets, JsonLayout::Array, BlobEncoding::Base64))
+            .unwrap();
+    let encoded = (0..4)
+        .map(|i| buffer(&v, i).as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(encoded, ["YQ==", "YWI=", "YWJj", "c2V0IGEgMQA="]);
+    assert_eq!(buffer(&v, 4), &Value::Array(Vec::new()));
+    assert_eq!(v[3]["PC"]["RawFile"]["name"], "4");
+
+    let v = serde_json::from_str::<Value>(&export(&assets, JsonLayout::Array, BlobEncoding::Stub))
+        .unwrap();
+    assert_eq!(buffer(&v, 3), "<8 bytes>");
+
+    // Other sequences of numbers are left alone.
+    let entry = LocalizeEntry::new("JSON_TEST", "x");
+    let v = serde_json::from_str::<Value>(&export(
+        &[XAsset::PC(XAssetGeneric::LocalizeEntry(Some(Box::new(
+            entry,
+        ))))],
+        JsonLayout::Array,
+        BlobEncoding::Base64,
+    ))
+    .unwrap();
+    assert_eq!(v[0]["PC"]["LocalizeEntry"]["value"], "x");
+}
+
+#[test]
+fn provenance() {
+    let loaded = assets()
+        .into_iter()
+        .enumerate()
+        .map(|(index, asset)| LoadedAsset {
+            asset,
+            provenance: Provenance {
+                source: "json.ff".to_string(),
+                index,
+                span: (index as u64 * 16, index as u64 * 16 + 16),
+                platform: XFilePlatform::Windows,
+            },
+        })
+        .collect::<Vec<_>>();
+    let json = |assets: &[LoadedAsset], options: JsonOptions| {
+        let mut out = Vec::new();
+        to_json_writer(assets, &mut out, options).unwrap();
+        serde_json::from_slice::<Value>(&out).unwrap()
+    };
+
+    // Off by default, so loaded assets are written like any other.
+    let plain = json(&loaded, JsonOptions::default());
+    assert_eq!(plain, serde_json::to_value(assets()).unwrap());
+
+    // On, they're written like `serde_json` writes a `LoadedAsset`.
+    let with = json(&loaded, JsonOptions::default().with_provenance(true));
+    assert_eq!(with, serde_json::to_value(&loaded).unwrap());
+    assert_eq!(with[1]["provenance"]["source"], "json.ff");
+    assert_eq!(with[1]["provenance"]["index"], 1);
+    assert_eq!(with[1]["asset"], plain[1]);
+
+    // Assets without a provenance aren't affected.
+    let mut out = Vec::new();
+    to_json_writer(
+        &assets(),
+        &mut out,
+        JsonOptions::default().with_provenance(true),
+    )
+    .unwrap();
+    assert_eq!(serde_json::from_slice::<Value>(&out).unwrap(), plain);
+}
+
+#[test]
+fn memory_is_bounded_by_largest_asset() {
+    const ASSET_SIZE: usize = 64 * 1024;
+    let assets = (0..256)
+        .map(|i| raw_file(&format!("json/{i}.bin"), vec![i as u8; ASSET_SIZE]))
+        .collect::<Vec<_>>();
+
+    // Serialized one at a time, nothing close to an asset is allocated.
+    #[cfg(not(feature = "rayon"))]
+    let bound = ASSET_SIZE;
+    // Whereas in parallel each of the four threads' assets is buffered. As
+    // JSON an array of bytes can take up to four times as much room, and
+    // growing the buffer can take three times that for a moment.
+    #[cfg(feature = "rayon")]
+    let bound = 4 * 12 * ASSET_SIZE;
+
+    let check = || {
+        for blobs in [
+            BlobEncoding::Array,
+            BlobEncoding::Base64,
+            BlobEncoding::Stub,
+        ] {
+            for layout in [JsonLayout::Array, JsonLayout::Ndjson] {
+                let mut sink = CountingSink::default();
+                let peak = peak_allocated(|| {
+                    to_json_writer(
+                        &assets,
+                        &mut sink,
+                        JsonOptions {
+                            layout,
+                            blobs,
+                            ..Default::default()
+                        },
+                    )
+                    .unwrap();
+                });
+                assert!(
+                    peak < bound,
+                    "{layout:?}, {blobs:?}: {peak} bytes allocated"
+                );
+                if blobs != BlobEncoding::Stub {
+                    assert!(sink.0 > assets.len() * ASSET_SIZE);
+                }
+            }
+        }
+
+        // Whereas building the whole string at once needs all of it.
+        let peak = peak_allocated(|| {
+            serde_json::to_string(&assets).unwrap();
+        });
+        assert!(peak > assets.len() * ASSET_SIZE);
+    };
+
+    #[cfg(not(feature = "rayon"))]
+    check();
+    #[cfg(feature = "rayon")]
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap()
+        .install(check);
+}
@@ -0,0 +1,71 @@
+//! Builds a language pack and checks that it deserializes back to the same
+//! entries, in the same order.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    XFilePlatform,
+    misc::XGlobals,
+    validate::Severity,
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::language_pack::LanguagePackBuilder;
+
+use common::TempFile;
+
+fn entries() -> Vec<(String, String)> {
+    (0..50)
+        .map(|i| (format!("MENU_ENTRY_{i}"), format!("Entry number {i}")))
+        .collect()
+}
+
+#[test]
+fn round_trip() {
+    let mut builder = LanguagePackBuilder::new(XFilePlatform::Windows)
+        .with_globals(XGlobals::default_for_patch("patch_fr"))
+        .with_prefixes(["MENU"]);
+    builder.add_all(entries()).unwrap();
+    assert!(builder.warnings().is_empty());
+
+    let bytes = builder.build().unwrap();
+    let temp = TempFile::new("language_pack.ff", bytes);
+
+    let assets = common::deserialize(&temp, false);
+    let assets = assets.unwrap();
+
+    assert_eq!(assets.len(), 51);
+    let XAsset::PC(XAssetGeneric::XGlobals(Some(globals))) = &assets[0] else {
+        panic!("expected XGlobals, got {:?}", assets[0]);
+    };
+    assert_eq!(globals.name.get(), "patch_fr");
+    assert_eq!(globals.cinematic_max_width, 1280);
+
+    for ((key, value), asset) in entries().iter().zip(&assets[1..]) {
+        let XAsset::PC(XAssetGeneric::LocalizeEntry(Some(entry))) = asset else {
+            panic!("expected LocalizeEntry, got {asset:?}");
+        };
+        assert_eq!(entry.name.get(), key);
+        assert_eq!(entry.value.get(), value);
+    }
+}
+
+#[test]
+fn warnings_and_bad_keys() {
+    let mut builder = LanguagePackBuilder::new(XFilePlatform::Windows).with_max_value_len(8);
+    builder.add("MENU_A", "short").unwrap();
+    builder.add("MENU_B", "much too long").unwrap();
+    builder.add("MENU_A", "again").unwrap();
+
+    let warnings = builder.warnings();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings.iter().all(|w| w.severity == Severity::Warning));
+    assert_eq!(warnings[0].index, 1);
+    assert_eq!(warnings[1].index, 0);
+    assert_eq!(builder.assets().len(), 2);
+
+    for key in ["menu_a", "MENU", "_MENU", "MENU_", "MENU__A", "MENU A"] {
+        assert!(builder.add(key, "x").is_err(), "{key} was accepted");
+    }
+}
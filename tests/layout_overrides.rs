@@ -0,0 +1,93 @@
+//! Checks that a weapon written with a non-retail layout only parses
+//! correctly when the deserializer is given matching [`LayoutOverrides`].
+
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use t5_xfile_defs::{
+    layout::{LayoutOverrides, RETAIL_WEAPON_XANIM_COUNT},
+    xasset::{XAsset, XAssetGeneric},
+};
+
+use common::{TempFile, u32s};
+
+const WEAPON: u32 = 0x18;
+const WEAPON_VARIANT_DEF_SIZE: usize = 228;
+const INLINE: u32 = 0xFFFFFFFF;
+
+const XANIM_COUNT: usize = RETAIL_WEAPON_XANIM_COUNT + 4;
+const PAD: usize = 8;
+
+/// An inflated blob holding one weapon with [`XANIM_COUNT`] xanims and
+/// [`PAD`] extra bytes after the [`WeaponVariantDef`].
+///
+/// [`WeaponVariantDef`]: t5_xfile_defs::weapon::WeaponVariantDef
+fn blob() -> Vec<u8> {
+    // Asset list: no script strings, one asset, and its table entry.
+    let mut data = u32s(&[0, 0, 1, INLINE, WEAPON, INLINE]);
+
+    // internal_name and xanims are inline, everything else is null.
+    let mut variant = vec![0u8; WEAPON_VARIANT_DEF_SIZE];
+    variant[0..4].copy_from_slice(&INLINE.to_le_bytes());
+    variant[16..20].copy_from_slice(&INLINE.to_le_bytes());
+    data.extend(variant);
+
+    data.extend([0xAA; PAD]);
+    data.extend(b"test_weapon\0");
+
+    // Only the last xanim (one retail doesn't have) is set.
+    let mut xanims = vec![0; XANIM_COUNT];
+    xanims[XANIM_COUNT - 1] = INLINE;
+    data.extend(u32s(&xanims));
+    data.extend(b"viewmodel_extra_anim\0");
+
+    let mut blob = u32s(&[data.len() as u32, 0, 0, 0, 0, 0, 0, 0, 0]);
+    blob.extend(data);
+    blob
+}
+
+fn deserialize(overrides: LayoutOverrides) -> t5_xfile_defs::Result<Vec<XAsset>> {
+    let temp = TempFile::new(
+        &format!("layout_overrides_{}.cache", overrides.is_retail()),
+        blob(),
+    );
+
+    common::deserialize_with(&temp, true, |b| b.with_layout_overrides(overrides))
+}
+
+#[test]
+fn with_overrides() {
+    let assets = deserialize(LayoutOverrides {
+        weapon_xanim_count: Some(XANIM_COUNT),
+        weapon_variant_def_pad: PAD,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let [XAsset::PC(XAssetGeneric::Weapon(Some(weapon)))] = assets.as_slice() else {
+        panic!("expected one weapon, got {assets:?}");
+    };
+    assert_eq!(weapon.internal_name.get(), "test_weapon");
+    assert_eq!(weapon.xanims.len(), XANIM_COUNT);
+    assert_eq!(weapon.xanims[XANIM_COUNT - 1].get(), "viewmodel_extra_anim");
+    assert!(
+        weapon.xanims[..XANIM_COUNT - 1]
+            .iter()
+            .all(|a| a.get().is_empty())
+    );
+}
+
+#[test]
+fn without_overrides() {
+    // Read as retail, the pad is taken for the name and everything after it
+    // is misaligned.
+    let parsed_correctly = deserialize(LayoutOverrides::default()).is_ok_and(|assets| {
+        matches!(
+            assets.as_slice(),
+            [XAsset::PC(XAssetGeneric::Weapon(Some(w)))]
+                if w.internal_name.get() == "test_weapon"
+        )
+    });
+    assert!(!parsed_correctly);
+}
@@ -0,0 +1,146 @@
+//! Checks [`GfxLightDef`]'s attenuation samples and lmap lookup validation.
+
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    gfx_world::{GfxLightGrid, GfxLightGridEntry},
+    light::{GfxLightDef, GfxLightImage, LmapLookupClamped},
+    techset::{GfxImage, GfxImageLoadDef, GfxTexture, MapType, d3dfmt},
+};
+
+/// A light def whose attenuation image is a `width` x 2 L8 image, with the
+/// first row falling off linearly from 255 to 0 and the second all 7s.
+fn light_def(width: u16, lmap_lookup_start: i32) -> GfxLightDef {
+    let mut resource = (0..width)
+        .map(|i| (255 - 255 * i as u32 / (width as u32 - 1)) as u8)
+        .collect::<Vec<_>>();
+    resource.extend(vec![7; width as usize]);
+
+    let image = GfxImage {
+        texture: GfxTexture::LoadDef(Some(Box::new(GfxImageLoadDef {
+            level_count: 1,
+            flags: 0,
+            format: d3dfmt::L8,
+            resource,
+        }))),
+        map_type: MapType::TWO_DIMENSIONAL,
+        width,
+        height: 2,
+        depth: 1,
+        level_count: 1,
+        ..Default::default()
+    };
+
+    GfxLightDef {
+        name: XString::from("light_point_linear".to_string()),
+        attenuation: GfxLightImage {
+            image: Some(Box::new(image)),
+            sampler_state: 0,
+        },
+        lmap_lookup_start,
+    }
+}
+
+fn light_grid(entries: usize) -> GfxLightGrid {
+    GfxLightGrid {
+        has_light_regions: false,
+        sun_primary_light_index: 1,
+        mins: [0; 3],
+        maxs: [0; 3],
+        row_axis: 0,
+        col_axis: 1,
+        row_data_start: Vec::new(),
+        raw_row_data: Vec::new(),
+        entries: vec![
+            GfxLightGridEntry {
+                colors_index: 0,
+                primary_light_index: 0,
+                needs_trace: 0,
+            };
+            entries
+        ],
+        colors: Vec::new(),
+    }
+}
+
+#[test]
+fn attenuation_samples() {
+    let def = light_def(5, 0);
+    assert_eq!(def.attenuation_sample_count(), 5);
+    assert_eq!(
+        def.attenuation_samples().unwrap(),
+        [1.0, 192.0 / 255.0, 128.0 / 255.0, 64.0 / 255.0, 0.0]
+    );
+
+    let def = GfxLightDef {
+        attenuation: GfxLightImage {
+            image: None,
+            sampler_state: 0,
+        },
+        ..def
+    };
+    assert_eq!(def.attenuation_sample_count(), 0);
+    assert!(def.attenuation_samples().unwrap().is_empty());
+}
+
+#[test]
+fn lmap_lookup_valid() {
+    let grid = light_grid(16);
+    for start in [0, 4, 12] {
+        for lenient in [false, true] {
+            let mut def = light_def(4, start);
+            assert_eq!(def.validate_lmap_lookup(&grid, lenient).unwrap(), None);
+            assert_eq!(def.lmap_lookup_start, start);
+        }
+    }
+}
+
+#[test]
+fn lmap_lookup_invalid_strict() {
+    let grid = light_grid(16);
+    for start in [-1, 13, i32::MAX] {
+        let mut def = light_def(4, start);
+        let err = def.validate_lmap_lookup(&grid, false).unwrap_err();
+        let ErrorKind::BrokenInvariant(message) = err.kind() else {
+            panic!("{err:?}");
+        };
+        assert!(
+            message.contains(&format!("({start}..{start}+4)")),
+            "{message}"
+        );
+        assert!(message.contains("(16)"), "{message}");
+        assert_eq!(def.lmap_lookup_start, start);
+    }
+}
+
+#[test]
+fn lmap_lookup_invalid_lenient() {
+    let grid = light_grid(16);
+    for (start, clamped) in [(-1, 0), (13, 12), (i32::MAX, 12)] {
+        let mut def = light_def(4, start);
+        let clamp = def.validate_lmap_lookup(&grid, true).unwrap().unwrap();
+        assert_eq!(def.lmap_lookup_start, clamped);
+        assert_eq!(
+            clamp,
+            LmapLookupClamped {
+                name: def.name.get().to_string(),
+                start,
+                clamped_start: clamped,
+                sample_count: 4,
+                grid_entries: 16,
+            }
+        );
+        let message = clamp.to_string();
+        assert!(
+            message.contains(&format!("({start}..{start}+4)")),
+            "{message}"
+        );
+        assert!(
+            message.ends_with(&format!("clamped start to {clamped}")),
+            "{message}"
+        );
+    }
+
+    // Clamping can't help if the samples don't fit at all.
+    let mut def = light_def(4, 0);
+    assert!(def.validate_lmap_lookup(&light_grid(3), true).is_err());
+}
@@ -0,0 +1,226 @@
+//! Checks the mobile localization formats in [`t5_xfile_deserializer::localize`].
+//! The XML ones are checked for well-formedness with a miniature scanner.
+
+#![cfg(feature = "localize")]
+
+use t5_xfile_defs::misc::LocalizeEntry;
+use t5_xfile_deserializer::localize::{from_android_xml, to_android_xml, to_arb, to_stringsdict};
+
+fn entries() -> Vec<LocalizeEntry> {
+    vec![
+        LocalizeEntry::new("MENU_START_GAME", "Start Game"),
+        LocalizeEntry::new("MENU_QUIT_CONFIRM", "Don't quit & lose <everything>?"),
+        LocalizeEntry::new(
+            "MPUI_PLAYERS",
+            "&&1 players, 100% \"ready\"\nback\\slash\t!",
+        ),
+        LocalizeEntry::new("MENU_AT", "@not_a_reference"),
+        LocalizeEntry::new("MENU_QUESTION", "?not_an_attribute"),
+        LocalizeEntry::new("MENU_UNICODE", "Überprüfen — ✓"),
+        LocalizeEntry::new("MENU_EMPTY", ""),
+    ]
+}
+
+/// Scans `xml` and panics if it isn't well-formed: every tag is closed in
+/// order, attribute values are quoted, `&` only starts a known entity, and
+/// there's exactly one root element.
+fn assert_well_formed(xml: &str) {
+    let mut stack = Vec::<String>::new();
+    let mut roots = 0;
+
+    let mut rest = xml;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            assert_text(rest);
+            assert!(rest.trim().is_empty(), "text after the root: {rest:?}");
+            break;
+        };
+        let text = &rest[..lt];
+        assert_text(text);
+        if stack.is_empty() {
+            assert!(text.trim().is_empty(), "text outside the root: {text:?}");
+        }
+        rest = &rest[lt..];
+
+        for (open, close) in [("<!--", "-->"), ("<?", "?>"), ("<!", ">")] {
+            if rest.starts_with(open) {
+                let end = rest.find(close).expect("unterminated markup");
+                rest = &rest[end + close.len()..];
+                break;
+            }
+        }
+        if !rest.starts_with('<') || rest.starts_with("<!") || rest.starts_with("<?") {
+            continue;
+        }
+
+        let end = rest.find('>').expect("unterminated tag");
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            assert_eq!(stack.pop().as_deref(), Some(name), "mismatched </{name}>");
+            continue;
+        }
+
+        let (tag, self_closing) = match tag.strip_suffix('/') {
+            Some(t) => (t, true),
+            None => (tag, false),
+        };
+        let (name, mut attrs) = tag.split_once(' ').unwrap_or((tag, ""));
+        assert!(
+            !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_'),
+            "bad tag name {name:?}"
+        );
+        while !attrs.trim().is_empty() {
+            let (key, value) = attrs
+                .trim_start()
+                .split_once('=')
+                .expect("attribute without =");
+            assert!(
+                !key.is_empty() && !key.contains(' '),
+                "bad attribute {key:?}"
+            );
+            let quote = value.chars().next().expect("missing attribute value");
+            assert!(quote == '"' || quote == '\'', "unquoted attribute {key}");
+            let end = value[1..].find(quote).expect("unterminated attribute") + 1;
+            assert_text(&value[1..end]);
+            assert!(!value[1..end].contains('<'));
+            attrs = &value[end + 1..];
+        }
+
+        if stack.is_empty() {
+            roots += 1;
+        }
+        if !self_closing {
+            stack.push(name.to_string());
+        }
+    }
+
+    assert!(stack.is_empty(), "unclosed {stack:?}");
+    assert_eq!(roots, 1);
+}
+
+/// Panics if `text` has a `>` or an `&` that doesn't start an entity.
+fn assert_text(text: &str) {
+    assert!(!text.contains('>'), "unescaped > in {text:?}");
+    for (i, _) in text.match_indices('&') {
+        let entity = &text[i + 1..];
+        let end = entity.find(';').expect("unterminated entity");
+        let entity = &entity[..end];
+        assert!(
+            ["amp", "lt", "gt", "quot", "apos"].contains(&entity)
+                || entity
+                    .strip_prefix('#')
+                    .is_some_and(|n| n.parse::<u32>().is_ok()),
+            "unknown entity &{entity};"
+        );
+    }
+}
+
+#[test]
+fn scanner_rejects_bad_xml() {
+    for bad in [
+        "<a><b></a></b>",
+        "<a>&</a>",
+        "<a x=1></a>",
+        "<a></a><b></b>",
+        "<a>",
+    ] {
+        assert!(
+            std::panic::catch_unwind(|| assert_well_formed(bad)).is_err(),
+            "{bad}"
+        );
+    }
+    assert_well_formed("<?xml version=\"1.0\"?>\n<!-- x -->\n<a b='c'>&amp;<d/></a>\n");
+}
+
+#[test]
+fn arb() {
+    let arb = to_arb(&entries());
+
+    assert_eq!(arb["MENU_START_GAME"], "Start Game");
+    assert_eq!(
+        arb["@MENU_START_GAME"]["description"],
+        "Referenced as @MENU_START_GAME"
+    );
+    assert_eq!(arb["MPUI_PLAYERS"], entries()[2].value.get());
+    assert_eq!(arb.as_object().unwrap().len(), entries().len() * 2);
+
+    // And it survives being written out.
+    let text = serde_json::to_string_pretty(&arb).unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&text).unwrap(),
+        arb
+    );
+}
+
+#[test]
+fn stringsdict() {
+    let xml = to_stringsdict(&entries());
+    assert_well_formed(&xml);
+
+    assert!(xml.contains("<key>MENU_QUIT_CONFIRM</key>"));
+    assert!(xml.contains("<string>Don't quit &amp; lose &lt;everything&gt;?</string>"));
+    assert!(xml.contains("100%% &quot;ready&quot;"));
+    assert_eq!(
+        xml.matches("<key>NSStringLocalizedFormatKey</key>").count(),
+        entries().len()
+    );
+}
+
+#[test]
+fn android_xml() {
+    let xml = to_android_xml(&entries());
+    assert_well_formed(&xml);
+
+    assert!(xml.contains(r#"<string name="MENU_START_GAME">Start Game</string>"#));
+    assert!(xml.contains(r"Don\'t quit &amp; lose &lt;everything&gt;?"));
+    assert!(xml.contains(r#"100% \"ready\"\nback\\slash\t!"#));
+    assert!(xml.contains(r">\@not_a_reference<"));
+    assert!(xml.contains(r">\?not_an_attribute<"));
+}
+
+#[test]
+fn android_xml_round_trip() {
+    let entries = entries();
+    let parsed = from_android_xml(&to_android_xml(&entries)).unwrap();
+
+    assert_eq!(parsed.len(), entries.len());
+    for (a, b) in parsed.iter().zip(&entries) {
+        assert_eq!(a.name.get(), b.name.get());
+        assert_eq!(a.value.get(), b.value.get());
+    }
+}
+
+#[test]
+fn android_xml_from_elsewhere() {
+    let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<!-- <string name="COMMENTED">out</string> -->
+<resources xmlns:tools="http://schemas.android.com/tools">
+    <string name='SINGLE' translatable="false">It&apos;s &#x263A; &#65;B</string>
+    <string name="EMPTY"/>
+    <plurals name="SKIPPED"><item quantity="one">x</item></plurals>
+</resources>
+"#;
+    let parsed = from_android_xml(xml).unwrap();
+
+    let pairs = parsed
+        .iter()
+        .map(|e| (e.name.get(), e.value.get()))
+        .collect::<Vec<_>>();
+    assert_eq!(pairs, [("SINGLE", "It's ☺ AB"), ("EMPTY", "")]);
+}
+
+#[test]
+fn android_xml_errors() {
+    for bad in [
+        "<resources><string>no name</string></resources>",
+        r#"<resources><string name="A">unclosed</resources>"#,
+        r#"<resources><string name="A"><b>bold</b></string></resources>"#,
+        r#"<resources><string name="A">&nbsp;</string></resources>"#,
+        r#"<resources><string name="A">\x</string></resources>"#,
+        "<!-- unterminated",
+    ] {
+        assert!(from_android_xml(bad).is_err(), "{bad}");
+    }
+}
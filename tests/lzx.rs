@@ -0,0 +1,200 @@
+//! Checks that LZX-compressed console Fastfiles are decompressed with the
+//! `lzx` feature, and rejected without it.
+//!
+//! Nothing here can LZX-compress, so the blobs are framed the way
+//! `XMemCompress` frames them but use uncompressed LZX blocks.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform, XString,
+    misc::RawFile,
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, T5XFileSerializerBuilder};
+
+use common::TempFile;
+
+/// The size of the header before the blob.
+const HEADER_SIZE: usize = 12;
+
+/// The uncompressed size of every frame but the last.
+const FRAME_SIZE: usize = 0x8000;
+
+/// A script big enough to span a few frames, with an odd length so that
+/// the block needs padding.
+fn script() -> Vec<u8> {
+    let mut buffer = (0..6000)
+        .flat_map(|i| format!("level.v{i} = {i};\n").into_bytes())
+        .collect::<Vec<_>>();
+    buffer.truncate(100_001);
+    buffer.push(0);
+    buffer
+}
+
+/// The header and the stored blob of an Xbox 360 Fastfile.
+fn fastfile() -> (Vec<u8>, Vec<u8>) {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Xbox360)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>(
+        [RawFile {
+            name: XString::from("maps/mp/lzx.gsc"),
+            buffer: script(),
+        }]
+        .into_iter()
+        .map(|f| XAsset::Console(XAssetGeneric::RawFile(Some(Box::new(f))))),
+    )
+    .unwrap();
+
+    let mut bytes = ser.deflate().unwrap();
+    assert_eq!(&bytes[..5], b"IWff0");
+    let blob = bytes.split_off(HEADER_SIZE);
+    (bytes, blob)
+}
+
+/// Wraps `data` in a single uncompressed LZX block, split into frames.
+fn lzx_frames(data: &[u8]) -> Vec<u8> {
+    // No E8 translation (1 bit), an uncompressed block (3 bits), its size
+    // (24 bits), and padding up to the next 16-bit word.
+    let bits = (0b0011 << 28) | ((data.len() as u32) << 4);
+    let mut stream = Vec::new();
+    for word in [(bits >> 16) as u16, bits as u16] {
+        stream.extend(word.to_le_bytes());
+    }
+    // R0, R1, and R2.
+    for _ in 0..3 {
+        stream.extend(1u32.to_le_bytes());
+    }
+
+    let mut out = Vec::new();
+    let frames = data.chunks(FRAME_SIZE).collect::<Vec<_>>();
+    for (i, frame) in frames.iter().enumerate() {
+        let mut chunk = if i == 0 {
+            std::mem::take(&mut stream)
+        } else {
+            Vec::new()
+        };
+        chunk.extend_from_slice(frame);
+        if i == frames.len() - 1 && !data.len().is_multiple_of(2) {
+            chunk.push(0);
+        }
+
+        if frame.len() == FRAME_SIZE {
+            out.extend((chunk.len() as u16).to_be_bytes());
+        } else {
+            out.push(0xFF);
+            out.extend((frame.len() as u16).to_be_bytes());
+            out.extend((chunk.len() as u16).to_be_bytes());
+        }
+        out.extend(chunk);
+    }
+
+    out
+}
+
+fn deserialize(
+    name: &str,
+    bytes: &[u8],
+    allow_truncated: bool,
+) -> t5_xfile_defs::Result<Vec<XAsset>> {
+    let temp = TempFile::new(&format!("lzx_{name}.ff"), bytes);
+    let mut file = temp.open();
+
+    T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Xbox360, true)
+        .with_silent(true)
+        .with_allow_truncated(allow_truncated)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .and_then(|de| de.deserialize_remaining())
+}
+
+fn check_script(assets: &[XAsset]) {
+    let [XAsset::Console(XAssetGeneric::RawFile(Some(f)))] = assets else {
+        panic!("expected one raw file, got {assets:?}");
+    };
+    assert_eq!(f.name.get(), "maps/mp/lzx.gsc");
+    assert_eq!(f.buffer, script());
+}
+
+#[test]
+fn stored() {
+    let (header, blob) = fastfile();
+    check_script(&deserialize("stored", &[header, blob].concat(), false).unwrap());
+}
+
+#[cfg(feature = "lzx")]
+#[test]
+fn compressed() {
+    let (header, blob) = fastfile();
+    assert!(blob.len() > 3 * FRAME_SIZE);
+    let ff = [header, lzx_frames(&blob)].concat();
+    check_script(&deserialize("compressed", &ff, false).unwrap());
+}
+
+#[cfg(feature = "lzx")]
+#[test]
+fn corrupt() {
+    let (header, blob) = fastfile();
+    let frames = lzx_frames(&blob);
+
+    // Cut off partway through the second frame.
+    let cut = [header.clone(), frames[..FRAME_SIZE + 100].to_vec()].concat();
+    let e = deserialize("cut", &cut, false).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::Lzx(_)), "{e}");
+
+    // An invalid block type, in the high byte of the first word after the
+    // frame's size.
+    let mut bad = frames;
+    bad[3] |= 0x70;
+    let e = deserialize("bad_block", &[header, bad].concat(), false).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::Lzx(_)), "{e}");
+}
+
+#[cfg(not(feature = "lzx"))]
+#[test]
+fn unsupported() {
+    let (header, blob) = fastfile();
+    let e = deserialize("unsupported", &[header, lzx_frames(&blob)].concat(), false).unwrap_err();
+    assert!(
+        matches!(e.kind(), ErrorKind::UnsupportedCompression(b'0')),
+        "{e}"
+    );
+}
+
+/// The blob `bytes` inflates to, allowing truncation.
+fn inflate_truncated(name: &str, bytes: &[u8]) -> Vec<u8> {
+    let temp = TempFile::new(&format!("lzx_{name}.ff"), bytes);
+    let mut file = temp.open();
+    let blob = T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Xbox360, true)
+        .with_silent(true)
+        .with_allow_truncated(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .map(|de| de.blob().to_vec());
+    blob.unwrap()
+}
+
+#[test]
+fn stored_cut() {
+    let (header, blob) = fastfile();
+    let cut = [header, blob[..FRAME_SIZE].to_vec()].concat();
+    assert_eq!(inflate_truncated("stored_cut", &cut), blob[..FRAME_SIZE]);
+}
+
+#[cfg(feature = "lzx")]
+#[test]
+fn compressed_cut() {
+    // Short enough to pass for a truncated stored blob, but it's LZX.
+    let (header, blob) = fastfile();
+    let frames = lzx_frames(&blob);
+    let cut = [header, frames[..FRAME_SIZE + 100].to_vec()].concat();
+    assert_eq!(
+        inflate_truncated("compressed_cut", &cut),
+        blob[..FRAME_SIZE]
+    );
+}
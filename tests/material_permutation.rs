@@ -0,0 +1,145 @@
+//! Checks [`Material::permutation_key`] and the slot mapping behind it.
+
+use t5_xfile_defs::{
+    XString,
+    techset::{
+        FogType, LitType, Material, MaterialPermutationKey, MaterialTechnique,
+        MaterialTechniqueSet, MaterialTextureDef, Semantic, ShadowType, SpecularType,
+        TechniqueType,
+    },
+};
+
+const LIT_TYPES: [LitType; 3] = [LitType::OpaqueLit, LitType::TransparentLit, LitType::Unlit];
+const FOG_TYPES: [FogType; 3] = [FogType::Linear, FogType::Exponential, FogType::None];
+const SHADOW_TYPES: [ShadowType; 3] = [ShadowType::None, ShadowType::Depth, ShadowType::Pcf];
+const SPECULAR_TYPES: [SpecularType; 3] =
+    [SpecularType::None, SpecularType::Blinn, SpecularType::Ggx];
+
+fn all_keys() -> impl Iterator<Item = MaterialPermutationKey> {
+    LIT_TYPES.into_iter().flat_map(|lit_type| {
+        FOG_TYPES.into_iter().flat_map(move |fog_type| {
+            SHADOW_TYPES.into_iter().flat_map(move |shadow_type| {
+                SPECULAR_TYPES
+                    .into_iter()
+                    .map(move |specular_type| MaterialPermutationKey {
+                        lit_type,
+                        fog_type,
+                        shadow_type,
+                        specular_type,
+                    })
+            })
+        })
+    })
+}
+
+fn material(camera_region: u8, types: &[TechniqueType], specular_map: bool) -> Material {
+    let mut techniques = vec![None; 130];
+    for ty in types {
+        techniques[ty.slot()] = Some(Box::new(MaterialTechnique {
+            name: XString::from(ty.name().to_string()),
+            flags: 0,
+            passes: Vec::new(),
+        }));
+    }
+
+    let textures = specular_map
+        .then(|| MaterialTextureDef {
+            semantic: Semantic::SPECULAR_MAP,
+            ..Default::default()
+        })
+        .into_iter()
+        .collect();
+
+    Material {
+        camera_region,
+        textures,
+        technique_set: Some(Box::new(MaterialTechniqueSet {
+            name: XString::from("test".to_string()),
+            world_vert_format: 0,
+            techset_flags: 0,
+            techniques,
+        })),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn round_trip() {
+    for key in all_keys() {
+        let slot = key.technique_set_slot_index();
+        let back = MaterialPermutationKey::from_slot_index(slot).unwrap();
+        assert_eq!(back.technique_set_slot_index(), slot, "{key:?}");
+
+        // Only lighting and shadows are encoded in the slot.
+        if key.fog_type == FogType::None
+            && key.specular_type == SpecularType::None
+            && (key.lit_type != LitType::Unlit || key.shadow_type == ShadowType::None)
+        {
+            assert_eq!(back, key);
+        }
+    }
+}
+
+#[test]
+fn slots() {
+    let key = |lit_type, shadow_type| MaterialPermutationKey {
+        lit_type,
+        fog_type: FogType::None,
+        shadow_type,
+        specular_type: SpecularType::None,
+    };
+
+    assert_eq!(
+        key(LitType::OpaqueLit, ShadowType::Depth).technique_type(),
+        TechniqueType::LIT_SUN_SHADOW
+    );
+    assert_eq!(
+        key(LitType::TransparentLit, ShadowType::Pcf).technique_type(),
+        TechniqueType::LIT_SPOT_SHADOW_FADE
+    );
+    assert_eq!(
+        key(LitType::Unlit, ShadowType::Depth).technique_set_slot_index(),
+        TechniqueType::UNLIT.slot()
+    );
+
+    assert!(MaterialPermutationKey::from_slot_index(TechniqueType::DEPTH_PREPASS.slot()).is_none());
+    assert!(MaterialPermutationKey::from_slot_index(130).is_none());
+}
+
+#[test]
+fn permutation_key() {
+    use TechniqueType as T;
+
+    let opaque = material(0, &[T::DEPTH_PREPASS, T::LIT_SUN, T::LIT_SUN_SHADOW], true);
+    let key = opaque.permutation_key();
+    assert_eq!(key.lit_type, LitType::OpaqueLit);
+    assert_eq!(key.shadow_type, ShadowType::Depth);
+    assert_eq!(key.specular_type, SpecularType::Blinn);
+    assert_eq!(key.fog_type, FogType::None);
+    assert!(opaque.has_technique_for(key));
+
+    let glass = material(1, &[T::LIT_SPOT_SHADOW_FADE], false);
+    let key = glass.permutation_key();
+    assert_eq!(key.lit_type, LitType::TransparentLit);
+    assert_eq!(key.shadow_type, ShadowType::Pcf);
+    assert_eq!(key.specular_type, SpecularType::None);
+    assert!(glass.has_technique_for(key));
+
+    let effect = material(2, &[T::UNLIT], true);
+    let key = effect.permutation_key();
+    assert_eq!(key.lit_type, LitType::Unlit);
+    assert_eq!(key.specular_type, SpecularType::None);
+    assert!(effect.has_technique_for(key));
+    assert!(!effect.has_technique_for(opaque.permutation_key()));
+
+    // Without a technique set, only the camera region is left to go by.
+    let no_techset = Material {
+        camera_region: 1,
+        ..Default::default()
+    };
+    assert_eq!(
+        no_techset.permutation_key().lit_type,
+        LitType::TransparentLit
+    );
+    assert!(!no_techset.has_technique_for(no_techset.permutation_key()));
+}
@@ -0,0 +1,101 @@
+//! Checks the decoded material sort keys and camera regions, and
+//! [`sort_materials_for_draw`].
+
+use t5_xfile_defs::{
+    XString,
+    techset::{
+        CameraRegion, Material, MaterialInfo, MaterialSortClass, MaterialSortKey,
+        sort_materials_for_draw,
+    },
+};
+
+fn material(name: &str, camera_region: u8, sort_key: u8) -> Material {
+    Material {
+        info: MaterialInfo {
+            name: XString::from(name),
+            sort_key,
+            ..Default::default()
+        },
+        camera_region,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn round_trip() {
+    for v in 0..=u8::MAX {
+        assert_eq!(u8::from(MaterialSortKey::from(v)), v);
+        assert_eq!(u8::from(CameraRegion::from(v)), v);
+    }
+
+    assert_eq!(MaterialSortKey::from(2), MaterialSortKey::Sky);
+    assert_eq!(MaterialSortKey::from(7), MaterialSortKey::DecalStatic);
+    assert_eq!(MaterialSortKey::from(27), MaterialSortKey::EffectAutoSort);
+    assert_eq!(MaterialSortKey::from(31), MaterialSortKey::ViewmodelEffect);
+    assert_eq!(MaterialSortKey::from(32), MaterialSortKey::Other(32));
+    assert_eq!(CameraRegion::from(3), CameraRegion::DepthHack);
+    assert_eq!(CameraRegion::from(4), CameraRegion::None);
+    assert_eq!(CameraRegion::from(7), CameraRegion::Other(7));
+}
+
+#[test]
+fn sort_class() {
+    let class = |region, key| material("m", region, key).sort_class();
+
+    assert_eq!(class(0, 0), MaterialSortClass::Opaque);
+    assert_eq!(class(0, 1), MaterialSortClass::Opaque);
+    assert_eq!(class(0, 2), MaterialSortClass::Sky);
+    assert_eq!(class(0, 3), MaterialSortClass::Sky);
+    assert_eq!(class(0, 40), MaterialSortClass::Opaque);
+    // The sort key only matters in the opaque list.
+    assert_eq!(class(1, 2), MaterialSortClass::Transparent);
+    assert_eq!(class(2, 0), MaterialSortClass::Emissive);
+    assert_eq!(class(3, 0), MaterialSortClass::DepthHack);
+    assert_eq!(class(4, 0), MaterialSortClass::Unsorted);
+    assert_eq!(class(9, 0), MaterialSortClass::Unsorted);
+
+    let m = material("m", 1, 12);
+    assert_eq!(m.region(), CameraRegion::LitTrans);
+    assert_eq!(m.sort_key(), MaterialSortKey::DecalTop1);
+}
+
+#[test]
+fn draw_order() {
+    let materials = [
+        material("viewmodel", 3, 1),
+        material("glass", 1, 20),
+        material("hidden", 4, 0),
+        material("wall_b", 0, 1),
+        material("sky", 0, 2),
+        material("smoke", 1, 8),
+        material("wall_a", 0, 1),
+        material("ambient", 0, 0),
+        material("glow", 2, 0),
+    ];
+    let refs = materials.iter().collect::<Vec<_>>();
+
+    let sorted = sort_materials_for_draw(&refs);
+    assert_eq!(
+        sorted.iter().map(|m| m.info.name.get()).collect::<Vec<_>>(),
+        [
+            "ambient",
+            // Equal keys keep their order.
+            "wall_b",
+            "wall_a",
+            "sky",
+            "smoke",
+            "glass",
+            "glow",
+            "viewmodel",
+            "hidden",
+        ]
+    );
+
+    // The classes come out in the order they're drawn.
+    assert!(
+        sorted
+            .windows(2)
+            .all(|w| w[0].sort_class() <= w[1].sort_class())
+    );
+    assert!(sort_materials_for_draw(&[]).is_empty());
+}
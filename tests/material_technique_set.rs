@@ -0,0 +1,205 @@
+//! Serializes a material with a technique set and checks that it
+//! deserializes back to the same data.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform, XString,
+    techset::{
+        GfxPixelShaderLoadDef, GfxVertexShaderLoadDef, Material, MaterialArgumentCodeConst,
+        MaterialArgumentDef, MaterialInfo, MaterialPass, MaterialPixelShader,
+        MaterialPixelShaderProgram, MaterialShaderArgument, MaterialStreamRouting,
+        MaterialTechnique, MaterialTechniqueSet, MaterialVertexDeclaration, MaterialVertexShader,
+        MaterialVertexShaderProgram, MtlArg, TechniqueType,
+    },
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, T5XFileSerializerBuilder};
+
+use common::TempFile;
+
+fn pass() -> MaterialPass {
+    let mut vertex_decl = MaterialVertexDeclaration::default();
+    vertex_decl.stream_count = 2;
+    vertex_decl.has_optional_source = true;
+    vertex_decl.routing.data[1] = MaterialStreamRouting { source: 1, data: 3 };
+    vertex_decl.routing.decl[0] = 0xDEADBEEF;
+
+    MaterialPass {
+        vertex_decl: Some(Box::new(vertex_decl)),
+        vertex_shader: Some(Box::new(MaterialVertexShader {
+            name: XString::from("lit_vs"),
+            prog: MaterialVertexShaderProgram {
+                vs: None,
+                load_def: GfxVertexShaderLoadDef {
+                    program: vec![0xFFFE0300, 1, 2, 0x0000FFFF],
+                },
+            },
+        })),
+        pixel_shader: Some(Box::new(MaterialPixelShader {
+            name: XString::from("lit_ps"),
+            prog: MaterialPixelShaderProgram {
+                ps: None,
+                load_def: GfxPixelShaderLoadDef {
+                    program: vec![0xFFFF0300, 4, 0x0000FFFF],
+                },
+            },
+        })),
+        per_prim_arg_count: 1,
+        per_obj_arg_count: 1,
+        stable_arg_count: 2,
+        custom_sampler_flags: 0x2,
+        args: vec![
+            MaterialShaderArgument {
+                arg_type: MtlArg::CODE_VERTEX_CONST,
+                dest: 0,
+                u: MaterialArgumentDef::CodeConst(MaterialArgumentCodeConst {
+                    index: 12,
+                    first_row: 0,
+                    row_count: 4,
+                }),
+            },
+            MaterialShaderArgument {
+                arg_type: MtlArg::LITERAL_PIXEL_CONST,
+                dest: 3,
+                u: MaterialArgumentDef::LiteralConst([0.5, 1.0, 2.0, 4.0].into()),
+            },
+            MaterialShaderArgument {
+                arg_type: MtlArg::MATERIAL_PIXEL_SAMPLER,
+                dest: 1,
+                u: MaterialArgumentDef::NameHash(0xA0AB1041),
+            },
+            MaterialShaderArgument {
+                arg_type: MtlArg::CODE_PIXEL_SAMPLER,
+                dest: 2,
+                u: MaterialArgumentDef::CodeSampler(9),
+            },
+        ],
+    }
+}
+
+fn technique_set() -> MaterialTechniqueSet {
+    let mut techniques = vec![None; 130];
+    techniques[TechniqueType::DEPTH_PREPASS as usize] = Some(Box::new(MaterialTechnique {
+        name: XString::from("zprepass"),
+        flags: 0,
+        passes: Vec::new(),
+    }));
+    techniques[TechniqueType::LIT as usize] = Some(Box::new(MaterialTechnique {
+        name: XString::from("lit"),
+        flags: 0x10,
+        passes: vec![
+            pass(),
+            MaterialPass {
+                vertex_decl: None,
+                vertex_shader: None,
+                pixel_shader: None,
+                args: Vec::new(),
+                per_prim_arg_count: 0,
+                per_obj_arg_count: 0,
+                stable_arg_count: 0,
+                ..pass()
+            },
+        ],
+    }));
+
+    MaterialTechniqueSet {
+        name: XString::from("mc_lit_sm"),
+        world_vert_format: 3,
+        techset_flags: 0x4,
+        techniques,
+    }
+}
+
+fn material(technique_set: MaterialTechniqueSet) -> Material {
+    Material {
+        info: MaterialInfo {
+            name: XString::from("mc/mtl_techset"),
+            ..Default::default()
+        },
+        technique_set: Some(Box::new(technique_set)),
+        ..Default::default()
+    }
+}
+
+fn serialize(material: Material) -> t5_xfile_defs::Result<Vec<u8>> {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()?;
+    ser.serialize_assets::<1>(
+        [XAsset::PC(XAssetGeneric::Material(Some(Box::new(
+            material,
+        ))))]
+        .into_iter(),
+    )?;
+    ser.deflate()
+}
+
+#[test]
+fn round_trip() {
+    let expected = material(technique_set());
+    let bytes = serialize(expected.clone()).unwrap();
+
+    let temp = TempFile::new("material_technique_set.ff", bytes);
+    let assets = common::deserialize(&temp, false);
+    let assets = assets.unwrap();
+
+    let [XAsset::PC(XAssetGeneric::Material(Some(material)))] = assets.as_slice() else {
+        panic!("expected one material, got {assets:?}");
+    };
+    assert_eq!(format!("{material:?}"), format!("{expected:?}"));
+
+    let lit = material.technique_for(TechniqueType::LIT).unwrap();
+    assert_eq!(lit.shader_names(), ("lit_vs", "lit_ps"));
+    assert_eq!(lit.passes[0].args.len(), 4);
+    assert!(material.technique_for(TechniqueType::UNLIT).is_none());
+}
+
+#[test]
+fn mismatched_argument() {
+    let mut set = technique_set();
+    let lit = set.techniques[TechniqueType::LIT as usize]
+        .as_mut()
+        .unwrap();
+    lit.passes[0].args[1].u = MaterialArgumentDef::NameHash(1);
+
+    let e = serialize(material(set)).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+}
+
+#[test]
+fn too_many_techniques() {
+    let mut set = technique_set();
+    set.techniques.push(None);
+
+    let e = serialize(material(set)).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+}
+
+#[test]
+fn broken_technique() {
+    let bytes = serialize(material(technique_set())).unwrap();
+    let temp = TempFile::new("material_technique_set_broken.ff", bytes);
+    let mut file = temp.open();
+    let mut blob = T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .unwrap()
+        .blob()
+        .to_vec();
+
+    // The lit technique's inline name, flags, and pass count. Give it far
+    // more passes than the blob holds.
+    let header = [0xFF, 0xFF, 0xFF, 0xFF, 0x10, 0x00, 0x02, 0x00];
+    let pos = blob.windows(8).position(|w| w == header).unwrap();
+    blob[pos + 6..pos + 8].copy_from_slice(&u16::MAX.to_le_bytes());
+
+    let temp = TempFile::new("material_technique_set_broken.cache", blob);
+    let result = common::deserialize(&temp, true);
+
+    // Not a material with the technique missing.
+    assert!(result.is_err(), "{result:?}");
+}
@@ -0,0 +1,121 @@
+//! Serializes a water material and a plain one and checks that they
+//! deserialize back to the same data.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    techset::{
+        Complex, GfxStateBits, Material, MaterialConstantDef, MaterialInfo, MaterialTextureDef,
+        MaterialTextureDefInfo, Semantic, Water,
+    },
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+use common::TempFile;
+
+fn material(name: &str, texture: MaterialTextureDef) -> Material {
+    Material {
+        info: MaterialInfo {
+            name: XString::from(name.to_string()),
+            game_flags: 0x40,
+            sort_key: 7,
+            ..Default::default()
+        },
+        textures: vec![texture],
+        constants: vec![MaterialConstantDef {
+            name_hash: 0x1234,
+            name: *b"waterColor\0\0",
+            literal: [0.1, 0.2, 0.3, 1.0],
+        }],
+        state_bits: vec![GfxStateBits {
+            load_bits: [0x1, 0x2],
+        }],
+        ..Default::default()
+    }
+}
+
+fn water() -> Water {
+    Water {
+        h0: (0..4)
+            .map(|i| Complex {
+                real: i as f32,
+                imag: -(i as f32),
+            })
+            .collect(),
+        w_term: vec![0.5, 1.5, 2.5, 3.5],
+        m: 2,
+        n: 2,
+        lx: 64.0,
+        ly: 32.0,
+        gravity: 9.8,
+        windvel: 12.0,
+        winddir: [1.0, 0.0].into(),
+        amplitude: 0.75,
+        code_constant: [1.0, 2.0, 3.0, 4.0].into(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn round_trip() {
+    let water = material(
+        "mc/water_lake",
+        MaterialTextureDef {
+            name_hash: 0xABCD,
+            name_start: 'w',
+            name_end: 'p',
+            semantic: Semantic::WATER_MAP,
+            u: MaterialTextureDefInfo::Water(Some(Box::new(water()))),
+            ..Default::default()
+        },
+    );
+    let plain = material(
+        "mc/plain",
+        MaterialTextureDef {
+            name_hash: 0xBEEF,
+            name_start: 'c',
+            name_end: 'p',
+            semantic: Semantic::COLOR_MAP,
+            ..Default::default()
+        },
+    );
+    let materials = [water, plain];
+
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>(
+        materials
+            .iter()
+            .map(|m| XAsset::PC(XAssetGeneric::Material(Some(Box::new(m.clone()))))),
+    )
+    .unwrap();
+    let bytes = ser.deflate().unwrap();
+
+    let temp = TempFile::new("material_water.ff", bytes);
+
+    let assets = common::deserialize(&temp, false);
+    let assets = assets.unwrap();
+
+    assert_eq!(assets.len(), materials.len());
+    for (expected, asset) in materials.iter().zip(&assets) {
+        let XAsset::PC(XAssetGeneric::Material(Some(material))) = asset else {
+            panic!("expected Material, got {asset:?}");
+        };
+        assert_eq!(format!("{material:?}"), format!("{expected:?}"));
+    }
+
+    let XAsset::PC(XAssetGeneric::Material(Some(material))) = &assets[0] else {
+        unreachable!()
+    };
+    let MaterialTextureDefInfo::Water(Some(water)) = &material.textures[0].u else {
+        panic!("expected water, got {:?}", material.textures[0].u);
+    };
+    assert_eq!(water.h0.len(), 4);
+    assert_eq!(water.w_term, [0.5, 1.5, 2.5, 3.5]);
+}
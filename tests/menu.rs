@@ -0,0 +1,221 @@
+//! Serializes a menu with an item of each type and checks that it
+//! deserializes back to the same data.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    menu::{
+        EditFieldDef, EnumDvarDef, ExpressionRpn, ExpressionRpnDataUnion, ExpressionStatement,
+        FocusDefData, FocusItemDef, GameMsgDef, ImageDef, ItemDef, ItemDefData, ListBoxDef,
+        MenuCell, MenuDef, MenuRow, MultiDef, Operand, OperandInternalDataUnion, OwnerDrawDef,
+        TextDef, TextDefData, WindowDef,
+    },
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+use common::TempFile;
+
+fn s(s: &str) -> XString {
+    XString::from(s.to_string())
+}
+
+fn window(name: &str) -> WindowDef<1> {
+    WindowDef {
+        name: s(name),
+        fore_color: [1.0, 1.0, 1.0, 1.0].into(),
+        ..Default::default()
+    }
+}
+
+fn item(name: &str, type_: i32, type_data: Option<ItemDefData<1>>) -> Box<ItemDef<1>> {
+    Box::new(ItemDef {
+        window: window(name),
+        type_,
+        type_data,
+        ..Default::default()
+    })
+}
+
+fn text(text_type_data: Option<TextDefData<1>>) -> Option<ItemDefData<1>> {
+    Some(ItemDefData::TextDef(Some(Box::new(TextDef {
+        text: s("@MENU_TEXT"),
+        textscale: 0.5,
+        text_type_data,
+        ..Default::default()
+    }))))
+}
+
+fn focus(focus_type_data: FocusDefData<1>) -> Option<ItemDefData<1>> {
+    text(Some(TextDefData::FocusItemDef(Some(Box::new(
+        FocusItemDef {
+            mouse_enter: s("play mouse_over"),
+            focus_type_data: Some(focus_type_data),
+            ..Default::default()
+        },
+    )))))
+}
+
+fn rpn(type_: i32, data: Option<ExpressionRpnDataUnion>) -> ExpressionRpn {
+    ExpressionRpn { type_, data }
+}
+
+fn constant(internals: OperandInternalDataUnion) -> ExpressionRpn {
+    rpn(
+        0,
+        Some(ExpressionRpnDataUnion::Constant(Operand { internals })),
+    )
+}
+
+fn menu() -> MenuDef<1> {
+    let list_box = ListBoxDef {
+        num_columns: 2,
+        rows: vec![
+            MenuRow {
+                cells: vec![
+                    MenuCell {
+                        type_: 0,
+                        max_chars: 16,
+                        string_value: s("first"),
+                    },
+                    MenuCell {
+                        type_: 0,
+                        max_chars: 16,
+                        string_value: s("second"),
+                    },
+                ],
+                event_name: s("row_selected"),
+                ..Default::default()
+            },
+            MenuRow::default(),
+        ],
+        ..Default::default()
+    };
+    let multi = MultiDef {
+        dvar_list: core::array::from_fn(|i| if i < 2 { s(&format!("opt{i}")) } else { s("") }),
+        dvar_str: core::array::from_fn(|i| if i < 2 { s(&format!("val{i}")) } else { s("") }),
+        count: 2,
+        action_on_press_enter_only: true,
+        ..Default::default()
+    };
+    let material_exp = ExpressionStatement {
+        filename: s("ui/main.menu"),
+        line: 42,
+        rpn: vec![
+            constant(OperandInternalDataUnion::String(s("menu_background"))),
+            constant(OperandInternalDataUnion::Int(-3)),
+            constant(OperandInternalDataUnion::Float(0.25)),
+            rpn(1, Some(ExpressionRpnDataUnion::CmdIdx(57))),
+            rpn(3, None),
+        ],
+    };
+
+    MenuDef {
+        window: window("main"),
+        font: s("fonts/normalFont"),
+        full_screen: true,
+        focus_color: [0.5, 0.5, 1.0, 1.0].into(),
+        items: vec![
+            item("text", 1, text(None)),
+            item(
+                "image",
+                2,
+                Some(ItemDefData::ImageDef(Some(Box::new(ImageDef {
+                    material_exp,
+                })))),
+            ),
+            item(
+                "list_box",
+                4,
+                focus(FocusDefData::ListBox(Some(Box::new(list_box)))),
+            ),
+            item(
+                "edit_field",
+                5,
+                focus(FocusDefData::EditField(Some(Box::new(EditFieldDef {
+                    max_chars: 32,
+                    ..Default::default()
+                })))),
+            ),
+            item(
+                "owner_draw",
+                6,
+                Some(ItemDefData::OwnerDrawDef(Some(Box::new(
+                    OwnerDrawDef::default(),
+                )))),
+            ),
+            item(
+                "multi",
+                10,
+                focus(FocusDefData::Multi(Some(Box::new(multi)))),
+            ),
+            item(
+                "enum_dvar",
+                11,
+                focus(FocusDefData::EnumDvar(Some(Box::new(EnumDvarDef {
+                    enum_dvar_name: s("ui_gametype"),
+                })))),
+            ),
+            item(
+                "game_msg",
+                15,
+                text(Some(TextDefData::GameMsgDef(Some(Box::new(GameMsgDef {
+                    game_msg_window_index: 1,
+                    game_msg_window_mode: 2,
+                }))))),
+            ),
+            item(
+                "blank_button",
+                21,
+                Some(ItemDefData::BlankButtonDef(Some(Box::new(FocusItemDef {
+                    mouse_exit: s("play mouse_exit"),
+                    ..Default::default()
+                })))),
+            ),
+        ],
+        ..Default::default()
+    }
+}
+
+fn serialize(menu: MenuDef<1>) -> t5_xfile_defs::Result<Vec<u8>> {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()?;
+    ser.serialize_assets::<1>([XAsset::PC(XAssetGeneric::Menu(Some(Box::new(menu))))].into_iter())?;
+    ser.deflate()
+}
+
+#[test]
+fn round_trip() {
+    let menu = menu();
+    let bytes = serialize(menu.clone()).unwrap();
+
+    let temp = TempFile::new("menu.ff", bytes);
+    let assets = common::deserialize(&temp, false);
+    let assets = assets.unwrap();
+
+    let [XAsset::PC(XAssetGeneric::Menu(Some(m)))] = assets.as_slice() else {
+        panic!("expected one menu, got {assets:?}");
+    };
+    assert_eq!(format!("{m:?}"), format!("{menu:?}"));
+
+    let Some(ItemDefData::ImageDef(Some(image))) = &m.items[1].type_data else {
+        panic!("expected an image, got {:?}", m.items[1].type_data);
+    };
+    assert!(matches!(
+        image.material_exp.rpn[3].data,
+        Some(ExpressionRpnDataUnion::CmdIdx(57))
+    ));
+}
+
+#[test]
+fn mismatched_type() {
+    let mut menu = menu();
+    // Type 2 is an image, not text.
+    menu.items[0].type_ = 2;
+
+    assert!(serialize(menu).is_err());
+}
@@ -0,0 +1,128 @@
+//! Checks the localize key and script extraction helpers on menus.
+
+use std::collections::BTreeSet;
+
+use t5_xfile_defs::{
+    XString,
+    menu::{
+        ExpressionStatement, FocusItemDef, GenericEventHandler, GenericEventScript, ItemDef,
+        ItemDefData, MenuDef, TextDef, TextDefData, WindowDef,
+    },
+};
+
+fn s(s: &str) -> XString {
+    XString::from(s.to_string())
+}
+
+fn script(action: &str, next: Option<Box<GenericEventScript>>) -> Option<Box<GenericEventScript>> {
+    Some(Box::new(GenericEventScript {
+        prerequisites: None,
+        condition: ExpressionStatement::default(),
+        type_: 0,
+        fire_on_true: false,
+        action: s(action),
+        block_id: 0,
+        construct_id: 0,
+        next,
+    }))
+}
+
+fn item(text: &str, focus: Option<FocusItemDef<1>>) -> Box<ItemDef<1>> {
+    Box::new(ItemDef {
+        window: WindowDef {
+            name: s(text),
+            ..Default::default()
+        },
+        type_: 1,
+        type_data: Some(ItemDefData::TextDef(Some(Box::new(TextDef {
+            text: s(text),
+            text_type_data: focus.map(|f| TextDefData::FocusItemDef(Some(Box::new(f)))),
+            ..Default::default()
+        })))),
+        ..Default::default()
+    })
+}
+
+fn menu() -> MenuDef<1> {
+    let mut button = item(
+        "@MENU_START_GAME",
+        Some(FocusItemDef {
+            mouse_enter_text: s("@MENU_START_GAME_DESC"),
+            mouse_enter: s("play mouse_over"),
+            ..Default::default()
+        }),
+    );
+    button.on_event = Some(Box::new(GenericEventHandler {
+        name: s("onFocus"),
+        event_script: script("play mouse_click", script("", None)),
+        next: Some(Box::new(GenericEventHandler {
+            name: s("action"),
+            event_script: script("open @MENU_START_GAME", None),
+            next: None,
+        })),
+    }));
+
+    MenuDef {
+        items: vec![item("@MENU_TITLE", None), button, item("Version 1.0", None)],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn localize_keys() {
+    let menu = MenuDef {
+        items: vec![
+            item("@MENU_TITLE", None),
+            item("@MENU_SUBTITLE", None),
+            item("Version 1.0", None),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(menu.items[0].localize_keys(), ["MENU_TITLE"]);
+    assert!(menu.items[2].localize_keys().is_empty());
+    assert_eq!(
+        menu.all_localize_keys_recursive(),
+        BTreeSet::from(["MENU_SUBTITLE".to_string(), "MENU_TITLE".to_string()])
+    );
+}
+
+#[test]
+fn focus_item_localize_keys() {
+    let menu = menu();
+    assert_eq!(
+        menu.items[1].localize_keys(),
+        ["MENU_START_GAME", "MENU_START_GAME_DESC"]
+    );
+
+    // Keys inside scripts, like `open @MENU_START_GAME`, aren't picked up.
+    let keys = menu.all_localize_keys_recursive();
+    assert_eq!(keys.len(), 3);
+}
+
+#[test]
+fn script_actions() {
+    let menu = menu();
+    assert_eq!(
+        menu.items[1].script_actions(),
+        [
+            "play mouse_click",
+            "open @MENU_START_GAME",
+            "play mouse_over"
+        ]
+    );
+    assert!(menu.items[0].script_actions().is_empty());
+}
+
+#[test]
+fn validate_localize_keys() {
+    let menu = menu();
+    let valid = BTreeSet::from(["MENU_TITLE", "MENU_START_GAME", "MENU_UNUSED"]);
+    assert_eq!(
+        menu.validate_localize_keys(&valid),
+        ["MENU_START_GAME_DESC"]
+    );
+
+    let valid = menu.all_localize_keys_recursive();
+    let valid = valid.iter().map(String::as_str).collect();
+    assert!(menu.validate_localize_keys(&valid).is_empty());
+}
@@ -0,0 +1,135 @@
+//! Checks [`merge_asset_lists`] and [`split_asset_list`].
+
+use std::collections::HashSet;
+
+use t5_xfile_defs::{
+    XString,
+    misc::{LocalizeEntry, RawFile},
+    xasset::{XAsset, XAssetGeneric, merge_asset_lists, split_asset_list},
+};
+
+fn localize(name: &str, value: &str) -> XAsset {
+    XAsset::PC(XAssetGeneric::LocalizeEntry(Some(Box::new(
+        LocalizeEntry::new(name, value),
+    ))))
+}
+
+fn raw_file(name: &str, contents: &str) -> XAsset {
+    let mut buffer = contents.as_bytes().to_vec();
+    buffer.push(0);
+    XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+        name: XString::from(name),
+        buffer,
+    }))))
+}
+
+/// Each asset's name and contents, in order.
+fn contents(assets: &[XAsset]) -> Vec<(&str, String)> {
+    assets
+        .iter()
+        .map(|a| match a {
+            XAsset::PC(XAssetGeneric::LocalizeEntry(Some(e))) => {
+                (e.name.get(), e.value.get().to_string())
+            }
+            XAsset::PC(XAssetGeneric::RawFile(Some(f))) => (
+                f.name.get(),
+                String::from_utf8_lossy(&f.buffer[..f.buffer.len() - 1]).into_owned(),
+            ),
+            _ => panic!("unexpected asset {a:?}"),
+        })
+        .collect()
+}
+
+fn base() -> Vec<XAsset> {
+    vec![
+        localize("MENU_A", "a"),
+        raw_file("mp/a.cfg", "base a"),
+        localize("MENU_B", "b"),
+    ]
+}
+
+#[test]
+fn merge() {
+    let overlay = vec![
+        // Replaces the base's copy where it is.
+        raw_file("mp/a.cfg", "overlay a"),
+        // Same name, different type.
+        raw_file("MENU_B", "not a localize entry"),
+        localize("MENU_C", "c"),
+        XAsset::PC(XAssetGeneric::RawFile(None)),
+    ];
+
+    let (merged, report) = merge_asset_lists(base(), overlay);
+    assert_eq!(
+        contents(&merged),
+        [
+            ("MENU_A", "a".to_string()),
+            ("mp/a.cfg", "overlay a".to_string()),
+            ("MENU_B", "b".to_string()),
+            ("MENU_C", "c".to_string()),
+        ]
+    );
+    assert_eq!(report.overridden, ["mp/a.cfg"]);
+    assert_eq!(report.added, ["MENU_C"]);
+    assert_eq!(report.conflicts, ["MENU_B"]);
+}
+
+#[test]
+fn merge_empty() {
+    let (merged, report) = merge_asset_lists(base(), Vec::new());
+    assert_eq!(contents(&merged), contents(&base()));
+    assert!(report.overridden.is_empty() && report.added.is_empty());
+
+    let (merged, report) = merge_asset_lists(Vec::new(), base());
+    assert_eq!(contents(&merged), contents(&base()));
+    assert_eq!(report.added, ["MENU_A", "mp/a.cfg", "MENU_B"]);
+}
+
+#[test]
+fn later_overlays_win() {
+    let (merged, _) = merge_asset_lists(base(), vec![localize("MENU_A", "first")]);
+    let (merged, report) = merge_asset_lists(merged, vec![localize("MENU_A", "second")]);
+    assert_eq!(contents(&merged)[0], ("MENU_A", "second".to_string()));
+    assert_eq!(report.overridden, ["MENU_A"]);
+}
+
+#[test]
+fn split() {
+    let overlay = vec![localize("MENU_C", "c"), localize("MENU_D", "d")];
+    let names = overlay
+        .iter()
+        .filter_map(|a| a.name().map(String::from))
+        .collect::<HashSet<_>>();
+
+    let (merged, _) = merge_asset_lists(base(), overlay);
+    let (primary, secondary) = split_asset_list(merged, &names);
+    assert_eq!(contents(&primary), contents(&base()));
+    assert_eq!(
+        contents(&secondary),
+        [("MENU_C", "c".to_string()), ("MENU_D", "d".to_string())]
+    );
+}
+
+#[test]
+fn duplicates_in_secondary() {
+    let overlay = vec![
+        localize("MENU_C", "first"),
+        raw_file("mp/c.cfg", "c"),
+        // Overrides the copy added just before it.
+        localize("MENU_C", "second"),
+        // Conflicts with the raw file added before it.
+        localize("mp/c.cfg", "not a raw file"),
+    ];
+
+    let (merged, report) = merge_asset_lists(base(), overlay);
+    assert_eq!(
+        contents(&merged)[3..],
+        [
+            ("MENU_C", "second".to_string()),
+            ("mp/c.cfg", "c".to_string()),
+        ]
+    );
+    assert_eq!(report.added, ["MENU_C", "mp/c.cfg"]);
+    assert_eq!(report.overridden, ["MENU_C"]);
+    assert_eq!(report.conflicts, ["mp/c.cfg"]);
+}
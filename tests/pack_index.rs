@@ -0,0 +1,95 @@
+//! Checks [`PackIndex`]'s integrity checks.
+
+use t5_xfile_defs::{
+    XString,
+    misc::{PackIndex, PackIndexEntry, PackIndexHeader},
+};
+
+const DATA_START: usize = 0x800;
+
+fn entry(hash: u32, offset: usize, size: usize) -> PackIndexEntry {
+    PackIndexEntry { hash, offset, size }
+}
+
+fn pack_index(entries: Vec<PackIndexEntry>) -> PackIndex {
+    PackIndex {
+        name: XString::from("test.pak".to_string()),
+        header: PackIndexHeader {
+            magic: 0,
+            timestamp: 0,
+            count: entries.len(),
+            alignment: 0x800,
+            data_start: DATA_START,
+        },
+        entries,
+    }
+}
+
+#[test]
+fn duplicate_hashes() {
+    let p = pack_index(vec![
+        entry(0xAAAA, DATA_START, 0x100),
+        entry(0xBBBB, DATA_START + 0x100, 0x100),
+        entry(0xAAAA, DATA_START + 0x200, 0x100),
+        entry(0xCCCC, DATA_START + 0x300, 0x100),
+        entry(0xAAAA, DATA_START + 0x400, 0x100),
+    ]);
+
+    assert_eq!(p.find_duplicate_hashes(), [(0, 2), (0, 4)]);
+    assert!(pack_index(Vec::new()).find_duplicate_hashes().is_empty());
+}
+
+#[test]
+fn sort_by_offset_sorted() {
+    let entries = vec![
+        entry(0xCCCC, DATA_START, 0x100),
+        entry(0xAAAA, DATA_START + 0x100, 0x100),
+        entry(0xBBBB, DATA_START + 0x200, 0x100),
+    ];
+    let mut p = pack_index(entries.clone());
+
+    p.sort_by_offset();
+    assert_eq!(p.entries, entries);
+}
+
+#[test]
+fn sort_by_offset_unsorted() {
+    let mut p = pack_index(vec![
+        entry(0xAAAA, DATA_START + 0x200, 0x100),
+        entry(0xBBBB, DATA_START, 0x100),
+        entry(0xCCCC, DATA_START + 0x100, 0x100),
+    ]);
+
+    p.sort_by_offset();
+    let hashes = p.entries.iter().map(|e| e.hash).collect::<Vec<_>>();
+    assert_eq!(hashes, [0xBBBB, 0xCCCC, 0xAAAA]);
+}
+
+#[test]
+fn validate_offsets() {
+    let p = pack_index(vec![
+        entry(0xAAAA, DATA_START, 0x100),
+        entry(0xBBBB, DATA_START + 0x100, 0x100),
+    ]);
+    assert!(p.validate_offsets(0x200).is_ok());
+    assert!(p.validate_offsets(0x1FF).is_err());
+
+    let before_data = pack_index(vec![entry(0xAAAA, DATA_START - 1, 1)]);
+    assert!(before_data.validate_offsets(0x200).is_err());
+
+    let overflowing = pack_index(vec![entry(0xAAAA, usize::MAX, 2)]);
+    assert!(overflowing.validate_offsets(usize::MAX).is_err());
+}
+
+#[test]
+fn header_is_valid() {
+    let mut header = pack_index(Vec::new()).header;
+    assert!(header.is_valid());
+
+    header.data_start += 1;
+    assert!(!header.is_valid());
+
+    header.data_start = DATA_START;
+    header.alignment = 0;
+    assert!(!header.is_valid());
+}
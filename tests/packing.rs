@@ -0,0 +1,151 @@
+//! Checks the vertex packing functions and that repacking an unmodified
+//! surface leaves its vertices alone.
+
+use t5_xfile_defs::{
+    packing::{
+        f32_to_half, half_to_f32, pack_color, pack_tex_coord, pack_unit_vec, unpack_color,
+        unpack_tex_coord, unpack_unit_vec,
+    },
+    xmodel::{GfxColor, GfxPackedVertex, TexCoords, UnitVec, XSurface},
+};
+
+#[test]
+fn half_known_values() {
+    assert_eq!(half_to_f32(0x3C00), 1.0);
+    assert_eq!(half_to_f32(0xC000), -2.0);
+    assert_eq!(half_to_f32(0x7BFF), 65504.0);
+    assert_eq!(half_to_f32(0x0001), 2f32.powi(-24));
+    assert_eq!(half_to_f32(0x7C00), f32::INFINITY);
+
+    assert_eq!(f32_to_half(1.0), 0x3C00);
+    assert_eq!(f32_to_half(-0.0), 0x8000);
+    assert_eq!(f32_to_half(1e6), 0x7C00);
+    assert_eq!(f32_to_half(2f32.powi(-26)), 0);
+    // Halfway between 1.0 and the next half up; ties go to even.
+    assert_eq!(f32_to_half(1.0 + 2f32.powi(-11)), 0x3C00);
+    assert_eq!(f32_to_half(1.0 + 3.0 * 2f32.powi(-11)), 0x3C02);
+    assert!(half_to_f32(f32_to_half(f32::NAN)).is_nan());
+}
+
+#[test]
+fn half_round_trip() {
+    for h in 0..=u16::MAX {
+        let f = half_to_f32(h);
+        if f.is_nan() {
+            assert!(half_to_f32(f32_to_half(f)).is_nan(), "{h:#06X}");
+        } else {
+            assert_eq!(f32_to_half(f), h, "{h:#06X} ({f})");
+        }
+    }
+}
+
+#[test]
+fn tex_coord_known_values() {
+    assert_eq!(unpack_tex_coord(0), 0.0);
+    assert_eq!(unpack_tex_coord(0x3C00), 1.0);
+    assert_eq!(unpack_tex_coord(0xBC00), -1.0);
+    assert_eq!(unpack_tex_coord(0x3800), 0.5);
+    assert_eq!(unpack_tex_coord(0x7FFF), 131008.0);
+
+    assert_eq!(pack_tex_coord(0.0), 0);
+    assert_eq!(pack_tex_coord(-0.0), 0);
+    assert_eq!(pack_tex_coord(1.0), 0x3C00);
+    assert_eq!(pack_tex_coord(1e9), 0x7FFF);
+}
+
+#[test]
+fn tex_coord_round_trip() {
+    for h in 0..=u16::MAX {
+        let f = unpack_tex_coord(h);
+        assert_eq!(pack_tex_coord(f), h, "{h:#06X} ({f})");
+    }
+}
+
+#[test]
+fn tex_coords() {
+    let packed = TexCoords::pack([1.0, 0.5]);
+    assert_eq!(packed.0, 0x3C00_3800);
+    assert_eq!(packed.get(), [1.0, 0.5]);
+}
+
+#[test]
+fn unit_vec() {
+    assert_eq!(unpack_unit_vec([127, 127, 127, 200]), [0.0; 3]);
+    assert_eq!(pack_unit_vec([0.0; 3]), [127, 127, 127, 0]);
+
+    for v in [
+        [1.0, 0.0, 0.0],
+        [0.0, -1.0, 0.0],
+        [0.6, 0.0, 0.8],
+        [-0.267_261_24, 0.534_522_5, 0.801_783_7],
+    ] {
+        let packed = pack_unit_vec(v);
+        let unpacked = unpack_unit_vec(packed);
+        for (a, b) in unpacked.iter().zip(v) {
+            assert!((a - b).abs() < 0.005, "{v:?} -> {packed:?} -> {unpacked:?}");
+        }
+        // Whatever the first packing loses is lost only once.
+        assert_eq!(pack_unit_vec(unpacked), packed, "{v:?}");
+    }
+
+    assert_eq!(UnitVec::pack([0.0, 0.0, 1.0].into()).get().get()[2], 1.0);
+}
+
+#[test]
+fn color() {
+    for c in 0..=u8::MAX {
+        assert_eq!(pack_color(unpack_color([c; 4])), [c; 4]);
+    }
+    assert_eq!(pack_color([-1.0, 0.0, 1.0, 2.0]), [0, 0, 255, 255]);
+    assert_eq!(GfxColor::pack([1.0, 0.0, 0.0, 1.0]).0, [255, 0, 0, 255]);
+}
+
+fn surface() -> XSurface {
+    let verts0 = (0..64u8)
+        .map(|i| GfxPackedVertex {
+            xyz: [i as f32, 0.5, -2.0].into(),
+            binormal_sign: if i % 2 == 0 { 1.0 } else { -1.0 },
+            color: GfxColor([i, i.wrapping_mul(3), 0xFF, 0x80]),
+            tex_coord: TexCoords((i as u32 * 0x0123_4567) & 0x7FFF_7FFF),
+            // Scales the game's exporter wouldn't pick, so packing the
+            // decoded vector afresh would give different bits.
+            normal: UnitVec([127 + i, 127, 127 - i, 255 - i]),
+            tangent: UnitVec([127, 127, 127, i]),
+        })
+        .collect();
+
+    XSurface {
+        verts0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn surface_round_trip() {
+    let mut surf = surface();
+    let decoded = surf.decoded_vertices();
+    surf.set_decoded_vertices(&decoded).unwrap();
+
+    for (a, b) in surf.verts0.iter().zip(&surface().verts0) {
+        assert_eq!(a.color.0, b.color.0);
+        assert_eq!(a.tex_coord.0, b.tex_coord.0);
+        assert_eq!(a.normal.0, b.normal.0);
+        assert_eq!(a.tangent.0, b.tangent.0);
+    }
+}
+
+#[test]
+fn surface_edit() {
+    let mut surf = surface();
+    let mut decoded = surf.decoded_vertices();
+    decoded[1].tex_coord[0] = 0.25;
+    decoded[1].normal = [0.0, 1.0, 0.0].into();
+    surf.set_decoded_vertices(&decoded).unwrap();
+
+    let v = &surf.verts0[1];
+    assert_eq!(v.tex_coord.get(), [0.25, decoded[1].tex_coord[1]]);
+    assert_eq!(v.normal.0, pack_unit_vec([0.0, 1.0, 0.0]));
+    assert_eq!(v.tangent.0, surface().verts0[1].tangent.0);
+
+    assert!(surf.set_decoded_vertices(&decoded[1..]).is_err());
+}
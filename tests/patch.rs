@@ -0,0 +1,221 @@
+//! Checks that [`build_patch`] only serializes the assets that changed.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+#[path = "../examples/build_patch_ff.rs"]
+mod example;
+
+mod common;
+
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    font::Font,
+    fx::FxEffectDef,
+    misc::{LocalizeEntry, RawFile},
+    techset::{Material, MaterialInfo},
+    weapon::WeaponVariantDef,
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+};
+use t5_xfile_deserializer::{
+    T5XFileSerializer, T5XFileSerializerBuilder,
+    patch::{PatchAsset, PatchReport, SubAssetReference, build_patch},
+};
+
+use common::TempFile;
+
+/// The example's four assets, and six more.
+fn base() -> Vec<XAsset> {
+    let mut assets = example::patch_assets().unwrap();
+    for i in 0..3 {
+        assets.push(XAsset::PC(XAssetGeneric::LocalizeEntry(Some(Box::new(
+            LocalizeEntry::new(format!("PATCH_STRING_{i}"), format!("String {i}")),
+        )))));
+        assets.push(XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(
+            RawFile {
+                name: XString::from(format!("patch/{i}.cfg")),
+                buffer: b"set a 1\0".to_vec(),
+            },
+        )))));
+    }
+    assets
+}
+
+fn id(asset_type: XAssetType, name: &str) -> PatchAsset {
+    PatchAsset {
+        asset_type,
+        name: name.to_string(),
+    }
+}
+
+fn serializer() -> T5XFileSerializer {
+    T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap()
+}
+
+/// Serializes the patch between `base` and `modified`, then deserializes
+/// it again.
+fn patch(name: &str, base: &[XAsset], modified: &[XAsset]) -> (PatchReport, Vec<XAsset>) {
+    let mut ser = serializer();
+    let report = build_patch(base, modified, &mut ser).unwrap();
+    assert!(report.inlined.is_empty(), "{:?}", report.inlined);
+
+    let temp = TempFile::new(&format!("patch_{name}.ff"), ser.deflate().unwrap());
+    let assets = common::deserialize(&temp, false).unwrap();
+
+    (report, assets)
+}
+
+/// The weapon in `assets`.
+fn weapon(assets: &mut [XAsset]) -> &mut WeaponVariantDef {
+    assets
+        .iter_mut()
+        .find_map(|a| match a {
+            XAsset::PC(XAssetGeneric::Weapon(Some(weapon))) => Some(&mut **weapon),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn only_changed_weapon() {
+    let base = base();
+    assert_eq!(base.len(), 10);
+
+    let mut modified = base.clone();
+    weapon(&mut modified).clip_size = 45;
+
+    let (report, assets) = patch("weapon", &base, &modified);
+    assert_eq!(report.included, [id(XAssetType::WEAPON, "crate_gun_mp")]);
+    assert!(report.externalized.is_empty());
+
+    let [XAsset::PC(XAssetGeneric::Weapon(Some(weapon)))] = &assets[..] else {
+        panic!("{assets:#?}");
+    };
+    assert_eq!(weapon.clip_size, 45);
+    assert_eq!(weapon.display_name.get(), "WEAPON_CRATE_GUN");
+}
+
+#[test]
+fn new_and_unchanged() {
+    let base = base();
+    let report = build_patch(&base, &base, &mut serializer()).unwrap();
+    assert_eq!(report, PatchReport::default());
+
+    let mut modified = base.clone();
+    modified.push(XAsset::PC(XAssetGeneric::LocalizeEntry(Some(Box::new(
+        LocalizeEntry::new("PATCH_NEW", "New"),
+    )))));
+    // Removing an asset doesn't put anything in the patch.
+    modified.remove(0);
+
+    let (report, assets) = patch("new", &base, &modified);
+    assert_eq!(
+        report.included,
+        [id(XAssetType::LOCALIZE_ENTRY, "PATCH_NEW")]
+    );
+    assert_eq!(assets.len(), 1);
+    assert_eq!(assets[0].name(), Some("PATCH_NEW"));
+}
+
+fn material(name: &str) -> Material {
+    Material {
+        info: MaterialInfo {
+            name: XString::from(name.to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn font(pixel_height: i32, material: Option<Material>) -> XAsset {
+    XAsset::PC(XAssetGeneric::Font(Some(Box::new(Font {
+        font_name: XString::from("fonts/patchfont".to_string()),
+        pixel_height,
+        material: material.map(Box::new),
+        glow_material: None,
+        glyphs: Vec::new(),
+    }))))
+}
+
+#[test]
+fn inlined_and_ordered() {
+    let shared = material("fonts/patchfont_mtl");
+    let base = vec![
+        XAsset::PC(XAssetGeneric::Material(Some(Box::new(shared.clone())))),
+        font(16, Some(shared.clone())),
+    ];
+
+    // The font still uses the base's material, so the patch has a copy.
+    let modified = vec![base[0].clone(), font(24, Some(shared.clone()))];
+    let report = build_patch(&base, &modified, &mut serializer()).unwrap();
+    assert_eq!(report.included, [id(XAssetType::FONT, "fonts/patchfont")]);
+    assert_eq!(
+        report.inlined,
+        [SubAssetReference {
+            from: id(XAssetType::FONT, "fonts/patchfont"),
+            to: id(XAssetType::MATERIAL, "fonts/patchfont_mtl"),
+        }]
+    );
+
+    // A changed material comes before the font that uses it, even if it's
+    // listed after.
+    let changed = material("fonts/patchfont_mtl_2");
+    let modified = vec![
+        font(24, Some(changed.clone())),
+        XAsset::PC(XAssetGeneric::Material(Some(Box::new(changed)))),
+    ];
+    let report = build_patch(&base, &modified, &mut serializer()).unwrap();
+    assert_eq!(
+        report.included,
+        [
+            id(XAssetType::MATERIAL, "fonts/patchfont_mtl_2"),
+            id(XAssetType::FONT, "fonts/patchfont"),
+        ]
+    );
+    assert!(report.inlined.is_empty());
+}
+
+#[test]
+fn externalized_effect() {
+    let flash = FxEffectDef {
+        name: XString::from("weapon/muzzleflashes/fx_crate_gun"),
+        total_size: 64,
+        ..FxEffectDef::external("")
+    };
+    let mut base = base();
+    weapon(&mut base)
+        .weap_def
+        .as_mut()
+        .unwrap()
+        .view_flash_effect = Some(Box::new(flash.clone()));
+    base.push(XAsset::PC(XAssetGeneric::Fx(Some(Box::new(flash)))));
+
+    // The weapon still uses the base's effect, so the patch only names it.
+    let mut modified = base.clone();
+    weapon(&mut modified).clip_size = 45;
+
+    let (report, mut assets) = patch("externalized", &base, &modified);
+    assert_eq!(report.included, [id(XAssetType::WEAPON, "crate_gun_mp")]);
+    assert_eq!(
+        report.externalized,
+        [SubAssetReference {
+            from: id(XAssetType::WEAPON, "crate_gun_mp"),
+            to: id(XAssetType::FX, "weapon/muzzleflashes/fx_crate_gun"),
+        }]
+    );
+
+    let weapon = weapon(&mut assets);
+    assert_eq!(weapon.clip_size, 45);
+    let flash = weapon
+        .weap_def
+        .as_ref()
+        .unwrap()
+        .view_flash_effect
+        .as_deref()
+        .unwrap();
+    assert!(flash.is_external());
+    assert_eq!(flash.name.get(), ",weapon/muzzleflashes/fx_crate_gun");
+    assert_eq!(flash.total_size, 0);
+}
@@ -0,0 +1,110 @@
+//! Checks [`PathData`]'s node graph, on a hand-built graph and on the first
+//! single-player level in the local corpus. See
+//! [`t5_xfile_deserializer::corpus`] for how to set it up.
+
+use t5_xfile_defs::gameworld::{PathData, PathLink, PathNode, PathNodeConstant};
+
+fn link(node_num: u16) -> PathLink {
+    PathLink {
+        dist: 64.0,
+        node_num,
+        disconnect_count: 0,
+        negotiation_link: 0,
+        bad_place_count: [0; 4],
+    }
+}
+
+fn node(links: &[u16]) -> PathNode {
+    PathNode {
+        constant: PathNodeConstant {
+            links: links.iter().copied().map(link).collect(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Two islands, {0, 1, 2} and {3, 4}, with the runtime slots after them.
+fn path_data() -> PathData {
+    let mut nodes = vec![
+        node(&[1, 1]),
+        node(&[0, 2]),
+        // One-way links still connect the island.
+        node(&[]),
+        node(&[4, 200]),
+        node(&[3]),
+    ];
+    nodes.resize_with(nodes.len() + 128, || node(&[]));
+
+    PathData {
+        nodes,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn node_graph() {
+    let path = path_data();
+    assert_eq!(path.node_count(), 5);
+    assert!(path.node_at(4).is_ok());
+    assert!(path.node_at(5).is_err());
+
+    // Duplicate links and links past the placed nodes are dropped.
+    assert_eq!(
+        path.node_graph(),
+        [vec![1], vec![0, 2], vec![], vec![4], vec![3]]
+    );
+    assert_eq!(path.connected_components(), [vec![0, 1, 2], vec![3, 4]]);
+}
+
+#[cfg(feature = "corpus")]
+#[test]
+#[ignore = "needs a directory of Fastfiles in T5_CORPUS_DIR"]
+fn single_player_level() {
+    use t5_xfile_defs::xasset::{XAsset, XAssetGeneric};
+    use t5_xfile_deserializer::{
+        T5XFileDeserializerBuilder,
+        corpus::{corpus_dir, find_files},
+    };
+
+    for file in find_files(corpus_dir().unwrap()).unwrap() {
+        let mut f = std::fs::File::open(&file.path).unwrap();
+        let Ok(assets) = T5XFileDeserializerBuilder::from_file(&mut f, file.platform, true)
+            .with_silent(true)
+            .build()
+            .and_then(|de| de.inflate())
+            .and_then(|de| de.no_cache())
+            .and_then(|de| de.deserialize_remaining())
+        else {
+            continue;
+        };
+
+        let Some(world) = assets.iter().find_map(|a| match a {
+            XAsset::PC(XAssetGeneric::GameWorldSp(Some(w)))
+            | XAsset::Console(XAssetGeneric::GameWorldSp(Some(w))) => Some(w),
+            _ => None,
+        }) else {
+            continue;
+        };
+
+        let path = &world.path;
+        let count = path.node_count();
+        assert!(count > 0, "{}: no path nodes", file.path.display());
+        assert_eq!(path.node_graph().len(), count);
+
+        // Levels can have a few separate islands of nodes (e.g., for
+        // scripted sequences), but most of them should be reachable from
+        // each other.
+        let components = path.connected_components();
+        let largest = components.iter().map(Vec::len).max().unwrap();
+        assert!(
+            largest * 2 > count,
+            "{}: largest of {} islands has {largest} of {count} nodes",
+            file.path.display(),
+            components.len()
+        );
+        return;
+    }
+
+    panic!("no single-player level in the corpus");
+}
@@ -0,0 +1,121 @@
+//! Checks [`PhysConstraints::validate`] and [`PhysConstraint`]'s joint frame.
+
+use t5_xfile_defs::{
+    ErrorKind,
+    common::Vec3,
+    xmodel::{AttachPointType, PhysConstraint, PhysConstraints},
+};
+
+fn attached(index1: usize, attach2: AttachPointType, index2: usize) -> PhysConstraint {
+    PhysConstraint {
+        attach_point_type1: AttachPointType::BONE,
+        target_index1: index1,
+        attach_point_type2: attach2,
+        target_index2: index2,
+        ..Default::default()
+    }
+}
+
+fn constraints(count: usize, data: Vec<PhysConstraint>) -> PhysConstraints {
+    PhysConstraints {
+        count,
+        data,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn validate() {
+    let ok = constraints(
+        2,
+        vec![
+            attached(0, AttachPointType::BONE, 3),
+            // Only bone attachments index the model's bones.
+            attached(1, AttachPointType::ENT, 100),
+        ],
+    );
+    ok.validate(4).unwrap();
+
+    let e = ok.validate(3).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+    assert!(
+        e.to_string().contains("constraint 0's target_index (3)"),
+        "{e}"
+    );
+
+    let bad = constraints(1, vec![attached(7, AttachPointType::WORLD, 0)]);
+    let e = bad.validate(4).unwrap_err();
+    assert!(e.to_string().contains("target_index (7)"), "{e}");
+}
+
+#[test]
+fn validate_count() {
+    // Entries past `count` aren't in use, so they aren't checked.
+    let unused = constraints(
+        1,
+        vec![
+            attached(0, AttachPointType::WORLD, 0),
+            attached(9, AttachPointType::BONE, 9),
+        ],
+    );
+    unused.validate(1).unwrap();
+
+    let e = constraints(3, vec![PhysConstraint::default()])
+        .validate(1)
+        .unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+}
+
+fn joint(dir: [f32; 3], pos: [f32; 3]) -> PhysConstraint {
+    PhysConstraint {
+        dir: dir.into(),
+        pos: pos.into(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn joint_transform() {
+    // X is `dir` normalized, Y is Z-up crossed with it, and Z completes a
+    // right-handed frame.
+    let c = joint([0.0, 2.0, 0.0], [1.0, 2.0, 3.0]);
+    assert_eq!(
+        c.joint_transform(),
+        [
+            [0.0, 1.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [1.0, 2.0, 3.0, 1.0],
+        ]
+    );
+    assert_eq!(c.joint_axis(0).get(), [0.0, 1.0, 0.0]);
+    assert_eq!(c.joint_axis(1).get(), [-1.0, 0.0, 0.0]);
+    assert_eq!(c.joint_axis(2).get(), [0.0, 0.0, 1.0]);
+    assert_eq!(c.joint_axis(7).get(), c.joint_axis(2).get());
+}
+
+#[test]
+fn joint_transform_vertical() {
+    // Z-up would be parallel to `dir`, so X-up is used instead.
+    let c = joint([0.0, 0.0, -3.0], [0.0; 3]);
+    let [x, y, z, _] = c.joint_transform();
+    assert_eq!(x, [0.0, 0.0, -1.0, 0.0]);
+    assert_eq!(y, [0.0, 1.0, 0.0, 0.0]);
+    assert_eq!(z, [1.0, 0.0, 0.0, 0.0]);
+
+    let (x, y): (Vec3, Vec3) = (c.joint_axis(0), c.joint_axis(1));
+    assert_eq!(x.cross(&y).get(), c.joint_axis(2).get());
+}
+
+#[test]
+fn joint_transform_no_dir() {
+    assert_eq!(
+        joint([0.0; 3], [4.0, 5.0, 6.0]).joint_transform(),
+        [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [4.0, 5.0, 6.0, 1.0],
+        ]
+    );
+}
@@ -0,0 +1,141 @@
+//! Converts assets deserialized from an Xbox 360 Fastfile with
+//! [`convert_platform`] and checks that they serialize into a Windows
+//! Fastfile that deserializes back to the same data.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform, XString,
+    convert::{ConversionOutcome, convert_platform},
+    misc::{LocalizeEntry, RawFile, XGlobals},
+    techset::{GfxImage, GfxImageLoadDef, GfxTexture, d3dfmt},
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+};
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, T5XFileSerializerBuilder};
+
+use common::TempFile;
+
+fn s(s: &str) -> XString {
+    XString::from(s.to_string())
+}
+
+fn console_assets() -> Vec<XAsset> {
+    vec![
+        XAsset::Console(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+            name: s("maps/mp/crate.gsc"),
+            buffer: b"main() {}\0".to_vec(),
+        })))),
+        XAsset::Console(XAssetGeneric::XModel(None)),
+        XAsset::Console(XAssetGeneric::LocalizeEntry(Some(Box::new(
+            LocalizeEntry::new("MENU_CRATE", "Crate"),
+        )))),
+        XAsset::Console(XAssetGeneric::XGlobals(Some(Box::new(XGlobals {
+            name: s("globals"),
+            xanim_stream_buffer_size: 0x20000,
+            cinematic_max_width: 1280,
+            cinematic_max_height: 720,
+            extracam_resolution: 256,
+            gump_reserve: 1,
+            screen_clear_color: [0.0, 0.0, 0.0, 1.0].into(),
+        })))),
+        XAsset::Console(XAssetGeneric::Image(Some(Box::new(GfxImage {
+            name: s("crate_col"),
+            width: 2,
+            height: 1,
+            depth: 1,
+            texture: GfxTexture::LoadDef(Some(Box::new(GfxImageLoadDef {
+                level_count: 1,
+                format: d3dfmt::A8R8G8B8,
+                resource: vec![1, 2, 3, 4, 5, 6, 7, 8],
+                ..Default::default()
+            }))),
+            ..Default::default()
+        })))),
+    ]
+}
+
+fn round_trip<const N: usize>(assets: Vec<XAsset>, platform: XFilePlatform) -> Vec<XAsset> {
+    let mut ser = T5XFileSerializerBuilder::new(platform)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<N>(assets.into_iter()).unwrap();
+    let bytes = ser.deflate().unwrap();
+
+    let temp = TempFile::new(&format!("platform_convert_{platform:?}.ff"), bytes);
+    let mut file = temp.open();
+
+    T5XFileDeserializerBuilder::from_file(&mut file, platform, false)
+        .with_silent(true)
+        .with_allow_unsupported_platforms(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .and_then(|de| de.deserialize_remaining())
+        .unwrap()
+}
+
+#[test]
+fn console_to_pc() {
+    let console = round_trip::<4>(console_assets(), XFilePlatform::Xbox360);
+    assert_eq!(console.len(), 5);
+    assert!(console.iter().all(XAsset::is_console));
+
+    let (pc, report) = convert_platform(console, XFilePlatform::Windows).unwrap();
+    assert_eq!(pc.len(), 4);
+    assert!(pc.iter().all(XAsset::is_pc));
+
+    let dropped = report.dropped().collect::<Vec<_>>();
+    assert_eq!(dropped.len(), 1);
+    assert_eq!(dropped[0].index, 1);
+    assert_eq!(dropped[0].asset_type, XAssetType::XMODEL);
+    assert_eq!(dropped[0].asset_name, None);
+    assert_eq!(report.converted().count(), 4);
+    assert!(matches!(
+        &report.assets[4].outcome,
+        ConversionOutcome::Transformed(_)
+    ));
+
+    let pc = round_trip::<1>(pc, XFilePlatform::Windows);
+    let names = pc.iter().map(|a| a.name().unwrap()).collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        ["maps/mp/crate.gsc", "MENU_CRATE", "globals", "crate_col"]
+    );
+
+    let XAsset::PC(XAssetGeneric::RawFile(Some(raw_file))) = &pc[0] else {
+        panic!("{:?}", pc[0]);
+    };
+    assert_eq!(raw_file.buffer, b"main() {}\0");
+    let XAsset::PC(XAssetGeneric::XGlobals(Some(globals))) = &pc[2] else {
+        panic!("{:?}", pc[2]);
+    };
+    assert_eq!(globals.cinematic_max_width, 1280);
+    let XAsset::PC(XAssetGeneric::Image(Some(image))) = &pc[3] else {
+        panic!("{:?}", pc[3]);
+    };
+    let GfxTexture::LoadDef(Some(load_def)) = &image.texture else {
+        panic!("{:?}", image.texture);
+    };
+    assert_eq!(load_def.resource, [4, 3, 2, 1, 8, 7, 6, 5]);
+}
+
+#[test]
+fn same_side_is_untouched() {
+    let (assets, report) = convert_platform(console_assets(), XFilePlatform::PS3).unwrap();
+    assert_eq!(assets.len(), 5);
+    assert!(
+        report
+            .assets
+            .iter()
+            .all(|a| a.outcome == ConversionOutcome::Converted)
+    );
+}
+
+#[test]
+fn wii_is_unimplemented() {
+    let e = convert_platform(console_assets(), XFilePlatform::Wii).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::UnimplementedPlatform(_)));
+}
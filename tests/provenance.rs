@@ -0,0 +1,78 @@
+//! Checks the [`Provenance`] of assets loaded from two fastfiles and merged
+//! together.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    XFilePlatform,
+    xasset::{LoadedAsset, Provenance, XAsset, merge_loaded_assets},
+};
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, language_pack::LanguagePackBuilder};
+
+use common::TempFile;
+
+fn load(source: &str, entries: &[(&str, &str)]) -> Vec<LoadedAsset> {
+    let mut builder = LanguagePackBuilder::new(XFilePlatform::Windows);
+    builder.add_all(entries.iter().copied()).unwrap();
+
+    let temp = TempFile::new(source, builder.build().unwrap());
+
+    let mut file = temp.open();
+    let assets = T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .with_source(source)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .and_then(|de| de.deserialize_remaining_loaded());
+    assets.unwrap()
+}
+
+#[test]
+fn provenance() {
+    let base = load("base.ff", &[("MENU_A", "a"), ("MENU_B", "b")]);
+
+    for (i, asset) in base.iter().enumerate() {
+        let Provenance {
+            source,
+            index,
+            span: (start, end),
+            platform,
+        } = &asset.provenance;
+        assert_eq!(source, "base.ff");
+        assert_eq!(*index, i);
+        assert_eq!(*platform, XFilePlatform::Windows);
+        assert!(start < end);
+    }
+    // Assets are stored back to back.
+    assert_eq!(base[0].provenance.span.1, base[1].provenance.span.0);
+
+    let overlay = load("overlay.ff", &[("MENU_B", "b2"), ("MENU_C", "c")]);
+    let (merged, report) = merge_loaded_assets(base, overlay);
+    assert_eq!(report.overridden, ["MENU_B"]);
+    assert_eq!(report.added, ["MENU_C"]);
+
+    let sources = merged
+        .iter()
+        .map(|a| {
+            (
+                a.asset.name().unwrap(),
+                a.provenance.source.as_str(),
+                a.provenance.index,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        sources,
+        [
+            ("MENU_A", "base.ff", 0),
+            ("MENU_B", "overlay.ff", 0),
+            ("MENU_C", "overlay.ff", 1)
+        ]
+    );
+
+    let assets = merged.into_iter().map(XAsset::from).collect::<Vec<_>>();
+    assert_eq!(assets.len(), 3);
+}
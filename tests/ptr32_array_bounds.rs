@@ -0,0 +1,159 @@
+//! Fuzzes [`validate_ptr32_array`] and the array readers that call it with
+//! random pointers and counts. Whatever they're given, they must either
+//! succeed or return [`ErrorKind::InvalidSeek`] or
+//! [`ErrorKind::UnexpectedEof`], and never panic.
+
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use t5_xfile_defs::{
+    ErrorKind, Result, T5XFileDeserialize, XFilePlatform,
+    limits::ResourceLimits,
+    util::{FatPointer, FatPointerCountFirstU32, Ptr32, Ptr32ArrayConst, validate_ptr32_array},
+};
+use t5_xfile_deserializer::{T5XFileDeserializer, T5XFileDeserializerBuilder};
+
+use common::{TempFile, u32s};
+
+const INLINE: u32 = 0xFFFFFFFF;
+const ITERATIONS: usize = 20_000;
+
+/// A small xorshift generator, so failures are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Null, inline, real, and arbitrary pointers, in roughly equal measure.
+    fn ptr(&mut self) -> u32 {
+        match self.next() % 4 {
+            0 => 0,
+            1 => [0xFFFFFFFF, 0xFFFFFFFE][self.next() as usize % 2],
+            2 => (self.next() % 0x200) as u32,
+            _ => self.next() as u32,
+        }
+    }
+
+    /// Counts that fit in the stream, counts that don't, and counts that
+    /// overflow.
+    fn count(&mut self) -> usize {
+        match self.next() % 4 {
+            0 => (self.next() % 16) as usize,
+            1 => (self.next() % 0x1000) as usize,
+            2 => self.next() as u32 as usize,
+            _ => self.next() as usize,
+        }
+    }
+}
+
+/// Runs `f` on a deserializer positioned just past an empty asset list, with
+/// 256 bytes of stream left.
+fn with_deserializer(f: impl FnOnce(&mut T5XFileDeserializer)) {
+    let mut body = u32s(&[0, INLINE, 0, INLINE]);
+    body.extend((0..=255).collect::<Vec<u8>>());
+    let mut blob = u32s(&[body.len() as u32, 0, 0, 0, 0, 0, 0, 0, 0]);
+    blob.extend(body);
+
+    let temp = TempFile::new("ptr32_fuzz.cache", blob);
+    let mut file = temp.open();
+    let de = T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        // Otherwise huge counts are stopped by the allocation limits first.
+        .with_resource_limits(ResourceLimits::unlimited())
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache());
+
+    f(&mut de.unwrap());
+}
+
+fn check<T>(what: &str, ptr: u32, count: usize, result: std::thread::Result<Result<T>>) {
+    match result {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => assert!(
+            matches!(
+                e.kind(),
+                ErrorKind::InvalidSeek { .. } | ErrorKind::UnexpectedEof { .. }
+            ),
+            "{what}({ptr:#010X}, {count}): {e}"
+        ),
+        Err(_) => panic!("{what}({ptr:#010X}, {count}) panicked"),
+    }
+}
+
+#[test]
+fn fuzz() {
+    with_deserializer(|de| {
+        let len = de.stream_len().unwrap();
+        let start = de.checkpoint().unwrap();
+        let mut rng = Rng(0x5EED_1234_ABCD_0001);
+
+        for _ in 0..ITERATIONS {
+            let (ptr, count) = (rng.ptr(), rng.count());
+            let p = Ptr32::<u32>::from_u32(ptr);
+
+            let mut de = AssertUnwindSafe(&mut *de);
+            let result = catch_unwind(AssertUnwindSafe(|| validate_ptr32_array(*de, &p, count)));
+            check("validate_ptr32_array", ptr, count, result);
+
+            let result = catch_unwind(AssertUnwindSafe(|| p.to_array(count).to_vec(*de)));
+            check("Ptr32Array::to_vec", ptr, count, result);
+            de.restore(start.clone()).unwrap();
+
+            let fat = FatPointerCountFirstU32::<u32>::new(p, count as u32 as usize);
+            let result = catch_unwind(AssertUnwindSafe(|| fat.to_vec(*de)));
+            check("FatPointerCountFirstU32::to_vec", ptr, count, result);
+            de.restore(start.clone()).unwrap();
+
+            let fixed = Ptr32ArrayConst::<u32, 21>::new(p, 21);
+            let result = catch_unwind(AssertUnwindSafe(|| fixed.to_vec(*de)));
+            check("Ptr32ArrayConst::to_vec", ptr, 21, result);
+            de.restore(start.clone()).unwrap();
+        }
+
+        assert_eq!(de.stream_len().unwrap(), len);
+    });
+}
+
+#[test]
+fn bounds() {
+    with_deserializer(|de| {
+        let pos = de.stream_pos().unwrap();
+        let left = (de.stream_len().unwrap() - pos) as usize;
+        assert_eq!(left, 256);
+
+        let inline = Ptr32::<u32>::from_u32(INLINE);
+        assert!(validate_ptr32_array(de, &inline, left / 4).is_ok());
+        // Running off the end of the blob could be a corrupted count, so it
+        // isn't reported as the end of a truncated file.
+        let e = validate_ptr32_array(de, &inline, left / 4 + 1).unwrap_err();
+        let end = (pos as usize + left + 4) as u32;
+        assert!(
+            matches!(e.kind(), ErrorKind::InvalidSeek { off, .. } if *off == end),
+            "{e}"
+        );
+        // Nor is an end that overflows.
+        let e = validate_ptr32_array(de, &inline, usize::MAX).unwrap_err();
+        assert!(matches!(e.kind(), ErrorKind::InvalidSeek { .. }), "{e}");
+
+        // Real offsets aren't followed, but the whole array still has to fit.
+        let len = de.stream_len().unwrap() as u32;
+        let real = Ptr32::<u32>::from_u32(len - 8);
+        assert!(validate_ptr32_array(de, &real, 2).is_ok());
+        let e = validate_ptr32_array(de, &real, 3).unwrap_err();
+        assert!(matches!(e.kind(), ErrorKind::InvalidSeek { .. }), "{e}");
+
+        // Null arrays are always fine.
+        let null = Ptr32::<u32>::from_u32(0);
+        assert!(validate_ptr32_array(de, &null, usize::MAX).is_ok());
+        assert_eq!(de.stream_pos().unwrap(), pos);
+    });
+}
@@ -0,0 +1,124 @@
+//! Checks [`RawFile`]'s text accessors and its CSV, key=value, and config
+//! table parsing.
+
+use t5_xfile_defs::{ErrorKind, XString, misc::RawFile};
+
+fn raw_file(contents: &[u8]) -> RawFile {
+    let mut buffer = contents.to_vec();
+    buffer.push(0);
+    RawFile {
+        name: XString::from("mp/test.cfg"),
+        buffer,
+    }
+}
+
+fn rows(contents: &str) -> Vec<Vec<String>> {
+    raw_file(contents.as_bytes()).parse_as_csv().unwrap()
+}
+
+#[test]
+fn text() {
+    let f = raw_file(b"set g_speed 190\n");
+    assert_eq!(f.contents(), b"set g_speed 190\n");
+    assert_eq!(f.as_str(), Some("set g_speed 190\n"));
+    assert!(f.is_text());
+
+    // Only one terminator is stripped, and none needs to be there.
+    let f = RawFile {
+        name: XString::from("x"),
+        buffer: b"ab\0\0".to_vec(),
+    };
+    assert_eq!(f.contents(), b"ab\0");
+    let f = RawFile {
+        name: XString::from("x"),
+        buffer: b"ab".to_vec(),
+    };
+    assert_eq!(f.contents(), b"ab");
+
+    let binary = raw_file(&[0x89, b'P', b'N', b'G', 0xFF]);
+    assert_eq!(binary.as_str(), None);
+    assert!(!binary.is_text());
+    for e in [
+        binary.parse_as_csv().unwrap_err(),
+        binary.parse_as_key_value().unwrap_err(),
+        binary.parse_as_config_table().unwrap_err(),
+    ] {
+        assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+    }
+}
+
+#[test]
+fn csv() {
+    assert_eq!(rows("a,b\nc,d\n"), [["a", "b"], ["c", "d"]]);
+    // No trailing newline, CRLF line endings, and blank lines.
+    assert_eq!(rows("a,b\r\n\r\n\nc,d"), [["a", "b"], ["c", "d"]]);
+    // Empty cells are kept.
+    assert_eq!(rows(",x,\n"), [["", "x", ""]]);
+    assert!(rows("").is_empty());
+
+    // Quoted fields can hold anything.
+    assert_eq!(
+        rows("\"a,b\",\"line\nbreak\",\"say \"\"hi\"\"\"\n"),
+        [["a,b", "line\nbreak", "say \"hi\""]]
+    );
+    // A quote partway through a cell is just a character.
+    assert_eq!(rows("12\" pipe,x\n"), [["12\" pipe", "x"]]);
+
+    let e = raw_file(b"a,b\n\"c,d\n").parse_as_csv().unwrap_err();
+    assert!(
+        matches!(e.kind(), ErrorKind::BrokenInvariant(s) if s.contains("row 2")),
+        "{e}"
+    );
+}
+
+#[test]
+fn key_value() {
+    let f = raw_file(
+        b"// Comment\n# Also a comment\n\n  name = Nuketown \nplayers=8\nname=Summit\nmotd=a=b\n",
+    );
+    let map = f.parse_as_key_value().unwrap();
+    assert_eq!(
+        map.into_iter().collect::<Vec<_>>(),
+        [
+            ("motd".to_string(), "a=b".to_string()),
+            // The last value wins.
+            ("name".to_string(), "Summit".to_string()),
+            ("players".to_string(), "8".to_string()),
+        ]
+    );
+
+    let e = raw_file(b"a=1\njunk\n").parse_as_key_value().unwrap_err();
+    assert!(
+        matches!(e.kind(), ErrorKind::BrokenInvariant(s) if s.contains("line 2")),
+        "{e}"
+    );
+}
+
+#[test]
+fn config_table() {
+    let table = raw_file(b"weapon,damage,range\nak47_mp,40,1500\nknife_mp,135\n")
+        .parse_as_config_table()
+        .unwrap();
+    assert_eq!(table.len(), 2);
+    assert_eq!(table[0]["weapon"], "ak47_mp");
+    assert_eq!(table[0]["range"], "1500");
+    // Short rows leave out the missing columns.
+    assert_eq!(table[1]["damage"], "135");
+    assert!(!table[1].contains_key("range"));
+
+    assert!(raw_file(b"").parse_as_config_table().unwrap().is_empty());
+    assert!(
+        raw_file(b"a,b\n")
+            .parse_as_config_table()
+            .unwrap()
+            .is_empty()
+    );
+
+    let e = raw_file(b"a,b\n1,2\n1,2,3\n")
+        .parse_as_config_table()
+        .unwrap_err();
+    assert!(
+        matches!(e.kind(), ErrorKind::BrokenInvariant(s) if s.contains("row 3")),
+        "{e}"
+    );
+}
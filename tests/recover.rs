@@ -0,0 +1,122 @@
+//! Checks that assets can be recovered from a blob with a damaged asset
+//! list.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    misc::{LocalizeEntry, RawFile, StringTable},
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+};
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, T5XFileSerializerBuilder, recover};
+
+use common::TempFile;
+
+const SCRIPT: &str = "init()\n{\n    level.recovered = true;\n}\n";
+
+/// Where the asset table starts: past the [`XFile`] and the asset list.
+///
+/// [`XFile`]: t5_xfile_defs::XFile
+const ASSET_TABLE_START: usize = 36 + 16;
+
+fn assets() -> Vec<XAsset> {
+    let mut script = SCRIPT.as_bytes().to_vec();
+    script.push(0);
+
+    vec![
+        XAsset::PC(XAssetGeneric::LocalizeEntry(Some(Box::new(
+            LocalizeEntry::new("MENU_RECOVERED", "Recovered"),
+        )))),
+        XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+            name: XString::from("maps/mp/_recovered.gsc".to_string()),
+            buffer: script,
+        })))),
+        XAsset::PC(XAssetGeneric::StringTable(Some(Box::new(
+            StringTable::from_csv("mp/recovered.csv", "a,b\nc,d\n").unwrap(),
+        )))),
+    ]
+}
+
+/// The inflated blob of a Fastfile holding [`assets`], with its asset
+/// table overwritten with junk.
+fn corrupted_blob(name: &str) -> Vec<u8> {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>(assets().into_iter()).unwrap();
+    let temp = TempFile::new(&format!("recover_{name}.ff"), ser.deflate().unwrap());
+
+    let mut file = temp.open();
+    let mut blob = T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .unwrap()
+        .blob()
+        .to_vec();
+
+    let table = ASSET_TABLE_START..ASSET_TABLE_START + assets().len() * 8;
+    blob[table].fill(0xAB);
+    blob
+}
+
+#[test]
+fn asset_list_is_unusable() {
+    let temp = TempFile::new("recover.cache", corrupted_blob("unusable"));
+
+    let result = common::deserialize(&temp, true);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn recovers_assets() {
+    let recovered = recover::scan(&corrupted_blob("recovers"));
+
+    let found = recovered
+        .iter()
+        .map(|r| (r.asset.asset_type(), r.asset.name().unwrap_or_default()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            (XAssetType::LOCALIZE_ENTRY, "MENU_RECOVERED"),
+            (XAssetType::RAWFILE, "maps/mp/_recovered.gsc"),
+            (XAssetType::STRINGTABLE, "mp/recovered.csv"),
+        ]
+    );
+
+    for r in &recovered {
+        assert!(r.asset.is_pc());
+        assert_eq!(r.confidence, 1.0, "{:?}", r.asset.asset_type());
+    }
+
+    // The assets follow each other with nothing in between.
+    assert_eq!(recovered[0].offset, ASSET_TABLE_START as u64 + 24);
+    for pair in recovered.windows(2) {
+        assert_eq!(pair[0].offset + pair[0].len, pair[1].offset);
+    }
+
+    let XAsset::PC(XAssetGeneric::RawFile(Some(rawfile))) = &recovered[1].asset else {
+        unreachable!();
+    };
+    assert_eq!(rawfile.as_str(), Some(SCRIPT));
+
+    let XAsset::PC(XAssetGeneric::StringTable(Some(table))) = &recovered[2].asset else {
+        unreachable!();
+    };
+    assert_eq!(table.cell(1, 1), Some("d"));
+}
+
+#[test]
+fn ignores_noise() {
+    let mut blob = vec![0xFF; 4096];
+    for (i, b) in blob.iter_mut().enumerate().filter(|(i, _)| i % 7 == 0) {
+        *b = (i % 251) as u8;
+    }
+    assert!(recover::scan(&blob).is_empty());
+    assert!(recover::scan(&[]).is_empty());
+}
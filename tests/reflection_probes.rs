@@ -0,0 +1,132 @@
+//! Checks [`GfxReflectionProbe`]'s accessors, and [`GfxWorld`]'s probe
+//! lookups against a level from the local corpus. See
+//! [`t5_xfile_deserializer::corpus`] for how to set that up.
+//!
+//! [`GfxWorld`]: t5_xfile_defs::gfx_world::GfxWorld
+
+use t5_xfile_defs::{
+    XString,
+    common::Vec3,
+    gfx_world::GfxReflectionProbe,
+    techset::{GfxImage, MapType},
+};
+
+fn probe(origin: [f32; 3], image: Option<GfxImage>) -> GfxReflectionProbe {
+    GfxReflectionProbe {
+        origin: Vec3::from(origin),
+        image: image.map(Box::new),
+        probe_volumes: Vec::new(),
+    }
+}
+
+#[test]
+fn probe_accessors() {
+    let cubemap = GfxImage {
+        name: XString::from("*reflection_probe3"),
+        map_type: MapType::CUBE,
+        ..Default::default()
+    };
+
+    let p = probe([128.0, -64.0, 32.0], Some(cubemap));
+    assert_eq!(p.position().get(), [128.0, -64.0, 32.0]);
+    let image = p.image().unwrap();
+    assert_eq!(image.name.get(), "*reflection_probe3");
+    assert!(image.is_cubemap());
+    assert_eq!(image.face_count(), 6);
+
+    assert!(probe([0.0; 3], None).image().is_none());
+
+    let flat = GfxImage {
+        map_type: MapType::TWO_DIMENSIONAL,
+        ..Default::default()
+    };
+    assert!(!flat.is_cubemap());
+    assert_eq!(flat.face_count(), 1);
+}
+
+#[cfg(feature = "corpus")]
+mod corpus {
+    use t5_xfile_defs::{
+        ErrorKind,
+        common::Vec3,
+        gfx_world::GfxWorld,
+        xasset::{XAsset, XAssetGeneric},
+    };
+    use t5_xfile_deserializer::{
+        T5XFileDeserializerBuilder,
+        corpus::{corpus_dir, find_files},
+    };
+
+    /// A multiplayer level, so it's sure to have reflection probes.
+    const LEVEL: &str = "mp_array.ff";
+
+    fn level() -> Vec<XAsset> {
+        let dir = corpus_dir().unwrap();
+        let file = find_files(&dir)
+            .unwrap()
+            .into_iter()
+            .find(|f| f.path.file_name().is_some_and(|n| n == LEVEL))
+            .unwrap_or_else(|| panic!("{LEVEL} not found in {}", dir.display()));
+
+        let mut f = std::fs::File::open(&file.path).unwrap();
+        T5XFileDeserializerBuilder::from_file(&mut f, file.platform, true)
+            .with_silent(true)
+            .build()
+            .and_then(|de| de.inflate())
+            .and_then(|de| de.no_cache())
+            .and_then(|de| de.deserialize_remaining())
+            .unwrap()
+    }
+
+    fn check_probes<const N: usize>(world: &GfxWorld<N>) {
+        let count = world.reflection_probe_count();
+        assert!(count > 0, "no reflection probes");
+
+        for i in 0..count {
+            let probe = world.reflection_probe_at(i).unwrap();
+            if let Some(image) = probe.image() {
+                assert!(image.is_cubemap(), "probe {i} isn't a cubemap");
+            }
+
+            // A probe's own origin is nearest to it, or to one at the same
+            // spot.
+            let (nearest, dist) = world.nearest_reflection_probe(probe.position()).unwrap();
+            assert_eq!(dist, 0.0);
+            assert_eq!(
+                world.reflection_probe_at(nearest).unwrap().position().get(),
+                probe.position().get()
+            );
+        }
+
+        let e = world.reflection_probe_at(count).unwrap_err();
+        assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+
+        // Checked against a linear scan from somewhere off in the corner.
+        let point = Vec3::from(world.mins.get().map(|c| c - 1000.0));
+        let (nearest, dist) = world.nearest_reflection_probe(point).unwrap();
+        let min = (0..count)
+            .map(|i| (world.reflection_probe_at(i).unwrap().position() - point).length())
+            .fold(f32::INFINITY, f32::min);
+        assert_eq!(dist, min);
+        assert_eq!(
+            (world.reflection_probe_at(nearest).unwrap().position() - point).length(),
+            min
+        );
+    }
+
+    #[test]
+    #[ignore = "needs a directory of Fastfiles in T5_CORPUS_DIR"]
+    fn world_probes() {
+        let mut worlds = 0;
+        for asset in &level() {
+            match asset {
+                XAsset::PC(XAssetGeneric::GfxWorld(Some(world))) => check_probes(world),
+                XAsset::Console(XAssetGeneric::GfxWorld(Some(world))) => check_probes(world),
+                _ => continue,
+            }
+            worlds += 1;
+        }
+
+        assert_eq!(worlds, 1, "expected one GfxWorld in {LEVEL}");
+    }
+}
@@ -0,0 +1,345 @@
+//! Checks that [`ResourceLimits`] stop crafted blobs before they allocate
+//! what they ask for.
+
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicIsize, Ordering},
+};
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform,
+    limits::{ResourceLimit, ResourceLimits},
+};
+use t5_xfile_deserializer::{T5XFileDeserializer, T5XFileDeserializerBuilder};
+
+use common::{TempFile, u32s};
+
+const RAWFILE: u32 = 0x24;
+const INLINE: u32 = 0xFFFFFFFF;
+
+/// Nothing under test should get anywhere near this.
+const SMALL: usize = 1 << 20;
+
+/// Counts the bytes allocated by threads that have set [`TRACKING`].
+struct CountingAlloc;
+
+static CURRENT: AtomicIsize = AtomicIsize::new(0);
+static PEAK: AtomicIsize = AtomicIsize::new(0);
+
+thread_local! {
+    static TRACKING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn tracking() -> bool {
+    TRACKING.try_with(Cell::get).unwrap_or(false)
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if tracking() {
+            let size = layout.size() as isize;
+            let current = CURRENT.fetch_add(size, Ordering::SeqCst) + size;
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if tracking() {
+            CURRENT.fetch_sub(layout.size() as isize, Ordering::SeqCst);
+        }
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// Runs `f`, returning what it returned and the most bytes this thread had
+/// allocated at once while it ran.
+fn peak_allocated<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    CURRENT.store(0, Ordering::SeqCst);
+    PEAK.store(0, Ordering::SeqCst);
+    TRACKING.with(|t| t.set(true));
+    let t = f();
+    TRACKING.with(|t| t.set(false));
+    (t, PEAK.load(Ordering::SeqCst) as usize)
+}
+
+/// An inflated blob whose asset list claims `strings` script strings and
+/// `assets` assets, followed by `data`.
+fn blob(strings: u32, assets: u32, data: &[u8]) -> Vec<u8> {
+    let mut body = u32s(&[strings, INLINE, assets, INLINE]);
+    body.extend(data);
+
+    let mut blob = u32s(&[body.len() as u32, 0, 0, 0, 0, 0, 0, 0, 0]);
+    blob.extend(body);
+    blob
+}
+
+/// An asset list entry and raw file for each of `files`, whose lengths are
+/// as claimed but whose contents are only there if `contents` is set.
+fn raw_files(files: &[(&str, u32)], contents: bool) -> Vec<u8> {
+    let mut data = Vec::new();
+    for _ in files {
+        data.extend(u32s(&[RAWFILE, INLINE]));
+    }
+    for (name, len) in files {
+        data.extend(u32s(&[INLINE, *len, INLINE]));
+        data.extend(name.as_bytes());
+        data.push(0);
+        if contents {
+            data.resize(data.len() + *len as usize, b'a');
+            data.push(0);
+        }
+    }
+    data
+}
+
+/// Builds a deserializer for `blob` with `limits`, stopping at the first
+/// error, and runs `f` on it.
+fn with_cache(
+    name: &str,
+    blob: &[u8],
+    limits: ResourceLimits,
+    f: impl FnOnce(T5XFileDeserializer) -> t5_xfile_defs::Result<()>,
+) -> t5_xfile_defs::Result<()> {
+    let temp = TempFile::new(&format!("limits_{name}.cache"), blob);
+
+    let mut file = temp.open();
+
+    T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .with_resource_limits(limits)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .and_then(f)
+}
+
+fn assert_limit(
+    result: t5_xfile_defs::Result<()>,
+    expected_which: ResourceLimit,
+    expected_limit: u64,
+    expected_requested: u64,
+) {
+    let e = result.unwrap_err();
+    match e.kind() {
+        &ErrorKind::ResourceLimit {
+            which,
+            limit,
+            requested,
+        } => assert_eq!(
+            (which, limit, requested),
+            (expected_which, expected_limit, expected_requested),
+            "{e}"
+        ),
+        _ => panic!("expected a resource limit error, got {e}"),
+    }
+}
+
+#[test]
+fn assets() {
+    // The default limit is enough to catch this one.
+    let (result, peak) = peak_allocated(|| {
+        with_cache(
+            "assets",
+            &blob(0, 100_000_000, &[]),
+            ResourceLimits::default(),
+            |_| Ok(()),
+        )
+    });
+    assert_limit(result, ResourceLimit::Assets, 1 << 20, 100_000_000);
+    assert!(peak < SMALL, "{peak} bytes allocated");
+
+    let files = [("a.cfg", 1), ("b.cfg", 1)];
+    let limits = ResourceLimits {
+        max_assets: Some(1),
+        ..Default::default()
+    };
+    let result = with_cache(
+        "assets_2",
+        &blob(0, 2, &raw_files(&files, true)),
+        limits,
+        |_| Ok(()),
+    );
+    assert_limit(result, ResourceLimit::Assets, 1, 2);
+
+    let limits = ResourceLimits {
+        max_assets: Some(2),
+        ..Default::default()
+    };
+    with_cache(
+        "assets_ok",
+        &blob(0, 2, &raw_files(&files, true)),
+        limits,
+        |de| {
+            assert_eq!(de.deserialize_remaining()?.len(), 2);
+            Ok(())
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn script_strings() {
+    let (result, peak) = peak_allocated(|| {
+        with_cache(
+            "strings",
+            &blob(u32::MAX, 0, &[]),
+            ResourceLimits::default(),
+            |_| Ok(()),
+        )
+    });
+    assert_limit(
+        result,
+        ResourceLimit::ScriptStrings,
+        u16::MAX as u64 + 1,
+        u32::MAX as u64,
+    );
+    assert!(peak < SMALL, "{peak} bytes allocated");
+}
+
+#[test]
+fn array_len() {
+    // A length of -2 wraps around to a buffer of `usize::MAX` bytes. By
+    // default no array can be longer than the blob.
+    let huge = blob(0, 1, &raw_files(&[("huge.bin", -2i32 as u32)], false));
+    let (result, peak) = peak_allocated(|| {
+        with_cache("array", &huge, ResourceLimits::default(), |de| {
+            de.deserialize_remaining().map(|_| ())
+        })
+    });
+    assert_limit(
+        result,
+        ResourceLimit::ArrayLen,
+        huge.len() as u64,
+        usize::MAX as u64,
+    );
+    assert!(peak < SMALL, "{peak} bytes allocated");
+
+    // Raw file buffers include their null terminator.
+    let files = raw_files(&[("a.cfg", 100)], true);
+    let limits = ResourceLimits {
+        max_array_len: Some(100),
+        ..Default::default()
+    };
+    let result = with_cache("array_2", &blob(0, 1, &files), limits, |de| {
+        de.deserialize_remaining().map(|_| ())
+    });
+    assert_limit(result, ResourceLimit::ArrayLen, 100, 101);
+
+    let limits = ResourceLimits {
+        max_array_len: Some(101),
+        ..Default::default()
+    };
+    with_cache("array_ok", &blob(0, 1, &files), limits, |de| {
+        de.deserialize_remaining().map(|_| ())
+    })
+    .unwrap();
+}
+
+#[test]
+fn allocated_bytes() {
+    // Each file takes 8 bytes for its asset list entry, 6 for its name and
+    // 101 for its buffer.
+    let files = raw_files(&[("a.cfg", 100), ("b.cfg", 100), ("c.cfg", 100)], true);
+    let limits = ResourceLimits {
+        max_allocated_bytes: Some(8 * 3 + 2 * (6 + 101)),
+        ..Default::default()
+    };
+    let result = with_cache("bytes", &blob(0, 3, &files), limits, |de| {
+        de.deserialize_remaining().map(|_| ())
+    });
+    assert_limit(
+        result,
+        ResourceLimit::AllocatedBytes,
+        8 * 3 + 2 * (6 + 101),
+        8 * 3 + 2 * (6 + 101) + 6,
+    );
+
+    let limits = ResourceLimits {
+        max_allocated_bytes: Some(8 * 3 + 3 * (6 + 101)),
+        ..Default::default()
+    };
+    with_cache("bytes_ok", &blob(0, 3, &files), limits, |de| {
+        assert_eq!(de.deserialize_remaining()?.len(), 3);
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn inflated_size() {
+    let blob = blob(0, 1, &raw_files(&[("a.cfg", 4096)], true));
+    let limits = ResourceLimits {
+        max_inflated_size: Some(1024),
+        ..Default::default()
+    };
+    let result = with_cache("inflated", &blob, limits, |_| Ok(()));
+    assert_limit(result, ResourceLimit::InflatedSize, 1024, 1025);
+
+    let limits = ResourceLimits {
+        max_inflated_size: Some(blob.len() as _),
+        ..Default::default()
+    };
+    with_cache("inflated_ok", &blob, limits, |_| Ok(())).unwrap();
+}
+
+#[cfg(feature = "serializer")]
+#[test]
+fn inflated_size_compressed() {
+    use t5_xfile_defs::{
+        XString,
+        misc::RawFile,
+        xasset::{XAsset, XAssetGeneric},
+    };
+    use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+    // Compresses to almost nothing, but inflates to 16 MiB.
+    const LEN: usize = 16 << 20;
+    let asset = XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+        name: XString::from("bomb.bin".to_string()),
+        buffer: vec![0; LEN],
+    }))));
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>([asset].into_iter()).unwrap();
+    let bytes = ser.deflate().unwrap();
+    assert!(bytes.len() < LEN / 100);
+
+    let temp = TempFile::new("limits_bomb.ff", bytes);
+    let mut file = temp.open();
+    let (result, peak) = peak_allocated(|| {
+        T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .with_resource_limits(ResourceLimits {
+                max_inflated_size: Some(1 << 16),
+                ..Default::default()
+            })
+            .build()
+            .and_then(|de| de.inflate())
+            .map(|_| ())
+    });
+
+    let e = result.unwrap_err();
+    assert!(
+        matches!(
+            e.kind(),
+            ErrorKind::ResourceLimit {
+                which: ResourceLimit::InflatedSize,
+                limit: 0x10000,
+                ..
+            }
+        ),
+        "{e}"
+    );
+    assert!(peak < SMALL, "{peak} bytes allocated");
+}
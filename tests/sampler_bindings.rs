@@ -0,0 +1,130 @@
+//! Checks [`Material::sampler_bindings`] and the lookups built on it for a
+//! lit material.
+
+use t5_xfile_defs::{
+    XString,
+    techset::{
+        GfxImage, Material, MaterialArgumentDef, MaterialPass, MaterialShaderArgument,
+        MaterialTechnique, MaterialTechniqueSet, MaterialTextureDef, MaterialTextureDefInfo,
+        MtlArg, Semantic, TechniqueType,
+    },
+};
+
+fn texture(name: &str, semantic: Semantic, image: Option<&str>) -> MaterialTextureDef {
+    MaterialTextureDef {
+        name_hash: MaterialTextureDef::hash_name(name),
+        name_start: name.chars().next().unwrap(),
+        name_end: name.chars().last().unwrap(),
+        semantic,
+        u: MaterialTextureDefInfo::Image(image.map(|name| {
+            Box::new(GfxImage {
+                name: XString::from(name.to_string()),
+                ..Default::default()
+            })
+        })),
+        ..Default::default()
+    }
+}
+
+/// A technique with one pass that binds each of `samplers` at its register.
+fn technique(name: &str, samplers: &[(&str, u16)]) -> Box<MaterialTechnique> {
+    let args = samplers
+        .iter()
+        .map(|&(name, dest)| MaterialShaderArgument {
+            arg_type: MtlArg::MATERIAL_PIXEL_SAMPLER,
+            dest,
+            u: MaterialArgumentDef::NameHash(MaterialTextureDef::hash_name(name)),
+        })
+        .collect();
+
+    Box::new(MaterialTechnique {
+        name: XString::from(name.to_string()),
+        flags: 0,
+        passes: vec![MaterialPass {
+            vertex_decl: None,
+            vertex_shader: None,
+            pixel_shader: None,
+            per_prim_arg_count: 0,
+            per_obj_arg_count: 0,
+            stable_arg_count: samplers.len() as _,
+            custom_sampler_flags: 0,
+            args,
+        }],
+    })
+}
+
+fn lit_material(textures: Vec<MaterialTextureDef>) -> Material {
+    let mut techniques = vec![None; 130];
+    techniques[TechniqueType::DEPTH_PREPASS.slot()] = Some(technique("depth_prepass", &[]));
+    techniques[TechniqueType::LIT_SUN.slot()] = Some(technique(
+        "lit_sun",
+        &[("colorMap", 0), ("normalMap", 3), ("specularMap", 5)],
+    ));
+
+    Material {
+        textures,
+        technique_set: Some(Box::new(MaterialTechniqueSet {
+            name: XString::from("mc_l_sm_r0c0n0s0".to_string()),
+            world_vert_format: 0,
+            techset_flags: 0,
+            techniques,
+        })),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn lit_material_bindings() {
+    let material = lit_material(vec![
+        texture("colorMap", Semantic::COLOR_MAP, Some("~wall_col")),
+        texture("normalMap", Semantic::NORMAL_MAP, Some("~wall_nml")),
+        texture("specularMap", Semantic::SPECULAR_MAP, None),
+        texture("mysteryMap", Semantic::FUNCTION, Some("mystery")),
+    ]);
+
+    let bindings = material.sampler_bindings();
+    let names = bindings.iter().map(|b| b.sampler_name).collect::<Vec<_>>();
+    assert_eq!(names, ["colorMap", "normalMap", "specularMap", "unknown"]);
+    let slots = bindings.iter().map(|b| b.sampler_slot).collect::<Vec<_>>();
+    // The last texture isn't bound by any argument, so it keeps its index.
+    assert_eq!(slots, [0, 3, 5, 3]);
+    assert_eq!(bindings[1].image.unwrap().name.get(), "~wall_nml");
+    assert!(bindings[2].image.is_none());
+
+    assert_eq!(
+        material
+            .image_by_sampler_name("colorMap")
+            .unwrap()
+            .name
+            .get(),
+        "~wall_col"
+    );
+    assert!(material.image_by_sampler_name("specularMap").is_none());
+    assert!(material.image_by_sampler_name("detailMap").is_none());
+    assert!(material.missing_required_samplers().is_empty());
+}
+
+#[test]
+fn missing_required_samplers() {
+    assert_eq!(
+        TechniqueType::LIT_SUN.required_samplers(),
+        ["colorMap", "normalMap"]
+    );
+    assert_eq!(TechniqueType::UNLIT.required_samplers(), ["colorMap"]);
+    assert!(TechniqueType::DEPTH_PREPASS.required_samplers().is_empty());
+
+    // An unknown hash falls back to the name for the texture's semantic.
+    let mut normal_map = texture("normalMap", Semantic::NORMAL_MAP, None);
+    normal_map.name_hash = 0x1234;
+    let material = lit_material(vec![normal_map]);
+    assert_eq!(material.sampler_bindings()[0].sampler_name, "normalMap");
+    assert_eq!(
+        material.missing_required_samplers(),
+        ["colorMap", "normalMap"]
+    );
+
+    let material = lit_material(vec![texture("colorMap", Semantic::COLOR_MAP, Some("c"))]);
+    assert_eq!(material.missing_required_samplers(), ["normalMap"]);
+
+    assert!(Material::default().missing_required_samplers().is_empty());
+}
@@ -0,0 +1,112 @@
+//! Checks that the serializer's script string table is deduplicated, in
+//! first-use order, and reads back the same.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    ErrorKind, ScriptString, T5XFileDeserialize, T5XFileSerialize, XFilePlatform, XString,
+    misc::RawFile,
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, T5XFileSerializer};
+
+use common::TempFile;
+
+/// Bone tags as two assets would intern them, with plenty of overlap.
+const TAGS: [&str; 9] = [
+    "tag_origin",
+    "j_head",
+    "tag_flash",
+    "j_head",
+    "",
+    "tag_origin",
+    "tag_brass",
+    "",
+    "tag_flash",
+];
+
+fn serializer() -> T5XFileSerializer {
+    T5XFileSerializer::new(true, XFilePlatform::Windows).unwrap()
+}
+
+fn intern_all(ser: &mut T5XFileSerializer, tags: &[&str]) -> Vec<u16> {
+    tags.iter().map(|t| ser.intern(t).unwrap().0).collect()
+}
+
+#[test]
+fn first_use_order() {
+    let mut ser = serializer();
+    assert_eq!(intern_all(&mut ser, &TAGS), [0, 1, 2, 1, 3, 0, 4, 3, 2]);
+    assert_eq!(
+        ser.script_strings(),
+        ["tag_origin", "j_head", "tag_flash", "", "tag_brass"]
+    );
+
+    // The same strings in the same order always get the same table.
+    let mut again = serializer();
+    intern_all(&mut again, &TAGS);
+    assert_eq!(again.script_strings(), ser.script_strings());
+
+    // Reinterning doesn't move anything.
+    assert_eq!(intern_all(&mut ser, &["tag_brass", "j_head"]), [4, 1]);
+    assert_eq!(ser.script_strings().len(), 5);
+}
+
+#[test]
+fn round_trip() {
+    let mut ser = serializer();
+    let indices = intern_all(&mut ser, &TAGS);
+    ser.serialize_assets::<1>(
+        [XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(
+            RawFile {
+                name: XString::from("tags.cfg"),
+                buffer: Vec::new(),
+            },
+        ))))]
+        .into_iter(),
+    )
+    .unwrap();
+    let bytes = ser.deflate().unwrap();
+
+    let temp = TempFile::new("script_strings.ff", bytes);
+    let mut file = temp.open();
+    let de = T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache());
+    let de = de.unwrap();
+
+    for (tag, i) in TAGS.iter().zip(indices) {
+        let s = de.get_script_string(ScriptString(i)).unwrap();
+        assert_eq!(s.unwrap_or_default(), *tag, "index {i}");
+    }
+    assert_eq!(de.get_script_string(ScriptString(5)).unwrap(), None);
+}
+
+#[test]
+fn non_latin1() {
+    let mut ser = serializer();
+    let e = ser.intern("tag_武器").unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BadChar(0x6B66)), "{e}");
+    assert!(ser.script_strings().is_empty());
+
+    // Latin-1 is one byte per char, so it's fine.
+    assert_eq!(ser.intern("tag_señal").unwrap().0, 0);
+}
+
+#[test]
+fn overflow() {
+    let mut ser = serializer();
+    for i in 0..=u16::MAX as u32 {
+        assert_eq!(ser.intern(&format!("s{i}")).unwrap().0 as u32, i);
+    }
+
+    // 0xFFFF was the last index, so the table's full...
+    let e = ser.intern("one_too_many").unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::ScriptStringOverflow), "{e}");
+    // ...but strings that are already in it still resolve.
+    assert_eq!(ser.intern("s65535").unwrap().0, 0xFFFF);
+}
@@ -0,0 +1,123 @@
+//! Checks that [`T5XFileSerializer::measure`] agrees with the number of
+//! bytes an asset actually takes up in a serialized blob.
+
+#![cfg(feature = "serializer")]
+
+use std::collections::BTreeMap;
+
+use t5_xfile_defs::{
+    CompressionMethod, XFilePlatform, XString,
+    font::{Font, Glyph},
+    misc::{LocalizeEntry, RawFile},
+    xasset::{AssetListAsset, XAsset, XAssetGeneric, XAssetType},
+};
+use t5_xfile_deserializer::{T5XFileSerializer, T5XFileSerializerBuilder};
+
+/// An asset to serialize alongside the ones being measured, since a blob
+/// can't be empty.
+fn base(platform: XFilePlatform) -> XAsset {
+    let raw_file = Some(Box::new(RawFile {
+        name: XString::from("base.gsc"),
+        buffer: b"\0".to_vec(),
+    }));
+    if platform.is_pc() {
+        XAsset::PC(XAssetGeneric::RawFile(raw_file))
+    } else {
+        XAsset::Console(XAssetGeneric::RawFile(raw_file))
+    }
+}
+
+/// How much longer the uncompressed blob gets when `assets` are added to
+/// it.
+fn added_len(platform: XFilePlatform, assets: Vec<XAsset>) -> usize {
+    let blob_len = |assets: Vec<XAsset>| {
+        let mut ser = T5XFileSerializerBuilder::new(platform)
+            .with_silent(true)
+            .with_compression(CompressionMethod::Uncompressed)
+            .build()
+            .unwrap();
+        ser.serialize_assets::<1>([base(platform)].into_iter().chain(assets))
+            .unwrap();
+        ser.deflate().unwrap().len()
+    };
+
+    blob_len(assets) - blob_len(Vec::new())
+}
+
+fn pc_assets() -> Vec<XAsset> {
+    let mut glyph = Glyph::default();
+    glyph.letter = b'A' as _;
+
+    [
+        XAssetGeneric::RawFile(Some(Box::new(RawFile {
+            name: XString::from("maps/mp/measure.gsc"),
+            buffer: b"main() {}\0".to_vec(),
+        }))),
+        XAssetGeneric::Font(Some(Box::new(Font {
+            font_name: XString::from("fonts/measure"),
+            pixel_height: 16,
+            material: None,
+            glow_material: None,
+            glyphs: vec![glyph; 3],
+        }))),
+        XAssetGeneric::AssetList(Some(Box::new(AssetListAsset {
+            name: XString::from("measure_assets"),
+            entries: BTreeMap::from([(
+                XAssetType::RAWFILE,
+                vec![XString::from("a.gsc"), XString::from("bb.gsc")],
+            )]),
+        }))),
+        XAssetGeneric::LocalizeEntry(Some(Box::new(LocalizeEntry {
+            value: XString::from("Measure"),
+            name: XString::from("MENU_MEASURE"),
+        }))),
+    ]
+    .into_iter()
+    .map(XAsset::PC)
+    .collect()
+}
+
+#[test]
+fn matches_serialized_length() {
+    let mut total = 0;
+    for asset in pc_assets() {
+        let sizes = T5XFileSerializer::measure(&asset).unwrap();
+        assert_eq!(
+            sizes.total,
+            sizes.structs + sizes.strings + sizes.arrays,
+            "{asset:?}"
+        );
+        assert_eq!(
+            sizes.total,
+            added_len(XFilePlatform::Windows, vec![asset.clone()]),
+            "{asset:?}"
+        );
+        total += sizes.total;
+    }
+
+    // Assets don't share anything but script strings, so the sizes add up.
+    assert_eq!(total, added_len(XFilePlatform::Windows, pc_assets()));
+}
+
+#[test]
+fn breakdown() {
+    let raw_file = XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+        name: XString::from("a.gsc"),
+        buffer: b"main() {}\0".to_vec(),
+    }))));
+    let sizes = T5XFileSerializer::measure(&raw_file).unwrap();
+    // The XAsset entry and the RawFile struct.
+    assert_eq!(sizes.structs, 8 + 12);
+    // The name and the buffer (which is stored like a string).
+    assert_eq!(sizes.strings + sizes.arrays, "a.gsc\0".len() + 10);
+}
+
+#[test]
+fn console() {
+    let asset = XAsset::Console(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+        name: XString::from("maps/mp/console.gsc"),
+        buffer: b"main() {}\0".to_vec(),
+    }))));
+    let sizes = T5XFileSerializer::measure(&asset).unwrap();
+    assert_eq!(sizes.total, added_len(XFilePlatform::Xbox360, vec![asset]));
+}
@@ -0,0 +1,151 @@
+//! Checks [`MaterialTechnique::arguments`] on a pass with one argument of
+//! each kind.
+
+use t5_xfile_defs::{
+    XString,
+    techset::{
+        Material, MaterialArgumentCodeConst, MaterialArgumentDef, MaterialConstantDef,
+        MaterialPass, MaterialShaderArgument, MaterialTechnique, MaterialTextureDef, MtlArg,
+        ShaderArgumentSampler, ShaderArgumentValue,
+    },
+};
+
+const COLOR_TINT_HASH: u32 = 0x3D9994DC;
+const COLOR_MAP_HASH: u32 = 0xA0AB1041;
+
+fn arg(arg_type: MtlArg, dest: u16, u: MaterialArgumentDef) -> MaterialShaderArgument {
+    MaterialShaderArgument { arg_type, dest, u }
+}
+
+fn technique() -> MaterialTechnique {
+    let args = vec![
+        arg(
+            MtlArg::CODE_VERTEX_CONST,
+            0,
+            MaterialArgumentDef::CodeConst(MaterialArgumentCodeConst {
+                index: 58,
+                first_row: 0,
+                row_count: 4,
+            }),
+        ),
+        arg(
+            MtlArg::MATERIAL_PIXEL_SAMPLER,
+            0,
+            MaterialArgumentDef::NameHash(COLOR_MAP_HASH),
+        ),
+        arg(
+            MtlArg::CODE_PIXEL_SAMPLER,
+            1,
+            MaterialArgumentDef::CodeSampler(9),
+        ),
+        arg(
+            MtlArg::MATERIAL_PRIM_END,
+            2,
+            MaterialArgumentDef::NameHash(COLOR_TINT_HASH),
+        ),
+        arg(
+            MtlArg::LITERAL_PIXEL_CONST,
+            3,
+            MaterialArgumentDef::LiteralConst([0.5, 1.0, 0.0, 2.0].into()),
+        ),
+    ];
+
+    MaterialTechnique {
+        name: XString::from("lit".to_string()),
+        flags: 0,
+        passes: vec![MaterialPass {
+            vertex_decl: None,
+            vertex_shader: None,
+            pixel_shader: None,
+            per_prim_arg_count: 0,
+            per_obj_arg_count: 1,
+            stable_arg_count: 4,
+            custom_sampler_flags: 0,
+            args,
+        }],
+    }
+}
+
+fn material() -> Material {
+    let mut name = [0; 12];
+    name[..9].copy_from_slice(b"colorTint");
+
+    Material {
+        textures: vec![
+            MaterialTextureDef {
+                name_hash: 0x1234,
+                ..Default::default()
+            },
+            MaterialTextureDef {
+                name_hash: COLOR_MAP_HASH,
+                ..Default::default()
+            },
+        ],
+        constants: vec![MaterialConstantDef {
+            name_hash: COLOR_TINT_HASH,
+            name,
+            literal: [1.0; 4],
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn decode_each_kind() {
+    let technique = technique();
+    let material = material();
+    let args = technique.arguments(Some(&material));
+
+    let values = args.iter().map(|a| a.value.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        [
+            ShaderArgumentValue::CodeConst {
+                index: 58,
+                first_row: 0,
+                row_count: 4
+            },
+            ShaderArgumentValue::Sampler(ShaderArgumentSampler::Material {
+                name_hash: COLOR_MAP_HASH,
+                texture_index: Some(1),
+            }),
+            ShaderArgumentValue::Sampler(ShaderArgumentSampler::Code(9)),
+            ShaderArgumentValue::MaterialConst {
+                name_hash: COLOR_TINT_HASH,
+                name: Some("colorTint".to_string()),
+            },
+            ShaderArgumentValue::LiteralConst([0.5, 1.0, 0.0, 2.0]),
+        ]
+    );
+
+    assert!(args.iter().all(|a| a.pass == 0));
+    let dests = args.iter().map(|a| a.dest).collect::<Vec<_>>();
+    assert_eq!(dests, [0, 0, 1, 2, 3]);
+    let pixel = args.iter().map(|a| a.raw.is_pixel()).collect::<Vec<_>>();
+    assert_eq!(pixel, [false, true, true, true, true]);
+
+    // The raw argument is kept as-is.
+    assert!(matches!(
+        args[3].raw.u,
+        MaterialArgumentDef::NameHash(COLOR_TINT_HASH)
+    ));
+}
+
+#[test]
+fn unresolved_without_material() {
+    let args = technique().arguments(None);
+    assert_eq!(
+        args[1].value,
+        ShaderArgumentValue::Sampler(ShaderArgumentSampler::Material {
+            name_hash: COLOR_MAP_HASH,
+            texture_index: None,
+        })
+    );
+    assert_eq!(
+        args[3].value,
+        ShaderArgumentValue::MaterialConst {
+            name_hash: COLOR_TINT_HASH,
+            name: None,
+        }
+    );
+}
@@ -0,0 +1,199 @@
+//! Checks [`T5XFileDeserializer::measure_sizes`] against the assets it
+//! measures.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    com_world::{ComBurnableCell, ComPrimaryLight, ComWorld},
+    font::{Font, Glyph},
+    misc::{LocalizeEntry, RawFile},
+    weapon::{WeaponDef, WeaponVariantDef},
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+};
+use t5_xfile_deserializer::{
+    AssetSizeEstimate, T5XFileDeserializer, T5XFileDeserializerBuilder, T5XFileSerializerBuilder,
+};
+
+use common::{
+    TempFile,
+    alloc::{CountingAlloc, retained_allocation},
+};
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+fn raw_file(name: &str, len: usize) -> XAsset {
+    let mut buffer = vec![b'x'; len];
+    buffer.push(0);
+    XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+        name: XString::from(name),
+        buffer,
+    }))))
+}
+
+fn assets() -> Vec<XAsset> {
+    vec![
+        raw_file("small.cfg", 16),
+        XAsset::PC(XAssetGeneric::LocalizeEntry(Some(Box::new(
+            LocalizeEntry::new("MENU_SIZE", "Size"),
+        )))),
+        raw_file("big.cfg", 10_000),
+    ]
+}
+
+/// Assets made mostly of structs, boxes, and arrays of them, rather than one
+/// big buffer.
+fn structured_assets() -> Vec<XAsset> {
+    let weapon = WeaponVariantDef {
+        internal_name: XString::from("ak47_mp"),
+        weap_def: Some(Box::new(WeaponDef::default())),
+        ..Default::default()
+    };
+    let world = ComWorld {
+        name: XString::from("maps/mp/mp_test.d3dbsp"),
+        primary_lights: (0..50)
+            .map(|i| ComPrimaryLight {
+                radius: i as f32,
+                ..Default::default()
+            })
+            .collect(),
+        burnable_cells: (0..100)
+            .map(|i| ComBurnableCell {
+                x: i,
+                y: i,
+                data: None,
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let font = Font {
+        font_name: XString::from("fonts/test"),
+        pixel_height: 16,
+        material: None,
+        glow_material: None,
+        glyphs: vec![Glyph::default(); 200],
+    };
+
+    vec![
+        XAsset::PC(XAssetGeneric::Weapon(Some(Box::new(weapon)))),
+        XAsset::PC(XAssetGeneric::ComWorld(Some(Box::new(world)))),
+        XAsset::PC(XAssetGeneric::Font(Some(Box::new(font)))),
+    ]
+}
+
+fn with_deserializer(f: impl FnOnce(&mut T5XFileDeserializer)) {
+    with_assets("size_estimates", assets(), f);
+}
+
+fn with_assets(name: &str, assets: Vec<XAsset>, f: impl FnOnce(&mut T5XFileDeserializer)) {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>(assets.into_iter()).unwrap();
+
+    let temp = TempFile::new(&format!("{name}.ff"), ser.deflate().unwrap());
+    let mut file = temp.open();
+    let de = T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache());
+
+    f(&mut de.unwrap());
+}
+
+#[test]
+fn estimates() {
+    with_deserializer(|de| {
+        let estimates = de.measure_sizes().unwrap();
+        assert_eq!(
+            estimates
+                .iter()
+                .map(|e| (e.index, e.asset_type, e.name.as_deref()))
+                .collect::<Vec<_>>(),
+            [
+                (0, XAssetType::RAWFILE, Some("small.cfg")),
+                (1, XAssetType::LOCALIZE_ENTRY, Some("MENU_SIZE")),
+                (2, XAssetType::RAWFILE, Some("big.cfg")),
+            ]
+        );
+
+        // A raw file is its header, its name, and its buffer, exactly.
+        assert_eq!(estimates[0].blob_bytes, 12 + 10 + 17);
+        assert_eq!(estimates[2].blob_bytes, 12 + 8 + 10_001);
+
+        // The buffer dominates the estimate, but isn't all of it.
+        for (e, len) in [(&estimates[0], 17), (&estimates[2], 10_001)] {
+            assert!(e.memory_bytes > len, "{e:?}");
+            assert!(e.memory_bytes < len + 1024, "{e:?}");
+        }
+        assert!(estimates[2].memory_bytes > estimates[0].memory_bytes);
+    });
+}
+
+#[test]
+fn rewinds() {
+    with_deserializer(|de| {
+        let pos = de.checkpoint().unwrap();
+        let before = de.measure_sizes().unwrap();
+        let after = de.measure_sizes().unwrap();
+        assert_eq!(
+            before.iter().map(|e| e.blob_bytes).collect::<Vec<_>>(),
+            after.iter().map(|e| e.blob_bytes).collect::<Vec<_>>()
+        );
+
+        // Every asset is still there to deserialize.
+        let first = de.deserialize_next().unwrap().unwrap();
+        assert_eq!(first.name(), Some("small.cfg"));
+        let rest = de.measure_sizes().unwrap();
+        assert_eq!(rest.len(), 2);
+        assert_eq!(rest[0].index, 1);
+
+        de.restore(pos).unwrap();
+        assert_eq!(de.measure_sizes().unwrap().len(), 3);
+    });
+}
+
+#[test]
+fn totals() {
+    with_deserializer(|de| {
+        let estimates = de.measure_sizes().unwrap();
+        let totals = AssetSizeEstimate::totals_by_type(&estimates);
+        assert_eq!(totals.len(), 2);
+
+        let raw = totals[&XAssetType::RAWFILE];
+        assert_eq!(raw.count, 2);
+        assert_eq!(
+            raw.blob_bytes,
+            estimates[0].blob_bytes + estimates[2].blob_bytes
+        );
+        assert_eq!(
+            raw.memory_bytes,
+            estimates[0].memory_bytes + estimates[2].memory_bytes
+        );
+        assert_eq!(totals[&XAssetType::LOCALIZE_ENTRY].count, 1);
+    });
+}
+
+#[test]
+fn matches_allocation() {
+    with_assets("size_estimates_structured", structured_assets(), |de| {
+        let estimates = de.measure_sizes().unwrap();
+        assert_eq!(estimates.len(), 3);
+
+        let mut assets = Vec::with_capacity(estimates.len());
+        for e in &estimates {
+            let (asset, allocated) = retained_allocation(|| de.deserialize_next().unwrap());
+            assets.push(asset.unwrap());
+
+            // The estimate also counts the asset's stack part.
+            let actual = (allocated + size_of::<XAsset>()) as f64;
+            let ratio = e.memory_bytes as f64 / actual;
+            assert!((0.8..1.2).contains(&ratio), "{e:?}: {actual} allocated");
+        }
+    });
+}
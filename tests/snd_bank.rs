@@ -0,0 +1,123 @@
+//! Checks [`SndBank::validate`].
+
+use t5_xfile_defs::{
+    XString,
+    sound::{
+        SndAlias, SndAliasList, SndBank, SndDriverGlobals, SndSnapshotGroup, SoundFile,
+        SoundFileRef, StreamedSound,
+    },
+};
+
+fn alias(name: &str) -> SndAlias {
+    SndAlias {
+        name: XString::from(name.to_string()),
+        sound_file: Some(Box::new(SoundFile {
+            u: SoundFileRef::Streamed(Some(Box::new(StreamedSound {
+                filename: XString::from(format!("{name}.wav")),
+                prime_snd: None,
+            }))),
+            exists: true,
+        })),
+        vol_min: 0x8000,
+        vol_max: 0xFFFF,
+        ..Default::default()
+    }
+}
+
+fn alias_list(name: &str, variants: usize) -> SndAliasList {
+    SndAliasList {
+        name: XString::from(name.to_string()),
+        aliases: vec![alias(name); variants],
+        ..Default::default()
+    }
+}
+
+fn bank(aliases: Vec<SndAliasList>) -> SndBank {
+    SndBank {
+        name: XString::from("test.all".to_string()),
+        aliases,
+        ..Default::default()
+    }
+}
+
+fn globals(snapshot_groups: usize) -> SndDriverGlobals {
+    SndDriverGlobals {
+        snapshot_groups: vec![
+            SndSnapshotGroup {
+                name: XString::from("default".to_string()),
+            };
+            snapshot_groups
+        ],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn valid() {
+    let b = bank(vec![alias_list("wpn_fire", 3), alias_list("wpn_reload", 1)]);
+
+    assert_eq!(b.alias_count(), 4);
+    assert_eq!(b.alias_list_count(), 2);
+    assert!(b.validate().is_valid());
+    assert!(b.validate_with_globals(&globals(1)).is_valid());
+}
+
+#[test]
+fn duplicate_alias_names() {
+    let b = bank(vec![
+        alias_list("wpn_fire", 1),
+        alias_list("wpn_reload", 1),
+        alias_list("wpn_fire", 1),
+        alias_list("wpn_fire", 1),
+    ]);
+
+    let report = b.validate();
+    assert_eq!(report.duplicate_alias_names, ["wpn_fire"]);
+    assert!(report.null_sound_files.is_empty());
+    assert!(!report.is_valid());
+}
+
+#[test]
+fn null_sound_files() {
+    let mut b = bank(vec![alias_list("wpn_fire", 2), alias_list("wpn_reload", 1)]);
+    b.aliases[0].aliases[1].sound_file = None;
+    b.aliases[1].aliases[0].sound_file = Some(Box::new(SoundFile {
+        u: SoundFileRef::Loaded(None),
+        exists: false,
+    }));
+
+    let report = b.validate();
+    assert_eq!(report.null_sound_files, ["wpn_fire", "wpn_reload"]);
+    assert!(!report.is_valid());
+}
+
+#[test]
+fn inverted_volumes() {
+    let mut b = bank(vec![alias_list("wpn_fire", 1)]);
+    b.aliases[0].aliases[0].vol_min = 0xFFFF;
+    b.aliases[0].aliases[0].vol_max = 0x1000;
+
+    let report = b.validate();
+    assert_eq!(
+        report.inverted_volumes,
+        [("wpn_fire".to_string(), 0xFFFF, 0x1000)]
+    );
+    assert!(!report.is_valid());
+}
+
+#[test]
+fn missing_snapshot_groups() {
+    let mut b = bank(vec![alias_list("wpn_fire", 1)]);
+    b.aliases[0].aliases[0].snapshot_group = 2;
+
+    // Without the globals, there's nothing to check against.
+    assert!(b.validate().is_valid());
+
+    assert!(b.validate_with_globals(&globals(3)).is_valid());
+    let report = b.validate_with_globals(&globals(2));
+    assert_eq!(
+        report.missing_snapshot_groups,
+        [("wpn_fire".to_string(), 2)]
+    );
+    assert!(!report.is_valid());
+}
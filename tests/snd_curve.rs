@@ -0,0 +1,140 @@
+//! Checks [`SndCurve::evaluate`] and [`SndAlias::volume_at_distance`]
+//! against hand-computed values.
+
+use t5_xfile_defs::{
+    XString,
+    common::Vec2,
+    sound::{SndAlias, SndCurve, SndDriverGlobals},
+};
+
+fn curve(name: &str, points: [[f32; 2]; 8]) -> SndCurve {
+    SndCurve {
+        name: XString::from(name.to_string()),
+        id: 0,
+        points: points.map(Vec2::from),
+    }
+}
+
+/// From 1.0 at 0.2 down to 0.5 at 0.6, with the unused points left as
+/// zeroes.
+fn two_knots() -> SndCurve {
+    let mut points = [[0.0; 2]; 8];
+    points[0] = [0.2, 1.0];
+    points[1] = [0.6, 0.5];
+    curve("two_knots", points)
+}
+
+/// `actual` is within rounding error of `expected`.
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-5, "{actual} != {expected}");
+}
+
+#[test]
+fn evaluate_two_knots() {
+    let c = two_knots();
+
+    assert_close(c.evaluate(0.2), 1.0);
+    assert_close(c.evaluate(0.3), 0.875);
+    assert_close(c.evaluate(0.4), 0.75);
+    assert_close(c.evaluate(0.5), 0.625);
+    assert_close(c.evaluate(0.6), 0.5);
+}
+
+#[test]
+fn evaluate_clamps() {
+    let c = two_knots();
+
+    assert_close(c.evaluate(0.0), 1.0);
+    assert_close(c.evaluate(-10.0), 1.0);
+    assert_close(c.evaluate(0.61), 0.5);
+    assert_close(c.evaluate(1.0), 0.5);
+    assert_close(c.evaluate(10.0), 0.5);
+}
+
+#[test]
+fn evaluate_all_knots() {
+    let c = curve(
+        "steps",
+        core::array::from_fn(|i| [i as f32 / 7.0, if i % 2 == 0 { 0.0 } else { 1.0 }]),
+    );
+
+    assert_close(c.evaluate(0.5 / 7.0), 0.5);
+    assert_close(c.evaluate(1.0 / 7.0), 1.0);
+    assert_close(c.evaluate(6.25 / 7.0), 0.25);
+    assert_close(c.evaluate(1.0), 1.0);
+}
+
+fn alias(dry_curve: u8) -> SndAlias {
+    SndAlias {
+        name: XString::from("wpn_fire".to_string()),
+        vol_max: u16::MAX,
+        dist_min: 100,
+        dist_max: 500,
+        dry_curve,
+        ..Default::default()
+    }
+}
+
+fn globals() -> SndDriverGlobals {
+    SndDriverGlobals {
+        curves: vec![
+            curve("default", [[0.0; 2]; 8]),
+            curve("allon", [[0.0; 2]; 8]),
+            two_knots(),
+        ],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn volume_at_distance_linear() {
+    let a = alias(SndCurve::LINEAR);
+    let g = SndDriverGlobals::default();
+
+    let volume = |d| a.volume_at_distance(d, &g).unwrap();
+    assert_close(volume(0.0), 1.0);
+    assert_close(volume(100.0), 1.0);
+    assert_close(volume(200.0), 0.75);
+    assert_close(volume(400.0), 0.25);
+    assert_close(volume(500.0), 0.0);
+    assert_close(volume(1000.0), 0.0);
+}
+
+#[test]
+fn volume_at_distance_identity() {
+    let mut a = alias(SndCurve::IDENTITY);
+    a.vol_max = u16::MAX / 2;
+    let g = SndDriverGlobals::default();
+
+    for d in [0.0, 300.0, 10000.0] {
+        assert_close(a.volume_at_distance(d, &g).unwrap(), 0.5 - 0.5 / 65535.0);
+    }
+}
+
+#[test]
+fn volume_at_distance_curve() {
+    let a = alias(2);
+
+    // Without the globals, the curve can't be found.
+    assert_eq!(
+        a.volume_at_distance(300.0, &SndDriverGlobals::default()),
+        None
+    );
+
+    // 300 units is halfway between dist_min and dist_max, at 0.5 on the
+    // curve.
+    let g = globals();
+    assert_close(a.volume_at_distance(300.0, &g).unwrap(), 0.625);
+    assert_close(a.volume_at_distance(0.0, &g).unwrap(), 1.0);
+    assert_close(a.volume_at_distance(1000.0, &g).unwrap(), 0.5);
+}
+
+#[test]
+fn volume_at_distance_no_range() {
+    let mut a = alias(SndCurve::LINEAR);
+    a.dist_max = a.dist_min;
+    let g = SndDriverGlobals::default();
+
+    assert_close(a.volume_at_distance(100.0, &g).unwrap(), 1.0);
+    assert_close(a.volume_at_distance(100.5, &g).unwrap(), 0.0);
+}
@@ -0,0 +1,164 @@
+//! Checks the alias manifest in [`t5_xfile_deserializer::sound`].
+
+#![cfg(feature = "json")]
+
+use serde_json::json;
+use t5_xfile_defs::{
+    XString,
+    sound::{SndAlias, SndAliasList, SndBank, SoundFile, SoundFileRef, StreamedSound},
+};
+use t5_xfile_deserializer::sound::{diff_manifests, from_json_manifest, to_json_manifest};
+
+fn alias(name: &str, vol_max: u16, flags: u32) -> SndAlias {
+    SndAlias {
+        name: XString::from(name.to_string()),
+        sound_file: Some(Box::new(SoundFile {
+            u: SoundFileRef::Streamed(Some(Box::new(StreamedSound {
+                filename: XString::from(format!("{name}.wav")),
+                prime_snd: None,
+            }))),
+            exists: true,
+        })),
+        flags,
+        vol_min: vol_max,
+        vol_max,
+        pitch_min: 0x4000,
+        pitch_max: 0x4000,
+        dist_min: 100,
+        dist_max: 2000,
+        radverb_send: 0x1234,
+        snapshot_group: 3,
+        ..Default::default()
+    }
+}
+
+fn bank() -> SndBank {
+    SndBank {
+        name: XString::from("test.all".to_string()),
+        aliases: vec![
+            SndAliasList {
+                name: XString::from("wpn_fire".to_string()),
+                aliases: vec![
+                    alias("wpn_fire", 0xFFFF, SndAlias::FLAG_3D),
+                    alias("wpn_fire", 0x8000, SndAlias::FLAG_3D | 2 << 30),
+                ],
+                ..Default::default()
+            },
+            SndAliasList {
+                name: XString::from("mus_menu".to_string()),
+                aliases: vec![alias(
+                    "mus_menu",
+                    0x4000,
+                    SndAlias::FLAG_LOOPING | SndAlias::FLAG_MUSIC,
+                )],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn manifest() {
+    let manifest = to_json_manifest(&bank());
+    assert_eq!(manifest.as_array().unwrap().len(), 3);
+    assert_eq!(
+        manifest[1],
+        json!({
+            "name": "wpn_fire",
+            "file": "wpn_fire.wav",
+            "group": 3,
+            "min_dist": 100,
+            "max_dist": 2000,
+            "volume": 0x8000 as f64 / 65535.0,
+            "pitch": 0x4000,
+            "randomize_type": 2,
+            "loop_": false,
+            "is_music": false,
+            "reverb": 0x1234,
+            "spatial_type": "3d",
+        })
+    );
+    assert_eq!(manifest[2]["loop_"], true);
+    assert_eq!(manifest[2]["is_music"], true);
+    assert_eq!(manifest[2]["spatial_type"], "2d");
+}
+
+#[test]
+fn round_trip() {
+    let manifest = to_json_manifest(&bank());
+    // Through text, as a tool would.
+    let text = serde_json::to_string(&manifest).unwrap();
+    let read = from_json_manifest(&serde_json::from_str(&text).unwrap()).unwrap();
+
+    let lists = read
+        .aliases
+        .iter()
+        .map(|l| (l.name.get(), l.aliases.len()))
+        .collect::<Vec<_>>();
+    assert_eq!(lists, [("wpn_fire", 2), ("mus_menu", 1)]);
+
+    let expected = bank();
+    let expected = expected.aliases.iter().flat_map(|l| &l.aliases);
+    for (read, expected) in read.aliases.iter().flat_map(|l| &l.aliases).zip(expected) {
+        assert_eq!(read.name.get(), expected.name.get());
+        assert_eq!(read.vol_max, expected.vol_max);
+        assert_eq!(read.flags, expected.flags);
+        assert_eq!(read.file_name(), expected.file_name());
+    }
+    assert_eq!(to_json_manifest(&read), manifest);
+}
+
+#[test]
+fn bad_manifests() {
+    for bad in [
+        json!({}),
+        json!([{ "file": "no_name.wav" }]),
+        json!([{ "name": "a", "volume": "loud" }]),
+        json!([{ "name": "a", "min_dist": 70000 }]),
+        json!([{ "name": "a", "spatial_type": "4d" }]),
+        json!([{ "name": "a", "randomize_type": 4 }]),
+    ] {
+        assert!(from_json_manifest(&bad).is_err(), "{bad}");
+    }
+
+    let minimal = from_json_manifest(&json!([{ "name": "a" }])).unwrap();
+    assert_eq!(minimal.alias_count(), 1);
+    assert_eq!(minimal.aliases[0].aliases[0].file_name(), None);
+}
+
+#[test]
+fn diff() {
+    let a = to_json_manifest(&bank());
+    assert_eq!(diff_manifests(&a, &a), json!([]));
+
+    let mut b = a.clone();
+    b[0]["volume"] = json!(0.5);
+    b[2]["file"] = json!("mus/menu~2.wav");
+    b.as_array_mut().unwrap().push(json!({ "name": "new" }));
+    let mut obj = b[1].as_object().unwrap().clone();
+    obj.remove("reverb");
+    obj.insert("a/b".to_string(), json!(1));
+    b[1] = obj.into();
+
+    let patch = diff_manifests(&a, &b);
+    let ops = patch.as_array().unwrap();
+    assert!(ops.contains(&json!({ "op": "replace", "path": "/0/volume", "value": 0.5 })));
+    assert!(
+        ops.contains(&json!({ "op": "replace", "path": "/2/file", "value": "mus/menu~2.wav" }))
+    );
+    assert!(ops.contains(&json!({ "op": "remove", "path": "/1/reverb" })));
+    assert!(ops.contains(&json!({ "op": "add", "path": "/1/a~1b", "value": 1 })));
+    assert!(ops.contains(&json!({ "op": "add", "path": "/-", "value": { "name": "new" } })));
+    assert_eq!(ops.len(), 5);
+
+    // Shrinking removes from the back.
+    let patch = diff_manifests(&b, &json!([]));
+    let paths = patch
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|op| op["path"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(paths, ["/3", "/2", "/1", "/0"]);
+}
@@ -0,0 +1,184 @@
+//! Checks [`SndPatch::overrides`], [`SndPatch::apply_to`],
+//! [`SndPatch::apply_to_alias`], and [`SndPatch::merge`].
+
+use t5_xfile_defs::{
+    XString,
+    sound::{SndAlias, SndAliasList, SndBank, SndPatch, SoundFile, SoundFileRef, StreamedSound},
+};
+
+fn file(name: &str) -> SoundFile {
+    SoundFile {
+        u: SoundFileRef::Streamed(Some(Box::new(StreamedSound {
+            filename: XString::from(name.to_string()),
+            prime_snd: None,
+        }))),
+        exists: true,
+    }
+}
+
+fn file_name(file: &SoundFile) -> &str {
+    match &file.u {
+        SoundFileRef::Streamed(Some(s)) => s.filename.get(),
+        _ => panic!("expected a streamed sound"),
+    }
+}
+
+fn alias(id: u32) -> SndAlias {
+    SndAlias {
+        id,
+        sound_file: Some(Box::new(file("original.wav"))),
+        dist_min: 100,
+        dist_max: 2000,
+        ..Default::default()
+    }
+}
+
+fn patch(name: &str, elements: &[u32], files: &[&str]) -> SndPatch {
+    SndPatch {
+        name: XString::from(name.to_string()),
+        elements: elements.to_vec(),
+        files: files.iter().map(|f| file(f)).collect(),
+    }
+}
+
+fn pairs(patch: &SndPatch) -> Vec<(u32, &str)> {
+    patch
+        .elements
+        .iter()
+        .copied()
+        .zip(patch.files.iter().map(file_name))
+        .collect()
+}
+
+fn bank(lists: &[&[u32]]) -> SndBank {
+    SndBank {
+        name: XString::from("mp_common"),
+        aliases: lists
+            .iter()
+            .map(|ids| SndAliasList {
+                aliases: ids.iter().map(|&id| alias(id)).collect(),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn bank_files(bank: &SndBank) -> Vec<(u32, &str)> {
+    bank.aliases
+        .iter()
+        .flat_map(|l| &l.aliases)
+        .map(|a| (a.id, file_name(a.sound_file.as_ref().unwrap())))
+        .collect()
+}
+
+#[test]
+fn overrides() {
+    let zone = patch("zone", &[7, 8, 9], &["a.wav", "b.wav"]);
+    let overrides = zone.overrides();
+    assert_eq!(
+        overrides
+            .iter()
+            .map(|o| (o.alias_id, o.file.map(file_name)))
+            .collect::<Vec<_>>(),
+        [(7, Some("a.wav")), (8, Some("b.wav")), (9, None)]
+    );
+
+    assert!(patch("empty", &[], &[]).overrides().is_empty());
+}
+
+#[test]
+fn apply_to() {
+    // Alias 2 is in two lists, as variants of the same sound are.
+    let mut bank = bank(&[&[1, 2], &[2, 3], &[4]]);
+    let patch = patch("zone", &[2, 5, 3, 4], &["b.wav", "e.wav", "c.wav"]);
+
+    let report = patch.apply_to(&mut bank);
+    assert_eq!(report.patched, [2, 3]);
+    assert_eq!(report.missing, [5]);
+    assert_eq!(report.without_file, [4]);
+    assert_eq!(
+        bank_files(&bank),
+        [
+            (1, "original.wav"),
+            (2, "b.wav"),
+            (2, "b.wav"),
+            (3, "c.wav"),
+            (4, "original.wav"),
+        ]
+    );
+
+    // Only files are touched.
+    for a in bank.aliases.iter().flat_map(|l| &l.aliases) {
+        assert_eq!((a.dist_min, a.dist_max), (100, 2000));
+    }
+
+    // Applying the same patch again changes nothing.
+    let before = bank_files(&bank)
+        .into_iter()
+        .map(|(id, f)| (id, f.to_string()))
+        .collect::<Vec<_>>();
+    patch.apply_to(&mut bank);
+    assert_eq!(
+        bank_files(&bank)
+            .into_iter()
+            .map(|(id, f)| (id, f.to_string()))
+            .collect::<Vec<_>>(),
+        before
+    );
+}
+
+#[test]
+fn apply_to_alias() {
+    let patch = patch("zone", &[1, 2, 1, 3], &["a.wav", "b.wav", "c.wav"]);
+
+    let mut a = alias(1);
+    assert!(patch.apply_to_alias(&mut a));
+    assert_eq!(file_name(a.sound_file.as_ref().unwrap()), "c.wav");
+    // Nothing but the file changes.
+    assert_eq!((a.dist_min, a.dist_max), (100, 2000));
+
+    let mut b = alias(2);
+    assert!(patch.apply_to_alias(&mut b));
+    assert_eq!(file_name(b.sound_file.as_ref().unwrap()), "b.wav");
+
+    // Patched without a file, and not patched at all.
+    for id in [3, 4] {
+        let mut a = alias(id);
+        assert!(!patch.apply_to_alias(&mut a));
+        assert_eq!(file_name(a.sound_file.as_ref().unwrap()), "original.wav");
+    }
+}
+
+#[test]
+fn merge() {
+    let base = patch("base", &[1, 2, 3], &["a.wav", "b.wav"]);
+    let zone = patch("zone", &[2, 4, 1], &["zone_b.wav", "zone_d.wav"]);
+
+    let merged = base.merge(&zone);
+    assert_eq!(merged.name.get(), "base");
+    assert_eq!(
+        pairs(&merged),
+        [(1, "a.wav"), (2, "zone_b.wav"), (4, "zone_d.wav")]
+    );
+    assert_eq!(merged.elements.len(), merged.files.len());
+
+    // Merging the other way round lets the base win instead.
+    assert_eq!(
+        pairs(&zone.merge(&base)),
+        [(4, "zone_d.wav"), (1, "a.wav"), (2, "b.wav")]
+    );
+
+    // Applying the merged patch is the same as applying both in order.
+    for id in 1..=4 {
+        let mut separately = alias(id);
+        base.apply_to_alias(&mut separately);
+        zone.apply_to_alias(&mut separately);
+        let mut merged_alias = alias(id);
+        merged.apply_to_alias(&mut merged_alias);
+        assert_eq!(
+            file_name(separately.sound_file.as_ref().unwrap()),
+            file_name(merged_alias.sound_file.as_ref().unwrap())
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Checks [`StaticModelBvh`]'s queries against testing every model.
+
+use t5_xfile_defs::{
+    common::Vec3,
+    gfx_world::{GfxStaticModelInst, bvh::StaticModelBvh},
+    xmodel::GfxColor,
+};
+
+/// A small deterministic PRNG, so failures are reproducible.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> f32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + self.next() * (max - min)
+    }
+
+    fn vec3(&mut self, min: f32, max: f32) -> [f32; 3] {
+        [
+            self.range(min, max),
+            self.range(min, max),
+            self.range(min, max),
+        ]
+    }
+}
+
+fn inst(mins: [f32; 3], maxs: [f32; 3]) -> GfxStaticModelInst {
+    GfxStaticModelInst {
+        mins: mins.into(),
+        maxs: maxs.into(),
+        lighting_origin: [0.0; 3].into(),
+        ground_lighting: GfxColor([0xFF; 4]),
+    }
+}
+
+/// 100 models of varying size scattered over a 512-unit cube, with a few
+/// exact duplicates.
+fn models() -> Vec<GfxStaticModelInst> {
+    let mut rng = Lcg(0x5EED);
+    let mut models = (0..96)
+        .map(|_| {
+            let mins = rng.vec3(-256.0, 256.0);
+            let size = rng.vec3(4.0, 128.0);
+            inst(
+                mins,
+                [mins[0] + size[0], mins[1] + size[1], mins[2] + size[2]],
+            )
+        })
+        .collect::<Vec<_>>();
+    models.extend((0..4).map(|_| inst([10.0; 3], [20.0; 3])));
+    models
+}
+
+fn overlaps(m: &GfxStaticModelInst, mins: [f32; 3], maxs: [f32; 3]) -> bool {
+    let (mmin, mmax) = (m.mins.get(), m.maxs.get());
+    (0..3).all(|i| mmin[i] <= maxs[i] && mins[i] <= mmax[i])
+}
+
+/// The same slab test the tree uses, against a single model.
+fn ray_hits(m: &GfxStaticModelInst, origin: [f32; 3], dir: [f32; 3], max_dist: f32) -> bool {
+    let (mmin, mmax) = (m.mins.get(), m.maxs.get());
+    let (mut near, mut far) = (0.0f32, max_dist);
+    for i in 0..3 {
+        if dir[i] == 0.0 {
+            if origin[i] < mmin[i] || origin[i] > mmax[i] {
+                return false;
+            }
+            continue;
+        }
+        let t0 = (mmin[i] - origin[i]) / dir[i];
+        let t1 = (mmax[i] - origin[i]) / dir[i];
+        near = near.max(t0.min(t1));
+        far = far.min(t0.max(t1));
+    }
+    near <= far
+}
+
+#[test]
+fn empty() {
+    let bvh = StaticModelBvh::new(&[]);
+    assert_eq!(bvh.node_count(), 0);
+    assert!(bvh.query_aabb([-1e9; 3].into(), [1e9; 3].into()).is_empty());
+    assert!(
+        bvh.query_ray([0.0; 3].into(), [1.0, 0.0, 0.0].into(), 1e9)
+            .is_empty()
+    );
+}
+
+#[test]
+fn node_count() {
+    let models = models();
+    let bvh = StaticModelBvh::new(&models);
+
+    // A binary tree with at least one model per leaf.
+    let nodes = bvh.node_count();
+    assert!(nodes % 2 == 1, "{nodes}");
+    assert!(nodes < models.len() * 2, "{nodes}");
+    assert!(nodes > 1, "{nodes}");
+}
+
+#[test]
+fn query_aabb() {
+    let models = models();
+    let bvh = StaticModelBvh::new(&models);
+
+    let mut rng = Lcg(0xB0C5);
+    let mut total = 0;
+    for _ in 0..200 {
+        let mins = rng.vec3(-300.0, 300.0);
+        let size = rng.vec3(0.0, 200.0);
+        let maxs = [mins[0] + size[0], mins[1] + size[1], mins[2] + size[2]];
+
+        let expected = (0..models.len())
+            .filter(|&i| overlaps(&models[i], mins, maxs))
+            .collect::<Vec<_>>();
+        assert_eq!(bvh.query_aabb(mins.into(), maxs.into()), expected);
+        total += expected.len();
+    }
+    // Make sure the queries actually hit something.
+    assert!(total > 50, "{total}");
+
+    // Everything, and the duplicates, including by touching.
+    assert_eq!(
+        bvh.query_aabb([-1e9; 3].into(), [1e9; 3].into()),
+        (0..models.len()).collect::<Vec<_>>()
+    );
+    let touching = bvh.query_aabb([20.0; 3].into(), [20.0; 3].into());
+    for i in 96..100 {
+        assert!(touching.contains(&i), "{touching:?}");
+    }
+}
+
+#[test]
+fn query_ray() {
+    let models = models();
+    let bvh = StaticModelBvh::new(&models);
+
+    let mut rng = Lcg(0x2A75);
+    let mut total = 0;
+    for _ in 0..200 {
+        let origin = rng.vec3(-300.0, 300.0);
+        let dir = Vec3::from(rng.vec3(-1.0, 1.0)).normalize().get();
+        let max_dist = rng.range(100.0, 1000.0);
+
+        let hits = bvh.query_ray(origin.into(), dir.into(), max_dist);
+        let mut indices = hits.iter().map(|&(i, _)| i).collect::<Vec<_>>();
+        indices.sort_unstable();
+        let expected = (0..models.len())
+            .filter(|&i| ray_hits(&models[i], origin, dir, max_dist))
+            .collect::<Vec<_>>();
+        assert_eq!(indices, expected);
+
+        assert!(hits.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!(hits.iter().all(|&(_, t)| (0.0..=max_dist).contains(&t)));
+        total += hits.len();
+    }
+    assert!(total > 20, "{total}");
+}
+
+#[test]
+fn query_ray_t() {
+    let bvh = StaticModelBvh::new(&[
+        inst([10.0, -1.0, -1.0], [12.0, 1.0, 1.0]),
+        inst([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]),
+        inst([30.0, -1.0, -1.0], [32.0, 1.0, 1.0]),
+        inst([5.0, 5.0, 5.0], [6.0, 6.0, 6.0]),
+    ]);
+
+    // Starting inside model 1, and stopping before model 2.
+    assert_eq!(
+        bvh.query_ray([0.0; 3].into(), [1.0, 0.0, 0.0].into(), 20.0),
+        [(1, 0.0), (0, 10.0)]
+    );
+    // An unnormalized direction scales t.
+    assert_eq!(
+        bvh.query_ray([0.0; 3].into(), [2.0, 0.0, 0.0].into(), 20.0),
+        [(1, 0.0), (0, 5.0), (2, 15.0)]
+    );
+    // Pointing away.
+    assert_eq!(
+        bvh.query_ray([40.0, 0.0, 0.0].into(), [1.0, 0.0, 0.0].into(), 100.0),
+        []
+    );
+}
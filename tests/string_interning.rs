@@ -0,0 +1,160 @@
+//! Checks that [`StringInterner`] shares storage between identical strings,
+//! and that this saves the deserializer memory. `benches/string_interning.rs`
+//! reports how much.
+
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use std::sync::Arc;
+
+use t5_xfile_defs::{
+    XString,
+    util::StringInterner,
+    xasset::{XAsset, XAssetGeneric},
+};
+
+use common::{
+    TempFile,
+    alloc::{CountingAlloc, retained_allocation},
+    u32s,
+};
+
+const RAWFILE: u32 = 0x24;
+const INLINE: u32 = 0xFFFFFFFF;
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// An inflated blob with a one-byte raw file for each of `names`.
+fn blob(names: &[&str]) -> Vec<u8> {
+    let mut body = u32s(&[0, INLINE, names.len() as u32, INLINE]);
+    for _ in names {
+        body.extend(u32s(&[RAWFILE, INLINE]));
+    }
+    for name in names {
+        body.extend(u32s(&[INLINE, 1, INLINE]));
+        body.extend(name.as_bytes());
+        body.extend([0, b'a', 0]);
+    }
+
+    let mut blob = u32s(&[body.len() as u32, 0, 0, 0, 0, 0, 0, 0, 0]);
+    blob.extend(body);
+    blob
+}
+
+fn deserialize(name: &str, blob: &[u8], string_interning: bool) -> Vec<XAsset> {
+    let temp = TempFile::new(&format!("interning_{name}.cache"), blob);
+
+    let assets =
+        common::deserialize_with(&temp, true, |b| b.with_string_interning(string_interning));
+    assets.unwrap()
+}
+
+fn raw_file_name(asset: &XAsset) -> &XString {
+    let XAsset::PC(XAssetGeneric::RawFile(Some(raw_file))) = asset else {
+        panic!("expected RawFile, got {asset:?}");
+    };
+    &raw_file.name
+}
+
+#[test]
+fn interner() {
+    let mut interner = StringInterner::new();
+    let a = interner.intern("colorMap");
+    let b = interner.intern(&String::from("colorMap"));
+    let c = interner.intern("normalMap");
+
+    assert_eq!(&*a, "colorMap");
+    assert!(Arc::ptr_eq(&a.0, &b.0));
+    assert!(!Arc::ptr_eq(&a.0, &c.0));
+    assert_eq!(interner.len(), 2);
+    assert_eq!(interner.byte_len(), "colorMap".len() + "normalMap".len());
+
+    // Strings made without an interner never share.
+    let d = XString::from("colorMap");
+    assert!(!Arc::ptr_eq(&a.0, &d.0));
+    assert_eq!(d.get(), a.get());
+}
+
+#[test]
+fn deserialized_strings_share_storage() {
+    let names = ["a.cfg", "b.cfg", "a.cfg", "b.cfg", "a.cfg"];
+    let blob = blob(&names);
+
+    let assets = deserialize("share", &blob, true);
+    let interned = assets.iter().map(raw_file_name).collect::<Vec<_>>();
+    assert_eq!(interned.iter().map(|s| s.get()).collect::<Vec<_>>(), names);
+    for (i, a) in interned.iter().enumerate() {
+        for (j, b) in interned.iter().enumerate() {
+            assert_eq!(Arc::ptr_eq(&a.0, &b.0), names[i] == names[j], "{i}, {j}");
+        }
+    }
+
+    let assets = deserialize("no_share", &blob, false);
+    let separate = assets.iter().map(raw_file_name).collect::<Vec<_>>();
+    assert!(!Arc::ptr_eq(&separate[0].0, &separate[2].0));
+}
+
+#[test]
+fn memory_savings() {
+    // Thousands of copies of a few long names, like the sampler, technique,
+    // and bone names repeated throughout a materials-heavy file.
+    const COPIES: usize = 2000;
+    let distinct = [
+        "mc/mtl_weapon_m16_body_col_nml_spc",
+        "mc/mtl_weapon_m16_stock_col_nml_spc",
+        "mc/mtl_weapon_ak47_body_col_nml_spc",
+        "mc/mtl_weapon_ak47_clip_col_nml_spc",
+    ];
+    let names = distinct
+        .iter()
+        .copied()
+        .cycle()
+        .take(COPIES)
+        .collect::<Vec<_>>();
+    let blob = blob(&names);
+
+    let (interned, interned_bytes) = retained_allocation(|| deserialize("mem", &blob, true));
+    let (separate, separate_bytes) = retained_allocation(|| deserialize("mem_no", &blob, false));
+    assert_eq!(interned.len(), COPIES);
+    assert_eq!(separate.len(), COPIES);
+
+    let name_bytes = names.iter().map(|n| n.len()).sum::<usize>();
+    let saved = separate_bytes - interned_bytes;
+    // Every copy but the first of each name is saved, along with its
+    // reference counts.
+    assert!(saved >= name_bytes - distinct.iter().map(|n| n.len()).sum::<usize>());
+}
+
+#[cfg(feature = "serializer")]
+#[test]
+fn serializer_round_trip() {
+    use t5_xfile_defs::XFilePlatform;
+    use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+    let assets = deserialize("round_trip", &blob(&["a.cfg", "b.cfg", "a.cfg"]), true);
+
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>(assets.iter().cloned()).unwrap();
+    let bytes = ser.deflate().unwrap();
+
+    let temp = TempFile::new("interning.ff", bytes);
+    let round_tripped = common::deserialize(&temp, false);
+    let round_tripped = round_tripped.unwrap();
+
+    assert_eq!(
+        round_tripped
+            .iter()
+            .map(|a| raw_file_name(a).get())
+            .collect::<Vec<_>>(),
+        ["a.cfg", "b.cfg", "a.cfg"]
+    );
+    assert!(Arc::ptr_eq(
+        &raw_file_name(&round_tripped[0]).0,
+        &raw_file_name(&round_tripped[2]).0
+    ));
+}
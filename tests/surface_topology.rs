@@ -0,0 +1,125 @@
+//! Checks [`XSurface`]'s topology checks on hand-made meshes.
+
+use t5_xfile_defs::xmodel::{GfxPackedVertex, TopologyReport, XSurface};
+
+fn surface(positions: &[[f32; 3]], tris: &[[u16; 3]]) -> XSurface {
+    XSurface {
+        verts0: positions
+            .iter()
+            .map(|&xyz| GfxPackedVertex {
+                xyz: xyz.into(),
+                ..Default::default()
+            })
+            .collect(),
+        tri_indices: tris.iter().flatten().copied().collect(),
+        ..Default::default()
+    }
+}
+
+const TETRAHEDRON: [[f32; 3]; 4] = [
+    [0.0, 0.0, 0.0],
+    [1.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0],
+    [0.0, 0.0, 1.0],
+];
+
+const TETRAHEDRON_TRIS: [[u16; 3]; 4] = [[0, 2, 1], [0, 1, 3], [1, 2, 3], [0, 3, 2]];
+
+#[test]
+fn closed() {
+    let surf = surface(&TETRAHEDRON, &TETRAHEDRON_TRIS);
+    assert!(surf.is_manifold());
+    assert!(surf.is_closed());
+    assert_eq!(surf.topology_report(), TopologyReport::default());
+}
+
+#[test]
+fn open() {
+    // A quad: two triangles sharing the 0-2 diagonal.
+    let surf = surface(
+        &[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ],
+        &[[0, 1, 2], [0, 2, 3]],
+    );
+    assert!(surf.is_manifold());
+    assert!(!surf.is_closed());
+    assert_eq!(surf.find_open_edges(), [[0, 1], [0, 3], [1, 2], [2, 3]]);
+    assert!(surf.find_non_manifold_edges().is_empty());
+}
+
+#[test]
+fn seams_are_welded() {
+    // The tetrahedron, with vertex 0 duplicated as 4 for the last face.
+    let mut positions = TETRAHEDRON.to_vec();
+    positions.push(TETRAHEDRON[0]);
+    let mut tris = TETRAHEDRON_TRIS;
+    tris[3] = [4, 3, 2];
+
+    let surf = surface(&positions, &tris);
+    assert!(surf.is_closed());
+    assert!(surf.find_degenerate_triangles().is_empty());
+
+    // -0.0 and 0.0 are the same position.
+    positions[4] = [-0.0, 0.0, -0.0];
+    assert!(surface(&positions, &tris).is_closed());
+}
+
+#[test]
+fn non_manifold() {
+    // Three triangles fanning out from the 0-1 edge.
+    let surf = surface(
+        &[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.5, 1.0, 0.0],
+            [0.5, -1.0, 0.0],
+            [0.5, 0.0, 1.0],
+        ],
+        &[[0, 1, 2], [1, 0, 3], [0, 1, 4]],
+    );
+    assert!(!surf.is_manifold());
+    assert!(!surf.is_closed());
+
+    let report = surf.topology_report();
+    assert_eq!(report.non_manifold_edges, [[0, 1]]);
+    assert_eq!(report.open_edges.len(), 6);
+    assert!(!report.is_manifold());
+    assert_eq!(report.non_manifold_edges, surf.find_non_manifold_edges());
+    assert_eq!(report.open_edges, surf.find_open_edges());
+}
+
+#[test]
+fn degenerate() {
+    // Triangle 1 has two corners at the same position, and triangle 2
+    // repeats an index.
+    let surf = surface(
+        &[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0],
+        ],
+        &[[0, 1, 2], [0, 1, 3], [2, 2, 0]],
+    );
+    assert_eq!(surf.find_degenerate_triangles(), [1, 2]);
+
+    // Degenerate triangles don't count towards edges.
+    assert_eq!(surf.find_open_edges(), [[0, 1], [0, 2], [1, 2]]);
+    assert!(surf.is_manifold());
+
+    let report = surf.topology_report();
+    assert_eq!(report.degenerate_triangles, [1, 2]);
+    assert!(report.is_manifold());
+    assert!(!report.is_closed());
+}
+
+#[test]
+fn empty() {
+    let surf = surface(&[], &[]);
+    assert!(surf.is_closed());
+    assert_eq!(surf.topology_report(), TopologyReport::default());
+}
@@ -0,0 +1,230 @@
+//! Checks that [`T5XFileDeserializer::survey`] carries on past a corrupted
+//! asset and finds the one after it.
+//!
+//! [`T5XFileDeserializer::survey`]: t5_xfile_deserializer::T5XFileDeserializer::survey
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform, XString,
+    limits::ResourceLimit,
+    misc::RawFile,
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+};
+use t5_xfile_deserializer::{
+    SurveyLimits, SurveyResult, T5XFileDeserializerBuilder, T5XFileSerializerBuilder,
+};
+
+use common::TempFile;
+
+const NAMES: [&str; 3] = ["maps/mp/a.gsc", "maps/mp/b.gsc", "maps/mp/c.gsc"];
+
+/// Each buffer is plain text, so that there's nothing in it that looks like
+/// the start of an asset.
+fn raw_files() -> Vec<RawFile> {
+    NAMES
+        .iter()
+        .map(|name| {
+            let mut buffer = format!("// {name}\n").repeat(20).into_bytes();
+            buffer.push(0);
+            RawFile {
+                name: XString::from(*name),
+                buffer,
+            }
+        })
+        .collect()
+}
+
+fn blob() -> Vec<u8> {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets::<1>(
+        raw_files()
+            .into_iter()
+            .map(|f| XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(f))))),
+    )
+    .unwrap();
+
+    let temp = TempFile::new("survey_blob.ff", ser.deflate().unwrap());
+    let mut file = temp.open();
+
+    T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .unwrap()
+        .blob()
+        .to_vec()
+}
+
+/// Where the raw file at `idx` starts in `blob`. Its name follows its
+/// 12-byte header.
+fn asset_start(blob: &[u8], idx: usize) -> usize {
+    let name = format!("{}\0", NAMES[idx]);
+    blob.windows(name.len())
+        .position(|w| w == name.as_bytes())
+        .unwrap()
+        - 12
+}
+
+/// Gives the raw file at `idx` a length that runs well past the end of the
+/// blob.
+fn corrupt(blob: &mut [u8], idx: usize) {
+    let len = asset_start(blob, idx) + 4;
+    blob[len..len + 4].copy_from_slice(&0x7FFF_0000u32.to_le_bytes());
+}
+
+fn survey(name: &str, blob: &[u8], limits: SurveyLimits) -> SurveyResult {
+    let temp = TempFile::new(&format!("survey_{name}.cache"), blob);
+    let mut file = temp.open();
+    let result =
+        T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .build()
+            .and_then(|de| de.inflate())
+            .and_then(|de| de.no_cache())
+            .and_then(|de| de.survey(limits));
+    result.unwrap()
+}
+
+fn names(result: &SurveyResult) -> Vec<String> {
+    result
+        .assets
+        .iter()
+        .map(|a| match a {
+            XAsset::PC(XAssetGeneric::RawFile(Some(f))) => f.name.get().to_string(),
+            _ => panic!("expected a raw file, got {a:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn intact() {
+    let result = survey("intact", &blob(), SurveyLimits::default());
+    assert_eq!(names(&result), NAMES);
+    assert!(result.errors.is_empty());
+    assert!(!result.stopped_early);
+}
+
+#[test]
+fn skips_corrupt_asset() {
+    let mut blob = blob();
+    let expected_skip = asset_start(&blob, 2) - asset_start(&blob, 1);
+    corrupt(&mut blob, 1);
+
+    let result = survey("middle", &blob, SurveyLimits::default());
+    assert_eq!(names(&result), [NAMES[0], NAMES[2]]);
+    assert!(!result.stopped_early);
+
+    let [e] = result.errors.as_slice() else {
+        panic!("expected one error, got {}", result.errors.len());
+    };
+    assert_eq!(e.index, 1);
+    assert_eq!(e.asset_type, Some(XAssetType::RAWFILE));
+    assert_eq!(e.skipped_bytes, Some(expected_skip as u64));
+}
+
+#[test]
+fn corrupt_last_asset() {
+    let mut blob = blob();
+    let start = asset_start(&blob, 2);
+    corrupt(&mut blob, 2);
+
+    // Nothing comes after it, so it's assumed to take up the rest of the
+    // blob.
+    let result = survey("last", &blob, SurveyLimits::default());
+    assert_eq!(names(&result), [NAMES[0], NAMES[1]]);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].index, 2);
+    assert_eq!(
+        result.errors[0].skipped_bytes,
+        Some((blob.len() - start) as u64)
+    );
+    assert!(!result.stopped_early);
+}
+
+#[test]
+fn limits() {
+    let mut blob = blob();
+    corrupt(&mut blob, 0);
+
+    // The survey stops at the last error it's allowed, without looking for
+    // the next asset.
+    let limits = SurveyLimits {
+        max_errors: 1,
+        ..Default::default()
+    };
+    let result = survey("max_errors", &blob, limits);
+    assert!(result.assets.is_empty());
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].skipped_bytes, None);
+    assert!(result.stopped_early);
+
+    // Too short a scan to reach the next asset.
+    let limits = SurveyLimits {
+        max_scan_bytes: 16,
+        ..Default::default()
+    };
+    let result = survey("max_scan", &blob, limits);
+    assert!(result.assets.is_empty());
+    assert_eq!(result.errors[0].skipped_bytes, None);
+    assert!(result.stopped_early);
+
+    // Enough scan, but the only candidate tried is in the asset's own
+    // header.
+    let limits = SurveyLimits {
+        max_candidates: 1,
+        ..Default::default()
+    };
+    assert!(survey("max_candidates", &blob, limits).stopped_early);
+}
+
+#[test]
+fn two_broken_assets() {
+    let mut blob = blob();
+    let expected_skip = asset_start(&blob, 1) - asset_start(&blob, 0);
+    // The asset table follows the 36-byte XFile and the 16-byte asset list,
+    // with 8 bytes per entry, starting with its type.
+    let table = 36 + 16;
+    let ty = table + 2 * 8;
+    assert_eq!(blob[ty..ty + 4], (XAssetType::RAWFILE as u32).to_le_bytes());
+
+    // The first raw file's buffer runs off the end of the blob, and the
+    // last asset's type isn't one at all.
+    corrupt(&mut blob, 0);
+    blob[ty..ty + 4].copy_from_slice(&0xFFFFu32.to_le_bytes());
+
+    let result = survey("two", &blob, SurveyLimits::default());
+    assert_eq!(names(&result), [NAMES[1]]);
+    assert!(!result.stopped_early);
+
+    let [first, last] = result.errors.as_slice() else {
+        panic!("expected two errors, got {}", result.errors.len());
+    };
+    assert_eq!(first.index, 0);
+    assert_eq!(first.asset_type, Some(XAssetType::RAWFILE));
+    assert!(
+        matches!(
+            first.error.kind(),
+            ErrorKind::ResourceLimit {
+                which: ResourceLimit::ArrayLen,
+                ..
+            }
+        ),
+        "{}",
+        first.error
+    );
+    assert_eq!(first.skipped_bytes, Some(expected_skip as u64));
+
+    assert_eq!(last.index, 2);
+    assert_eq!(last.asset_type, None);
+    assert!(
+        matches!(last.error.kind(), ErrorKind::InvalidXAssetType(0xFFFF)),
+        "{}",
+        last.error
+    );
+}
@@ -0,0 +1,104 @@
+//! Checks [`TechniqueType`]'s slot order and the lookups built on it.
+
+use t5_xfile_defs::{
+    XString,
+    techset::{Material, MaterialTechnique, MaterialTechniqueSet, TechniqueType},
+};
+
+fn technique(name: &str) -> Option<Box<MaterialTechnique>> {
+    Some(Box::new(MaterialTechnique {
+        name: XString::from(name.to_string()),
+        flags: 0,
+        passes: Vec::new(),
+    }))
+}
+
+/// A techset with only a depth prepass, a shadowcaster, a lit technique, and
+/// something in the last slot.
+fn techset() -> MaterialTechniqueSet {
+    let mut techniques = vec![None; 130];
+    techniques[0] = technique("zprepass");
+    techniques[2] = technique("shadowcaster");
+    techniques[8] = technique("lit");
+    techniques[129] = technique("last");
+
+    MaterialTechniqueSet {
+        name: XString::from("mc_l_sm_r0c0".to_string()),
+        world_vert_format: 0,
+        techset_flags: 0,
+        techniques,
+    }
+}
+
+#[test]
+fn slots() {
+    assert_eq!(TechniqueType::DEPTH_PREPASS.slot(), 0);
+    assert_eq!(TechniqueType::BUILD_SHADOWMAP_DEPTH.slot(), 2);
+    assert_eq!(TechniqueType::UNLIT.slot(), 4);
+    assert_eq!(TechniqueType::EMISSIVE.slot(), 5);
+    assert_eq!(TechniqueType::LIT.slot(), 8);
+    assert_eq!(TechniqueType::LIT_SUN_SHADOW.slot(), 12);
+    assert_eq!(TechniqueType::LIT_OMNI_SHADOW_FADE.slot(), 21);
+
+    assert_eq!(TechniqueType::from_slot(8), Some(TechniqueType::LIT));
+    assert_eq!(TechniqueType::from_slot(130), None);
+    assert!(TechniqueType::all().map(TechniqueType::slot).eq(0..130));
+}
+
+#[test]
+fn names() {
+    assert_eq!(TechniqueType::LIT_SUN.name(), "lit_sun");
+    assert_eq!(TechniqueType::UNKNOWN_16.name(), "unknown");
+    for ty in TechniqueType::all() {
+        assert_eq!(ty.name(), MaterialTechniqueSet::slot_name(ty.slot()));
+    }
+}
+
+#[test]
+fn technique_by_type() {
+    let set = techset();
+
+    let name = |ty| set.technique(ty).map(|t| t.name.get());
+    assert_eq!(name(TechniqueType::DEPTH_PREPASS), Some("zprepass"));
+    assert_eq!(
+        name(TechniqueType::BUILD_SHADOWMAP_DEPTH),
+        Some("shadowcaster")
+    );
+    assert_eq!(name(TechniqueType::LIT), Some("lit"));
+    assert_eq!(name(TechniqueType::UNKNOWN_81), Some("last"));
+    assert_eq!(name(TechniqueType::EMISSIVE), None);
+}
+
+#[test]
+fn techniques_used() {
+    let set = techset();
+
+    assert_eq!(
+        set.techniques_used(),
+        [
+            TechniqueType::DEPTH_PREPASS,
+            TechniqueType::BUILD_SHADOWMAP_DEPTH,
+            TechniqueType::LIT,
+            TechniqueType::UNKNOWN_81,
+        ]
+    );
+    assert!(
+        set.techniques_by_type()
+            .all(|(ty, t)| set.technique(ty).is_some_and(|u| core::ptr::eq(t, u)))
+    );
+}
+
+#[test]
+fn technique_for() {
+    let mut material = Material::default();
+    assert!(material.technique_for(TechniqueType::LIT).is_none());
+
+    material.technique_set = Some(Box::new(techset()));
+    assert_eq!(
+        material
+            .technique_for(TechniqueType::LIT)
+            .map(|t| t.name.get()),
+        Some("lit")
+    );
+    assert!(material.technique_for(TechniqueType::UNLIT).is_none());
+}
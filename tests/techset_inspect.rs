@@ -0,0 +1,145 @@
+//! Checks the [`MaterialTechniqueSet`] slot, pass, shader, and vertex
+//! declaration accessors.
+
+use t5_xfile_defs::{
+    XString,
+    techset::{
+        GfxPixelShaderLoadDef, GfxVertexShaderLoadDef, MaterialPass, MaterialPixelShader,
+        MaterialPixelShaderProgram, MaterialStreamRouting, MaterialTechnique, MaterialTechniqueSet,
+        MaterialVertexDeclaration, MaterialVertexShader, MaterialVertexShaderProgram,
+    },
+};
+
+fn vertex_shader(name: &str) -> Option<Box<MaterialVertexShader>> {
+    Some(Box::new(MaterialVertexShader {
+        name: XString::from(name),
+        prog: MaterialVertexShaderProgram {
+            vs: None,
+            load_def: GfxVertexShaderLoadDef {
+                program: Vec::new(),
+            },
+        },
+    }))
+}
+
+fn pixel_shader(name: &str) -> Option<Box<MaterialPixelShader>> {
+    Some(Box::new(MaterialPixelShader {
+        name: XString::from(name),
+        prog: MaterialPixelShaderProgram {
+            ps: None,
+            load_def: GfxPixelShaderLoadDef {
+                program: Vec::new(),
+            },
+        },
+    }))
+}
+
+/// A vertex declaration routing `streams` as `(source, data)` pairs. Every
+/// routing slot past them is filled with junk, which shouldn't be seen.
+fn vertex_decl(streams: &[(u8, u8)]) -> Option<Box<MaterialVertexDeclaration>> {
+    let mut decl = MaterialVertexDeclaration::default();
+    decl.stream_count = streams.len() as _;
+    decl.routing.data = [MaterialStreamRouting {
+        source: 0xEE,
+        data: 0xEE,
+    }; 16];
+    for (r, &(source, data)) in decl.routing.data.iter_mut().zip(streams) {
+        *r = MaterialStreamRouting { source, data };
+    }
+    Some(Box::new(decl))
+}
+
+fn pass(vs: &str, ps: &str, streams: Option<&[(u8, u8)]>) -> MaterialPass {
+    MaterialPass {
+        vertex_decl: streams.and_then(vertex_decl),
+        vertex_shader: (!vs.is_empty()).then(|| vertex_shader(vs)).flatten(),
+        pixel_shader: (!ps.is_empty()).then(|| pixel_shader(ps)).flatten(),
+        per_prim_arg_count: 0,
+        per_obj_arg_count: 0,
+        stable_arg_count: 0,
+        custom_sampler_flags: 0,
+        args: Vec::new(),
+    }
+}
+
+fn technique(name: &str, passes: Vec<MaterialPass>) -> Option<Box<MaterialTechnique>> {
+    Some(Box::new(MaterialTechnique {
+        name: XString::from(name),
+        flags: 0,
+        passes,
+    }))
+}
+
+fn techset() -> MaterialTechniqueSet {
+    let mut techniques = vec![None; 130];
+    techniques[0] = technique("zprepass", vec![pass("zprepass_vs", "", Some(&[(0, 0)]))]);
+    techniques[8] = technique(
+        "lit",
+        vec![
+            pass("lit_vs", "lit_ps", Some(&[(0, 0), (4, 3), (5, 8)])),
+            pass("lit_add_vs", "lit_add_ps", None),
+        ],
+    );
+    techniques[100] = technique("empty", Vec::new());
+
+    MaterialTechniqueSet {
+        name: XString::from("mc_l_sm_r0c0"),
+        world_vert_format: 0,
+        techset_flags: 0,
+        techniques,
+    }
+}
+
+#[test]
+fn slots() {
+    let set = techset();
+    assert_eq!(set.technique_count(), 3);
+    assert_eq!(set.technique_at(8).unwrap().name.get(), "lit");
+    assert!(set.technique_at(1).is_none());
+    assert!(set.technique_at(130).is_none());
+
+    assert_eq!(MaterialTechniqueSet::slot_name(0), "depth_prepass");
+    assert_eq!(MaterialTechniqueSet::slot_name(8), "lit");
+    assert_eq!(MaterialTechniqueSet::slot_name(21), "lit_omni_shadow_fade");
+    assert_eq!(MaterialTechniqueSet::slot_name(22), "unknown");
+    assert_eq!(MaterialTechniqueSet::slot_name(usize::MAX), "unknown");
+}
+
+#[test]
+fn passes_and_shaders() {
+    let set = techset();
+
+    let lit = set.technique_at(8).unwrap();
+    assert_eq!(lit.pass_count(), 2);
+    // The technique's shaders are its first pass's.
+    assert_eq!(lit.shader_names(), ("lit_vs", "lit_ps"));
+    assert_eq!(lit.passes[1].shader_names(), ("lit_add_vs", "lit_add_ps"));
+
+    // Missing shaders and passes are empty strings.
+    let zprepass = set.technique_at(0).unwrap();
+    assert_eq!(zprepass.shader_names(), ("zprepass_vs", ""));
+    let empty = set.technique_at(100).unwrap();
+    assert_eq!(empty.pass_count(), 0);
+    assert_eq!(empty.shader_names(), ("", ""));
+}
+
+#[test]
+fn vertex_decls() {
+    let set = techset();
+    let routing = |p: &MaterialPass| {
+        p.vertex_decl()
+            .iter()
+            .map(|r| (r.source, r.data))
+            .collect::<Vec<_>>()
+    };
+
+    let lit = set.technique_at(8).unwrap();
+    assert_eq!(routing(&lit.passes[0]), [(0, 0), (4, 3), (5, 8)]);
+    assert_eq!(routing(&lit.passes[1]), []);
+    assert_eq!(routing(&set.technique_at(0).unwrap().passes[0]), [(0, 0)]);
+
+    // A corrupt count is clamped to the routing table.
+    let mut p = pass("vs", "ps", Some(&[(1, 1)]));
+    p.vertex_decl.as_mut().unwrap().stream_count = 200;
+    assert_eq!(p.vertex_decl().len(), 16);
+}
@@ -0,0 +1,251 @@
+//! Checks that a Fastfile cut off partway through an asset still gives up
+//! the assets before the cut, and that one cut off in its script string or
+//! asset table is read as having no assets.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    ErrorKind, T5XFileSerialize, XFilePlatform, XString,
+    misc::RawFile,
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+};
+use t5_xfile_deserializer::{PartialResult, T5XFileDeserializerBuilder, T5XFileSerializerBuilder};
+
+use common::TempFile;
+
+const NAMES: [&str; 3] = ["maps/mp/a.gsc", "maps/mp/b.gsc", "maps/mp/c.gsc"];
+
+/// Interned before the raw files are serialized, so that the blob has a
+/// script string table to cut.
+const SCRIPT_STRINGS: [&str; 3] = ["tag_origin", "j_head", "tag_flash"];
+
+/// The 36-byte XFile and 16-byte asset list come before the tables.
+const TABLES_START: usize = 36 + 16;
+
+/// One pointer per script string, then each string's bytes.
+fn script_string_table_len() -> usize {
+    SCRIPT_STRINGS.iter().map(|s| 4 + s.len() + 1).sum()
+}
+
+/// Three raw files, each 2000 bytes of noise so that they don't compress
+/// and the compressed Fastfile is laid out roughly like the blob.
+fn raw_files() -> Vec<RawFile> {
+    let mut x = 0x2545_F491_4F6C_DD1Du64;
+    NAMES
+        .iter()
+        .map(|name| {
+            let mut buffer = (0..2000)
+                .map(|_| {
+                    x ^= x << 13;
+                    x ^= x >> 7;
+                    x ^= x << 17;
+                    x as u8
+                })
+                .collect::<Vec<_>>();
+            buffer.push(0);
+            RawFile {
+                name: XString::from(*name),
+                buffer,
+            }
+        })
+        .collect()
+}
+
+fn fastfile() -> Vec<u8> {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    for s in SCRIPT_STRINGS {
+        ser.intern(s).unwrap();
+    }
+    ser.serialize_assets::<1>(
+        raw_files()
+            .into_iter()
+            .map(|f| XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(f))))),
+    )
+    .unwrap();
+    ser.deflate().unwrap()
+}
+
+fn blob() -> Vec<u8> {
+    let temp = TempFile::new("truncated_blob.ff", fastfile());
+    let mut file = temp.open();
+
+    T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .unwrap()
+        .blob()
+        .to_vec()
+}
+
+fn deserialize_partial(
+    name: &str,
+    bytes: &[u8],
+    cache: bool,
+    allow_truncated: bool,
+) -> t5_xfile_defs::Result<PartialResult> {
+    let temp = TempFile::new(&format!("truncated_{name}"), bytes);
+    let mut file = temp.open();
+    let builder = if cache {
+        T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, false)
+    } else {
+        T5XFileDeserializerBuilder::from_file(&mut file, XFilePlatform::Windows, false)
+    };
+
+    builder
+        .with_silent(true)
+        .with_allow_truncated(allow_truncated)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .and_then(|de| de.deserialize_remaining_partial())
+}
+
+/// Checks that `result` holds the first `n` raw files, and that the next one
+/// is the one that was cut.
+fn check_prefix(result: &PartialResult, n: usize) {
+    let expected = raw_files();
+    assert!(result.truncated);
+    assert_eq!(result.assets.len(), n);
+    for (asset, expected) in result.assets.iter().zip(&expected) {
+        let XAsset::PC(XAssetGeneric::RawFile(Some(f))) = asset else {
+            panic!("expected a raw file, got {asset:?}");
+        };
+        assert_eq!(f.name.get(), expected.name.get());
+        assert_eq!(f.buffer, expected.buffer);
+    }
+
+    let cut = result.cut_asset.as_ref().expect("no cut asset");
+    assert_eq!(cut.index, n);
+    assert_eq!(cut.asset_type, Some(XAssetType::RAWFILE));
+    // An array that doesn't fit is reported as a bad seek, but counts as a
+    // cut since the blob is known to be short.
+    assert!(
+        matches!(
+            cut.error.kind(),
+            ErrorKind::UnexpectedEof { .. } | ErrorKind::InvalidSeek { .. }
+        ),
+        "{}",
+        cut.error
+    );
+}
+
+#[test]
+fn blob_cut_mid_asset() {
+    let blob = blob();
+    let cut = &blob[..blob.len() - 500];
+
+    let result = deserialize_partial("mid.cache", cut, true, true).unwrap();
+    check_prefix(&result, 2);
+
+    let e = deserialize_partial("strict.cache", cut, true, false)
+        .err()
+        .unwrap();
+    assert!(
+        matches!(e.kind(), ErrorKind::TruncatedBlob { expected, .. } if *expected == blob.len()),
+        "{e}"
+    );
+}
+
+#[test]
+fn blob_cut_between_assets() {
+    let blob = blob();
+    // The last asset's header, name, and buffer.
+    let last = 12 + NAMES[2].len() + 1 + 2001;
+    let cut = &blob[..blob.len() - last];
+
+    let result = deserialize_partial("between.cache", cut, true, true).unwrap();
+    check_prefix(&result, 2);
+}
+
+#[test]
+fn fastfile_cut_mid_asset() {
+    let ff = fastfile();
+    let cut = &ff[..ff.len() * 4 / 5];
+
+    // Inflating stops where the compressed stream does, and what came out
+    // before that is used.
+    let result = deserialize_partial("mid.ff", cut, false, true).unwrap();
+    check_prefix(&result, 2);
+
+    let e = deserialize_partial("strict.ff", cut, false, false)
+        .err()
+        .unwrap();
+    assert!(
+        matches!(
+            e.kind(),
+            ErrorKind::TruncatedBlob { .. } | ErrorKind::Inflate(_)
+        ),
+        "{e}"
+    );
+}
+
+/// Checks that cutting `blob` at `len`, somewhere in the tables, gives no
+/// assets rather than an error, unless truncation isn't allowed.
+fn check_cut_in_tables(name: &str, blob: &[u8], len: usize) {
+    let cut = &blob[..len];
+    let result = deserialize_partial(&format!("{name}.cache"), cut, true, true).unwrap();
+    assert!(result.truncated);
+    assert!(result.assets.is_empty());
+    assert!(result.cut_asset.is_none());
+
+    let e = deserialize_partial(&format!("{name}_strict.cache"), cut, true, false)
+        .err()
+        .unwrap();
+    assert!(matches!(e.kind(), ErrorKind::TruncatedBlob { .. }), "{e}");
+}
+
+#[test]
+fn blob_cut_mid_script_string_table() {
+    let blob = blob();
+    let strings = &blob[TABLES_START + 4 * SCRIPT_STRINGS.len()..];
+    assert!(strings.starts_with(b"tag_origin\0j_head\0"));
+
+    // Partway through the pointers, so the array doesn't fit.
+    check_cut_in_tables("strings_ptrs", &blob, TABLES_START + 6);
+    // Partway through the second string's bytes.
+    let len = TABLES_START + 4 * SCRIPT_STRINGS.len() + SCRIPT_STRINGS[0].len() + 3;
+    check_cut_in_tables("strings_bytes", &blob, len);
+}
+
+#[test]
+fn blob_cut_mid_asset_table() {
+    let blob = blob();
+    let table = TABLES_START + script_string_table_len();
+    // Each entry is the asset's type, then its data pointer.
+    assert_eq!(
+        blob[table..table + 4],
+        (XAssetType::RAWFILE as u32).to_le_bytes()
+    );
+
+    check_cut_in_tables("assets", &blob, table + 8 + 4);
+}
+
+#[test]
+fn corrupted_count_isnt_truncation() {
+    let mut blob = blob();
+    // The last raw file's length follows its name pointer, and its name
+    // follows its 12-byte header.
+    let name = format!("{}\0", NAMES[2]);
+    let start = blob
+        .windows(name.len())
+        .position(|w| w == name.as_bytes())
+        .unwrap()
+        - 12;
+    // Any longer than the blob, and it'd be stopped by the array length limit
+    // before it's checked against the end.
+    let len = blob.len() as u32 - 1;
+    blob[start + 4..start + 8].copy_from_slice(&len.to_le_bytes());
+
+    // The blob is as long as its header says, so the buffer running past
+    // its end is an error, not a cut.
+    let e = deserialize_partial("corrupted.cache", &blob, true, true)
+        .err()
+        .unwrap();
+    assert!(matches!(e.kind(), ErrorKind::InvalidSeek { .. }), "{e}");
+}
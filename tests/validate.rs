@@ -0,0 +1,223 @@
+//! Checks that [`validate`] flags suspicious values, and only those.
+
+use t5_xfile_defs::{
+    XString,
+    com_world::{ComPrimaryLight, ComWorld},
+    common::Vec3,
+    techset::{Material, MaterialInfo},
+    validate::{Severity, validate},
+    weapon::{ImpactType, WeapType, WeaponDef, WeaponVariantDef},
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+    xmodel::PhysPreset,
+};
+
+fn weapon(def: WeaponDef) -> WeaponVariantDef {
+    WeaponVariantDef {
+        internal_name: XString::from("m16_mp"),
+        clip_size: 30,
+        reload_time: 2000,
+        weap_def: Some(Box::new(def)),
+        ..Default::default()
+    }
+}
+
+fn weapon_def() -> WeaponDef {
+    WeaponDef {
+        fire_time: 80,
+        damage: 40,
+        min_damage: 30,
+        max_damage_range: 1000.0,
+        min_damage_range: 2000.0,
+        max_range: 8000.0,
+        ..Default::default()
+    }
+}
+
+/// `(field, severity)` for every finding on `asset`.
+fn findings(asset: XAsset) -> Vec<(String, Severity)> {
+    let report = validate(&[asset]);
+    report
+        .findings
+        .into_iter()
+        .map(|f| (f.field, f.severity))
+        .collect()
+}
+
+fn weapon_asset(w: WeaponVariantDef) -> XAsset {
+    XAsset::PC(XAssetGeneric::Weapon(Some(Box::new(w))))
+}
+
+#[test]
+fn clean_weapon() {
+    assert_eq!(findings(weapon_asset(weapon(weapon_def()))), []);
+}
+
+#[test]
+fn bad_weapon() {
+    let mut w = weapon(WeaponDef {
+        weap_type: WeapType::NUM,
+        impact_type: ImpactType::COUNT,
+        fire_time: -1,
+        damage_duration: f32::NAN,
+        // Inverted.
+        max_damage_range: 3000.0,
+        min_damage_range: 500.0,
+        location_damage_multipliers: Some(Box::new({
+            let mut m = [1.0; 19];
+            m[4] = -2.0;
+            m
+        })),
+        ..weapon_def()
+    });
+    w.internal_name = XString::from("");
+    w.clip_size = -5;
+    w.ads_zoom_fov_1 = f32::INFINITY;
+
+    let found = findings(weapon_asset(w));
+    for expected in [
+        ("internal_name", Severity::Error),
+        ("clip_size", Severity::Warning),
+        ("ads_zoom_fov_1", Severity::Error),
+        ("weap_def.weap_type", Severity::Error),
+        ("weap_def.impact_type", Severity::Error),
+        ("weap_def.fire_time", Severity::Warning),
+        ("weap_def.damage_duration", Severity::Error),
+        ("weap_def.min_damage_range", Severity::Warning),
+        ("weap_def.location_damage_multipliers[4]", Severity::Warning),
+    ] {
+        assert!(
+            found.contains(&(expected.0.to_string(), expected.1)),
+            "{expected:?} not in {found:?}"
+        );
+    }
+    assert_eq!(found.len(), 9, "{found:?}");
+}
+
+#[test]
+fn weapon_without_def() {
+    let w = WeaponVariantDef {
+        weap_def: None,
+        ..weapon(weapon_def())
+    };
+    assert_eq!(
+        findings(weapon_asset(w)),
+        [("weap_def".to_string(), Severity::Info)]
+    );
+}
+
+#[test]
+fn material() {
+    let m = Material {
+        info: MaterialInfo {
+            name: XString::from("mc/mtl_test"),
+            ..Default::default()
+        },
+        state_bits_entry: {
+            let mut e = [0xFF; 130];
+            e[0] = 0;
+            e[5] = 3;
+            e
+        },
+        state_bits: vec![Default::default(); 2],
+        ..Default::default()
+    };
+
+    let found = findings(XAsset::PC(XAssetGeneric::Material(Some(Box::new(m)))));
+    assert_eq!(
+        found,
+        [
+            ("state_bits_entry[5]".to_string(), Severity::Error),
+            ("technique_set".to_string(), Severity::Info),
+        ]
+    );
+}
+
+#[test]
+fn phys_preset() {
+    let clean = PhysPreset {
+        name: XString::from("default"),
+        mass: 10.0,
+        buoyancy_box_min: Vec3::from([-1.0; 3]),
+        buoyancy_box_max: Vec3::from([1.0; 3]),
+        ..Default::default()
+    };
+    let asset = |p: &PhysPreset| XAsset::PC(XAssetGeneric::PhysPreset(Some(Box::new(p.clone()))));
+    assert_eq!(findings(asset(&clean)), []);
+
+    let bad = PhysPreset {
+        mass: 0.0,
+        friction: -0.5,
+        gravity_scale: f32::NAN,
+        buoyancy_box_min: Vec3::from([2.0, -1.0, -1.0]),
+        ..clean
+    };
+    assert_eq!(
+        findings(asset(&bad)),
+        [
+            ("mass".to_string(), Severity::Info),
+            ("friction".to_string(), Severity::Warning),
+            ("gravity_scale".to_string(), Severity::Error),
+            ("buoyancy_box_min".to_string(), Severity::Warning),
+        ]
+    );
+}
+
+#[test]
+fn com_world() {
+    let light = |type_, radius| ComPrimaryLight {
+        type_,
+        radius,
+        ..Default::default()
+    };
+    let mut world = ComWorld {
+        name: XString::from("maps/mp/mp_test.d3dbsp"),
+        primary_lights: vec![
+            // The placeholder is never checked.
+            light(0, f32::NAN),
+            // The sun has no radius.
+            light(1, 0.0),
+            light(2, 256.0),
+            light(3, 0.0),
+            light(2, f32::INFINITY),
+        ],
+        ..Default::default()
+    };
+    world.water_header.minx = 10;
+    world.water_header.maxx = -10;
+
+    assert_eq!(
+        findings(XAsset::PC(XAssetGeneric::ComWorld(Some(Box::new(world))))),
+        [
+            ("primary_lights[3].radius".to_string(), Severity::Warning),
+            ("primary_lights[4].radius".to_string(), Severity::Error),
+            ("water_header".to_string(), Severity::Warning),
+        ]
+    );
+}
+
+#[test]
+fn report() {
+    let assets = [
+        XAsset::PC(XAssetGeneric::RawFile(None)),
+        weapon_asset(WeaponVariantDef {
+            clip_size: -1,
+            internal_name: XString::from(""),
+            ..weapon(weapon_def())
+        }),
+    ];
+    let report = validate(&assets);
+    assert_eq!(report.findings.len(), 2);
+    assert!(report.has_errors());
+    assert_eq!(report.count(Severity::Error), 1);
+    assert_eq!(report.count(Severity::Warning), 1);
+    assert_eq!(report.count(Severity::Info), 0);
+
+    let f = report.with_severity(Severity::Error).next().unwrap();
+    assert_eq!(
+        (f.index, f.asset_type, f.field.as_str()),
+        (1, XAssetType::WEAPON, "internal_name")
+    );
+    assert!(f.to_string().starts_with("error: "), "{f}");
+
+    assert!(!validate(&assets[..1]).has_errors());
+}
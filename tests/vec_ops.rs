@@ -0,0 +1,100 @@
+//! Checks the arithmetic on [`Vec3`] and [`Vec4`].
+
+use t5_xfile_defs::common::{Vec3, Vec4};
+
+fn v3(x: f32, y: f32, z: f32) -> Vec3 {
+    Vec3::from([x, y, z])
+}
+
+fn close(a: Vec3, b: [f32; 3]) -> bool {
+    let a: [f32; 3] = a.into();
+    a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5)
+}
+
+#[test]
+fn operators() {
+    let a = v3(1.0, 2.0, 3.0);
+    let b = v3(4.0, -5.0, 0.5);
+
+    assert_eq!(<[f32; 3]>::from(a + b), [5.0, -3.0, 3.5]);
+    assert_eq!(<[f32; 3]>::from(a - b), [-3.0, 7.0, 2.5]);
+    assert_eq!(<[f32; 3]>::from(a * 2.0), [2.0, 4.0, 6.0]);
+    assert_eq!(<[f32; 3]>::from(a / 2.0), [0.5, 1.0, 1.5]);
+    assert_eq!(<[f32; 3]>::from(-a), [-1.0, -2.0, -3.0]);
+    assert_eq!((a[0], a[1], a[2]), (1.0, 2.0, 3.0));
+    assert_eq!(<[f32; 3]>::from(Vec3::default()), [0.0; 3]);
+
+    let c = Vec4::from([1.0, 2.0, 3.0, 4.0]);
+    let d = Vec4::from([0.5; 4]);
+    assert_eq!(<[f32; 4]>::from(c + d), [1.5, 2.5, 3.5, 4.5]);
+    assert_eq!(<[f32; 4]>::from(c - d), [0.5, 1.5, 2.5, 3.5]);
+    assert_eq!(<[f32; 4]>::from(c * -1.0), <[f32; 4]>::from(-c));
+    assert_eq!(<[f32; 4]>::from(c / 4.0), [0.25, 0.5, 0.75, 1.0]);
+    assert_eq!(c[3], 4.0);
+}
+
+#[test]
+#[should_panic]
+fn index_out_of_range() {
+    let _ = v3(1.0, 2.0, 3.0)[3];
+}
+
+#[test]
+fn products() {
+    let x = v3(1.0, 0.0, 0.0);
+    let y = v3(0.0, 1.0, 0.0);
+    let z = v3(0.0, 0.0, 1.0);
+
+    assert_eq!(x.dot(&y), 0.0);
+    assert_eq!(v3(1.0, 2.0, 3.0).dot(&v3(4.0, -5.0, 6.0)), 12.0);
+    // Right-handed.
+    assert_eq!(<[f32; 3]>::from(x.cross(&y)), [0.0, 0.0, 1.0]);
+    assert_eq!(<[f32; 3]>::from(y.cross(&x)), [0.0, 0.0, -1.0]);
+    assert_eq!(<[f32; 3]>::from(z.cross(&z)), [0.0; 3]);
+}
+
+#[test]
+fn length_and_normalize() {
+    let a = v3(3.0, 4.0, 12.0);
+    assert_eq!(a.length(), 13.0);
+    assert!(close(a.normalize(), [3.0 / 13.0, 4.0 / 13.0, 12.0 / 13.0]));
+    assert!((a.normalize().length() - 1.0).abs() < 1e-6);
+
+    // Zero stays zero instead of turning into NaNs.
+    assert_eq!(<[f32; 3]>::from(Vec3::default().normalize()), [0.0; 3]);
+}
+
+#[test]
+fn lerp() {
+    let a = v3(0.0, 10.0, -4.0);
+    let b = v3(10.0, 20.0, 4.0);
+    assert_eq!(<[f32; 3]>::from(a.lerp(&b, 0.0)), [0.0, 10.0, -4.0]);
+    assert_eq!(<[f32; 3]>::from(a.lerp(&b, 1.0)), [10.0, 20.0, 4.0]);
+    assert_eq!(<[f32; 3]>::from(a.lerp(&b, 0.25)), [2.5, 12.5, -2.0]);
+}
+
+#[test]
+fn quaternion_rotate() {
+    let v = v3(1.0, 2.0, 3.0);
+    let identity = Vec4::from([0.0, 0.0, 0.0, 1.0]);
+    assert!(close(
+        identity.as_quaternion_rotate_vec3(&v),
+        [1.0, 2.0, 3.0]
+    ));
+
+    // 90 degrees about +Z takes +X to +Y.
+    let h = core::f32::consts::FRAC_1_SQRT_2;
+    let yaw = Vec4::from([0.0, 0.0, h, h]);
+    assert_eq!(<[f32; 3]>::from(yaw.xyz()), [0.0, 0.0, h]);
+    assert!(close(
+        yaw.as_quaternion_rotate_vec3(&v3(1.0, 0.0, 0.0)),
+        [0.0, 1.0, 0.0]
+    ));
+    assert!(close(yaw.as_quaternion_rotate_vec3(&v), [-2.0, 1.0, 3.0]));
+
+    // Rotation doesn't change length.
+    let q = Vec4::from([0.2, -0.4, 0.1, 0.8]);
+    let len = (q.xyz().dot(&q.xyz()) + 0.64f32).sqrt();
+    let q = q / len;
+    assert!((q.as_quaternion_rotate_vec3(&v).length() - v.length()).abs() < 1e-5);
+}
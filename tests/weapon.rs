@@ -0,0 +1,283 @@
+//! Checks [`FlameTable`]'s dependency lists, and [`WeaponDef`]'s projectile
+//! simulation and effect chain.
+
+use t5_xfile_defs::{
+    XString,
+    fx::{FxEffectDef, FxEffectDefFlags},
+    techset::{Material, MaterialInfo},
+    weapon::{EffectRole, EffectTiming, FlameTable, GRAVITY, WeaponDef},
+};
+
+fn material(name: &str) -> Option<Box<Material>> {
+    Some(Box::new(Material {
+        info: MaterialInfo {
+            name: XString::from(name.to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    }))
+}
+
+#[test]
+fn flame_table_materials() {
+    let mut table = FlameTable {
+        fire: material("fire"),
+        smoke: material("smoke"),
+        heat: material("heat"),
+        drips: material("drips"),
+        stream_fuel: material("stream_fuel"),
+        stream_fuel_2: material("stream_fuel_2"),
+        stream_flame: material("stream_flame"),
+        stream_flame_2: material("stream_flame_2"),
+        flame_ignite_sound: XString::from("flame_ignite".to_string()),
+        ..Default::default()
+    };
+
+    assert_eq!(table.all_materials().len(), 8);
+    assert!(table.has_complete_materials());
+    assert_eq!(
+        table.referenced_material_names(),
+        [
+            "fire",
+            "smoke",
+            "heat",
+            "drips",
+            "stream_fuel",
+            "stream_fuel_2",
+            "stream_flame",
+            "stream_flame_2"
+        ]
+    );
+    assert_eq!(table.all_sound_names(), ["", "flame_ignite", "", ""]);
+
+    table.heat = None;
+    assert_eq!(table.all_materials().len(), 7);
+    assert!(!table.has_complete_materials());
+    assert!(!table.referenced_material_names().contains(&"heat"));
+}
+
+/// A mortar that's fired at 45 degrees and lands back at launch height
+/// when its lifetime runs out.
+fn mortar() -> WeaponDef {
+    WeaponDef {
+        projectile_speed: 1000,
+        projectile_speed_up: 1000,
+        proj_lifetime: 2.0 * 1000.0 / GRAVITY,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn mortar_trajectory() {
+    let mortar = mortar();
+
+    // v^2 / 2g and v * t, give or take the integration error.
+    let height = mortar.projectile_max_height();
+    assert!(height > 0.0);
+    assert!((height - 625.0).abs() < 10.0, "{height}");
+    let range = mortar.projectile_range();
+    assert!((range - 2500.0).abs() < 1.0, "{range}");
+
+    let dt = 0.01;
+    let arc = mortar.simulate_projectile(dt, 250);
+    assert_eq!(arc.len(), 251);
+    assert_eq!(arc[0].get(), [0.0; 3]);
+
+    let z = arc.iter().map(|p| p.get()[2]).collect::<Vec<_>>();
+    let peak = (0..z.len()).max_by(|&a, &b| z[a].total_cmp(&z[b])).unwrap();
+    assert!(peak > 0 && peak < z.len() - 1);
+    for k in 1..peak.min(z.len() - 1 - peak) {
+        let (before, after) = (z[peak - k], z[peak + k]);
+        // Within one step's climb of each other.
+        assert!(
+            (before - after).abs() <= 1000.0 * dt,
+            "{k}: {before} {after}"
+        );
+    }
+    assert!(arc.iter().all(|p| p.get()[1] == 0.0));
+}
+
+#[test]
+fn accelerating_curved_projectile() {
+    let rocket = WeaponDef {
+        projectile_speed: 1000,
+        time_to_accelerate: 1.0,
+        projectile_curvature: 90.0,
+        proj_lifetime: 1.0,
+        ..Default::default()
+    };
+
+    // Half the full speed on average over the ramp, and bent off +X.
+    let end = rocket
+        .simulate_projectile(0.001, 1000)
+        .last()
+        .unwrap()
+        .get();
+    assert!(end[1] > 0.0, "{end:?}");
+    assert!(end[0] < 500.0, "{end:?}");
+    assert!(rocket.projectile_range() < 500.0);
+    assert_eq!(rocket.projectile_max_height(), 0.0);
+}
+
+fn effect(name: &str) -> Option<Box<FxEffectDef>> {
+    Some(Box::new(FxEffectDef {
+        name: XString::from(name.to_string()),
+        flags: FxEffectDefFlags::empty(),
+        ef_priority: 0,
+        total_size: 0,
+        msec_looping_life: 0,
+        elem_def_count_looping: 0,
+        elem_def_count_one_shot: 0,
+        elem_def_count_emission: 0,
+        elem_defs: Vec::new(),
+        bounding_box_dim: Default::default(),
+        bounding_sphere: Default::default(),
+    }))
+}
+
+/// `(role, effect name, timing)` for each stage of `weapon`'s effect chain.
+fn chain(weapon: &WeaponDef) -> Vec<(EffectRole, Option<&str>, EffectTiming)> {
+    weapon
+        .effect_chain()
+        .iter()
+        .map(|s| (s.role, s.effect.map(|e| e.name.get()), s.timing))
+        .collect()
+}
+
+#[test]
+fn grenade_effect_chain() {
+    let grenade = WeaponDef {
+        fuse_time: 3500,
+        detonate_delay: 100,
+        proj_explosion_effect: effect("explosions/grenadeexp_default"),
+        proj_explosion_effect_force_normal_up: true,
+        proj_explosion_effect_2: effect("explosions/grenadeexp_dust"),
+        ..Default::default()
+    };
+
+    let stages = chain(&grenade);
+    assert_eq!(stages.len(), 14);
+    assert!(stages[..8].iter().all(|s| s.1.is_none()));
+    assert_eq!(
+        stages[8..],
+        [
+            (
+                EffectRole::Explosion(1),
+                Some("explosions/grenadeexp_default"),
+                EffectTiming::FuseTime(3500)
+            ),
+            (
+                EffectRole::Explosion(2),
+                Some("explosions/grenadeexp_dust"),
+                EffectTiming::FuseTime(3500)
+            ),
+            (EffectRole::Explosion(3), None, EffectTiming::FuseTime(3500)),
+            (EffectRole::Explosion(4), None, EffectTiming::FuseTime(3500)),
+            (EffectRole::Explosion(5), None, EffectTiming::FuseTime(3500)),
+            (EffectRole::Dud, None, EffectTiming::OnImpact),
+        ]
+    );
+
+    let explosion = &grenade.effect_chain()[8];
+    assert!(explosion.force_normal_up);
+    assert_eq!(explosion.timing.ms_after_fire(), Some(3500));
+
+    // With a detonator, the fuse doesn't matter.
+    let satchel = WeaponDef {
+        has_detonator: true,
+        ..grenade
+    };
+    let explosion = &satchel.effect_chain()[8];
+    assert_eq!(explosion.timing, EffectTiming::DetonateDelay(100));
+    assert_eq!(explosion.timing.ms_after_fire(), None);
+}
+
+#[test]
+fn rocket_effect_chain() {
+    let rocket = WeaponDef {
+        view_flash_effect: effect("weapon/muzzleflashes/fx_rpg7_view"),
+        world_flash_effect: effect("weapon/muzzleflashes/fx_rpg7_world"),
+        proj_ignition_delay: 150,
+        proj_ignition_effect: effect("weapon/rocket/fx_rpg7_ignite"),
+        proj_tail_effect: effect("weapon/rocket/fx_rpg7_trail"),
+        proj_impact_explode: true,
+        fuse_time: 5000,
+        proj_explosion_effect: effect("explosions/fx_rpg7_exp"),
+        proj_dud_effect: effect("weapon/rocket/fx_rpg7_dud"),
+        ..Default::default()
+    };
+
+    let stages = chain(&rocket)
+        .into_iter()
+        .filter(|s| s.1.is_some())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        stages,
+        [
+            (
+                EffectRole::ViewFlash,
+                Some("weapon/muzzleflashes/fx_rpg7_view"),
+                EffectTiming::OnFire
+            ),
+            (
+                EffectRole::WorldFlash,
+                Some("weapon/muzzleflashes/fx_rpg7_world"),
+                EffectTiming::OnFire
+            ),
+            (
+                EffectRole::Ignition,
+                Some("weapon/rocket/fx_rpg7_ignite"),
+                EffectTiming::ProjIgnitionDelay(150)
+            ),
+            (
+                EffectRole::Tail,
+                Some("weapon/rocket/fx_rpg7_trail"),
+                EffectTiming::ProjIgnitionDelay(150)
+            ),
+            (
+                EffectRole::Explosion(1),
+                Some("explosions/fx_rpg7_exp"),
+                EffectTiming::OnImpact
+            ),
+            (
+                EffectRole::Dud,
+                Some("weapon/rocket/fx_rpg7_dud"),
+                EffectTiming::OnImpact
+            ),
+        ]
+    );
+}
+
+#[test]
+fn effect_chain_order() {
+    // An ignition delay longer than the fuse puts the explosion first.
+    let weapon = WeaponDef {
+        proj_ignition_delay: 2000,
+        fuse_time: 1000,
+        ..Default::default()
+    };
+    let roles = weapon
+        .effect_chain()
+        .iter()
+        .map(|s| s.role)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        roles,
+        [
+            EffectRole::ViewFlash,
+            EffectRole::WorldFlash,
+            EffectRole::ViewShellEject,
+            EffectRole::WorldShellEject,
+            EffectRole::ViewLastShotEject,
+            EffectRole::WorldLastShotEject,
+            EffectRole::Explosion(1),
+            EffectRole::Explosion(2),
+            EffectRole::Explosion(3),
+            EffectRole::Explosion(4),
+            EffectRole::Explosion(5),
+            EffectRole::Ignition,
+            EffectRole::Tail,
+            EffectRole::Dud,
+        ]
+    );
+}
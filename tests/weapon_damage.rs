@@ -0,0 +1,84 @@
+//! Checks [`WeaponDef`]'s damage falloff over range.
+
+use t5_xfile_defs::weapon::WeaponDef;
+
+fn weapon(damage: i32, min_damage: i32, max_damage_range: f32, min_damage_range: f32) -> WeaponDef {
+    WeaponDef {
+        damage,
+        min_damage,
+        max_damage_range,
+        min_damage_range,
+        ..Default::default()
+    }
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-3, "{a} != {b}");
+}
+
+#[test]
+fn damage_at_range() {
+    let smg = weapon(40, 10, 500.0, 1500.0);
+
+    assert_eq!(smg.damage_at_range(0.0), 40.0);
+    assert_eq!(smg.damage_at_range(500.0), 40.0);
+    assert_close(smg.damage_at_range(750.0), 32.5);
+    assert_close(smg.damage_at_range(1000.0), 25.0);
+    assert_eq!(smg.damage_at_range(1500.0), 10.0);
+    assert_eq!(smg.damage_at_range(1_000_000.0), 10.0);
+
+    // With the ranges out of order, or equal, damage drops straight to the
+    // minimum.
+    for backwards in [
+        weapon(40, 10, 1000.0, 500.0),
+        weapon(40, 10, 1000.0, 1000.0),
+    ] {
+        assert_eq!(backwards.damage_at_range(0.0), 40.0);
+        assert_eq!(backwards.damage_at_range(1000.0), 40.0);
+        assert_eq!(backwards.damage_at_range(1000.5), 10.0);
+    }
+}
+
+#[test]
+fn damage_falloff_curve() {
+    let smg = weapon(40, 10, 500.0, 1500.0);
+    assert_eq!(
+        smg.damage_falloff_curve(),
+        [(0.0, 40.0), (500.0, 40.0), (1500.0, 10.0)]
+    );
+
+    // Every control point is on the curve.
+    for (range, damage) in smg.damage_falloff_curve() {
+        assert_eq!(smg.damage_at_range(range), damage);
+    }
+
+    // The points keep the ranges' order, even when it's backwards.
+    assert_eq!(
+        weapon(40, 10, 1000.0, 500.0).damage_falloff_curve(),
+        [(0.0, 40.0), (1000.0, 40.0), (500.0, 10.0)]
+    );
+}
+
+#[test]
+fn effective_range() {
+    // Halfway between 40 and 10 is two thirds of the way from 500 to 1500.
+    let smg = weapon(40, 10, 500.0, 1500.0);
+    assert_close(smg.effective_range(), 500.0 + 1000.0 * 2.0 / 3.0);
+    assert_close(smg.damage_at_range(smg.effective_range()), 20.0);
+
+    // Exactly half at the far end.
+    assert_close(weapon(40, 20, 500.0, 1500.0).effective_range(), 1500.0);
+
+    // Never below half.
+    assert_eq!(
+        weapon(50, 30, 1000.0, 2000.0).effective_range(),
+        f32::INFINITY
+    );
+
+    // Out of order ranges drop straight past the max damage range.
+    assert_eq!(weapon(40, 10, 1000.0, 500.0).effective_range(), 1000.0);
+    assert_eq!(weapon(40, 10, 1000.0, 1000.0).effective_range(), 1000.0);
+
+    // A weapon with full damage from zero range.
+    assert_eq!(weapon(40, 10, 0.0, 0.0).effective_range(), 0.0);
+}
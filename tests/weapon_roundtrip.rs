@@ -0,0 +1,135 @@
+//! Checks that [`WeaponVariantDef`]s survive being serialized and read back.
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform, XString,
+    layout::{LayoutOverrides, RETAIL_WEAPON_XANIM_COUNT},
+    weapon::{WeaponDef, WeaponVariantDef},
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+use common::TempFile;
+
+fn serialize(
+    weapon: WeaponVariantDef,
+    overrides: LayoutOverrides,
+) -> t5_xfile_defs::Result<Vec<u8>> {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .with_layout_overrides(overrides)
+        .build()?;
+    ser.serialize_assets::<1>(
+        [XAsset::PC(XAssetGeneric::Weapon(Some(Box::new(weapon))))].into_iter(),
+    )?;
+    ser.deflate()
+}
+
+fn deserialize(
+    name: &str,
+    bytes: &[u8],
+    overrides: LayoutOverrides,
+) -> t5_xfile_defs::Result<Vec<XAsset>> {
+    let temp = TempFile::new(&format!("weapon_roundtrip_{name}.ff"), bytes);
+    common::deserialize_with(&temp, false, |b| b.with_layout_overrides(overrides))
+}
+
+fn roundtrip_with(
+    name: &str,
+    weapon: WeaponVariantDef,
+    overrides: LayoutOverrides,
+) -> WeaponVariantDef {
+    let bytes = serialize(weapon, overrides).unwrap();
+    let mut assets = deserialize(name, &bytes, overrides).unwrap();
+
+    assert_eq!(assets.len(), 1);
+    let Some(XAsset::PC(XAssetGeneric::Weapon(Some(weapon)))) = assets.pop() else {
+        panic!("expected a weapon");
+    };
+    *weapon
+}
+
+fn roundtrip(name: &str, weapon: WeaponVariantDef) -> WeaponVariantDef {
+    roundtrip_with(name, weapon, LayoutOverrides::default())
+}
+
+#[test]
+fn no_xanims_or_hide_tags() {
+    let weapon = roundtrip(
+        "empty",
+        WeaponVariantDef {
+            internal_name: XString::from("knife_mp"),
+            weap_def: Some(Box::new(WeaponDef::default())),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(weapon.internal_name.get(), "knife_mp");
+    assert!(weapon.xanims.is_empty());
+    assert!(weapon.hide_tags.is_empty());
+}
+
+/// A weapon with an animation in the last of `xanim_count` slots.
+fn extra_anim_weapon(xanim_count: usize) -> WeaponVariantDef {
+    let mut xanims = vec![XString::new(); xanim_count];
+    xanims[0] = XString::from("viewmodel_idle");
+    xanims[xanim_count - 1] = XString::from("viewmodel_extra_anim");
+
+    WeaponVariantDef {
+        internal_name: XString::from("extra_anim_mp"),
+        weap_def: Some(Box::new(WeaponDef::default())),
+        xanims: xanims.into(),
+        hide_tags: vec![XString::from("tag_clip")],
+        clip_size: 30,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn non_retail_xanim_count() {
+    const XANIM_COUNT: usize = RETAIL_WEAPON_XANIM_COUNT + 4;
+    let overrides = LayoutOverrides {
+        weapon_xanim_count: Some(XANIM_COUNT),
+        weapon_variant_def_pad: 8,
+        weapon_def_pad: 4,
+    };
+
+    let weapon = roundtrip_with("extra_xanims", extra_anim_weapon(XANIM_COUNT), overrides);
+    assert_eq!(weapon.internal_name.get(), "extra_anim_mp");
+    assert_eq!(weapon.xanims.len(), XANIM_COUNT);
+    assert_eq!(weapon.xanims[0].get(), "viewmodel_idle");
+    assert_eq!(weapon.xanims[XANIM_COUNT - 1].get(), "viewmodel_extra_anim");
+    assert_eq!(weapon.hide_tags[0].get(), "tag_clip");
+    assert_eq!(weapon.clip_size, 30);
+
+    // Fewer xanims than the count are padded out to it.
+    let weapon = roundtrip_with("few_xanims", extra_anim_weapon(3), overrides);
+    assert_eq!(weapon.xanims.len(), XANIM_COUNT);
+    assert_eq!(weapon.xanims[2].get(), "viewmodel_extra_anim");
+
+    // Read as retail, the file is misaligned.
+    let bytes = serialize(extra_anim_weapon(XANIM_COUNT), overrides).unwrap();
+    let parsed_correctly = deserialize("extra_xanims_retail", &bytes, LayoutOverrides::default())
+        .is_ok_and(|assets| {
+            matches!(
+                assets.as_slice(),
+                [XAsset::PC(XAssetGeneric::Weapon(Some(w)))]
+                    if w.internal_name.get() == "extra_anim_mp"
+                        && w.xanims.len() == XANIM_COUNT
+            )
+        });
+    assert!(!parsed_correctly);
+}
+
+#[test]
+fn too_many_xanims() {
+    let e = serialize(
+        extra_anim_weapon(RETAIL_WEAPON_XANIM_COUNT + 1),
+        LayoutOverrides::default(),
+    )
+    .unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+}
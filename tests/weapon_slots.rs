@@ -0,0 +1,132 @@
+//! Checks [`WeaponXAnims`]' animation slots, and [`WeaponDef`]'s
+//! notetrack sounds and surface-indexed bounce settings.
+
+use t5_xfile_defs::{
+    XString,
+    fx::SurfaceType,
+    weapon::{WeaponAnimSlot, WeaponDef, WeaponVariantDef, WeaponXAnims},
+};
+
+#[test]
+fn anim_slots() {
+    let mut xanims = vec![XString::default(); WeaponAnimSlot::NUM as usize];
+    xanims[WeaponAnimSlot::IDLE as usize] = XString::from("viewmodel_ak47_idle");
+    xanims[WeaponAnimSlot::RELOAD_EMPTY as usize] = XString::from("viewmodel_ak47_reload_empty");
+    xanims[WeaponAnimSlot::ADS_DOWN as usize] = XString::from("viewmodel_ak47_ads_down");
+    let variant = WeaponVariantDef {
+        xanims: WeaponXAnims(xanims),
+        ..Default::default()
+    };
+    // Still indexable like the array it wraps.
+    assert_eq!(variant.xanims.len(), WeaponAnimSlot::NUM as usize);
+    assert_eq!(
+        variant.xanims[WeaponAnimSlot::ADS_DOWN as usize].get(),
+        "viewmodel_ak47_ads_down"
+    );
+
+    assert_eq!(
+        variant.xanims.get(WeaponAnimSlot::IDLE).unwrap().get(),
+        "viewmodel_ak47_idle"
+    );
+    assert!(variant.xanims.get(WeaponAnimSlot::FIRE).is_none());
+    assert!(variant.xanims.get(WeaponAnimSlot::NUM).is_none());
+
+    assert_eq!(
+        variant
+            .xanims
+            .by_slot()
+            .map(|(slot, a)| (slot, a.get()))
+            .collect::<Vec<_>>(),
+        [
+            (WeaponAnimSlot::IDLE, "viewmodel_ak47_idle"),
+            (WeaponAnimSlot::RELOAD_EMPTY, "viewmodel_ak47_reload_empty"),
+            (WeaponAnimSlot::ADS_DOWN, "viewmodel_ak47_ads_down"),
+        ]
+    );
+
+    // A weapon with fewer slots than retail just has fewer animations.
+    let short = WeaponVariantDef {
+        xanims: vec![XString::default(), XString::from("idle")].into(),
+        ..Default::default()
+    };
+    assert_eq!(
+        short.xanims.get(WeaponAnimSlot::IDLE).unwrap().get(),
+        "idle"
+    );
+    assert!(short.xanims.get(WeaponAnimSlot::ADS_DOWN).is_none());
+    assert_eq!(short.xanims.by_slot().count(), 1);
+}
+
+#[test]
+fn anim_slot_names() {
+    assert_eq!(WeaponAnimSlot::IDLE.name(), "idleAnim");
+    assert_eq!(WeaponAnimSlot::RELOAD_RIGHT.name(), "reloadAnimRight");
+    assert_eq!(WeaponAnimSlot::SPRINT_EMPTY_IN.name(), "sprintInEmptyAnim");
+    assert_eq!(WeaponAnimSlot::ADS_DOWN.name(), "adsDownAnim");
+    assert_eq!(WeaponAnimSlot::ROOT.name(), "");
+    assert_eq!(WeaponAnimSlot::NUM.name(), "");
+}
+
+#[test]
+fn notetrack_sounds() {
+    assert_eq!(WeaponDef::default().notetrack_sound_map().count(), 0);
+
+    let mut keys: [String; 20] = Default::default();
+    let mut values: [String; 20] = Default::default();
+    keys[0] = "clip_out".to_string();
+    values[0] = "wpn_ak47_clip_out".to_string();
+    // Unused entries in the middle are skipped.
+    keys[5] = "bolt_back".to_string();
+    values[5] = "wpn_ak47_bolt".to_string();
+    values[6] = "orphaned".to_string();
+    let weapon = WeaponDef {
+        notetrack_sound_map_keys: Some(Box::new(keys)),
+        notetrack_sound_map_values: Some(Box::new(values)),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        weapon.notetrack_sound_map().collect::<Vec<_>>(),
+        [
+            ("clip_out", "wpn_ak47_clip_out"),
+            ("bolt_back", "wpn_ak47_bolt")
+        ]
+    );
+}
+
+#[test]
+fn bounce() {
+    let none = WeaponDef::default();
+    assert!(none.bounce_sound_for(SurfaceType::METAL).is_none());
+    assert!(none.bounce_for(SurfaceType::METAL).is_none());
+
+    let mut sounds: [XString; 31] = Default::default();
+    sounds[SurfaceType::METAL as usize] = XString::from("grenade_bounce_metal");
+    let mut parallel = [0.5; 31];
+    let mut perpendicular = [0.3; 31];
+    parallel[SurfaceType::WATER as usize] = 0.1;
+    perpendicular[SurfaceType::WATER as usize] = 0.0;
+    let grenade = WeaponDef {
+        bounce_sound: Some(Box::new(sounds)),
+        parallel_bounce: Some(Box::new(parallel)),
+        perpendicular_bounce: Some(Box::new(perpendicular)),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        grenade.bounce_sound_for(SurfaceType::METAL).unwrap().get(),
+        "grenade_bounce_metal"
+    );
+    assert!(grenade.bounce_sound_for(SurfaceType::DIRT).is_none());
+    assert_eq!(grenade.bounce_for(SurfaceType::DIRT), Some((0.5, 0.3)));
+    assert_eq!(grenade.bounce_for(SurfaceType::WATER), Some((0.1, 0.0)));
+    assert!(grenade.bounce_sound_for(SurfaceType::COUNT).is_none());
+    assert!(grenade.bounce_for(SurfaceType::COUNT).is_none());
+
+    // Both factors are needed.
+    let half = WeaponDef {
+        parallel_bounce: Some(Box::new([0.5; 31])),
+        ..Default::default()
+    };
+    assert!(half.bounce_for(SurfaceType::DIRT).is_none());
+}
@@ -0,0 +1,96 @@
+//! Checks [`WeaponDef`]'s fire rate, time to kill, and metric conversions.
+
+use t5_xfile_defs::weapon::{UNIT_SCALE, WeaponDef};
+
+/// Fires every 100ms, doing 40 damage up to 500 units and 10 past 1500.
+fn smg() -> WeaponDef {
+    WeaponDef {
+        fire_time: 100,
+        damage: 40,
+        min_damage: 10,
+        max_damage_range: 500.0,
+        min_damage_range: 1500.0,
+        projectile_speed: 2000,
+        ..Default::default()
+    }
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-4, "{a} != {b}");
+}
+
+#[test]
+fn fire_rate_rpm() {
+    assert_eq!(smg().fire_rate_rpm(), 600.0);
+
+    let bolt = WeaponDef {
+        fire_time: 1500,
+        ..smg()
+    };
+    assert_eq!(bolt.fire_rate_rpm(), 40.0);
+
+    // A fire time of zero fires infinitely fast.
+    let instant = WeaponDef {
+        fire_time: 0,
+        ..smg()
+    };
+    assert_eq!(instant.fire_rate_rpm(), f32::INFINITY);
+}
+
+#[test]
+fn time_to_kill_at_range() {
+    let weapon = smg();
+
+    // Three shots, the first at time zero.
+    assert_close(weapon.time_to_kill_at_range(0.0, 100.0), 0.2);
+    // Exactly two shots.
+    assert_close(weapon.time_to_kill_at_range(0.0, 80.0), 0.1);
+    // One shot is enough, even for zero hitpoints.
+    assert_eq!(weapon.time_to_kill_at_range(0.0, 40.0), 0.0);
+    assert_eq!(weapon.time_to_kill_at_range(0.0, 0.0), 0.0);
+    // Ten shots at 10 damage past the minimum damage range.
+    assert_close(weapon.time_to_kill_at_range(2000.0, 100.0), 0.9);
+
+    // Shots are instant with a zero fire time.
+    let instant = WeaponDef {
+        fire_time: 0,
+        ..smg()
+    };
+    assert_eq!(instant.time_to_kill_at_range(0.0, 100.0), 0.0);
+
+    // No damage, no kill.
+    let harmless = WeaponDef {
+        damage: 0,
+        min_damage: 0,
+        ..smg()
+    };
+    assert_eq!(harmless.time_to_kill_at_range(0.0, 100.0), f32::INFINITY);
+    let falls_to_zero = WeaponDef {
+        min_damage: 0,
+        ..smg()
+    };
+    assert_close(falls_to_zero.time_to_kill_at_range(0.0, 100.0), 0.2);
+    assert_eq!(
+        falls_to_zero.time_to_kill_at_range(1500.0, 100.0),
+        f32::INFINITY
+    );
+}
+
+#[test]
+fn metric() {
+    // Game units are inches.
+    assert_eq!(UNIT_SCALE, 0.0254);
+    assert_close(39.37 * UNIT_SCALE, 1.0);
+
+    let weapon = smg();
+    assert_close(weapon.muzzle_velocity_m_per_s(), 50.8);
+    assert_close(weapon.max_effective_range_m(), 38.1);
+
+    let stationary = WeaponDef {
+        projectile_speed: 0,
+        min_damage_range: 0.0,
+        ..smg()
+    };
+    assert_eq!(stationary.muzzle_velocity_m_per_s(), 0.0);
+    assert_eq!(stationary.max_effective_range_m(), 0.0);
+}
@@ -0,0 +1,132 @@
+//! Checks [`WorldVisibility`] on a hand-built world of three cells in a row,
+//! where the two at the ends can't see each other.
+
+use t5_xfile_defs::{
+    ErrorKind,
+    gfx_world::{DpvsPlane, GfxCell, GfxPortal, visibility::WorldVisibility},
+};
+
+fn portal(x: f32, cell: Option<Box<GfxCell>>) -> GfxPortal {
+    GfxPortal {
+        plane: DpvsPlane {
+            coeffs: [1.0, 0.0, 0.0, x].into(),
+            side: [0, 0, 0],
+        },
+        cell,
+        vertices: vec![
+            [x, 0.0, 0.0].into(),
+            [x, 10.0, 0.0].into(),
+            [x, 10.0, 10.0].into(),
+            [x, 0.0, 10.0].into(),
+        ],
+        hull_axis: [[0.0, 1.0, 0.0].into(), [0.0, 0.0, 1.0].into()],
+    }
+}
+
+/// A 10-unit cube starting at `x`.
+fn cell(x: f32, portals: Vec<GfxPortal>) -> GfxCell {
+    GfxCell {
+        mins: [x, 0.0, 0.0].into(),
+        maxs: [x + 10.0, 10.0, 10.0].into(),
+        aabb_tree: Vec::new(),
+        portals,
+        cull_groups: Vec::new(),
+        reflection_probes: Vec::new(),
+    }
+}
+
+/// Cells 0, 1 and 2 along X, with portals between neighbours. Cell 1's
+/// portal into cell 2 has its target set; the rest are found from where
+/// they are.
+fn cells() -> Vec<GfxCell> {
+    vec![
+        cell(0.0, vec![portal(10.0, None)]),
+        cell(
+            10.0,
+            vec![
+                portal(10.0, None),
+                portal(20.0, Some(Box::new(cell(20.0, Vec::new())))),
+            ],
+        ),
+        cell(20.0, vec![portal(20.0, None)]),
+    ]
+}
+
+/// Every cell sees itself and its neighbours, but 0 and 2 can't see each
+/// other.
+const VIS_BITS: [u32; 3] = [0b011, 0b111, 0b110];
+
+#[test]
+fn visibility() {
+    let cells = cells();
+    let vis = WorldVisibility::new(&cells, &VIS_BITS).unwrap();
+    assert_eq!(vis.cell_count(), 3);
+
+    for (a, b, visible) in [
+        (0, 0, true),
+        (0, 1, true),
+        (1, 0, true),
+        (1, 2, true),
+        (2, 1, true),
+        (0, 2, false),
+        (2, 0, false),
+        (0, 3, false),
+    ] {
+        assert_eq!(vis.visible(a, b), visible, "{a} -> {b}");
+    }
+}
+
+#[test]
+fn portals() {
+    let cells = cells();
+    let vis = WorldVisibility::new(&cells, &VIS_BITS).unwrap();
+
+    let pairs = vis
+        .portals()
+        .iter()
+        .map(|p| (p.from, p.to))
+        .collect::<Vec<_>>();
+    assert_eq!(pairs, [(0, 1), (1, 0), (1, 2), (2, 1)]);
+
+    let between = vis.portals_between(2, 1).collect::<Vec<_>>();
+    assert_eq!(between.len(), 2);
+    for p in between {
+        let winding = p.winding.iter().map(|v| v.get()).collect::<Vec<_>>();
+        assert_eq!(
+            winding,
+            [
+                [20.0, 0.0, 0.0],
+                [20.0, 10.0, 0.0],
+                [20.0, 10.0, 10.0],
+                [20.0, 0.0, 10.0]
+            ]
+        );
+    }
+    assert_eq!(vis.portals_between(0, 2).count(), 0);
+}
+
+#[test]
+fn cells_containing() {
+    let cells = cells();
+    let vis = WorldVisibility::new(&cells, &VIS_BITS).unwrap();
+
+    assert_eq!(vis.cells_containing([5.0, 5.0, 5.0].into()), [0]);
+    assert_eq!(vis.cells_containing([25.0, 1.0, 9.0].into()), [2]);
+    // On the boundary between two cells.
+    assert_eq!(vis.cells_containing([10.0, 5.0, 5.0].into()), [0, 1]);
+    assert!(vis.cells_containing([35.0, 5.0, 5.0].into()).is_empty());
+
+    let (mins, maxs) = vis.cell_bounds(1).unwrap();
+    assert_eq!(
+        (mins.get(), maxs.get()),
+        ([10.0, 0.0, 0.0], [20.0, 10.0, 10.0])
+    );
+    assert!(vis.cell_bounds(3).is_none());
+}
+
+#[test]
+fn bad_vis_bits() {
+    let cells = cells();
+    let e = WorldVisibility::new(&cells, &VIS_BITS[..2]).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+}
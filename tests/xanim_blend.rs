@@ -0,0 +1,343 @@
+//! Checks [`XAnimParts::blend_with`] and [`XAnimParts::additive_apply`]
+//! against hand-built delta animations.
+
+use t5_xfile_defs::{
+    XString,
+    common::Vec3,
+    xanim::{
+        XAnimBoneTrack, XAnimDeltaPart, XAnimDeltaPartQuat, XAnimDeltaPartQuatData,
+        XAnimDeltaPartQuatDataFrames, XAnimDynamicFrames, XAnimDynamicIndices, XAnimIndices,
+        XAnimPartTrans, XAnimPartTransData, XAnimPartTransFrames, XAnimParts,
+    },
+};
+
+/// Quantization leaves this much error at most.
+const EPSILON: f32 = 0.01;
+
+/// A delta animation `numframes` long whose root moves in a straight line
+/// from the origin to `end`, turning by `yaw` (an unnormalized `[z, w]`).
+fn line(name: &str, numframes: u16, end: [f32; 3], yaw: [i16; 2]) -> XAnimParts {
+    // One quantization step per unit along `end`.
+    let trans = XAnimPartTrans {
+        size: 1,
+        small_trans: 0,
+        u: Some(XAnimPartTransData::Frames(XAnimPartTransFrames {
+            mins: Vec3::from([0.0; 3]),
+            maxs: Vec3::from([1.0; 3]),
+            frames: XAnimDynamicFrames::_2(vec![[0; 3], end.map(|c| c as u16)]),
+            indices: XAnimDynamicIndices::_1(vec![0, numframes as u8]),
+        })),
+    };
+    let quat = XAnimDeltaPartQuat {
+        size: 1,
+        u: Some(XAnimDeltaPartQuatData::Frames(
+            XAnimDeltaPartQuatDataFrames {
+                frames: vec![[0, i16::MAX], yaw],
+                indices: XAnimDynamicIndices::_1(vec![0, numframes as u8]),
+            },
+        )),
+    };
+
+    XAnimParts {
+        name: XString::from(name.to_string()),
+        numframes,
+        loop_: false,
+        delta: true,
+        left_hand_grip_ik: false,
+        streamable: false,
+        streamed_file_size: 0,
+        bone_count: [0; 10],
+        notify_count: 0,
+        asset_type: 0,
+        is_default: false,
+        index_count: 0,
+        framerate: 30.0,
+        frequency: 1.0,
+        primed_length: 0.0,
+        loop_entry_time: 0.0,
+        names: Vec::new(),
+        data_byte: Vec::new(),
+        data_short: Vec::new(),
+        data_int: Vec::new(),
+        random_data_short: Vec::new(),
+        random_data_byte: Vec::new(),
+        random_data_int: Vec::new(),
+        indices: XAnimIndices::_1(Vec::new()),
+        notify: Vec::new(),
+        delta_part: Some(Box::new(XAnimDeltaPart {
+            trans: Some(Box::new(trans)),
+            quat: Some(Box::new(quat)),
+        })),
+    }
+}
+
+/// A bone that turns `degrees` to the left and moves from `start` to
+/// `end` over `numframes`.
+fn bone(
+    name: &str,
+    numframes: u16,
+    degrees: f32,
+    start: [f32; 3],
+    end: [f32; 3],
+) -> XAnimBoneTrack {
+    let half = degrees.to_radians() / 2.0;
+    XAnimBoneTrack {
+        rotation: vec![
+            (0, [0.0, 0.0, 0.0, 1.0]),
+            (numframes, [0.0, 0.0, half.sin(), half.cos()]),
+        ],
+        translation: vec![(0, Vec3::from(start)), (numframes, Vec3::from(end))],
+        ..XAnimBoneTrack::new(name.to_string())
+    }
+}
+
+/// `anim` with `bones` packed into it.
+fn with_bones(mut anim: XAnimParts, bones: &[XAnimBoneTrack]) -> XAnimParts {
+    anim.set_bone_tracks(bones).unwrap();
+    anim
+}
+
+/// `anim`'s track for bone `name`.
+fn track(anim: &XAnimParts, name: &str) -> XAnimBoneTrack {
+    anim.bone_tracks()
+        .unwrap()
+        .into_iter()
+        .find(|t| t.name == name)
+        .unwrap()
+}
+
+/// How far `q` turns to the left, in degrees.
+fn quat_yaw_degrees(q: [f32; 4]) -> f32 {
+    2.0 * q[2].atan2(q[3]).to_degrees()
+}
+
+fn assert_near(a: Vec3, b: [f32; 3]) {
+    let a = a.get();
+    assert!(
+        (0..3).all(|i| (a[i] - b[i]).abs() <= EPSILON),
+        "{a:?} != {b:?}"
+    );
+}
+
+/// The yaw of `anim`'s root over the whole animation, in degrees.
+fn yaw_degrees(anim: &XAnimParts) -> f32 {
+    let [_, _, z, w] = anim.root_rotation_delta().unwrap();
+    2.0 * z.atan2(w).to_degrees()
+}
+
+#[test]
+fn blend_midpoint() {
+    let forward = line("forward", 30, [30.0, 0.0, 0.0], [0, i16::MAX]);
+    let strafe = line("strafe", 30, [0.0, 60.0, 0.0], [0, i16::MAX]);
+
+    let blended = forward.blend_with(&strafe, 0.5, &[]).unwrap();
+    assert_eq!(blended.numframes, 30);
+    assert_eq!(blended.name.get(), "forward");
+    for frame in [0, 10, 15, 30] {
+        let t = frame as f32 / 30.0;
+        assert_near(
+            blended.root_offset_at(frame).unwrap(),
+            [15.0 * t, 30.0 * t, 0.0],
+        );
+    }
+
+    // The ends are each input.
+    let none = forward.blend_with(&strafe, 0.0, &[true]).unwrap();
+    assert_near(none.root_offset_at(30).unwrap(), [30.0, 0.0, 0.0]);
+    let all = forward.blend_with(&strafe, 1.0, &[]).unwrap();
+    assert_near(all.root_offset_at(30).unwrap(), [0.0, 60.0, 0.0]);
+}
+
+#[test]
+fn blend_resamples_other() {
+    let forward = line("forward", 30, [30.0, 0.0, 0.0], [0, i16::MAX]);
+    let short = line("short", 10, [0.0, 60.0, 0.0], [0, i16::MAX]);
+
+    // Halfway through `forward` is halfway through `short`.
+    let blended = forward.blend_with(&short, 0.5, &[]).unwrap();
+    assert_eq!(blended.numframes, 30);
+    assert_near(blended.root_offset_at(15).unwrap(), [7.5, 15.0, 0.0]);
+}
+
+#[test]
+fn blend_rotation() {
+    // Quarter turns each way, as [sin(a / 2), cos(a / 2)].
+    let half = (i16::MAX as f32 * core::f32::consts::FRAC_1_SQRT_2) as i16;
+    let left = line("left", 30, [0.0; 3], [half, half]);
+    let straight = line("straight", 30, [0.0; 3], [0, i16::MAX]);
+
+    assert!((yaw_degrees(&left) - 90.0).abs() < 0.1);
+    let blended = left.blend_with(&straight, 0.5, &[]).unwrap();
+    assert!((yaw_degrees(&blended) - 45.0).abs() < 0.1);
+}
+
+#[test]
+fn blend_bones() {
+    let walk = with_bones(
+        line("walk", 30, [30.0, 0.0, 0.0], [0, i16::MAX]),
+        &[
+            bone("j_spine", 30, 90.0, [0.0; 3], [10.0, 0.0, 0.0]),
+            bone("j_head", 30, 0.0, [0.0, 0.0, 5.0], [0.0, 0.0, 5.0]),
+        ],
+    );
+    let aim = with_bones(
+        line("aim", 30, [0.0; 3], [0, i16::MAX]),
+        &[
+            bone("j_head", 30, 40.0, [0.0; 3], [0.0; 3]),
+            bone("j_spine", 30, 0.0, [0.0; 3], [0.0, 20.0, 0.0]),
+            bone("j_gun", 30, 10.0, [0.0; 3], [1.0; 3]),
+        ],
+    );
+
+    // Only the spine, which is relative to its parent, so blends on its
+    // own.
+    let mask = walk
+        .names
+        .iter()
+        .map(|n| n.get() == "j_spine")
+        .collect::<Vec<_>>();
+    let blended = walk.blend_with(&aim, 0.5, &mask).unwrap();
+    assert_eq!(blended.numframes, 30);
+
+    let spine = track(&blended, "j_spine");
+    assert_near(spine.translation_at(15.0).unwrap(), [2.5, 5.0, 0.0]);
+    assert_near(spine.translation_at(30.0).unwrap(), [5.0, 10.0, 0.0]);
+    assert!((quat_yaw_degrees(spine.rotation_at(30.0).unwrap()) - 45.0).abs() < 0.1);
+
+    // Masked-out bones keep `walk`'s tracks, and `aim`'s other bones
+    // aren't added.
+    let head = track(&blended, "j_head");
+    assert_eq!(head.rotation, track(&walk, "j_head").rotation);
+    assert_near(head.translation_at(30.0).unwrap(), [0.0, 0.0, 5.0]);
+    assert_eq!(blended.names.len(), 2);
+    // The root still blends.
+    assert_near(blended.root_offset_at(30).unwrap(), [15.0, 0.0, 0.0]);
+
+    // All the way to `aim`.
+    let all = walk.blend_with(&aim, 1.0, &[true, true]).unwrap();
+    assert_near(
+        track(&all, "j_spine").translation_at(30.0).unwrap(),
+        [0.0, 20.0, 0.0],
+    );
+    assert!(
+        (quat_yaw_degrees(track(&all, "j_head").rotation_at(30.0).unwrap()) - 40.0).abs() < 0.1
+    );
+}
+
+#[test]
+fn blend_bones_resamples_other() {
+    let walk = with_bones(
+        line("walk", 30, [0.0; 3], [0, i16::MAX]),
+        &[bone("j_spine", 30, 0.0, [0.0; 3], [30.0, 0.0, 0.0])],
+    );
+    let short = with_bones(
+        line("short", 10, [0.0; 3], [0, i16::MAX]),
+        &[bone("j_spine", 10, 0.0, [0.0; 3], [0.0, 60.0, 0.0])],
+    );
+
+    let blended = walk.blend_with(&short, 0.5, &[true]).unwrap();
+    assert_near(
+        track(&blended, "j_spine").translation_at(15.0).unwrap(),
+        [7.5, 15.0, 0.0],
+    );
+}
+
+#[test]
+fn blend_bone_channels() {
+    // `still` doesn't move its spine, so the blend keeps `walk`'s
+    // translation, and `walk` doesn't rotate it, so there's none.
+    let walk = with_bones(
+        line("walk", 30, [0.0; 3], [0, i16::MAX]),
+        &[XAnimBoneTrack {
+            rotation: Vec::new(),
+            ..bone("j_spine", 30, 0.0, [0.0; 3], [30.0, 0.0, 0.0])
+        }],
+    );
+    let still = with_bones(
+        line("still", 30, [0.0; 3], [0, i16::MAX]),
+        &[XAnimBoneTrack {
+            translation: Vec::new(),
+            ..bone("j_spine", 30, 90.0, [0.0; 3], [0.0; 3])
+        }],
+    );
+
+    let spine = track(&walk.blend_with(&still, 0.5, &[true]).unwrap(), "j_spine");
+    assert!(spine.rotation.is_empty());
+    assert_near(spine.translation_at(30.0).unwrap(), [30.0, 0.0, 0.0]);
+}
+
+#[test]
+fn additive() {
+    let base = line("walk", 30, [80.0, 0.0, 0.0], [0, i16::MAX]);
+    let lean = line("lean", 10, [0.0, 10.0, 0.0], [0, i16::MAX]);
+
+    let applied = lean.additive_apply(&base).unwrap();
+    assert_eq!(applied.numframes, 30);
+    assert_eq!(applied.name.get(), "walk");
+    assert_near(applied.root_offset_at(5).unwrap(), [40.0 / 3.0, 5.0, 0.0]);
+    // `lean` holds its last frame after it ends.
+    assert_near(applied.root_offset_at(30).unwrap(), [80.0, 10.0, 0.0]);
+
+    // The result is as long as the longer input.
+    let applied = base.additive_apply(&lean).unwrap();
+    assert_eq!(applied.numframes, 30);
+    assert_eq!(applied.name.get(), "lean");
+    assert_near(applied.root_offset_at(30).unwrap(), [80.0, 10.0, 0.0]);
+}
+
+#[test]
+fn additive_bones() {
+    let base = with_bones(
+        line("walk", 30, [0.0; 3], [0, i16::MAX]),
+        &[
+            bone("j_spine", 30, 90.0, [0.0; 3], [10.0, 0.0, 0.0]),
+            bone("j_head", 30, 0.0, [0.0, 0.0, 5.0], [0.0, 0.0, 5.0]),
+        ],
+    );
+    let lean = with_bones(
+        line("lean", 60, [0.0; 3], [0, i16::MAX]),
+        &[
+            // Only its motion from the first frame is added.
+            bone("j_spine", 60, 180.0, [5.0; 3], [5.0, 25.0, 5.0]),
+            bone("j_neck", 60, 0.0, [0.0; 3], [0.0, 0.0, 6.0]),
+        ],
+    );
+
+    let applied = lean.additive_apply(&base).unwrap();
+    assert_eq!(applied.numframes, 60);
+    assert_eq!(applied.name.get(), "walk");
+
+    let spine = track(&applied, "j_spine");
+    assert_near(spine.translation_at(30.0).unwrap(), [10.0, 10.0, 0.0]);
+    assert!((quat_yaw_degrees(spine.rotation_at(30.0).unwrap()) - 180.0).abs() < 0.1);
+    // `base` holds its last frame.
+    assert_near(spine.translation_at(60.0).unwrap(), [10.0, 20.0, 0.0]);
+    assert!((quat_yaw_degrees(spine.rotation_at(60.0).unwrap()).abs() - 270.0).abs() < 0.1);
+
+    // Bones `lean` doesn't have are as they were...
+    assert_near(
+        track(&applied, "j_head").translation_at(45.0).unwrap(),
+        [0.0, 0.0, 5.0],
+    );
+    // ...and ones `base` doesn't are added to nothing.
+    let neck = track(&applied, "j_neck");
+    assert_near(neck.translation_at(30.0).unwrap(), [0.0, 0.0, 3.0]);
+    assert!((quat_yaw_degrees(neck.rotation_at(30.0).unwrap())).abs() < 0.1);
+}
+
+#[test]
+fn additive_lengthens_bones() {
+    // Lengthening `base` past 255 frames widens its key indices.
+    let base = with_bones(
+        line("walk", 30, [0.0; 3], [0, i16::MAX]),
+        &[bone("j_spine", 30, 0.0, [0.0; 3], [10.0, 0.0, 0.0])],
+    );
+    let idle = line("idle", 300, [0.0; 3], [0, i16::MAX]);
+
+    let applied = idle.additive_apply(&base).unwrap();
+    assert_eq!(applied.numframes, 300);
+    assert!(matches!(applied.indices, XAnimIndices::_2(_)));
+    let spine = track(&applied, "j_spine");
+    assert_near(spine.translation_at(15.0).unwrap(), [5.0, 0.0, 0.0]);
+    assert_near(spine.translation_at(300.0).unwrap(), [10.0, 0.0, 0.0]);
+}
@@ -0,0 +1,253 @@
+//! Checks [`XAnimParts::bone_tracks`] against hand-packed streams, and that
+//! [`XAnimParts::set_bone_tracks`] packs tracks that decode the same.
+
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    common::Vec3,
+    xanim::{XAnimBoneTrack, XAnimIndices, XAnimParts},
+};
+
+/// Quantization leaves this much error at most.
+const EPSILON: f32 = 0.01;
+
+/// A quarter turn to the left, as `[x, y, z, w]`.
+const LEFT: [f32; 4] = [
+    0.0,
+    0.0,
+    core::f32::consts::FRAC_1_SQRT_2,
+    core::f32::consts::FRAC_1_SQRT_2,
+];
+
+/// A non-delta animation `numframes` long with no bones.
+fn anim(numframes: u16) -> XAnimParts {
+    XAnimParts {
+        name: XString::from("packed".to_string()),
+        numframes,
+        loop_: false,
+        delta: false,
+        left_hand_grip_ik: false,
+        streamable: false,
+        streamed_file_size: 0,
+        bone_count: [0; 10],
+        notify_count: 0,
+        asset_type: 0,
+        is_default: false,
+        index_count: 0,
+        framerate: 30.0,
+        frequency: 1.0,
+        primed_length: 0.0,
+        loop_entry_time: 0.0,
+        names: Vec::new(),
+        data_byte: Vec::new(),
+        data_short: Vec::new(),
+        data_int: Vec::new(),
+        random_data_short: Vec::new(),
+        random_data_byte: Vec::new(),
+        random_data_int: Vec::new(),
+        indices: XAnimIndices::_1(Vec::new()),
+        notify: Vec::new(),
+        delta_part: None,
+    }
+}
+
+/// A leg whose hip moves forward, whose knee turns left and whose ankle is
+/// rolled a quarter turn, packed by hand.
+fn leg() -> XAnimParts {
+    let bits = |v: [f32; 3]| v.map(|c| c.to_bits() as i32);
+    let half = (i16::MAX as f32 * core::f32::consts::FRAC_1_SQRT_2).round() as i16;
+
+    XAnimParts {
+        names: ["j_hip", "j_knee", "j_ankle"]
+            .map(|n| XString::from(n.to_string()))
+            .to_vec(),
+        // One each of: no rotation, keyed yaw, and full rotation with one
+        // key; keyed and one-key translations, and one without.
+        bone_count: [1, 1, 0, 0, 1, 0, 1, 1, 1, 3],
+        // The hip's and the ankle's translations.
+        data_byte: vec![0, 2],
+        // The knee's size, the ankle's rotation, then the hip's size.
+        data_short: vec![1, half, 0, 0, half, 1],
+        // The hip's minimum and step size, then the ankle's translation.
+        data_int: [bits([1.0, 2.0, 3.0]), bits([0.5; 3]), bits([0.0, 0.0, 4.0])].concat(),
+        // The knee's keys, then the hip's.
+        random_data_short: vec![0, i16::MAX, half, half, 0, 0, 0, 100, 0, 0],
+        indices: XAnimIndices::_1(vec![0, 10, 0, 10]),
+        index_count: 4,
+        ..anim(10)
+    }
+}
+
+fn assert_quat_near(a: [f32; 4], b: [f32; 4]) {
+    // `q` and `-q` are the same rotation.
+    let dot = (0..4).map(|i| a[i] * b[i]).sum::<f32>();
+    assert!(dot.abs() >= 1.0 - EPSILON, "{a:?} != {b:?}");
+}
+
+fn assert_near(a: Vec3, b: [f32; 3]) {
+    let a = a.get();
+    assert!(
+        (0..3).all(|i| (a[i] - b[i]).abs() <= EPSILON),
+        "{a:?} != {b:?}"
+    );
+}
+
+fn assert_tracks_near(a: &[XAnimBoneTrack], b: &[XAnimBoneTrack]) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b) {
+        assert_eq!(a.name, b.name);
+        assert_eq!(a.rotation.len(), b.rotation.len(), "{}", a.name);
+        for (&(ka, qa), &(kb, qb)) in a.rotation.iter().zip(&b.rotation) {
+            assert_eq!(ka, kb, "{}", a.name);
+            assert_quat_near(qa, qb);
+        }
+        assert_eq!(a.translation.len(), b.translation.len(), "{}", a.name);
+        for (&(ka, va), &(kb, vb)) in a.translation.iter().zip(&b.translation) {
+            assert_eq!(ka, kb, "{}", a.name);
+            assert_near(va, vb.get());
+        }
+    }
+}
+
+#[test]
+fn decode() {
+    let tracks = leg().bone_tracks().unwrap();
+    let [hip, knee, ankle] = tracks.as_slice() else {
+        panic!("expected three bones, got {tracks:?}");
+    };
+
+    assert_eq!(hip.name, "j_hip");
+    assert!(hip.rotation.is_empty());
+    assert_eq!(hip.translation.len(), 2);
+    assert_near(hip.translation_at(0.0).unwrap(), [1.0, 2.0, 3.0]);
+    assert_near(hip.translation_at(5.0).unwrap(), [26.0, 2.0, 3.0]);
+    assert_near(hip.translation_at(10.0).unwrap(), [51.0, 2.0, 3.0]);
+
+    assert_eq!(knee.name, "j_knee");
+    assert!(knee.translation.is_empty());
+    assert_eq!(
+        knee.rotation.iter().map(|k| k.0).collect::<Vec<_>>(),
+        [0, 10]
+    );
+    assert_quat_near(knee.rotation_at(0.0).unwrap(), [0.0, 0.0, 0.0, 1.0]);
+    assert_quat_near(knee.rotation_at(10.0).unwrap(), LEFT);
+    assert!(knee.translation_at(5.0).is_none());
+
+    assert_eq!(ankle.name, "j_ankle");
+    assert_quat_near(
+        ankle.rotation_at(7.0).unwrap(),
+        [
+            core::f32::consts::FRAC_1_SQRT_2,
+            0.0,
+            0.0,
+            core::f32::consts::FRAC_1_SQRT_2,
+        ],
+    );
+    assert_near(ankle.translation_at(7.0).unwrap(), [0.0, 0.0, 4.0]);
+}
+
+#[test]
+fn roundtrip() {
+    let leg = leg();
+    let tracks = leg.bone_tracks().unwrap();
+
+    let mut packed = anim(10);
+    packed.set_bone_tracks(&tracks).unwrap();
+    assert_eq!(packed.bone_count, leg.bone_count);
+    assert_eq!(packed.data_byte, leg.data_byte);
+    assert_eq!(packed.data_short, leg.data_short);
+    assert_tracks_near(&packed.bone_tracks().unwrap(), &tracks);
+}
+
+#[test]
+fn sorted_by_rotation() {
+    let tracks = vec![
+        XAnimBoneTrack {
+            rotation: vec![(0, [0.0, 0.0, 0.0, 1.0])],
+            ..XAnimBoneTrack::new("posed".to_string())
+        },
+        XAnimBoneTrack {
+            rotation: vec![(0, [0.0, 0.0, 0.0, 1.0]), (4, [0.5, 0.5, 0.5, 0.5])],
+            ..XAnimBoneTrack::new("rolled".to_string())
+        },
+        XAnimBoneTrack {
+            translation: vec![(2, Vec3::from([1.0, 0.0, 0.0]))],
+            ..XAnimBoneTrack::new("moved".to_string())
+        },
+    ];
+
+    let mut packed = anim(4);
+    packed.set_bone_tracks(&tracks).unwrap();
+    let names = packed.names.iter().map(|n| n.get()).collect::<Vec<_>>();
+    assert_eq!(names, ["moved", "rolled", "posed"]);
+    assert_eq!(packed.bone_count, [1, 0, 1, 1, 0, 0, 0, 1, 2, 3]);
+
+    let decoded = packed.bone_tracks().unwrap();
+    assert_tracks_near(&decoded[1..], &[tracks[1].clone(), tracks[0].clone()]);
+    // A translation with one key is stored without its frame.
+    assert_eq!(decoded[0].name, "moved");
+    assert!(decoded[0].rotation.is_empty());
+    let [(0, moved)] = decoded[0].translation.as_slice() else {
+        panic!("expected one key at frame 0, got {:?}", decoded[0]);
+    };
+    assert_near(*moved, [1.0, 0.0, 0.0]);
+}
+
+#[test]
+fn many_keys() {
+    // Enough keys for a lookup table, and wide enough indices for frames
+    // past 255.
+    let numframes = 300;
+    let track = XAnimBoneTrack {
+        rotation: (0..=numframes)
+            .map(|f| {
+                let a = f as f32 / numframes as f32 * core::f32::consts::FRAC_PI_4;
+                (f, [0.0, 0.0, a.sin(), a.cos()])
+            })
+            .collect(),
+        translation: (0..=numframes)
+            .step_by(3)
+            .map(|f| (f, Vec3::from([f as f32, 0.0, -(f as f32)])))
+            .collect(),
+        ..XAnimBoneTrack::new("j_spine".to_string())
+    };
+
+    let mut packed = anim(numframes);
+    packed
+        .set_bone_tracks(std::slice::from_ref(&track))
+        .unwrap();
+    assert!(matches!(&packed.indices, XAnimIndices::_2(v) if v.len() == 301 + 101));
+    assert_eq!(packed.index_count, 301 + 101);
+    // Each track's size, then a short for each 256 keys.
+    assert_eq!(packed.data_short, [300, 0, 256, 100, 0]);
+    assert_tracks_near(&packed.bone_tracks().unwrap(), &[track]);
+}
+
+#[test]
+fn too_short() {
+    let mut leg = leg();
+    leg.random_data_short.pop();
+    let e = leg.bone_tracks().unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+
+    // More bones than names.
+    let mut leg = self::leg();
+    leg.bone_count[0] += 1;
+    let e = leg.bone_tracks().unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+}
+
+#[test]
+fn keys_out_of_order() {
+    let mut packed = anim(10);
+    for translation in [
+        vec![(5, Vec3::default()), (5, Vec3::default())],
+        vec![(0, Vec3::default()), (11, Vec3::default())],
+    ] {
+        let track = XAnimBoneTrack {
+            translation,
+            ..XAnimBoneTrack::new("j_hip".to_string())
+        };
+        let e = packed.set_bone_tracks(&[track]).unwrap_err();
+        assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+    }
+}
@@ -0,0 +1,119 @@
+//! Checks [`XAnimParts::notetracks`] and [`WeaponDef::timed_sounds`].
+
+use t5_xfile_defs::{
+    XString,
+    weapon::WeaponDef,
+    xanim::{Notetrack, XAnimIndices, XAnimNotifyInfo, XAnimParts},
+};
+
+fn notify(name: &str, time: f32) -> XAnimNotifyInfo {
+    XAnimNotifyInfo {
+        name: XString::from(name.to_string()),
+        time,
+    }
+}
+
+/// A 2 second reload: 60 frames at 30 frames a second. The clip goes in
+/// before it comes out in storage.
+fn reload() -> XAnimParts {
+    XAnimParts {
+        name: XString::from("viewmodel_m16_reload".to_string()),
+        numframes: 60,
+        loop_: false,
+        delta: false,
+        left_hand_grip_ik: false,
+        streamable: false,
+        streamed_file_size: 0,
+        bone_count: [0; 10],
+        notify_count: 3,
+        asset_type: 0,
+        is_default: false,
+        index_count: 0,
+        framerate: 30.0,
+        frequency: 0.5,
+        primed_length: 0.0,
+        loop_entry_time: 0.0,
+        names: Vec::new(),
+        data_byte: Vec::new(),
+        data_short: Vec::new(),
+        data_int: Vec::new(),
+        random_data_short: Vec::new(),
+        random_data_byte: Vec::new(),
+        random_data_int: Vec::new(),
+        indices: XAnimIndices::_1(Vec::new()),
+        notify: vec![
+            notify("sound_clipin", 0.75),
+            notify("sound_clipout", 0.25),
+            notify("end", 1.0),
+        ],
+        delta_part: None,
+    }
+}
+
+fn notetrack(name: &str, frame: u32, time_seconds: f32) -> Notetrack {
+    Notetrack {
+        name: name.to_string(),
+        frame,
+        time_seconds,
+    }
+}
+
+#[test]
+fn notetracks() {
+    let anim = reload();
+    assert_eq!(anim.duration_seconds(), 2.0);
+    assert!(!anim.is_looping());
+
+    assert_eq!(
+        anim.notetracks(),
+        [
+            notetrack("sound_clipout", 15, 0.5),
+            notetrack("sound_clipin", 45, 1.5),
+            notetrack("end", 60, 2.0),
+        ]
+    );
+    assert_eq!(
+        anim.notetracks_matching("sound_"),
+        [
+            notetrack("sound_clipout", 15, 0.5),
+            notetrack("sound_clipin", 45, 1.5),
+        ]
+    );
+    assert!(anim.notetracks_matching("fx_").is_empty());
+
+    // Without a frequency, the duration comes from the frame count.
+    let anim = XAnimParts {
+        frequency: 0.0,
+        framerate: 15.0,
+        loop_: true,
+        ..reload()
+    };
+    assert_eq!(anim.duration_seconds(), 4.0);
+    assert!(anim.is_looping());
+    assert_eq!(anim.notetracks()[0], notetrack("sound_clipout", 15, 1.0));
+}
+
+#[test]
+fn timed_sounds() {
+    let mut keys = Box::new(<[String; 20]>::default());
+    let mut values = Box::new(<[String; 20]>::default());
+    keys[0] = "sound_clipin".to_string();
+    values[0] = "wpn_m16_clipin".to_string();
+    keys[1] = "sound_clipout".to_string();
+    values[1] = "wpn_m16_clipout".to_string();
+    let weapon = WeaponDef {
+        notetrack_sound_map_keys: Some(keys),
+        notetrack_sound_map_values: Some(values),
+        ..Default::default()
+    };
+
+    let anim = reload();
+    assert_eq!(
+        weapon.timed_sounds(&[&anim]),
+        [
+            (0.5, "wpn_m16_clipout".to_string()),
+            (1.5, "wpn_m16_clipin".to_string()),
+        ]
+    );
+    assert!(WeaponDef::default().timed_sounds(&[&anim]).is_empty());
+}
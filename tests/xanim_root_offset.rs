@@ -0,0 +1,206 @@
+//! Checks [`XAnimParts::root_offset_at`] and the root motion deltas
+//! against a hand-built delta animation whose root steps forward, pauses,
+//! then steps again.
+
+use t5_xfile_defs::{
+    XString,
+    common::Vec3,
+    xanim::{
+        XAnimDeltaPart, XAnimDeltaPartQuat, XAnimDeltaPartQuatData, XAnimDeltaPartQuatDataFrames,
+        XAnimDynamicFrames, XAnimDynamicIndices, XAnimIndices, XAnimPartTrans, XAnimPartTransData,
+        XAnimPartTransFrames, XAnimParts,
+    },
+};
+
+/// Keyframes at frames 0, 10, 20 and 30, at x = 0, 40, 40 and 80. Each
+/// quantization step is 0.5 units.
+fn walk() -> XAnimParts {
+    let trans = XAnimPartTrans {
+        size: 3,
+        small_trans: 1,
+        u: Some(XAnimPartTransData::Frames(XAnimPartTransFrames {
+            mins: Vec3::from([0.0, 8.0, 0.0]),
+            maxs: Vec3::from([0.5, 0.5, 0.5]),
+            frames: XAnimDynamicFrames::_1(vec![[0, 0, 0], [80, 0, 0], [80, 0, 0], [160, 0, 0]]),
+            indices: XAnimDynamicIndices::_1(vec![0, 10, 20, 30]),
+        })),
+    };
+
+    XAnimParts {
+        name: XString::from("mp_walk_forward".to_string()),
+        numframes: 30,
+        loop_: true,
+        delta: true,
+        left_hand_grip_ik: false,
+        streamable: false,
+        streamed_file_size: 0,
+        bone_count: [0; 10],
+        notify_count: 0,
+        asset_type: 0,
+        is_default: false,
+        index_count: 0,
+        framerate: 30.0,
+        frequency: 1.0,
+        primed_length: 0.0,
+        loop_entry_time: 0.0,
+        names: Vec::new(),
+        data_byte: Vec::new(),
+        data_short: Vec::new(),
+        data_int: Vec::new(),
+        random_data_short: Vec::new(),
+        random_data_byte: Vec::new(),
+        random_data_int: Vec::new(),
+        indices: XAnimIndices::_1(Vec::new()),
+        notify: Vec::new(),
+        delta_part: Some(Box::new(XAnimDeltaPart {
+            trans: Some(Box::new(trans)),
+            quat: None,
+        })),
+    }
+}
+
+fn x(anim: &XAnimParts, frame: usize) -> Option<f32> {
+    anim.root_offset_at(frame).map(|v| v.get()[0])
+}
+
+#[test]
+fn root_offset_at() {
+    let anim = walk();
+
+    assert_eq!(x(&anim, 0), Some(0.0));
+    assert_eq!(x(&anim, 5), Some(20.0));
+    assert_eq!(x(&anim, 10), Some(40.0));
+    // Planted between 10 and 20.
+    assert_eq!(x(&anim, 15), Some(40.0));
+    assert_eq!(x(&anim, 25), Some(60.0));
+    assert_eq!(x(&anim, 30), Some(80.0));
+    assert_eq!(x(&anim, 31), None);
+
+    // Relative to the first frame, so the base offset in mins cancels out.
+    assert_eq!(anim.root_offset_at(30).unwrap().get(), [80.0, 0.0, 0.0]);
+    assert_eq!(
+        anim.root_offset_at(30).unwrap().get(),
+        anim.root_motion_delta().unwrap().get()
+    );
+}
+
+#[test]
+fn root_offset_non_delta() {
+    let anim = XAnimParts {
+        delta: false,
+        ..walk()
+    };
+    assert!(anim.root_offset_at(0).is_none());
+
+    let anim = XAnimParts {
+        delta_part: None,
+        ..walk()
+    };
+    assert_eq!(anim.root_offset_at(12).unwrap().get(), [0.0; 3]);
+}
+
+#[test]
+fn has_ik_data() {
+    assert!(!walk().has_ik_data());
+    assert!(
+        XAnimParts {
+            left_hand_grip_ik: true,
+            ..walk()
+        }
+        .has_ik_data()
+    );
+}
+
+fn with_quat(anim: XAnimParts, u: XAnimDeltaPartQuatData) -> XAnimParts {
+    let mut anim = anim;
+    anim.delta_part.as_mut().unwrap().quat = Some(Box::new(XAnimDeltaPartQuat {
+        size: 1,
+        u: Some(u),
+    }));
+    anim
+}
+
+fn quat_frames(frames: Vec<[i16; 2]>) -> XAnimDeltaPartQuatData {
+    let indices = (0..frames.len() as u8).map(|i| i * 10).collect();
+    XAnimDeltaPartQuatData::Frames(XAnimDeltaPartQuatDataFrames {
+        frames,
+        indices: XAnimDynamicIndices::_1(indices),
+    })
+}
+
+fn close(a: [f32; 4], b: [f32; 4]) -> bool {
+    a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-4)
+}
+
+#[test]
+fn root_motion_delta() {
+    let anim = walk();
+    // Last minus first, so the base offset in mins cancels out.
+    assert_eq!(anim.root_motion_delta().unwrap().get(), [80.0, 0.0, 0.0]);
+    assert_eq!(anim.motion_per_cycle().unwrap().get(), [80.0, 0.0, 0.0]);
+
+    let once = XAnimParts {
+        loop_: false,
+        ..walk()
+    };
+    assert_eq!(once.root_motion_delta().unwrap().get(), [80.0, 0.0, 0.0]);
+    assert!(once.motion_per_cycle().is_none());
+
+    let non_delta = XAnimParts {
+        delta: false,
+        ..walk()
+    };
+    assert!(non_delta.root_motion_delta().is_none());
+    assert!(non_delta.motion_per_cycle().is_none());
+
+    // No translation track means the root doesn't move.
+    let mut still = walk();
+    still.delta_part.as_mut().unwrap().trans = None;
+    assert_eq!(still.root_motion_delta().unwrap().get(), [0.0; 3]);
+
+    let mut frame0 = walk();
+    frame0
+        .delta_part
+        .as_mut()
+        .unwrap()
+        .trans
+        .as_mut()
+        .unwrap()
+        .u = Some(XAnimPartTransData::Frame0(Vec3::from([5.0, 6.0, 7.0])));
+    assert_eq!(frame0.root_motion_delta().unwrap().get(), [0.0; 3]);
+}
+
+#[test]
+fn root_rotation_delta() {
+    const IDENTITY: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+    let h = core::f32::consts::FRAC_1_SQRT_2;
+
+    // No rotation track.
+    assert_eq!(walk().root_rotation_delta(), Some(IDENTITY));
+    assert!(
+        XAnimParts {
+            delta: false,
+            ..walk()
+        }
+        .root_rotation_delta()
+        .is_none()
+    );
+
+    // A quarter turn, from facing forward.
+    let turn = with_quat(
+        walk(),
+        quat_frames(vec![[0, 32767], [16000, 20000], [23170, 23170]]),
+    );
+    assert!(close(turn.root_rotation_delta().unwrap(), [0.0, 0.0, h, h]));
+
+    // Another quarter turn, from already facing left. Only the difference
+    // counts, and the quantized values don't need to be unit length.
+    let turn = with_quat(walk(), quat_frames(vec![[100, 100], [1000, 0]]));
+    assert!(close(turn.root_rotation_delta().unwrap(), [0.0, 0.0, h, h]));
+
+    // A single frame, and a zero quaternion, are no rotation at all.
+    let fixed = with_quat(walk(), XAnimDeltaPartQuatData::Frame0([12000, 3000]));
+    assert!(close(fixed.root_rotation_delta().unwrap(), IDENTITY));
+    let zero = with_quat(walk(), quat_frames(vec![[0, 0], [0, 0]]));
+    assert!(close(zero.root_rotation_delta().unwrap(), IDENTITY));
+}
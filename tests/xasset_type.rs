@@ -0,0 +1,98 @@
+//! Checks the [`XAssetType`] queries, including that
+//! [`XAssetType::is_used_by_t5`] agrees with which types the deserializer
+//! accepts.
+
+mod common;
+
+use t5_xfile_defs::xasset::{AssetCategory, XAssetType};
+
+#[cfg(feature = "deserializer")]
+use common::TempFile;
+
+fn all() -> Vec<XAssetType> {
+    (0..=XAssetType::ASSETLIST as u32)
+        .map(|t| XAssetType::from_raw(t).unwrap())
+        .collect()
+}
+
+#[test]
+fn all_used() {
+    let used = all()
+        .into_iter()
+        .filter(XAssetType::is_used_by_t5)
+        .collect::<Vec<_>>();
+    assert_eq!(XAssetType::all_used(), used);
+    assert_eq!(used.len(), 34);
+    assert_eq!(all().len(), 45);
+
+    for unused in [
+        XAssetType::AITYPE,
+        XAssetType::MPBODY,
+        XAssetType::MPHEAD,
+        XAssetType::WEAPONDEF,
+    ] {
+        assert!(!unused.is_used_by_t5());
+        assert_eq!(unused.expected_raw_struct_size(), None);
+    }
+}
+
+#[test]
+fn expected_raw_struct_size() {
+    for t in XAssetType::all_used() {
+        assert!(t.expected_raw_struct_size().is_some(), "{t:?}");
+    }
+
+    for (t, size) in [
+        (XAssetType::XMODEL, 252),
+        (XAssetType::PHYSCONSTRAINTS, 2696),
+        (XAssetType::WEAPON, 228),
+        (XAssetType::GFXWORLD, 1084),
+        (XAssetType::MENU, 400),
+        (XAssetType::CLIPMAP_PVS, 332),
+        (XAssetType::ASSETLIST, 364),
+    ] {
+        assert_eq!(t.expected_raw_struct_size(), Some(size), "{t:?}");
+    }
+}
+
+#[test]
+fn category() {
+    assert_eq!(XAssetType::XMODEL.category(), AssetCategory::Geometry);
+    assert_eq!(XAssetType::IMAGE.category(), AssetCategory::Material);
+    assert_eq!(XAssetType::XANIMPARTS.category(), AssetCategory::Animation);
+    assert_eq!(XAssetType::SOUND.category(), AssetCategory::Audio);
+    assert_eq!(XAssetType::GFXWORLD.category(), AssetCategory::World);
+    assert_eq!(XAssetType::MENU.category(), AssetCategory::UI);
+    assert_eq!(XAssetType::WEAPON.category(), AssetCategory::Weapon);
+    assert_eq!(XAssetType::RAWFILE.category(), AssetCategory::Misc);
+}
+
+/// An inflated blob holding a single null asset of type `asset_type`.
+#[cfg(feature = "deserializer")]
+fn null_asset_blob(asset_type: XAssetType) -> Vec<u8> {
+    const INLINE: u32 = 0xFFFFFFFF;
+    let data = [0, 0, 1, INLINE, asset_type as u32, 0];
+
+    [data.len() as u32 * 4, 0, 0, 0, 0, 0, 0, 0, 0]
+        .iter()
+        .chain(&data)
+        .flat_map(|v| v.to_le_bytes())
+        .collect()
+}
+
+#[cfg(feature = "deserializer")]
+#[test]
+fn used_matches_deserializer() {
+    use t5_xfile_defs::ErrorKind;
+
+    for t in all() {
+        let temp = TempFile::new("xasset_type.cache", null_asset_blob(t));
+        let result = common::deserialize(&temp, true);
+
+        let rejected = matches!(
+            result.as_ref().map_err(|e| e.kind()),
+            Err(ErrorKind::UnusedXAssetType(u)) if *u == t
+        );
+        assert_eq!(rejected, !t.is_used_by_t5(), "{t:?}: {:?}", result.err());
+    }
+}
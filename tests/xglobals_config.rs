@@ -0,0 +1,78 @@
+//! Checks [`XGlobals`]' config map conversions.
+
+use std::collections::BTreeMap;
+
+use t5_xfile_defs::misc::XGlobals;
+
+fn globals() -> XGlobals {
+    XGlobals {
+        xanim_stream_buffer_size: 0x40000,
+        gump_reserve: 0x20_0000,
+        screen_clear_color: [0.25, 0.5, 0.75, 1.0].into(),
+        ..XGlobals::default_for_patch("mp_crate")
+    }
+}
+
+#[test]
+fn round_trip() {
+    let map = globals().to_config_map();
+    assert_eq!(map.len(), 9);
+    assert_eq!(map["xanim_stream_buffer_size"], 262144.0);
+    assert_eq!(map["cinematic_max_width"], 1280.0);
+    assert_eq!(map["screen_clear_color_b"], 0.75);
+
+    let back = XGlobals::from_config_map("mp_crate", &map).unwrap();
+    assert_eq!(back.name.get(), "mp_crate");
+    assert_eq!(back.to_config_map(), map);
+    assert_eq!(back.gump_reserve, 0x20_0000);
+    assert_eq!(back.screen_clear_color.get(), [0.25, 0.5, 0.75, 1.0]);
+}
+
+#[test]
+fn bad_maps() {
+    let mut map = globals().to_config_map();
+    map.insert("not_a_field", 1.0);
+    assert!(XGlobals::from_config_map("x", &map).is_ok());
+
+    let mut missing = map.clone();
+    missing.remove("gump_reserve");
+    assert!(XGlobals::from_config_map("x", &missing).is_err());
+
+    for bad in [0.5, f32::NAN, f32::INFINITY, 3e9] {
+        let mut map = map.clone();
+        map.insert("extracam_resolution", bad);
+        assert!(XGlobals::from_config_map("x", &map).is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn diff() {
+    let a = globals().to_config_map();
+    let mut b = XGlobals {
+        extracam_resolution: 1024,
+        ..globals()
+    }
+    .to_config_map();
+    assert_eq!(
+        XGlobals::diff_config_maps(&a, &b),
+        [("extracam_resolution", 512.0, 1024.0)]
+    );
+    assert!(XGlobals::diff_config_maps(&a, &a).is_empty());
+
+    b.remove("screen_clear_color_a");
+    let diff = XGlobals::diff_config_maps(&a, &b);
+    assert_eq!(diff.len(), 2);
+    assert_eq!((diff[1].0, diff[1].1), ("screen_clear_color_a", 1.0));
+    assert!(diff[1].2.is_nan());
+
+    let empty = BTreeMap::new();
+    assert_eq!(XGlobals::diff_config_maps(&empty, &a).len(), 9);
+}
+
+#[test]
+fn describe_field() {
+    for key in globals().to_config_map().keys() {
+        assert_ne!(XGlobals::describe_field(key), "unknown", "{key}");
+    }
+    assert_eq!(XGlobals::describe_field("name"), "unknown");
+}
@@ -0,0 +1,125 @@
+//! Checks the vertex and index buffers built from an [`XModel`]'s LODs.
+
+use t5_xfile_defs::{
+    XString,
+    xmodel::{
+        GfxColor, GfxPackedVertex, TexCoords, UnitVec, VertexBufferFormat, XModel, XModelLodInfo,
+        XRigidVertList, XSurface,
+    },
+};
+
+const FORMATS: [VertexBufferFormat; 3] = [
+    VertexBufferFormat::PositionNormalUV,
+    VertexBufferFormat::PositionNormalUVColor,
+    VertexBufferFormat::PositionNormalUVSkinned,
+];
+
+fn vertex(x: f32) -> GfxPackedVertex {
+    GfxPackedVertex {
+        xyz: [x, 2.0, 3.0].into(),
+        color: GfxColor([0x11, 0x22, 0x33, 0x44]),
+        // U = 1.0, V = 0.0
+        tex_coord: TexCoords(0x3C00 << 16),
+        normal: UnitVec([127, 127, 254, 0]),
+        ..Default::default()
+    }
+}
+
+/// A quad: four vertices and two triangles, attached to `bone`.
+fn quad(bone: usize) -> XSurface {
+    XSurface {
+        verts0: (0..4).map(|i| vertex(i as f32)).collect(),
+        tri_indices: vec![0, 1, 2, 2, 1, 3],
+        vert_list: vec![XRigidVertList {
+            bone_offset: bone * 64,
+            vert_count: 4,
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+/// LOD 0 has two quads, LOD 1 has one.
+fn model() -> XModel {
+    let mut lod_info = <[XModelLodInfo; 4]>::default();
+    lod_info[0].numsurfs = 2;
+    lod_info[1].surf_index = 2;
+    lod_info[1].numsurfs = 1;
+
+    XModel {
+        name: XString::from("test_model".to_string()),
+        surfs: vec![quad(1), quad(2), quad(0)],
+        lod_info,
+        num_lods: 2,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn vertex_buffer_lengths() {
+    let model = model();
+    for format in FORMATS {
+        for (lod, vertex_count) in [(0, 8), (1, 4)] {
+            let buf = model.build_vertex_buffer(lod, format).unwrap();
+            assert_eq!(buf.len(), XModel::vertex_stride(format) * vertex_count);
+        }
+    }
+}
+
+#[test]
+fn vertex_buffer_contents() {
+    let model = model();
+    let f32s = |b: &[u8]| {
+        b.chunks(4)
+            .map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+            .collect::<Vec<_>>()
+    };
+
+    let buf = model
+        .build_vertex_buffer(0, VertexBufferFormat::PositionNormalUVColor)
+        .unwrap();
+    let v = &buf[36..72];
+    assert_eq!(f32s(&v[..12]), [1.0, 2.0, 3.0]);
+    assert_eq!(f32s(&v[24..32]), [1.0, 0.0]);
+    assert_eq!(v[32..], [0x11, 0x22, 0x33, 0x44]);
+
+    // The second quad is attached to bone 2 with its full weight.
+    let buf = model
+        .build_vertex_buffer(0, VertexBufferFormat::PositionNormalUVSkinned)
+        .unwrap();
+    let v = &buf[48 * 4..48 * 5];
+    let u16s = v[32..]
+        .chunks(2)
+        .map(|c| u16::from_le_bytes(c.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(u16s, [2, 0, 0, 0, u16::MAX, 0, 0, 0]);
+}
+
+#[test]
+fn index_buffer() {
+    let model = model();
+    assert_eq!(
+        model.build_index_buffer(0).unwrap(),
+        [0, 1, 2, 2, 1, 3, 4, 5, 6, 6, 5, 7]
+    );
+    assert_eq!(model.build_index_buffer(1).unwrap(), [0, 1, 2, 2, 1, 3]);
+}
+
+#[test]
+fn bad_lod() {
+    let model = model();
+    assert!(model.build_index_buffer(2).is_err());
+    assert!(
+        model
+            .build_vertex_buffer(4, VertexBufferFormat::PositionNormalUV)
+            .is_err()
+    );
+}
+
+#[test]
+fn index_out_of_range() {
+    let mut model = model();
+    model.surfs[1].tri_indices[5] = 4;
+    assert!(model.build_index_buffer(0).is_err());
+    assert!(model.build_index_buffer(1).is_ok());
+}
@@ -0,0 +1,142 @@
+//! Checks [`XModel::hitboxes`] and that collision surfaces attached to bones
+//! the model doesn't have are rejected.
+
+mod common;
+
+use t5_xfile_defs::{
+    XString,
+    common::{Bounds, Vec3},
+    xmodel::{HitLocation, XModel, XModelCollSurf},
+};
+
+fn coll_surf(bone_idx: usize, mins: [f32; 3], maxs: [f32; 3]) -> XModelCollSurf {
+    XModelCollSurf {
+        bone_idx,
+        mins: Vec3(mins),
+        maxs: Vec3(maxs),
+        ..Default::default()
+    }
+}
+
+fn bounds(b: &Bounds) -> ([f32; 3], [f32; 3]) {
+    (b.mins.0, b.maxs.0)
+}
+
+#[test]
+fn hitboxes() {
+    let model = XModel {
+        name: XString::from("c_usa_marine_body"),
+        num_bones: 3,
+        bone_names: vec![
+            "j_head".to_string(),
+            "j_spine4".to_string(),
+            "j_wrist_le".to_string(),
+        ],
+        part_classification: vec![
+            HitLocation::HEAD as u8,
+            HitLocation::TORSO_UPR as u8,
+            // Not a valid location.
+            0x40,
+        ],
+        coll_surfs: vec![
+            coll_surf(0, [-4.0, -4.0, 0.0], [4.0, 4.0, 8.0]),
+            coll_surf(1, [-8.0, -6.0, -12.0], [8.0, 6.0, 12.0]),
+            coll_surf(2, [-1.0; 3], [1.0; 3]),
+        ],
+        ..Default::default()
+    };
+
+    let hitboxes = model.hitboxes();
+    assert_eq!(
+        hitboxes
+            .iter()
+            .map(|h| (h.bone.as_str(), h.loc))
+            .collect::<Vec<_>>(),
+        [
+            ("j_head", HitLocation::HEAD),
+            ("j_spine4", HitLocation::TORSO_UPR),
+            ("j_wrist_le", HitLocation::NONE),
+        ]
+    );
+    assert_eq!(
+        bounds(&hitboxes[1].bounds),
+        ([-8.0, -6.0, -12.0], [8.0, 6.0, 12.0])
+    );
+
+    assert_eq!(model.hit_location(0), HitLocation::HEAD);
+    assert_eq!(model.hit_location(3), HitLocation::NONE);
+}
+
+#[cfg(feature = "deserializer")]
+mod deserialize {
+    use crate::common::{self, TempFile, u32s};
+
+    use t5_xfile_defs::{
+        ErrorKind,
+        xasset::{XAsset, XAssetGeneric},
+    };
+
+    const XMODEL: u32 = 0x05;
+    const INLINE: u32 = 0xFFFFFFFF;
+
+    /// An inflated blob with one model named `m`, with a single root bone
+    /// and a single collision surface attached to `bone_idx`. Everything
+    /// else is null.
+    fn blob(bone_idx: i32) -> Vec<u8> {
+        let mut model = vec![0u8; 252];
+        model[0..4].copy_from_slice(&INLINE.to_le_bytes());
+        // num_bones, num_root_bones
+        model[4] = 1;
+        model[5] = 1;
+        // coll_surfs
+        model[172..176].copy_from_slice(&INLINE.to_le_bytes());
+        model[176..180].copy_from_slice(&1u32.to_le_bytes());
+
+        let mut body = u32s(&[0, INLINE, 1, INLINE, XMODEL, INLINE]);
+        body.extend(model);
+        body.extend(b"m\0");
+        // coll_tris (null), mins, maxs
+        body.extend(vec![0u8; 32]);
+        body.extend(bone_idx.to_le_bytes());
+        // contents, surf_flags
+        body.extend(vec![0u8; 8]);
+
+        let mut blob = u32s(&[body.len() as u32, 0, 0, 0, 0, 0, 0, 0, 0]);
+        blob.extend(body);
+        blob
+    }
+
+    fn deserialize(name: &str, bone_idx: i32) -> t5_xfile_defs::Result<Vec<XAsset>> {
+        let temp = TempFile::new(&format!("xmodel_hitboxes_{name}.cache"), blob(bone_idx));
+        common::deserialize(&temp, true)
+    }
+
+    #[test]
+    fn valid_bone_idx() {
+        let assets = deserialize("valid", 0).unwrap();
+        let [XAsset::PC(XAssetGeneric::XModel(Some(model)))] = assets.as_slice() else {
+            panic!("expected one XModel, got {assets:?}");
+        };
+        assert_eq!(model.coll_surfs.len(), 1);
+        assert_eq!(model.coll_surfs[0].bone_idx, 0);
+    }
+
+    #[test]
+    fn bad_bone_idx() {
+        let message = |bone_idx| {
+            let e = deserialize(&format!("bone_{bone_idx}"), bone_idx).unwrap_err();
+            let ErrorKind::BrokenInvariant(message) = e.kind() else {
+                panic!("expected a broken invariant, got {e}");
+            };
+            message.clone()
+        };
+
+        for bone_idx in [1, 200] {
+            assert_eq!(
+                message(bone_idx),
+                format!("XModelCollSurf: bone_idx ({bone_idx}) >= num_bones (1)")
+            );
+        }
+        assert_eq!(message(-1), "XModelCollSurf: bone_idx (-1) < 0");
+    }
+}
@@ -0,0 +1,82 @@
+//! Checks [`XModel::select_lod`] on a model with three LODs.
+
+use t5_xfile_defs::xmodel::{
+    LOD_REFERENCE_FOV, LOD_REFERENCE_SCREEN_HEIGHT, XModel, XModelLodInfo,
+};
+
+fn lod(dist: f32) -> XModelLodInfo {
+    XModelLodInfo {
+        dist,
+        ..Default::default()
+    }
+}
+
+/// Full detail up to 500 units, then 1500, then 4000.
+fn crate_model() -> XModel {
+    XModel {
+        radius: 32.0,
+        num_lods: 3,
+        lod_info: [lod(500.0), lod(1500.0), lod(4000.0), lod(9000.0)],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn thresholds() {
+    let model = crate_model();
+    assert_eq!(model.lod_count(), 3);
+    assert_eq!(model.lod_dist_thresholds(), [500.0, 1500.0, 4000.0]);
+
+    let model = XModel {
+        num_lods: 7,
+        ..crate_model()
+    };
+    assert_eq!(model.lod_count(), 4);
+    assert_eq!(XModel::default().lod_count(), 0);
+    assert_eq!(XModel::default().select_lod(100.0, 480, 65.0), 0);
+}
+
+#[test]
+fn reference_view() {
+    let model = crate_model();
+    let select = |dist| model.select_lod(dist, LOD_REFERENCE_SCREEN_HEIGHT, LOD_REFERENCE_FOV);
+
+    assert_eq!(select(0.0), 0);
+    assert_eq!(select(100.0), 0);
+    assert_eq!(select(499.0), 0);
+    assert_eq!(select(500.0), 1);
+    assert_eq!(select(1000.0), 1);
+    assert_eq!(select(3000.0), 2);
+    // Past the last threshold, the last LOD is the best there is.
+    assert_eq!(select(20000.0), 2);
+}
+
+#[test]
+fn other_views() {
+    let model = crate_model();
+
+    // Twice the resolution keeps the model twice as many pixels across, so
+    // it stays detailed twice as far out.
+    assert_eq!(model.select_lod(900.0, 960, LOD_REFERENCE_FOV), 0);
+    assert_eq!(model.select_lod(1100.0, 960, LOD_REFERENCE_FOV), 1);
+    assert_eq!(model.select_lod(900.0, 240, LOD_REFERENCE_FOV), 2);
+
+    // Zooming in does the same.
+    let half_tan = (LOD_REFERENCE_FOV.to_radians() / 2.0).tan() / 2.0;
+    let zoomed = (half_tan.atan() * 2.0).to_degrees();
+    assert_eq!(model.select_lod(900.0, 480, zoomed), 0);
+    assert_eq!(model.select_lod(1100.0, 480, zoomed), 1);
+    // Whereas widening the view shrinks it: at 90 degrees, 1000 units looks
+    // as far as about 1570 does normally.
+    assert_eq!(model.select_lod(900.0, 480, 90.0), 1);
+    assert_eq!(model.select_lod(1000.0, 480, 90.0), 2);
+}
+
+#[test]
+fn unlimited_last_lod() {
+    let model = XModel {
+        lod_info: [lod(500.0), lod(1500.0), lod(0.0), lod(0.0)],
+        ..crate_model()
+    };
+    assert_eq!(model.select_lod(100_000.0, 480, 65.0), 2);
+}
@@ -0,0 +1,205 @@
+//! Checks the `.xom` exporter, and the bone and vertex decoding it's built
+//! on.
+
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    common::{Vec3, Vec4},
+    techset::{Material, MaterialInfo},
+    xmodel::{
+        DObjAnimMat, GfxPackedVertex, TexCoords, UnitVec, XModel, XModelLodInfo, XRigidVertList,
+        XSurface, XSurfaceVertexInfo,
+        xom::{write_with_skeleton, write_xom},
+    },
+};
+
+/// 0.25 as a half.
+const QUARTER: u16 = 0x3400;
+
+fn vertex(x: f32) -> GfxPackedVertex {
+    GfxPackedVertex {
+        xyz: [x, 2.0, 3.0].into(),
+        // U = 1.0, V = 0.0
+        tex_coord: TexCoords(0x3C00 << 16),
+        // Straight up.
+        normal: UnitVec([127, 127, 254, 63]),
+        ..Default::default()
+    }
+}
+
+fn pose(trans: [f32; 3]) -> DObjAnimMat {
+    DObjAnimMat {
+        quat: Vec4::from([0.0, 0.0, 0.0, 1.0]),
+        trans: Vec3::from(trans),
+        trans_weight: 2.0,
+    }
+}
+
+/// Three vertices: one on bone 1, one split 3:1 between bones 1 and 2, and
+/// one left out of the blend info.
+fn skinned() -> XSurface {
+    XSurface {
+        verts0: (0..3).map(|i| vertex(i as f32)).collect(),
+        // The trailing index doesn't make a whole triangle.
+        tri_indices: vec![0, 1, 2, 0],
+        vert_info: XSurfaceVertexInfo {
+            vert_count: [1, 1, 0, 0],
+            verts_blend: vec![64, 64, 128, QUARTER],
+            tension_data: Vec::new(),
+        },
+        ..Default::default()
+    }
+}
+
+/// `tag_origin` -> `j_spine` -> `j_head`.
+fn model() -> XModel {
+    let mut lod_info = <[XModelLodInfo; 4]>::default();
+    lod_info[0].numsurfs = 1;
+
+    XModel {
+        name: XString::from("c_test_body"),
+        num_bones: 3,
+        num_root_bones: 1,
+        bone_names: vec![
+            "tag_origin".to_string(),
+            "j_spine".to_string(),
+            "j_head".to_string(),
+        ],
+        parent_list: vec![1, 1],
+        base_mat: vec![
+            pose([0.0; 3]),
+            pose([0.0, 0.0, 40.0]),
+            pose([0.0, 0.0, 64.5]),
+        ],
+        surfs: vec![skinned()],
+        material_handles: vec![Box::new(Material {
+            info: MaterialInfo {
+                name: XString::from("mtl_body"),
+                ..Default::default()
+            },
+            ..Default::default()
+        })],
+        lod_info,
+        num_lods: 1,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn decoding() {
+    let model = model();
+    assert_eq!(model.bone_parent(0), None);
+    assert_eq!(model.bone_parent(1), Some(0));
+    assert_eq!(model.bone_parent(2), Some(1));
+    assert_eq!(model.bone_parent(3), None);
+
+    assert_eq!(TexCoords(0x3C00 << 16).get(), [1.0, 0.0]);
+    assert_eq!(TexCoords(0).get(), [0.0, 0.0]);
+    let [x, y, z] = UnitVec([127, 127, 254, 63]).get().get();
+    assert_eq!([x, y], [0.0, 0.0]);
+    assert!((z - 1.0).abs() < 1e-6, "{z}");
+
+    assert_eq!(
+        model.surfs[0].bone_weights().unwrap(),
+        [
+            [(1, 1.0), (0, 0.0), (0, 0.0), (0, 0.0)],
+            [(1, 0.75), (2, 0.25), (0, 0.0), (0, 0.0)],
+            [(0, 1.0), (0, 0.0), (0, 0.0), (0, 0.0)],
+        ]
+    );
+
+    let rigid = XSurface {
+        verts0: (0..3).map(|i| vertex(i as f32)).collect(),
+        vert_list: vec![XRigidVertList {
+            bone_offset: 2 * 64,
+            vert_count: 2,
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let weights = rigid.bone_weights().unwrap();
+    assert_eq!(
+        weights.iter().map(|w| w[0]).collect::<Vec<_>>(),
+        [(2, 1.0), (2, 1.0), (0, 1.0)]
+    );
+}
+
+#[test]
+fn layout() {
+    let mut out = Vec::new();
+    write_xom(&model(), &mut out).unwrap();
+
+    assert_eq!(&out[..4], b"XOM1");
+    let header_len = u32::from_le_bytes(out[4..8].try_into().unwrap()) as usize;
+    let header = std::str::from_utf8(&out[8..8 + header_len]).unwrap();
+    assert_eq!(
+        header,
+        concat!(
+            r#"{"name":"c_test_body","bones":["#,
+            r#"{"name":"tag_origin","parent":null,"position":[0,0,0],"rotation":[0,0,0,1]},"#,
+            r#"{"name":"j_spine","parent":0,"position":[0,0,40],"rotation":[0,0,0,1]},"#,
+            r#"{"name":"j_head","parent":1,"position":[0,0,64.5],"rotation":[0,0,0,1]}"#,
+            r#"],"surfaces":[{"material":"mtl_body","vertex_count":3,"triangle_count":1}]}"#,
+        )
+    );
+
+    let body = &out[8 + header_len..];
+    assert_eq!(body.len(), 3 * 52 + 3 * 2);
+
+    let f32_at = |off: usize| f32::from_le_bytes(body[off..off + 4].try_into().unwrap());
+    // The second vertex: position, normal, uv, bones, then weights.
+    let v = 52;
+    assert_eq!([f32_at(v), f32_at(v + 4), f32_at(v + 8)], [1.0, 2.0, 3.0]);
+    assert!((f32_at(v + 20) - 1.0).abs() < 1e-6);
+    assert_eq!([f32_at(v + 24), f32_at(v + 28)], [1.0, 0.0]);
+    assert_eq!(body[v + 32..v + 36], [1, 2, 0, 0]);
+    assert_eq!(
+        [
+            f32_at(v + 36),
+            f32_at(v + 40),
+            f32_at(v + 44),
+            f32_at(v + 48)
+        ],
+        [0.75, 0.25, 0.0, 0.0]
+    );
+
+    assert_eq!(body[3 * 52..], [0, 0, 1, 0, 2, 0]);
+}
+
+#[test]
+fn explicit_skeleton() {
+    let model = model();
+    let rest_pose = [pose([1.0; 3]), pose([2.0; 3]), pose([3.0; 3])];
+    let mut out = Vec::new();
+    write_with_skeleton(&model, &rest_pose, &mut out).unwrap();
+    let header = String::from_utf8_lossy(&out);
+    assert!(header.contains(r#""j_head","parent":1,"position":[3,3,3]"#));
+
+    // NaNs aren't valid JSON.
+    let mut nan = rest_pose.clone();
+    nan[1].trans = Vec3::from([f32::NAN, 0.0, 0.0]);
+    let mut out = Vec::new();
+    write_with_skeleton(&model, &nan, &mut out).unwrap();
+    assert!(String::from_utf8_lossy(&out).contains(r#""position":[null,0,0]"#));
+}
+
+#[test]
+fn errors() {
+    let model = model();
+
+    let e = write_with_skeleton(&model, &model.base_mat[..2], &mut Vec::new()).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+
+    let mut short_blend = model.clone();
+    short_blend.surfs[0].vert_info.verts_blend.pop();
+    let e = write_xom(&short_blend, &mut Vec::new()).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e}");
+
+    // Bone indices have to fit in a byte.
+    let mut many_bones = model.clone();
+    many_bones.surfs[0].vert_info.verts_blend[0] = 256 * 64;
+    let e = write_xom(&many_bones, &mut Vec::new()).unwrap_err();
+    assert!(
+        matches!(e.kind(), ErrorKind::BrokenInvariant(s) if s.contains("256")),
+        "{e}"
+    );
+}
@@ -0,0 +1,116 @@
+//! Checks writing and reading zstd-compressed Fastfiles with
+//! [`CompressionMethod::Zstd`].
+
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    CompressionMethod, ErrorKind, XFilePlatform, XString,
+    misc::RawFile,
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+use common::TempFile;
+
+/// The size of the header before the blob.
+#[cfg(feature = "zstd")]
+const HEADER_SIZE: usize = 12;
+
+fn assets() -> Vec<XAsset> {
+    ["maps/mp/a.gsc", "maps/mp/b.gsc"]
+        .into_iter()
+        .map(|name| {
+            XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+                name: XString::from(name),
+                buffer: b"main() {}\0".to_vec(),
+            }))))
+        })
+        .collect()
+}
+
+fn serialize(method: CompressionMethod) -> t5_xfile_defs::Result<Vec<u8>> {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .with_compression(method)
+        .build()?;
+    ser.serialize_assets::<1>(assets().into_iter())?;
+    ser.deflate()
+}
+
+fn deserialize(name: &str, bytes: &[u8]) -> t5_xfile_defs::Result<Vec<XAsset>> {
+    let temp = TempFile::new(&format!("zstd_{name}.ff"), bytes);
+    common::deserialize(&temp, false)
+}
+
+#[test]
+fn compression_method() {
+    for (byte, method) in [
+        (b'u', CompressionMethod::Deflate),
+        (b'0', CompressionMethod::Uncompressed),
+        (b'z', CompressionMethod::Zstd),
+    ] {
+        assert_eq!(CompressionMethod::from_byte(byte), Some(method));
+        assert_eq!(method.as_byte(), byte);
+    }
+    assert_eq!(CompressionMethod::from_byte(b'x'), None);
+}
+
+#[test]
+fn unknown_compression() {
+    let mut bytes = serialize(CompressionMethod::Deflate).unwrap();
+    bytes[4] = b'x';
+    assert_eq!(&bytes[..8], b"IWffx100");
+
+    let e = deserialize("unknown", &bytes).unwrap_err();
+    assert!(
+        matches!(e.kind(), ErrorKind::UnsupportedCompression(b'x')),
+        "{e}"
+    );
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn roundtrip() {
+    let bytes = serialize(CompressionMethod::Zstd).unwrap();
+    assert_eq!(&bytes[..8], b"IWffz100");
+    // A zstd frame, not a zlib stream.
+    assert_eq!(
+        &bytes[HEADER_SIZE..HEADER_SIZE + 4],
+        [0x28, 0xB5, 0x2F, 0xFD]
+    );
+
+    let assets = deserialize("roundtrip", &bytes).unwrap();
+    let names = assets
+        .iter()
+        .map(|a| match a {
+            XAsset::PC(XAssetGeneric::RawFile(Some(f))) => f.name.get(),
+            _ => panic!("expected a raw file, got {a:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["maps/mp/a.gsc", "maps/mp/b.gsc"]);
+
+    // The same assets come back whichever way they were compressed.
+    let deflated = deserialize("deflated", &serialize(CompressionMethod::Deflate).unwrap());
+    assert_eq!(format!("{assets:?}"), format!("{:?}", deflated.unwrap()));
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn corrupt() {
+    let mut bytes = serialize(CompressionMethod::Zstd).unwrap();
+    bytes[HEADER_SIZE] ^= 0xFF;
+    let e = deserialize("corrupt", &bytes).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::Zstd(_)), "{e}");
+}
+
+#[cfg(not(feature = "zstd"))]
+#[test]
+fn unsupported() {
+    let e = serialize(CompressionMethod::Zstd).unwrap_err();
+    assert!(
+        matches!(e.kind(), ErrorKind::UnsupportedCompression(b'z')),
+        "{e}"
+    );
+}